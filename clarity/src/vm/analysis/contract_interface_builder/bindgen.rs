@@ -0,0 +1,1606 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Generates typed Rust and TypeScript bindings for a contract from its `ContractInterface`.
+//!
+//! Tuples become structs (or interfaces), responses become enums (or tagged unions), and every
+//! public and read-only function gets a builder for its contract-call payload and a decoder for
+//! its result.  Data vars, maps and the values the contract `print`s get typed decoders too.
+
+use std::fmt::Write;
+
+use crate::vm::analysis::contract_interface_builder::{
+    ContractInterface, ContractInterfaceAtomType, ContractInterfaceFunction,
+    ContractInterfaceFunctionAccess, ContractInterfaceVariableAccess,
+};
+use crate::vm::analysis::type_checker::contexts::TypeMap;
+use crate::vm::analysis::types::ContractAnalysis;
+use crate::vm::SymbolicExpression;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BindingsLanguage {
+    Rust,
+    TypeScript,
+}
+
+/// The type of a value that a contract `print`s
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrintEventType {
+    /// The function that prints it, or `None` if it is printed by top-level code
+    pub function: Option<String>,
+    pub type_f: ContractInterfaceAtomType,
+}
+
+/// Find the distinct types of the values a contract `print`s, in source order.  This needs the
+/// type map of the analysis, so it finds nothing for an analysis loaded from the database.
+pub fn infer_print_event_types(contract_analysis: &ContractAnalysis) -> Vec<PrintEventType> {
+    let mut events = vec![];
+    if let Some(ref type_map) = contract_analysis.type_map {
+        for expression in contract_analysis.expressions.iter() {
+            let function = defined_function_name(expression);
+            collect_print_event_types(expression, &function, type_map, &mut events);
+        }
+    }
+    events
+}
+
+fn defined_function_name(expression: &SymbolicExpression) -> Option<String> {
+    let list = expression.match_list()?;
+    match list.first()?.match_atom()?.as_str() {
+        "define-public" | "define-read-only" | "define-private" => {
+            let signature = list.get(1)?.match_list()?;
+            Some(signature.first()?.match_atom()?.to_string())
+        }
+        _ => None,
+    }
+}
+
+fn collect_print_event_types(
+    expression: &SymbolicExpression,
+    function: &Option<String>,
+    type_map: &TypeMap,
+    events: &mut Vec<PrintEventType>,
+) {
+    let list = match expression.match_list() {
+        Some(list) => list,
+        None => return,
+    };
+    let is_print = list.len() == 2
+        && list[0]
+            .match_atom()
+            .map(|name| name.as_str() == "print")
+            .unwrap_or(false);
+    if is_print {
+        if let Some(signature) = type_map.get_type(&list[1]) {
+            let type_f = ContractInterfaceAtomType::from_type_signature(signature);
+            if !events.iter().any(|event| event.type_f == type_f) {
+                events.push(PrintEventType {
+                    function: function.clone(),
+                    type_f,
+                });
+            }
+        }
+    }
+    for child in list.iter() {
+        collect_print_event_types(child, function, type_map, events);
+    }
+}
+
+/// Generate bindings for the contract `contract_name` in the given language
+pub fn generate_bindings(
+    language: BindingsLanguage,
+    contract_name: &str,
+    interface: &ContractInterface,
+    print_events: &[PrintEventType],
+) -> String {
+    let generator = BindingsGenerator::new(contract_name, interface, print_events);
+    match language {
+        BindingsLanguage::Rust => generator.rust(),
+        BindingsLanguage::TypeScript => generator.typescript(),
+    }
+}
+
+/// A tuple or response type that gets its own declaration in the bindings
+struct NamedType {
+    name: String,
+    type_f: ContractInterfaceAtomType,
+}
+
+struct BindingsGenerator<'a> {
+    contract_name: &'a str,
+    interface: &'a ContractInterface,
+    /// Name of the generated contract type
+    contract_type: String,
+    /// Name of the generated print event type, and the variant name of each print event
+    print_event_type: String,
+    print_events: Vec<(String, &'a PrintEventType)>,
+    named_types: Vec<NamedType>,
+}
+
+impl<'a> BindingsGenerator<'a> {
+    fn new(
+        contract_name: &'a str,
+        interface: &'a ContractInterface,
+        print_events: &'a [PrintEventType],
+    ) -> BindingsGenerator<'a> {
+        let mut generator = BindingsGenerator {
+            contract_name,
+            interface,
+            contract_type: format!("{}Contract", pascal_case(contract_name)),
+            print_event_type: format!("{}PrintEvent", pascal_case(contract_name)),
+            print_events: vec![],
+            named_types: vec![],
+        };
+
+        // declared vars and maps name their types before the functions that use them
+        for variable in interface.variables.iter() {
+            generator.name_types(&variable.type_f, &variable.name);
+        }
+        for map in interface.maps.iter() {
+            generator.name_types(&map.key, &format!("{}-key", map.name));
+            generator.name_types(&map.value, &format!("{}-value", map.name));
+        }
+        for function in generator.callable_functions() {
+            for arg in function.args.iter() {
+                generator.name_types(&arg.type_f, &format!("{}-{}", function.name, arg.name));
+            }
+            match function.outputs.type_f {
+                ContractInterfaceAtomType::response { .. } => {
+                    generator.name_types(&function.outputs.type_f, &function.name)
+                }
+                ref type_f => generator.name_types(type_f, &format!("{}-result", function.name)),
+            }
+        }
+        for event in print_events.iter() {
+            let function = event.function.as_deref().unwrap_or("deploy");
+            generator.name_types(&event.type_f, &format!("{}-print", function));
+            let variant = unique_name(pascal_case(function), |name| {
+                generator.print_events.iter().any(|(used, _)| used == name)
+            });
+            generator.print_events.push((variant, event));
+        }
+
+        generator
+    }
+
+    /// The functions that can be called from outside the contract
+    fn callable_functions(&self) -> impl Iterator<Item = &'a ContractInterfaceFunction> {
+        self.interface
+            .functions
+            .iter()
+            .filter(|function| function.access != ContractInterfaceFunctionAccess::private)
+    }
+
+    /// Give names to the tuple and response types within `type_f`, based on where it occurs
+    fn name_types(&mut self, type_f: &ContractInterfaceAtomType, context: &str) {
+        match type_f {
+            ContractInterfaceAtomType::tuple(entries) => {
+                for entry in entries.iter() {
+                    self.name_types(&entry.type_f, &format!("{}-{}", context, entry.name));
+                }
+                self.name_type(type_f, context);
+            }
+            ContractInterfaceAtomType::response { ok, error } => {
+                self.name_types(ok, &format!("{}-ok", context));
+                self.name_types(error, &format!("{}-err", context));
+                self.name_type(type_f, &format!("{}-response", context));
+            }
+            ContractInterfaceAtomType::optional(inner) => self.name_types(inner, context),
+            ContractInterfaceAtomType::list { type_f, .. } => {
+                self.name_types(type_f, &format!("{}-item", context))
+            }
+            _ => {}
+        }
+    }
+
+    fn name_type(&mut self, type_f: &ContractInterfaceAtomType, context: &str) {
+        if self.type_name(type_f).is_some() {
+            return;
+        }
+        let name = unique_name(pascal_case(context), |name| {
+            name == self.contract_type
+                || name == self.print_event_type
+                || RESERVED_TYPE_NAMES.contains(&name)
+                || self.named_types.iter().any(|named| named.name == name)
+        });
+        self.named_types.push(NamedType {
+            name,
+            type_f: type_f.clone(),
+        });
+    }
+
+    fn type_name(&self, type_f: &ContractInterfaceAtomType) -> Option<&str> {
+        self.named_types
+            .iter()
+            .find(|named| &named.type_f == type_f)
+            .map(|named| named.name.as_str())
+    }
+
+    fn named_type(&self, type_f: &ContractInterfaceAtomType) -> &str {
+        self.type_name(type_f)
+            .expect("BUG: no name for tuple or response type")
+    }
+
+    fn rust_type(&self, type_f: &ContractInterfaceAtomType) -> String {
+        use crate::vm::analysis::contract_interface_builder::ContractInterfaceAtomType::*;
+        match type_f {
+            none => "()".into(),
+            int128 => "i128".into(),
+            uint128 => "u128".into(),
+            bool => "bool".into(),
+            principal => "PrincipalData".into(),
+            trait_reference => "QualifiedContractIdentifier".into(),
+            buffer { .. } => "Vec<u8>".into(),
+            string_ascii { .. } | string_utf8 { .. } => "String".into(),
+            optional(inner) => format!("Option<{}>", self.rust_type(inner)),
+            list { type_f, .. } => format!("Vec<{}>", self.rust_type(type_f)),
+            tuple(_) | response { .. } => self.named_type(type_f).to_string(),
+        }
+    }
+
+    /// An expression that decodes the Clarity `Value` in `expr`, for use in a function that
+    /// returns a `BindingResult`
+    fn rust_decode(&self, type_f: &ContractInterfaceAtomType, expr: &str) -> String {
+        use crate::vm::analysis::contract_interface_builder::ContractInterfaceAtomType::*;
+        match type_f {
+            none => format!("{{ let _ = {}; }}", expr),
+            int128 => format!("expect_int({})?", expr),
+            uint128 => format!("expect_uint({})?", expr),
+            bool => format!("expect_bool({})?", expr),
+            principal => format!("expect_principal({})?", expr),
+            trait_reference => format!("expect_contract({})?", expr),
+            buffer { .. } => format!("expect_buff({})?", expr),
+            string_ascii { .. } => format!("expect_string_ascii({})?", expr),
+            string_utf8 { .. } => format!("expect_string_utf8({})?", expr),
+            optional(inner) => format!(
+                "match expect_optional({})? {{ Some(value) => Some({}), None => None }}",
+                expr,
+                self.rust_decode(inner, "value")
+            ),
+            list { type_f, .. } => format!(
+                "expect_list({})?.into_iter().map(|value| -> BindingResult<_> {{ Ok({}) }}).collect::<BindingResult<Vec<_>>>()?",
+                expr,
+                self.rust_decode(type_f, "value")
+            ),
+            tuple(_) | response { .. } => {
+                format!("{}::from_clarity({})?", self.named_type(type_f), expr)
+            }
+        }
+    }
+
+    /// An expression that encodes the owned binding value in `expr` as a Clarity `Value`, for
+    /// use in a function that returns a `BindingResult`
+    fn rust_encode(&self, type_f: &ContractInterfaceAtomType, expr: &str) -> String {
+        use crate::vm::analysis::contract_interface_builder::ContractInterfaceAtomType::*;
+        match type_f {
+            none => format!("{{ let _ = {}; Value::none() }}", expr),
+            int128 => format!("Value::Int({})", expr),
+            uint128 => format!("Value::UInt({})", expr),
+            bool => format!("Value::Bool({})", expr),
+            principal => format!("Value::Principal({})", expr),
+            trait_reference => format!("Value::Principal(PrincipalData::Contract({}))", expr),
+            buffer { .. } => format!("Value::buff_from({})?", expr),
+            string_ascii { .. } => format!("Value::string_ascii_from_bytes({}.into_bytes())?", expr),
+            string_utf8 { .. } => format!("Value::string_utf8_from_bytes({}.into_bytes())?", expr),
+            optional(inner) => format!(
+                "match {} {{ Some(value) => Value::some({})?, None => Value::none() }}",
+                expr,
+                self.rust_encode(inner, "value")
+            ),
+            list { type_f, .. } => format!(
+                "Value::list_from({}.into_iter().map(|value| -> BindingResult<_> {{ Ok({}) }}).collect::<BindingResult<Vec<_>>>()?)?",
+                expr,
+                self.rust_encode(type_f, "value")
+            ),
+            tuple(_) | response { .. } => format!("{}.to_clarity()?", expr),
+        }
+    }
+
+    fn rust(&self) -> String {
+        let mut out = String::new();
+        writeln!(
+            out,
+            "// Rust bindings for the `{}` contract, generated by `clarity-cli bindgen`.  Do not edit.",
+            self.contract_name
+        )
+        .unwrap();
+        out.push_str(RUST_PRELUDE);
+
+        for named in self.named_types.iter() {
+            self.rust_named_type(&mut out, named);
+        }
+        self.rust_contract(&mut out);
+        if !self.print_events.is_empty() {
+            self.rust_print_events(&mut out);
+        }
+        out
+    }
+
+    fn rust_named_type(&self, out: &mut String, named: &NamedType) {
+        let name = &named.name;
+        match named.type_f {
+            ContractInterfaceAtomType::tuple(ref entries) => {
+                let fields = unique_idents(
+                    entries.iter().map(|entry| snake_case(&entry.name)),
+                    RUST_KEYWORDS,
+                );
+                writeln!(out, "\n#[derive(Debug, Clone, PartialEq)]").unwrap();
+                writeln!(out, "pub struct {} {{", name).unwrap();
+                for (field, entry) in fields.iter().zip(entries.iter()) {
+                    writeln!(out, "    pub {}: {},", field, self.rust_type(&entry.type_f)).unwrap();
+                }
+                writeln!(out, "}}\n\nimpl {} {{", name).unwrap();
+                writeln!(
+                    out,
+                    "    pub fn from_clarity(value: Value) -> BindingResult<{}> {{",
+                    name
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    "        let tuple = expect_tuple(value, {})?;",
+                    entries.len()
+                )
+                .unwrap();
+                writeln!(out, "        Ok({} {{", name).unwrap();
+                for (field, entry) in fields.iter().zip(entries.iter()) {
+                    let entry_expr = format!("tuple_entry(&tuple, \"{}\")?", entry.name);
+                    writeln!(
+                        out,
+                        "            {}: {},",
+                        field,
+                        self.rust_decode(&entry.type_f, &entry_expr)
+                    )
+                    .unwrap();
+                }
+                writeln!(out, "        }})\n    }}\n").unwrap();
+                writeln!(
+                    out,
+                    "    pub fn to_clarity(self) -> BindingResult<Value> {{"
+                )
+                .unwrap();
+                writeln!(out, "        Ok(Value::Tuple(TupleData::from_data(vec![").unwrap();
+                for (field, entry) in fields.iter().zip(entries.iter()) {
+                    writeln!(
+                        out,
+                        "            (clarity_name(\"{}\"), {}),",
+                        entry.name,
+                        self.rust_encode(&entry.type_f, &format!("self.{}", field))
+                    )
+                    .unwrap();
+                }
+                writeln!(out, "        ])?))\n    }}\n}}").unwrap();
+            }
+            ContractInterfaceAtomType::response { ref ok, ref error } => {
+                writeln!(out, "\n#[derive(Debug, Clone, PartialEq)]").unwrap();
+                writeln!(out, "pub enum {} {{", name).unwrap();
+                writeln!(out, "    Ok({}),", self.rust_type(ok)).unwrap();
+                writeln!(out, "    Err({}),", self.rust_type(error)).unwrap();
+                writeln!(out, "}}\n\nimpl {} {{", name).unwrap();
+                writeln!(
+                    out,
+                    "    pub fn from_clarity(value: Value) -> BindingResult<{}> {{",
+                    name
+                )
+                .unwrap();
+                writeln!(out, "        match expect_response(value)? {{").unwrap();
+                writeln!(
+                    out,
+                    "            Ok(value) => Ok({}::Ok({})),",
+                    name,
+                    self.rust_decode(ok, "value")
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    "            Err(value) => Ok({}::Err({})),",
+                    name,
+                    self.rust_decode(error, "value")
+                )
+                .unwrap();
+                writeln!(out, "        }}\n    }}\n").unwrap();
+                writeln!(
+                    out,
+                    "    pub fn to_clarity(self) -> BindingResult<Value> {{"
+                )
+                .unwrap();
+                writeln!(out, "        match self {{").unwrap();
+                writeln!(
+                    out,
+                    "            {}::Ok(value) => Ok(Value::okay({})?),",
+                    name,
+                    self.rust_encode(ok, "value")
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    "            {}::Err(value) => Ok(Value::error({})?),",
+                    name,
+                    self.rust_encode(error, "value")
+                )
+                .unwrap();
+                writeln!(out, "        }}\n    }}\n}}").unwrap();
+            }
+            _ => unreachable!("BUG: only tuples and responses are named"),
+        }
+    }
+
+    fn rust_contract(&self, out: &mut String) {
+        let contract_type = &self.contract_type;
+        writeln!(
+            out,
+            "\n/// Typed calls into the `{}` contract",
+            self.contract_name
+        )
+        .unwrap();
+        writeln!(out, "#[derive(Debug, Clone, PartialEq)]").unwrap();
+        writeln!(out, "pub struct {} {{", contract_type).unwrap();
+        writeln!(out, "    pub address: StacksAddress,").unwrap();
+        writeln!(out, "    pub name: ContractName,").unwrap();
+        writeln!(out, "}}\n\nimpl {} {{", contract_type).unwrap();
+        writeln!(
+            out,
+            "    pub fn new(address: StacksAddress, name: ContractName) -> {} {{",
+            contract_type
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "        {} {{ address, name }}\n    }}\n",
+            contract_type
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "    fn contract_call(&self, function_name: &str, function_args: Vec<Value>) -> TransactionContractCall {{"
+        )
+        .unwrap();
+        writeln!(out, "        TransactionContractCall {{").unwrap();
+        writeln!(out, "            address: self.address.clone(),").unwrap();
+        writeln!(out, "            contract_name: self.name.clone(),").unwrap();
+        writeln!(
+            out,
+            "            function_name: clarity_name(function_name),"
+        )
+        .unwrap();
+        writeln!(out, "            function_args,").unwrap();
+        writeln!(out, "        }}\n    }}").unwrap();
+
+        for function in self.callable_functions() {
+            let method = snake_case(&function.name);
+            let method = if RUST_KEYWORDS.contains(&method.as_str())
+                || ["new", "contract_call"].contains(&method.as_str())
+            {
+                format!("{}_call", method)
+            } else {
+                method
+            };
+            let args = unique_idents(
+                function.args.iter().map(|arg| snake_case(&arg.name)),
+                &[RUST_KEYWORDS, &["self"]].concat(),
+            );
+            let params: Vec<_> = args
+                .iter()
+                .zip(function.args.iter())
+                .map(|(ident, arg)| format!(", {}: {}", ident, self.rust_type(&arg.type_f)))
+                .collect();
+            let values: Vec<_> = args
+                .iter()
+                .zip(function.args.iter())
+                .map(|(ident, arg)| self.rust_encode(&arg.type_f, ident))
+                .collect();
+
+            writeln!(
+                out,
+                "\n    /// Call the {} function `{}`",
+                access_name(&function.access),
+                function.name
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "    pub fn {}(&self{}) -> BindingResult<TransactionContractCall> {{",
+                method,
+                params.concat()
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "        Ok(self.contract_call(\"{}\", vec![{}]))\n    }}",
+                function.name,
+                values.join(", ")
+            )
+            .unwrap();
+
+            writeln!(out, "\n    /// Decode the result of `{}`", function.name).unwrap();
+            writeln!(
+                out,
+                "    pub fn decode_{}_result(value: Value) -> BindingResult<{}> {{",
+                snake_case(&function.name),
+                self.rust_type(&function.outputs.type_f)
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "        Ok({})\n    }}",
+                self.rust_decode(&function.outputs.type_f, "value")
+            )
+            .unwrap();
+        }
+
+        for variable in self.interface.variables.iter() {
+            let (kind, suffix) = match variable.access {
+                ContractInterfaceVariableAccess::variable => ("data var", "var"),
+                ContractInterfaceVariableAccess::constant => ("constant", "constant"),
+            };
+            writeln!(out, "\n    /// Name of the {} `{}`", kind, variable.name).unwrap();
+            writeln!(
+                out,
+                "    pub const {}_{}: &'static str = \"{}\";",
+                snake_case(&variable.name).to_uppercase(),
+                suffix.to_uppercase(),
+                variable.name
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "\n    /// Decode the value of the {} `{}`",
+                kind, variable.name
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "    pub fn decode_{}_{}(value: Value) -> BindingResult<{}> {{",
+                snake_case(&variable.name),
+                suffix,
+                self.rust_type(&variable.type_f)
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "        Ok({})\n    }}",
+                self.rust_decode(&variable.type_f, "value")
+            )
+            .unwrap();
+        }
+
+        for map in self.interface.maps.iter() {
+            let map_ident = snake_case(&map.name);
+            writeln!(out, "\n    /// Name of the map `{}`", map.name).unwrap();
+            writeln!(
+                out,
+                "    pub const {}_MAP: &'static str = \"{}\";",
+                map_ident.to_uppercase(),
+                map.name
+            )
+            .unwrap();
+            writeln!(out, "\n    /// Encode a key of the map `{}`", map.name).unwrap();
+            writeln!(
+                out,
+                "    pub fn encode_{}_key(key: {}) -> BindingResult<Value> {{",
+                map_ident,
+                self.rust_type(&map.key)
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "        Ok({})\n    }}",
+                self.rust_encode(&map.key, "key")
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "\n    /// Decode an entry of the map `{}`, as returned by `map-get?`",
+                map.name
+            )
+            .unwrap();
+            let entry_type = ContractInterfaceAtomType::optional(Box::new(map.value.clone()));
+            writeln!(
+                out,
+                "    pub fn decode_{}_entry(value: Value) -> BindingResult<{}> {{",
+                map_ident,
+                self.rust_type(&entry_type)
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "        Ok({})\n    }}",
+                self.rust_decode(&entry_type, "value")
+            )
+            .unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+    }
+
+    fn rust_print_events(&self, out: &mut String) {
+        let event_type = &self.print_event_type;
+        writeln!(
+            out,
+            "\n/// Values printed by the `{}` contract, as far as their types could be inferred",
+            self.contract_name
+        )
+        .unwrap();
+        writeln!(out, "#[derive(Debug, Clone, PartialEq)]").unwrap();
+        writeln!(out, "pub enum {} {{", event_type).unwrap();
+        for (variant, event) in self.print_events.iter() {
+            writeln!(out, "    /// Printed by {}", print_event_origin(event)).unwrap();
+            writeln!(out, "    {}({}),", variant, self.rust_type(&event.type_f)).unwrap();
+        }
+        writeln!(out, "}}\n\nimpl {} {{", event_type).unwrap();
+        writeln!(
+            out,
+            "    /// Decode a printed value by trying each known type in turn"
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "    pub fn from_clarity(value: Value) -> BindingResult<{}> {{",
+            event_type
+        )
+        .unwrap();
+        for (variant, event) in self.print_events.iter() {
+            writeln!(
+                out,
+                "        if let Ok(event) = (|value: Value| -> BindingResult<_> {{ Ok({}) }})(value.clone()) {{",
+                self.rust_decode(&event.type_f, "value")
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "            return Ok({}::{}(event));\n        }}",
+                event_type, variant
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "        Err(BindingError::UnexpectedValue(\"print event\", value))\n    }}\n}}"
+        )
+        .unwrap();
+    }
+
+    fn typescript_type(&self, type_f: &ContractInterfaceAtomType) -> String {
+        use crate::vm::analysis::contract_interface_builder::ContractInterfaceAtomType::*;
+        match type_f {
+            none => "null".into(),
+            int128 | uint128 => "bigint".into(),
+            bool => "boolean".into(),
+            principal | trait_reference | string_ascii { .. } | string_utf8 { .. } => {
+                "string".into()
+            }
+            buffer { .. } => "Uint8Array".into(),
+            optional(inner) => format!("{} | null", self.typescript_type(inner)),
+            list { type_f, .. } => match **type_f {
+                optional(_) => format!("({})[]", self.typescript_type(type_f)),
+                _ => format!("{}[]", self.typescript_type(type_f)),
+            },
+            tuple(_) | response { .. } => self.named_type(type_f).to_string(),
+        }
+    }
+
+    /// An expression that decodes the `ClarityValue` in `expr`, which is evaluated once
+    fn typescript_decode(&self, type_f: &ContractInterfaceAtomType, expr: &str) -> String {
+        use crate::vm::analysis::contract_interface_builder::ContractInterfaceAtomType::*;
+        match type_f {
+            none => "null".into(),
+            int128 => format!("expectInt({})", expr),
+            uint128 => format!("expectUInt({})", expr),
+            bool => format!("expectBool({})", expr),
+            principal | trait_reference => format!("expectPrincipal({})", expr),
+            buffer { .. } => format!("expectBuffer({})", expr),
+            string_ascii { .. } => format!("expectStringAscii({})", expr),
+            string_utf8 { .. } => format!("expectStringUtf8({})", expr),
+            optional(inner) => format!(
+                "((some) => (some === null ? null : {}))(expectOptional({}))",
+                self.typescript_decode(inner, "some"),
+                expr
+            ),
+            list { type_f, .. } => format!(
+                "expectList({}).map((item) => {})",
+                expr,
+                self.typescript_decode(type_f, "item")
+            ),
+            tuple(_) | response { .. } => format!("decode{}({})", self.named_type(type_f), expr),
+        }
+    }
+
+    /// An expression that encodes the binding value in `expr` as a `ClarityValue`.  `expr` may
+    /// be evaluated more than once, so it must be a plain reference.
+    fn typescript_encode(&self, type_f: &ContractInterfaceAtomType, expr: &str) -> String {
+        use crate::vm::analysis::contract_interface_builder::ContractInterfaceAtomType::*;
+        match type_f {
+            none => "noneCV()".into(),
+            int128 => format!("intCV({})", expr),
+            uint128 => format!("uintCV({})", expr),
+            bool => format!("boolCV({})", expr),
+            principal | trait_reference => format!("principalCV({})", expr),
+            buffer { .. } => format!("bufferCV(Buffer.from({}))", expr),
+            string_ascii { .. } => format!("stringAsciiCV({})", expr),
+            string_utf8 { .. } => format!("stringUtf8CV({})", expr),
+            optional(inner) => format!(
+                "({} === null ? noneCV() : someCV({}))",
+                expr,
+                self.typescript_encode(inner, expr)
+            ),
+            list { type_f, .. } => format!(
+                "listCV({}.map((item) => {}))",
+                expr,
+                self.typescript_encode(type_f, "item")
+            ),
+            tuple(_) | response { .. } => format!("encode{}({})", self.named_type(type_f), expr),
+        }
+    }
+
+    fn typescript(&self) -> String {
+        let mut out = String::new();
+        writeln!(
+            out,
+            "// TypeScript bindings for the `{}` contract, generated by `clarity-cli bindgen`.  Do not edit.",
+            self.contract_name
+        )
+        .unwrap();
+        out.push_str(TYPESCRIPT_PRELUDE);
+
+        for named in self.named_types.iter() {
+            self.typescript_named_type(&mut out, named);
+        }
+        self.typescript_contract(&mut out);
+        if !self.print_events.is_empty() {
+            self.typescript_print_events(&mut out);
+        }
+        out
+    }
+
+    fn typescript_named_type(&self, out: &mut String, named: &NamedType) {
+        let name = &named.name;
+        match named.type_f {
+            ContractInterfaceAtomType::tuple(ref entries) => {
+                let fields =
+                    unique_idents(entries.iter().map(|entry| camel_case(&entry.name)), &[]);
+                writeln!(out, "\nexport interface {} {{", name).unwrap();
+                for (field, entry) in fields.iter().zip(entries.iter()) {
+                    writeln!(out, "  {}: {};", field, self.typescript_type(&entry.type_f)).unwrap();
+                }
+                writeln!(out, "}}\n").unwrap();
+                writeln!(
+                    out,
+                    "export function decode{}(value: ClarityValue): {} {{",
+                    name, name
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    "  const tuple = expectTuple(value, {});",
+                    entries.len()
+                )
+                .unwrap();
+                writeln!(out, "  return {{").unwrap();
+                for (field, entry) in fields.iter().zip(entries.iter()) {
+                    let entry_expr = format!("tupleEntry(tuple, \"{}\")", entry.name);
+                    writeln!(
+                        out,
+                        "    {}: {},",
+                        field,
+                        self.typescript_decode(&entry.type_f, &entry_expr)
+                    )
+                    .unwrap();
+                }
+                writeln!(out, "  }};\n}}\n").unwrap();
+                writeln!(
+                    out,
+                    "export function encode{}(value: {}): ClarityValue {{",
+                    name, name
+                )
+                .unwrap();
+                writeln!(out, "  return tupleCV({{").unwrap();
+                for (field, entry) in fields.iter().zip(entries.iter()) {
+                    writeln!(
+                        out,
+                        "    \"{}\": {},",
+                        entry.name,
+                        self.typescript_encode(&entry.type_f, &format!("value.{}", field))
+                    )
+                    .unwrap();
+                }
+                writeln!(out, "  }});\n}}").unwrap();
+            }
+            ContractInterfaceAtomType::response { ref ok, ref error } => {
+                writeln!(
+                    out,
+                    "\nexport type {} = Response<{}, {}>;\n",
+                    name,
+                    self.typescript_type(ok),
+                    self.typescript_type(error)
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    "export function decode{}(value: ClarityValue): {} {{",
+                    name, name
+                )
+                .unwrap();
+                writeln!(out, "  const response = expectResponse(value);").unwrap();
+                writeln!(out, "  return response.ok").unwrap();
+                writeln!(
+                    out,
+                    "    ? {{ ok: true, value: {} }}",
+                    self.typescript_decode(ok, "response.value")
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    "    : {{ ok: false, value: {} }};\n}}\n",
+                    self.typescript_decode(error, "response.value")
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    "export function encode{}(value: {}): ClarityValue {{",
+                    name, name
+                )
+                .unwrap();
+                writeln!(out, "  return value.ok").unwrap();
+                writeln!(
+                    out,
+                    "    ? responseOkCV({})",
+                    self.typescript_encode(ok, "value.value")
+                )
+                .unwrap();
+                writeln!(
+                    out,
+                    "    : responseErrorCV({});\n}}",
+                    self.typescript_encode(error, "value.value")
+                )
+                .unwrap();
+            }
+            _ => unreachable!("BUG: only tuples and responses are named"),
+        }
+    }
+
+    fn typescript_contract(&self, out: &mut String) {
+        writeln!(
+            out,
+            "\n/** Typed calls into the `{}` contract */",
+            self.contract_name
+        )
+        .unwrap();
+        writeln!(out, "export class {} {{", self.contract_type).unwrap();
+        writeln!(
+            out,
+            "  constructor(readonly contractAddress: string, readonly contractName: string) {{}}\n"
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "  private contractCall(functionName: string, functionArgs: ClarityValue[]): ContractCall {{"
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "    return {{ contractAddress: this.contractAddress, contractName: this.contractName, functionName, functionArgs }};\n  }}"
+        )
+        .unwrap();
+
+        for function in self.callable_functions() {
+            let method = camel_case(&function.name);
+            let method = if ["constructor", "contractCall"].contains(&method.as_str()) {
+                format!("{}Call", method)
+            } else {
+                method
+            };
+            let args = unique_idents(
+                function.args.iter().map(|arg| camel_case(&arg.name)),
+                TYPESCRIPT_KEYWORDS,
+            );
+            let params: Vec<_> = args
+                .iter()
+                .zip(function.args.iter())
+                .map(|(ident, arg)| format!("{}: {}", ident, self.typescript_type(&arg.type_f)))
+                .collect();
+            let values: Vec<_> = args
+                .iter()
+                .zip(function.args.iter())
+                .map(|(ident, arg)| self.typescript_encode(&arg.type_f, ident))
+                .collect();
+
+            writeln!(
+                out,
+                "\n  /** Call the {} function `{}` */",
+                access_name(&function.access),
+                function.name
+            )
+            .unwrap();
+            writeln!(out, "  {}({}): ContractCall {{", method, params.join(", ")).unwrap();
+            writeln!(
+                out,
+                "    return this.contractCall(\"{}\", [{}]);\n  }}",
+                function.name,
+                values.join(", ")
+            )
+            .unwrap();
+
+            writeln!(out, "\n  /** Decode the result of `{}` */", function.name).unwrap();
+            writeln!(
+                out,
+                "  static decode{}Result(value: ClarityValue): {} {{",
+                pascal_case(&function.name),
+                self.typescript_type(&function.outputs.type_f)
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "    return {};\n  }}",
+                self.typescript_decode(&function.outputs.type_f, "value")
+            )
+            .unwrap();
+        }
+
+        for variable in self.interface.variables.iter() {
+            let (kind, suffix) = match variable.access {
+                ContractInterfaceVariableAccess::variable => ("data var", "Var"),
+                ContractInterfaceVariableAccess::constant => ("constant", "Constant"),
+            };
+            writeln!(out, "\n  /** Name of the {} `{}` */", kind, variable.name).unwrap();
+            writeln!(
+                out,
+                "  static readonly {}{} = \"{}\";",
+                camel_case(&variable.name),
+                suffix,
+                variable.name
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "\n  /** Decode the value of the {} `{}` */",
+                kind, variable.name
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "  static decode{}{}(value: ClarityValue): {} {{",
+                pascal_case(&variable.name),
+                suffix,
+                self.typescript_type(&variable.type_f)
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "    return {};\n  }}",
+                self.typescript_decode(&variable.type_f, "value")
+            )
+            .unwrap();
+        }
+
+        for map in self.interface.maps.iter() {
+            writeln!(out, "\n  /** Name of the map `{}` */", map.name).unwrap();
+            writeln!(
+                out,
+                "  static readonly {}Map = \"{}\";",
+                camel_case(&map.name),
+                map.name
+            )
+            .unwrap();
+            writeln!(out, "\n  /** Encode a key of the map `{}` */", map.name).unwrap();
+            writeln!(
+                out,
+                "  static encode{}Key(key: {}): ClarityValue {{",
+                pascal_case(&map.name),
+                self.typescript_type(&map.key)
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "    return {};\n  }}",
+                self.typescript_encode(&map.key, "key")
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "\n  /** Decode an entry of the map `{}`, as returned by `map-get?` */",
+                map.name
+            )
+            .unwrap();
+            let entry_type = ContractInterfaceAtomType::optional(Box::new(map.value.clone()));
+            writeln!(
+                out,
+                "  static decode{}Entry(value: ClarityValue): {} {{",
+                pascal_case(&map.name),
+                self.typescript_type(&entry_type)
+            )
+            .unwrap();
+            writeln!(
+                out,
+                "    return {};\n  }}",
+                self.typescript_decode(&entry_type, "value")
+            )
+            .unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+    }
+
+    fn typescript_print_events(&self, out: &mut String) {
+        let event_type = &self.print_event_type;
+        writeln!(
+            out,
+            "\n/** Values printed by the `{}` contract, as far as their types could be inferred */",
+            self.contract_name
+        )
+        .unwrap();
+        writeln!(out, "export type {} =", event_type).unwrap();
+        let variants: Vec<_> = self
+            .print_events
+            .iter()
+            .map(|(variant, event)| {
+                format!(
+                    "  /** Printed by {} */\n  | {{ kind: \"{}\"; value: {} }}",
+                    print_event_origin(event),
+                    camel_case(variant),
+                    self.typescript_type(&event.type_f)
+                )
+            })
+            .collect();
+        writeln!(out, "{};\n", variants.join("\n")).unwrap();
+        writeln!(
+            out,
+            "/** Decode a printed value by trying each known type in turn */"
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "export function decode{}(value: ClarityValue): {} {{",
+            event_type, event_type
+        )
+        .unwrap();
+        for (variant, event) in self.print_events.iter() {
+            writeln!(out, "  try {{").unwrap();
+            writeln!(
+                out,
+                "    return {{ kind: \"{}\", value: {} }};",
+                camel_case(variant),
+                self.typescript_decode(&event.type_f, "value")
+            )
+            .unwrap();
+            writeln!(out, "  }} catch (e) {{}}").unwrap();
+        }
+        writeln!(out, "  throw new BindingError(\"print event\", value);\n}}").unwrap();
+    }
+}
+
+fn access_name(access: &ContractInterfaceFunctionAccess) -> &'static str {
+    match access {
+        ContractInterfaceFunctionAccess::public => "public",
+        ContractInterfaceFunctionAccess::read_only => "read-only",
+        ContractInterfaceFunctionAccess::private => "private",
+    }
+}
+
+fn print_event_origin(event: &PrintEventType) -> String {
+    match event.function {
+        Some(ref function) => format!("`{}`", function),
+        None => "top-level code".into(),
+    }
+}
+
+/// Split a Clarity name like `get-balance?` into its alphanumeric words
+fn words(name: &str) -> Vec<String> {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_string())
+        .collect()
+}
+
+fn capitalize(word: &str) -> String {
+    // treat all-caps words like `ERR` in `ERR-NOT-FOUND` as ordinary words
+    let word = if word.chars().any(|c| c.is_ascii_lowercase()) {
+        word.to_string()
+    } else {
+        word.to_ascii_lowercase()
+    };
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+fn pascal_case(name: &str) -> String {
+    let ident: String = words(name).iter().map(|word| capitalize(word)).collect();
+    if ident.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        ident
+    } else {
+        format!("T{}", ident)
+    }
+}
+
+fn camel_case(name: &str) -> String {
+    let pascal = pascal_case(name);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + chars.as_str(),
+        None => pascal,
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let ident = words(name).join("_").to_ascii_lowercase();
+    if ident.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        ident
+    } else {
+        format!("v_{}", ident)
+    }
+}
+
+/// Append a number to `base` until `is_used` no longer holds
+fn unique_name<F: Fn(&str) -> bool>(base: String, is_used: F) -> String {
+    let mut name = base.clone();
+    let mut suffix = 2;
+    while is_used(&name) {
+        name = format!("{}{}", base, suffix);
+        suffix += 1;
+    }
+    name
+}
+
+/// Make a list of identifiers distinct from each other and from the reserved words
+fn unique_idents<I: Iterator<Item = String>>(idents: I, reserved: &[&str]) -> Vec<String> {
+    let mut unique: Vec<String> = vec![];
+    for ident in idents {
+        let ident = if reserved.contains(&ident.as_str()) {
+            format!("{}_", ident)
+        } else {
+            ident
+        };
+        let ident = unique_name(ident, |name| unique.iter().any(|used| used == name));
+        unique.push(ident);
+    }
+    unique
+}
+
+/// Names declared by the preludes, which generated types must not shadow
+const RESERVED_TYPE_NAMES: &[&str] = &[
+    "BindingError",
+    "BindingResult",
+    "ClarityError",
+    "ClarityName",
+    "ClarityValue",
+    "ContractCall",
+    "ContractName",
+    "PrincipalData",
+    "QualifiedContractIdentifier",
+    "Response",
+    "StacksAddress",
+    "TransactionContractCall",
+    "TupleData",
+    "Value",
+];
+
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in",
+    "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "static", "struct", "super", "trait", "true", "try", "type", "typeof", "unsafe",
+    "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+const TYPESCRIPT_KEYWORDS: &[&str] = &[
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+const RUST_PRELUDE: &str = r#"#![allow(dead_code, unused_imports, clippy::all)]
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use blockstack_lib::chainstate::stacks::TransactionContractCall;
+use blockstack_lib::types::chainstate::StacksAddress;
+use blockstack_lib::vm::errors::Error as ClarityError;
+use blockstack_lib::vm::types::{
+    CharType, PrincipalData, QualifiedContractIdentifier, SequenceData, TupleData,
+};
+use blockstack_lib::vm::{ClarityName, ContractName, Value};
+
+/// Error converting between binding types and Clarity values
+#[derive(Debug)]
+pub enum BindingError {
+    /// A Clarity value could not be constructed
+    Clarity(ClarityError),
+    /// A Clarity value did not have the expected type
+    UnexpectedValue(&'static str, Value),
+}
+
+impl fmt::Display for BindingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BindingError::Clarity(e) => write!(f, "{}", e),
+            BindingError::UnexpectedValue(expected, value) => {
+                write!(f, "expected {}, got {}", expected, value)
+            }
+        }
+    }
+}
+
+impl From<ClarityError> for BindingError {
+    fn from(e: ClarityError) -> BindingError {
+        BindingError::Clarity(e)
+    }
+}
+
+pub type BindingResult<T> = Result<T, BindingError>;
+
+fn clarity_name(name: &str) -> ClarityName {
+    ClarityName::try_from(name.to_string()).expect("BUG: invalid Clarity name in bindings")
+}
+
+fn expect_int(value: Value) -> BindingResult<i128> {
+    match value {
+        Value::Int(x) => Ok(x),
+        value => Err(BindingError::UnexpectedValue("int", value)),
+    }
+}
+
+fn expect_uint(value: Value) -> BindingResult<u128> {
+    match value {
+        Value::UInt(x) => Ok(x),
+        value => Err(BindingError::UnexpectedValue("uint", value)),
+    }
+}
+
+fn expect_bool(value: Value) -> BindingResult<bool> {
+    match value {
+        Value::Bool(x) => Ok(x),
+        value => Err(BindingError::UnexpectedValue("bool", value)),
+    }
+}
+
+fn expect_principal(value: Value) -> BindingResult<PrincipalData> {
+    match value {
+        Value::Principal(principal) => Ok(principal),
+        Value::CallableContract(callable) => {
+            Ok(PrincipalData::Contract(callable.contract_identifier))
+        }
+        value => Err(BindingError::UnexpectedValue("principal", value)),
+    }
+}
+
+fn expect_contract(value: Value) -> BindingResult<QualifiedContractIdentifier> {
+    match value {
+        Value::Principal(PrincipalData::Contract(contract)) => Ok(contract),
+        Value::CallableContract(callable) => Ok(callable.contract_identifier),
+        value => Err(BindingError::UnexpectedValue("contract principal", value)),
+    }
+}
+
+fn expect_buff(value: Value) -> BindingResult<Vec<u8>> {
+    match value {
+        Value::Sequence(SequenceData::Buffer(buff)) => Ok(buff.data),
+        value => Err(BindingError::UnexpectedValue("buff", value)),
+    }
+}
+
+fn expect_string_ascii(value: Value) -> BindingResult<String> {
+    match value {
+        Value::Sequence(SequenceData::String(CharType::ASCII(string))) => {
+            Ok(string.data.into_iter().map(char::from).collect())
+        }
+        value => Err(BindingError::UnexpectedValue("string-ascii", value)),
+    }
+}
+
+fn expect_string_utf8(value: Value) -> BindingResult<String> {
+    match value {
+        Value::Sequence(SequenceData::String(CharType::UTF8(string))) => {
+            Ok(String::from_utf8_lossy(&string.data.concat()).into_owned())
+        }
+        value => Err(BindingError::UnexpectedValue("string-utf8", value)),
+    }
+}
+
+fn expect_optional(value: Value) -> BindingResult<Option<Value>> {
+    match value {
+        Value::Optional(optional) => Ok(optional.data.map(|value| *value)),
+        value => Err(BindingError::UnexpectedValue("optional", value)),
+    }
+}
+
+fn expect_response(value: Value) -> BindingResult<Result<Value, Value>> {
+    match value {
+        Value::Response(response) if response.committed => Ok(Ok(*response.data)),
+        Value::Response(response) => Ok(Err(*response.data)),
+        value => Err(BindingError::UnexpectedValue("response", value)),
+    }
+}
+
+fn expect_list(value: Value) -> BindingResult<Vec<Value>> {
+    match value {
+        Value::Sequence(SequenceData::List(list)) => Ok(list.data),
+        value => Err(BindingError::UnexpectedValue("list", value)),
+    }
+}
+
+fn expect_tuple(value: Value, num_entries: usize) -> BindingResult<TupleData> {
+    match value {
+        Value::Tuple(tuple) if tuple.data_map.len() == num_entries => Ok(tuple),
+        value => Err(BindingError::UnexpectedValue("tuple", value)),
+    }
+}
+
+fn tuple_entry(tuple: &TupleData, name: &str) -> BindingResult<Value> {
+    Ok(tuple.get(name)?.clone())
+}
+"#;
+
+const TYPESCRIPT_PRELUDE: &str = r#"import {
+  ClarityType,
+  ClarityValue,
+  boolCV,
+  bufferCV,
+  intCV,
+  listCV,
+  noneCV,
+  principalCV,
+  principalToString,
+  responseErrorCV,
+  responseOkCV,
+  someCV,
+  stringAsciiCV,
+  stringUtf8CV,
+  tupleCV,
+  uintCV,
+} from "@stacks/transactions";
+
+/** A contract call, as taken by `makeContractCall()` and friends */
+export interface ContractCall {
+  contractAddress: string;
+  contractName: string;
+  functionName: string;
+  functionArgs: ClarityValue[];
+}
+
+/** A decoded Clarity response */
+export type Response<T, E> = { ok: true; value: T } | { ok: false; value: E };
+
+/** Thrown when a Clarity value does not have the expected type */
+export class BindingError extends Error {
+  constructor(readonly expected: string, readonly value: ClarityValue) {
+    super(`expected ${expected}, got Clarity type ${value.type}`);
+  }
+}
+
+function expectInt(value: ClarityValue): bigint {
+  if (value.type !== ClarityType.Int) throw new BindingError("int", value);
+  return BigInt(value.value);
+}
+
+function expectUInt(value: ClarityValue): bigint {
+  if (value.type !== ClarityType.UInt) throw new BindingError("uint", value);
+  return BigInt(value.value);
+}
+
+function expectBool(value: ClarityValue): boolean {
+  if (value.type === ClarityType.BoolTrue) return true;
+  if (value.type === ClarityType.BoolFalse) return false;
+  throw new BindingError("bool", value);
+}
+
+function expectPrincipal(value: ClarityValue): string {
+  if (value.type !== ClarityType.PrincipalStandard && value.type !== ClarityType.PrincipalContract) {
+    throw new BindingError("principal", value);
+  }
+  return principalToString(value);
+}
+
+function expectBuffer(value: ClarityValue): Uint8Array {
+  if (value.type !== ClarityType.Buffer) throw new BindingError("buff", value);
+  return value.buffer;
+}
+
+function expectStringAscii(value: ClarityValue): string {
+  if (value.type !== ClarityType.StringASCII) throw new BindingError("string-ascii", value);
+  return value.data;
+}
+
+function expectStringUtf8(value: ClarityValue): string {
+  if (value.type !== ClarityType.StringUTF8) throw new BindingError("string-utf8", value);
+  return value.data;
+}
+
+function expectOptional(value: ClarityValue): ClarityValue | null {
+  if (value.type === ClarityType.OptionalNone) return null;
+  if (value.type === ClarityType.OptionalSome) return value.value;
+  throw new BindingError("optional", value);
+}
+
+function expectResponse(value: ClarityValue): Response<ClarityValue, ClarityValue> {
+  if (value.type === ClarityType.ResponseOk) return { ok: true, value: value.value };
+  if (value.type === ClarityType.ResponseErr) return { ok: false, value: value.value };
+  throw new BindingError("response", value);
+}
+
+function expectList(value: ClarityValue): ClarityValue[] {
+  if (value.type !== ClarityType.List) throw new BindingError("list", value);
+  return value.list;
+}
+
+function expectTuple(value: ClarityValue, numEntries: number): Record<string, ClarityValue> {
+  if (value.type !== ClarityType.Tuple || Object.keys(value.data).length !== numEntries) {
+    throw new BindingError("tuple", value);
+  }
+  return value.data;
+}
+
+function tupleEntry(tuple: Record<string, ClarityValue>, name: string): ClarityValue {
+  const entry = tuple[name];
+  if (entry === undefined) throw new Error(`missing tuple entry ${name}`);
+  return entry;
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::analysis::contract_interface_builder::build_contract_interface;
+    use crate::vm::analysis::mem_type_check;
+    use crate::vm::ClarityVersion;
+    use stacks_common::types::StacksEpochId;
+
+    const CONTRACT: &str = "
+        (define-map balances principal uint)
+        (define-data-var owner principal tx-sender)
+        (define-public (transfer (amount uint) (recipient principal))
+          (begin
+            (print { event: \"transfer\", amount: amount, recipient: recipient })
+            (ok true)))
+        (define-read-only (get-info (who principal))
+          (ok { balance: (default-to u0 (map-get? balances who)), owner: (var-get owner) }))
+        (define-private (helper) (ok u1))
+    ";
+
+    fn bindings(language: BindingsLanguage) -> String {
+        let (_, analysis) =
+            mem_type_check(CONTRACT, ClarityVersion::Clarity2, StacksEpochId::Epoch21).unwrap();
+        let interface = build_contract_interface(&analysis);
+        let print_events = infer_print_event_types(&analysis);
+        generate_bindings(language, "token-vault", &interface, &print_events)
+    }
+
+    #[test]
+    fn test_infer_print_event_types() {
+        let (_, analysis) =
+            mem_type_check(CONTRACT, ClarityVersion::Clarity2, StacksEpochId::Epoch21).unwrap();
+        let print_events = infer_print_event_types(&analysis);
+        assert_eq!(print_events.len(), 1);
+        assert_eq!(print_events[0].function.as_deref(), Some("transfer"));
+        match print_events[0].type_f {
+            ContractInterfaceAtomType::tuple(ref entries) => {
+                let names: Vec<_> = entries.iter().map(|entry| entry.name.as_str()).collect();
+                assert_eq!(names, vec!["amount", "event", "recipient"]);
+            }
+            ref other => panic!("Unexpected print type {:?}", other),
+        }
+    }
+
+    // blockstack-core's tests/bindgen.rs builds the generated Rust against `blockstack_lib`
+    #[test]
+    fn test_rust_bindings() {
+        let code = bindings(BindingsLanguage::Rust);
+        assert!(code.contains("pub struct TokenVaultContract {"));
+        assert!(code.contains(
+            "pub fn transfer(&self, amount: u128, recipient: PrincipalData) -> BindingResult<TransactionContractCall> {"
+        ));
+        assert!(code.contains(
+            "Ok(self.contract_call(\"transfer\", vec![Value::UInt(amount), Value::Principal(recipient)]))"
+        ));
+        assert!(code.contains("pub enum TransferResponse {\n    Ok(bool),\n    Err(()),\n}"));
+        assert!(code.contains(
+            "pub struct GetInfoOk {\n    pub balance: u128,\n    pub owner: PrincipalData,\n}"
+        ));
+        assert!(code.contains(
+            "pub fn decode_get_info_result(value: Value) -> BindingResult<GetInfoResponse> {"
+        ));
+        assert!(code.contains("pub const OWNER_VAR: &'static str = \"owner\";"));
+        assert!(code
+            .contains("pub fn encode_balances_key(key: PrincipalData) -> BindingResult<Value> {"));
+        assert!(code.contains(
+            "pub fn decode_balances_entry(value: Value) -> BindingResult<Option<u128>> {"
+        ));
+        assert!(code.contains("pub enum TokenVaultPrintEvent {"));
+        assert!(code.contains("    Transfer(TransferPrint),"));
+        // private functions can't be called from outside the contract
+        assert!(!code.contains("helper"));
+    }
+
+    #[test]
+    fn test_typescript_bindings() {
+        let code = bindings(BindingsLanguage::TypeScript);
+        assert!(code.contains("export class TokenVaultContract {"));
+        assert!(code.contains("  transfer(amount: bigint, recipient: string): ContractCall {"));
+        assert!(code.contains(
+            "return this.contractCall(\"transfer\", [uintCV(amount), principalCV(recipient)]);"
+        ));
+        assert!(code.contains("export type TransferResponse = Response<boolean, null>;"));
+        assert!(
+            code.contains("export interface GetInfoOk {\n  balance: bigint;\n  owner: string;\n}")
+        );
+        assert!(code.contains("  static decodeBalancesEntry(value: ClarityValue): bigint | null {"));
+        assert!(code.contains("  | { kind: \"transfer\"; value: TransferPrint }"));
+        assert!(!code.contains("helper"));
+    }
+
+    #[test]
+    fn test_identifiers() {
+        assert_eq!(pascal_case("get-balance?"), "GetBalance");
+        assert_eq!(camel_case("get-balance?"), "getBalance");
+        assert_eq!(snake_case("set-owner!"), "set_owner");
+        assert_eq!(pascal_case("ERR-NOT-FOUND"), "ErrNotFound");
+        assert_eq!(pascal_case("2fa"), "T2fa");
+        assert_eq!(
+            unique_idents(
+                vec!["type".to_string(), "a-b".to_string(), "a_b".to_string()]
+                    .into_iter()
+                    .map(|name| snake_case(&name)),
+                RUST_KEYWORDS
+            ),
+            vec!["type_", "a_b", "a_b2"]
+        );
+    }
+}
@@ -14,8 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+pub mod bindgen;
+
 use stacks_common::types::StacksEpochId;
 
+use crate::vm::analysis::cost_analyzer::WorstCaseCost;
 use crate::vm::analysis::types::ContractAnalysis;
 use crate::vm::types::signatures::CallableSubtype;
 use crate::vm::types::{
@@ -51,6 +54,8 @@ pub fn build_contract_interface(contract_analysis: &ContractAnalysis) -> Contrac
         cost_track: _,
         contract_interface: _,
         is_cost_contract_eligible: _,
+        worst_case_costs,
+        lint_warnings: _,
     } = contract_analysis;
 
     contract_interface
@@ -58,6 +63,7 @@ pub fn build_contract_interface(contract_analysis: &ContractAnalysis) -> Contrac
         .append(&mut ContractInterfaceFunction::from_map(
             private_function_types,
             ContractInterfaceFunctionAccess::private,
+            worst_case_costs,
         ));
 
     contract_interface
@@ -65,6 +71,7 @@ pub fn build_contract_interface(contract_analysis: &ContractAnalysis) -> Contrac
         .append(&mut ContractInterfaceFunction::from_map(
             public_function_types,
             ContractInterfaceFunctionAccess::public,
+            worst_case_costs,
         ));
 
     contract_interface
@@ -72,6 +79,7 @@ pub fn build_contract_interface(contract_analysis: &ContractAnalysis) -> Contrac
         .append(&mut ContractInterfaceFunction::from_map(
             read_only_function_types,
             ContractInterfaceFunctionAccess::read_only,
+            worst_case_costs,
         ));
 
     contract_interface
@@ -259,12 +267,15 @@ pub struct ContractInterfaceFunction {
     pub access: ContractInterfaceFunctionAccess,
     pub args: Vec<ContractInterfaceFunctionArg>,
     pub outputs: ContractInterfaceFunctionOutput,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worst_case_cost: Option<WorstCaseCost>,
 }
 
 impl ContractInterfaceFunction {
     pub fn from_map(
         map: &BTreeMap<ClarityName, FunctionType>,
         access: ContractInterfaceFunctionAccess,
+        worst_case_costs: &BTreeMap<ClarityName, WorstCaseCost>,
     ) -> Vec<ContractInterfaceFunction> {
         map.iter()
             .map(|(name, function_type)| ContractInterfaceFunction {
@@ -286,6 +297,7 @@ impl ContractInterfaceFunction {
                     }
                     _ => panic!("Contract functions should only have fixed function arguments!"),
                 },
+                worst_case_cost: worst_case_costs.get(name).cloned(),
             })
             .collect()
     }
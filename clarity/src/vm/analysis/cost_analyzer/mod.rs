@@ -0,0 +1,654 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use stacks_common::types::StacksEpochId;
+
+use crate::vm::analysis::type_checker::contexts::TypeMap;
+use crate::vm::analysis::types::{AnalysisPass, ContractAnalysis};
+use crate::vm::callables::CallableType;
+use crate::vm::costs::cost_functions::ClarityCostFunction;
+use crate::vm::costs::{CostTracker, ExecutionCost, LimitedCostTracker};
+use crate::vm::functions::define::DefineFunctionsParsed;
+use crate::vm::functions::{lookup_reserved_functions, NativeFunctions};
+use crate::vm::representations::SymbolicExpressionType::{
+    Atom, AtomValue, Field, List, LiteralValue, TraitReference,
+};
+use crate::vm::representations::{ClarityName, SymbolicExpression};
+use crate::vm::types::{
+    FixedFunction, FunctionType, SequenceSubtype, StringSubtype, TypeSignature,
+};
+use crate::vm::variables::NativeVariables;
+use crate::vm::ClarityVersion;
+
+use super::errors::{CheckErrors, CheckResult};
+use super::AnalysisDatabase;
+
+/// The statically computed upper bound on the cost of invoking a function.
+///
+/// The bound covers evaluating the function body, including every private
+///  function it calls, but not the cost of loading the contract itself.
+/// If the function uses `contract-call?`, the cost of the callee is not
+///  known statically and `calls_contracts` is set.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorstCaseCost {
+    pub cost: ExecutionCost,
+    pub calls_contracts: bool,
+}
+
+impl WorstCaseCost {
+    pub fn zero() -> WorstCaseCost {
+        WorstCaseCost {
+            cost: ExecutionCost::zero(),
+            calls_contracts: false,
+        }
+    }
+
+    fn from_cost(cost: ExecutionCost) -> WorstCaseCost {
+        WorstCaseCost {
+            cost,
+            calls_contracts: false,
+        }
+    }
+
+    fn add(&mut self, other: &WorstCaseCost) {
+        self.cost.runtime = self.cost.runtime.saturating_add(other.cost.runtime);
+        self.cost.read_count = self.cost.read_count.saturating_add(other.cost.read_count);
+        self.cost.read_length = self.cost.read_length.saturating_add(other.cost.read_length);
+        self.cost.write_count = self.cost.write_count.saturating_add(other.cost.write_count);
+        self.cost.write_length = self
+            .cost
+            .write_length
+            .saturating_add(other.cost.write_length);
+        self.calls_contracts |= other.calls_contracts;
+    }
+
+    fn multiply(&mut self, times: u64) {
+        self.cost.runtime = self.cost.runtime.saturating_mul(times);
+        self.cost.read_count = self.cost.read_count.saturating_mul(times);
+        self.cost.read_length = self.cost.read_length.saturating_mul(times);
+        self.cost.write_count = self.cost.write_count.saturating_mul(times);
+        self.cost.write_length = self.cost.write_length.saturating_mul(times);
+    }
+
+    fn max(a: WorstCaseCost, b: WorstCaseCost) -> WorstCaseCost {
+        WorstCaseCost {
+            cost: ExecutionCost::max_cost(a.cost, b.cost),
+            calls_contracts: a.calls_contracts || b.calls_contracts,
+        }
+    }
+}
+
+///
+/// A static-analysis pass that computes an upper bound on the execution cost
+///  of each public and read-only function in a contract.
+/// Every runtime cost charged by the interpreter is mirrored here, with the
+///  input sizes replaced by the maximum sizes admitted by the type checker,
+///  and with branches resolved to whichever is more expensive. The cost
+///  functions are evaluated with the contract's cost tracker, so the bound
+///  reflects the cost contract (`costs-2` or `costs-3`) of the epoch.
+///
+/// This pass is not part of `run_analysis`: it never affects whether a
+///  contract is accepted by the network, and tools that want the bound
+///  (e.g., `clarity-cli check --costs`) run it after the type checker.
+///
+pub struct CostAnalyzer<'a> {
+    epoch: StacksEpochId,
+    clarity_version: ClarityVersion,
+    contract_analysis: &'a ContractAnalysis,
+    type_map: &'a TypeMap,
+    cost_tracker: &'a mut LimitedCostTracker,
+    functions: HashMap<&'a ClarityName, &'a SymbolicExpression>,
+    function_costs: HashMap<ClarityName, WorstCaseCost>,
+    in_progress: HashSet<ClarityName>,
+}
+
+impl AnalysisPass for CostAnalyzer<'_> {
+    fn run_pass(
+        epoch: &StacksEpochId,
+        contract_analysis: &mut ContractAnalysis,
+        _analysis_db: &mut AnalysisDatabase,
+    ) -> CheckResult<()> {
+        let mut cost_tracker = contract_analysis.take_contract_cost_tracker();
+        let result = CostAnalyzer::run(epoch, contract_analysis, &mut cost_tracker);
+        contract_analysis.replace_contract_cost_tracker(cost_tracker);
+        contract_analysis.worst_case_costs = result?;
+        Ok(())
+    }
+}
+
+impl<'a> CostAnalyzer<'a> {
+    fn run(
+        epoch: &StacksEpochId,
+        contract_analysis: &'a ContractAnalysis,
+        cost_tracker: &'a mut LimitedCostTracker,
+    ) -> CheckResult<BTreeMap<ClarityName, WorstCaseCost>> {
+        let type_map = contract_analysis.type_map.as_ref().ok_or_else(|| {
+            CheckErrors::CostComputationFailed(
+                "cost analysis requires a type-checked contract".into(),
+            )
+        })?;
+
+        let mut functions = HashMap::new();
+        for expr in contract_analysis.expressions.iter() {
+            use crate::vm::functions::define::DefineFunctionsParsed::*;
+            match DefineFunctionsParsed::try_parse(expr)? {
+                Some(PrivateFunction { signature, body })
+                | Some(ReadOnlyFunction { signature, body })
+                | Some(PublicFunction { signature, body }) => {
+                    let name = signature
+                        .get(0)
+                        .and_then(|name| name.match_atom())
+                        .ok_or(CheckErrors::DefineFunctionBadSignature)?;
+                    functions.insert(name, body);
+                }
+                _ => {}
+            }
+        }
+
+        let mut analyzer = CostAnalyzer {
+            epoch: *epoch,
+            clarity_version: contract_analysis.clarity_version,
+            contract_analysis,
+            type_map,
+            cost_tracker,
+            functions,
+            function_costs: HashMap::new(),
+            in_progress: HashSet::new(),
+        };
+
+        let mut worst_case_costs = BTreeMap::new();
+        for name in contract_analysis
+            .public_function_types
+            .keys()
+            .chain(contract_analysis.read_only_function_types.keys())
+        {
+            let cost = analyzer.function_cost(name)?;
+            worst_case_costs.insert(name.clone(), cost);
+        }
+        Ok(worst_case_costs)
+    }
+
+    fn runtime_cost(
+        &mut self,
+        cost_function: ClarityCostFunction,
+        input: u64,
+    ) -> CheckResult<WorstCaseCost> {
+        let cost = self.cost_tracker.compute_cost(cost_function, &[input])?;
+        Ok(WorstCaseCost::from_cost(cost))
+    }
+
+    fn function_type(&self, name: &str) -> Option<&'a FixedFunction> {
+        let function_type = self
+            .contract_analysis
+            .get_private_function(name)
+            .or_else(|| self.contract_analysis.get_public_function_type(name))
+            .or_else(|| self.contract_analysis.get_read_only_function_type(name))?;
+        match function_type {
+            FunctionType::Fixed(fixed) => Some(fixed),
+            _ => None,
+        }
+    }
+
+    /// The cost of applying a user-defined function to already evaluated
+    ///  arguments, mirroring `DefinedFunction::execute_apply`.
+    fn function_cost(&mut self, name: &ClarityName) -> CheckResult<WorstCaseCost> {
+        if let Some(cost) = self.function_costs.get(name) {
+            return Ok(cost.clone());
+        }
+        if !self.in_progress.insert(name.clone()) {
+            return Err(CheckErrors::CircularReference(vec![name.to_string()]).into());
+        }
+
+        let function_type = self
+            .function_type(name)
+            .ok_or_else(|| CheckErrors::UnknownFunction(name.to_string()))?;
+        let body = *self
+            .functions
+            .get(name)
+            .ok_or_else(|| CheckErrors::UnknownFunction(name.to_string()))?;
+
+        let mut cost = self.runtime_cost(
+            ClarityCostFunction::UserFunctionApplication,
+            function_type.args.len() as u64,
+        )?;
+        for arg in function_type.args.iter() {
+            cost.add(&self.runtime_cost(
+                ClarityCostFunction::InnerTypeCheckCost,
+                u64::from(arg.signature.size()),
+            )?);
+        }
+        cost.add(&self.expression_cost(body, 0)?);
+
+        self.in_progress.remove(name);
+        self.function_costs.insert(name.clone(), cost.clone());
+        Ok(cost)
+    }
+
+    fn expression_cost(
+        &mut self,
+        expr: &SymbolicExpression,
+        depth: u64,
+    ) -> CheckResult<WorstCaseCost> {
+        match expr.expr {
+            AtomValue(_) | LiteralValue(_) | Field(_) | TraitReference(..) => {
+                Ok(WorstCaseCost::zero())
+            }
+            Atom(ref name) => self.variable_cost(expr, name, depth),
+            List(ref children) => {
+                let (function_name, args) = children
+                    .split_first()
+                    .ok_or(CheckErrors::NonFunctionApplication)?;
+                let function_name = function_name
+                    .match_atom()
+                    .ok_or(CheckErrors::BadFunctionName)?;
+
+                let mut cost = self.runtime_cost(ClarityCostFunction::LookupFunction, 0)?;
+                cost.add(&self.application_cost(expr, function_name, args, depth)?);
+                Ok(cost)
+            }
+        }
+    }
+
+    fn expressions_cost(
+        &mut self,
+        exprs: &[SymbolicExpression],
+        depth: u64,
+    ) -> CheckResult<WorstCaseCost> {
+        let mut cost = WorstCaseCost::zero();
+        for expr in exprs.iter() {
+            cost.add(&self.expression_cost(expr, depth)?);
+        }
+        Ok(cost)
+    }
+
+    /// Mirrors `lookup_variable`: reserved variables which read chain state
+    ///  are charged as a data-var fetch, while local and contract-level
+    ///  bindings are charged by context depth and the size of their type.
+    /// Atoms the type checker did not annotate are names, not variables.
+    fn variable_cost(
+        &mut self,
+        expr: &SymbolicExpression,
+        name: &str,
+        depth: u64,
+    ) -> CheckResult<WorstCaseCost> {
+        if let Some(variable) =
+            NativeVariables::lookup_by_name_at_version(name, &self.clarity_version)
+        {
+            return match variable {
+                NativeVariables::BlockHeight
+                | NativeVariables::BurnBlockHeight
+                | NativeVariables::TotalLiquidMicroSTX => {
+                    self.runtime_cost(ClarityCostFunction::FetchVar, 1)
+                }
+                _ => Ok(WorstCaseCost::zero()),
+            };
+        }
+        match self.type_map.get_type(expr) {
+            Some(var_type) => {
+                let size = u64::from(var_type.size());
+                let mut cost =
+                    self.runtime_cost(ClarityCostFunction::LookupVariableDepth, depth)?;
+                cost.add(&self.runtime_cost(ClarityCostFunction::LookupVariableSize, size)?);
+                Ok(cost)
+            }
+            None => Ok(WorstCaseCost::zero()),
+        }
+    }
+
+    fn application_cost(
+        &mut self,
+        expr: &SymbolicExpression,
+        function_name: &ClarityName,
+        args: &[SymbolicExpression],
+        depth: u64,
+    ) -> CheckResult<WorstCaseCost> {
+        if let Some(function) = lookup_reserved_functions(function_name, &self.clarity_version) {
+            return match function {
+                CallableType::SpecialFunction(..) => {
+                    let native = NativeFunctions::lookup_by_name_at_version(
+                        function_name,
+                        &self.clarity_version,
+                    )
+                    .ok_or_else(|| CheckErrors::UnknownFunction(function_name.to_string()))?;
+                    self.special_cost(expr, native, args, depth)
+                }
+                _ => {
+                    let mut cost = self.expressions_cost(args, depth)?;
+                    let arg_sizes = self.size_bounds(args);
+                    cost.add(&self.native_cost(&function, &arg_sizes)?);
+                    Ok(cost)
+                }
+            };
+        }
+
+        let mut cost = self.expressions_cost(args, depth)?;
+        cost.add(&self.function_cost(function_name)?);
+        Ok(cost)
+    }
+
+    /// The cost charged when applying a non-special native to evaluated
+    ///  arguments of the given sizes.
+    fn native_cost(
+        &mut self,
+        function: &CallableType,
+        arg_sizes: &[u64],
+    ) -> CheckResult<WorstCaseCost> {
+        match function {
+            CallableType::NativeFunction(_, _, cost_function) => {
+                self.runtime_cost(*cost_function, arg_sizes.len() as u64)
+            }
+            CallableType::NativeFunction205(_, _, cost_function, _) => {
+                let input = if self.epoch >= StacksEpochId::Epoch2_05 {
+                    arg_sizes
+                        .iter()
+                        .fold(0u64, |sum, size| sum.saturating_add(*size))
+                } else {
+                    arg_sizes.len() as u64
+                };
+                self.runtime_cost(*cost_function, input)
+            }
+            _ => Ok(WorstCaseCost::zero()),
+        }
+    }
+
+    fn special_cost(
+        &mut self,
+        expr: &SymbolicExpression,
+        native: NativeFunctions,
+        args: &[SymbolicExpression],
+        depth: u64,
+    ) -> CheckResult<WorstCaseCost> {
+        use crate::vm::functions::NativeFunctions::*;
+        match native {
+            If => {
+                if args.len() != 3 {
+                    return Err(CheckErrors::IncorrectArgumentCount(3, args.len()).into());
+                }
+                let mut cost = self.expression_cost(&args[0], depth)?;
+                cost.add(&self.runtime_cost(ClarityCostFunction::If, 0)?);
+                let then_branch = self.expression_cost(&args[1], depth)?;
+                let else_branch = self.expression_cost(&args[2], depth)?;
+                cost.add(&WorstCaseCost::max(then_branch, else_branch));
+                Ok(cost)
+            }
+            Let => {
+                let bindings = args
+                    .get(0)
+                    .and_then(|bindings| bindings.match_list())
+                    .ok_or(CheckErrors::BadLetSyntax)?;
+                let mut cost =
+                    self.runtime_cost(ClarityCostFunction::Let, bindings.len() as u64)?;
+                for binding in bindings.iter() {
+                    let value = binding
+                        .match_list()
+                        .and_then(|pair| pair.get(1))
+                        .ok_or(CheckErrors::BadSyntaxBinding)?;
+                    cost.add(&self.expression_cost(value, depth + 1)?);
+                }
+                cost.add(&self.expressions_cost(&args[1..], depth + 1)?);
+                Ok(cost)
+            }
+            Match => {
+                // (match opt some-name some-branch none-branch)
+                // (match resp ok-name ok-branch err-name err-branch)
+                let (first_branch, second_branch) = match args.len() {
+                    4 => (&args[2], &args[3]),
+                    5 => (&args[2], &args[4]),
+                    _ => return Err(CheckErrors::BadMatchInput(TypeSignature::NoType).into()),
+                };
+                let mut cost = self.expression_cost(&args[0], depth)?;
+                cost.add(&self.runtime_cost(ClarityCostFunction::Match, 0)?);
+                let first_branch = self.expression_cost(first_branch, depth + 1)?;
+                let second_branch = self.expression_cost(second_branch, depth + 1)?;
+                cost.add(&WorstCaseCost::max(first_branch, second_branch));
+                Ok(cost)
+            }
+            TupleCons => {
+                let mut cost =
+                    self.runtime_cost(ClarityCostFunction::TupleCons, args.len() as u64)?;
+                for pair in args.iter() {
+                    let value = pair
+                        .match_list()
+                        .and_then(|pair| pair.get(1))
+                        .ok_or(CheckErrors::BadSyntaxBinding)?;
+                    cost.add(&self.expression_cost(value, depth)?);
+                }
+                Ok(cost)
+            }
+            Map | Filter | Fold => self.iteration_cost(expr, native, args, depth),
+            _ => {
+                // arguments in these positions are names, not evaluated expressions
+                let name_position = match native {
+                    FetchVar | SetVar | FetchEntry | SetEntry | InsertEntry | DeleteEntry
+                    | MintAsset | MintToken | TransferAsset | TransferToken | GetTokenBalance
+                    | GetAssetOwner | BurnAsset | BurnToken | GetTokenSupply | TupleGet
+                    | GetBlockInfo | GetBurnBlockInfo | FromConsensusBuff => Some(0),
+                    ContractCall => Some(1),
+                    _ => None,
+                };
+
+                let mut cost = WorstCaseCost::zero();
+                let mut input = args.len() as u64;
+                for (index, arg) in args.iter().enumerate() {
+                    if Some(index) == name_position {
+                        continue;
+                    }
+                    cost.add(&self.expression_cost(arg, depth)?);
+                    input = input.saturating_add(self.size_bound(arg));
+                }
+                if name_position == Some(0) {
+                    if let Some(name) = args.first().and_then(|name| name.match_atom()) {
+                        input = input.saturating_add(self.declared_size(name));
+                    }
+                }
+                if native == ContractCall {
+                    cost.calls_contracts = true;
+                }
+                if let Some(cost_function) = self.special_cost_function(native) {
+                    cost.add(&self.runtime_cost(cost_function, input)?);
+                }
+                Ok(cost)
+            }
+        }
+    }
+
+    /// `map`, `filter` and `fold` apply a function once per element of the
+    ///  input sequences, which is bounded by the sequences' maximum length.
+    fn iteration_cost(
+        &mut self,
+        expr: &SymbolicExpression,
+        native: NativeFunctions,
+        args: &[SymbolicExpression],
+        depth: u64,
+    ) -> CheckResult<WorstCaseCost> {
+        let function_name = args
+            .get(0)
+            .and_then(|name| name.match_atom())
+            .ok_or(CheckErrors::ExpectedName)?;
+        let sequences = match native {
+            NativeFunctions::Fold => args.get(1..2),
+            _ => args.get(1..),
+        }
+        .ok_or(CheckErrors::IncorrectArgumentCount(2, args.len()))?;
+
+        let mut cost = match native {
+            NativeFunctions::Map => {
+                self.runtime_cost(ClarityCostFunction::Map, args.len() as u64)?
+            }
+            NativeFunctions::Filter => self.runtime_cost(ClarityCostFunction::Filter, 0)?,
+            _ => self.runtime_cost(ClarityCostFunction::Fold, 0)?,
+        };
+        cost.add(&self.runtime_cost(ClarityCostFunction::LookupFunction, 0)?);
+        cost.add(&self.expressions_cost(&args[1..], depth)?);
+
+        let mut iterations: Option<u64> = None;
+        let mut element_sizes = vec![];
+        for sequence in sequences.iter() {
+            let (max_len, element_size) = match self.type_map.get_type(sequence) {
+                Some(TypeSignature::SequenceType(sequence_type)) => (
+                    sequence_max_len(sequence_type),
+                    size_bound(&sequence_type.unit_type()),
+                ),
+                _ => (0, 0),
+            };
+            iterations = Some(iterations.map_or(max_len, |len| len.min(max_len)));
+            element_sizes.push(element_size);
+        }
+        if native == NativeFunctions::Fold {
+            let accumulator_size = args
+                .get(2)
+                .map_or(0, |initial| self.size_bound(initial))
+                .max(self.size_bound(expr));
+            element_sizes.push(accumulator_size);
+        }
+
+        let mut per_element = self.function_application_cost(function_name, &element_sizes)?;
+        per_element.multiply(iterations.unwrap_or(0));
+        cost.add(&per_element);
+        Ok(cost)
+    }
+
+    /// The cost of applying `function_name` to already evaluated arguments,
+    ///  as `map`, `filter` and `fold` do.
+    fn function_application_cost(
+        &mut self,
+        function_name: &ClarityName,
+        arg_sizes: &[u64],
+    ) -> CheckResult<WorstCaseCost> {
+        match lookup_reserved_functions(function_name, &self.clarity_version) {
+            Some(CallableType::SpecialFunction(..)) => {
+                let native = NativeFunctions::lookup_by_name_at_version(
+                    function_name,
+                    &self.clarity_version,
+                )
+                .ok_or_else(|| CheckErrors::UnknownFunction(function_name.to_string()))?;
+                let input = arg_sizes.iter().fold(arg_sizes.len() as u64, |sum, size| {
+                    sum.saturating_add(*size)
+                });
+                match self.special_cost_function(native) {
+                    Some(cost_function) => self.runtime_cost(cost_function, input),
+                    None => Ok(WorstCaseCost::zero()),
+                }
+            }
+            Some(function) => self.native_cost(&function, arg_sizes),
+            None => self.function_cost(function_name),
+        }
+    }
+
+    /// The size of the data var, map entry or asset named by `name`.
+    fn declared_size(&self, name: &str) -> u64 {
+        if let Some(var_type) = self.contract_analysis.get_persisted_variable_type(name) {
+            size_bound(var_type)
+        } else if let Some((key_type, value_type)) = self.contract_analysis.get_map_type(name) {
+            size_bound(key_type).saturating_add(size_bound(value_type))
+        } else if let Some(asset_type) = self.contract_analysis.non_fungible_tokens.get(name) {
+            size_bound(asset_type)
+        } else {
+            0
+        }
+    }
+
+    fn size_bound(&self, expr: &SymbolicExpression) -> u64 {
+        self.type_map.get_type(expr).map_or(0, size_bound)
+    }
+
+    fn size_bounds(&self, exprs: &[SymbolicExpression]) -> Vec<u64> {
+        exprs.iter().map(|expr| self.size_bound(expr)).collect()
+    }
+
+    /// The cost function charged by each special function's implementation.
+    fn special_cost_function(&self, native: NativeFunctions) -> Option<ClarityCostFunction> {
+        use crate::vm::functions::NativeFunctions::*;
+        let cost_function = match native {
+            CmpGeq => ClarityCostFunction::Geq,
+            CmpLeq => ClarityCostFunction::Leq,
+            CmpLess => ClarityCostFunction::Le,
+            CmpGreater => ClarityCostFunction::Ge,
+            And => ClarityCostFunction::And,
+            Or => ClarityCostFunction::Or,
+            If => ClarityCostFunction::If,
+            Let => ClarityCostFunction::Let,
+            Match => ClarityCostFunction::Match,
+            Asserts => ClarityCostFunction::Asserts,
+            Map => ClarityCostFunction::Map,
+            Filter => ClarityCostFunction::Filter,
+            Fold => ClarityCostFunction::Fold,
+            Concat => ClarityCostFunction::Concat,
+            AsMaxLen => ClarityCostFunction::AsMaxLen,
+            Append => ClarityCostFunction::Append,
+            Slice => ClarityCostFunction::Slice,
+            ReplaceAt => ClarityCostFunction::ReplaceAt,
+            ListCons => ClarityCostFunction::ListCons,
+            FetchVar => ClarityCostFunction::FetchVar,
+            SetVar => ClarityCostFunction::SetVar,
+            FetchEntry => ClarityCostFunction::FetchEntry,
+            SetEntry | InsertEntry | DeleteEntry => ClarityCostFunction::SetEntry,
+            TupleCons => ClarityCostFunction::TupleCons,
+            TupleGet => ClarityCostFunction::TupleGet,
+            Secp256k1Recover => ClarityCostFunction::Secp256k1recover,
+            Secp256k1Verify => ClarityCostFunction::Secp256k1verify,
+            Print => ClarityCostFunction::Print,
+            ContractCall => ClarityCostFunction::ContractCall,
+            AsContract if self.epoch >= StacksEpochId::Epoch21 => ClarityCostFunction::AsContract,
+            ContractOf => ClarityCostFunction::ContractOf,
+            PrincipalOf => ClarityCostFunction::PrincipalOf,
+            AtBlock => ClarityCostFunction::AtBlock,
+            GetBlockInfo => ClarityCostFunction::BlockInfo,
+            GetBurnBlockInfo => ClarityCostFunction::GetBurnBlockInfo,
+            IsStandard => ClarityCostFunction::IsStandard,
+            PrincipalDestruct => ClarityCostFunction::PrincipalDestruct,
+            PrincipalConstruct => ClarityCostFunction::PrincipalConstruct,
+            MintAsset => ClarityCostFunction::NftMint,
+            MintToken => ClarityCostFunction::FtMint,
+            TransferAsset => ClarityCostFunction::NftTransfer,
+            TransferToken => ClarityCostFunction::FtTransfer,
+            GetTokenBalance => ClarityCostFunction::FtBalance,
+            GetAssetOwner => ClarityCostFunction::NftOwner,
+            BurnAsset => ClarityCostFunction::NftBurn,
+            BurnToken => ClarityCostFunction::FtBurn,
+            GetTokenSupply => ClarityCostFunction::FtSupply,
+            GetStxBalance => ClarityCostFunction::StxBalance,
+            StxTransfer | StxBurn => ClarityCostFunction::StxTransfer,
+            StxTransferMemo => ClarityCostFunction::StxTransferMemo,
+            StxGetAccount => ClarityCostFunction::StxGetAccount,
+            FromConsensusBuff => ClarityCostFunction::FromConsensusBuff,
+            _ => return None,
+        };
+        Some(cost_function)
+    }
+}
+
+/// An upper bound on the size of any value of `type_sig`, as measured both by
+///  `Value::size()` and by its serialized length.
+fn size_bound(type_sig: &TypeSignature) -> u64 {
+    let serialized_size = type_sig.max_serialized_size().unwrap_or(0);
+    u64::from(type_sig.size().max(serialized_size))
+}
+
+fn sequence_max_len(sequence_type: &SequenceSubtype) -> u64 {
+    let max_len = match sequence_type {
+        SequenceSubtype::BufferType(len) => u32::from(len),
+        SequenceSubtype::ListType(list_type) => list_type.get_max_len(),
+        SequenceSubtype::StringType(StringSubtype::ASCII(len)) => u32::from(len),
+        SequenceSubtype::StringType(StringSubtype::UTF8(len)) => u32::from(len),
+    };
+    u64::from(max_len)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,66 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use stacks_common::types::StacksEpochId;
+
+use crate::vm::analysis::contract_interface_builder::build_contract_interface;
+use crate::vm::analysis::cost_analyzer::CostAnalyzer;
+use crate::vm::analysis::{mem_type_check, AnalysisPass};
+use crate::vm::database::MemoryBackingStore;
+use crate::vm::ClarityVersion;
+
+#[test]
+fn test_worst_case_costs_cover_public_functions() {
+    let contract = "(define-trait incrementer ((increment () (response uint uint))))
+        (define-data-var counter uint u0)
+        (define-private (bump (by uint)) (var-set counter (+ (var-get counter) by)))
+        (define-public (increment) (ok (bump u1)))
+        (define-public (forward (target <incrementer>))
+          (contract-call? target increment))
+        (define-read-only (get-counter)
+          (fold + (list u1 u2 u3) (var-get counter)))";
+
+    let mut analysis = mem_type_check(contract, ClarityVersion::Clarity2, StacksEpochId::Epoch21)
+        .unwrap()
+        .1;
+    let mut marf = MemoryBackingStore::new();
+    CostAnalyzer::run_pass(
+        &StacksEpochId::Epoch21,
+        &mut analysis,
+        &mut marf.as_analysis_db(),
+    )
+    .unwrap();
+
+    let names: Vec<_> = analysis
+        .worst_case_costs
+        .keys()
+        .map(|name| name.to_string())
+        .collect();
+    assert_eq!(names, vec!["forward", "get-counter", "increment"]);
+    assert!(analysis.worst_case_costs["forward"].calls_contracts);
+    assert!(!analysis.worst_case_costs["increment"].calls_contracts);
+    assert!(!analysis.worst_case_costs["get-counter"].calls_contracts);
+
+    let interface = build_contract_interface(&analysis);
+    for function in interface.functions.iter() {
+        assert_eq!(
+            function.worst_case_cost.is_some(),
+            function.name != "bump",
+            "{}",
+            function.name
+        );
+    }
+}
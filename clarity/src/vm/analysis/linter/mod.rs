@@ -0,0 +1,576 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{HashMap, HashSet};
+
+use stacks_common::types::StacksEpochId;
+
+use crate::vm::analysis::types::{AnalysisPass, ContractAnalysis};
+use crate::vm::diagnostic::{Diagnostic, Level};
+use crate::vm::functions::define::DefineFunctionsParsed;
+use crate::vm::functions::{lookup_reserved_functions, NativeFunctions};
+use crate::vm::representations::{ClarityName, Span, SymbolicExpression};
+use crate::vm::types::{FunctionType, TypeSignature};
+use crate::vm::variables::is_reserved_name;
+use crate::vm::ClarityVersion;
+
+use super::errors::{CheckErrors, CheckResult};
+use super::AnalysisDatabase;
+
+#[cfg(test)]
+mod tests;
+
+define_named_enum!(LintRule {
+    UnusedBinding("unused-binding"),
+    UnusedPrivateFunction("unused-private-function"),
+    UnwrapPanicInPublic("unwrap-panic-in-public"),
+    PublicWithoutErr("public-without-err"),
+    AsContractTransfer("as-contract-transfer"),
+    ShadowedName("shadowed-name"),
+    UncheckedContractCall("unchecked-contract-call"),
+});
+
+/// A warning produced by one of the lint rules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintWarning {
+    pub rule: LintRule,
+    pub diagnostic: Diagnostic,
+}
+
+/// Which lint rules to run. Every rule is enabled by default.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LintSettings {
+    disabled: HashSet<LintRule>,
+}
+
+impl LintSettings {
+    pub fn enable(&mut self, rule: LintRule) {
+        self.disabled.remove(&rule);
+    }
+
+    pub fn disable(&mut self, rule: LintRule) {
+        self.disabled.insert(rule);
+    }
+
+    pub fn is_enabled(&self, rule: LintRule) -> bool {
+        !self.disabled.contains(&rule)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionKind {
+    Private,
+    ReadOnly,
+    Public,
+}
+
+struct DefinedFunction<'a> {
+    definition: &'a SymbolicExpression,
+    kind: FunctionKind,
+    name: &'a ClarityName,
+    signature: &'a [SymbolicExpression],
+    body: &'a SymbolicExpression,
+}
+
+///
+/// A static-analysis pass that reports code which type checks, but is likely
+///  to be a mistake: unused bindings and private functions, `unwrap-panic` in
+///  public functions, public functions that can never return `err`,
+///  `as-contract` blocks that move assets, local bindings that shadow other
+///  names, and `contract-call?` responses which are never inspected.
+///
+/// Like the `CostAnalyzer`, this pass is not part of `run_analysis`. It only
+///  produces warnings, which are stored in `ContractAnalysis::lint_warnings`.
+///
+pub struct Linter<'a> {
+    settings: &'a LintSettings,
+    contract_analysis: &'a ContractAnalysis,
+    clarity_version: ClarityVersion,
+    functions: Vec<DefinedFunction<'a>>,
+    transfers_assets: HashMap<&'a ClarityName, bool>,
+    warnings: Vec<LintWarning>,
+}
+
+impl AnalysisPass for Linter<'_> {
+    fn run_pass(
+        _epoch: &StacksEpochId,
+        contract_analysis: &mut ContractAnalysis,
+        _analysis_db: &mut AnalysisDatabase,
+    ) -> CheckResult<()> {
+        Linter::run_with_settings(contract_analysis, &LintSettings::default())
+    }
+}
+
+impl<'a> Linter<'a> {
+    /// Run the enabled lint rules, replacing `contract_analysis.lint_warnings`.
+    pub fn run_with_settings(
+        contract_analysis: &mut ContractAnalysis,
+        settings: &LintSettings,
+    ) -> CheckResult<()> {
+        let warnings = Linter::run(contract_analysis, settings)?;
+        contract_analysis.lint_warnings = warnings;
+        Ok(())
+    }
+
+    fn run(
+        contract_analysis: &'a ContractAnalysis,
+        settings: &'a LintSettings,
+    ) -> CheckResult<Vec<LintWarning>> {
+        let mut functions = vec![];
+        for expr in contract_analysis.expressions.iter() {
+            use crate::vm::functions::define::DefineFunctionsParsed::*;
+            let (kind, signature, body) = match DefineFunctionsParsed::try_parse(expr)? {
+                Some(PrivateFunction { signature, body }) => {
+                    (FunctionKind::Private, signature, body)
+                }
+                Some(ReadOnlyFunction { signature, body }) => {
+                    (FunctionKind::ReadOnly, signature, body)
+                }
+                Some(PublicFunction { signature, body }) => (FunctionKind::Public, signature, body),
+                _ => continue,
+            };
+            let name = signature
+                .first()
+                .and_then(|name| name.match_atom())
+                .ok_or(CheckErrors::DefineFunctionBadSignature)?;
+            functions.push(DefinedFunction {
+                definition: expr,
+                kind,
+                name,
+                signature,
+                body,
+            });
+        }
+
+        let mut linter = Linter {
+            settings,
+            contract_analysis,
+            clarity_version: contract_analysis.clarity_version,
+            functions,
+            transfers_assets: HashMap::new(),
+            warnings: vec![],
+        };
+
+        linter.check_unused_private_functions();
+        for index in 0..linter.functions.len() {
+            linter.check_function(index);
+        }
+        linter.check_top_level_contract_calls();
+
+        let mut warnings = linter.warnings;
+        warnings.sort_by_key(|warning| {
+            warning
+                .diagnostic
+                .spans
+                .first()
+                .map(|span| (span.start_line, span.start_column))
+        });
+        Ok(warnings)
+    }
+
+    fn warn(
+        &mut self,
+        rule: LintRule,
+        expr: &SymbolicExpression,
+        message: String,
+        suggestion: Option<&str>,
+    ) {
+        if !self.settings.is_enabled(rule) {
+            return;
+        }
+        self.warnings.push(LintWarning {
+            rule,
+            diagnostic: Diagnostic {
+                level: Level::Warning,
+                message,
+                spans: expression_spans(expr),
+                suggestion: suggestion.map(|suggestion| suggestion.to_string()),
+            },
+        });
+    }
+
+    fn native_function(&self, expr: &SymbolicExpression) -> Option<NativeFunctions> {
+        let name = expr.match_list()?.first()?.match_atom()?;
+        NativeFunctions::lookup_by_name_at_version(name, &self.clarity_version)
+    }
+
+    fn check_function(&mut self, index: usize) {
+        let function = &self.functions[index];
+        let (kind, name, signature, body) = (
+            function.kind,
+            function.name,
+            function.signature,
+            function.body,
+        );
+
+        for arg in signature[1..].iter() {
+            if let Some(arg_name) = arg
+                .match_list()
+                .and_then(|arg| arg.first())
+                .and_then(|arg_name| arg_name.match_atom())
+            {
+                self.check_shadowing(arg_name, arg);
+            }
+        }
+
+        if kind == FunctionKind::Public {
+            self.check_public_errors(name, &signature[0]);
+        }
+
+        self.check_expression(body, kind == FunctionKind::Public, name);
+        self.check_contract_calls(body, true);
+    }
+
+    /// Private functions which are never referenced by any other expression.
+    fn check_unused_private_functions(&mut self) {
+        let mut referenced = HashSet::new();
+        for expr in self.contract_analysis.expressions.iter() {
+            // a function's own signature does not count as a reference.
+            match self
+                .functions
+                .iter()
+                .find(|function| std::ptr::eq(function.definition, expr))
+            {
+                Some(function) => collect_atoms(function.body, &mut referenced),
+                None => collect_atoms(expr, &mut referenced),
+            }
+        }
+
+        let unused: Vec<_> = self
+            .functions
+            .iter()
+            .filter(|function| {
+                function.kind == FunctionKind::Private && !referenced.contains(function.name)
+            })
+            .map(|function| (function.name, &function.signature[0]))
+            .collect();
+        for (name, name_expr) in unused {
+            self.warn(
+                LintRule::UnusedPrivateFunction,
+                name_expr,
+                format!("private function '{}' is never called", name),
+                Some("Remove the function, or call it from a public or read-only function."),
+            );
+        }
+    }
+
+    fn check_public_errors(&mut self, name: &ClarityName, name_expr: &SymbolicExpression) {
+        let returns = match self.contract_analysis.get_public_function_type(name) {
+            Some(FunctionType::Fixed(function)) => &function.returns,
+            _ => return,
+        };
+        if let TypeSignature::ResponseType(response) = returns {
+            if response.1 == TypeSignature::NoType {
+                self.warn(
+                    LintRule::PublicWithoutErr,
+                    name_expr,
+                    format!(
+                        "public function '{}' never returns an err response, so its changes are always committed",
+                        name
+                    ),
+                    Some("Return an err response when the function cannot succeed, for example with asserts!."),
+                );
+            }
+        }
+    }
+
+    /// Walks a function body, reporting `let` bindings which are never used
+    ///  or shadow another name, `unwrap-panic` in public functions, and
+    ///  `as-contract` blocks which move assets.
+    fn check_expression(
+        &mut self,
+        expr: &'a SymbolicExpression,
+        in_public: bool,
+        function_name: &ClarityName,
+    ) {
+        let children = match expr.match_list() {
+            Some(children) => children,
+            None => return,
+        };
+
+        match self.native_function(expr) {
+            Some(NativeFunctions::Let) => {
+                if let Some(bindings) = children.get(1).and_then(|list| list.match_list()) {
+                    for (index, binding) in bindings.iter().enumerate() {
+                        let binding_name = match binding_name(binding) {
+                            Some(binding_name) => binding_name,
+                            None => continue,
+                        };
+                        self.check_shadowing(binding_name, binding);
+
+                        let used = bindings[index + 1..]
+                            .iter()
+                            .filter_map(|binding| binding.match_list()?.get(1))
+                            .chain(children[2..].iter())
+                            .any(|expr| references(expr, binding_name));
+                        if !used {
+                            self.warn(
+                                LintRule::UnusedBinding,
+                                binding,
+                                format!("let binding '{}' is never used", binding_name),
+                                Some("Remove the binding, or evaluate the expression in the let body if only its side effects are needed."),
+                            );
+                        }
+                    }
+                }
+            }
+            Some(NativeFunctions::Match) => {
+                let binding_names: &[usize] = if children.len() == 6 { &[2, 4] } else { &[2] };
+                for index in binding_names {
+                    if let Some(binding_name) = children.get(*index).and_then(|n| n.match_atom()) {
+                        self.check_shadowing(binding_name, &children[*index]);
+                    }
+                }
+            }
+            Some(NativeFunctions::Unwrap) | Some(NativeFunctions::UnwrapErr) if in_public => {
+                self.warn(
+                    LintRule::UnwrapPanicInPublic,
+                    expr,
+                    format!(
+                        "public function '{}' uses {}, which aborts the transaction without an error code",
+                        function_name, children[0]
+                    ),
+                    Some("Use unwrap! or unwrap-err! with an explicit err response instead."),
+                );
+            }
+            Some(NativeFunctions::AsContract)
+                if children[1..]
+                    .iter()
+                    .any(|expr| self.expression_transfers_assets(expr)) =>
+            {
+                self.warn(
+                    LintRule::AsContractTransfer,
+                    expr,
+                    "as-contract block transfers or burns assets owned by the contract".into(),
+                    Some("Make sure the recipient and amount cannot be chosen by an arbitrary caller."),
+                );
+            }
+            _ => {}
+        }
+
+        for child in children.iter() {
+            self.check_expression(child, in_public, function_name);
+        }
+    }
+
+    /// Local bindings which share their name with a native function or
+    ///  variable, or with a contract-level definition.
+    fn check_shadowing(&mut self, name: &ClarityName, expr: &SymbolicExpression) {
+        let shadowed = if is_reserved_name(name, &self.clarity_version) {
+            "a built-in variable, which takes precedence over the binding"
+        } else if lookup_reserved_functions(name, &self.clarity_version).is_some() {
+            "a built-in function"
+        } else if self.is_contract_definition(name) {
+            "a definition in this contract"
+        } else {
+            return;
+        };
+        self.warn(
+            LintRule::ShadowedName,
+            expr,
+            format!("binding '{}' shadows {}", name, shadowed),
+            Some("Rename the binding."),
+        );
+    }
+
+    fn is_contract_definition(&self, name: &ClarityName) -> bool {
+        let analysis = self.contract_analysis;
+        analysis.private_function_types.contains_key(name)
+            || analysis.public_function_types.contains_key(name)
+            || analysis.read_only_function_types.contains_key(name)
+            || analysis.variable_types.contains_key(name)
+            || analysis.persisted_variable_types.contains_key(name)
+            || analysis.map_types.contains_key(name)
+            || analysis.fungible_tokens.contains(name)
+            || analysis.non_fungible_tokens.contains_key(name)
+            || analysis.defined_traits.contains_key(name)
+    }
+
+    /// Whether evaluating the expression may move assets, either directly or
+    ///  through the private functions it calls.
+    fn expression_transfers_assets(&mut self, expr: &'a SymbolicExpression) -> bool {
+        let children = match expr.match_list() {
+            Some(children) => children,
+            None => return false,
+        };
+        match self.native_function(expr) {
+            Some(NativeFunctions::StxTransfer)
+            | Some(NativeFunctions::StxTransferMemo)
+            | Some(NativeFunctions::TransferToken)
+            | Some(NativeFunctions::TransferAsset)
+            | Some(NativeFunctions::StxBurn)
+            | Some(NativeFunctions::BurnToken)
+            | Some(NativeFunctions::BurnAsset) => return true,
+            _ => {}
+        }
+        if let Some(name) = children.first().and_then(|name| name.match_atom()) {
+            if self.function_transfers_assets(name) {
+                return true;
+            }
+        }
+        children
+            .iter()
+            .any(|child| self.expression_transfers_assets(child))
+    }
+
+    fn function_transfers_assets(&mut self, name: &ClarityName) -> bool {
+        let function = match self
+            .functions
+            .iter()
+            .find(|function| function.kind == FunctionKind::Private && function.name == name)
+        {
+            Some(function) => (function.name, function.body),
+            None => return false,
+        };
+        if let Some(transfers) = self.transfers_assets.get(function.0) {
+            return *transfers;
+        }
+        // Clarity functions cannot recurse, but guard against it anyway.
+        self.transfers_assets.insert(function.0, false);
+        let transfers = self.expression_transfers_assets(function.1);
+        self.transfers_assets.insert(function.0, transfers);
+        transfers
+    }
+
+    /// Reports `contract-call?` expressions whose response is neither
+    ///  inspected nor returned to the caller. `checked` is true when the
+    ///  value of `expr` is inspected or returned.
+    /// A response bound by `let` is treated as checked: whether the binding
+    ///  is used at all is reported by the `unused-binding` rule.
+    fn check_contract_calls(&mut self, expr: &'a SymbolicExpression, checked: bool) {
+        let children = match expr.match_list() {
+            Some(children) => children,
+            None => return,
+        };
+        let native = self.native_function(expr);
+        let args = &children[1..];
+        match native {
+            Some(NativeFunctions::ContractCall) => {
+                if !checked {
+                    self.warn(
+                        LintRule::UncheckedContractCall,
+                        expr,
+                        "the response of this contract-call? is never checked".into(),
+                        Some("Handle the err case with try!, unwrap!, match or asserts!."),
+                    );
+                }
+                for arg in args.iter() {
+                    self.check_contract_calls(arg, false);
+                }
+            }
+            Some(NativeFunctions::TryRet)
+            | Some(NativeFunctions::UnwrapRet)
+            | Some(NativeFunctions::UnwrapErrRet)
+            | Some(NativeFunctions::Unwrap)
+            | Some(NativeFunctions::UnwrapErr)
+            | Some(NativeFunctions::IsOkay)
+            | Some(NativeFunctions::IsErr)
+            | Some(NativeFunctions::Equals) => {
+                for arg in args.iter() {
+                    self.check_contract_calls(arg, true);
+                }
+            }
+            Some(NativeFunctions::Let) => {
+                if let Some(bindings) = args.first().and_then(|list| list.match_list()) {
+                    for value in bindings
+                        .iter()
+                        .filter_map(|binding| binding.match_list()?.get(1))
+                    {
+                        self.check_contract_calls(value, true);
+                    }
+                }
+                self.check_body(&args[1..], checked);
+            }
+            Some(NativeFunctions::Begin) => self.check_body(args, checked),
+            Some(NativeFunctions::If) | Some(NativeFunctions::Match) => {
+                for (index, arg) in args.iter().enumerate() {
+                    // the condition of an `if` is a bool, while the input
+                    //  of a `match` is inspected by the match itself.
+                    let arg_checked = if index == 0 {
+                        native == Some(NativeFunctions::Match)
+                    } else {
+                        checked
+                    };
+                    self.check_contract_calls(arg, arg_checked);
+                }
+            }
+            Some(NativeFunctions::AsContract) => {
+                for arg in args.iter() {
+                    self.check_contract_calls(arg, checked);
+                }
+            }
+            _ => {
+                for child in children.iter() {
+                    self.check_contract_calls(child, false);
+                }
+            }
+        }
+    }
+
+    fn check_body(&mut self, body: &'a [SymbolicExpression], checked: bool) {
+        if let Some((last, rest)) = body.split_last() {
+            for expr in rest.iter() {
+                self.check_contract_calls(expr, false);
+            }
+            self.check_contract_calls(last, checked);
+        }
+    }
+
+    /// Calls made by top-level expressions, whose results are discarded.
+    fn check_top_level_contract_calls(&mut self) {
+        for expr in self.contract_analysis.expressions.iter() {
+            if let Ok(None) = DefineFunctionsParsed::try_parse(expr) {
+                self.check_contract_calls(expr, false);
+            }
+        }
+    }
+}
+
+fn binding_name(binding: &SymbolicExpression) -> Option<&ClarityName> {
+    binding.match_list()?.first()?.match_atom()
+}
+
+fn references(expr: &SymbolicExpression, name: &ClarityName) -> bool {
+    match expr.match_list() {
+        Some(children) => children.iter().any(|child| references(child, name)),
+        None => expr.match_atom() == Some(name),
+    }
+}
+
+fn collect_atoms<'a>(expr: &'a SymbolicExpression, atoms: &mut HashSet<&'a ClarityName>) {
+    match expr.match_list() {
+        Some(children) => {
+            for child in children.iter() {
+                collect_atoms(child, atoms);
+            }
+        }
+        None => {
+            if let Some(atom) = expr.match_atom() {
+                atoms.insert(atom);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "developer-mode")]
+fn expression_spans(expr: &SymbolicExpression) -> Vec<Span> {
+    vec![expr.span.clone()]
+}
+
+#[cfg(not(feature = "developer-mode"))]
+fn expression_spans(_expr: &SymbolicExpression) -> Vec<Span> {
+    vec![]
+}
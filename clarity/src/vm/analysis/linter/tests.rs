@@ -0,0 +1,110 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use stacks_common::types::StacksEpochId;
+
+use crate::vm::analysis::linter::{LintRule, LintSettings, Linter};
+use crate::vm::analysis::{mem_type_check, AnalysisPass};
+use crate::vm::database::MemoryBackingStore;
+use crate::vm::ClarityVersion;
+
+fn lint(contract: &str, settings: &LintSettings) -> Vec<(LintRule, u32)> {
+    let mut analysis = mem_type_check(contract, ClarityVersion::Clarity2, StacksEpochId::Epoch21)
+        .unwrap()
+        .1;
+    Linter::run_with_settings(&mut analysis, settings).unwrap();
+    analysis
+        .lint_warnings
+        .iter()
+        .map(|warning| (warning.rule, warning.diagnostic.spans[0].start_line))
+        .collect()
+}
+
+#[test]
+fn test_clean_contract() {
+    let contract = "(define-trait token ((transfer (uint principal) (response bool uint))))
+        (define-data-var owner principal tx-sender)
+        (define-private (is-owner) (is-eq tx-sender (var-get owner)))
+        (define-public (withdraw (amount uint) (ft <token>))
+          (let ((recipient tx-sender))
+            (asserts! (is-owner) (err u1))
+            (try! (contract-call? ft transfer amount recipient))
+            (ok true)))";
+
+    let mut analysis = mem_type_check(contract, ClarityVersion::Clarity2, StacksEpochId::Epoch21)
+        .unwrap()
+        .1;
+    let mut marf = MemoryBackingStore::new();
+    Linter::run_pass(
+        &StacksEpochId::Epoch21,
+        &mut analysis,
+        &mut marf.as_analysis_db(),
+    )
+    .unwrap();
+    assert_eq!(analysis.lint_warnings, vec![]);
+}
+
+#[test]
+fn test_lint_rules() {
+    let contract = "(define-trait token ((transfer (uint principal) (response bool uint))))
+        (define-private (helper) u1)
+        (define-private (pay (amount uint))
+          (stx-transfer? amount tx-sender 'S1G2081040G2081040G2081040G208105NK8PE5))
+        (define-read-only (get-x) u1)
+        (define-public (f (list uint))
+          (let ((a u1) (b (+ list u1)) (get-x u2))
+            (ok (+ b get-x))))
+        (define-public (g (ft <token>))
+          (begin
+            (unwrap-panic (as-contract (pay u10)))
+            (ok (contract-call? ft transfer u1 tx-sender))))
+        (define-public (h (ft <token>))
+          (match (contract-call? ft transfer u1 tx-sender)
+            tx-sender (ok tx-sender)
+            code (err code)))";
+
+    assert_eq!(
+        lint(contract, &LintSettings::default()),
+        vec![
+            (LintRule::UnusedPrivateFunction, 2),
+            (LintRule::PublicWithoutErr, 6),
+            (LintRule::ShadowedName, 6),
+            (LintRule::UnusedBinding, 7),
+            (LintRule::ShadowedName, 7),
+            (LintRule::PublicWithoutErr, 9),
+            (LintRule::UnwrapPanicInPublic, 11),
+            (LintRule::AsContractTransfer, 11),
+            (LintRule::UncheckedContractCall, 12),
+            (LintRule::ShadowedName, 15),
+        ]
+    );
+
+    let mut settings = LintSettings::default();
+    settings.disable(LintRule::PublicWithoutErr);
+    settings.disable(LintRule::ShadowedName);
+    settings.disable(LintRule::UnusedBinding);
+    settings.enable(LintRule::UnusedBinding);
+    assert_eq!(
+        lint(contract, &settings),
+        vec![
+            (LintRule::UnusedPrivateFunction, 2),
+            (LintRule::UnusedBinding, 7),
+            (LintRule::UnwrapPanicInPublic, 11),
+            (LintRule::AsContractTransfer, 11),
+            (LintRule::UncheckedContractCall, 12),
+        ]
+    );
+}
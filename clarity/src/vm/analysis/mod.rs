@@ -17,7 +17,9 @@
 pub mod analysis_db;
 pub mod arithmetic_checker;
 pub mod contract_interface_builder;
+pub mod cost_analyzer;
 pub mod errors;
+pub mod linter;
 pub mod read_only_checker;
 pub mod trait_checker;
 pub mod type_checker;
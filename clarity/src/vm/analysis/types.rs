@@ -18,7 +18,9 @@ use stacks_common::types::StacksEpochId;
 
 use crate::vm::analysis::analysis_db::AnalysisDatabase;
 use crate::vm::analysis::contract_interface_builder::ContractInterface;
+use crate::vm::analysis::cost_analyzer::WorstCaseCost;
 use crate::vm::analysis::errors::{CheckErrors, CheckResult};
+use crate::vm::analysis::linter::LintWarning;
 use crate::vm::analysis::type_checker::contexts::TypeMap;
 use crate::vm::costs::{CostTracker, ExecutionCost, LimitedCostTracker};
 use crate::vm::types::signatures::FunctionSignature;
@@ -62,6 +64,13 @@ pub struct ContractAnalysis {
     pub type_map: Option<TypeMap>,
     #[serde(skip)]
     pub cost_track: Option<LimitedCostTracker>,
+    /// Upper bounds on the cost of each public and read-only function,
+    ///  filled in by the `CostAnalyzer` pass when it is run.
+    #[serde(skip)]
+    pub worst_case_costs: BTreeMap<ClarityName, WorstCaseCost>,
+    /// Warnings reported by the `Linter` pass when it is run.
+    #[serde(skip)]
+    pub lint_warnings: Vec<LintWarning>,
 }
 
 impl ContractAnalysis {
@@ -88,6 +97,8 @@ impl ContractAnalysis {
             fungible_tokens: BTreeSet::new(),
             non_fungible_tokens: BTreeMap::new(),
             cost_track: Some(cost_track),
+            worst_case_costs: BTreeMap::new(),
+            lint_warnings: vec![],
             is_cost_contract_eligible: false,
             epoch,
             clarity_version,
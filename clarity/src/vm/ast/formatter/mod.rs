@@ -0,0 +1,575 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A canonical source formatter for Clarity, built on the v2 parser.
+//!
+//! Expressions which fit on the current line are printed flat. Longer
+//! lists keep their function name (and, for known forms like `let` or
+//! `define-public`, their leading arguments) on the first line and put
+//! each remaining argument on its own line, indented under the opening
+//! parenthesis. Long tuples put one entry per line. Comments are kept where
+//! they were, either trailing the preceding expression or on their own
+//! line, and single blank lines between expressions are preserved.
+//!
+//! Literals and names are copied verbatim from the input, and the result is
+//! re-parsed and compared to the original, so formatting never changes the
+//! meaning of a contract.
+
+use std::error;
+use std::fmt;
+
+use crate::vm::ast::errors::ParseError;
+use crate::vm::ast::parser::v2::parse;
+use crate::vm::representations::{PreSymbolicExpression, PreSymbolicExpressionType};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatterSettings {
+    /// Expressions are broken across lines once they would extend past this column.
+    pub max_line_width: usize,
+    /// Number of spaces used to indent the arguments of a broken list.
+    pub indentation: usize,
+}
+
+impl Default for FormatterSettings {
+    fn default() -> FormatterSettings {
+        FormatterSettings {
+            max_line_width: 80,
+            indentation: 2,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FormatError {
+    /// The input is not valid Clarity source.
+    Parse(ParseError),
+    /// The formatted source would not parse to the same expressions as the input.
+    /// This indicates a bug in the formatter.
+    ChangedSource,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatError::Parse(e) => write!(f, "Failed to parse source: {}", e),
+            FormatError::ChangedSource => {
+                write!(f, "Formatting would change the parsed source")
+            }
+        }
+    }
+}
+
+impl error::Error for FormatError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            FormatError::Parse(e) => Some(e),
+            FormatError::ChangedSource => None,
+        }
+    }
+}
+
+impl From<ParseError> for FormatError {
+    fn from(err: ParseError) -> Self {
+        FormatError::Parse(err)
+    }
+}
+
+/// Format `source` with the default settings.
+pub fn format_source(source: &str) -> Result<String, FormatError> {
+    format_source_with_settings(source, &FormatterSettings::default())
+}
+
+/// Format `source`, checking that the result parses to the same expressions
+///  and comments as the input.
+pub fn format_source_with_settings(
+    source: &str,
+    settings: &FormatterSettings,
+) -> Result<String, FormatError> {
+    let expressions = parse(source)?;
+    let formatter = Formatter::new(source, settings);
+    let formatted = formatter.format(&expressions);
+
+    let reparsed = parse(&formatted).map_err(|_| FormatError::ChangedSource)?;
+    if !same_expressions(&expressions, &reparsed) || comments(&expressions) != comments(&reparsed) {
+        return Err(FormatError::ChangedSource);
+    }
+    Ok(formatted)
+}
+
+/// Returns true if `source` is already formatted with the default settings.
+pub fn is_formatted(source: &str) -> Result<bool, FormatError> {
+    Ok(format_source(source)? == source)
+}
+
+/// How many arguments of a known form stay on the same line as the function
+///  name when the form is broken across lines.
+fn header_arguments(name: &str) -> Option<usize> {
+    let count = match name {
+        "begin" | "as-contract" | "try!" | "unwrap-panic" | "unwrap-err-panic" => 0,
+        "define-public"
+        | "define-private"
+        | "define-read-only"
+        | "define-constant"
+        | "define-map"
+        | "define-non-fungible-token"
+        | "define-trait"
+        | "let"
+        | "if"
+        | "match"
+        | "asserts!"
+        | "unwrap!"
+        | "unwrap-err!"
+        | "default-to"
+        | "at-block"
+        | "map"
+        | "filter"
+        | "fold" => 1,
+        "define-data-var" | "contract-call?" => 2,
+        _ => return None,
+    };
+    Some(count)
+}
+
+struct Formatter<'a> {
+    source_lines: Vec<Vec<char>>,
+    settings: &'a FormatterSettings,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(source: &str, settings: &'a FormatterSettings) -> Formatter<'a> {
+        Formatter {
+            source_lines: source
+                .split('\n')
+                .map(|line| line.chars().collect())
+                .collect(),
+            settings,
+        }
+    }
+
+    fn format(&self, expressions: &[PreSymbolicExpression]) -> String {
+        let mut out = String::new();
+        let ends_with_comment = self.layout(&mut out, expressions, 0, None, true);
+        if !out.is_empty() || ends_with_comment {
+            out.push('\n');
+        }
+        out
+    }
+
+    /// The source text of a literal or name, copied verbatim.
+    fn source_text(&self, expr: &PreSymbolicExpression) -> String {
+        let span = &expr.span;
+        let mut text = String::new();
+        for line in span.start_line..=span.end_line {
+            let chars = match self.source_lines.get(line as usize - 1) {
+                Some(chars) => chars,
+                None => break,
+            };
+            let start = if line == span.start_line {
+                span.start_column as usize - 1
+            } else {
+                text.push('\n');
+                0
+            };
+            let end = if line == span.end_line {
+                (span.end_column as usize).min(chars.len())
+            } else {
+                chars.len()
+            };
+            text.extend(chars[start.min(end)..end].iter());
+        }
+        text
+    }
+
+    fn comment_text(comment: &str) -> String {
+        if comment.is_empty() || comment.starts_with(';') {
+            format!(";;{}", comment)
+        } else {
+            format!(";; {}", comment)
+        }
+    }
+
+    /// Render `expr` on a single line, if it contains no comments or line breaks.
+    fn flat(&self, expr: &PreSymbolicExpression) -> Option<String> {
+        match &expr.pre_expr {
+            PreSymbolicExpressionType::Comment(_) => None,
+            PreSymbolicExpressionType::List(children) => {
+                let children = children
+                    .iter()
+                    .map(|child| self.flat(child))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(format!("({})", children.join(" ")))
+            }
+            PreSymbolicExpressionType::Tuple(children) => {
+                let children = children
+                    .iter()
+                    .map(|child| self.flat(child))
+                    .collect::<Option<Vec<_>>>()?;
+                let entries: Vec<_> = children.chunks(2).map(|entry| entry.join(": ")).collect();
+                Some(format!("{{ {} }}", entries.join(", ")))
+            }
+            _ => {
+                let text = self.source_text(expr);
+                if text.contains('\n') {
+                    None
+                } else {
+                    Some(text)
+                }
+            }
+        }
+    }
+
+    fn fits(&self, column: usize, text: &str) -> bool {
+        column + text.chars().count() <= self.settings.max_line_width
+    }
+
+    /// Render `expr` starting at `column`. Lines after the first are indented
+    ///  absolutely.
+    fn render(&self, expr: &PreSymbolicExpression, column: usize) -> String {
+        if let Some(flat) = self.flat(expr) {
+            if self.fits(column, &flat) {
+                return flat;
+            }
+        }
+        match &expr.pre_expr {
+            PreSymbolicExpressionType::List(children) => self.render_list(children, column),
+            PreSymbolicExpressionType::Tuple(children) => self.render_tuple(children, column),
+            PreSymbolicExpressionType::Comment(comment) => Self::comment_text(comment),
+            _ => self.source_text(expr),
+        }
+    }
+
+    fn render_list(&self, children: &[PreSymbolicExpression], column: usize) -> String {
+        let mut out = String::from("(");
+        let name = match children.first().map(|head| &head.pre_expr) {
+            Some(PreSymbolicExpressionType::Atom(name)) => name,
+            _ => {
+                // lists of lists (e.g., `let` bindings) are aligned under the first element
+                if self.layout(&mut out, children, column + 1, None, true) {
+                    out.push('\n');
+                    out.push_str(&" ".repeat(column));
+                }
+                out.push(')');
+                return out;
+            }
+        };
+
+        out.push_str(name);
+        let mut line_column = column + 1 + name.chars().count();
+        let mut last_line = children[0].span.end_line;
+        let args = &children[1..];
+
+        let first_comment = args
+            .iter()
+            .position(|arg| matches!(arg.pre_expr, PreSymbolicExpressionType::Comment(_)))
+            .unwrap_or(args.len());
+        let header = match header_arguments(name) {
+            Some(count) => count.min(first_comment),
+            None => {
+                // keep leading names and literals on the first line while they fit
+                let mut count = 0;
+                let mut header_column = line_column;
+                for arg in args[..first_comment].iter() {
+                    if matches!(
+                        arg.pre_expr,
+                        PreSymbolicExpressionType::List(_) | PreSymbolicExpressionType::Tuple(_)
+                    ) {
+                        break;
+                    }
+                    let text = self.source_text(arg);
+                    if text.contains('\n') || !self.fits(header_column + 1, &text) {
+                        break;
+                    }
+                    header_column += 1 + text.chars().count();
+                    count += 1;
+                }
+                count
+            }
+        };
+
+        for arg in args[..header].iter() {
+            out.push(' ');
+            let rendered = self.render(arg, line_column + 1);
+            line_column = match rendered.rfind('\n') {
+                Some(index) => rendered[index + 1..].chars().count(),
+                None => line_column + 1 + rendered.chars().count(),
+            };
+            out.push_str(&rendered);
+            last_line = arg.span.end_line;
+        }
+
+        let ends_with_comment = self.layout(
+            &mut out,
+            &args[header..],
+            column + self.settings.indentation,
+            Some(last_line),
+            false,
+        );
+        if ends_with_comment {
+            out.push('\n');
+            out.push_str(&" ".repeat(column));
+        }
+        out.push(')');
+        out
+    }
+
+    fn render_tuple(&self, children: &[PreSymbolicExpression], column: usize) -> String {
+        let entry_column = column + self.settings.indentation;
+        let indent = " ".repeat(entry_column);
+        let entry_count = children
+            .iter()
+            .filter(|child| !matches!(child.pre_expr, PreSymbolicExpressionType::Comment(_)))
+            .count()
+            / 2;
+
+        let mut out = String::from("{");
+        let mut last_line: Option<u32> = None;
+        let mut key: Option<String> = None;
+        let mut entries = 0;
+        for child in children.iter() {
+            if let PreSymbolicExpressionType::Comment(comment) = &child.pre_expr {
+                self.push_comment(&mut out, comment, child, last_line, entry_column);
+                last_line = Some(child.span.end_line);
+                continue;
+            }
+            match key.take() {
+                None => key = Some(self.source_text(child)),
+                Some(key) => {
+                    self.push_line_break(&mut out, child, last_line);
+                    out.push_str(&indent);
+                    out.push_str(&key);
+                    out.push_str(": ");
+                    let value_column = entry_column + key.chars().count() + 2;
+                    out.push_str(&self.render(child, value_column));
+                    entries += 1;
+                    if entries < entry_count {
+                        out.push(',');
+                    }
+                    last_line = Some(child.span.end_line);
+                }
+            }
+        }
+        out.push('\n');
+        out.push_str(&" ".repeat(column));
+        out.push('}');
+        out
+    }
+
+    /// Start a new line for `expr`, keeping a single blank line if the source had one.
+    fn push_line_break(
+        &self,
+        out: &mut String,
+        expr: &PreSymbolicExpression,
+        last_line: Option<u32>,
+    ) {
+        if let Some(last_line) = last_line {
+            if expr.span.start_line > last_line + 1 {
+                out.push('\n');
+            }
+        }
+        if !out.is_empty() {
+            out.push('\n');
+        }
+    }
+
+    /// Comments which followed an expression on the same source line stay there;
+    ///  all others get a line of their own.
+    fn push_comment(
+        &self,
+        out: &mut String,
+        comment: &str,
+        expr: &PreSymbolicExpression,
+        last_line: Option<u32>,
+        column: usize,
+    ) {
+        if last_line == Some(expr.span.start_line) && !out.is_empty() {
+            out.push(' ');
+        } else {
+            self.push_line_break(out, expr, last_line);
+            out.push_str(&" ".repeat(column));
+        }
+        out.push_str(&Self::comment_text(comment));
+    }
+
+    /// Lay out `exprs` one per line at `column`. If `inline` is set, the first
+    ///  expression continues the current line. Returns true if the last thing
+    ///  written was a comment, which must be followed by a line break.
+    fn layout(
+        &self,
+        out: &mut String,
+        exprs: &[PreSymbolicExpression],
+        column: usize,
+        mut last_line: Option<u32>,
+        mut inline: bool,
+    ) -> bool {
+        let mut ends_with_comment = false;
+        for expr in exprs.iter() {
+            if let PreSymbolicExpressionType::Comment(comment) = &expr.pre_expr {
+                self.push_comment(out, comment, expr, last_line, column);
+                ends_with_comment = true;
+            } else {
+                if !inline {
+                    self.push_line_break(out, expr, last_line);
+                    out.push_str(&" ".repeat(column));
+                }
+                out.push_str(&self.render(expr, column));
+                ends_with_comment = false;
+            }
+            inline = false;
+            last_line = Some(expr.span.end_line);
+        }
+        ends_with_comment
+    }
+}
+
+/// Compare two parses, ignoring source locations and comments.
+fn same_expressions(a: &[PreSymbolicExpression], b: &[PreSymbolicExpression]) -> bool {
+    let a: Vec<_> = a.iter().filter(|expr| !is_comment(expr)).collect();
+    let b: Vec<_> = b.iter().filter(|expr| !is_comment(expr)).collect();
+    a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .all(|(a, b)| match (&a.pre_expr, &b.pre_expr) {
+                (PreSymbolicExpressionType::List(a), PreSymbolicExpressionType::List(b))
+                | (PreSymbolicExpressionType::Tuple(a), PreSymbolicExpressionType::Tuple(b)) => {
+                    same_expressions(a, b)
+                }
+                (a, b) => a == b,
+            })
+}
+
+fn is_comment(expr: &PreSymbolicExpression) -> bool {
+    matches!(expr.pre_expr, PreSymbolicExpressionType::Comment(_))
+}
+
+/// All comments, in source order.
+fn comments(exprs: &[PreSymbolicExpression]) -> Vec<String> {
+    let mut out = vec![];
+    for expr in exprs.iter() {
+        match &expr.pre_expr {
+            PreSymbolicExpressionType::Comment(comment) => out.push(comment.clone()),
+            PreSymbolicExpressionType::List(children)
+            | PreSymbolicExpressionType::Tuple(children) => out.extend(comments(children)),
+            _ => {}
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_short_forms_stay_flat() {
+        let source = "(define-data-var   counter uint\n   u0)\n(define-read-only (get-counter) (var-get counter))";
+        assert_eq!(
+            format_source(source).unwrap(),
+            "(define-data-var counter uint u0)\n(define-read-only (get-counter) (var-get counter))\n"
+        );
+    }
+
+    #[test]
+    fn test_format_breaks_long_forms() {
+        let source = "(define-public (deposit (amount uint)) (begin (map-set deposits tx-sender (+ amount (default-to u0 (map-get? deposits tx-sender)))) (let ((total (var-get total-deposits)) (next (+ total amount))) (var-set total-deposits next) (ok next))))";
+        let expected = "(define-public (deposit (amount uint))
+  (begin
+    (map-set deposits tx-sender
+      (+ amount (default-to u0 (map-get? deposits tx-sender))))
+    (let ((total (var-get total-deposits)) (next (+ total amount)))
+      (var-set total-deposits next)
+      (ok next))))
+";
+        assert_eq!(format_source(source).unwrap(), expected);
+        assert!(is_formatted(expected).unwrap());
+
+        // bindings which don't fit on one line are aligned under each other
+        let source = "(let ((first-binding (some-long-function-name u1 u2 u3)) (second (+ first-binding u1))) second)";
+        let expected = "(let ((first-binding (some-long-function-name u1 u2 u3))
+      (second (+ first-binding u1)))
+  second)
+";
+        assert_eq!(format_source(source).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_format_tuples() {
+        let settings = FormatterSettings {
+            max_line_width: 30,
+            indentation: 2,
+        };
+        let source = "(ok {a:1,b : u2})\n(print {name: \"a long string value\", count: u100})";
+        let expected = "(ok { a: 1, b: u2 })
+(print
+  {
+    name: \"a long string value\",
+    count: u100
+  })
+";
+        assert_eq!(
+            format_source_with_settings(source, &settings).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_format_preserves_comments_and_blank_lines() {
+        let source = ";; header comment
+;;; section
+
+(define-constant owner tx-sender) ;; the deployer
+
+
+(define-private (check (x int)) ;; trailing
+  (begin
+    ;; on its own line
+    (asserts! (> x 0) (err u1))
+    (ok x) ;; last
+  ))
+";
+        let expected = ";; header comment
+;;; section
+
+(define-constant owner tx-sender) ;; the deployer
+
+(define-private (check (x int)) ;; trailing
+  (begin
+    ;; on its own line
+    (asserts! (> x 0) (err u1))
+    (ok x) ;; last
+  ))
+";
+        let formatted = format_source(source).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format_source(&formatted).unwrap(), formatted);
+    }
+
+    #[test]
+    fn test_format_keeps_literals_verbatim() {
+        let source = "(list u\"caf\\u{e9}\"  \"tab\\t\" 0x00ff\n 'SP000000000000000000002Q6VF78 .c.trait -1)";
+        assert_eq!(
+            format_source(source).unwrap(),
+            "(list u\"caf\\u{e9}\" \"tab\\t\" 0x00ff 'SP000000000000000000002Q6VF78 .c.trait -1)\n"
+        );
+    }
+
+    #[test]
+    fn test_format_rejects_invalid_source() {
+        match format_source("(define-constant x (+ 1 2)") {
+            Err(FormatError::Parse(_)) => {}
+            result => panic!("expected a parse error, got {:?}", result),
+        }
+    }
+}
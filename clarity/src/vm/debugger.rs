@@ -0,0 +1,681 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2022 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! An interactive, line-oriented debugger for Clarity programs.  It is an `EvalHook`, so it sees
+//! every expression before and after it is evaluated, and pauses evaluation to read commands
+//! whenever a breakpoint, watchpoint or step completes.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufRead, Write};
+
+use crate::vm::ast::{build_ast_with_rules, ASTRules};
+use crate::vm::contexts::{Environment, LocalContext};
+use crate::vm::errors::Error;
+use crate::vm::types::QualifiedContractIdentifier;
+use crate::vm::{eval, EvalHook, ExecutionResult, SymbolicExpression, Value};
+
+const HELP: &str = "Commands:
+  c, continue              run until the next breakpoint or watchpoint
+  s, step                  step into the next expression, including across contract-call?
+  n, next                  step over the current expression
+  f, finish                run until the current function returns
+  b, break [CONTRACT:]LINE pause before evaluating LINE of the contract (or of any contract)
+  w, watch NAME            pause after the data-var or map NAME is written
+  d, delete ID             delete a breakpoint or watchpoint
+  i, info                  list breakpoints and watchpoints
+  bt, backtrace            show the stack of user-defined functions being evaluated
+  l, locals                show the local variable bindings
+  vars                     show the contract's data-vars
+  m, map NAME KEY          show the entry of the map NAME at KEY
+  p, print EXPR            evaluate EXPR in the current context
+  q, quit                  stop debugging and run the program to completion
+  h, help                  show this message";
+
+/// Native functions that write to a data-var or map, whose first argument names it
+const WRITE_FUNCTIONS: [&str; 4] = ["var-set", "map-set", "map-insert", "map-delete"];
+
+enum StepMode {
+    /// Only pause at breakpoints and watchpoints
+    Continue,
+    /// Pause before the next expression
+    StepIn,
+    /// Pause before the next expression at or above this evaluation depth
+    StepOver(usize),
+    /// Pause before the next expression with fewer than this many user functions on the stack
+    StepOut(usize),
+}
+
+struct Breakpoint {
+    id: usize,
+    contract: Option<QualifiedContractIdentifier>,
+    line: u32,
+}
+
+struct Watchpoint {
+    id: usize,
+    name: String,
+}
+
+/// A write to a watched data-var or map that is being evaluated
+struct PendingWrite {
+    watchpoint_id: usize,
+    eval_depth: usize,
+    function: String,
+    name: String,
+    /// Value of the data-var before it was written
+    old_value: Option<Value>,
+}
+
+pub struct Debugger<R: BufRead, W: Write> {
+    input: R,
+    output: W,
+    breakpoints: Vec<Breakpoint>,
+    watchpoints: Vec<Watchpoint>,
+    next_id: usize,
+    step: StepMode,
+    /// Once detached, the debugger no longer pauses
+    detached: bool,
+    /// Number of expressions being evaluated, nested in one another
+    eval_depth: usize,
+    /// Last source line that started being evaluated, so that breakpoints trigger once each time
+    /// evaluation reaches their line rather than once for every expression on it
+    last_line: Option<(QualifiedContractIdentifier, u32)>,
+    pending_writes: Vec<PendingWrite>,
+    sources: HashMap<QualifiedContractIdentifier, Vec<String>>,
+}
+
+#[cfg(feature = "developer-mode")]
+fn expr_line(expr: &SymbolicExpression) -> u32 {
+    expr.span.start_line
+}
+
+#[cfg(not(feature = "developer-mode"))]
+fn expr_line(_expr: &SymbolicExpression) -> u32 {
+    0
+}
+
+/// Names of the user-defined functions being evaluated, outermost first
+fn user_function_stack(env: &Environment) -> Vec<String> {
+    env.call_stack
+        .make_stack_trace()
+        .iter()
+        .map(|function| function.to_string())
+        .filter(|function| !function.starts_with("_native_:"))
+        .collect()
+}
+
+/// If `expr` writes to a data-var or map, return the write function and the name written to
+fn match_write(expr: &SymbolicExpression) -> Option<(&str, &str)> {
+    let list = expr.match_list()?;
+    let function = list.first()?.match_atom()?;
+    let name = list.get(1)?.match_atom()?;
+    if WRITE_FUNCTIONS.contains(&function.as_str()) {
+        Some((function.as_str(), name.as_str()))
+    } else {
+        None
+    }
+}
+
+impl<R: BufRead, W: Write> Debugger<R, W> {
+    /// Create a debugger that reads commands from `input` and writes to `output`.  It pauses
+    /// before the first expression is evaluated, so that breakpoints can be set.
+    pub fn new(input: R, output: W) -> Debugger<R, W> {
+        Debugger {
+            input,
+            output,
+            breakpoints: vec![],
+            watchpoints: vec![],
+            next_id: 1,
+            step: StepMode::StepIn,
+            detached: false,
+            eval_depth: 0,
+            last_line: None,
+            pending_writes: vec![],
+            sources: HashMap::new(),
+        }
+    }
+
+    /// Register a contract's source code, for showing where evaluation is paused.  Sources of
+    /// contracts that aren't registered are loaded from the Clarity database as needed.
+    pub fn add_source(&mut self, contract_identifier: QualifiedContractIdentifier, source: &str) {
+        self.sources.insert(
+            contract_identifier,
+            source.lines().map(|line| line.to_string()).collect(),
+        );
+    }
+
+    pub fn add_breakpoint(
+        &mut self,
+        contract: Option<QualifiedContractIdentifier>,
+        line: u32,
+    ) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.breakpoints.push(Breakpoint { id, contract, line });
+        id
+    }
+
+    pub fn add_watchpoint(&mut self, name: &str) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.watchpoints.push(Watchpoint {
+            id,
+            name: name.to_string(),
+        });
+        id
+    }
+
+    pub fn into_output(self) -> W {
+        self.output
+    }
+
+    fn should_pause(&self, env: &Environment) -> bool {
+        match self.step {
+            StepMode::Continue => false,
+            StepMode::StepIn => true,
+            StepMode::StepOver(depth) => self.eval_depth <= depth,
+            StepMode::StepOut(num_frames) => user_function_stack(env).len() < num_frames,
+        }
+    }
+
+    fn breakpoint_at(&self, contract: &QualifiedContractIdentifier, line: u32) -> Option<usize> {
+        if line == 0 {
+            return None;
+        }
+        if let Some((ref last_contract, last_line)) = self.last_line {
+            if last_contract == contract && last_line == line {
+                return None;
+            }
+        }
+        self.breakpoints
+            .iter()
+            .find(|bp| {
+                bp.line == line
+                    && bp
+                        .contract
+                        .as_ref()
+                        .map(|bp_contract| bp_contract == contract)
+                        .unwrap_or(true)
+            })
+            .map(|bp| bp.id)
+    }
+
+    /// Show the source line (or, failing that, the expression) where evaluation is paused
+    fn show_location(
+        &mut self,
+        env: &mut Environment,
+        expr: &SymbolicExpression,
+    ) -> io::Result<()> {
+        let contract = env.contract_context.contract_identifier.clone();
+        let line = expr_line(expr);
+        if !self.sources.contains_key(&contract) {
+            if let Some(source) = env.global_context.database.get_contract_src(&contract) {
+                self.add_source(contract.clone(), &source);
+            }
+        }
+        let source_line = self
+            .sources
+            .get(&contract)
+            .and_then(|lines| lines.get((line as usize).wrapping_sub(1)));
+        match source_line {
+            Some(source_line) => writeln!(self.output, "{}:{} | {}", contract, line, source_line),
+            None => writeln!(self.output, "{} | {}", contract, expr),
+        }
+    }
+
+    fn show_locals(&mut self, context: &LocalContext) -> io::Result<()> {
+        let mut bindings = BTreeMap::new();
+        let mut cur_context = Some(context);
+        while let Some(local_context) = cur_context {
+            for (name, value) in local_context.variables.iter() {
+                // inner bindings shadow outer ones
+                bindings.entry(name.to_string()).or_insert(value);
+            }
+            cur_context = local_context.parent;
+        }
+        if bindings.is_empty() {
+            writeln!(self.output, "No local bindings")?;
+        }
+        for (name, value) in bindings.iter() {
+            writeln!(self.output, "{} = {}", name, value)?;
+        }
+        Ok(())
+    }
+
+    fn show_data_vars(&mut self, env: &mut Environment) -> io::Result<()> {
+        let contract = env.contract_context.contract_identifier.clone();
+        let mut names: Vec<_> = env.contract_context.meta_data_var.keys().collect();
+        names.sort();
+        if names.is_empty() {
+            writeln!(self.output, "No data-vars")?;
+        }
+        for name in names.into_iter() {
+            match env
+                .global_context
+                .database
+                .lookup_variable_unknown_descriptor(&contract, name)
+            {
+                Ok(value) => writeln!(self.output, "{} = {}", name, value)?,
+                Err(e) => writeln!(self.output, "{}: {}", name, e)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse and evaluate a single expression in the context where evaluation is paused
+    fn eval_source(
+        &mut self,
+        source: &str,
+        env: &mut Environment,
+        context: &LocalContext,
+    ) -> Result<Value, String> {
+        let contract_identifier = env.contract_context.contract_identifier.clone();
+        let ast = build_ast_with_rules(
+            &contract_identifier,
+            source,
+            &mut (),
+            *env.contract_context.get_clarity_version(),
+            env.global_context.epoch_id,
+            ASTRules::PrecheckSize,
+        )
+        .map_err(|e| e.to_string())?;
+        match ast.expressions.as_slice() {
+            [expr] => eval(expr, env, context).map_err(|e| e.to_string()),
+            _ => Err("Expected exactly one expression".to_string()),
+        }
+    }
+
+    fn show_map_entry(
+        &mut self,
+        args: &str,
+        env: &mut Environment,
+        context: &LocalContext,
+    ) -> io::Result<()> {
+        let (name, key_source) = match args.split_once(' ') {
+            Some((name, key_source)) => (name, key_source.trim()),
+            None => return writeln!(self.output, "Usage: map NAME KEY"),
+        };
+        let key = match self.eval_source(key_source, env, context) {
+            Ok(key) => key,
+            Err(e) => return writeln!(self.output, "Failed to evaluate key: {}", e),
+        };
+        let contract = env.contract_context.contract_identifier.clone();
+        match env
+            .global_context
+            .database
+            .fetch_entry_unknown_descriptor(&contract, name, &key)
+        {
+            Ok(entry) => writeln!(self.output, "{}", entry),
+            Err(e) => writeln!(self.output, "{}", e),
+        }
+    }
+
+    fn show_breakpoints(&mut self) -> io::Result<()> {
+        if self.breakpoints.is_empty() && self.watchpoints.is_empty() {
+            writeln!(self.output, "No breakpoints or watchpoints")?;
+        }
+        for bp in self.breakpoints.iter() {
+            match bp.contract {
+                Some(ref contract) => writeln!(
+                    self.output,
+                    "Breakpoint {}: {}:{}",
+                    bp.id, contract, bp.line
+                )?,
+                None => writeln!(self.output, "Breakpoint {}: line {}", bp.id, bp.line)?,
+            }
+        }
+        for wp in self.watchpoints.iter() {
+            writeln!(self.output, "Watchpoint {}: {}", wp.id, wp.name)?;
+        }
+        Ok(())
+    }
+
+    fn add_breakpoint_command(&mut self, args: &str) -> io::Result<()> {
+        let (contract, line) = match args.rsplit_once(':') {
+            Some((contract, line)) => match QualifiedContractIdentifier::parse(contract) {
+                Ok(contract) => (Some(contract), line),
+                Err(e) => return writeln!(self.output, "Bad contract identifier: {}", e),
+            },
+            None => (None, args),
+        };
+        match line.parse::<u32>() {
+            Ok(line) if line > 0 => {
+                let id = self.add_breakpoint(contract, line);
+                writeln!(self.output, "Breakpoint {} set", id)
+            }
+            _ => writeln!(self.output, "Usage: break [CONTRACT:]LINE"),
+        }
+    }
+
+    fn delete_command(&mut self, args: &str) -> io::Result<()> {
+        let id = match args.parse::<usize>() {
+            Ok(id) => id,
+            Err(_) => return writeln!(self.output, "Usage: delete ID"),
+        };
+        let num_points = self.breakpoints.len() + self.watchpoints.len();
+        self.breakpoints.retain(|bp| bp.id != id);
+        self.watchpoints.retain(|wp| wp.id != id);
+        if self.breakpoints.len() + self.watchpoints.len() == num_points {
+            writeln!(self.output, "No breakpoint or watchpoint {}", id)
+        } else {
+            writeln!(self.output, "Deleted {}", id)
+        }
+    }
+
+    /// Read and run commands until one of them resumes evaluation.
+    fn prompt(
+        &mut self,
+        env: &mut Environment,
+        context: &LocalContext,
+        expr: &SymbolicExpression,
+    ) -> io::Result<()> {
+        self.show_location(env, expr)?;
+        loop {
+            write!(self.output, "(debug) ")?;
+            self.output.flush()?;
+
+            let mut command_line = String::new();
+            if self.input.read_line(&mut command_line)? == 0 {
+                // nobody left to take commands from
+                writeln!(self.output)?;
+                self.detached = true;
+                return Ok(());
+            }
+            let command_line = command_line.trim();
+            let (command, args) = match command_line.split_once(' ') {
+                Some((command, args)) => (command, args.trim()),
+                None => (command_line, ""),
+            };
+
+            match command {
+                "c" | "continue" => {
+                    self.step = StepMode::Continue;
+                    return Ok(());
+                }
+                "s" | "step" => {
+                    self.step = StepMode::StepIn;
+                    return Ok(());
+                }
+                "n" | "next" => {
+                    self.step = StepMode::StepOver(self.eval_depth);
+                    return Ok(());
+                }
+                "f" | "finish" => {
+                    self.step = StepMode::StepOut(user_function_stack(env).len());
+                    return Ok(());
+                }
+                "q" | "quit" => {
+                    self.detached = true;
+                    return Ok(());
+                }
+                "b" | "break" => self.add_breakpoint_command(args)?,
+                "w" | "watch" => {
+                    if args.is_empty() {
+                        writeln!(self.output, "Usage: watch NAME")?;
+                    } else {
+                        let id = self.add_watchpoint(args);
+                        writeln!(self.output, "Watchpoint {} set", id)?;
+                    }
+                }
+                "d" | "delete" => self.delete_command(args)?,
+                "i" | "info" => self.show_breakpoints()?,
+                "bt" | "backtrace" => {
+                    let stack = user_function_stack(env);
+                    if stack.is_empty() {
+                        writeln!(self.output, "Not in a function")?;
+                    }
+                    for (i, function) in stack.iter().rev().enumerate() {
+                        writeln!(self.output, "#{} {}", i, function)?;
+                    }
+                }
+                "l" | "locals" => self.show_locals(context)?,
+                "vars" => self.show_data_vars(env)?,
+                "m" | "map" => self.show_map_entry(args, env, context)?,
+                "p" | "print" => match self.eval_source(args, env, context) {
+                    Ok(value) => writeln!(self.output, "{}", value)?,
+                    Err(e) => writeln!(self.output, "Error: {}", e)?,
+                },
+                "h" | "help" => writeln!(self.output, "{}", HELP)?,
+                "" => {}
+                _ => writeln!(
+                    self.output,
+                    "Unknown command '{}'.  Type 'help' for a list of commands.",
+                    command
+                )?,
+            }
+        }
+    }
+
+    fn pause(&mut self, env: &mut Environment, context: &LocalContext, expr: &SymbolicExpression) {
+        if let Err(e) = self.prompt(env, context, expr) {
+            // can't talk to the user anymore, so let the program run
+            error!("Debugger I/O failed: {}", e);
+            self.detached = true;
+        }
+    }
+}
+
+impl<R: BufRead, W: Write> EvalHook for Debugger<R, W> {
+    fn will_begin_eval(
+        &mut self,
+        env: &mut Environment,
+        context: &LocalContext,
+        expr: &SymbolicExpression,
+    ) {
+        self.eval_depth += 1;
+        if self.detached {
+            return;
+        }
+
+        let contract = env.contract_context.contract_identifier.clone();
+        let line = expr_line(expr);
+        let breakpoint = self.breakpoint_at(&contract, line);
+        if line != 0 {
+            self.last_line = Some((contract.clone(), line));
+        }
+
+        if let Some((function, name)) = match_write(expr) {
+            if let Some(wp) = self.watchpoints.iter().find(|wp| wp.name == name) {
+                let old_value = if function == "var-set" {
+                    env.global_context
+                        .database
+                        .lookup_variable_unknown_descriptor(&contract, name)
+                        .ok()
+                } else {
+                    None
+                };
+                self.pending_writes.push(PendingWrite {
+                    watchpoint_id: wp.id,
+                    eval_depth: self.eval_depth,
+                    function: function.to_string(),
+                    name: name.to_string(),
+                    old_value,
+                });
+            }
+        }
+
+        if let Some(id) = breakpoint {
+            let _ = writeln!(self.output, "Breakpoint {} hit", id);
+            self.pause(env, context, expr);
+        } else if self.should_pause(env) {
+            self.pause(env, context, expr);
+        }
+    }
+
+    fn did_finish_eval(
+        &mut self,
+        env: &mut Environment,
+        context: &LocalContext,
+        expr: &SymbolicExpression,
+        res: &Result<Value, Error>,
+    ) {
+        let write = match self.pending_writes.last() {
+            Some(write) if write.eval_depth == self.eval_depth => self.pending_writes.pop(),
+            _ => None,
+        };
+        self.eval_depth = self.eval_depth.saturating_sub(1);
+
+        let write = match write {
+            Some(write) if !self.detached => write,
+            _ => return,
+        };
+        let message = match (res, write.old_value) {
+            (Err(e), _) => format!("{} {} failed: {}", write.function, write.name, e),
+            (Ok(_), Some(old_value)) => {
+                let contract = env.contract_context.contract_identifier.clone();
+                match env
+                    .global_context
+                    .database
+                    .lookup_variable_unknown_descriptor(&contract, &write.name)
+                {
+                    Ok(new_value) => format!(
+                        "{} {}: {} -> {}",
+                        write.function, write.name, old_value, new_value
+                    ),
+                    Err(_) => format!("{} {}: was {}", write.function, write.name, old_value),
+                }
+            }
+            (Ok(result), None) => format!("{} {} returned {}", write.function, write.name, result),
+        };
+        let _ = writeln!(
+            self.output,
+            "Watchpoint {}: {}",
+            write.watchpoint_id, message
+        );
+        self.pause(env, context, expr);
+    }
+
+    fn did_complete(&mut self, _result: Result<&mut ExecutionResult, String>) {}
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::vm::contexts::OwnedEnvironment;
+    use crate::vm::database::MemoryBackingStore;
+    use crate::vm::types::PrincipalData;
+    use stacks_common::types::StacksEpochId;
+
+    const CONTRACT: &str = "(define-data-var counter uint u0)
+(define-map balances principal uint)
+(define-private (bump (n uint))
+  (begin
+    (var-set counter (+ (var-get counter) n))
+    (map-set balances tx-sender (var-get counter))
+    (ok (var-get counter))))
+(define-public (bump-twice (n uint))
+  (begin
+    (try! (bump n))
+    (bump (* n u2))))
+";
+
+    /// Deploy the test contract and call `bump-twice`, with a debugger reading `commands`.
+    /// Returns what the debugger printed.
+    fn debug_session(commands: &str) -> String {
+        let contract_id = QualifiedContractIdentifier::local("counter").unwrap();
+        let sender = PrincipalData::parse("S1G2081040G2081040G2081040G208105NK8PE5").unwrap();
+        let mut marf = MemoryBackingStore::new();
+        let mut debugger = Debugger::new(Cursor::new(commands.as_bytes().to_vec()), vec![]);
+        debugger.add_source(contract_id.clone(), CONTRACT);
+
+        let mut owned_env = OwnedEnvironment::new(marf.as_clarity_db(), StacksEpochId::Epoch21);
+        owned_env
+            .initialize_contract(contract_id.clone(), CONTRACT, None, ASTRules::PrecheckSize)
+            .unwrap();
+
+        let mut owned_env = OwnedEnvironment::new(marf.as_clarity_db(), StacksEpochId::Epoch21);
+        owned_env.add_eval_hook(&mut debugger);
+        let (result, ..) = owned_env
+            .execute_transaction(
+                sender,
+                None,
+                contract_id,
+                "bump-twice",
+                &[SymbolicExpression::atom_value(Value::UInt(3))],
+            )
+            .unwrap();
+        assert_eq!(result, Value::okay(Value::UInt(9)).unwrap());
+        drop(owned_env);
+
+        String::from_utf8(debugger.into_output()).unwrap()
+    }
+
+    #[test]
+    fn test_breakpoints_and_inspection() {
+        let output =
+            debug_session("b 5\nc\nl\np (+ n u1)\nvars\nbt\nc\nl\nm balances tx-sender\nd 1\nc\n");
+        assert_eq!(
+            output,
+            "S1G2081040G2081040G2081040G208105NK8PE5.counter:9 |   (begin
+(debug) Breakpoint 1 set
+(debug) Breakpoint 1 hit
+S1G2081040G2081040G2081040G208105NK8PE5.counter:5 |     (var-set counter (+ (var-get counter) n))
+(debug) n = u3
+(debug) u4
+(debug) counter = u0
+(debug) #0 S1G2081040G2081040G2081040G208105NK8PE5.counter:bump
+#1 S1G2081040G2081040G2081040G208105NK8PE5.counter:bump-twice
+(debug) Breakpoint 1 hit
+S1G2081040G2081040G2081040G208105NK8PE5.counter:5 |     (var-set counter (+ (var-get counter) n))
+(debug) n = u6
+(debug) (some u3)
+(debug) Deleted 1
+(debug) "
+        );
+    }
+
+    #[test]
+    fn test_watchpoints() {
+        let output = debug_session("w counter\nc\nc\nq\n");
+        assert_eq!(
+            output,
+            "S1G2081040G2081040G2081040G208105NK8PE5.counter:9 |   (begin
+(debug) Watchpoint 1 set
+(debug) Watchpoint 1: var-set counter: u0 -> u3
+S1G2081040G2081040G2081040G208105NK8PE5.counter:5 |     (var-set counter (+ (var-get counter) n))
+(debug) Watchpoint 1: var-set counter: u3 -> u9
+S1G2081040G2081040G2081040G208105NK8PE5.counter:5 |     (var-set counter (+ (var-get counter) n))
+(debug) "
+        );
+
+        let output = debug_session("w balances\nc\nq\n");
+        assert!(output.contains("(debug) Watchpoint 1: map-set balances returned true\n"));
+    }
+
+    #[test]
+    fn test_stepping() {
+        // step into `bump`, then out of it again
+        let output = debug_session("b 10\nc\ns\ns\ns\nbt\nf\nbt\nq\n");
+        assert!(output.ends_with(
+            "(debug) S1G2081040G2081040G2081040G208105NK8PE5.counter:4 |   (begin
+(debug) #0 S1G2081040G2081040G2081040G208105NK8PE5.counter:bump
+#1 S1G2081040G2081040G2081040G208105NK8PE5.counter:bump-twice
+(debug) S1G2081040G2081040G2081040G208105NK8PE5.counter:11 |     (bump (* n u2))))
+(debug) #0 S1G2081040G2081040G2081040G208105NK8PE5.counter:bump-twice
+(debug) "
+        ));
+
+        // step over the first call to `bump`
+        let output = debug_session("b 10\nc\nn\nq\n");
+        assert!(output.ends_with(
+            "S1G2081040G2081040G2081040G208105NK8PE5.counter:10 |     (try! (bump n))
+(debug) S1G2081040G2081040G2081040G208105NK8PE5.counter:11 |     (bump (* n u2))))
+(debug) "
+        ));
+    }
+}
@@ -0,0 +1,388 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2022 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A profiler that attributes execution costs to the functions that incur them.
+//!
+//! Every time evaluation begins or finishes an expression, the cost spent since the previous
+//! time is charged to the stack of functions being evaluated in between.  The call stack already
+//! has user-defined functions (including those called with `contract-call?`) and special forms
+//! such as `let` and `if` on it, but a native function is only pushed after its arguments are
+//! evaluated, and popped before evaluation of its expression finishes.  So the cost spent since
+//! the last argument of a function application was evaluated is charged to that function.
+//!
+//! Call stacks are only kept with the `developer-mode` feature, so without it, all costs are
+//! charged to the innermost function application.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::vm::callables::FunctionIdentifier;
+use crate::vm::contexts::{Environment, LocalContext};
+use crate::vm::costs::ExecutionCost;
+use crate::vm::errors::Error;
+use crate::vm::functions::{lookup_reserved_functions, NativeFunctions};
+use crate::vm::{ClarityVersion, EvalHook, ExecutionResult, SymbolicExpression, Value};
+
+/// Name of the bottom-most frame of costs incurred outside of any function, e.g. while
+/// evaluating the top-level expressions of a contract that is being deployed
+const TOP_LEVEL_FRAME: &str = "<top-level>";
+
+/// The cost dimension to write folded stacks for
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfileMetric {
+    Runtime,
+    ReadCount,
+    ReadLength,
+    WriteCount,
+    WriteLength,
+}
+
+impl ProfileMetric {
+    fn of(&self, cost: &ExecutionCost) -> u64 {
+        match *self {
+            ProfileMetric::Runtime => cost.runtime,
+            ProfileMetric::ReadCount => cost.read_count,
+            ProfileMetric::ReadLength => cost.read_length,
+            ProfileMetric::WriteCount => cost.write_count,
+            ProfileMetric::WriteLength => cost.write_length,
+        }
+    }
+}
+
+impl fmt::Display for ProfileMetric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            ProfileMetric::Runtime => "runtime",
+            ProfileMetric::ReadCount => "read_count",
+            ProfileMetric::ReadLength => "read_length",
+            ProfileMetric::WriteCount => "write_count",
+            ProfileMetric::WriteLength => "write_length",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for ProfileMetric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ProfileMetric, String> {
+        match s {
+            "runtime" => Ok(ProfileMetric::Runtime),
+            "read_count" => Ok(ProfileMetric::ReadCount),
+            "read_length" => Ok(ProfileMetric::ReadLength),
+            "write_count" => Ok(ProfileMetric::WriteCount),
+            "write_length" => Ok(ProfileMetric::WriteLength),
+            _ => Err(format!("Unknown cost metric: {}", s)),
+        }
+    }
+}
+
+/// Costs attributed to one function
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionProfile {
+    /// User-defined functions are named `<contract>:<function>`, and native ones by their name
+    pub name: String,
+    pub calls: u64,
+    /// Cost of evaluating the function, including the functions it calls
+    pub total_cost: ExecutionCost,
+    /// Cost of evaluating the function, not counting the functions it calls
+    pub self_cost: ExecutionCost,
+}
+
+pub struct CostProfiler {
+    /// Clarity names of the native functions, by their identifiers on the call stack
+    native_names: HashMap<String, String>,
+    last_total: ExecutionCost,
+    last_stack: Vec<String>,
+    functions: HashMap<String, FunctionProfile>,
+    /// Self cost of each distinct stack of functions
+    stacks: BTreeMap<Vec<String>, ExecutionCost>,
+}
+
+fn add_cost(total: &mut ExecutionCost, cost: &ExecutionCost) {
+    if total.add(cost).is_err() {
+        *total = ExecutionCost::max_value();
+    }
+}
+
+impl CostProfiler {
+    pub fn new() -> CostProfiler {
+        let native_names = NativeFunctions::ALL_NAMES
+            .iter()
+            .filter_map(|name| {
+                let function = lookup_reserved_functions(name, &ClarityVersion::latest())?;
+                Some((function.get_identifier().to_string(), name.to_string()))
+            })
+            .collect();
+        CostProfiler {
+            native_names,
+            last_total: ExecutionCost::zero(),
+            last_stack: vec![],
+            functions: HashMap::new(),
+            stacks: BTreeMap::new(),
+        }
+    }
+
+    /// Charge whatever has been spent since the last time to `stack`.
+    fn charge(&mut self, env: &Environment, stack: Vec<String>) {
+        let total = env.global_context.cost_track.get_total();
+        let mut cost = total.clone();
+        if cost.sub(&self.last_total).is_err() {
+            // costs were reset
+            cost = ExecutionCost::zero();
+        }
+        self.last_total = total;
+
+        // a frame was entered if it wasn't on the stack last time
+        let num_common = stack
+            .iter()
+            .zip(self.last_stack.iter())
+            .take_while(|(frame, last_frame)| frame == last_frame)
+            .count();
+        for (i, frame) in stack.iter().enumerate() {
+            let profile = self
+                .functions
+                .entry(frame.clone())
+                .or_insert_with(|| FunctionProfile {
+                    name: frame.clone(),
+                    calls: 0,
+                    total_cost: ExecutionCost::zero(),
+                    self_cost: ExecutionCost::zero(),
+                });
+            if i >= num_common {
+                profile.calls += 1;
+            }
+            // don't count the cost twice if a function is on the stack more than once
+            if !stack[..i].contains(frame) {
+                add_cost(&mut profile.total_cost, &cost);
+            }
+            if i + 1 == stack.len() {
+                add_cost(&mut profile.self_cost, &cost);
+            }
+        }
+
+        if cost != ExecutionCost::zero() {
+            let stack_cost = self
+                .stacks
+                .entry(stack.clone())
+                .or_insert_with(ExecutionCost::zero);
+            add_cost(stack_cost, &cost);
+        }
+        self.last_stack = stack;
+    }
+
+    /// User-defined functions are named `<contract>:<function>`, and native ones by their name
+    fn frame_name(&self, function: &FunctionIdentifier) -> String {
+        let identifier = function.to_string();
+        match self.native_names.get(&identifier) {
+            Some(native_name) => native_name.clone(),
+            None => identifier,
+        }
+    }
+
+    fn current_stack(&self, env: &Environment) -> Vec<String> {
+        let mut stack = vec![TOP_LEVEL_FRAME.to_string()];
+        for function in env.call_stack.make_stack_trace().iter() {
+            stack.push(self.frame_name(function));
+        }
+        stack
+    }
+
+    /// Profiles of every function that was evaluated, most expensive (by total runtime) first
+    pub fn function_profiles(&self) -> Vec<FunctionProfile> {
+        let mut profiles: Vec<_> = self.functions.values().cloned().collect();
+        profiles.sort_by(|a, b| {
+            b.total_cost
+                .runtime
+                .cmp(&a.total_cost.runtime)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        profiles
+    }
+
+    /// Write a table of the cost of each function, most expensive first.  All costs but the
+    /// self runtime include the functions called.
+    pub fn write_table<W: Write>(&self, fd: &mut W) -> io::Result<()> {
+        let profiles = self.function_profiles();
+        let name_width = profiles
+            .iter()
+            .map(|profile| profile.name.len())
+            .max()
+            .unwrap_or(0)
+            .max("Function".len());
+        writeln!(
+            fd,
+            "{:<name_width$} {:>8} {:>14} {:>14} {:>12} {:>12} {:>12} {:>12}",
+            "Function",
+            "Calls",
+            "Runtime",
+            "Self runtime",
+            "Read count",
+            "Read length",
+            "Write count",
+            "Write length",
+            name_width = name_width
+        )?;
+        for profile in profiles.iter() {
+            writeln!(
+                fd,
+                "{:<name_width$} {:>8} {:>14} {:>14} {:>12} {:>12} {:>12} {:>12}",
+                profile.name,
+                profile.calls,
+                profile.total_cost.runtime,
+                profile.self_cost.runtime,
+                profile.total_cost.read_count,
+                profile.total_cost.read_length,
+                profile.total_cost.write_count,
+                profile.total_cost.write_length,
+                name_width = name_width
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write one line per stack of functions with the given cost, in the "folded" format that
+    /// flamegraph tools take: the frames, separated by semicolons, then the cost.
+    pub fn write_folded<W: Write>(&self, fd: &mut W, metric: ProfileMetric) -> io::Result<()> {
+        for (stack, cost) in self.stacks.iter() {
+            let value = metric.of(cost);
+            if value > 0 {
+                writeln!(fd, "{} {}", stack.join(";"), value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl EvalHook for CostProfiler {
+    fn will_begin_eval(
+        &mut self,
+        env: &mut Environment,
+        _context: &LocalContext,
+        _expr: &SymbolicExpression,
+    ) {
+        let stack = self.current_stack(env);
+        self.charge(env, stack);
+    }
+
+    fn did_finish_eval(
+        &mut self,
+        env: &mut Environment,
+        _context: &LocalContext,
+        expr: &SymbolicExpression,
+        _res: &Result<Value, Error>,
+    ) {
+        let mut stack = self.current_stack(env);
+        let function_name = expr
+            .match_list()
+            .and_then(|list| list.first())
+            .and_then(|function| function.match_atom());
+        if let Some(function_name) = function_name {
+            let frame = match env.contract_context.lookup_function(function_name) {
+                Some(function) => self.frame_name(&function.get_identifier()),
+                None => function_name.to_string(),
+            };
+            stack.push(frame);
+        }
+        self.charge(env, stack);
+    }
+
+    fn did_complete(&mut self, _result: Result<&mut ExecutionResult, String>) {}
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vm::ast::ASTRules;
+    use crate::vm::contexts::OwnedEnvironment;
+    use crate::vm::database::MemoryBackingStore;
+    use crate::vm::types::{PrincipalData, QualifiedContractIdentifier};
+    use stacks_common::types::StacksEpochId;
+
+    #[test]
+    fn test_profile_contract_call() {
+        let callee_id = QualifiedContractIdentifier::local("callee").unwrap();
+        let caller_id = QualifiedContractIdentifier::local("caller").unwrap();
+        let sender = PrincipalData::parse("S1G2081040G2081040G2081040G208105NK8PE5").unwrap();
+        let mut marf = MemoryBackingStore::new();
+        let mut profiler = CostProfiler::new();
+
+        let mut owned_env = OwnedEnvironment::new(marf.as_clarity_db(), StacksEpochId::Epoch21);
+        owned_env
+            .initialize_contract(
+                callee_id,
+                "(define-data-var total uint u0)
+                 (define-public (add (n uint))
+                   (begin (var-set total (+ (var-get total) n)) (ok (var-get total))))",
+                None,
+                ASTRules::PrecheckSize,
+            )
+            .unwrap();
+        owned_env
+            .initialize_contract(
+                caller_id.clone(),
+                "(define-private (double (n uint)) (* n u2))
+                 (define-public (add-twice (n uint))
+                   (begin
+                     (try! (contract-call? .callee add (double n)))
+                     (contract-call? .callee add n)))",
+                None,
+                ASTRules::PrecheckSize,
+            )
+            .unwrap();
+
+        let mut owned_env = OwnedEnvironment::new(marf.as_clarity_db(), StacksEpochId::Epoch21);
+        owned_env.add_eval_hook(&mut profiler);
+        owned_env
+            .execute_transaction(
+                sender,
+                None,
+                caller_id,
+                "add-twice",
+                &[SymbolicExpression::atom_value(Value::UInt(3))],
+            )
+            .unwrap();
+        drop(owned_env);
+
+        let profiles: HashMap<_, _> = profiler
+            .function_profiles()
+            .into_iter()
+            .map(|profile| (profile.name.clone(), profile))
+            .collect();
+
+        // the call stack is followed across contract-call?
+        assert_eq!(profiles[TOP_LEVEL_FRAME].calls, 1);
+        let add_twice = &profiles["S1G2081040G2081040G2081040G208105NK8PE5.caller:add-twice"];
+        assert_eq!(add_twice.calls, 1);
+        let add = &profiles["S1G2081040G2081040G2081040G208105NK8PE5.callee:add"];
+        assert_eq!(add.calls, 2);
+        assert_eq!(profiles["var-set"].calls, 2);
+        assert_eq!(profiles["contract-call?"].calls, 2);
+        assert_eq!(
+            profiles["S1G2081040G2081040G2081040G208105NK8PE5.caller:double"].calls,
+            1
+        );
+        assert_eq!(profiles["*"].calls, 1);
+
+        let mut table = vec![];
+        profiler.write_table(&mut table).unwrap();
+        let table = String::from_utf8(table).unwrap();
+        assert!(table.starts_with("Function "));
+        assert_eq!(table.lines().count(), profiles.len() + 1);
+    }
+}
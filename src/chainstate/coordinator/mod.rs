@@ -673,6 +673,37 @@ fn dispatcher_announce_burn_ops<T: BlockEventDispatcher>(
     );
 }
 
+/// Announce an already-processed burnchain block to `dispatcher` again, with the same payouts and
+/// reward slot holders that were announced when its sortition was first evaluated.
+/// `parent_snapshot` is the sortition that precedes the burnchain block.
+pub fn replay_burn_block<T: BlockEventDispatcher, U: RewardSetProvider>(
+    dispatcher: &T,
+    burnchain_block: &BurnchainBlockData,
+    parent_snapshot: &BlockSnapshot,
+    chain_state: &mut StacksChainState,
+    sort_db: &mut SortitionDB,
+    burnchain: &Burnchain,
+    provider: &U,
+    always_use_affirmation_maps: bool,
+) -> Result<(), Error> {
+    let reward_set_info = get_next_recipients(
+        parent_snapshot,
+        chain_state,
+        sort_db,
+        burnchain,
+        provider,
+        always_use_affirmation_maps,
+    )?;
+    let paid_rewards = calculate_paid_rewards(&burnchain_block.ops);
+    dispatcher_announce_burn_ops(
+        dispatcher,
+        &burnchain_block.header,
+        paid_rewards,
+        reward_set_info,
+    );
+    Ok(())
+}
+
 /// Forget that all Stacks blocks that were mined on descendants of `burn_header` are orphaned.
 /// They may be valid again, after a PoX reorg.
 fn forget_orphan_stacks_blocks(
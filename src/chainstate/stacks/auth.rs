@@ -984,6 +984,15 @@ impl TransactionAuth {
         }
     }
 
+    /// Does the origin or the sponsor (if present) have no signatures at all?
+    pub fn is_unsigned(&self) -> bool {
+        self.origin().num_signatures() == 0
+            || self
+                .sponsor()
+                .map(|sponsor| sponsor.num_signatures() == 0)
+                .unwrap_or(false)
+    }
+
     /// When beginning to sign a sponsored transaction, the origin account will not commit to any
     /// information about the sponsor (only that it is sponsored).  It does so by using sentinel
     /// sponsored account information.
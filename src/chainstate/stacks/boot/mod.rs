@@ -1757,6 +1757,100 @@ pub mod test {
         }
     }
 
+    #[test]
+    fn test_replay_block_events() {
+        let mut burnchain = Burnchain::default_unittest(
+            0,
+            &BurnchainHeaderHash::from_hex(BITCOIN_REGTEST_FIRST_BLOCK_HASH).unwrap(),
+        );
+        burnchain.pox_constants.reward_cycle_length = 5;
+        burnchain.pox_constants.prepare_length = 2;
+        burnchain.pox_constants.anchor_threshold = 1;
+
+        let observer = TestEventObserver::new();
+        let (mut peer, keys) = instantiate_pox_peer_with_epoch(
+            &burnchain,
+            function_name!(),
+            6028,
+            None,
+            Some(&observer),
+        );
+
+        let mut coinbase_nonce = 0;
+        for nonce in 0..5 {
+            let transfer =
+                make_token_transfer(&keys[0], nonce, 0, key_to_stacks_addr(&keys[1]).into(), 1);
+            peer.tenure_with_txs(&[transfer], &mut coinbase_nonce);
+        }
+
+        let block_payload = |b: &TestEventObserverBlock| {
+            let all_events: Vec<_> = b
+                .receipts
+                .iter()
+                .flat_map(|r| {
+                    r.events
+                        .iter()
+                        .map(move |e| (!r.post_condition_aborted, r.transaction.txid(), e))
+                })
+                .collect();
+            events::make_new_block_processed_payload(
+                all_events.iter().enumerate().collect(),
+                &b.block,
+                &b.metadata,
+                b.receipts.iter().enumerate().collect(),
+                &b.parent,
+                &b.winner_txid,
+                &events::make_mature_rewards_payload(
+                    &b.matured_rewards,
+                    b.matured_rewards_info.as_ref(),
+                ),
+                BurnchainHeaderHash([0; 32]),
+                0,
+                0,
+                &ExecutionCost::zero(),
+                &ExecutionCost::zero(),
+                &burnchain.pox_constants,
+            )
+        };
+
+        let tip_before =
+            SortitionDB::get_canonical_stacks_chain_tip_hash(peer.sortdb.as_ref().unwrap().conn())
+                .unwrap();
+        let blocks = observer.get_blocks();
+        assert_eq!(blocks.len(), 5);
+
+        for block in blocks.iter() {
+            // replay each block twice, to verify that replaying does not store anything
+            for _ in 0..2 {
+                let replayed = TestEventObserver::new();
+                let mut sortdb = peer.sortdb.take().unwrap();
+                {
+                    let mut sort_tx = sortdb.tx_begin_at_tip();
+                    peer.chainstate()
+                        .replay_block(&mut sort_tx, &block.metadata.index_block_hash(), &replayed)
+                        .unwrap();
+                }
+                peer.sortdb = Some(sortdb);
+
+                let replayed_blocks = replayed.get_blocks();
+                assert_eq!(replayed_blocks.len(), 1);
+                assert_eq!(replayed_blocks[0].receipts, block.receipts);
+                assert_eq!(block_payload(&replayed_blocks[0]), block_payload(block));
+            }
+        }
+
+        assert_eq!(
+            SortitionDB::get_canonical_stacks_chain_tip_hash(peer.sortdb.as_ref().unwrap().conn())
+                .unwrap(),
+            tip_before
+        );
+
+        // the chain still advances normally afterwards
+        let transfer = make_token_transfer(&keys[0], 5, 0, key_to_stacks_addr(&keys[1]).into(), 1);
+        peer.tenure_with_txs(&[transfer], &mut coinbase_nonce);
+        assert_eq!(observer.get_blocks().len(), 6);
+    }
+
     #[test]
     fn test_lockups() {
         let mut peer_config = TestPeerConfig::new(function_name!(), 2000, 2001);
@@ -166,6 +166,21 @@ pub enum MemPoolRejection {
     DBError(db_error),
     EstimatorError(EstimatorError),
     TemporarilyBlacklisted,
+    FeeRateBelowEvictionFloor {
+        fee_rate: f64,
+        eviction_floor: f64,
+    },
+    TooManyPendingTransactions {
+        max_txs: u64,
+        principal: PrincipalData,
+        is_origin: bool,
+    },
+    TooManyPendingBytes {
+        max_bytes: u64,
+        actual_bytes: u64,
+        principal: PrincipalData,
+        is_origin: bool,
+    },
     Other(String),
 }
 
@@ -312,6 +327,42 @@ impl MemPoolRejection {
                 Some(json!({"message": e.to_string()})),
             ),
             TemporarilyBlacklisted => ("TemporarilyBlacklisted", None),
+            FeeRateBelowEvictionFloor {
+                fee_rate,
+                eviction_floor,
+            } => (
+                "FeeRateBelowEvictionFloor",
+                Some(json!({
+                    "message": "Mempool is full, and transaction fee rate is too low to evict another transaction",
+                    "expected": eviction_floor,
+                    "actual": fee_rate})),
+            ),
+            TooManyPendingTransactions {
+                max_txs,
+                principal,
+                is_origin,
+            } => (
+                "TooManyPendingTransactions",
+                Some(json!({
+                    "message": "Account has too many pending transactions in mempool",
+                    "expected": max_txs,
+                    "principal": principal.to_string(),
+                    "is_origin": is_origin})),
+            ),
+            TooManyPendingBytes {
+                max_bytes,
+                actual_bytes,
+                principal,
+                is_origin,
+            } => (
+                "TooManyPendingBytes",
+                Some(json!({
+                    "message": "Account's pending transactions in mempool are too large",
+                    "expected": max_bytes,
+                    "actual": actual_bytes,
+                    "principal": principal.to_string(),
+                    "is_origin": is_origin})),
+            ),
             Other(s) => ("ServerFailureOther", Some(json!({ "message": s }))),
         };
         let mut result = json!({
@@ -5886,6 +5937,11 @@ impl StacksChainState {
     /// necessary so that the Headers database and Clarity database's
     /// transactions can commit very close to one another, after the
     /// event observer has emitted.
+    ///
+    /// If `replay` is true, then the block must have already been processed.  Its transactions are
+    /// re-executed to regenerate its receipts, but its header is loaded from the headers DB
+    /// instead of being inserted.  The caller must roll back both the returned
+    /// `PreCommitClarityBlock` and `chainstate_tx`.
     fn append_block<'a>(
         chainstate_tx: &mut ChainstateTx,
         clarity_instance: &'a mut ClarityInstance,
@@ -5903,6 +5959,7 @@ impl StacksChainState {
         burnchain_sortition_burn: u64,
         user_burns: &[StagingUserBurnSupport],
         affirmation_weight: u64,
+        replay: bool,
     ) -> Result<(StacksEpochReceipt, PreCommitClarityBlock<'a>), Error> {
         debug!(
             "Process block {:?} with {} transactions",
@@ -6268,6 +6325,30 @@ impl StacksChainState {
             .as_ref()
             .map(|(_, _, _, info)| info.clone());
 
+        if replay {
+            let header = StacksChainState::get_anchored_block_header_info(
+                &chainstate_tx.tx,
+                chain_tip_consensus_hash,
+                &block.block_hash(),
+            )?
+            .ok_or(Error::NoSuchBlockError)?;
+
+            let epoch_receipt = StacksEpochReceipt {
+                header,
+                tx_receipts,
+                matured_rewards,
+                matured_rewards_info,
+                parent_microblocks_cost: microblock_execution_cost,
+                anchored_block_cost: block_execution_cost,
+                parent_burn_block_hash,
+                parent_burn_block_height,
+                parent_burn_block_timestamp,
+                evaluated_epoch,
+                epoch_transition: applied_epoch_transition,
+            };
+            return Ok((epoch_receipt, clarity_commit));
+        }
+
         let new_tip = StacksChainState::advance_tip(
             &mut chainstate_tx.tx,
             &parent_chain_tip.anchored_header,
@@ -6652,6 +6733,7 @@ impl StacksChainState {
             next_staging_block.sortition_burn,
             &user_supports,
             block_am.weight(),
+            false,
         ) {
             Ok(next_chain_tip_info) => next_chain_tip_info,
             Err(e) => {
@@ -6750,23 +6832,12 @@ impl StacksChainState {
         }
 
         if let Some(dispatcher) = dispatcher_opt {
-            let parent_id = StacksBlockId::new(
-                &next_staging_block.parent_consensus_hash,
-                &next_staging_block.parent_anchored_block_hash,
-            );
-            dispatcher.announce_block(
+            StacksChainState::announce_epoch_receipt(
+                dispatcher,
+                &next_staging_block,
                 &block,
-                &epoch_receipt.header.clone(),
-                &epoch_receipt.tx_receipts,
-                &parent_id,
+                &epoch_receipt,
                 winning_block_txid,
-                &epoch_receipt.matured_rewards,
-                epoch_receipt.matured_rewards_info.as_ref(),
-                epoch_receipt.parent_burn_block_hash,
-                epoch_receipt.parent_burn_block_height,
-                epoch_receipt.parent_burn_block_timestamp,
-                &epoch_receipt.anchored_block_cost,
-                &epoch_receipt.parent_microblocks_cost,
                 &pox_constants,
             );
         }
@@ -6792,6 +6863,132 @@ impl StacksChainState {
         Ok((Some(epoch_receipt), None))
     }
 
+    /// Announce a processed block and its receipts to the event dispatcher.
+    fn announce_epoch_receipt<T: BlockEventDispatcher>(
+        dispatcher: &T,
+        staging_block: &StagingBlock,
+        block: &StacksBlock,
+        epoch_receipt: &StacksEpochReceipt,
+        winning_block_txid: Txid,
+        pox_constants: &PoxConstants,
+    ) {
+        let parent_id = StacksBlockId::new(
+            &staging_block.parent_consensus_hash,
+            &staging_block.parent_anchored_block_hash,
+        );
+        dispatcher.announce_block(
+            block,
+            &epoch_receipt.header.clone(),
+            &epoch_receipt.tx_receipts,
+            &parent_id,
+            winning_block_txid,
+            &epoch_receipt.matured_rewards,
+            epoch_receipt.matured_rewards_info.as_ref(),
+            epoch_receipt.parent_burn_block_hash,
+            epoch_receipt.parent_burn_block_height,
+            epoch_receipt.parent_burn_block_timestamp,
+            &epoch_receipt.anchored_block_cost,
+            &epoch_receipt.parent_microblocks_cost,
+            pox_constants,
+        );
+    }
+
+    /// Re-execute an already-processed block in order to regenerate its receipts, and announce
+    /// them to `dispatcher` exactly as they were announced when the block was first processed.
+    /// Nothing is written to the chainstate or to `sort_tx` -- all of the block's writes are
+    /// rolled back once it has been announced.
+    ///
+    /// The node must not be processing blocks while this runs.
+    pub fn replay_block<T: BlockEventDispatcher>(
+        &mut self,
+        sort_tx: &mut SortitionHandleTx,
+        index_block_hash: &StacksBlockId,
+        dispatcher: &T,
+    ) -> Result<StacksEpochReceipt, Error> {
+        let blocks_path = self.blocks_path.clone();
+        let (mut chainstate_tx, clarity_instance) = self.chainstate_tx_begin()?;
+
+        let staging_block =
+            StacksChainState::load_staging_block_info(&chainstate_tx.tx, index_block_hash)?
+                .ok_or(Error::NoSuchBlockError)?;
+        if !staging_block.processed {
+            let msg = format!("Block {} has not been processed", index_block_hash);
+            warn!("{}", &msg);
+            return Err(Error::InvalidStacksBlock(msg));
+        }
+
+        let block = StacksChainState::load_block(
+            &blocks_path,
+            &staging_block.consensus_hash,
+            &staging_block.anchored_block_hash,
+        )?
+        .ok_or(Error::NoSuchBlockError)?;
+        let block_size = block.serialize_to_vec().len() as u64;
+
+        let snapshot =
+            SortitionDB::get_block_snapshot_consensus(sort_tx, &staging_block.consensus_hash)?
+                .ok_or(Error::NoSuchBlockError)?;
+
+        let parent_header_info =
+            StacksChainState::get_parent_header_info(&mut chainstate_tx, &staging_block)?
+                .ok_or(Error::NoSuchBlockError)?;
+
+        let microblocks = if block.has_microblock_parent() {
+            StacksChainState::load_microblock_stream_fork(
+                &chainstate_tx.tx,
+                &staging_block.parent_consensus_hash,
+                &staging_block.parent_anchored_block_hash,
+                &block.header.parent_microblock,
+            )?
+            .ok_or(Error::NoSuchBlockError)?
+        } else {
+            vec![]
+        };
+
+        let user_supports = StacksChainState::load_staging_block_user_supports(
+            chainstate_tx.deref().deref(),
+            &staging_block.consensus_hash,
+            &staging_block.anchored_block_hash,
+        )?;
+
+        // the affirmation weight is only used when inserting the block's header, which a replay
+        // does not do.
+        let pox_constants = sort_tx.context.pox_constants.clone();
+        let (epoch_receipt, clarity_commit) = StacksChainState::append_block(
+            &mut chainstate_tx,
+            clarity_instance,
+            sort_tx,
+            &pox_constants,
+            &parent_header_info,
+            &staging_block.consensus_hash,
+            &snapshot.burn_header_hash,
+            snapshot.block_height as u32,
+            snapshot.burn_header_timestamp,
+            &block,
+            block_size,
+            &microblocks,
+            staging_block.commit_burn,
+            staging_block.sortition_burn,
+            &user_supports,
+            0,
+            true,
+        )?;
+
+        clarity_commit.rollback();
+        drop(chainstate_tx);
+
+        StacksChainState::announce_epoch_receipt(
+            dispatcher,
+            &staging_block,
+            &block,
+            &epoch_receipt,
+            snapshot.winning_block_txid,
+            &pox_constants,
+        );
+
+        Ok(epoch_receipt)
+    }
+
     /// Process staging blocks at the canonical chain tip,
     ///  this only needs to be used in contexts that aren't
     ///  PoX aware (i.e., unit tests, and old stacks-node loops),
@@ -453,6 +453,14 @@ impl StacksChainState {
         // valid auth?
         tx.verify().map_err(Error::NetError)?;
 
+        StacksChainState::process_transaction_precheck_unverified(config, tx)
+    }
+
+    /// Pre-check a transaction without verifying its signatures
+    fn process_transaction_precheck_unverified(
+        config: &DBConfig,
+        tx: &StacksTransaction,
+    ) -> Result<(), Error> {
         // destined for us?
         if config.chain_id != tx.chain_id {
             let msg = format!(
@@ -1302,11 +1310,37 @@ impl StacksChainState {
         tx: &StacksTransaction,
         quiet: bool,
         ast_rules: ASTRules,
+    ) -> Result<(u64, StacksTransactionReceipt), Error> {
+        StacksChainState::inner_process_transaction(clarity_block, tx, quiet, ast_rules, true)
+    }
+
+    /// Process a transaction without verifying its signatures, so that a transaction which has
+    /// not been signed yet can be simulated.  Everything else is checked as in
+    /// `process_transaction()`.  The resulting block must never be committed.
+    pub fn process_unsigned_transaction(
+        clarity_block: &mut ClarityTx,
+        tx: &StacksTransaction,
+        quiet: bool,
+        ast_rules: ASTRules,
+    ) -> Result<(u64, StacksTransactionReceipt), Error> {
+        StacksChainState::inner_process_transaction(clarity_block, tx, quiet, ast_rules, false)
+    }
+
+    fn inner_process_transaction(
+        clarity_block: &mut ClarityTx,
+        tx: &StacksTransaction,
+        quiet: bool,
+        ast_rules: ASTRules,
+        verify_auth: bool,
     ) -> Result<(u64, StacksTransactionReceipt), Error> {
         debug!("Process transaction {} ({})", tx.txid(), tx.payload.name());
         let epoch = clarity_block.get_epoch();
 
-        StacksChainState::process_transaction_precheck(&clarity_block.config, tx)?;
+        if verify_auth {
+            StacksChainState::process_transaction_precheck(&clarity_block.config, tx)?;
+        } else {
+            StacksChainState::process_transaction_precheck_unverified(&clarity_block.config, tx)?;
+        }
 
         // what version of Clarity did the transaction caller want? And, is it valid now?
         let clarity_version = StacksChainState::get_tx_clarity_version(clarity_block, tx)?;
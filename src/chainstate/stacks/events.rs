@@ -1,9 +1,16 @@
-use crate::burnchains::Txid;
+use crate::burnchains::{PoxConstants, Txid};
+use crate::chainstate::stacks::address::PoxAddress;
+use crate::chainstate::stacks::db::accounts::MinerReward;
+use crate::chainstate::stacks::db::{MinerRewardInfo, StacksHeaderInfo};
+use crate::chainstate::stacks::StacksBlock;
 use crate::chainstate::stacks::StacksMicroblockHeader;
 use crate::chainstate::stacks::StacksTransaction;
+use crate::chainstate::stacks::TransactionPayload;
 use crate::codec::StacksMessageCodec;
-use crate::types::chainstate::StacksAddress;
+use crate::types::chainstate::{BurnchainHeaderHash, StacksAddress, StacksBlockId};
+use clarity::util::hash::bytes_to_hex;
 use clarity::util::hash::to_hex;
+use clarity::vm::analysis::contract_interface_builder::build_contract_interface;
 use clarity::vm::analysis::ContractAnalysis;
 use clarity::vm::costs::ExecutionCost;
 use clarity::vm::types::{
@@ -13,6 +20,20 @@ use clarity::vm::types::{
 use crate::chainstate::burn::operations::BlockstackOperationType;
 pub use clarity::vm::events::StacksTransactionEvent;
 
+/// Paths that event observers receive payloads on
+pub const PATH_MICROBLOCK_SUBMIT: &str = "new_microblocks";
+pub const PATH_MEMPOOL_TX_SUBMIT: &str = "new_mempool_tx";
+pub const PATH_MEMPOOL_TX_DROP: &str = "drop_mempool_tx";
+pub const PATH_MINED_BLOCK: &str = "mined_block";
+pub const PATH_MINED_MICROBLOCK: &str = "mined_microblock";
+pub const PATH_BURN_BLOCK_SUBMIT: &str = "new_burn_block";
+pub const PATH_BLOCK_PROCESSED: &str = "new_block";
+pub const PATH_ATTACHMENT_PROCESSED: &str = "attachments/new";
+
+const STATUS_RESP_TRUE: &str = "success";
+const STATUS_RESP_NOT_COMMITTED: &str = "abort_by_response";
+const STATUS_RESP_POST_CONDITION: &str = "abort_by_post_condition";
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TransactionOrigin {
     Stacks(StacksTransaction),
@@ -56,3 +77,210 @@ pub struct StacksTransactionReceipt {
     /// This is really a string-formatted CheckError (which can't be clone()'ed)
     pub vm_error: Option<String>,
 }
+
+struct ReceiptPayloadInfo {
+    txid: String,
+    success: &'static str,
+    raw_result: String,
+    raw_tx: String,
+    contract_interface_json: serde_json::Value,
+    burnchain_op_json: serde_json::Value,
+}
+
+impl StacksTransactionReceipt {
+    /// Returns tuple of (txid, success, raw_result, raw_tx, contract_interface_json)
+    fn generate_payload_info(&self) -> ReceiptPayloadInfo {
+        let tx = &self.transaction;
+
+        let success = match (self.post_condition_aborted, &self.result) {
+            (false, Value::Response(response_data)) => {
+                if response_data.committed {
+                    STATUS_RESP_TRUE
+                } else {
+                    STATUS_RESP_NOT_COMMITTED
+                }
+            }
+            (true, Value::Response(_)) => STATUS_RESP_POST_CONDITION,
+            _ => {
+                if let TransactionOrigin::Stacks(inner_tx) = &tx {
+                    if let TransactionPayload::PoisonMicroblock(..) = &inner_tx.payload {
+                        STATUS_RESP_TRUE
+                    } else {
+                        unreachable!() // Transaction results should otherwise always be a Value::Response type
+                    }
+                } else {
+                    unreachable!() // Transaction results should always be a Value::Response type
+                }
+            }
+        };
+
+        let (txid, raw_tx, burnchain_op_json) = match tx {
+            TransactionOrigin::Burn(op) => (
+                op.txid().to_string(),
+                "00".to_string(),
+                BlockstackOperationType::blockstack_op_to_json(op),
+            ),
+            TransactionOrigin::Stacks(ref tx) => {
+                let txid = tx.txid().to_string();
+                let bytes = tx.serialize_to_vec();
+                (txid, bytes_to_hex(&bytes), json!(null))
+            }
+        };
+
+        let raw_result = {
+            let bytes = self.result.serialize_to_vec();
+            bytes_to_hex(&bytes)
+        };
+        let contract_interface_json = {
+            match &self.contract_analysis {
+                Some(analysis) => json!(build_contract_interface(analysis)),
+                None => json!(null),
+            }
+        };
+        ReceiptPayloadInfo {
+            txid,
+            success,
+            raw_result,
+            raw_tx,
+            contract_interface_json,
+            burnchain_op_json,
+        }
+    }
+
+    /// Returns the json payload that describes this transaction in a new block or microblock
+    /// event
+    pub fn json_serialize(&self, tx_index: u32) -> serde_json::Value {
+        let receipt_payload_info = self.generate_payload_info();
+
+        json!({
+            "txid": format!("0x{}", &receipt_payload_info.txid),
+            "tx_index": tx_index,
+            "status": receipt_payload_info.success,
+            "raw_result": format!("0x{}", &receipt_payload_info.raw_result),
+            "raw_tx": format!("0x{}", &receipt_payload_info.raw_tx),
+            "contract_abi": receipt_payload_info.contract_interface_json,
+            "burnchain_op": receipt_payload_info.burnchain_op_json,
+            "execution_cost": self.execution_cost,
+            "microblock_sequence": self.microblock_header.as_ref().map(|x| x.sequence),
+            "microblock_hash": self.microblock_header.as_ref().map(|x| format!("0x{}", x.block_hash())),
+            "microblock_parent_hash": self.microblock_header.as_ref().map(|x| format!("0x{}", x.prev_block)),
+        })
+    }
+}
+
+/// Returns the json payload sent to event observers when a burnchain block is processed
+pub fn make_new_burn_block_payload(
+    burn_block: &BurnchainHeaderHash,
+    burn_block_height: u64,
+    rewards: Vec<(PoxAddress, u64)>,
+    burns: u64,
+    slot_holders: Vec<PoxAddress>,
+) -> serde_json::Value {
+    let reward_recipients = rewards
+        .into_iter()
+        .map(|(pox_addr, amt)| {
+            json!({
+                "recipient": pox_addr.to_b58(),
+                "amt": amt,
+            })
+        })
+        .collect();
+
+    let reward_slot_holders = slot_holders
+        .into_iter()
+        .map(|pox_addr| json!(pox_addr.to_b58()))
+        .collect();
+
+    json!({
+        "burn_block_hash": format!("0x{}", burn_block),
+        "burn_block_height": burn_block_height,
+        "reward_recipients": serde_json::Value::Array(reward_recipients),
+        "reward_slot_holders": serde_json::Value::Array(reward_slot_holders),
+        "burn_amount": burns
+    })
+}
+
+/// Returns the json list of miner rewards that matured in a newly processed block
+pub fn make_mature_rewards_payload(
+    mature_rewards: &[MinerReward],
+    mature_rewards_info: Option<&MinerRewardInfo>,
+) -> serde_json::Value {
+    let mature_rewards_vec = if let Some(rewards_info) = mature_rewards_info {
+        mature_rewards
+            .iter()
+            .map(|reward| {
+                json!({
+                    "recipient": reward.recipient.to_string(),
+                    "miner_address": reward.address.to_string(),
+                    "coinbase_amount": reward.coinbase.to_string(),
+                    "tx_fees_anchored": reward.tx_fees_anchored.to_string(),
+                    "tx_fees_streamed_confirmed": reward.tx_fees_streamed_confirmed.to_string(),
+                    "tx_fees_streamed_produced": reward.tx_fees_streamed_produced.to_string(),
+                    "from_stacks_block_hash": format!("0x{}", rewards_info.from_stacks_block_hash),
+                    "from_index_consensus_hash": format!("0x{}", StacksBlockId::new(&rewards_info.from_block_consensus_hash,
+                                                                                    &rewards_info.from_stacks_block_hash)),
+                })
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+
+    serde_json::Value::Array(mature_rewards_vec)
+}
+
+/// Returns the json payload sent to event observers when a Stacks block is processed.
+/// `filtered_events` are the (event index, (committed, txid, event)) tuples this observer is
+/// subscribed to.
+pub fn make_new_block_processed_payload(
+    filtered_events: Vec<(usize, &(bool, Txid, &StacksTransactionEvent))>,
+    block: &StacksBlock,
+    metadata: &StacksHeaderInfo,
+    filtered_receipts: Vec<(usize, &StacksTransactionReceipt)>,
+    parent_index_hash: &StacksBlockId,
+    winner_txid: &Txid,
+    mature_rewards: &serde_json::Value,
+    parent_burn_block_hash: BurnchainHeaderHash,
+    parent_burn_block_height: u32,
+    parent_burn_block_timestamp: u64,
+    anchored_consumed: &ExecutionCost,
+    mblock_confirmed_consumed: &ExecutionCost,
+    pox_constants: &PoxConstants,
+) -> serde_json::Value {
+    // Serialize events to JSON
+    let serialized_events: Vec<serde_json::Value> = filtered_events
+        .iter()
+        .map(|(event_index, (committed, txid, event))| {
+            event.json_serialize(*event_index, txid, *committed)
+        })
+        .collect();
+
+    let serialized_txs: Vec<serde_json::Value> = filtered_receipts
+        .iter()
+        .map(|(tx_index, receipt)| receipt.json_serialize(*tx_index as u32))
+        .collect();
+
+    // Wrap events
+    json!({
+        "block_hash": format!("0x{}", block.block_hash()),
+        "block_height": metadata.stacks_block_height,
+        "burn_block_hash": format!("0x{}", metadata.burn_header_hash),
+        "burn_block_height": metadata.burn_header_height,
+        "miner_txid": format!("0x{}", winner_txid),
+        "burn_block_time": metadata.burn_header_timestamp,
+        "index_block_hash": format!("0x{}", metadata.index_block_hash()),
+        "parent_block_hash": format!("0x{}", block.header.parent_block),
+        "parent_index_block_hash": format!("0x{}", parent_index_hash),
+        "parent_microblock": format!("0x{}", block.header.parent_microblock),
+        "parent_microblock_sequence": block.header.parent_microblock_sequence,
+        "matured_miner_rewards": mature_rewards.clone(),
+        "events": serialized_events,
+        "transactions": serialized_txs,
+        "parent_burn_block_hash":  format!("0x{}", parent_burn_block_hash),
+        "parent_burn_block_height": parent_burn_block_height,
+        "parent_burn_block_timestamp": parent_burn_block_timestamp,
+        "anchored_cost": anchored_consumed,
+        "confirmed_microblocks_cost": mblock_confirmed_consumed,
+        "pox_v1_unlock_height": pox_constants.v1_unlock_height,
+    })
+}
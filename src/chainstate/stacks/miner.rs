@@ -1151,16 +1151,22 @@ impl<'a> StacksMicroblockBuilder<'a> {
             &self.anchor_block, bytes_so_far
         );
         let mut blocked = false;
+        let mut tx_selection_strategy = mempool_settings.tx_selection.make_strategy(
+            mempool_settings.consider_no_estimate_tx_prob,
+            &block_limit,
+            &clarity_tx.cost_so_far(),
+        );
 
         let result = {
             let mut intermediate_result;
             loop {
                 let mut num_added = 0;
-                intermediate_result = mem_pool.iterate_candidates(
+                intermediate_result = mem_pool.iterate_candidates_with_strategy(
                     &mut clarity_tx,
                     &mut tx_events,
                     self.anchor_block_height,
                     mempool_settings.clone(),
+                    tx_selection_strategy.as_mut(),
                     |clarity_tx, to_consider, estimator| {
                         let mempool_tx = &to_consider.tx;
                         let update_estimator = to_consider.update_estimate;
@@ -2421,20 +2427,27 @@ impl StacksBlockBuilder {
         let deadline = ts_start + (max_miner_time_ms as u128);
         let mut num_txs = 0;
         let mut blocked = false;
+        let mut tx_selection_strategy = mempool_settings.tx_selection.make_strategy(
+            mempool_settings.consider_no_estimate_tx_prob,
+            &block_limit,
+            &epoch_tx.cost_so_far(),
+        );
 
         debug!(
             "Anchored block transaction selection begins (child of {})",
-            &parent_stacks_header.anchored_header.block_hash()
+            &parent_stacks_header.anchored_header.block_hash();
+            "tx_selection_strategy" => tx_selection_strategy.name()
         );
         let result = {
             let mut intermediate_result = Ok(0);
             while block_limit_hit != BlockLimitFunction::LIMIT_REACHED {
                 let mut num_considered = 0;
-                intermediate_result = mempool.iterate_candidates(
+                intermediate_result = mempool.iterate_candidates_with_strategy(
                     &mut epoch_tx,
                     &mut tx_events,
                     tip_height,
                     mempool_settings.clone(),
+                    tx_selection_strategy.as_mut(),
                     |epoch_tx, to_consider, estimator| {
                         // first, have we been preempted?
                         blocked = (*settings.miner_status.lock().expect("FATAL: mutex poisoned"))
@@ -2659,6 +2672,7 @@ impl StacksBlockBuilder {
             "execution_consumed" => %consumed,
             "%-full" => block_limit.proportion_largest_dimension(&consumed),
             "assembly_time_ms" => ts_end.saturating_sub(ts_start),
+            "tx_selection_strategy" => tx_selection_strategy.name(),
             "tx_fees_microstacks" => block.txs.iter().fold(0, |agg: u64, tx| {
                 agg.saturating_add(tx.get_tx_fee())
             })
@@ -4493,7 +4493,6 @@ fn paramaterized_mempool_walk_test(
                 round_index.try_into().unwrap(),
                 &sponsor_address,
                 round_index.try_into().unwrap(),
-                None,
             )
             .unwrap();
 
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::HashMap;
 use std::convert::TryInto;
 use std::env;
 use std::ffi::OsStr;
@@ -21,11 +22,14 @@ use std::fs;
 use std::io;
 use std::io::{Read, Write};
 use std::iter::Iterator;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process;
 
 use clarity::util::get_epoch_time_ms;
 use clarity::vm::coverage::CoverageReporter;
+use clarity::vm::debugger::Debugger;
+use clarity::vm::profiler::{CostProfiler, ProfileMetric};
+use clarity::vm::EvalHook;
 use rand::Rng;
 use rusqlite::types::ToSql;
 use rusqlite::Row;
@@ -40,24 +44,31 @@ use stacks_common::util::hash::{bytes_to_hex, Hash160, Sha512Trunc256Sum};
 
 use crate::clarity::{
     vm::analysis,
-    vm::analysis::contract_interface_builder::build_contract_interface,
+    vm::analysis::contract_interface_builder::bindgen::{
+        generate_bindings, infer_print_event_types, BindingsLanguage,
+    },
+    vm::analysis::contract_interface_builder::{build_contract_interface, ContractInterface},
+    vm::analysis::cost_analyzer::CostAnalyzer,
+    vm::analysis::linter::{LintRule, LintSettings, Linter},
+    vm::analysis::AnalysisPass,
     vm::analysis::{errors::CheckError, errors::CheckResult, AnalysisDatabase, ContractAnalysis},
     vm::ast,
     vm::ast::build_ast_with_rules,
+    vm::ast::formatter::format_source,
     vm::ast::ASTRules,
     vm::contexts::GlobalContext,
-    vm::contexts::{AssetMap, OwnedEnvironment},
+    vm::contexts::{AssetMap, LocalContext, OwnedEnvironment},
     vm::costs::ExecutionCost,
     vm::costs::LimitedCostTracker,
     vm::database::{
         BurnStateDB, ClarityDatabase, HeadersDB, STXBalance, SqliteConnection, NULL_BURN_STATE_DB,
     },
     vm::errors::{Error, InterpreterResult, RuntimeErrorType},
-    vm::eval_all,
-    vm::types::{OptionalData, PrincipalData, QualifiedContractIdentifier},
+    vm::types::{OptionalData, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData},
     vm::ClarityVersion,
     vm::ContractContext,
     vm::ContractName,
+    vm::{eval, eval_all},
     vm::{SymbolicExpression, SymbolicExpressionType, Value},
 };
 use stacks_common::util::log;
@@ -154,8 +165,13 @@ where command is one of:
   eval_at_block      like `eval_at_chaintip`, but accepts a index-block-hash to evaluate at,
                      must be passed eval string via stdin.
   eval_raw           to typecheck and evaluate an expression without a contract or database context.
-  repl               to typecheck and evaluate expressions in a stdin/stdout loop.
+  repl               to deploy contracts and evaluate expressions in an interactive session.
   execute            to execute a public function of a defined contract.
+  debug              to `launch` or `execute` in an interactive debugger.
+  profile            to `launch` or `execute` while measuring the cost of each function.
+  test               to run the `test-` functions of test contracts in an in-memory database.
+  fmt                to format Clarity source files, or check that they are formatted.
+  bindgen            to generate typed Rust or TypeScript bindings for a contract.
   generate_address   to generate a random Stacks public address for testing purposes.
 ",
         invoked_by
@@ -204,7 +220,7 @@ fn parse(
     Ok(ast.expressions)
 }
 
-trait ClarityStorage {
+pub(crate) trait ClarityStorage {
     fn get_clarity_db<'a>(
         &'a mut self,
         headers_db: &'a dyn HeadersDB,
@@ -291,6 +307,76 @@ fn run_analysis<C: ClarityStorage>(
     )
 }
 
+/// Compute the worst-case cost of the contract's public and read-only functions,
+///  using the cost tracker that performed the analysis.
+fn run_cost_analysis<C: ClarityStorage>(
+    mut contract_analysis: ContractAnalysis,
+    marf_kv: &mut C,
+) -> Result<ContractAnalysis, (CheckError, LimitedCostTracker)> {
+    match CostAnalyzer::run_pass(
+        &DEFAULT_CLI_EPOCH,
+        &mut contract_analysis,
+        &mut marf_kv.get_analysis_db(),
+    ) {
+        Ok(()) => Ok(contract_analysis),
+        Err(e) => Err((e, contract_analysis.take_contract_cost_tracker())),
+    }
+}
+
+/// Parses `--lint`, `--lint_enable` and `--lint_disable` for the `check` command.
+/// Returns `None` if linting was not requested. `--lint_enable` restricts
+///  linting to the listed rules, and implies `--lint`, as does `--lint_disable`.
+fn parse_lint_settings(argv: &mut Vec<String>) -> Option<LintSettings> {
+    let lint = if let Ok(Some(_)) = consume_arg(argv, &["--lint"], false) {
+        true
+    } else {
+        false
+    };
+    let enabled = consume_lint_rules(argv, "--lint_enable");
+    let disabled = consume_lint_rules(argv, "--lint_disable");
+    if !lint && enabled.is_none() && disabled.is_none() {
+        return None;
+    }
+
+    let mut settings = LintSettings::default();
+    if let Some(enabled) = enabled {
+        for rule in LintRule::ALL.iter() {
+            if !enabled.contains(rule) {
+                settings.disable(*rule);
+            }
+        }
+    }
+    for rule in disabled.unwrap_or_default() {
+        settings.disable(rule);
+    }
+    Some(settings)
+}
+
+fn consume_lint_rules(argv: &mut Vec<String>, flag: &str) -> Option<Vec<LintRule>> {
+    let rules = match consume_arg(argv, &[flag], true) {
+        Ok(rules) => rules?,
+        Err(_) => {
+            eprintln!("Expected a comma-separated list of lint rules for {}", flag);
+            panic_test!();
+        }
+    };
+    let rules = rules
+        .split(',')
+        .map(|name| match LintRule::lookup_by_name(name) {
+            Some(rule) => rule,
+            None => {
+                eprintln!(
+                    "Unknown lint rule '{}'. Lint rules: {}",
+                    name,
+                    LintRule::ALL_NAMES.join(", ")
+                );
+                panic_test!();
+            }
+        })
+        .collect();
+    Some(rules)
+}
+
 fn create_or_open_db(path: &String) -> Connection {
     let open_flags = if path == ":memory:" {
         OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_CREATE
@@ -448,7 +534,7 @@ fn with_env_costs<F, R>(
     mainnet: bool,
     header_db: &CLIHeadersDB,
     marf: &mut WritableMarfStore,
-    coverage: Option<&mut CoverageReporter>,
+    eval_hooks: Vec<&mut dyn EvalHook>,
     f: F,
 ) -> (R, ExecutionCost)
 where
@@ -474,8 +560,8 @@ where
         cost_track,
         DEFAULT_CLI_EPOCH,
     );
-    if let Some(coverage) = coverage {
-        vm_env.add_eval_hook(coverage);
+    for eval_hook in eval_hooks.into_iter() {
+        vm_env.add_eval_hook(eval_hook);
     }
     let result = f(&mut vm_env);
     let cost = vm_env.get_cost_total();
@@ -510,6 +596,65 @@ pub fn vm_execute(program: &str, clarity_version: ClarityVersion) -> Result<Opti
     })
 }
 
+type CliDebugger = Debugger<io::BufReader<io::Stdin>, io::Stderr>;
+
+/// Debug with commands from stdin.  The debugger writes to stderr, leaving stdout to the
+/// command's own output.
+fn make_debugger() -> CliDebugger {
+    Debugger::new(io::BufReader::new(io::stdin()), io::stderr())
+}
+
+fn eval_hooks<'a>(
+    coverage: &'a mut Option<CoverageReporter>,
+    debugger: &'a mut Option<CliDebugger>,
+    profiler: &'a mut Option<CostProfiler>,
+) -> Vec<&'a mut dyn EvalHook> {
+    let mut eval_hooks: Vec<&mut dyn EvalHook> = vec![];
+    if let Some(coverage) = coverage.as_mut() {
+        eval_hooks.push(coverage);
+    }
+    if let Some(debugger) = debugger.as_mut() {
+        eval_hooks.push(debugger);
+    }
+    if let Some(profiler) = profiler.as_mut() {
+        eval_hooks.push(profiler);
+    }
+    eval_hooks
+}
+
+/// Consume the `--profile` and `--profile_metric` arguments added by the `profile` command
+fn consume_profile_args(argv: &mut Vec<String>) -> Option<(String, ProfileMetric)> {
+    let profile_file = if let Ok(profile_file) = consume_arg(argv, &["--profile"], true) {
+        profile_file
+    } else {
+        None
+    };
+    let metric = match consume_arg(argv, &["--profile_metric"], true) {
+        Ok(Some(metric)) => friendly_expect(
+            metric.parse::<ProfileMetric>(),
+            "Failed to parse profile metric.",
+        ),
+        _ => ProfileMetric::Runtime,
+    };
+    profile_file.map(|profile_file| (profile_file, metric))
+}
+
+/// Print the table of function costs to stderr, and write the folded stacks to the profile file
+fn save_profile(profile_args: Option<(String, ProfileMetric)>, profiler: Option<CostProfiler>) {
+    if let (Some((profile_file, metric)), Some(profiler)) = (profile_args, profiler) {
+        profiler
+            .write_table(&mut io::stderr())
+            .expect("Failed to write profile table");
+        let mut fd = friendly_expect(
+            fs::File::create(&profile_file),
+            &format!("Failed to create {}", profile_file),
+        );
+        profiler
+            .write_folded(&mut fd, metric)
+            .expect("Failed to write folded stacks");
+    }
+}
+
 fn save_coverage(
     coverage_folder: Option<String>,
     coverage: Option<CoverageReporter>,
@@ -531,7 +676,7 @@ fn save_coverage(
     }
 }
 
-struct CLIHeadersDB {
+pub(crate) struct CLIHeadersDB {
     db_path: String,
     conn: Connection,
 }
@@ -814,6 +959,777 @@ struct InitialAllocation {
     amount: u64,
 }
 
+/// Settings for running tests, given either for all of the tests in a test manifest, or for one test
+#[derive(Serialize, Deserialize, Default)]
+struct TestSettings {
+    /// The `tx-sender` of the test.  Defaults to the issuer of the test contract.
+    sender: Option<String>,
+    block_height: Option<u32>,
+    /// STX balances to set before running the test
+    #[serde(default)]
+    balances: Vec<InitialAllocation>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TestManifestContract {
+    contract_id: String,
+    /// Path to the contract's source, relative to the manifest
+    path: String,
+    /// Settings for the tests of a test contract, by test function name
+    #[serde(default)]
+    settings: HashMap<String, TestSettings>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TestManifest {
+    /// Contracts under test, deployed in order before the test contracts
+    #[serde(default)]
+    contracts: Vec<TestManifestContract>,
+    /// Contracts whose public functions named `test-...` are run as tests
+    tests: Vec<TestManifestContract>,
+    /// Settings for all of the tests.  The balances are set before any contract is deployed.
+    #[serde(flatten)]
+    settings: TestSettings,
+}
+
+fn parse_allocations(allocations: &[InitialAllocation]) -> Vec<(PrincipalData, u64)> {
+    allocations
+        .iter()
+        .map(|a| {
+            (
+                friendly_expect(
+                    PrincipalData::parse(&a.principal),
+                    "Failed to parse principal in JSON",
+                ),
+                a.amount,
+            )
+        })
+        .collect()
+}
+
+fn set_balances(db: &mut ClarityDatabase, balances: &[(PrincipalData, u64)]) {
+    for (principal, amount) in balances.iter() {
+        let mut snapshot = db.get_stx_balance_snapshot_genesis(principal);
+        snapshot.set_balance(STXBalance::initial(*amount as u128));
+        snapshot.save();
+    }
+}
+
+/// Run one test.  Everything the test does, including setting the balances, is rolled back
+/// afterwards.
+fn run_test(
+    marf: &mut MemoryBackingStore,
+    coverage: &mut Option<CoverageReporter>,
+    contract_identifier: &QualifiedContractIdentifier,
+    test_name: &str,
+    sender: PrincipalData,
+    balances: &[(PrincipalData, u64)],
+) -> Result<Value, Error> {
+    let mut db = marf.as_clarity_db();
+    db.begin();
+    set_balances(&mut db, balances);
+
+    let mut vm_env =
+        OwnedEnvironment::new_free(false, default_chain_id(false), db, DEFAULT_CLI_EPOCH);
+    if let Some(coverage) = coverage.as_mut() {
+        vm_env.add_eval_hook(coverage);
+    }
+    let result = vm_env
+        .execute_transaction(sender, None, contract_identifier.clone(), test_name, &[])
+        .map(|(value, ..)| value);
+
+    let (mut db, _) = vm_env
+        .destruct()
+        .expect("FATAL: test did not return to the top-level context");
+    db.roll_back();
+    result
+}
+
+/// Write LCOV for the given contracts, by way of the files that `launch --c` and `make_lcov` use.
+fn write_test_lcov(
+    lcov_file: &str,
+    contracts: &[(
+        QualifiedContractIdentifier,
+        PathBuf,
+        Vec<SymbolicExpression>,
+    )],
+    coverage: &CoverageReporter,
+) -> io::Result<()> {
+    let coverage_folder = env::temp_dir().join(format!(
+        "clarity_test_{}_{}",
+        process::id(),
+        get_epoch_time_ms()
+    ));
+    fs::create_dir_all(&coverage_folder)?;
+
+    let mut register_files = vec![];
+    for (i, (contract_identifier, src_file, ast)) in contracts.iter().enumerate() {
+        let register_file = coverage_folder.join(format!("test_{}.clarcovref", i));
+        CoverageReporter::register_src_file(
+            contract_identifier,
+            &src_file.to_string_lossy(),
+            ast,
+            &register_file,
+        )?;
+        register_files.push(register_file);
+    }
+    let coverage_file = coverage_folder.join("test.clarcov");
+    coverage.to_file(&coverage_file)?;
+
+    CoverageReporter::produce_lcov(lcov_file, &register_files, &[coverage_file])?;
+    fs::remove_dir_all(&coverage_folder)
+}
+
+/// Deploy the contracts of a test manifest into a fresh in-memory database, and run the tests.
+fn run_tests(
+    manifest: &TestManifest,
+    manifest_dir: &Path,
+    lcov_file: Option<String>,
+) -> (i32, Option<serde_json::Value>) {
+    let clarity_version = ClarityVersion::default_for_epoch(DEFAULT_CLI_EPOCH);
+    let mut marf = MemoryBackingStore::new();
+    let mut coverage = lcov_file.as_ref().map(|_| CoverageReporter::new());
+
+    let default_block_height = manifest.settings.block_height.unwrap_or(0);
+    marf.set_block_height(default_block_height);
+    {
+        let mut db = marf.as_clarity_db();
+        db.begin();
+        set_balances(&mut db, &parse_allocations(&manifest.settings.balances));
+        db.commit();
+    }
+
+    let mut deployed = vec![];
+    for contract in manifest.contracts.iter().chain(manifest.tests.iter()) {
+        let contract_identifier = friendly_expect(
+            QualifiedContractIdentifier::parse(&contract.contract_id),
+            "Failed to parse contract identifier.",
+        );
+        let src_file = manifest_dir.join(&contract.path);
+        let contract_content: String = friendly_expect(
+            fs::read_to_string(&src_file),
+            &format!("Error reading file: {}", src_file.display()),
+        );
+        let mut ast = friendly_expect(
+            parse(&contract_identifier, &contract_content, clarity_version),
+            "Failed to parse program.",
+        );
+
+        let analysis = match run_analysis_free(&contract_identifier, &mut ast, &mut marf, true) {
+            Ok(analysis) => analysis,
+            Err((error, _)) => {
+                return (
+                    1,
+                    Some(json!({
+                        "contract": contract.contract_id,
+                        "error": {
+                            "analysis": serde_json::to_value(&format!("{}", error)).unwrap()
+                        }
+                    })),
+                );
+            }
+        };
+
+        let mut vm_env = OwnedEnvironment::new_free(
+            false,
+            default_chain_id(false),
+            marf.as_clarity_db(),
+            DEFAULT_CLI_EPOCH,
+        );
+        if let Some(coverage) = coverage.as_mut() {
+            vm_env.add_eval_hook(coverage);
+        }
+        if let Err(error) = vm_env.initialize_versioned_contract(
+            contract_identifier.clone(),
+            clarity_version,
+            &contract_content,
+            None,
+            ASTRules::PrecheckSize,
+        ) {
+            return (
+                1,
+                Some(json!({
+                    "contract": contract.contract_id,
+                    "error": {
+                        "initialization": serde_json::to_value(&format!("{}", error)).unwrap()
+                    }
+                })),
+            );
+        }
+
+        deployed.push((contract_identifier, src_file, ast, analysis));
+    }
+
+    let mut results = vec![];
+    let mut num_failed = 0;
+    let test_contracts = deployed.iter().skip(manifest.contracts.len());
+    for (contract, (contract_identifier, _, _, analysis)) in
+        manifest.tests.iter().zip(test_contracts)
+    {
+        for test_name in analysis.public_function_types.keys() {
+            if !test_name.starts_with("test-") {
+                continue;
+            }
+            let no_settings = TestSettings::default();
+            let settings = contract
+                .settings
+                .get(test_name.as_str())
+                .unwrap_or(&no_settings);
+
+            let sender = match settings
+                .sender
+                .as_ref()
+                .or(manifest.settings.sender.as_ref())
+            {
+                Some(sender_in) => {
+                    if let Ok(sender) = PrincipalData::parse_standard_principal(sender_in) {
+                        PrincipalData::Standard(sender)
+                    } else {
+                        eprintln!("Unexpected result parsing sender: {}", sender_in);
+                        panic_test!();
+                    }
+                }
+                None => PrincipalData::Standard(contract_identifier.issuer.clone()),
+            };
+            marf.set_block_height(settings.block_height.unwrap_or(default_block_height));
+
+            let result = run_test(
+                &mut marf,
+                &mut coverage,
+                contract_identifier,
+                test_name,
+                sender,
+                &parse_allocations(&settings.balances),
+            );
+            let result = match result {
+                Ok(value) => {
+                    let success = match value {
+                        Value::Response(ref data) => data.committed,
+                        _ => false,
+                    };
+                    json!({
+                        "contract": contract.contract_id,
+                        "test": test_name.as_str(),
+                        "success": success,
+                        "output": format!("{}", value),
+                    })
+                }
+                Err(error) => json!({
+                    "contract": contract.contract_id,
+                    "test": test_name.as_str(),
+                    "success": false,
+                    "error": format!("{}", error),
+                }),
+            };
+            if result["success"] == false {
+                num_failed += 1;
+            }
+            results.push(result);
+        }
+    }
+
+    if let (Some(lcov_file), Some(coverage)) = (lcov_file, coverage) {
+        let contracts: Vec<_> = deployed
+            .into_iter()
+            .map(|(contract_identifier, src_file, ast, _)| (contract_identifier, src_file, ast))
+            .collect();
+        friendly_expect(
+            write_test_lcov(&lcov_file, &contracts, &coverage),
+            "Failed to produce an lcov output",
+        );
+    }
+
+    let num_passed = results.len() - num_failed;
+    let result = json!({
+        "message": format!("{} passed, {} failed", num_passed, num_failed),
+        "passed": num_passed,
+        "failed": num_failed,
+        "tests": results,
+    });
+    if num_failed == 0 {
+        (0, Some(result))
+    } else {
+        (1, Some(result))
+    }
+}
+
+const REPL_HELP: &str = "\
+::help                              show this help
+::deploy <name> <program-file.clar> deploy a contract as <sender>.<name>
+::sender [principal]                show or set the tx-sender
+::advance-blocks <count>            advance the block height
+::mint-stx <principal> <amount>     credit a principal with uSTX
+::balances                          show the STX balances of the principals used in this session
+::get-costs [expression]            show the cost of evaluating the expression, or of the last input
+::epoch [2.0 | 2.05 | 2.1]          show or set the epoch
+::clarity-version [1 | 2]           show or set the Clarity version for evaluation and deploys
+::history                           list the inputs of this session
+::save <file>                       save this session to a file
+::load <file>                       resume a session saved with ::save
+Anything else is evaluated as Clarity code, and may span several lines.";
+
+/// Settings of a `repl` session, which `::save` stores alongside its chain state
+#[derive(Serialize, Deserialize)]
+struct ReplSessionSettings {
+    mainnet: bool,
+    epoch: String,
+    clarity_version: String,
+    sender: String,
+    block_height: u32,
+    principals: Vec<String>,
+    history: Vec<String>,
+}
+
+/// A `repl` session: an in-memory chain state (with the boot code installed) that contracts
+/// are deployed into and expressions are evaluated against, one input at a time.
+struct ReplSession {
+    marf: MemoryBackingStore,
+    mainnet: bool,
+    epoch: StacksEpochId,
+    clarity_version: ClarityVersion,
+    sender: StandardPrincipalData,
+    block_height: u32,
+    /// Principals that `::balances` reports on, in the order they were first used
+    principals: Vec<PrincipalData>,
+    history: Vec<String>,
+    last_cost: Option<ExecutionCost>,
+}
+
+fn parse_epoch(epoch: &str) -> Option<StacksEpochId> {
+    match epoch {
+        "2.0" => Some(StacksEpochId::Epoch20),
+        "2.05" => Some(StacksEpochId::Epoch2_05),
+        "2.1" => Some(StacksEpochId::Epoch21),
+        _ => None,
+    }
+}
+
+fn parse_clarity_version(version: &str) -> Option<ClarityVersion> {
+    match version {
+        "1" => Some(ClarityVersion::Clarity1),
+        "2" => Some(ClarityVersion::Clarity2),
+        _ => None,
+    }
+}
+
+fn clarity_version_number(version: ClarityVersion) -> &'static str {
+    match version {
+        ClarityVersion::Clarity1 => "1",
+        ClarityVersion::Clarity2 => "2",
+    }
+}
+
+fn format_cost(cost: &ExecutionCost) -> String {
+    format!(
+        "runtime: {}\nread_count: {}\nread_length: {}\nwrite_count: {}\nwrite_length: {}",
+        cost.runtime, cost.read_count, cost.read_length, cost.write_count, cost.write_length
+    )
+}
+
+/// Whether `input` has an unclosed list, tuple or string, so the REPL should read another line
+fn is_incomplete_input(input: &str) -> bool {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut chars = input.chars();
+    while let Some(c) = chars.next() {
+        if in_string {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' | '{' => depth += 1,
+            ')' | '}' => depth -= 1,
+            ';' => {
+                // skip the comment
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    in_string || depth > 0
+}
+
+impl ReplSession {
+    fn new(mainnet: bool) -> ReplSession {
+        let header_db = CLIHeadersDB::new_memory(mainnet);
+        let mut marf = MemoryBackingStore::new();
+        install_boot_code(&header_db, &mut marf);
+
+        let sender = QualifiedContractIdentifier::transient().issuer;
+        ReplSession {
+            marf,
+            mainnet,
+            epoch: DEFAULT_CLI_EPOCH,
+            clarity_version: ClarityVersion::default_for_epoch(DEFAULT_CLI_EPOCH),
+            principals: vec![sender.clone().into()],
+            sender,
+            block_height: 0,
+            history: vec![],
+            last_cost: None,
+        }
+    }
+
+    /// Resume a session saved with `::save`
+    fn load(path: &str) -> Result<ReplSession, String> {
+        let settings: String = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .and_then(|conn| {
+                conn.query_row("SELECT settings FROM repl_session", NO_PARAMS, |row| {
+                    row.get(0)
+                })
+            })
+            .map_err(|e| format!("Failed to read a saved session from {}: {}", path, e))?;
+        let settings: ReplSessionSettings = serde_json::from_str(&settings)
+            .map_err(|e| format!("Failed to parse the settings of the saved session: {}", e))?;
+        let mut marf = MemoryBackingStore::load_from_file(path)
+            .map_err(|e| format!("Failed to load the chain state of the saved session: {}", e))?;
+        marf.set_block_height(settings.block_height);
+
+        let invalid =
+            |what: &str, value: &str| format!("Invalid {} in saved session: {}", what, value);
+        let principals = settings
+            .principals
+            .iter()
+            .map(|principal| {
+                PrincipalData::parse(principal).map_err(|_| invalid("principal", principal))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ReplSession {
+            marf,
+            mainnet: settings.mainnet,
+            epoch: parse_epoch(&settings.epoch).ok_or_else(|| invalid("epoch", &settings.epoch))?,
+            clarity_version: parse_clarity_version(&settings.clarity_version)
+                .ok_or_else(|| invalid("Clarity version", &settings.clarity_version))?,
+            sender: PrincipalData::parse_standard_principal(&settings.sender)
+                .map_err(|_| invalid("sender", &settings.sender))?,
+            block_height: settings.block_height,
+            principals,
+            history: settings.history,
+            last_cost: None,
+        })
+    }
+
+    /// Handle one complete input, returning the text to show for it
+    fn handle_input(&mut self, input: &str) -> String {
+        let input = input.trim();
+        if input.is_empty() {
+            return String::new();
+        }
+        self.history.push(input.to_string());
+
+        let result = if !input.starts_with("::") {
+            self.eval(input)
+        } else if let Some(program) = input.strip_prefix("::get-costs ") {
+            self.eval(program).map(|output| {
+                let cost = self
+                    .last_cost
+                    .as_ref()
+                    .expect("BUG: evaluation did not set a cost");
+                format!("{}\n{}", output, format_cost(cost))
+            })
+        } else {
+            let mut words = input.split_whitespace();
+            let command = words.next().unwrap_or_default();
+            let args: Vec<&str> = words.collect();
+            match (command, args.as_slice()) {
+                ("::help", []) => Ok(REPL_HELP.to_string()),
+                ("::deploy", [name, path]) => self.deploy(name, path),
+                ("::sender", []) => Ok(self.sender.to_string()),
+                ("::sender", [principal]) => self.set_sender(principal),
+                ("::advance-blocks", [count]) => self.advance_blocks(count),
+                ("::mint-stx", [principal, amount]) => self.mint_stx(principal, amount),
+                ("::balances", []) => Ok(self.balances()),
+                ("::get-costs", []) => match self.last_cost {
+                    Some(ref cost) => Ok(format_cost(cost)),
+                    None => Err("Nothing has been evaluated yet".into()),
+                },
+                ("::epoch", []) => Ok(self.epoch.to_string()),
+                ("::epoch", [epoch]) => self.set_epoch(epoch),
+                ("::clarity-version", []) => Ok(self.clarity_version.to_string()),
+                ("::clarity-version", [version]) => self.set_clarity_version(version),
+                ("::history", []) => Ok(self
+                    .history
+                    .iter()
+                    .enumerate()
+                    .map(|(i, input)| format!("{:>4}  {}", i + 1, input))
+                    .collect::<Vec<_>>()
+                    .join("\n")),
+                ("::save", [path]) => self.save(path),
+                ("::load", [path]) => ReplSession::load(path).map(|session| {
+                    *self = session;
+                    format!("Loaded session from {}", path)
+                }),
+                _ => Err(format!(
+                    "Unknown command or wrong arguments: {}\nType ::help for the list of commands.",
+                    input
+                )),
+            }
+        };
+        result.unwrap_or_else(|error| error)
+    }
+
+    /// Run `f` in a cost-tracking environment over the session's chain state
+    fn with_env<F, R>(&mut self, f: F) -> (R, ExecutionCost)
+    where
+        F: FnOnce(&mut OwnedEnvironment) -> R,
+    {
+        let mainnet = self.mainnet;
+        let mut db = self.marf.as_clarity_db();
+        let cost_track = LimitedCostTracker::new(
+            mainnet,
+            default_chain_id(mainnet),
+            if mainnet {
+                BLOCK_LIMIT_MAINNET_205.clone()
+            } else {
+                HELIUM_BLOCK_LIMIT_20.clone()
+            },
+            &mut db,
+            self.epoch,
+        )
+        .expect("FATAL: failed to load the cost functions");
+        let mut vm_env = OwnedEnvironment::new_cost_limited(
+            mainnet,
+            default_chain_id(mainnet),
+            db,
+            cost_track,
+            self.epoch,
+        );
+        let result = f(&mut vm_env);
+        let cost = vm_env.get_cost_total();
+        (result, cost)
+    }
+
+    fn parse_and_check(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        program: &str,
+    ) -> Result<(Vec<SymbolicExpression>, ContractAnalysis), String> {
+        let mut ast = build_ast_with_rules(
+            contract_identifier,
+            program,
+            &mut (),
+            self.clarity_version,
+            self.epoch,
+            ASTRules::PrecheckSize,
+        )
+        .map_err(|e| format!("Parse error:\n{}", e))?
+        .expressions;
+        let analysis = analysis::run_analysis(
+            contract_identifier,
+            &mut ast,
+            &mut self.marf.as_analysis_db(),
+            false,
+            LimitedCostTracker::new_free(),
+            self.epoch,
+            self.clarity_version,
+        )
+        .map_err(|(e, _)| format!("Type check error:\n{}", e))?;
+        Ok((ast, analysis))
+    }
+
+    /// Type-check and evaluate each expression of `program` as the sender, committing any
+    /// changes it makes
+    fn eval(&mut self, program: &str) -> Result<String, String> {
+        // `.name` refers to a contract deployed by the sender
+        let mut contract_identifier = QualifiedContractIdentifier::transient();
+        contract_identifier.issuer = self.sender.clone();
+        let (ast, _) = self.parse_and_check(&contract_identifier, program)?;
+
+        let sender = PrincipalData::from(self.sender.clone());
+        let contract_context = ContractContext::new(contract_identifier, self.clarity_version);
+        let (result, cost) = self.with_env(|vm_env| {
+            vm_env.execute_in_env(sender, None, Some(contract_context), |env| {
+                ast.iter()
+                    .map(|expression| eval(expression, env, &LocalContext::new()))
+                    .collect::<Result<Vec<_>, Error>>()
+            })
+        });
+        self.last_cost = Some(cost);
+
+        let (values, ..) = result.map_err(|e| format!("Execution error:\n{}", e))?;
+        Ok(values
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    fn deploy(&mut self, name: &str, path: &str) -> Result<String, String> {
+        let contract_name = ContractName::try_from(name.to_string())
+            .map_err(|e| format!("Invalid contract name: {}", e))?;
+        let contract_identifier =
+            QualifiedContractIdentifier::new(self.sender.clone(), contract_name);
+        let content =
+            fs::read_to_string(path).map_err(|e| format!("Error reading file: {}\n{}", path, e))?;
+        let (_, analysis) = self.parse_and_check(&contract_identifier, &content)?;
+
+        let clarity_version = self.clarity_version;
+        let (result, cost) = self.with_env(|vm_env| {
+            vm_env.initialize_versioned_contract(
+                contract_identifier.clone(),
+                clarity_version,
+                &content,
+                None,
+                ASTRules::PrecheckSize,
+            )
+        });
+        self.last_cost = Some(cost);
+        result.map_err(|e| format!("Initialization error:\n{}", e))?;
+
+        // only keep the analysis of a contract that was deployed
+        self.marf
+            .as_analysis_db()
+            .execute(|db| db.insert_contract(&contract_identifier, &analysis))
+            .map_err(|e| format!("Failed to store the contract analysis: {}", e))?;
+        self.add_principal(contract_identifier.clone().into());
+        Ok(format!("Deployed {}", contract_identifier))
+    }
+
+    fn add_principal(&mut self, principal: PrincipalData) {
+        if !self.principals.contains(&principal) {
+            self.principals.push(principal);
+        }
+    }
+
+    fn set_sender(&mut self, principal: &str) -> Result<String, String> {
+        self.sender = PrincipalData::parse_standard_principal(principal)
+            .map_err(|_| format!("Invalid standard principal: {}", principal))?;
+        self.add_principal(self.sender.clone().into());
+        Ok(format!("tx-sender is now {}", self.sender))
+    }
+
+    fn advance_blocks(&mut self, count: &str) -> Result<String, String> {
+        self.block_height = count
+            .parse::<u32>()
+            .ok()
+            .and_then(|count| self.block_height.checked_add(count))
+            .ok_or_else(|| format!("Invalid block count: {}", count))?;
+        self.marf.set_block_height(self.block_height);
+        Ok(format!("block-height is now {}", self.block_height))
+    }
+
+    fn mint_stx(&mut self, principal: &str, amount: &str) -> Result<String, String> {
+        let principal = PrincipalData::parse(principal)
+            .map_err(|_| format!("Invalid principal: {}", principal))?;
+        let amount = amount
+            .parse::<u128>()
+            .map_err(|_| format!("Invalid amount: {}", amount))?;
+
+        let mut db = self.marf.as_clarity_db();
+        db.begin();
+        let mut snapshot = db.get_stx_balance_snapshot_genesis(&principal);
+        snapshot.credit(amount);
+        snapshot.save();
+        if let Err(e) = db.increment_ustx_liquid_supply(amount) {
+            db.roll_back();
+            return Err(format!("Failed to mint STX: {}", e));
+        }
+        db.commit();
+
+        self.add_principal(principal);
+        Ok(self.balances())
+    }
+
+    fn balances(&mut self) -> String {
+        let mut db = self.marf.as_clarity_db();
+        db.begin();
+        let lines: Vec<_> = self
+            .principals
+            .iter()
+            .map(|principal| {
+                let balance = db.get_account_stx_balance(principal);
+                match balance.amount_locked() {
+                    0 => format!("{}: {} uSTX", principal, balance.get_total_balance()),
+                    locked => format!(
+                        "{}: {} uSTX ({} locked)",
+                        principal,
+                        balance.get_total_balance(),
+                        locked
+                    ),
+                }
+            })
+            .collect();
+        db.roll_back();
+        lines.join("\n")
+    }
+
+    fn set_epoch(&mut self, epoch: &str) -> Result<String, String> {
+        self.epoch = parse_epoch(epoch).ok_or_else(|| format!("Unsupported epoch: {}", epoch))?;
+        if self.clarity_version > ClarityVersion::default_for_epoch(self.epoch) {
+            self.clarity_version = ClarityVersion::default_for_epoch(self.epoch);
+        }
+        let mut db = self.marf.as_clarity_db();
+        db.begin();
+        db.set_clarity_epoch_version(self.epoch);
+        db.commit();
+        Ok(format!(
+            "Epoch is now {} ({})",
+            self.epoch, self.clarity_version
+        ))
+    }
+
+    fn set_clarity_version(&mut self, version: &str) -> Result<String, String> {
+        let clarity_version = parse_clarity_version(version)
+            .ok_or_else(|| format!("Unsupported Clarity version: {}", version))?;
+        if clarity_version > ClarityVersion::default_for_epoch(self.epoch) {
+            return Err(format!(
+                "{} is not supported in epoch {}",
+                clarity_version, self.epoch
+            ));
+        }
+        self.clarity_version = clarity_version;
+        Ok(format!("Clarity version is now {}", self.clarity_version))
+    }
+
+    /// Save the chain state and settings of this session to a SQLite database, replacing
+    /// the file if it exists
+    fn save(&self, path: &str) -> Result<String, String> {
+        let settings = ReplSessionSettings {
+            mainnet: self.mainnet,
+            epoch: self.epoch.to_string(),
+            clarity_version: clarity_version_number(self.clarity_version).to_string(),
+            sender: self.sender.to_string(),
+            block_height: self.block_height,
+            principals: self.principals.iter().map(|p| p.to_string()).collect(),
+            history: self.history.clone(),
+        };
+        let settings =
+            serde_json::to_string(&settings).expect("FATAL: failed to serialize settings");
+
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => {
+                return Err(format!("Failed to replace {}: {}", path, e));
+            }
+            _ => {}
+        }
+        self.marf
+            .save_to_file(path)
+            .and_then(|_| Connection::open(path))
+            .and_then(|conn| {
+                conn.execute(
+                    "CREATE TABLE repl_session (settings TEXT NOT NULL)",
+                    NO_PARAMS,
+                )?;
+                conn.execute(
+                    "INSERT INTO repl_session (settings) VALUES (?1)",
+                    &[&settings],
+                )
+            })
+            .map_err(|e| format!("Failed to save the session to {}: {}", path, e))?;
+        Ok(format!("Saved session to {}", path))
+    }
+}
+
 fn consume_arg(
     args: &mut Vec<String>,
     argnames: &[&str],
@@ -853,7 +1769,7 @@ fn consume_arg(
 }
 
 /// This function uses Clarity1 to parse the boot code.
-fn install_boot_code<C: ClarityStorage>(header_db: &CLIHeadersDB, marf: &mut C) {
+pub(crate) fn install_boot_code<C: ClarityStorage>(header_db: &CLIHeadersDB, marf: &mut C) {
     let mainnet = header_db.is_mainnet();
     let boot_code = if mainnet {
         *STACKS_BOOT_CODE_MAINNET_2_1
@@ -965,6 +1881,43 @@ pub fn add_costs(result: &mut serde_json::Value, costs: bool, runtime: Execution
     }
 }
 
+/// Report each function's worst-case cost, and whether it could exceed `block_limit`.
+pub fn add_worst_case_costs(
+    result: &mut serde_json::Value,
+    contract_analysis: &ContractAnalysis,
+    block_limit: &ExecutionCost,
+) {
+    let mut worst_case_costs = serde_json::Map::new();
+    for (name, worst_case) in contract_analysis.worst_case_costs.iter() {
+        worst_case_costs.insert(
+            name.to_string(),
+            json!({
+                "cost": serde_json::to_value(&worst_case.cost).unwrap(),
+                "calls_contracts": worst_case.calls_contracts,
+                "exceeds_block_limit": worst_case.cost.exceeds(block_limit),
+            }),
+        );
+    }
+    result["worst_case_costs"] = serde_json::Value::Object(worst_case_costs);
+}
+
+pub fn add_lint_warnings(result: &mut serde_json::Value, contract_analysis: &ContractAnalysis) {
+    let warnings: Vec<_> = contract_analysis
+        .lint_warnings
+        .iter()
+        .map(|warning| {
+            json!({
+                "rule": warning.rule.get_name(),
+                "level": serde_json::to_value(&warning.diagnostic.level).unwrap(),
+                "message": warning.diagnostic.message,
+                "spans": serde_json::to_value(&warning.diagnostic.spans).unwrap(),
+                "suggestion": warning.diagnostic.suggestion,
+            })
+        })
+        .collect();
+    result["lint"] = serde_json::Value::Array(warnings);
+}
+
 pub fn add_assets(result: &mut serde_json::Value, assets: bool, asset_map: AssetMap) {
     if assets {
         result["assets"] = asset_map.to_json();
@@ -1107,9 +2060,10 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
         "check" => {
             if args.len() < 2 {
                 eprintln!(
-                    "Usage: {} {} [program-file.clar] [--contract_id CONTRACT_ID] [--output_analysis] [--costs] [--testnet] (vm-state.db)",
+                    "Usage: {} {} [program-file.clar] [--contract_id CONTRACT_ID] [--output_analysis] [--costs] [--lint] [--lint_enable RULE,...] [--lint_disable RULE,...] [--testnet] (vm-state.db)",
                     invoked_by, args[0]
                 );
+                eprintln!("Lint rules: {}", LintRule::ALL_NAMES.join(", "));
                 panic_test!();
             }
 
@@ -1142,6 +2096,8 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 false
             };
 
+            let lint_settings = parse_lint_settings(&mut argv);
+
             // NOTE: ignored if we're using a DB
             let mut testnet_given = false;
             let mainnet = if let Ok(Some(_)) = consume_arg(&mut argv, &["--testnet"], false) {
@@ -1188,7 +2144,14 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
 
                     let result = at_chaintip(&argv[2], marf_kv, |mut marf| {
                         let result =
-                            run_analysis(&contract_id, &mut ast, &header_db, &mut marf, false);
+                            run_analysis(&contract_id, &mut ast, &header_db, &mut marf, false)
+                                .and_then(|analysis| {
+                                    if costs {
+                                        run_cost_analysis(analysis, &mut marf)
+                                    } else {
+                                        Ok(analysis)
+                                    }
+                                });
                         (marf, result)
                     });
                     result
@@ -1204,6 +2167,13 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                         &mut analysis_marf,
                         false,
                     )
+                    .and_then(|analysis| {
+                        if costs {
+                            run_cost_analysis(analysis, &mut analysis_marf)
+                        } else {
+                            Ok(analysis)
+                        }
+                    })
                 }
             };
 
@@ -1225,11 +2195,19 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 "message": "Checks passed."
             });
 
-            add_costs(
-                &mut result,
-                costs,
-                contract_analysis.take_contract_cost_tracker().get_total(),
-            );
+            if let Some(lint_settings) = lint_settings {
+                friendly_expect(
+                    Linter::run_with_settings(&mut contract_analysis, &lint_settings),
+                    "Failed to lint contract",
+                );
+                add_lint_warnings(&mut result, &contract_analysis);
+            }
+
+            let cost_tracker = contract_analysis.take_contract_cost_tracker();
+            add_costs(&mut result, costs, cost_tracker.get_total());
+            if costs {
+                add_worst_case_costs(&mut result, &contract_analysis, &cost_tracker.get_limit());
+            }
 
             if output_analysis {
                 result["analysis"] =
@@ -1244,69 +2222,46 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             } else {
                 true
             };
-            let mut marf = MemoryBackingStore::new();
-            let mut vm_env = OwnedEnvironment::new_free(
-                mainnet,
-                default_chain_id(mainnet),
-                marf.as_clarity_db(),
-                DEFAULT_CLI_EPOCH,
-            );
-            let mut placeholder_context = ContractContext::new(
-                QualifiedContractIdentifier::transient(),
-                ClarityVersion::Clarity2,
-            );
-            let mut exec_env = vm_env.get_exec_environment(None, None, &mut placeholder_context);
-            let mut analysis_marf = MemoryBackingStore::new();
-
-            let contract_id = QualifiedContractIdentifier::transient();
+            let mut session = match consume_arg(&mut argv, &["--load"], true) {
+                Ok(Some(path)) => {
+                    friendly_expect(ReplSession::load(&path), "Failed to load session")
+                }
+                Ok(None) => ReplSession::new(mainnet),
+                Err(_) => {
+                    eprintln!("Expected argument for --load");
+                    panic_test!();
+                }
+            };
 
             let mut stdout = io::stdout();
-
+            let mut input = String::new();
             loop {
-                let content: String = {
-                    let mut buffer = String::new();
-                    stdout.write(b"> ").unwrap_or_else(|e| {
-                        panic!("Failed to write stdout prompt string:\n{}", e);
-                    });
-                    stdout.flush().unwrap_or_else(|e| {
-                        panic!("Failed to flush stdout prompt string:\n{}", e);
-                    });
-                    match io::stdin().read_line(&mut buffer) {
-                        Ok(_) => buffer,
-                        Err(error) => {
-                            eprintln!("Error reading from stdin:\n{}", error);
-                            panic_test!();
-                        }
-                    }
-                };
-
-                let mut ast = match parse(&contract_id, &content, ClarityVersion::Clarity2) {
-                    Ok(val) => val,
-                    Err(error) => {
-                        println!("Parse error:\n{}", error);
-                        continue;
-                    }
-                };
-
-                match run_analysis_free(&contract_id, &mut ast, &mut analysis_marf, true) {
+                let prompt: &[u8] = if input.is_empty() { b"> " } else { b"... " };
+                stdout.write_all(prompt).unwrap_or_else(|e| {
+                    panic!("Failed to write stdout prompt string:\n{}", e);
+                });
+                stdout.flush().unwrap_or_else(|e| {
+                    panic!("Failed to flush stdout prompt string:\n{}", e);
+                });
+                match io::stdin().read_line(&mut input) {
+                    Ok(0) => break,
                     Ok(_) => (),
-                    Err((error, _)) => {
-                        println!("Type check error:\n{}", error);
-                        continue;
+                    Err(error) => {
+                        eprintln!("Error reading from stdin:\n{}", error);
+                        panic_test!();
                     }
                 }
+                if is_incomplete_input(&input) {
+                    continue;
+                }
 
-                let eval_result =
-                    match exec_env.eval_raw_with_rules(&content, ASTRules::PrecheckSize) {
-                        Ok(val) => val,
-                        Err(error) => {
-                            println!("Execution error:\n{}", error);
-                            continue;
-                        }
-                    };
-
-                println!("{}", eval_result);
+                let output = session.handle_input(&input);
+                if !output.is_empty() {
+                    println!("{}", output);
+                }
+                input.clear();
             }
+            (0, None)
         }
         "eval_raw" => {
             let content: String = {
@@ -1394,7 +2349,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
 
             let (_, _, result_and_cost) = in_block(header_db, marf_kv, |header_db, mut marf| {
                 let result_and_cost =
-                    with_env_costs(mainnet, &header_db, &mut marf, None, |vm_env| {
+                    with_env_costs(mainnet, &header_db, &mut marf, vec![], |vm_env| {
                         vm_env
                             .get_exec_environment(None, None, &mut placeholder_context)
                             .eval_read_only_with_rules(
@@ -1470,7 +2425,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                     mainnet,
                     &header_db,
                     &mut marf,
-                    coverage.as_mut(),
+                    eval_hooks(&mut coverage, &mut None, &mut None),
                     |vm_env| {
                         vm_env
                             .get_exec_environment(None, None, &mut placeholder_context)
@@ -1558,7 +2513,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             );
             let result_and_cost = at_block(chain_tip, marf_kv, |mut marf| {
                 let result_and_cost =
-                    with_env_costs(mainnet, &header_db, &mut marf, None, |vm_env| {
+                    with_env_costs(mainnet, &header_db, &mut marf, vec![], |vm_env| {
                         vm_env
                             .get_exec_environment(None, None, &mut placeholder_context)
                             .eval_read_only_with_rules(
@@ -1619,6 +2574,12 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 } else {
                     false
                 };
+            let debug = if let Ok(Some(_)) = consume_arg(&mut argv, &["--debug"], false) {
+                true
+            } else {
+                false
+            };
+            let profile_args = consume_profile_args(&mut argv);
             if argv.len() < 4 {
                 eprintln!(
                     "Usage: {} {} [--costs] [--assets] [--output_analysis] [contract-identifier] [contract-definition.clar] [vm-state.db]",
@@ -1677,6 +2638,14 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             } else {
                 None
             };
+            let mut debugger = if debug {
+                let mut debugger = make_debugger();
+                debugger.add_source(contract_identifier.clone(), &contract_content);
+                Some(debugger)
+            } else {
+                None
+            };
+            let mut profiler = profile_args.as_ref().map(|_| CostProfiler::new());
             let (_, _, analysis_result_and_cost) =
                 in_block(header_db, marf_kv, |header_db, mut marf| {
                     let analysis_result =
@@ -1688,7 +2657,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                                 mainnet,
                                 &header_db,
                                 &mut marf,
-                                coverage.as_mut(),
+                                eval_hooks(&mut coverage, &mut debugger, &mut profiler),
                                 |vm_env| {
                                     vm_env.initialize_versioned_contract(
                                         contract_identifier,
@@ -1704,6 +2673,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                         }
                     }
                 });
+            save_profile(profile_args, profiler);
 
             match analysis_result_and_cost {
                 Ok((contract_analysis, (Ok((_x, asset_map, events)), cost))) => {
@@ -1769,6 +2739,13 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 false
             };
 
+            let debug = if let Ok(Some(_)) = consume_arg(&mut argv, &["--debug"], false) {
+                true
+            } else {
+                false
+            };
+            let profile_args = consume_profile_args(&mut argv);
+
             if argv.len() < 5 {
                 eprintln!("Usage: {} {} [--costs] [--assets] [vm-state.db] [contract-identifier] [public-function-name] [sender-address] [args...]", invoked_by, argv[0]);
                 panic_test!();
@@ -1820,12 +2797,14 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             } else {
                 None
             };
+            let mut debugger = if debug { Some(make_debugger()) } else { None };
+            let mut profiler = profile_args.as_ref().map(|_| CostProfiler::new());
             let (_, _, result_and_cost) = in_block(header_db, marf_kv, |header_db, mut marf| {
                 let result_and_cost = with_env_costs(
                     mainnet,
                     &header_db,
                     &mut marf,
-                    coverage.as_mut(),
+                    eval_hooks(&mut coverage, &mut debugger, &mut profiler),
                     |vm_env| {
                         vm_env.execute_transaction(
                             sender,
@@ -1839,6 +2818,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 let (result, cost) = result_and_cost;
                 (header_db, marf, (result, cost))
             });
+            save_profile(profile_args, profiler);
 
             match result_and_cost {
                 (Ok((x, asset_map, events)), cost) => {
@@ -1898,6 +2878,246 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 }
             }
         }
+        "debug" => {
+            if args.len() < 2 || (args[1] != "launch" && args[1] != "execute") {
+                eprintln!(
+                    "Usage: {} {} [launch|execute] [args...]
+
+Runs `launch` or `execute` with the same arguments, pausing before the first expression is
+evaluated to read debugger commands from stdin.  Type `help` at the prompt for a list of commands.",
+                    invoked_by, args[0]
+                );
+                panic_test!();
+            }
+            let mut argv: Vec<String> = args[1..].to_vec();
+            argv.push("--debug".to_string());
+            invoke_command(invoked_by, &argv)
+        }
+        "profile" => {
+            let mut argv: Vec<String> = args.to_vec();
+            let metric = if let Ok(metric) = consume_arg(&mut argv, &["--metric"], true) {
+                metric
+            } else {
+                None
+            };
+            if argv.len() < 3 || (argv[2] != "launch" && argv[2] != "execute") {
+                eprintln!(
+                    "Usage: {} {} [--metric runtime|read_count|read_length|write_count|write_length] [folded-stacks-output-file] [launch|execute] [args...]
+
+Runs `launch` or `execute` with the same arguments, then prints the cost of each function to
+stderr, and writes the chosen cost (runtime by default) of each call stack to the output file
+in the folded format taken by flamegraph tools.",
+                    invoked_by, argv[0]
+                );
+                panic_test!();
+            }
+            let mut profiled_argv: Vec<String> = argv[2..].to_vec();
+            profiled_argv.push("--profile".to_string());
+            profiled_argv.push(argv[1].clone());
+            if let Some(metric) = metric {
+                profiled_argv.push("--profile_metric".to_string());
+                profiled_argv.push(metric);
+            }
+            invoke_command(invoked_by, &profiled_argv)
+        }
+        "test" => {
+            let mut argv: Vec<String> = args.to_vec();
+            let lcov_file = if let Ok(lcov_file) = consume_arg(&mut argv, &["--lcov"], true) {
+                lcov_file
+            } else {
+                None
+            };
+            if argv.len() < 2 {
+                eprintln!(
+                    "Usage: {} {} [--lcov lcov-output-file] [test-manifest.json]",
+                    invoked_by, argv[0]
+                );
+                eprintln!("   test-manifest.json is a JSON object like {{ contracts: [...], tests: [...], sender: \"ST...\", block_height: 1, balances: [...] }}.");
+                eprintln!("   `contracts` and `tests` are arrays of {{ contract_id: \"ST....name\", path: \"name.clar\" }} like objects, deployed in order.");
+                eprintln!("   Every public function named `test-...` in a `tests` contract is run, and passes if it returns `(ok ...)`.");
+                eprintln!("   `sender`, `block_height` and `balances` (like initial-allocations.json) are optional, and can be set for one test");
+                eprintln!("   with a `settings` object in its contract's entry, like {{ \"test-name\": {{ sender: \"ST...\" }} }}.");
+                panic_test!();
+            }
+
+            let manifest_file = &argv[1];
+            let manifest_json = friendly_expect(
+                fs::read_to_string(manifest_file),
+                &format!("Error reading file: {}", manifest_file),
+            );
+            let manifest: TestManifest =
+                friendly_expect(serde_json::from_str(&manifest_json), "Failure parsing JSON");
+            let manifest_dir = Path::new(manifest_file)
+                .parent()
+                .unwrap_or_else(|| Path::new(""));
+
+            run_tests(&manifest, manifest_dir, lcov_file)
+        }
+        "fmt" => {
+            let mut argv: Vec<String> = args.to_vec();
+            let check = if let Ok(Some(_)) = consume_arg(&mut argv, &["--check"], false) {
+                true
+            } else {
+                false
+            };
+            let write = if let Ok(Some(_)) = consume_arg(&mut argv, &["--write"], false) {
+                true
+            } else {
+                false
+            };
+            if argv.len() < 2 || (!check && !write && argv.len() > 2) {
+                eprintln!(
+                    "Usage: {} {} [--check | --write] [program-file.clar...]",
+                    invoked_by, argv[0]
+                );
+                eprintln!("   Without options, prints the formatted source of a single file (or `-` for stdin).");
+                eprintln!("   --check exits with an error if any file is not formatted, and --write formats files in place.");
+                panic_test!();
+            }
+
+            let mut unformatted = vec![];
+            for path in argv[1..].iter() {
+                let content: String = if path == "-" {
+                    let mut buffer = String::new();
+                    friendly_expect(
+                        io::stdin().read_to_string(&mut buffer),
+                        "Error reading from stdin.",
+                    );
+                    buffer
+                } else {
+                    friendly_expect(
+                        fs::read_to_string(path),
+                        &format!("Error reading file: {}", path),
+                    )
+                };
+                let formatted = friendly_expect(
+                    format_source(&content),
+                    &format!("Failed to format {}", path),
+                );
+
+                if !check && !write {
+                    print!("{}", formatted);
+                    return (0, None);
+                }
+                if formatted != content {
+                    if write && path != "-" {
+                        friendly_expect(
+                            fs::write(path, &formatted),
+                            &format!("Error writing file: {}", path),
+                        );
+                    }
+                    unformatted.push(path.clone());
+                }
+            }
+
+            if check && !unformatted.is_empty() {
+                (
+                    1,
+                    Some(json!({
+                        "message": "Files are not formatted.",
+                        "unformatted": unformatted,
+                    })),
+                )
+            } else if write {
+                (
+                    0,
+                    Some(json!({
+                        "message": "Files formatted.",
+                        "formatted": unformatted,
+                    })),
+                )
+            } else {
+                (0, Some(json!({ "message": "Files are formatted." })))
+            }
+        }
+        "bindgen" => {
+            let mut argv: Vec<String> = args.to_vec();
+            let language = match consume_arg(&mut argv, &["--typescript"], false) {
+                Ok(Some(_)) => BindingsLanguage::TypeScript,
+                _ => BindingsLanguage::Rust,
+            };
+            let contract_id = if let Ok(optarg) = consume_arg(&mut argv, &["--contract_id"], true) {
+                optarg.map(|optarg_str| {
+                    friendly_expect(
+                        QualifiedContractIdentifier::parse(&optarg_str),
+                        &format!("Error parsing contract identifier '{}", &optarg_str),
+                    )
+                })
+            } else {
+                eprintln!("Expected argument for --contract-id");
+                panic_test!();
+            };
+            let mainnet = !matches!(consume_arg(&mut argv, &["--testnet"], false), Ok(Some(_)));
+            if argv.len() != 2 {
+                eprintln!(
+                    "Usage: {} {} [--typescript] [--contract_id CONTRACT_ID] [--testnet] [program-file.clar | interface.json]",
+                    invoked_by, argv[0]
+                );
+                eprintln!("   Prints typed Rust (or TypeScript) bindings for a contract, given its source or its interface JSON.");
+                panic_test!();
+            }
+
+            let path = &argv[1];
+            let content = friendly_expect(
+                fs::read_to_string(path),
+                &format!("Error reading file: {}", path),
+            );
+            let contract_name = match contract_id {
+                Some(ref contract_id) => contract_id.name.to_string(),
+                None => Path::new(path)
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .unwrap_or("contract")
+                    .to_string(),
+            };
+
+            let (interface, print_events) = if path.ends_with(".json") {
+                // an interface carries no type map, so printed values can't be typed
+                let interface: ContractInterface = friendly_expect(
+                    serde_json::from_str(&content),
+                    "Failed to parse contract interface",
+                );
+                (interface, vec![])
+            } else {
+                let contract_id =
+                    contract_id.unwrap_or_else(QualifiedContractIdentifier::transient);
+                let mut ast = friendly_expect(
+                    parse(&contract_id, &content, ClarityVersion::Clarity2),
+                    "Failed to parse program",
+                );
+                let header_db = CLIHeadersDB::new_memory(mainnet);
+                let mut analysis_marf = MemoryBackingStore::new();
+                install_boot_code(&header_db, &mut analysis_marf);
+                let contract_analysis = match run_analysis(
+                    &contract_id,
+                    &mut ast,
+                    &header_db,
+                    &mut analysis_marf,
+                    false,
+                ) {
+                    Ok(contract_analysis) => contract_analysis,
+                    Err((e, _)) => {
+                        let result = json!({
+                            "message": "Checks failed.",
+                            "error": {
+                                "analysis": serde_json::to_value(&e.diagnostic).unwrap(),
+                            }
+                        });
+                        return (1, Some(result));
+                    }
+                };
+                (
+                    build_contract_interface(&contract_analysis),
+                    infer_print_event_types(&contract_analysis),
+                )
+            };
+
+            print!(
+                "{}",
+                generate_bindings(language, &contract_name, &interface, &print_events)
+            );
+            (0, None)
+        }
         "make_lcov" => {
             let mut register_files = vec![];
             let mut coverage_files = vec![];
@@ -2297,6 +3517,565 @@ mod test {
         assert!(result["costs"] != json!(null));
     }
 
+    #[test]
+    fn test_profile() {
+        let db_name = format!("/tmp/db_{}", rand::thread_rng().gen::<i32>());
+        let launch_profile = format!("/tmp/profile_launch_{}", rand::thread_rng().gen::<i32>());
+        let execute_profile = format!("/tmp/profile_execute_{}", rand::thread_rng().gen::<i32>());
+
+        eprintln!("initialize");
+        invoke_command("test", &["initialize".to_string(), db_name.clone()]);
+
+        eprintln!("profile launch tokens");
+        let invoked = invoke_command(
+            "test",
+            &[
+                "profile".to_string(),
+                launch_profile.clone(),
+                "launch".to_string(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "sample-contracts/tokens.clar".to_string(),
+                db_name.clone(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 0);
+        assert!(result["message"].as_str().unwrap().len() > 0);
+
+        // the top-level expressions call token-credit!, which calls get-balance
+        let folded = fs::read_to_string(&launch_profile).unwrap();
+        assert!(folded.lines().all(|line| line.starts_with("<top-level>")));
+        assert!(folded.lines().any(|line| line.starts_with(
+            "<top-level>;S1G2081040G2081040G2081040G208105NK8PE5.tokens:token-credit!;"
+        )));
+        assert!(folded.contains("tokens:get-balance;"));
+
+        eprintln!("profile execute mint!");
+        let invoked = invoke_command(
+            "test",
+            &[
+                "profile".to_string(),
+                "--metric".to_string(),
+                "write_count".to_string(),
+                execute_profile.clone(),
+                "execute".to_string(),
+                db_name.clone(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens".to_string(),
+                "mint!".to_string(),
+                "SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR".to_string(),
+                "u1000".to_string(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 0);
+        assert_eq!(result["success"], true);
+
+        // the only write is the map-set in token-credit!
+        let folded = fs::read_to_string(&execute_profile).unwrap();
+        assert_eq!(
+            folded,
+            "<top-level>;S1G2081040G2081040G2081040G208105NK8PE5.tokens:mint!;let;S1G2081040G2081040G2081040G208105NK8PE5.tokens:token-credit!;if;let;map-set 1\n"
+        );
+    }
+
+    #[test]
+    fn test_check_worst_case_costs() {
+        let db_name = format!("/tmp/db_{}", rand::thread_rng().gen::<i32>());
+        let contract_file = format!("/tmp/worst_case_{}.clar", rand::thread_rng().gen::<i32>());
+        fs::write(
+            &contract_file,
+            "(define-trait callee ((ping () (response bool uint))))
+(define-data-var total uint u0)
+(define-map deposits principal uint)
+(define-public (noop)
+  (ok true))
+(define-public (deposit (amount uint))
+  (begin
+    (map-set deposits tx-sender (+ amount (default-to u0 (map-get? deposits tx-sender))))
+    (if (> amount u100)
+        (ok (var-set total (+ (var-get total) amount)))
+        (ok false))))
+(define-read-only (sum (items (list 10 uint)))
+  (fold + items u0))
+(define-public (forward (target <callee>))
+  (contract-call? target ping))
+",
+        )
+        .unwrap();
+
+        invoke_command("test", &["initialize".to_string(), db_name.clone()]);
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "check".to_string(),
+                "--costs".to_string(),
+                contract_file.clone(),
+                db_name.clone(),
+            ],
+        );
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+
+        assert_eq!(exit, 0);
+        let worst_case_costs = result["worst_case_costs"].as_object().unwrap();
+        let names: Vec<_> = worst_case_costs.keys().collect();
+        assert_eq!(names, vec!["deposit", "forward", "noop", "sum"]);
+        assert_eq!(worst_case_costs["forward"]["calls_contracts"], true);
+        assert_eq!(worst_case_costs["deposit"]["calls_contracts"], false);
+        assert_eq!(worst_case_costs["deposit"]["exceeds_block_limit"], false);
+        assert!(
+            worst_case_costs["noop"]["cost"]["runtime"]
+                .as_u64()
+                .unwrap()
+                > 0
+        );
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "launch".to_string(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.worst-case".to_string(),
+                contract_file.clone(),
+                db_name.clone(),
+            ],
+        );
+        assert_eq!(invoked.0, 0);
+
+        let mut actual_costs = HashMap::new();
+        for (function, args) in [("noop", vec![]), ("deposit", vec!["u200".to_string()])] {
+            let mut argv = vec![
+                "execute".to_string(),
+                "--costs".to_string(),
+                db_name.clone(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.worst-case".to_string(),
+                function.to_string(),
+                "SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR".to_string(),
+            ];
+            argv.extend(args);
+            let invoked = invoke_command("test", &argv);
+            let result = invoked.1.unwrap();
+            assert_eq!(invoked.0, 0);
+            assert_eq!(result["success"], true);
+            actual_costs.insert(function, result["costs"].clone());
+        }
+
+        // executing a function also pays to load the contract, which the bound excludes:
+        //  `noop` is straight-line code, so its bound is exact and the difference is that load.
+        for dimension in [
+            "runtime",
+            "read_count",
+            "read_length",
+            "write_count",
+            "write_length",
+        ] {
+            let noop_bound = worst_case_costs["noop"]["cost"][dimension]
+                .as_u64()
+                .unwrap();
+            let noop_actual = actual_costs["noop"][dimension].as_u64().unwrap();
+            assert!(noop_actual >= noop_bound, "{}", dimension);
+            let load_cost = noop_actual - noop_bound;
+
+            let deposit_bound = worst_case_costs["deposit"]["cost"][dimension]
+                .as_u64()
+                .unwrap();
+            let deposit_actual = actual_costs["deposit"][dimension].as_u64().unwrap();
+            assert!(
+                deposit_actual <= deposit_bound + load_cost,
+                "{}: {} > {} + {}",
+                dimension,
+                deposit_actual,
+                deposit_bound,
+                load_cost
+            );
+        }
+    }
+
+    #[test]
+    fn test_fmt() {
+        let source_file = format!("/tmp/fmt_{}.clar", rand::thread_rng().gen::<i32>());
+        fs::write(
+            &source_file,
+            ";; a counter
+(define-data-var   count uint u0)
+(define-public (increment) (begin (var-set count (+ (var-get count) u1)) (ok (var-get count))))
+",
+        )
+        .unwrap();
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "fmt".to_string(),
+                "--check".to_string(),
+                source_file.clone(),
+            ],
+        );
+        assert_eq!(invoked.0, 1);
+        assert_eq!(
+            invoked.1.unwrap()["unformatted"],
+            json!([source_file.clone()])
+        );
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "fmt".to_string(),
+                "--write".to_string(),
+                source_file.clone(),
+            ],
+        );
+        assert_eq!(invoked.0, 0);
+        assert_eq!(
+            fs::read_to_string(&source_file).unwrap(),
+            ";; a counter
+(define-data-var count uint u0)
+(define-public (increment)
+  (begin (var-set count (+ (var-get count) u1)) (ok (var-get count))))
+"
+        );
+
+        // sample contracts can be formatted, and formatting is idempotent
+        let mut files = vec![source_file];
+        for sample in ["tokens", "names"] {
+            let file = format!(
+                "/tmp/fmt_{}_{}.clar",
+                sample,
+                rand::thread_rng().gen::<i32>()
+            );
+            fs::copy(format!("sample-contracts/{}.clar", sample), &file).unwrap();
+            files.push(file);
+        }
+        let mut argv = vec!["fmt".to_string(), "--write".to_string()];
+        argv.extend(files.iter().cloned());
+        assert_eq!(invoke_command("test", &argv).0, 0);
+
+        let mut argv = vec!["fmt".to_string(), "--check".to_string()];
+        argv.extend(files.iter().cloned());
+        let invoked = invoke_command("test", &argv);
+        assert_eq!(invoked.0, 0);
+        assert_eq!(invoked.1.unwrap()["message"], "Files are formatted.");
+    }
+
+    #[test]
+    fn test_check_lint() {
+        let contract_file = format!("/tmp/lint_{}.clar", rand::thread_rng().gen::<i32>());
+        fs::write(
+            &contract_file,
+            "(define-private (helper) u1)
+(define-public (pay (amount uint))
+  (let ((unused u1))
+    (as-contract (stx-transfer? amount tx-sender 'S1G2081040G2081040G2081040G208105NK8PE5))))
+",
+        )
+        .unwrap();
+
+        let invoked = invoke_command("test", &["check".to_string(), contract_file.clone()]);
+        assert_eq!(invoked.0, 0);
+        assert!(invoked.1.unwrap().get("lint").is_none());
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "check".to_string(),
+                "--lint".to_string(),
+                contract_file.clone(),
+            ],
+        );
+        assert_eq!(invoked.0, 0);
+        let result = invoked.1.unwrap();
+        let rules: Vec<_> = result["lint"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|warning| warning["rule"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                "unused-private-function",
+                "unused-binding",
+                "as-contract-transfer"
+            ]
+        );
+        assert_eq!(result["lint"][1]["level"], "Warning");
+        assert_eq!(result["lint"][1]["spans"][0]["start_line"], 3);
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "check".to_string(),
+                "--lint_disable".to_string(),
+                "unused-binding,unused-private-function".to_string(),
+                contract_file.clone(),
+            ],
+        );
+        let result = invoked.1.unwrap();
+        assert_eq!(result["lint"].as_array().unwrap().len(), 1);
+        assert_eq!(result["lint"][0]["rule"], "as-contract-transfer");
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "check".to_string(),
+                "--lint_enable".to_string(),
+                "unused-binding".to_string(),
+                contract_file.clone(),
+            ],
+        );
+        let result = invoked.1.unwrap();
+        assert_eq!(result["lint"].as_array().unwrap().len(), 1);
+        assert_eq!(result["lint"][0]["rule"], "unused-binding");
+    }
+
+    #[test]
+    fn test_repl_session() {
+        let contract_file = format!("/tmp/repl_{}.clar", rand::thread_rng().gen::<i32>());
+        fs::write(
+            &contract_file,
+            "(define-data-var count uint u0)
+(define-public (increment)
+  (begin (var-set count (+ (var-get count) u1)) (ok (var-get count))))
+",
+        )
+        .unwrap();
+
+        let mut session = ReplSession::new(false);
+        assert_eq!(session.handle_input("(+ 1 2)"), "3");
+        assert_eq!(
+            session.handle_input(&format!("::deploy counter {}", contract_file)),
+            "Deployed S1G2081040G2081040G2081040G208105NK8PE5.counter"
+        );
+        assert!(session
+            .handle_input(&format!("::deploy counter {}", contract_file))
+            .starts_with("Initialization error:"));
+
+        let input = "(list\n  (contract-call? .counter increment) ;; comment )\n";
+        assert!(is_incomplete_input(input));
+        let input = format!("{}  (contract-call? .counter increment))", input);
+        assert!(!is_incomplete_input(&input));
+        assert_eq!(session.handle_input(&input), "((ok u1) (ok u2))");
+        assert_eq!(session.handle_input("(+ 1 2) \")(\""), "3\n\")(\"");
+        assert!(session.handle_input("::get-costs").starts_with("runtime: "));
+
+        assert_eq!(
+            session.handle_input("::advance-blocks 5"),
+            "block-height is now 5"
+        );
+        assert_eq!(session.handle_input("block-height"), "u5");
+
+        session.handle_input("::mint-stx ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM 1000");
+        session.handle_input("::sender ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM");
+        assert_eq!(
+            session.handle_input(
+                "(stx-transfer? u100 tx-sender 'S1G2081040G2081040G2081040G208105NK8PE5)"
+            ),
+            "(ok true)"
+        );
+        assert_eq!(
+            session.handle_input("::balances"),
+            "S1G2081040G2081040G2081040G208105NK8PE5: 100 uSTX
+S1G2081040G2081040G2081040G208105NK8PE5.counter: 0 uSTX
+ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM: 900 uSTX"
+        );
+
+        assert_eq!(
+            session.handle_input("::epoch 2.05"),
+            "Epoch is now 2.05 (Clarity 1)"
+        );
+        assert_eq!(
+            session.handle_input("::clarity-version 2"),
+            "Clarity 2 is not supported in epoch 2.05"
+        );
+        let output = session.handle_input(
+            "::get-costs (contract-call? 'S1G2081040G2081040G2081040G208105NK8PE5.counter increment)",
+        );
+        assert!(output.starts_with("(ok u3)\nruntime: "));
+
+        let session_file = format!("/tmp/repl_{}.db", rand::thread_rng().gen::<i32>());
+        assert_eq!(
+            session.handle_input(&format!("::save {}", session_file)),
+            format!("Saved session to {}", session_file)
+        );
+        let mut session = ReplSession::load(&session_file).unwrap();
+        assert_eq!(session.history.len(), 16);
+        assert_eq!(
+            session.handle_input("::sender"),
+            "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM"
+        );
+        assert_eq!(session.handle_input("::epoch"), "2.05");
+        assert_eq!(session.handle_input("block-height"), "u5");
+        assert_eq!(
+            session.handle_input(
+                "(contract-call? 'S1G2081040G2081040G2081040G208105NK8PE5.counter increment)"
+            ),
+            "(ok u4)"
+        );
+    }
+
+    #[test]
+    fn test_bindgen() {
+        for args in [vec![], vec!["--typescript".to_string()]] {
+            let mut argv = vec!["bindgen".to_string()];
+            argv.extend(args);
+            argv.push("sample-contracts/tokens.clar".to_string());
+            assert_eq!(invoke_command("test", &argv), (0, None));
+        }
+
+        // bindings can also be generated from the interface that `check` outputs
+        let invoked = invoke_command(
+            "test",
+            &[
+                "check".to_string(),
+                "--output_analysis".to_string(),
+                "sample-contracts/tokens-ft.clar".to_string(),
+            ],
+        );
+        let interface_file = format!("/tmp/bindgen_{}.json", rand::thread_rng().gen::<i32>());
+        fs::write(&interface_file, invoked.1.unwrap()["analysis"].to_string()).unwrap();
+        let invoked = invoke_command(
+            "test",
+            &[
+                "bindgen".to_string(),
+                "--contract_id".to_string(),
+                "S1G2081040G2081040G2081040G208105NK8PE5.tokens-ft".to_string(),
+                interface_file,
+            ],
+        );
+        assert_eq!(invoked, (0, None));
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "bindgen".to_string(),
+                "sample-contracts/tokens-ft-mint.clar".to_string(),
+            ],
+        );
+        assert_eq!(invoked.0, 1);
+        assert_eq!(invoked.1.unwrap()["message"], "Checks failed.");
+    }
+
+    #[test]
+    fn test_run_tests() {
+        let test_dir = format!("/tmp/clarity_tests_{}", rand::thread_rng().gen::<i32>());
+        fs::create_dir_all(&test_dir).unwrap();
+        fs::write(
+            format!("{}/counter.clar", test_dir),
+            "(define-data-var count uint u0)
+(define-public (increment)
+  (begin
+    (asserts! (< (var-get count) u100) (err u0))
+    (var-set count (+ (var-get count) u1))
+    (ok (var-get count))))
+(define-read-only (get-count)
+  (var-get count))
+",
+        )
+        .unwrap();
+        fs::write(
+            format!("{}/counter-test.clar", test_dir),
+            "(define-public (test-a-increment)
+  (begin
+    (try! (contract-call? .counter increment))
+    (asserts! (is-eq (contract-call? .counter get-count) u1) (err u1))
+    (ok true)))
+(define-public (test-b-isolated)
+  (begin
+    (asserts! (is-eq (contract-call? .counter get-count) u0) (err u2))
+    (asserts! (is-eq tx-sender 'S1G2081040G2081040G2081040G208105NK8PE5) (err u3))
+    (asserts! (is-eq block-height u5) (err u4))
+    (asserts! (is-eq (stx-get-balance tx-sender) u500) (err u5))
+    (ok true)))
+(define-public (test-c-failure)
+  (err u100))
+(define-public (test-d-settings)
+  (begin
+    (asserts! (is-eq tx-sender 'SP3D6PV2ACBPEKYJTCMH7HEN02KP87QSP8KTEH335) (err u3))
+    (asserts! (is-eq block-height u10) (err u4))
+    (asserts! (is-eq (stx-get-balance tx-sender) u1000) (err u5))
+    (ok true)))
+(define-public (not-a-test)
+  (err u200))
+",
+        )
+        .unwrap();
+        let manifest_file = format!("{}/manifest.json", test_dir);
+        fs::write(
+            &manifest_file,
+            r#"{
+  "contracts": [
+    { "contract_id": "S1G2081040G2081040G2081040G208105NK8PE5.counter", "path": "counter.clar" }
+  ],
+  "tests": [
+    {
+      "contract_id": "S1G2081040G2081040G2081040G208105NK8PE5.counter-test",
+      "path": "counter-test.clar",
+      "settings": {
+        "test-d-settings": {
+          "sender": "SP3D6PV2ACBPEKYJTCMH7HEN02KP87QSP8KTEH335",
+          "block_height": 10,
+          "balances": [{ "principal": "SP3D6PV2ACBPEKYJTCMH7HEN02KP87QSP8KTEH335", "amount": 1000 }]
+        }
+      }
+    }
+  ],
+  "block_height": 5,
+  "balances": [{ "principal": "S1G2081040G2081040G2081040G208105NK8PE5", "amount": 500 }]
+}"#,
+        )
+        .unwrap();
+        let lcov_file = format!("{}/coverage.lcov", test_dir);
+
+        let invoked = invoke_command(
+            "test",
+            &[
+                "test".to_string(),
+                manifest_file,
+                "--lcov".to_string(),
+                lcov_file.clone(),
+            ],
+        );
+
+        let exit = invoked.0;
+        let result = invoked.1.unwrap();
+        eprintln!("{}", serde_json::to_string(&result).unwrap());
+
+        assert_eq!(exit, 1);
+        assert_eq!(result["message"], "3 passed, 1 failed");
+        let tests = result["tests"].as_array().unwrap();
+        let outcomes: Vec<_> = tests
+            .iter()
+            .map(|test| {
+                (
+                    test["test"].as_str().unwrap(),
+                    test["success"].as_bool().unwrap(),
+                    test["output"].as_str().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                ("test-a-increment", true, "(ok true)"),
+                ("test-b-isolated", true, "(ok true)"),
+                ("test-c-failure", false, "(err u100)"),
+                ("test-d-settings", true, "(ok true)"),
+            ]
+        );
+
+        let lcov = fs::read_to_string(&lcov_file).unwrap();
+        assert!(lcov.contains(&format!("SF:{}/counter.clar\n", test_dir)));
+        assert!(lcov.contains(&format!("SF:{}/counter-test.clar\n", test_dir)));
+        // `increment` ran once, in the first test
+        assert!(lcov.contains("DA:5,1\n"));
+    }
+
     #[test]
     fn test_assets() {
         let db_name = format!("/tmp/db_{}", rand::thread_rng().gen::<i32>());
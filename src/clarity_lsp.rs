@@ -0,0 +1,789 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A Language Server Protocol server for Clarity, speaking JSON-RPC over
+//! stdin/stdout. Documents are re-analyzed in full whenever they change.
+
+use std::collections::HashMap;
+use std::io;
+use std::io::{BufRead, Write};
+
+use serde_json::Value;
+
+use crate::clarity::vm::analysis::{run_analysis, ContractAnalysis};
+use crate::clarity::vm::ast::build_ast_with_diagnostics;
+use crate::clarity::vm::costs::LimitedCostTracker;
+use crate::clarity::vm::diagnostic::{Diagnostic, Level};
+use crate::clarity::vm::docs::{
+    make_api_reference, make_define_reference, make_keyword_reference, FunctionAPI,
+};
+use crate::clarity::vm::functions::define::{DefineFunctions, DefineFunctionsParsed};
+use crate::clarity::vm::functions::NativeFunctions;
+use crate::clarity::vm::representations::{
+    ClarityName, Span, SymbolicExpression, SymbolicExpressionType,
+};
+use crate::clarity::vm::types::{FunctionType, QualifiedContractIdentifier};
+use crate::clarity::vm::variables::NativeVariables;
+use crate::clarity::vm::ClarityVersion;
+use crate::clarity_cli::{install_boot_code, CLIHeadersDB, DEFAULT_CLI_EPOCH};
+use crate::clarity_vm::database::MemoryBackingStore;
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+// LSP enumerations
+const TEXT_DOCUMENT_SYNC_FULL: u64 = 1;
+const COMPLETION_ITEM_KIND_FUNCTION: u64 = 3;
+const COMPLETION_ITEM_KIND_KEYWORD: u64 = 14;
+
+/// An open document: its expressions, and its analysis if it type checks.
+struct Document {
+    expressions: Vec<SymbolicExpression>,
+    analysis: Option<ContractAnalysis>,
+}
+
+pub struct LanguageServer {
+    documents: HashMap<String, Document>,
+    /// Analysis database with the boot contracts installed, so that contracts
+    ///  may call them. Created when the first document is analyzed.
+    boot_code: Option<MemoryBackingStore>,
+    shutdown_requested: bool,
+    exit_code: Option<i32>,
+}
+
+/// Read one JSON-RPC message framed by a `Content-Length` header.
+/// Returns `Ok(None)` once the input is closed.
+pub fn read_message<R: BufRead>(input: &mut R) -> io::Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                let length = value.trim().parse::<usize>().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidData, "invalid Content-Length header")
+                })?;
+                content_length = Some(length);
+            }
+        }
+    }
+
+    let content_length = content_length.ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidData, "missing Content-Length header")
+    })?;
+    let mut body = vec![0u8; content_length];
+    input.read_exact(&mut body)?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Write one JSON-RPC message, framed by a `Content-Length` header.
+pub fn write_message<W: Write>(output: &mut W, message: &Value) -> io::Result<()> {
+    let body = serde_json::to_string(message)?;
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+/// Serve LSP requests from `input` until the client sends `exit`.
+/// Returns the process exit code: 0 if the client shut the server down
+///  cleanly, and 1 otherwise.
+pub fn run<R: BufRead, W: Write>(input: &mut R, output: &mut W) -> i32 {
+    let mut server = LanguageServer::new();
+    loop {
+        let message = match read_message(input) {
+            Ok(Some(message)) => message,
+            Ok(None) => return 1,
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                warn!("Failed to read LSP message: {}", &e);
+                let reply = error_response(Value::Null, PARSE_ERROR, &e.to_string());
+                if write_message(output, &reply).is_err() {
+                    return 1;
+                }
+                continue;
+            }
+            Err(e) => {
+                warn!("Failed to read from LSP client: {:?}", &e);
+                return 1;
+            }
+        };
+
+        for reply in server.handle_message(message) {
+            if let Err(e) = write_message(output, &reply) {
+                warn!("Failed to write to LSP client: {:?}", &e);
+                return 1;
+            }
+        }
+
+        if let Some(exit_code) = server.exit_code {
+            return exit_code;
+        }
+    }
+}
+
+fn response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "method": method, "params": params })
+}
+
+/// Convert a 1-based, end-inclusive Clarity span to a 0-based, end-exclusive LSP range.
+fn lsp_range(span: &Span) -> Value {
+    json!({
+        "start": {
+            "line": span.start_line.saturating_sub(1),
+            "character": span.start_column.saturating_sub(1),
+        },
+        "end": {
+            "line": span.end_line.saturating_sub(1),
+            "character": span.end_column,
+        },
+    })
+}
+
+fn lsp_diagnostic(diagnostic: &Diagnostic) -> Value {
+    let severity = match diagnostic.level {
+        Level::Error => 1,
+        Level::Warning => 2,
+        Level::Note => 3,
+    };
+    let range = lsp_range(diagnostic.spans.first().unwrap_or(&Span::zero()));
+    json!({
+        "range": range,
+        "severity": severity,
+        "source": "clarity",
+        "message": diagnostic.message,
+    })
+}
+
+fn span_contains(span: &Span, line: u32, column: u32) -> bool {
+    (span.start_line, span.start_column) <= (line, column)
+        && (line, column) <= (span.end_line, span.end_column)
+}
+
+/// The innermost expression whose span contains the 1-based `line` and `column`.
+fn expression_at(
+    expressions: &[SymbolicExpression],
+    line: u32,
+    column: u32,
+) -> Option<&SymbolicExpression> {
+    let expr = expressions
+        .iter()
+        .find(|expr| span_contains(&expr.span, line, column))?;
+    match expr.match_list() {
+        Some(children) => expression_at(children, line, column).or(Some(expr)),
+        None => Some(expr),
+    }
+}
+
+/// Map each name defined at the top level of a contract to its definition.
+fn top_level_definitions(
+    expressions: &[SymbolicExpression],
+) -> HashMap<&ClarityName, &SymbolicExpression> {
+    use crate::clarity::vm::functions::define::DefineFunctionsParsed::*;
+    let mut definitions = HashMap::new();
+    for expr in expressions.iter() {
+        let name = match DefineFunctionsParsed::try_parse(expr) {
+            Ok(Some(PrivateFunction { signature, .. }))
+            | Ok(Some(ReadOnlyFunction { signature, .. }))
+            | Ok(Some(PublicFunction { signature, .. })) => {
+                signature.first().and_then(|name| name.match_atom())
+            }
+            Ok(Some(Constant { name, .. }))
+            | Ok(Some(NonFungibleToken { name, .. }))
+            | Ok(Some(BoundedFungibleToken { name, .. }))
+            | Ok(Some(UnboundedFungibleToken { name }))
+            | Ok(Some(Map { name, .. }))
+            | Ok(Some(PersistedVariable { name, .. }))
+            | Ok(Some(Trait { name, .. }))
+            | Ok(Some(UseTrait { name, .. })) => Some(name),
+            _ => None,
+        };
+        if let Some(name) = name {
+            definitions.insert(name, expr);
+        }
+    }
+    definitions
+}
+
+/// The name referred to by an atom or a trait reference (`<name>`).
+fn referenced_name(expr: &SymbolicExpression) -> Option<&ClarityName> {
+    match expr.expr {
+        SymbolicExpressionType::Atom(ref name) => Some(name),
+        SymbolicExpressionType::TraitReference(ref name, _) => Some(name),
+        _ => None,
+    }
+}
+
+fn function_api_markdown(api: &FunctionAPI) -> String {
+    format!(
+        "```clarity\n{}\n```\n\n**input:** `{}`\n\n**output:** `{}`\n\n{}\n\n**example:**\n```clarity\n{}\n```",
+        api.signature, api.input_type, api.output_type, api.description, api.example
+    )
+}
+
+fn user_function_markdown(kind: &str, name: &ClarityName, function_type: &FunctionType) -> String {
+    match function_type {
+        FunctionType::Fixed(function) => {
+            let args: Vec<String> = function
+                .args
+                .iter()
+                .map(|arg| format!(" ({} {})", arg.name, arg.signature))
+                .collect();
+            format!(
+                "```clarity\n({} ({}{}) {})\n```",
+                kind,
+                name,
+                args.join(""),
+                function.returns
+            )
+        }
+        _ => format!("```clarity\n({} ({}))\n```", kind, name),
+    }
+}
+
+/// Describe a name defined by the contract, using the types inferred by the
+///  type checker.
+fn user_definition_markdown(analysis: &ContractAnalysis, name: &ClarityName) -> Option<String> {
+    if let Some(function_type) = analysis.public_function_types.get(name) {
+        return Some(user_function_markdown("define-public", name, function_type));
+    }
+    if let Some(function_type) = analysis.read_only_function_types.get(name) {
+        return Some(user_function_markdown(
+            "define-read-only",
+            name,
+            function_type,
+        ));
+    }
+    if let Some(function_type) = analysis.private_function_types.get(name) {
+        return Some(user_function_markdown(
+            "define-private",
+            name,
+            function_type,
+        ));
+    }
+    let definition = if let Some(var_type) = analysis.variable_types.get(name) {
+        format!("(define-constant {}) ;; {}", name, var_type)
+    } else if let Some(var_type) = analysis.persisted_variable_types.get(name) {
+        format!("(define-data-var {} {})", name, var_type)
+    } else if let Some((key_type, value_type)) = analysis.map_types.get(name) {
+        format!("(define-map {} {} {})", name, key_type, value_type)
+    } else if analysis.fungible_tokens.contains(name) {
+        format!("(define-fungible-token {})", name)
+    } else if let Some(asset_type) = analysis.non_fungible_tokens.get(name) {
+        format!("(define-non-fungible-token {} {})", name, asset_type)
+    } else if let Some(functions) = analysis.defined_traits.get(name) {
+        let functions: Vec<String> = functions
+            .iter()
+            .map(|(function_name, signature)| {
+                let args: Vec<String> = signature.args.iter().map(|arg| arg.to_string()).collect();
+                format!(
+                    "\n  ({} ({}) {})",
+                    function_name,
+                    args.join(" "),
+                    signature.returns
+                )
+            })
+            .collect();
+        format!("(define-trait {} ({}))", name, functions.join(""))
+    } else {
+        return None;
+    };
+    Some(format!("```clarity\n{}\n```", definition))
+}
+
+impl Default for LanguageServer {
+    fn default() -> LanguageServer {
+        LanguageServer::new()
+    }
+}
+
+impl LanguageServer {
+    pub fn new() -> LanguageServer {
+        LanguageServer {
+            documents: HashMap::new(),
+            boot_code: None,
+            shutdown_requested: false,
+            exit_code: None,
+        }
+    }
+
+    /// Set once the client has sent `exit`.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    /// Handle one message from the client, returning the messages to send back:
+    ///  the response to a request, and any notifications it produced.
+    pub fn handle_message(&mut self, message: Value) -> Vec<Value> {
+        let method = match message.get("method").and_then(|method| method.as_str()) {
+            Some(method) => method.to_string(),
+            None => {
+                // responses from the client are not expected: we never send requests.
+                if message.get("id").is_some() && message.get("result").is_none() {
+                    return vec![error_response(
+                        message["id"].clone(),
+                        INVALID_REQUEST,
+                        "message has no method",
+                    )];
+                }
+                return vec![];
+            }
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let id = match message.get("id") {
+            Some(id) => id.clone(),
+            None => return self.handle_notification(&method, &params),
+        };
+
+        if self.shutdown_requested && method != "exit" {
+            return vec![error_response(
+                id,
+                INVALID_REQUEST,
+                "server is shutting down",
+            )];
+        }
+
+        let result = match method.as_str() {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": TEXT_DOCUMENT_SYNC_FULL,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "completionProvider": { "triggerCharacters": ["("] },
+                },
+                "serverInfo": { "name": "clarity-lsp" },
+            })),
+            "shutdown" => {
+                self.shutdown_requested = true;
+                Ok(Value::Null)
+            }
+            "textDocument/hover" => self.hover(&params),
+            "textDocument/definition" => self.definition(&params),
+            "textDocument/completion" => Ok(LanguageServer::completion()),
+            _ => Err((METHOD_NOT_FOUND, format!("unknown method '{}'", method))),
+        };
+
+        match result {
+            Ok(result) => vec![response(id, result)],
+            Err((code, message)) => vec![error_response(id, code, &message)],
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .map(|uri| uri.to_string());
+        match (method, uri) {
+            ("exit", _) => {
+                self.exit_code = Some(if self.shutdown_requested { 0 } else { 1 });
+                vec![]
+            }
+            ("textDocument/didOpen", Some(uri)) => {
+                let text = params["textDocument"]["text"]
+                    .as_str()
+                    .unwrap_or("")
+                    .to_string();
+                vec![self.update_document(uri, text)]
+            }
+            ("textDocument/didChange", Some(uri)) => {
+                // we only advertise full document sync, so the last change holds the full text.
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str());
+                match text {
+                    Some(text) => vec![self.update_document(uri, text.to_string())],
+                    None => vec![],
+                }
+            }
+            ("textDocument/didClose", Some(uri)) => {
+                self.documents.remove(&uri);
+                vec![notification(
+                    "textDocument/publishDiagnostics",
+                    json!({ "uri": uri, "diagnostics": [] }),
+                )]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Parse and type check a document, and return its diagnostics notification.
+    fn update_document(&mut self, uri: String, text: String) -> Value {
+        let contract_identifier = QualifiedContractIdentifier::transient();
+        let (mut ast, mut diagnostics, success) = build_ast_with_diagnostics(
+            &contract_identifier,
+            &text,
+            &mut (),
+            ClarityVersion::Clarity2,
+            DEFAULT_CLI_EPOCH,
+        );
+
+        let analysis = if success {
+            let boot_code = self.boot_code.get_or_insert_with(|| {
+                let mut marf = MemoryBackingStore::new();
+                install_boot_code(&CLIHeadersDB::new_memory(true), &mut marf);
+                marf
+            });
+            match run_analysis(
+                &contract_identifier,
+                &mut ast.expressions,
+                &mut boot_code.as_analysis_db(),
+                false,
+                LimitedCostTracker::new_free(),
+                DEFAULT_CLI_EPOCH,
+                ClarityVersion::Clarity2,
+            ) {
+                Ok(analysis) => Some(analysis),
+                Err((e, _)) => {
+                    diagnostics.push(e.diagnostic);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let diagnostics: Vec<Value> = diagnostics.iter().map(lsp_diagnostic).collect();
+        self.documents.insert(
+            uri.clone(),
+            Document {
+                expressions: ast.expressions,
+                analysis,
+            },
+        );
+        notification(
+            "textDocument/publishDiagnostics",
+            json!({ "uri": uri, "diagnostics": diagnostics }),
+        )
+    }
+
+    /// Find the open document and the 1-based line and column named by
+    ///  `TextDocumentPositionParams`.
+    fn document_position(&self, params: &Value) -> Result<(&Document, u32, u32), (i64, String)> {
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .ok_or((INVALID_PARAMS, "missing textDocument.uri".to_string()))?;
+        let document = self
+            .documents
+            .get(uri)
+            .ok_or_else(|| (INVALID_PARAMS, format!("document '{}' is not open", uri)))?;
+        let line = params["position"]["line"].as_u64();
+        let character = params["position"]["character"].as_u64();
+        match (line, character) {
+            (Some(line), Some(character)) => Ok((document, line as u32 + 1, character as u32 + 1)),
+            _ => Err((INVALID_PARAMS, "missing position".to_string())),
+        }
+    }
+
+    fn hover(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let (document, line, column) = self.document_position(params)?;
+        let expressions = match document.analysis {
+            Some(ref analysis) => &analysis.expressions,
+            None => &document.expressions,
+        };
+        let expr = match expression_at(expressions, line, column) {
+            Some(expr) => expr,
+            None => return Ok(Value::Null),
+        };
+        let name = match referenced_name(expr) {
+            Some(name) => name,
+            None => return Ok(Value::Null),
+        };
+
+        let user_definition = document
+            .analysis
+            .as_ref()
+            .and_then(|analysis| user_definition_markdown(analysis, name));
+        let contents = if let Some(contents) = user_definition {
+            contents
+        } else if let Some(function) =
+            NativeFunctions::lookup_by_name_at_version(name, &ClarityVersion::Clarity2)
+        {
+            function_api_markdown(&make_api_reference(&function))
+        } else if let Some(define) = DefineFunctions::lookup_by_name(name) {
+            function_api_markdown(&make_define_reference(&define))
+        } else if let Some(keyword) =
+            NativeVariables::lookup_by_name_at_version(name, &ClarityVersion::Clarity2)
+                .and_then(|variable| make_keyword_reference(&variable))
+        {
+            format!(
+                "```clarity\n{}\n```\n\n**output:** `{}`\n\n{}\n\n**example:**\n```clarity\n{}\n```",
+                keyword.name, keyword.output_type, keyword.description, keyword.example
+            )
+        } else if let Some(var_type) = document
+            .analysis
+            .as_ref()
+            .and_then(|analysis| analysis.type_map.as_ref())
+            .and_then(|type_map| type_map.get_type(expr))
+        {
+            format!("```clarity\n{}: {}\n```", name, var_type)
+        } else {
+            return Ok(Value::Null);
+        };
+
+        Ok(json!({
+            "contents": { "kind": "markdown", "value": contents },
+            "range": lsp_range(&expr.span),
+        }))
+    }
+
+    fn definition(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let (document, line, column) = self.document_position(params)?;
+        let name =
+            match expression_at(&document.expressions, line, column).and_then(referenced_name) {
+                Some(name) => name,
+                None => return Ok(Value::Null),
+            };
+        let definitions = top_level_definitions(&document.expressions);
+        match definitions.get(name) {
+            Some(definition) => Ok(json!({
+                "uri": params["textDocument"]["uri"],
+                "range": lsp_range(&definition.span),
+            })),
+            None => Ok(Value::Null),
+        }
+    }
+
+    fn completion() -> Value {
+        let mut items = vec![];
+        for function in NativeFunctions::ALL.iter() {
+            let api = make_api_reference(function);
+            if NativeFunctions::lookup_by_name_at_version(&api.name, &ClarityVersion::Clarity2)
+                .is_none()
+            {
+                continue;
+            }
+            items.push(json!({
+                "label": api.name,
+                "kind": COMPLETION_ITEM_KIND_FUNCTION,
+                "detail": api.signature,
+                "documentation": { "kind": "markdown", "value": api.description },
+            }));
+        }
+        for define in DefineFunctions::ALL.iter() {
+            let api = make_define_reference(define);
+            items.push(json!({
+                "label": api.name,
+                "kind": COMPLETION_ITEM_KIND_KEYWORD,
+                "detail": api.signature,
+                "documentation": { "kind": "markdown", "value": api.description },
+            }));
+        }
+        json!({ "isIncomplete": false, "items": items })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const URI: &str = "file:///tmp/counter.clar";
+
+    fn open(server: &mut LanguageServer, text: &str) -> Value {
+        let mut replies = server.handle_message(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": {
+                "textDocument": { "uri": URI, "languageId": "clarity", "version": 1, "text": text }
+            }
+        }));
+        assert_eq!(replies.len(), 1);
+        replies.remove(0)
+    }
+
+    fn request(server: &mut LanguageServer, method: &str, params: Value) -> Value {
+        let mut replies = server.handle_message(json!({
+            "jsonrpc": "2.0",
+            "id": 7,
+            "method": method,
+            "params": params,
+        }));
+        assert_eq!(replies.len(), 1);
+        let reply = replies.remove(0);
+        assert_eq!(reply["id"], 7);
+        reply
+    }
+
+    fn position(line: u64, character: u64) -> Value {
+        json!({
+            "textDocument": { "uri": URI },
+            "position": { "line": line, "character": character },
+        })
+    }
+
+    #[test]
+    fn test_message_framing() {
+        let message = json!({ "jsonrpc": "2.0", "id": 1, "method": "shutdown" });
+        let mut buffer = vec![];
+        write_message(&mut buffer, &message).unwrap();
+        write_message(&mut buffer, &message).unwrap();
+
+        let mut input = io::Cursor::new(buffer);
+        assert_eq!(read_message(&mut input).unwrap(), Some(message.clone()));
+        assert_eq!(read_message(&mut input).unwrap(), Some(message));
+        assert_eq!(read_message(&mut input).unwrap(), None);
+
+        let mut input = io::Cursor::new(b"Content-Type: foo\r\n\r\n{}".to_vec());
+        assert_eq!(
+            read_message(&mut input).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let session = [
+            json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+            json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+            json!({ "jsonrpc": "2.0", "id": 2, "method": "workspace/symbol", "params": {} }),
+            json!({ "jsonrpc": "2.0", "id": 3, "method": "shutdown" }),
+            json!({ "jsonrpc": "2.0", "method": "exit" }),
+        ];
+        let mut input = vec![];
+        for message in session.iter() {
+            write_message(&mut input, message).unwrap();
+        }
+        let mut output = vec![];
+        assert_eq!(run(&mut io::Cursor::new(input), &mut output), 0);
+
+        let mut output = io::Cursor::new(output);
+        let initialized = read_message(&mut output).unwrap().unwrap();
+        assert_eq!(initialized["id"], 1);
+        assert_eq!(initialized["result"]["capabilities"]["hoverProvider"], true);
+        let unknown = read_message(&mut output).unwrap().unwrap();
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+        let shutdown = read_message(&mut output).unwrap().unwrap();
+        assert_eq!(shutdown["id"], 3);
+        assert_eq!(shutdown["result"], Value::Null);
+        assert_eq!(read_message(&mut output).unwrap(), None);
+
+        // exiting without a shutdown request is an error
+        let mut server = LanguageServer::new();
+        server.handle_message(json!({ "jsonrpc": "2.0", "method": "exit" }));
+        assert_eq!(server.exit_code(), Some(1));
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let mut server = LanguageServer::new();
+
+        let published = open(
+            &mut server,
+            "(define-constant a u1)\n(define-public (f) (ok (+ a",
+        );
+        assert_eq!(published["method"], "textDocument/publishDiagnostics");
+        let diagnostics = published["params"]["diagnostics"].as_array().unwrap();
+        assert!(!diagnostics.is_empty());
+        assert_eq!(diagnostics[0]["severity"], 1);
+
+        let published = open(
+            &mut server,
+            "(define-constant a u1)\n(define-public (f) (ok (+ a 1)))",
+        );
+        let diagnostics = published["params"]["diagnostics"].as_array().unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0]["range"]["start"]["line"], 1);
+
+        let published = server.handle_message(json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": URI, "version": 2 },
+                "contentChanges": [
+                    { "text": "(define-constant a u1)\n(define-public (f) (ok (+ a u1)))" }
+                ]
+            }
+        }));
+        assert_eq!(published[0]["params"]["diagnostics"], json!([]));
+    }
+
+    #[test]
+    fn test_hover_definition_and_completion() {
+        let mut server = LanguageServer::new();
+        let published = open(
+            &mut server,
+            "(define-constant step u1)
+(define-data-var counter uint u0)
+(define-public (increment)
+  (let ((next (+ (var-get counter) step)))
+    (ok (var-set counter next))))",
+        );
+        assert_eq!(published["params"]["diagnostics"], json!([]));
+
+        // a native function
+        let hover = request(&mut server, "textDocument/hover", position(3, 15));
+        let contents = hover["result"]["contents"]["value"].as_str().unwrap();
+        assert!(contents.contains("(+ i1 i2...)"), "{}", contents);
+
+        // a user definition
+        let hover = request(&mut server, "textDocument/hover", position(3, 36));
+        assert_eq!(
+            hover["result"]["contents"]["value"],
+            "```clarity\n(define-constant step) ;; uint\n```"
+        );
+        assert_eq!(
+            hover["result"]["range"],
+            json!({ "start": { "line": 3, "character": 35 }, "end": { "line": 3, "character": 39 } })
+        );
+
+        // a local variable
+        let hover = request(&mut server, "textDocument/hover", position(4, 27));
+        assert_eq!(
+            hover["result"]["contents"]["value"],
+            "```clarity\nnext: uint\n```"
+        );
+
+        let definition = request(&mut server, "textDocument/definition", position(4, 20));
+        assert_eq!(definition["result"]["uri"], URI);
+        assert_eq!(
+            definition["result"]["range"],
+            json!({ "start": { "line": 1, "character": 0 }, "end": { "line": 1, "character": 33 } })
+        );
+        let definition = request(&mut server, "textDocument/definition", position(4, 27));
+        assert_eq!(definition["result"], Value::Null);
+
+        let completion = request(&mut server, "textDocument/completion", position(0, 0));
+        let labels: Vec<_> = completion["result"]["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["label"].as_str().unwrap().to_string())
+            .collect();
+        assert!(labels.contains(&"stx-transfer-memo?".to_string()));
+        assert!(labels.contains(&"define-public".to_string()));
+    }
+}
@@ -719,6 +719,12 @@ impl<'a> PreCommitClarityBlock<'a> {
         debug!("Committing Clarity block connection"; "index_block" => %self.commit_to);
         self.datastore.commit_to(&self.commit_to);
     }
+
+    /// Drop all of the block's writes instead of committing them.
+    pub fn rollback(self) {
+        debug!("Rolling back pre-committed Clarity block"; "index_block" => %self.commit_to);
+        self.datastore.rollback_block();
+    }
 }
 
 impl<'a, 'b> ClarityBlockConnection<'a, 'b> {
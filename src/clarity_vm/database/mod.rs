@@ -1,5 +1,5 @@
 use clarity::vm::types::PrincipalData;
-use rusqlite::{Connection, OptionalExtension, Row, ToSql};
+use rusqlite::{Connection, OptionalExtension, Row, ToSql, NO_PARAMS};
 
 use crate::chainstate::burn::db::sortdb::{
     get_ancestor_sort_id, get_ancestor_sort_id_tx, SortitionDB, SortitionDBConn, SortitionHandle,
@@ -618,13 +618,17 @@ impl BurnStateDB for SortitionDBConn<'_> {
 
 pub struct MemoryBackingStore {
     side_store: Connection,
+    block_height: u32,
 }
 
 impl MemoryBackingStore {
     pub fn new() -> MemoryBackingStore {
         let side_store = SqliteConnection::memory().unwrap();
 
-        let mut memory_marf = MemoryBackingStore { side_store };
+        let mut memory_marf = MemoryBackingStore {
+            side_store,
+            block_height: 0,
+        };
 
         memory_marf.as_clarity_db().initialize();
 
@@ -648,6 +652,38 @@ impl MemoryBackingStore {
     pub fn as_analysis_db<'a>(&'a mut self) -> AnalysisDatabase<'a> {
         AnalysisDatabase::new(self)
     }
+
+    /// Set the height reported as the current block height, e.g. by `block-height`
+    pub fn set_block_height(&mut self, block_height: u32) {
+        self.block_height = block_height;
+    }
+
+    /// Write the contents of this store, but not its block height, to a new SQLite database
+    /// at `path`.  Fails if a non-empty file already exists there.
+    pub fn save_to_file(&self, path: &str) -> Result<(), rusqlite::Error> {
+        self.side_store.execute("VACUUM INTO ?1", &[path])?;
+        Ok(())
+    }
+
+    /// Load a store from a SQLite database written by `save_to_file`.  Its block height is 0.
+    pub fn load_from_file(path: &str) -> Result<MemoryBackingStore, rusqlite::Error> {
+        let side_store = SqliteConnection::memory().unwrap();
+        side_store.execute("ATTACH DATABASE ?1 AS saved", &[path])?;
+        side_store.execute(
+            "INSERT INTO data_table SELECT key, value FROM saved.data_table",
+            NO_PARAMS,
+        )?;
+        side_store.execute(
+            "INSERT INTO metadata_table SELECT key, blockhash, value FROM saved.metadata_table",
+            NO_PARAMS,
+        )?;
+        side_store.execute("DETACH DATABASE saved", NO_PARAMS)?;
+
+        Ok(MemoryBackingStore {
+            side_store,
+            block_height: 0,
+        })
+    }
 }
 
 impl ClarityBackingStore for MemoryBackingStore {
@@ -668,7 +704,8 @@ impl ClarityBackingStore for MemoryBackingStore {
     }
 
     fn get_block_at_height(&mut self, height: u32) -> Option<StacksBlockId> {
-        if height == 0 {
+        // the blocks before the current one have no contents, so they all share an ID
+        if height == 0 || height < self.block_height {
             Some(StacksBlockId::sentinel())
         } else {
             None
@@ -684,7 +721,7 @@ impl ClarityBackingStore for MemoryBackingStore {
     }
 
     fn get_current_block_height(&mut self) -> u32 {
-        0
+        self.block_height
     }
 
     fn get_cc_special_cases_handler(&self) -> Option<SpecialCaseHandler> {
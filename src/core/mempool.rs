@@ -15,7 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::cmp::{self, Ordering};
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
 use std::fs;
 use std::hash::Hasher;
 use std::io::{Read, Write};
@@ -44,6 +44,7 @@ use crate::chainstate::stacks::{
     index::Error as MarfError, Error as ChainstateError, StacksTransaction,
 };
 use crate::chainstate::stacks::{StacksMicroblock, TransactionPayload};
+use crate::core::tx_selection::{TxSelectionSettings, TxSelectionStrategy};
 use crate::core::ExecutionCost;
 use crate::core::StacksEpochId;
 use crate::core::FIRST_BURNCHAIN_CONSENSUS_HASH;
@@ -60,7 +61,9 @@ use crate::util_lib::db::Error as db_error;
 use crate::util_lib::db::FromColumn;
 use crate::util_lib::db::{query_row, Error};
 use crate::util_lib::db::{sql_pragma, DBConn, DBTx, FromRow};
+use clarity::vm::database::NULL_BURN_STATE_DB;
 use clarity::vm::types::PrincipalData;
+use clarity::vm::types::QualifiedContractIdentifier;
 use stacks_common::util::get_epoch_time_ms;
 use stacks_common::util::get_epoch_time_secs;
 use stacks_common::util::hash::to_hex;
@@ -78,6 +81,7 @@ use crate::chainstate::stacks::miner::TransactionEvent;
 use crate::chainstate::stacks::StacksBlock;
 use crate::codec::Error as codec_error;
 use crate::codec::StacksMessageCodec;
+use crate::codec::{read_next, write_next};
 use crate::cost_estimates;
 use crate::cost_estimates::metrics::CostMetric;
 use crate::cost_estimates::metrics::UnitMetric;
@@ -114,6 +118,11 @@ pub const DEFAULT_BLACKLIST_MAX_SIZE: u64 = 134217728; // 2**27 -- the blacklist
 // loading the bloom filter, even though the bloom filter is larger.
 const DEFAULT_MAX_TX_TAGS: u32 = 2048;
 
+// how many candidates will the mempool walk hold at once, for strategies that rank candidates by
+// something other than their fee rate?  Candidates are ranked against each other within this
+// window as they're streamed out of the mempool, best fee rate first.
+const MAX_RANKED_CANDIDATES: usize = 4096;
+
 /// A node-specific transaction tag -- the first 8 bytes of siphash(local-seed,txid)
 #[derive(Debug, Clone, PartialEq, Hash, Eq)]
 pub struct TxTag(pub [u8; 8]);
@@ -148,6 +157,58 @@ impl StacksMessageCodec for TxTag {
     }
 }
 
+/// A mempool transaction as written out by `MemPoolDB::export_txs`.  The transaction is encoded
+/// just as `stream_txs` encodes it, and is followed by the metadata that carries over when it is
+/// imported into another node's mempool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemPoolExportedTx {
+    pub tx: StacksTransaction,
+    pub accept_time: u64,
+    /// The exporting node's fee rate estimate, if it had one
+    pub fee_rate: Option<f64>,
+}
+
+impl StacksMessageCodec for MemPoolExportedTx {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.tx)?;
+        write_next(fd, &self.accept_time)?;
+        match self.fee_rate {
+            Some(fee_rate) => {
+                write_next(fd, &1u8)?;
+                write_next(fd, &fee_rate.to_bits())?;
+            }
+            None => {
+                write_next(fd, &0u8)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<MemPoolExportedTx, codec_error> {
+        let tx: StacksTransaction = read_next(fd)?;
+        let accept_time: u64 = read_next(fd)?;
+        let has_fee_rate: u8 = read_next(fd)?;
+        let fee_rate = match has_fee_rate {
+            0 => None,
+            1 => {
+                let fee_rate_bits: u64 = read_next(fd)?;
+                Some(f64::from_bits(fee_rate_bits))
+            }
+            _ => {
+                return Err(codec_error::DeserializeError(format!(
+                    "Invalid fee rate flag {}",
+                    has_fee_rate
+                )));
+            }
+        };
+        Ok(MemPoolExportedTx {
+            tx,
+            accept_time,
+            fee_rate,
+        })
+    }
+}
+
 pub struct MemPoolAdmitter {
     cur_block: BlockHeaderHash,
     cur_consensus_hash: ConsensusHash,
@@ -181,14 +242,20 @@ impl MemPoolAdmitter {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MemPoolDropReason {
     REPLACE_ACROSS_FORK,
     REPLACE_BY_FEE,
     STALE_COLLECT,
     TOO_EXPENSIVE,
     PROBLEMATIC,
+    MEMPOOL_FULL,
 }
 
+/// Transactions dropped while adding a transaction to the mempool, along with why.  These are
+/// only announced to the event observer once the mempool transaction commits.
+pub type MemPoolDroppedTxs = Vec<(Vec<Txid>, MemPoolDropReason)>;
+
 pub struct ConsiderTransaction {
     /// Transaction to consider in block assembly
     pub tx: MemPoolTxInfo,
@@ -212,6 +279,7 @@ impl std::fmt::Display for MemPoolDropReason {
             MemPoolDropReason::REPLACE_ACROSS_FORK => write!(f, "ReplaceAcrossFork"),
             MemPoolDropReason::REPLACE_BY_FEE => write!(f, "ReplaceByFee"),
             MemPoolDropReason::PROBLEMATIC => write!(f, "Problematic"),
+            MemPoolDropReason::MEMPOOL_FULL => write!(f, "MempoolFull"),
         }
     }
 }
@@ -243,7 +311,8 @@ pub struct MemPoolTxInfo {
 }
 
 /// This class is a minimal version of `MemPoolTxInfo`. It contains
-/// just enough information to 1) filter by nonce readiness, 2) sort by fee rate.
+/// just enough information to 1) filter by nonce readiness, 2) rank it with a
+/// `TxSelectionStrategy`.
 #[derive(Debug, Clone)]
 pub struct MemPoolTxInfoPartial {
     pub txid: Txid,
@@ -252,6 +321,10 @@ pub struct MemPoolTxInfoPartial {
     pub origin_nonce: u64,
     pub sponsor_address: StacksAddress,
     pub sponsor_nonce: u64,
+    pub tx_fee: u64,
+    pub len: u64,
+    /// Contract called by this transaction, if it is a contract-call
+    pub contract_call: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -300,6 +373,8 @@ pub struct MemPoolWalkSettings {
     /// Size of the candidate cache. These are the candidates that will be retried after each
     /// transaction is mined.
     pub candidate_retry_cache_size: u64,
+    /// How to choose which transactions to consider, and in what order
+    pub tx_selection: TxSelectionSettings,
 }
 
 impl MemPoolWalkSettings {
@@ -310,6 +385,7 @@ impl MemPoolWalkSettings {
             consider_no_estimate_tx_prob: 5,
             nonce_cache_size: 1024 * 1024,
             candidate_retry_cache_size: 64 * 1024,
+            tx_selection: TxSelectionSettings::default(),
         }
     }
     pub fn zero() -> MemPoolWalkSettings {
@@ -319,10 +395,123 @@ impl MemPoolWalkSettings {
             consider_no_estimate_tx_prob: 5,
             nonce_cache_size: 1024 * 1024,
             candidate_retry_cache_size: 64 * 1024,
+            tx_selection: TxSelectionSettings::default(),
+        }
+    }
+}
+
+/// How much more a transaction must pay to replace pending transactions that spend the same
+/// origin or sponsor nonces in the same fork.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplaceByFeePolicy {
+    /// Minimum amount, in microSTX, by which the replacement's fee must exceed the total fee of
+    /// the transactions it replaces
+    pub min_fee_increase: u64,
+    /// Minimum amount by which the replacement's fee must exceed the total fee of the
+    /// transactions it replaces, as a percentage of that total
+    pub min_fee_increase_percent: u64,
+}
+
+impl Default for ReplaceByFeePolicy {
+    fn default() -> ReplaceByFeePolicy {
+        ReplaceByFeePolicy {
+            min_fee_increase: 1,
+            min_fee_increase_percent: 0,
         }
     }
 }
 
+impl ReplaceByFeePolicy {
+    /// The lowest fee that can replace transactions paying `replaced_fee` in total
+    pub fn min_replacement_fee(&self, replaced_fee: u64) -> u64 {
+        let percent_increase = replaced_fee.saturating_mul(self.min_fee_increase_percent) / 100;
+        replaced_fee.saturating_add(cmp::max(self.min_fee_increase, percent_increase))
+    }
+}
+
+/// How large the mempool may grow.  Once it's full, the transactions with the lowest fee rates
+/// are evicted to make room for new ones.  Transactions without a fee rate estimate are evicted
+/// first.
+/// The per-account limits apply to each account's pending transactions, whether the account is
+/// their origin or their sponsor.  Transactions that would exceed them are rejected.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MemPoolLimits {
+    /// Maximum number of transactions in the mempool
+    pub max_tx_count: Option<u64>,
+    /// Maximum total size of the transactions in the mempool, in bytes
+    pub max_bytes: Option<u64>,
+    /// Maximum number of pending transactions per account
+    pub max_txs_per_account: Option<u64>,
+    /// Maximum total size of the pending transactions per account, in bytes
+    pub max_bytes_per_account: Option<u64>,
+    /// Maximum amount by which a transaction's nonce may exceed its account's nonce in the chain
+    /// tip.  This can only tighten the limit of `MAXIMUM_MEMPOOL_TX_CHAINING`.
+    pub max_nonce_gap: Option<u64>,
+}
+
+impl MemPoolLimits {
+    fn is_capped(&self) -> bool {
+        self.max_tx_count.is_some() || self.max_bytes.is_some()
+    }
+
+    /// Would a mempool with this many transactions and bytes need to evict transactions?
+    fn is_exceeded(&self, tx_count: u64, bytes: u64) -> bool {
+        self.max_tx_count.map(|max| tx_count > max).unwrap_or(false)
+            || self.max_bytes.map(|max| bytes > max).unwrap_or(false)
+    }
+
+    /// Is a mempool with this many transactions and bytes full?
+    fn is_full(&self, tx_count: u64, bytes: u64) -> bool {
+        self.max_tx_count
+            .map(|max| tx_count >= max)
+            .unwrap_or(false)
+            || self.max_bytes.map(|max| bytes >= max).unwrap_or(false)
+    }
+}
+
+/// A record of a transaction that was dropped from the mempool in favor of another one
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemPoolTxReplacement {
+    /// The transaction that was dropped
+    pub txid: Txid,
+    /// The transaction that replaced it
+    pub replaced_by: Txid,
+    /// Why it was dropped (a `MemPoolDropReason`)
+    pub reason: String,
+    pub replace_time: u64,
+}
+
+/// Which pending transactions to list with `MemPoolDB::get_pending_txs`
+#[derive(Debug, Clone, PartialEq)]
+pub enum PendingTxQuery {
+    /// Transactions that spend one of the address's nonces in `[min_nonce, max_nonce]`, either
+    /// as their origin (`is_origin == Some(true)`), as their sponsor (`Some(false)`), or as
+    /// either one (`None`)
+    Address {
+        address: StacksAddress,
+        is_origin: Option<bool>,
+        min_nonce: u64,
+        max_nonce: u64,
+    },
+    /// Transactions that call the given contract
+    ContractCall(QualifiedContractIdentifier),
+}
+
+/// A pending transaction, as listed by `MemPoolDB::get_pending_txs`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemPoolPendingTx {
+    pub tx_info: MemPoolTxInfo,
+    pub fee_rate: Option<f64>,
+}
+
+impl FromRow<MemPoolPendingTx> for MemPoolPendingTx {
+    fn from_row(row: &Row) -> Result<MemPoolPendingTx, db_error> {
+        let tx_info = MemPoolTxInfo::from_row(row)?;
+        let fee_rate: Option<f64> = row.get("fee_rate")?;
+        Ok(MemPoolPendingTx { tx_info, fee_rate })
+    }
+}
+
 impl FromRow<Txid> for Txid {
     fn from_row<'a>(row: &'a Row) -> Result<Txid, db_error> {
         row.get(0).map_err(db_error::SqliteError)
@@ -363,6 +552,22 @@ impl FromRow<MemPoolTxMetadata> for MemPoolTxMetadata {
     }
 }
 
+impl FromRow<MemPoolTxReplacement> for MemPoolTxReplacement {
+    fn from_row(row: &Row) -> Result<MemPoolTxReplacement, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let replaced_by = Txid::from_column(row, "replaced_by")?;
+        let reason: String = row.get_unwrap("reason");
+        let replace_time = u64::from_column(row, "replace_time")?;
+
+        Ok(MemPoolTxReplacement {
+            txid,
+            replaced_by,
+            reason,
+            replace_time,
+        })
+    }
+}
+
 impl FromRow<MemPoolTxInfo> for MemPoolTxInfo {
     fn from_row<'a>(row: &'a Row) -> Result<MemPoolTxInfo, db_error> {
         let md = MemPoolTxMetadata::from_row(row)?;
@@ -381,6 +586,22 @@ impl FromRow<MemPoolTxInfo> for MemPoolTxInfo {
     }
 }
 
+impl FromRow<MemPoolExportedTx> for MemPoolExportedTx {
+    fn from_row(row: &Row) -> Result<MemPoolExportedTx, db_error> {
+        let tx_bytes: Vec<u8> = row.get_unwrap("tx");
+        let tx = StacksTransaction::consensus_deserialize(&mut &tx_bytes[..])
+            .map_err(|_e| db_error::ParseError)?;
+        let accept_time = u64::from_column(row, "accept_time")?;
+        let fee_rate: Option<f64> = row.get("fee_rate")?;
+
+        Ok(MemPoolExportedTx {
+            tx,
+            accept_time,
+            fee_rate,
+        })
+    }
+}
+
 impl FromRow<MemPoolTxInfoPartial> for MemPoolTxInfoPartial {
     fn from_row<'a>(row: &'a Row) -> Result<MemPoolTxInfoPartial, db_error> {
         let txid = Txid::from_column(row, "txid")?;
@@ -392,6 +613,9 @@ impl FromRow<MemPoolTxInfoPartial> for MemPoolTxInfoPartial {
         let origin_nonce = u64::from_column(row, "origin_nonce")?;
         let sponsor_address = StacksAddress::from_column(row, "sponsor_address")?;
         let sponsor_nonce = u64::from_column(row, "sponsor_nonce")?;
+        let tx_fee = u64::from_column(row, "tx_fee")?;
+        let len = u64::from_column(row, "length")?;
+        let contract_call: Option<String> = row.get_unwrap("contract_call");
 
         Ok(MemPoolTxInfoPartial {
             txid,
@@ -400,6 +624,9 @@ impl FromRow<MemPoolTxInfoPartial> for MemPoolTxInfoPartial {
             origin_nonce,
             sponsor_address,
             sponsor_nonce,
+            tx_fee,
+            len,
+            contract_call,
         })
     }
 }
@@ -548,6 +775,67 @@ const MEMPOOL_SCHEMA_6_NONCES: &'static [&'static str] = &[
     "#,
 ];
 
+const MEMPOOL_SCHEMA_7_REPLACEMENTS: &[&str] = &[
+    r#"
+    -- Transactions that were dropped from the mempool in favor of another transaction, so clients
+    -- can find out what became of them.  Rows are garbage-collected along with the mempool, by
+    -- the height of the replacing transaction.
+    CREATE TABLE tx_replacements(
+        txid TEXT PRIMARY KEY NOT NULL,
+        replaced_by TEXT NOT NULL,
+        reason TEXT NOT NULL,
+        height INTEGER NOT NULL,
+        replace_time INTEGER NOT NULL
+    );
+    "#,
+    r#"
+    INSERT INTO schema_version (version) VALUES (7)
+    "#,
+];
+
+const MEMPOOL_SCHEMA_8_CONTRACT_CALLS: &[&str] = &[
+    r#"
+    -- The contract that a transaction calls, if it is a contract-call, so that the pending calls
+    -- to a contract can be listed.
+    ALTER TABLE mempool ADD COLUMN contract_call TEXT;
+    "#,
+    r#"
+    INSERT INTO schema_version (version) VALUES (8)
+    "#,
+];
+
+const MEMPOOL_SCHEMA_9_SIZE: &[&str] = &[
+    r#"
+    -- The number of transactions in the mempool and their total size in bytes, so that the
+    -- mempool's limits can be checked without scanning it.  A stored transaction never replaces
+    -- another row, since its conflicts are always dropped first.
+    CREATE TABLE mempool_size(
+        tx_count INTEGER NOT NULL,
+        bytes INTEGER NOT NULL
+    );
+    "#,
+    r#"
+    CREATE TRIGGER mempool_size_inc
+    AFTER INSERT ON mempool
+    BEGIN
+        UPDATE mempool_size SET tx_count = tx_count + 1, bytes = bytes + NEW.length;
+    END
+    "#,
+    r#"
+    CREATE TRIGGER mempool_size_dec
+    AFTER DELETE ON mempool
+    BEGIN
+        UPDATE mempool_size SET tx_count = tx_count - 1, bytes = bytes - OLD.length;
+    END
+    "#,
+    r#"
+    INSERT INTO mempool_size (tx_count, bytes) SELECT COUNT(*), IFNULL(SUM(length), 0) FROM mempool
+    "#,
+    r#"
+    INSERT INTO schema_version (version) VALUES (9)
+    "#,
+];
+
 const MEMPOOL_INDEXES: &'static [&'static str] = &[
     "CREATE INDEX IF NOT EXISTS by_txid ON mempool(txid);",
     "CREATE INDEX IF NOT EXISTS by_height ON mempool(height);",
@@ -560,6 +848,8 @@ const MEMPOOL_INDEXES: &'static [&'static str] = &[
     "CREATE INDEX IF NOT EXISTS by_ordered_hashed_txid ON randomized_txids(hashed_txid ASC);",
     "CREATE INDEX IF NOT EXISTS by_hashed_txid ON randomized_txids(txid,hashed_txid);",
     "CREATE INDEX IF NOT EXISTS by_arrival_time_desc ON tx_blacklist(arrival_time DESC);",
+    "CREATE INDEX IF NOT EXISTS tx_replacements_by_height ON tx_replacements(height);",
+    "CREATE INDEX IF NOT EXISTS by_contract_call ON mempool(contract_call);",
 ];
 
 pub struct MemPoolDB {
@@ -572,12 +862,16 @@ pub struct MemPoolDB {
     metric: Box<dyn CostMetric>,
     pub blacklist_timeout: u64,
     pub blacklist_max_size: u64,
+    pub rbf_policy: ReplaceByFeePolicy,
+    pub limits: MemPoolLimits,
 }
 
 pub struct MemPoolTx<'a> {
     tx: DBTx<'a>,
     admitter: &'a mut MemPoolAdmitter,
     bloom_counter: Option<&'a mut BloomCounter<BloomNodeHasher>>,
+    rbf_policy: &'a ReplaceByFeePolicy,
+    limits: &'a MemPoolLimits,
 }
 
 impl<'a> Deref for MemPoolTx<'a> {
@@ -598,11 +892,15 @@ impl<'a> MemPoolTx<'a> {
         tx: DBTx<'a>,
         admitter: &'a mut MemPoolAdmitter,
         bloom_counter: &'a mut BloomCounter<BloomNodeHasher>,
+        rbf_policy: &'a ReplaceByFeePolicy,
+        limits: &'a MemPoolLimits,
     ) -> MemPoolTx<'a> {
         MemPoolTx {
             tx,
             admitter,
             bloom_counter: Some(bloom_counter),
+            rbf_policy,
+            limits,
         }
     }
 
@@ -655,8 +953,8 @@ impl<'a> MemPoolTx<'a> {
         Ok(())
     }
 
-    /// Add the txid to the bloom counter in the mempool DB, optionally replacing a prior
-    /// transaction (identified by prior_txid) if the bloom counter is full.
+    /// Add the txid to the bloom counter in the mempool DB, removing any prior transactions it
+    /// replaces (identified by prior_txids).
     /// If this is the first txid at this block height, then also garbage-collect the bloom counter to remove no-longer-recent transactions.
     /// If the bloom counter is saturated -- i.e. it represents more than MAX_BLOOM_COUNTER_TXS
     /// transactions -- then pick another transaction to evict from the bloom filter and return its txid.
@@ -666,7 +964,7 @@ impl<'a> MemPoolTx<'a> {
         &mut self,
         height: u64,
         txid: &Txid,
-        prior_txid: Option<Txid>,
+        prior_txids: &[Txid],
     ) -> Result<Option<Txid>, MemPoolRejection> {
         // is this the first-ever txid at this height?
         let sql = "SELECT 1 FROM mempool WHERE height = ?1";
@@ -680,8 +978,8 @@ impl<'a> MemPoolTx<'a> {
         }
 
         MemPoolTx::with_bloom_state(self, |ref mut dbtx, ref mut bloom_counter| {
-            // remove replaced transaction
-            if let Some(prior_txid) = prior_txid {
+            // remove replaced transactions
+            for prior_txid in prior_txids.iter() {
                 bloom_counter.remove_raw(dbtx, &prior_txid.0)?;
             }
 
@@ -980,6 +1278,180 @@ impl CandidateCache {
     }
 }
 
+/// A candidate that the transaction-selection strategy was able to rank
+struct RankedCandidate {
+    priority: u32,
+    score: f64,
+    /// Order in which the candidate was loaded, to break ties
+    seq: u64,
+    candidate: MemPoolTxInfoPartial,
+    estimated_cost: Option<ExecutionCost>,
+}
+
+impl PartialEq for RankedCandidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for RankedCandidate {}
+
+impl PartialOrd for RankedCandidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RankedCandidate {
+    /// Higher priority first, then higher score, then whichever was loaded first
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| {
+                self.score
+                    .partial_cmp(&other.score)
+                    .unwrap_or(Ordering::Equal)
+            })
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+/// The mempool candidates that have not been considered yet, in the order that a
+/// `TxSelectionStrategy` would have them considered.  Candidates are streamed out of the mempool
+/// in descending fee-rate order, followed by those without a fee rate, and at most `window` of
+/// them are held for ranking at a time.  Strategies that rank by fee rate only need a window of
+/// one; other strategies rank each candidate against the others in the window.
+struct CandidateQueue<'a> {
+    fee_rate_rows: Rows<'a>,
+    no_fee_rate_rows: Rows<'a>,
+    window: usize,
+    ranked: BinaryHeap<RankedCandidate>,
+    unranked: VecDeque<MemPoolTxInfoPartial>,
+    next_seq: u64,
+}
+
+impl<'a> CandidateQueue<'a> {
+    fn new(fee_rate_rows: Rows<'a>, no_fee_rate_rows: Rows<'a>, window: usize) -> Self {
+        Self {
+            fee_rate_rows,
+            no_fee_rate_rows,
+            window: cmp::max(window, 1),
+            ranked: BinaryHeap::new(),
+            unranked: VecDeque::new(),
+            next_seq: 0,
+        }
+    }
+
+    /// Read the next candidate from `rows` that the strategy doesn't exclude
+    fn read_next(
+        rows: &mut Rows<'a>,
+        strategy: &dyn TxSelectionStrategy,
+    ) -> Result<Option<MemPoolTxInfoPartial>, db_error> {
+        while let Some(row) = rows.next()? {
+            let candidate = MemPoolTxInfoPartial::from_row(row)?;
+            if !strategy.is_excluded(&candidate) {
+                return Ok(Some(candidate));
+            }
+        }
+        Ok(None)
+    }
+
+    fn push(
+        &mut self,
+        mempool: &MemPoolDB,
+        epoch: &StacksEpochId,
+        candidate: MemPoolTxInfoPartial,
+        strategy: &dyn TxSelectionStrategy,
+    ) -> Result<(), db_error> {
+        let estimated_cost = if strategy.uses_cost_estimates() {
+            mempool.estimate_candidate_cost(&candidate.txid, epoch)?
+        } else {
+            None
+        };
+        match strategy.score(&candidate, estimated_cost.as_ref()) {
+            Some(score) => {
+                self.ranked.push(RankedCandidate {
+                    priority: strategy.priority(&candidate),
+                    score,
+                    seq: self.next_seq,
+                    candidate,
+                    estimated_cost,
+                });
+                self.next_seq += 1;
+            }
+            None => self.unranked.push_back(candidate),
+        }
+        Ok(())
+    }
+
+    /// Read candidates until the window of ranked candidates is full.  At most `window`
+    /// candidates are read, so that candidates that can't be ranked aren't all read at once.
+    fn fill(
+        &mut self,
+        mempool: &MemPoolDB,
+        epoch: &StacksEpochId,
+        strategy: &dyn TxSelectionStrategy,
+    ) -> Result<(), db_error> {
+        for _ in 0..self.window {
+            if self.ranked.len() >= self.window {
+                break;
+            }
+            let candidate = match Self::read_next(&mut self.fee_rate_rows, strategy)? {
+                Some(candidate) => candidate,
+                None => match Self::read_next(&mut self.no_fee_rate_rows, strategy)? {
+                    Some(candidate) => candidate,
+                    None => break,
+                },
+            };
+            self.push(mempool, epoch, candidate, strategy)?;
+        }
+        Ok(())
+    }
+
+    /// Retrieve the best-ranked candidate.  Scores can drop as the block fills up, so the top
+    /// candidate is re-scored, and put back if it is no longer the best (or moved to the unranked
+    /// candidates, if it can no longer be ranked at all).
+    fn next_ranked(
+        &mut self,
+        mempool: &MemPoolDB,
+        epoch: &StacksEpochId,
+        strategy: &dyn TxSelectionStrategy,
+    ) -> Result<Option<MemPoolTxInfoPartial>, db_error> {
+        self.fill(mempool, epoch, strategy)?;
+        while let Some(mut top) = self.ranked.pop() {
+            match strategy.score(&top.candidate, top.estimated_cost.as_ref()) {
+                Some(score) => {
+                    top.score = score;
+                    match self.ranked.peek() {
+                        Some(next) if *next > top => self.ranked.push(top),
+                        _ => return Ok(Some(top.candidate)),
+                    }
+                }
+                None => self.unranked.push_back(top.candidate),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Retrieve the next candidate that could not be ranked.  If there isn't one yet, read ahead
+    /// through the candidates without a fee rate, as long as there's room in the window for the
+    /// ones that can be ranked.
+    fn next_unranked(
+        &mut self,
+        mempool: &MemPoolDB,
+        epoch: &StacksEpochId,
+        strategy: &dyn TxSelectionStrategy,
+    ) -> Result<Option<MemPoolTxInfoPartial>, db_error> {
+        while self.unranked.is_empty() && self.ranked.len() < self.window {
+            match Self::read_next(&mut self.no_fee_rate_rows, strategy)? {
+                Some(candidate) => self.push(mempool, epoch, candidate, strategy)?,
+                None => break,
+            }
+        }
+        Ok(self.unranked.pop_front())
+    }
+}
+
 /// Evaluates the pair of nonces, to determine an order
 ///
 /// Returns:
@@ -1068,6 +1540,15 @@ impl MemPoolDB {
                     MemPoolDB::instantiate_nonces(tx)?;
                 }
                 6 => {
+                    MemPoolDB::instantiate_tx_replacements(tx)?;
+                }
+                7 => {
+                    MemPoolDB::instantiate_contract_calls(tx)?;
+                }
+                8 => {
+                    MemPoolDB::instantiate_size(tx)?;
+                }
+                9 => {
                     break;
                 }
                 _ => {
@@ -1139,6 +1620,51 @@ impl MemPoolDB {
         Ok(())
     }
 
+    /// Add the tx replacements table
+    fn instantiate_tx_replacements(tx: &DBTx) -> Result<(), db_error> {
+        for sql_exec in MEMPOOL_SCHEMA_7_REPLACEMENTS {
+            tx.execute_batch(sql_exec)?;
+        }
+
+        Ok(())
+    }
+
+    /// Add the contract_call column, and fill it in for the transactions already in the mempool
+    fn instantiate_contract_calls(tx: &DBTx) -> Result<(), db_error> {
+        for sql_exec in MEMPOOL_SCHEMA_8_CONTRACT_CALLS {
+            tx.execute_batch(sql_exec)?;
+        }
+
+        let txs: Vec<MemPoolTxInfo> = query_rows(tx, "SELECT * FROM mempool", NO_PARAMS)?;
+        for tx_info in txs.iter() {
+            MemPoolDB::set_contract_call(tx, &tx_info.tx)?;
+        }
+
+        Ok(())
+    }
+
+    /// Add the running count of the mempool's transactions and bytes
+    fn instantiate_size(tx: &DBTx) -> Result<(), db_error> {
+        for sql_exec in MEMPOOL_SCHEMA_9_SIZE {
+            tx.execute_batch(sql_exec)?;
+        }
+        Ok(())
+    }
+
+    /// Record the contract that a stored transaction calls, if any
+    fn set_contract_call(tx: &DBTx, stacks_tx: &StacksTransaction) -> Result<(), db_error> {
+        if let TransactionPayload::ContractCall(ref contract_call) = stacks_tx.payload {
+            tx.execute(
+                "UPDATE mempool SET contract_call = ?1 WHERE txid = ?2",
+                rusqlite::params![
+                    contract_call.contract_identifier().to_string(),
+                    &stacks_tx.txid()
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
     pub fn db_path(chainstate_root_path: &str) -> Result<String, db_error> {
         let mut path = PathBuf::from(chainstate_root_path);
 
@@ -1200,6 +1726,8 @@ impl MemPoolDB {
             metric,
             blacklist_timeout: DEFAULT_BLACKLIST_TIMEOUT,
             blacklist_max_size: DEFAULT_BLACKLIST_MAX_SIZE,
+            rbf_policy: ReplaceByFeePolicy::default(),
+            limits: MemPoolLimits::default(),
         })
     }
 
@@ -1328,18 +1856,53 @@ impl MemPoolDB {
         }
     }
 
+    /// Iterate over candidates in the mempool, in the order given by the transaction-selection
+    /// strategy in `settings`.  See `iterate_candidates_with_strategy()`.
+    pub fn iterate_candidates<F, E, C>(
+        &mut self,
+        clarity_tx: &mut C,
+        output_events: &mut Vec<TransactionEvent>,
+        tip_height: u64,
+        settings: MemPoolWalkSettings,
+        todo: F,
+    ) -> Result<u64, E>
+    where
+        C: ClarityConnection,
+        F: FnMut(
+            &mut C,
+            &ConsiderTransaction,
+            &mut dyn CostEstimator,
+        ) -> Result<Option<TransactionEvent>, E>,
+        E: From<db_error> + From<ChainstateError>,
+    {
+        let mut strategy = settings.tx_selection.make_strategy(
+            settings.consider_no_estimate_tx_prob,
+            &ExecutionCost::max_value(),
+            &ExecutionCost::zero(),
+        );
+        self.iterate_candidates_with_strategy(
+            clarity_tx,
+            output_events,
+            tip_height,
+            settings,
+            strategy.as_mut(),
+            todo,
+        )
+    }
+
     /// Iterate over candidates in the mempool
     /// `todo` will be called once for each transaction that is a valid
     /// candidate for inclusion in the next block, meaning its origin and
     /// sponsor nonces are equal to the nonces of the corresponding accounts.
-    /// Best effort will be made to process the transactions in fee-rate order.
-    /// That is, transactions will be processed in fee-rate order until the
-    /// candidate cache is full, at which point, transactions with a lower
-    /// fee-rate may be considered before those with a higher fee-rate.
+    /// Best effort will be made to process the transactions in the order
+    /// given by `strategy`, which is told about each transaction that gets
+    /// mined.  That is, transactions will be processed in that order until the
+    /// candidate cache is full, at which point, lower-ranked transactions
+    /// may be considered before higher-ranked ones.
     /// When the candidate cache fills, a subsequent call to
     /// `iterate_candidates` will be needed to reconsider transactions which
     /// were skipped on the first pass, but become valid after some lower
-    /// fee-rate transactions are considered.
+    /// ranked transactions are considered.
     ///
     /// The size of the candidate cache and the nonce cache are configurable
     /// in the settings struct. This method is interruptable -- in the
@@ -1353,12 +1916,13 @@ impl MemPoolDB {
     /// `output_events` is modified in place, adding all substantive
     /// transaction events (success and error events, but not skipped) output
     /// by `todo`.
-    pub fn iterate_candidates<F, E, C>(
+    pub fn iterate_candidates_with_strategy<F, E, C>(
         &mut self,
         clarity_tx: &mut C,
         output_events: &mut Vec<TransactionEvent>,
         _tip_height: u64,
         settings: MemPoolWalkSettings,
+        strategy: &mut dyn TxSelectionStrategy,
         mut todo: F,
     ) -> Result<u64, E>
     where
@@ -1373,7 +1937,8 @@ impl MemPoolDB {
         let start_time = Instant::now();
         let mut total_considered = 0;
 
-        debug!("Mempool walk for {}ms", settings.max_walk_time_ms,);
+        debug!("Mempool walk for {}ms", settings.max_walk_time_ms;
+               "tx_selection_strategy" => strategy.name());
 
         let tx_consideration_sampler = Uniform::new(0, 100);
         let mut rng = rand::thread_rng();
@@ -1385,31 +1950,35 @@ impl MemPoolDB {
         let mut retry_store = HashMap::new();
 
         let sql = "
-             SELECT txid, origin_nonce, origin_address, sponsor_nonce, sponsor_address, fee_rate
+             SELECT txid, origin_nonce, origin_address, sponsor_nonce, sponsor_address, fee_rate,
+                    tx_fee, length, contract_call
              FROM mempool
-             WHERE fee_rate IS NULL
+             WHERE fee_rate IS NOT NULL
+             ORDER BY fee_rate DESC
              ";
-        let mut query_stmt_null = self
-            .db
-            .prepare(&sql)
-            .map_err(|err| Error::SqliteError(err))?;
-        let mut null_iterator = query_stmt_null
+        let mut fee_rate_stmt = self.db.prepare(sql).map_err(db_error::SqliteError)?;
+        let fee_rate_rows = fee_rate_stmt
             .query(NO_PARAMS)
-            .map_err(|err| Error::SqliteError(err))?;
+            .map_err(db_error::SqliteError)?;
 
         let sql = "
-            SELECT txid, origin_nonce, origin_address, sponsor_nonce, sponsor_address, fee_rate
-            FROM mempool
-            WHERE fee_rate IS NOT NULL
-            ORDER BY fee_rate DESC
-            ";
-        let mut query_stmt_fee = self
-            .db
-            .prepare(&sql)
-            .map_err(|err| Error::SqliteError(err))?;
-        let mut fee_iterator = query_stmt_fee
+             SELECT txid, origin_nonce, origin_address, sponsor_nonce, sponsor_address, fee_rate,
+                    tx_fee, length, contract_call
+             FROM mempool
+             WHERE fee_rate IS NULL
+             ";
+        let mut no_fee_rate_stmt = self.db.prepare(sql).map_err(db_error::SqliteError)?;
+        let no_fee_rate_rows = no_fee_rate_stmt
             .query(NO_PARAMS)
-            .map_err(|err| Error::SqliteError(err))?;
+            .map_err(db_error::SqliteError)?;
+
+        let epoch = clarity_tx.get_epoch();
+        let window = if strategy.ranks_by_fee_rate() {
+            1
+        } else {
+            MAX_RANKED_CANDIDATES
+        };
+        let mut candidates = CandidateQueue::new(fee_rate_rows, no_fee_rate_rows, window);
 
         loop {
             if start_time.elapsed().as_millis() > settings.max_walk_time_ms as u128 {
@@ -1419,49 +1988,36 @@ impl MemPoolDB {
             }
 
             let start_with_no_estimate =
-                tx_consideration_sampler.sample(&mut rng) < settings.consider_no_estimate_tx_prob;
+                tx_consideration_sampler.sample(&mut rng) < strategy.consider_unranked_prob();
 
             // First, try to read from the retry list
-            let (candidate, update_estimate) = match candidate_cache.next() {
-                Some(tx) => {
-                    let update_estimate = tx.fee_rate.is_none();
-                    (tx, update_estimate)
-                }
+            let candidate = match candidate_cache.next() {
+                Some(tx) => tx,
                 None => {
-                    // When the retry list is empty, read from the mempool db,
-                    // randomly selecting from either the null fee-rate transactions
-                    // or those with fee-rate estimates.
+                    // When the retry list is empty, take the next candidate from the mempool,
+                    // randomly selecting from either the unranked candidates or the ranked ones.
+                    // If the selected kind has run out, take one of the other.
                     let opt_tx = if start_with_no_estimate {
-                        null_iterator
-                            .next()
-                            .map_err(|err| Error::SqliteError(err))?
+                        match candidates.next_unranked(self, &epoch, strategy)? {
+                            Some(tx) => Some(tx),
+                            None => candidates.next_ranked(self, &epoch, strategy)?,
+                        }
                     } else {
-                        fee_iterator.next().map_err(|err| Error::SqliteError(err))?
+                        match candidates.next_ranked(self, &epoch, strategy)? {
+                            Some(tx) => Some(tx),
+                            None => candidates.next_unranked(self, &epoch, strategy)?,
+                        }
                     };
                     match opt_tx {
-                        Some(row) => (MemPoolTxInfoPartial::from_row(row)?, start_with_no_estimate),
+                        Some(tx) => tx,
                         None => {
-                            // If the selected iterator is empty, check the other
-                            match if start_with_no_estimate {
-                                fee_iterator.next().map_err(|err| Error::SqliteError(err))?
-                            } else {
-                                null_iterator
-                                    .next()
-                                    .map_err(|err| Error::SqliteError(err))?
-                            } {
-                                Some(row) => (
-                                    MemPoolTxInfoPartial::from_row(row)?,
-                                    !start_with_no_estimate,
-                                ),
-                                None => {
-                                    debug!("No more transactions to consider in mempool");
-                                    break;
-                                }
-                            }
+                            debug!("No more transactions to consider in mempool");
+                            break;
                         }
                     }
                 }
             };
+            let update_estimate = candidate.fee_rate.is_none();
 
             // Check the nonces.
             let (expected_origin_nonce, retry_store_origin_nonce) =
@@ -1551,7 +2107,9 @@ impl MemPoolDB {
             match todo(clarity_tx, &consider, self.cost_estimator.as_mut())? {
                 Some(tx_event) => {
                     match tx_event {
-                        TransactionEvent::Success(_) => {
+                        TransactionEvent::Success(ref success) => {
+                            strategy.tx_mined(&success.execution_cost);
+
                             // Bump nonces in the cache for the executed transaction
                             let stored = nonce_cache.update(
                                 consider.tx.metadata.origin_address,
@@ -1606,13 +2164,9 @@ impl MemPoolDB {
             candidate_cache.reset();
         }
 
-        // drop these rusqlite statements and queries, since their existence as immutable borrows on the
-        // connection prevents us from beginning a transaction below (which requires a mutable
-        // borrow).
-        drop(null_iterator);
-        drop(fee_iterator);
-        drop(query_stmt_null);
-        drop(query_stmt_fee);
+        drop(candidates);
+        drop(fee_rate_stmt);
+        drop(no_fee_rate_stmt);
 
         if retry_store.len() > 0 {
             let tx = self.tx_begin()?;
@@ -1630,6 +2184,23 @@ impl MemPoolDB {
         Ok(total_considered)
     }
 
+    /// Estimate the cost of a mempool transaction with the miner's cost estimator.
+    /// Returns None if the transaction is gone, or the estimator has no estimate for it.
+    fn estimate_candidate_cost(
+        &self,
+        txid: &Txid,
+        epoch: &StacksEpochId,
+    ) -> Result<Option<ExecutionCost>, db_error> {
+        let tx_info = match MemPoolDB::get_tx(self.conn(), txid)? {
+            Some(tx_info) => tx_info,
+            None => return Ok(None),
+        };
+        Ok(self
+            .cost_estimator
+            .estimate_cost(&tx_info.tx.payload, epoch)
+            .ok())
+    }
+
     pub fn conn(&self) -> &DBConn {
         &self.db
     }
@@ -1640,6 +2211,8 @@ impl MemPoolDB {
             tx,
             &mut self.admitter,
             &mut self.bloom_counter,
+            &self.rbf_policy,
+            &self.limits,
         ))
     }
 
@@ -1783,90 +2356,267 @@ impl MemPoolDB {
         }
     }
 
-    /// Add a transaction to the mempool.  If it already exists, then replace it if the given fee
-    /// is higher than the one that's already there.
-    /// Carry out the mempool admission test before adding.
-    /// Don't call directly; use submit().
-    /// This is `pub` only for testing.
-    pub fn try_add_tx(
-        tx: &mut MemPoolTx,
-        chainstate: &mut StacksChainState,
-        consensus_hash: &ConsensusHash,
-        block_header_hash: &BlockHeaderHash,
-        txid: Txid,
-        tx_bytes: Vec<u8>,
-        tx_fee: u64,
-        height: u64,
+    /// Get all pending transactions that spend the given origin nonce as their origin, or the given
+    /// sponsor nonce as their sponsor.  A sponsored transaction can conflict with two different
+    /// transactions.
+    fn get_conflicting_txs(
+        conn: &DBConn,
         origin_address: &StacksAddress,
         origin_nonce: u64,
         sponsor_address: &StacksAddress,
         sponsor_nonce: u64,
-        event_observer: Option<&dyn MemPoolEventDispatcher>,
-    ) -> Result<(), MemPoolRejection> {
-        let length = tx_bytes.len() as u64;
-
-        // do we already have txs with either the same origin nonce or sponsor nonce ?
-        let prior_tx = {
-            match MemPoolDB::get_tx_metadata_by_address(tx, true, origin_address, origin_nonce)? {
-                Some(prior_tx) => Some(prior_tx),
-                None => MemPoolDB::get_tx_metadata_by_address(
-                    tx,
-                    false,
-                    sponsor_address,
-                    sponsor_nonce,
-                )?,
+    ) -> Result<Vec<MemPoolTxMetadata>, db_error> {
+        let mut conflicts = vec![];
+        if let Some(conflict) =
+            MemPoolDB::get_tx_metadata_by_address(conn, true, origin_address, origin_nonce)?
+        {
+            conflicts.push(conflict);
+        }
+        if let Some(conflict) =
+            MemPoolDB::get_tx_metadata_by_address(conn, false, sponsor_address, sponsor_nonce)?
+        {
+            if !conflicts.iter().any(|c| c.txid == conflict.txid) {
+                conflicts.push(conflict);
             }
-        };
+        }
+        Ok(conflicts)
+    }
 
-        let mut replace_reason = MemPoolDropReason::REPLACE_BY_FEE;
+    /// Get all pending transactions that spend a nonce of the given account higher than `nonce`,
+    /// either as their origin or as their sponsor.
+    fn get_txs_after_account_nonce(
+        conn: &DBConn,
+        addr: &StacksAddress,
+        nonce: u64,
+    ) -> Result<Vec<MemPoolTxMetadata>, db_error> {
+        let sql = "SELECT 
+                          txid,
+                          origin_address,
+                          origin_nonce,
+                          sponsor_address,
+                          sponsor_nonce,
+                          tx_fee,
+                          length,
+                          consensus_hash,
+                          block_header_hash,
+                          height,
+                          accept_time,
+                          last_known_sponsor_nonce,
+                          last_known_origin_nonce
+                          FROM mempool
+                          WHERE (origin_address = ?1 AND origin_nonce > ?2)
+                          OR (sponsor_address = ?1 AND sponsor_nonce > ?2)";
+        let args: &[&dyn ToSql] = &[&addr.to_string(), &u64_to_sql(nonce)?];
+        query_rows(conn, sql, args)
+    }
+
+    /// Find the pending transactions that can no longer be mined once the given transactions are
+    /// replaced by a transaction that spends `kept_nonces`.  These are the transactions of each
+    /// account that spend a nonce after one that is no longer spent by any pending transaction.
+    /// Accounts whose nonce has already moved past the vacated nonce in the chain tip are left
+    /// alone, as are all accounts if their chain nonces can't be looked up at the tip.
+    fn get_orphaned_descendants(
+        tx: &MemPoolTx,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        replaced: &[MemPoolTxMetadata],
+        kept_nonces: &[(&StacksAddress, u64)],
+    ) -> Result<Vec<Txid>, db_error> {
+        let mut chain_nonces: HashMap<StacksAddress, Option<u64>> = HashMap::new();
+        let mut descendants: Vec<Txid> = vec![];
+        let mut vacated: VecDeque<(StacksAddress, u64)> = VecDeque::new();
+        let mut visited: HashSet<(StacksAddress, u64)> = HashSet::new();
+
+        let mut vacate = |queue: &mut VecDeque<(StacksAddress, u64)>, md: &MemPoolTxMetadata| {
+            for (addr, nonce) in [
+                (md.origin_address, md.origin_nonce),
+                (md.sponsor_address, md.sponsor_nonce),
+            ] {
+                if kept_nonces.iter().any(|(a, n)| **a == addr && *n == nonce) {
+                    continue;
+                }
+                if visited.insert((addr, nonce)) {
+                    queue.push_back((addr, nonce));
+                }
+            }
+        };
+
+        for md in replaced.iter() {
+            vacate(&mut vacated, md);
+        }
+
+        while let Some((addr, nonce)) = vacated.pop_front() {
+            let chain_nonce = match chain_nonces.get(&addr) {
+                Some(chain_nonce) => *chain_nonce,
+                None => {
+                    let principal: PrincipalData = addr.into();
+                    let chain_nonce =
+                        chainstate.with_read_only_clarity_tx(&NULL_BURN_STATE_DB, tip, |conn| {
+                            StacksChainState::get_nonce(conn, &principal)
+                        });
+                    chain_nonces.insert(addr, chain_nonce);
+                    chain_nonce
+                }
+            };
+            match chain_nonce {
+                Some(chain_nonce) if chain_nonce <= nonce => {}
+                _ => continue,
+            }
+
+            for md in MemPoolDB::get_txs_after_account_nonce(tx, &addr, nonce)?.into_iter() {
+                if replaced.iter().any(|r| r.txid == md.txid) || descendants.contains(&md.txid) {
+                    continue;
+                }
+                vacate(&mut vacated, &md);
+                descendants.push(md.txid);
+            }
+        }
+
+        Ok(descendants)
+    }
+
+    /// Add a transaction to the mempool.  If it conflicts with pending transactions -- i.e. it
+    /// spends the same origin or sponsor nonces -- then replace them if they were submitted in a
+    /// different fork, or if the given fee is high enough according to the mempool's
+    /// `ReplaceByFeePolicy`.  Pending transactions that can no longer be mined after a
+    /// replace-by-fee are dropped as well.
+    /// Carry out the mempool admission test before adding.
+    /// Returns the transactions that were dropped, which the caller must announce once it commits.
+    /// Don't call directly; use submit().
+    /// This is `pub` only for testing.
+    pub fn try_add_tx(
+        tx: &mut MemPoolTx,
+        chainstate: &mut StacksChainState,
+        consensus_hash: &ConsensusHash,
+        block_header_hash: &BlockHeaderHash,
+        txid: Txid,
+        tx_bytes: Vec<u8>,
+        tx_fee: u64,
+        height: u64,
+        origin_address: &StacksAddress,
+        origin_nonce: u64,
+        sponsor_address: &StacksAddress,
+        sponsor_nonce: u64,
+    ) -> Result<MemPoolDroppedTxs, MemPoolRejection> {
+        let length = tx_bytes.len() as u64;
+        let account_nonces = [
+            (origin_address, origin_nonce),
+            (sponsor_address, sponsor_nonce),
+        ];
+
+        // do we already have txs with either the same origin nonce or sponsor nonce ?
+        let conflicts = MemPoolDB::get_conflicting_txs(
+            tx,
+            origin_address,
+            origin_nonce,
+            sponsor_address,
+            sponsor_nonce,
+        )?;
+        let total_conflict_fee = conflicts
+            .iter()
+            .fold(0u64, |total, c| total.saturating_add(c.tx_fee));
 
         // if so, is this a replace-by-fee? or a replace-in-chain-tip?
-        let add_tx = if let Some(ref prior_tx) = prior_tx {
-            if tx_fee > prior_tx.tx_fee {
-                // is this a replace-by-fee ?
-                debug!(
-                    "Can replace {} with {} for {},{} by fee ({} < {})",
-                    &prior_tx.txid, &txid, origin_address, origin_nonce, &prior_tx.tx_fee, &tx_fee
-                );
-                replace_reason = MemPoolDropReason::REPLACE_BY_FEE;
-                true
-            } else if !MemPoolDB::are_blocks_in_same_fork(
-                chainstate,
-                &prior_tx.consensus_hash,
-                &prior_tx.block_header_hash,
-                consensus_hash,
-                block_header_hash,
-            )? {
-                // is this a replace-across-fork ?
-                debug!(
-                    "Can replace {} with {} for {},{} across fork",
-                    &prior_tx.txid, &txid, origin_address, origin_nonce
-                );
-                replace_reason = MemPoolDropReason::REPLACE_ACROSS_FORK;
-                true
-            } else {
-                // there's a >= fee tx in this fork, cannot add
-                info!("TX conflicts with sponsor/origin nonce in same fork with >= fee";
+        let (replaced_by_fee, replaced_across_fork) = if conflicts.is_empty() {
+            // no conflicting TX with this origin/sponsor, go ahead and add
+            (vec![], vec![])
+        } else if tx_fee >= tx.rbf_policy.min_replacement_fee(total_conflict_fee) {
+            // is this a replace-by-fee ?
+            debug!(
+                "Can replace {:?} with {} for {},{} by fee ({} < {})",
+                conflicts.iter().map(|c| &c.txid).collect::<Vec<_>>(),
+                &txid,
+                origin_address,
+                origin_nonce,
+                total_conflict_fee,
+                tx_fee
+            );
+            (conflicts, vec![])
+        } else {
+            let mut same_fork = vec![];
+            let mut across_fork = vec![];
+            for conflict in conflicts.into_iter() {
+                if MemPoolDB::are_blocks_in_same_fork(
+                    chainstate,
+                    &conflict.consensus_hash,
+                    &conflict.block_header_hash,
+                    consensus_hash,
+                    block_header_hash,
+                )? {
+                    same_fork.push(conflict);
+                } else {
+                    // is this a replace-across-fork ?
+                    debug!(
+                        "Can replace {} with {} for {},{} across fork",
+                        &conflict.txid, &txid, origin_address, origin_nonce
+                    );
+                    across_fork.push(conflict);
+                }
+            }
+
+            let same_fork_fee = same_fork
+                .iter()
+                .fold(0u64, |total, c| total.saturating_add(c.tx_fee));
+            let min_fee = tx.rbf_policy.min_replacement_fee(same_fork_fee);
+            if !same_fork.is_empty() && tx_fee < min_fee {
+                // there's a tx in this fork that this one doesn't pay enough to replace
+                info!("TX conflicts with sponsor/origin nonce in same fork with insufficient fee bump";
                       "new_txid" => %txid,
-                      "old_txid" => %prior_tx.txid,
+                      "old_txids" => ?same_fork.iter().map(|c| &c.txid).collect::<Vec<_>>(),
                       "origin_addr" => %origin_address,
                       "origin_nonce" => origin_nonce,
                       "sponsor_addr" => %sponsor_address,
                       "sponsor_nonce" => sponsor_nonce,
                       "new_fee" => tx_fee,
-                      "old_fee" => prior_tx.tx_fee);
-                false
+                      "old_fee" => same_fork_fee,
+                      "min_fee" => min_fee);
+                return Err(MemPoolRejection::ConflictingNonceInMempool);
             }
+            (same_fork, across_fork)
+        };
+
+        // replacing a tx can leave a gap in its accounts' nonces, which strands their later txs
+        let descendants = if replaced_by_fee.is_empty() {
+            vec![]
         } else {
-            // no conflicting TX with this origin/sponsor, go ahead and add
-            true
+            MemPoolDB::get_orphaned_descendants(
+                tx,
+                chainstate,
+                &StacksBlockId::new(consensus_hash, block_header_hash),
+                &replaced_by_fee,
+                &account_nonces,
+            )?
         };
 
-        if !add_tx {
-            return Err(MemPoolRejection::ConflictingNonceInMempool);
-        }
+        let replaced_txids: Vec<Txid> = replaced_by_fee
+            .iter()
+            .chain(replaced_across_fork.iter())
+            .map(|c| c.txid)
+            .collect();
 
-        tx.update_bloom_counter(height, &txid, prior_tx.as_ref().map(|tx| tx.txid.clone()))?;
+        tx.update_bloom_counter(height, &txid, &replaced_txids)?;
+
+        let mut rbf_txids: Vec<Txid> = replaced_by_fee.into_iter().map(|c| c.txid).collect();
+        rbf_txids.extend(descendants);
+        let across_fork_txids: Vec<Txid> =
+            replaced_across_fork.into_iter().map(|c| c.txid).collect();
+
+        MemPoolDB::inner_drop_txs(tx, &rbf_txids)?;
+        MemPoolDB::inner_drop_txs(tx, &across_fork_txids)?;
+        MemPoolDB::record_tx_replacements(
+            tx,
+            &rbf_txids,
+            &txid,
+            MemPoolDropReason::REPLACE_BY_FEE,
+            height,
+        )?;
+        MemPoolDB::record_tx_replacements(
+            tx,
+            &across_fork_txids,
+            &txid,
+            MemPoolDropReason::REPLACE_ACROSS_FORK,
+            height,
+        )?;
 
         let sql = "INSERT OR REPLACE INTO mempool (
             txid,
@@ -1903,14 +2653,357 @@ impl MemPoolDB {
 
         tx.update_mempool_pager(&txid)?;
 
-        // broadcast drop event if a tx is being replaced
-        if let (Some(prior_tx), Some(event_observer)) = (prior_tx, event_observer) {
-            event_observer.mempool_txs_dropped(vec![prior_tx.txid], replace_reason);
+        let mut dropped = vec![];
+        if !rbf_txids.is_empty() {
+            dropped.push((rbf_txids, MemPoolDropReason::REPLACE_BY_FEE));
+        }
+        if !across_fork_txids.is_empty() {
+            dropped.push((across_fork_txids, MemPoolDropReason::REPLACE_ACROSS_FORK));
+        }
+        Ok(dropped)
+    }
+
+    /// Tell the event observer about transactions that were dropped by a committed submission.
+    fn announce_dropped_txs(
+        event_observer: Option<&dyn MemPoolEventDispatcher>,
+        dropped: MemPoolDroppedTxs,
+    ) {
+        if let Some(event_observer) = event_observer {
+            for (txids, reason) in dropped.into_iter() {
+                event_observer.mempool_txs_dropped(txids, reason);
+            }
+        }
+    }
+
+    /// Remember that the given transactions were dropped in favor of `replaced_by`.
+    fn record_tx_replacements(
+        tx: &DBTx,
+        txids: &[Txid],
+        replaced_by: &Txid,
+        reason: MemPoolDropReason,
+        height: u64,
+    ) -> Result<(), db_error> {
+        let sql = "INSERT OR REPLACE INTO tx_replacements (txid, replaced_by, reason, height, replace_time) VALUES (?1, ?2, ?3, ?4, ?5)";
+        let reason = reason.to_string();
+        for txid in txids.iter() {
+            if txid == replaced_by {
+                // re-submitted in another fork
+                continue;
+            }
+            let args: &[&dyn ToSql] = &[
+                txid,
+                replaced_by,
+                &reason,
+                &u64_to_sql(height)?,
+                &u64_to_sql(get_epoch_time_secs())?,
+            ];
+            tx.execute(sql, args)?;
+        }
+        Ok(())
+    }
+
+    /// Find out what became of a transaction that was replaced in the mempool.  Returns the chain
+    /// of replacements starting from `txid`: the transaction that replaced it, the transaction
+    /// that replaced that one, and so on.  Empty if `txid` was never replaced (or if its
+    /// replacement has since been garbage-collected).
+    pub fn get_tx_replacements(
+        conn: &DBConn,
+        txid: &Txid,
+    ) -> Result<Vec<MemPoolTxReplacement>, db_error> {
+        let sql =
+            "SELECT txid, replaced_by, reason, replace_time FROM tx_replacements WHERE txid = ?1";
+        let mut replacements: Vec<MemPoolTxReplacement> = vec![];
+        let mut seen = HashSet::new();
+        let mut next_txid = *txid;
+        while seen.insert(next_txid) {
+            let args: &[&dyn ToSql] = &[&next_txid];
+            match query_row::<MemPoolTxReplacement, _>(conn, sql, args)? {
+                Some(replacement) => {
+                    next_txid = replacement.replaced_by;
+                    replacements.push(replacement);
+                }
+                None => break,
+            }
+        }
+        Ok(replacements)
+    }
+
+    /// List a page of the pending transactions that match `query`.  Transactions are ordered by
+    /// nonce for address queries, and by arrival time for contract-call queries.  Returns the
+    /// total number of matching transactions along with the page.
+    /// Mined transactions stay in the mempool until they're garbage-collected, so address queries
+    /// should start at the account's nonce in the chain tip to only list unmined transactions.
+    pub fn get_pending_txs(
+        conn: &DBConn,
+        query: &PendingTxQuery,
+        offset: u64,
+        limit: u64,
+    ) -> Result<(u64, Vec<MemPoolPendingTx>), db_error> {
+        let (filter, order, key, nonces) = match query {
+            PendingTxQuery::Address {
+                address,
+                is_origin,
+                min_nonce,
+                max_nonce,
+            } => {
+                // nonces are stored as signed integers
+                let min_nonce = u64_to_sql(cmp::min(*min_nonce, i64::MAX as u64))?;
+                let max_nonce = u64_to_sql(cmp::min(*max_nonce, i64::MAX as u64))?;
+                let (filter, order) = match is_origin {
+                    Some(true) => (
+                        "origin_address = ?1 AND origin_nonce BETWEEN ?2 AND ?3",
+                        "origin_nonce ASC",
+                    ),
+                    // the sponsor of an unsponsored transaction is its origin
+                    Some(false) => (
+                        "sponsor_address = ?1 AND origin_address != ?1 AND sponsor_nonce BETWEEN ?2 AND ?3",
+                        "sponsor_nonce ASC",
+                    ),
+                    None => (
+                        "((origin_address = ?1 AND origin_nonce BETWEEN ?2 AND ?3) OR (sponsor_address = ?1 AND sponsor_nonce BETWEEN ?2 AND ?3))",
+                        "CASE WHEN origin_address = ?1 THEN origin_nonce ELSE sponsor_nonce END ASC",
+                    ),
+                };
+                (
+                    filter,
+                    order,
+                    address.to_string(),
+                    Some((min_nonce, max_nonce)),
+                )
+            }
+            PendingTxQuery::ContractCall(contract_id) => (
+                "contract_call = ?1",
+                "accept_time ASC",
+                contract_id.to_string(),
+                None,
+            ),
         };
+        let mut args: Vec<&dyn ToSql> = vec![&key];
+        if let Some((min_nonce, max_nonce)) = nonces.as_ref() {
+            args.push(min_nonce);
+            args.push(max_nonce);
+        }
+        let args = args.as_slice();
+
+        let total = query_int(
+            conn,
+            &format!("SELECT COUNT(*) FROM mempool WHERE {}", filter),
+            args,
+        )?;
+        let sql = format!(
+            "SELECT * FROM mempool WHERE {} ORDER BY {}, txid ASC LIMIT {} OFFSET {}",
+            filter,
+            order,
+            u64_to_sql(limit)?,
+            u64_to_sql(offset)?
+        );
+        let txs = query_rows(conn, &sql, args)?;
+        Ok((total as u64, txs))
+    }
+
+    /// Get the number of transactions in the mempool, and their total size in bytes
+    fn get_size(conn: &DBConn) -> Result<(u64, u64), db_error> {
+        let sql = "SELECT tx_count, bytes FROM mempool_size";
+        Ok(query_row(conn, sql, NO_PARAMS)?.unwrap_or((0, 0)))
+    }
 
+    /// Get the number of pending transactions that spend the account's nonces, either as their
+    /// origin or as their sponsor, and their total size in bytes.  Transactions that spend a nonce
+    /// below `chain_nonce` have already been mined, and are not counted.
+    fn get_account_size(
+        conn: &DBConn,
+        addr: &StacksAddress,
+        chain_nonce: u64,
+    ) -> Result<(u64, u64), db_error> {
+        let sql = "SELECT COUNT(*), IFNULL(SUM(length), 0) FROM mempool
+                   WHERE (origin_address = ?1 AND origin_nonce >= ?2)
+                   OR (sponsor_address = ?1 AND sponsor_nonce >= ?2)";
+        let args: &[&dyn ToSql] = &[&addr.to_string(), &u64_to_sql(chain_nonce)?];
+        Ok(query_row(conn, sql, args)?.unwrap_or((0, 0)))
+    }
+
+    /// Check that adding a transaction doesn't take its origin or sponsor account past the
+    /// mempool's per-account limits.  The transaction must already be stored.
+    /// `accounts` holds the (address, nonce, is_origin) of each account whose nonce it spends.
+    fn check_account_limits(
+        tx: &MemPoolTx,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        accounts: &[(StacksAddress, u64, bool)],
+    ) -> Result<(), MemPoolRejection> {
+        let limits = tx.limits;
+        let check_size =
+            limits.max_txs_per_account.is_some() || limits.max_bytes_per_account.is_some();
+        for (addr, nonce, is_origin) in accounts.iter() {
+            if limits.max_nonce_gap.is_none() && !check_size {
+                continue;
+            }
+            let principal: PrincipalData = (*addr).into();
+            let chain_nonce =
+                chainstate.with_read_only_clarity_tx(&NULL_BURN_STATE_DB, tip, |conn| {
+                    StacksChainState::get_nonce(conn, &principal)
+                });
+
+            if let Some(max_nonce_gap) = limits.max_nonce_gap {
+                if let Some(chain_nonce) = chain_nonce {
+                    let max_nonce = chain_nonce.saturating_add(max_nonce_gap);
+                    if *nonce > max_nonce {
+                        return Err(MemPoolRejection::TooMuchChaining {
+                            max_nonce,
+                            actual_nonce: *nonce,
+                            principal,
+                            is_origin: *is_origin,
+                        });
+                    }
+                }
+            }
+
+            if !check_size {
+                continue;
+            }
+            let (num_txs, bytes) = MemPoolDB::get_account_size(tx, addr, chain_nonce.unwrap_or(0))?;
+            if let Some(max_txs) = limits.max_txs_per_account {
+                if num_txs > max_txs {
+                    return Err(MemPoolRejection::TooManyPendingTransactions {
+                        max_txs,
+                        principal: (*addr).into(),
+                        is_origin: *is_origin,
+                    });
+                }
+            }
+            if let Some(max_bytes) = limits.max_bytes_per_account {
+                if bytes > max_bytes {
+                    return Err(MemPoolRejection::TooManyPendingBytes {
+                        max_bytes,
+                        actual_bytes: bytes,
+                        principal: (*addr).into(),
+                        is_origin: *is_origin,
+                    });
+                }
+            }
+        }
         Ok(())
     }
 
+    /// Get the transaction that would be evicted next if the mempool were full, along with its
+    /// fee rate: the one with the lowest fee rate, and of those, the one that arrived last.
+    fn get_next_eviction(
+        conn: &DBConn,
+        exclude_txid: Option<&Txid>,
+    ) -> Result<Option<(MemPoolTxMetadata, Option<f64>)>, db_error> {
+        let sql = "SELECT 
+                          txid,
+                          origin_address,
+                          origin_nonce,
+                          sponsor_address,
+                          sponsor_nonce,
+                          tx_fee,
+                          length,
+                          consensus_hash,
+                          block_header_hash,
+                          height,
+                          accept_time,
+                          last_known_sponsor_nonce,
+                          last_known_origin_nonce,
+                          fee_rate
+                          FROM mempool WHERE txid != ?1
+                          ORDER BY fee_rate ASC, accept_time DESC, rowid DESC LIMIT 1";
+        let exclude_txid = exclude_txid.cloned().unwrap_or(Txid([0u8; 32]));
+        let args: &[&dyn ToSql] = &[&exclude_txid];
+        let mut stmt = conn.prepare(sql)?;
+        let mut rows = stmt.query(args)?;
+        match rows.next()? {
+            Some(row) => {
+                let md = MemPoolTxMetadata::from_row(row)?;
+                let fee_rate: Option<f64> = row.get("fee_rate")?;
+                Ok(Some((md, fee_rate)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Get the fee rate that a new transaction must beat to get into the mempool, if it's full.
+    /// Transactions without a fee rate estimate count as having a fee rate of 0.
+    /// Returns None if the mempool is not full.
+    pub fn get_eviction_floor(&self) -> Result<Option<f64>, db_error> {
+        if !self.limits.is_capped() {
+            return Ok(None);
+        }
+        let (tx_count, bytes) = MemPoolDB::get_size(self.conn())?;
+        if !self.limits.is_full(tx_count, bytes) {
+            return Ok(None);
+        }
+        Ok(MemPoolDB::get_next_eviction(self.conn(), None)?
+            .map(|(_, fee_rate)| fee_rate.unwrap_or(0.0)))
+    }
+
+    /// Evict the lowest fee-rate transactions until the mempool is within its limits, along with
+    /// any transactions that can no longer be mined once they are gone.
+    /// If the newly-added transaction `new_txid` would be evicted, then it's rejected instead,
+    /// unless `keep_new_tx` is set.
+    /// Returns the txids of the evicted transactions.
+    /// Don't call directly; it's `pub` only for testing.
+    pub fn enforce_limits(
+        tx: &mut MemPoolTx,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        new_txid: &Txid,
+        new_fee_rate: Option<f64>,
+        keep_new_tx: bool,
+    ) -> Result<Vec<Txid>, MemPoolRejection> {
+        let mut evicted = vec![];
+        if !tx.limits.is_capped() {
+            return Ok(evicted);
+        }
+
+        let exclude_txid = if keep_new_tx { Some(new_txid) } else { None };
+        loop {
+            let (tx_count, bytes) = MemPoolDB::get_size(tx)?;
+            if !tx.limits.is_exceeded(tx_count, bytes) {
+                break;
+            }
+
+            let (lowest, _) = match MemPoolDB::get_next_eviction(tx, exclude_txid)? {
+                Some(next) => next,
+                None => break,
+            };
+
+            let mut dropped = vec![];
+            if lowest.txid != *new_txid {
+                dropped = MemPoolDB::get_orphaned_descendants(
+                    tx,
+                    chainstate,
+                    tip,
+                    std::slice::from_ref(&lowest),
+                    &[],
+                )?;
+            }
+
+            if lowest.txid == *new_txid || (!keep_new_tx && dropped.contains(new_txid)) {
+                let eviction_floor = MemPoolDB::get_next_eviction(tx, Some(new_txid))?
+                    .map(|(_, fee_rate)| fee_rate.unwrap_or(0.0))
+                    .unwrap_or(0.0);
+                info!("Mempool is full, and TX fee rate is below the eviction floor";
+                      "txid" => %new_txid,
+                      "fee_rate" => ?new_fee_rate,
+                      "eviction_floor" => eviction_floor);
+                return Err(MemPoolRejection::FeeRateBelowEvictionFloor {
+                    fee_rate: new_fee_rate.unwrap_or(0.0),
+                    eviction_floor,
+                });
+            }
+
+            dropped.insert(0, lowest.txid);
+            dropped.retain(|txid| txid != new_txid);
+            debug!("Mempool is full; evicting {} transaction(s)", dropped.len();
+                   "txids" => ?dropped);
+
+            MemPoolDB::inner_drop_txs(tx, &dropped)?;
+            evicted.extend(dropped);
+        }
+        Ok(evicted)
+    }
+
     /// Garbage-collect the mempool.  Remove transactions that have a given number of
     /// confirmations.
     pub fn garbage_collect(
@@ -1929,6 +3022,10 @@ impl MemPoolDB {
         let sql = "DELETE FROM mempool WHERE height < ?1";
 
         tx.execute(sql, args)?;
+
+        let sql = "DELETE FROM tx_replacements WHERE height < ?1";
+        tx.execute(sql, args)?;
+
         increment_stx_mempool_gc();
         Ok(())
     }
@@ -1970,6 +3067,9 @@ impl MemPoolDB {
     }
 
     /// Submit a transaction to the mempool at a particular chain tip.
+    /// If `accept_time` is given, it's stored in place of the current time.
+    /// Returns the transactions that were dropped to make room for it, which must only be
+    /// announced once `mempool_tx` commits.
     fn tx_submit(
         mempool_tx: &mut MemPoolTx,
         chainstate: &mut StacksChainState,
@@ -1977,9 +3077,9 @@ impl MemPoolDB {
         block_hash: &BlockHeaderHash,
         tx: &StacksTransaction,
         do_admission_checks: bool,
-        event_observer: Option<&dyn MemPoolEventDispatcher>,
         fee_rate_estimate: Option<f64>,
-    ) -> Result<(), MemPoolRejection> {
+        accept_time: Option<u64>,
+    ) -> Result<MemPoolDroppedTxs, MemPoolRejection> {
         test_debug!(
             "Mempool submit {} at {}/{}",
             tx.txid(),
@@ -2030,7 +3130,7 @@ impl MemPoolDB {
             mempool_tx.admitter.will_admit_tx(chainstate, tx, len)?;
         }
 
-        MemPoolDB::try_add_tx(
+        let mut dropped = MemPoolDB::try_add_tx(
             mempool_tx,
             chainstate,
             &consensus_hash,
@@ -2043,21 +3143,76 @@ impl MemPoolDB {
             origin_nonce,
             &sponsor_address,
             sponsor_nonce,
-            event_observer,
         )?;
 
+        MemPoolDB::set_contract_call(mempool_tx, tx)?;
+
+        let tip = StacksBlockId::new(consensus_hash, block_hash);
+        if do_admission_checks {
+            let mut accounts = vec![(origin_address, origin_nonce, true)];
+            if tx.sponsor_address().is_some() {
+                accounts.push((sponsor_address, sponsor_nonce, false));
+            }
+            MemPoolDB::check_account_limits(mempool_tx, chainstate, &tip, &accounts)?;
+        }
+
         mempool_tx
             .execute(
                 "UPDATE mempool SET fee_rate = ? WHERE txid = ?",
                 rusqlite::params![fee_rate_estimate, &txid],
             )
             .map_err(db_error::from)?;
+        if let Some(accept_time) = accept_time {
+            mempool_tx
+                .execute(
+                    "UPDATE mempool SET accept_time = ? WHERE txid = ?",
+                    rusqlite::params![u64_to_sql(accept_time)?, &txid],
+                )
+                .map_err(db_error::from)?;
+        }
+
+        let evicted = MemPoolDB::enforce_limits(
+            mempool_tx,
+            chainstate,
+            &tip,
+            &txid,
+            fee_rate_estimate,
+            !do_admission_checks,
+        )?;
+        if !evicted.is_empty() {
+            dropped.push((evicted, MemPoolDropReason::MEMPOOL_FULL));
+        }
 
         if let Err(e) = monitoring::mempool_accepted(&txid, &chainstate.root_path) {
             warn!("Failed to monitor TX receive: {:?}", e; "txid" => %txid);
         }
 
-        Ok(())
+        Ok(dropped)
+    }
+
+    /// Estimate a transaction's fee rate, if the cost estimator has an estimate for it
+    fn estimate_fee_rate(
+        &self,
+        tx: &StacksTransaction,
+        block_limit: &ExecutionCost,
+        stacks_epoch_id: &StacksEpochId,
+    ) -> Result<Option<f64>, MemPoolRejection> {
+        match cost_estimates::estimate_fee_rate(
+            tx,
+            self.cost_estimator.as_ref(),
+            self.metric.as_ref(),
+            block_limit,
+            stacks_epoch_id,
+        ) {
+            Ok(x) => Ok(Some(x)),
+            Err(EstimatorError::NoEstimateAvailable) => Ok(None),
+            Err(e) => {
+                warn!("Error while estimating mempool tx rate";
+                      "txid" => %tx.txid(),
+                      "error" => ?e);
+                Err(MemPoolRejection::EstimatorError(e))
+            }
+        }
     }
 
     /// One-shot submit
@@ -2077,38 +3232,21 @@ impl MemPoolDB {
             return Err(MemPoolRejection::TemporarilyBlacklisted);
         }
 
-        let estimator_result = cost_estimates::estimate_fee_rate(
-            tx,
-            self.cost_estimator.as_ref(),
-            self.metric.as_ref(),
-            block_limit,
-            stacks_epoch_id,
-        );
-
+        let fee_rate = self.estimate_fee_rate(tx, block_limit, stacks_epoch_id)?;
         let mut mempool_tx = self.tx_begin().map_err(MemPoolRejection::DBError)?;
 
-        let fee_rate = match estimator_result {
-            Ok(x) => Some(x),
-            Err(EstimatorError::NoEstimateAvailable) => None,
-            Err(e) => {
-                warn!("Error while estimating mempool tx rate";
-                      "txid" => %tx.txid(),
-                      "error" => ?e);
-                return Err(MemPoolRejection::EstimatorError(e));
-            }
-        };
-
-        MemPoolDB::tx_submit(
+        let dropped = MemPoolDB::tx_submit(
             &mut mempool_tx,
             chainstate,
             consensus_hash,
             block_hash,
             tx,
             true,
-            event_observer,
             fee_rate,
+            None,
         )?;
         mempool_tx.commit().map_err(MemPoolRejection::DBError)?;
+        MemPoolDB::announce_dropped_txs(event_observer, dropped);
         Ok(())
     }
 
@@ -2126,17 +3264,18 @@ impl MemPoolDB {
 
         let fee_estimate = Some(miner_estimate);
 
-        MemPoolDB::tx_submit(
+        let dropped = MemPoolDB::tx_submit(
             &mut mempool_tx,
             chainstate,
             consensus_hash,
             block_hash,
             tx,
             false,
-            event_observer,
             fee_estimate,
+            None,
         )?;
         mempool_tx.commit().map_err(MemPoolRejection::DBError)?;
+        MemPoolDB::announce_dropped_txs(event_observer, dropped);
         Ok(())
     }
 
@@ -2191,8 +3330,8 @@ impl MemPoolDB {
             block_hash,
             &tx,
             false,
-            None,
             fee_rate,
+            None,
         )?;
         mempool_tx.commit().map_err(MemPoolRejection::DBError)?;
         Ok(())
@@ -2636,4 +3775,57 @@ impl MemPoolDB {
         }
         Ok(num_written)
     }
+
+    /// Write out every transaction in the mempool, oldest first, along with its accept time and
+    /// fee rate estimate.  Returns the number of transactions written.
+    pub fn export_txs<W: Write>(&self, fd: &mut W) -> Result<u64, ChainstateError> {
+        let sql =
+            "SELECT tx, accept_time, fee_rate FROM mempool ORDER BY accept_time ASC, rowid ASC";
+        let txs: Vec<MemPoolExportedTx> = query_rows(self.conn(), sql, NO_PARAMS)?;
+        for exported_tx in txs.iter() {
+            exported_tx
+                .consensus_serialize(fd)
+                .map_err(ChainstateError::CodecError)?;
+        }
+        Ok(txs.len() as u64)
+    }
+
+    /// Admit a transaction exported from another node's mempool through the usual admission
+    /// checks.  If it is admitted, its original accept time is kept, as is the exporting node's
+    /// fee rate estimate (if it had one) in place of this node's.
+    pub fn import_tx(
+        &mut self,
+        chainstate: &mut StacksChainState,
+        consensus_hash: &ConsensusHash,
+        block_hash: &BlockHeaderHash,
+        exported_tx: &MemPoolExportedTx,
+        block_limit: &ExecutionCost,
+        stacks_epoch_id: &StacksEpochId,
+    ) -> Result<(), MemPoolRejection> {
+        let tx = &exported_tx.tx;
+        if self.is_tx_blacklisted(&tx.txid())? {
+            // don't re-store this transaction
+            test_debug!("Transaction {} is temporarily blacklisted", &tx.txid());
+            return Err(MemPoolRejection::TemporarilyBlacklisted);
+        }
+
+        let fee_rate = match exported_tx.fee_rate {
+            Some(fee_rate) => Some(fee_rate),
+            None => self.estimate_fee_rate(tx, block_limit, stacks_epoch_id)?,
+        };
+        let mut mempool_tx = self.tx_begin().map_err(MemPoolRejection::DBError)?;
+
+        MemPoolDB::tx_submit(
+            &mut mempool_tx,
+            chainstate,
+            consensus_hash,
+            block_hash,
+            tx,
+            true,
+            fee_rate,
+            Some(exported_tx.accept_time),
+        )?;
+        mempool_tx.commit().map_err(MemPoolRejection::DBError)?;
+        Ok(())
+    }
 }
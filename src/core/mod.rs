@@ -28,6 +28,7 @@ use crate::types::chainstate::{BlockHeaderHash, BurnchainHeaderHash};
 use stacks_common::types::StacksEpoch as GenericStacksEpoch;
 pub use stacks_common::types::StacksEpochId;
 pub mod mempool;
+pub mod tx_selection;
 
 #[cfg(test)]
 pub mod tests;
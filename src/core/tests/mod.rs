@@ -29,6 +29,7 @@ use crate::chainstate::stacks::db::StreamCursor;
 use crate::chainstate::stacks::events::StacksTransactionReceipt;
 use crate::chainstate::stacks::miner::TransactionResult;
 use crate::chainstate::stacks::test::codec_all_transactions;
+use crate::chainstate::stacks::tests::{make_user_contract_call, make_user_stacks_transfer};
 use crate::chainstate::stacks::{
     db::blocks::MemPoolRejection, db::StacksChainState, index::MarfTrieId, CoinbasePayload,
     Error as ChainstateError, SinglesigHashMode, SinglesigSpendingCondition, StacksPrivateKey,
@@ -41,11 +42,21 @@ use crate::chainstate::stacks::{
     C32_ADDRESS_VERSION_MAINNET_SINGLESIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
 };
 use crate::core::mempool::db_get_all_nonces;
+use crate::core::mempool::MemPoolDropReason;
+use crate::core::mempool::MemPoolExportedTx;
+use crate::core::mempool::MemPoolLimits;
+use crate::core::mempool::MemPoolTx;
 use crate::core::mempool::MemPoolWalkSettings;
+use crate::core::mempool::PendingTxQuery;
+use crate::core::mempool::ReplaceByFeePolicy;
 use crate::core::mempool::TxTag;
 use crate::core::mempool::{BLOOM_COUNTER_DEPTH, BLOOM_COUNTER_ERROR_RATE, MAX_BLOOM_COUNTER_TXS};
+use crate::core::tx_selection::{TxSelectionList, TxSelectionSettings, TxSelectionStrategyName};
+use crate::core::StacksEpochId;
 use crate::core::FIRST_BURNCHAIN_CONSENSUS_HASH;
 use crate::core::FIRST_STACKS_BLOCK_HASH;
+use crate::cost_estimates::metrics::UnitMetric;
+use crate::cost_estimates::UnitEstimator;
 use crate::net::Error as NetError;
 use crate::net::HttpResponseType;
 use crate::net::MemPoolSyncData;
@@ -262,7 +273,6 @@ fn mempool_walk_over_fork() {
             origin_nonce,
             &sponsor_address,
             sponsor_nonce,
-            None,
         )
         .unwrap();
 
@@ -521,7 +531,6 @@ fn mempool_walk_over_fork() {
         origin_nonce,
         &sponsor_address,
         sponsor_nonce,
-        None,
     )
     .is_err());
 
@@ -574,7 +583,6 @@ fn mempool_walk_over_fork() {
         origin_nonce,
         &sponsor_address,
         sponsor_nonce,
-        None,
     )
     .unwrap();
 
@@ -653,7 +661,6 @@ fn test_iterate_candidates_consider_no_estimate_tx_prob() {
             nonce,
             &sponsor_address,
             nonce,
-            None,
         )
         .unwrap();
 
@@ -849,7 +856,6 @@ fn test_iterate_candidates_skipped_transaction() {
             nonce,
             &sponsor_address,
             nonce,
-            None,
         )
         .unwrap();
 
@@ -962,7 +968,6 @@ fn test_iterate_candidates_processing_error_transaction() {
             nonce,
             &sponsor_address,
             nonce,
-            None,
         )
         .unwrap();
 
@@ -1077,7 +1082,6 @@ fn test_iterate_candidates_problematic_transaction() {
             nonce,
             &sponsor_address,
             nonce,
-            None,
         )
         .unwrap();
 
@@ -1206,7 +1210,6 @@ fn test_iterate_candidates_concurrent_write_lock() {
             nonce,
             &sponsor_address,
             nonce,
-            None,
         )
         .unwrap();
 
@@ -1364,7 +1367,6 @@ fn mempool_do_not_replace_tx() {
         origin_nonce,
         &sponsor_address,
         sponsor_nonce,
-        None,
     )
     .unwrap();
 
@@ -1392,7 +1394,6 @@ fn mempool_do_not_replace_tx() {
         origin_nonce,
         &sponsor_address,
         sponsor_nonce,
-        None,
     )
     .unwrap_err();
     assert!(match err_resp {
@@ -1465,7 +1466,6 @@ fn mempool_db_load_store_replace_tx() {
             origin_nonce,
             &sponsor_address,
             sponsor_nonce,
-            None,
         )
         .unwrap();
 
@@ -1522,7 +1522,6 @@ fn mempool_db_load_store_replace_tx() {
             origin_nonce,
             &sponsor_address,
             sponsor_nonce,
-            None,
         )
         .unwrap();
 
@@ -1582,7 +1581,6 @@ fn mempool_db_load_store_replace_tx() {
             origin_nonce,
             &sponsor_address,
             sponsor_nonce,
-            None,
         )
         .unwrap_err()
         {
@@ -1721,7 +1719,6 @@ fn mempool_db_test_rbf() {
         origin_nonce,
         &sponsor_address,
         sponsor_nonce,
-        None,
     )
     .unwrap();
     assert!(MemPoolDB::db_has_tx(&mempool_tx, &txid).unwrap());
@@ -1770,7 +1767,6 @@ fn mempool_db_test_rbf() {
         origin_nonce,
         &sponsor_address,
         sponsor_nonce,
-        None,
     )
     .unwrap();
 
@@ -1852,7 +1848,6 @@ fn test_add_txs_bloom_filter() {
                 origin_nonce,
                 &sponsor_addr,
                 sponsor_nonce,
-                None,
             )
             .unwrap();
 
@@ -1962,7 +1957,6 @@ fn test_txtags() {
                 origin_nonce,
                 &sponsor_addr,
                 sponsor_nonce,
-                None,
             )
             .unwrap();
 
@@ -2055,7 +2049,6 @@ fn test_make_mempool_sync_data() {
                     origin_nonce,
                     &sponsor_addr,
                     sponsor_nonce,
-                    None,
                 )
                 .unwrap();
 
@@ -2232,7 +2225,6 @@ fn test_find_next_missing_transactions() {
             origin_nonce,
             &sponsor_addr,
             sponsor_nonce,
-            None,
         )
         .unwrap();
 
@@ -2504,7 +2496,6 @@ fn test_stream_txs() {
             origin_nonce,
             &sponsor_addr,
             sponsor_nonce,
-            None,
         )
         .unwrap();
 
@@ -2847,7 +2838,6 @@ fn test_drop_and_blacklist_txs_by_time() {
             origin_nonce,
             &sponsor_addr,
             sponsor_nonce,
-            None,
         )
         .unwrap();
 
@@ -2966,7 +2956,6 @@ fn test_drop_and_blacklist_txs_by_size() {
             origin_nonce,
             &sponsor_addr,
             sponsor_nonce,
-            None,
         )
         .unwrap();
 
@@ -3013,3 +3002,979 @@ fn test_drop_and_blacklist_txs_by_size() {
 
     assert_eq!(num_blacklisted, 5);
 }
+
+/// Add a placeholder transaction that spends the given origin and sponsor nonces
+fn try_add_test_tx(
+    mempool_tx: &mut MemPoolTx,
+    chainstate: &mut StacksChainState,
+    tip: (&ConsensusHash, &BlockHeaderHash),
+    id: u8,
+    tx_fee: u64,
+    origin: (&StacksAddress, u64),
+    sponsor: (&StacksAddress, u64),
+) -> Result<Txid, MemPoolRejection> {
+    let txid = Txid([id; 32]);
+    MemPoolDB::try_add_tx(
+        mempool_tx,
+        chainstate,
+        tip.0,
+        tip.1,
+        txid.clone(),
+        vec![id; 100],
+        tx_fee,
+        1,
+        origin.0,
+        origin.1,
+        sponsor.0,
+        sponsor.1,
+    )?;
+    Ok(txid)
+}
+
+#[test]
+fn mempool_rbf_min_fee_increase() {
+    let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+    mempool.rbf_policy = ReplaceByFeePolicy {
+        min_fee_increase: 100,
+        min_fee_increase_percent: 10,
+    };
+    assert_eq!(mempool.rbf_policy.min_replacement_fee(500), 600);
+    assert_eq!(mempool.rbf_policy.min_replacement_fee(2000), 2200);
+
+    let tip = (&ConsensusHash([0x1; 20]), &BlockHeaderHash([0x2; 32]));
+    let origin = StacksAddress {
+        version: 22,
+        bytes: Hash160([0x11; 20]),
+    };
+
+    let mut mempool_tx = mempool.tx_begin().unwrap();
+    let first = try_add_test_tx(
+        &mut mempool_tx,
+        &mut chainstate,
+        tip,
+        1,
+        2000,
+        (&origin, 0),
+        (&origin, 0),
+    )
+    .unwrap();
+
+    // a higher fee is not enough if it's not 10% higher
+    match try_add_test_tx(
+        &mut mempool_tx,
+        &mut chainstate,
+        tip,
+        2,
+        2199,
+        (&origin, 0),
+        (&origin, 0),
+    ) {
+        Err(MemPoolRejection::ConflictingNonceInMempool) => {}
+        x => panic!("Expected ConflictingNonceInMempool, got {:?}", x),
+    }
+    assert!(MemPoolDB::db_has_tx(&mempool_tx, &first).unwrap());
+
+    let second = try_add_test_tx(
+        &mut mempool_tx,
+        &mut chainstate,
+        tip,
+        3,
+        2200,
+        (&origin, 0),
+        (&origin, 0),
+    )
+    .unwrap();
+    assert!(!MemPoolDB::db_has_tx(&mempool_tx, &first).unwrap());
+    assert!(MemPoolDB::db_has_tx(&mempool_tx, &second).unwrap());
+    mempool_tx.commit().unwrap();
+
+    let replacements = MemPoolDB::get_tx_replacements(mempool.conn(), &first).unwrap();
+    assert_eq!(replacements.len(), 1);
+    assert_eq!(replacements[0].txid, first);
+    assert_eq!(replacements[0].replaced_by, second);
+    assert_eq!(
+        replacements[0].reason,
+        MemPoolDropReason::REPLACE_BY_FEE.to_string()
+    );
+    assert!(MemPoolDB::get_tx_replacements(mempool.conn(), &second)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn mempool_rbf_sponsored_conflicts() {
+    let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+    let tip = (&ConsensusHash([0x1; 20]), &BlockHeaderHash([0x2; 32]));
+    let alice = StacksAddress {
+        version: 22,
+        bytes: Hash160([0x11; 20]),
+    };
+    let bob = StacksAddress {
+        version: 22,
+        bytes: Hash160([0x22; 20]),
+    };
+    let carol = StacksAddress {
+        version: 22,
+        bytes: Hash160([0x33; 20]),
+    };
+
+    let mut mempool_tx = mempool.tx_begin().unwrap();
+
+    // alice's tx, sponsored by bob, and bob's own tx
+    let sponsored = try_add_test_tx(
+        &mut mempool_tx,
+        &mut chainstate,
+        tip,
+        1,
+        100,
+        (&alice, 0),
+        (&bob, 0),
+    )
+    .unwrap();
+    let bobs = try_add_test_tx(
+        &mut mempool_tx,
+        &mut chainstate,
+        tip,
+        2,
+        100,
+        (&bob, 1),
+        (&bob, 1),
+    )
+    .unwrap();
+
+    // a tx that bob sponsors with his nonce 0 conflicts with the sponsored tx
+    match try_add_test_tx(
+        &mut mempool_tx,
+        &mut chainstate,
+        tip,
+        3,
+        100,
+        (&carol, 0),
+        (&bob, 0),
+    ) {
+        Err(MemPoolRejection::ConflictingNonceInMempool) => {}
+        x => panic!("Expected ConflictingNonceInMempool, got {:?}", x),
+    }
+
+    // a tx from alice sponsored with bob's nonce 1 must outbid both txs together
+    match try_add_test_tx(
+        &mut mempool_tx,
+        &mut chainstate,
+        tip,
+        4,
+        200,
+        (&alice, 0),
+        (&bob, 1),
+    ) {
+        Err(MemPoolRejection::ConflictingNonceInMempool) => {}
+        x => panic!("Expected ConflictingNonceInMempool, got {:?}", x),
+    }
+    let replacement = try_add_test_tx(
+        &mut mempool_tx,
+        &mut chainstate,
+        tip,
+        5,
+        201,
+        (&alice, 0),
+        (&bob, 1),
+    )
+    .unwrap();
+    assert!(!MemPoolDB::db_has_tx(&mempool_tx, &sponsored).unwrap());
+    assert!(!MemPoolDB::db_has_tx(&mempool_tx, &bobs).unwrap());
+    assert!(MemPoolDB::db_has_tx(&mempool_tx, &replacement).unwrap());
+
+    // an unrelated sponsor's tx is untouched by later replacements
+    let carols = try_add_test_tx(
+        &mut mempool_tx,
+        &mut chainstate,
+        tip,
+        6,
+        100,
+        (&carol, 0),
+        (&carol, 0),
+    )
+    .unwrap();
+    let replacement_2 = try_add_test_tx(
+        &mut mempool_tx,
+        &mut chainstate,
+        tip,
+        7,
+        300,
+        (&alice, 0),
+        (&bob, 1),
+    )
+    .unwrap();
+    assert!(MemPoolDB::db_has_tx(&mempool_tx, &carols).unwrap());
+    mempool_tx.commit().unwrap();
+
+    // the replacement chain is followed to the latest replacement
+    let replacements = MemPoolDB::get_tx_replacements(mempool.conn(), &sponsored).unwrap();
+    let replaced_by: Vec<_> = replacements.iter().map(|r| r.replaced_by.clone()).collect();
+    assert_eq!(replaced_by, vec![replacement, replacement_2]);
+}
+
+#[test]
+fn mempool_rbf_evicts_orphaned_descendants() {
+    let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+    // the chain tip must be known to look up account nonces
+    let tip = (&FIRST_BURNCHAIN_CONSENSUS_HASH, &FIRST_STACKS_BLOCK_HASH);
+    let alice = StacksAddress {
+        version: 22,
+        bytes: Hash160([0x11; 20]),
+    };
+    let bob = StacksAddress {
+        version: 22,
+        bytes: Hash160([0x22; 20]),
+    };
+
+    let mut mempool_tx = mempool.tx_begin().unwrap();
+
+    // alice's nonces 0-2, with nonce 0 sponsored by bob
+    let alice_0 = try_add_test_tx(
+        &mut mempool_tx,
+        &mut chainstate,
+        tip,
+        1,
+        100,
+        (&alice, 0),
+        (&bob, 0),
+    )
+    .unwrap();
+    let alice_1 = try_add_test_tx(
+        &mut mempool_tx,
+        &mut chainstate,
+        tip,
+        2,
+        100,
+        (&alice, 1),
+        (&alice, 1),
+    )
+    .unwrap();
+    let alice_2 = try_add_test_tx(
+        &mut mempool_tx,
+        &mut chainstate,
+        tip,
+        3,
+        100,
+        (&alice, 2),
+        (&alice, 2),
+    )
+    .unwrap();
+    let bob_1 = try_add_test_tx(
+        &mut mempool_tx,
+        &mut chainstate,
+        tip,
+        4,
+        100,
+        (&bob, 1),
+        (&bob, 1),
+    )
+    .unwrap();
+
+    // bob spends his nonce 0 himself instead, which leaves alice's nonce 0 unspent
+    let bob_0 = try_add_test_tx(
+        &mut mempool_tx,
+        &mut chainstate,
+        tip,
+        5,
+        200,
+        (&bob, 0),
+        (&bob, 0),
+    )
+    .unwrap();
+
+    assert!(MemPoolDB::db_has_tx(&mempool_tx, &bob_0).unwrap());
+    assert!(MemPoolDB::db_has_tx(&mempool_tx, &bob_1).unwrap());
+    for txid in [&alice_0, &alice_1, &alice_2] {
+        assert!(!MemPoolDB::db_has_tx(&mempool_tx, txid).unwrap());
+    }
+    mempool_tx.commit().unwrap();
+
+    for txid in [&alice_0, &alice_1, &alice_2] {
+        let replacements = MemPoolDB::get_tx_replacements(mempool.conn(), txid).unwrap();
+        assert_eq!(replacements.len(), 1);
+        assert_eq!(replacements[0].replaced_by, bob_0);
+    }
+
+    // replacing a tx with one that spends the same nonces evicts nothing else
+    let mut mempool_tx = mempool.tx_begin().unwrap();
+    let bob_0_again = Txid([6; 32]);
+    let dropped = MemPoolDB::try_add_tx(
+        &mut mempool_tx,
+        &mut chainstate,
+        tip.0,
+        tip.1,
+        bob_0_again.clone(),
+        vec![6; 100],
+        300,
+        1,
+        &bob,
+        0,
+        &bob,
+        0,
+    )
+    .unwrap();
+    assert_eq!(
+        dropped,
+        vec![(vec![bob_0], MemPoolDropReason::REPLACE_BY_FEE)]
+    );
+    assert!(MemPoolDB::db_has_tx(&mempool_tx, &bob_0_again).unwrap());
+    assert!(MemPoolDB::db_has_tx(&mempool_tx, &bob_1).unwrap());
+    mempool_tx.commit().unwrap();
+}
+
+/// Add a placeholder transaction with the given fee rate, and enforce the mempool's limits
+fn submit_test_tx_with_fee_rate(
+    mempool: &mut MemPoolDB,
+    chainstate: &mut StacksChainState,
+    tip: (&ConsensusHash, &BlockHeaderHash),
+    id: u8,
+    fee_rate: Option<f64>,
+    origin: (&StacksAddress, u64),
+) -> Result<Vec<Txid>, MemPoolRejection> {
+    let mut mempool_tx = mempool.tx_begin().unwrap();
+    let txid = try_add_test_tx(&mut mempool_tx, chainstate, tip, id, 1000, origin, origin)?;
+    mempool_tx
+        .execute(
+            "UPDATE mempool SET fee_rate = ? WHERE txid = ?",
+            rusqlite::params![fee_rate, &txid],
+        )
+        .unwrap();
+    let evicted = MemPoolDB::enforce_limits(
+        &mut mempool_tx,
+        chainstate,
+        &StacksBlockId::new(tip.0, tip.1),
+        &txid,
+        fee_rate,
+        false,
+    )?;
+    mempool_tx.commit().unwrap();
+    Ok(evicted)
+}
+
+#[test]
+fn mempool_evicts_lowest_fee_rate() {
+    let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+    mempool.limits = MemPoolLimits {
+        max_tx_count: Some(3),
+        max_bytes: None,
+        ..MemPoolLimits::default()
+    };
+
+    let tip = (&ConsensusHash([0x1; 20]), &BlockHeaderHash([0x2; 32]));
+    let addrs: Vec<_> = (0..6)
+        .map(|i| StacksAddress {
+            version: 22,
+            bytes: Hash160([i; 20]),
+        })
+        .collect();
+
+    for (i, fee_rate) in [None, Some(2.0), Some(3.0)].iter().enumerate() {
+        let evicted = submit_test_tx_with_fee_rate(
+            &mut mempool,
+            &mut chainstate,
+            tip,
+            i as u8 + 1,
+            *fee_rate,
+            (&addrs[i], 0),
+        )
+        .unwrap();
+        assert!(evicted.is_empty());
+    }
+
+    // full, and txs without an estimate are evicted first
+    assert_eq!(mempool.get_eviction_floor().unwrap(), Some(0.0));
+    let evicted = submit_test_tx_with_fee_rate(
+        &mut mempool,
+        &mut chainstate,
+        tip,
+        4,
+        Some(1.0),
+        (&addrs[3], 0),
+    )
+    .unwrap();
+    assert_eq!(evicted, vec![Txid([1; 32])]);
+    assert_eq!(mempool.get_eviction_floor().unwrap(), Some(1.0));
+
+    // a tx at or below the floor is rejected, and doesn't evict anything
+    match submit_test_tx_with_fee_rate(
+        &mut mempool,
+        &mut chainstate,
+        tip,
+        5,
+        Some(1.0),
+        (&addrs[4], 0),
+    ) {
+        Err(MemPoolRejection::FeeRateBelowEvictionFloor {
+            fee_rate,
+            eviction_floor,
+        }) => {
+            assert_eq!(fee_rate, 1.0);
+            assert_eq!(eviction_floor, 1.0);
+        }
+        x => panic!("Expected FeeRateBelowEvictionFloor, got {:?}", x),
+    }
+    assert!(!mempool.has_tx(&Txid([5; 32])));
+    assert!(mempool.has_tx(&Txid([4; 32])));
+
+    let evicted = submit_test_tx_with_fee_rate(
+        &mut mempool,
+        &mut chainstate,
+        tip,
+        6,
+        Some(10.0),
+        (&addrs[5], 0),
+    )
+    .unwrap();
+    assert_eq!(evicted, vec![Txid([4; 32])]);
+    assert_eq!(mempool.get_eviction_floor().unwrap(), Some(2.0));
+
+    // not full anymore
+    mempool.limits.max_tx_count = Some(4);
+    assert_eq!(mempool.get_eviction_floor().unwrap(), None);
+}
+
+#[test]
+fn mempool_evicts_by_size_with_descendants() {
+    let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+    // each placeholder tx is 100 bytes
+    mempool.limits = MemPoolLimits {
+        max_tx_count: None,
+        max_bytes: Some(300),
+        ..MemPoolLimits::default()
+    };
+
+    // the chain tip must be known to look up account nonces
+    let tip = (&FIRST_BURNCHAIN_CONSENSUS_HASH, &FIRST_STACKS_BLOCK_HASH);
+    let alice = StacksAddress {
+        version: 22,
+        bytes: Hash160([0x11; 20]),
+    };
+    let bob = StacksAddress {
+        version: 22,
+        bytes: Hash160([0x22; 20]),
+    };
+    let carol = StacksAddress {
+        version: 22,
+        bytes: Hash160([0x33; 20]),
+    };
+
+    submit_test_tx_with_fee_rate(
+        &mut mempool,
+        &mut chainstate,
+        tip,
+        1,
+        Some(1.0),
+        (&alice, 0),
+    )
+    .unwrap();
+    submit_test_tx_with_fee_rate(
+        &mut mempool,
+        &mut chainstate,
+        tip,
+        2,
+        Some(10.0),
+        (&alice, 1),
+    )
+    .unwrap();
+    submit_test_tx_with_fee_rate(&mut mempool, &mut chainstate, tip, 3, Some(5.0), (&bob, 0))
+        .unwrap();
+
+    // evicting alice's first tx strands her second one
+    let evicted = submit_test_tx_with_fee_rate(
+        &mut mempool,
+        &mut chainstate,
+        tip,
+        4,
+        Some(2.0),
+        (&carol, 0),
+    )
+    .unwrap();
+    assert_eq!(evicted, vec![Txid([1; 32]), Txid([2; 32])]);
+    assert!(mempool.has_tx(&Txid([3; 32])));
+    assert!(mempool.has_tx(&Txid([4; 32])));
+    assert_eq!(mempool.get_eviction_floor().unwrap(), None);
+}
+
+#[test]
+fn mempool_enforces_account_limits() {
+    let privk = StacksPrivateKey::from_hex(
+        "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+    )
+    .unwrap();
+    let addr = StacksAddress::from_public_keys(
+        C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+        &AddressHashMode::SerializeP2PKH,
+        1,
+        &vec![StacksPublicKey::from_private(&privk)],
+    )
+    .unwrap();
+    let recipient: PrincipalData = StacksAddress {
+        version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+        bytes: Hash160([0x22; 20]),
+    }
+    .into();
+
+    let mut chainstate = instantiate_chainstate_with_balances(
+        false,
+        0x80000000,
+        function_name!(),
+        vec![(addr.clone(), 1_000_000_000)],
+    );
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+    mempool.limits = MemPoolLimits {
+        max_txs_per_account: Some(2),
+        max_nonce_gap: Some(3),
+        ..MemPoolLimits::default()
+    };
+
+    let mut submit = |mempool: &mut MemPoolDB, nonce: u64| {
+        let tx = make_user_stacks_transfer(&privk, nonce, 200, &recipient, 1);
+        mempool.submit(
+            &mut chainstate,
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &FIRST_STACKS_BLOCK_HASH,
+            &tx,
+            None,
+            &ExecutionCost::max_value(),
+            &StacksEpochId::Epoch21,
+        )
+    };
+
+    submit(&mut mempool, 0).unwrap();
+    submit(&mut mempool, 1).unwrap();
+    match submit(&mut mempool, 2) {
+        Err(MemPoolRejection::TooManyPendingTransactions {
+            max_txs, is_origin, ..
+        }) => {
+            assert_eq!(max_txs, 2);
+            assert!(is_origin);
+        }
+        x => panic!("Expected TooManyPendingTransactions, got {:?}", x),
+    }
+
+    // the nonce can't run too far ahead of the account's nonce in the chain tip
+    mempool.limits.max_txs_per_account = None;
+    submit(&mut mempool, 3).unwrap();
+    match submit(&mut mempool, 4) {
+        Err(MemPoolRejection::TooMuchChaining {
+            max_nonce,
+            actual_nonce,
+            ..
+        }) => {
+            assert_eq!(max_nonce, 3);
+            assert_eq!(actual_nonce, 4);
+        }
+        x => panic!("Expected TooMuchChaining, got {:?}", x),
+    }
+
+    // the account's 3 pending txs already take up more than 2 txs' worth of bytes
+    let tx_len = make_user_stacks_transfer(&privk, 2, 200, &recipient, 1)
+        .serialize_to_vec()
+        .len() as u64;
+    mempool.limits.max_bytes_per_account = Some(tx_len * 2);
+    match submit(&mut mempool, 2) {
+        Err(MemPoolRejection::TooManyPendingBytes {
+            max_bytes,
+            actual_bytes,
+            ..
+        }) => {
+            assert_eq!(max_bytes, tx_len * 2);
+            assert_eq!(actual_bytes, tx_len * 4);
+        }
+        x => panic!("Expected TooManyPendingBytes, got {:?}", x),
+    }
+    assert!(!mempool.has_tx(&make_user_stacks_transfer(&privk, 2, 200, &recipient, 1).txid()));
+}
+
+#[test]
+fn mempool_get_pending_txs() {
+    let privk = StacksPrivateKey::from_hex(
+        "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+    )
+    .unwrap();
+    let addr = StacksAddress::from_public_keys(
+        C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+        &AddressHashMode::SerializeP2PKH,
+        1,
+        &vec![StacksPublicKey::from_private(&privk)],
+    )
+    .unwrap();
+    let other_privk = StacksPrivateKey::new();
+    let recipient: PrincipalData = StacksAddress {
+        version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+        bytes: Hash160([0x22; 20]),
+    }
+    .into();
+    let contract_addr = StacksAddress {
+        version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+        bytes: Hash160([0x33; 20]),
+    };
+
+    let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+    let mut txs: Vec<_> = (0..3)
+        .map(|nonce| make_user_stacks_transfer(&privk, nonce, 200, &recipient, 1))
+        .collect();
+    txs.push(make_user_contract_call(
+        &privk,
+        3,
+        200,
+        &contract_addr,
+        "hello-world",
+        "get-value",
+        vec![],
+    ));
+    txs.push(make_user_contract_call(
+        &other_privk,
+        0,
+        200,
+        &contract_addr,
+        "hello-world",
+        "get-value",
+        vec![],
+    ));
+    for (i, tx) in txs.iter().enumerate() {
+        mempool
+            .miner_submit(
+                &mut chainstate,
+                &FIRST_BURNCHAIN_CONSENSUS_HASH,
+                &FIRST_STACKS_BLOCK_HASH,
+                tx,
+                None,
+                i as f64,
+            )
+            .unwrap();
+    }
+
+    let address_query = |is_origin, min_nonce, max_nonce| PendingTxQuery::Address {
+        address: addr.clone(),
+        is_origin,
+        min_nonce,
+        max_nonce,
+    };
+    let get_txids = |query: &PendingTxQuery, offset, limit| {
+        let (total, pending_txs) =
+            MemPoolDB::get_pending_txs(mempool.conn(), query, offset, limit).unwrap();
+        let txids: Vec<_> = pending_txs
+            .iter()
+            .map(|pending_tx| pending_tx.tx_info.tx.txid())
+            .collect();
+        (total, txids)
+    };
+
+    // by nonce range, in nonce order
+    assert_eq!(
+        get_txids(&address_query(None, 1, 2), 0, 10),
+        (2, vec![txs[1].txid(), txs[2].txid()])
+    );
+    assert_eq!(
+        get_txids(&address_query(Some(true), 0, u64::MAX), 1, 2),
+        (4, vec![txs[1].txid(), txs[2].txid()])
+    );
+
+    // the account doesn't sponsor anything
+    assert_eq!(
+        get_txids(&address_query(Some(false), 0, u64::MAX), 0, 10),
+        (0, vec![])
+    );
+
+    // by contract called
+    let contract_id = QualifiedContractIdentifier::new(
+        contract_addr.into(),
+        ContractName::try_from("hello-world".to_string()).unwrap(),
+    );
+    let (total, txids) = get_txids(&PendingTxQuery::ContractCall(contract_id), 0, 10);
+    assert_eq!(total, 2);
+    assert!(txids.contains(&txs[3].txid()));
+    assert!(txids.contains(&txs[4].txid()));
+
+    // fee rates are included
+    let (_, pending_txs) =
+        MemPoolDB::get_pending_txs(mempool.conn(), &address_query(None, 3, 3), 0, 10).unwrap();
+    assert_eq!(pending_txs[0].fee_rate, Some(3.0));
+}
+
+#[test]
+fn mempool_export_import() {
+    let privk = StacksPrivateKey::from_hex(
+        "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+    )
+    .unwrap();
+    let addr = StacksAddress::from_public_keys(
+        C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+        &AddressHashMode::SerializeP2PKH,
+        1,
+        &vec![StacksPublicKey::from_private(&privk)],
+    )
+    .unwrap();
+    let recipient: PrincipalData = StacksAddress {
+        version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+        bytes: Hash160([0x22; 20]),
+    }
+    .into();
+
+    let mut chainstate = instantiate_chainstate_with_balances(
+        false,
+        0x80000000,
+        function_name!(),
+        vec![(addr.clone(), 1_000_000_000)],
+    );
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+    let mut imported_mempool = MemPoolDB::open_db(
+        &format!("{}/imported_mempool.sqlite", &chainstate_path),
+        Box::new(UnitEstimator),
+        Box::new(UnitMetric),
+    )
+    .unwrap();
+
+    let txs: Vec<_> = (0..2)
+        .map(|nonce| make_user_stacks_transfer(&privk, nonce, 200, &recipient, 1))
+        .collect();
+    for tx in txs.iter() {
+        mempool
+            .submit(
+                &mut chainstate,
+                &FIRST_BURNCHAIN_CONSENSUS_HASH,
+                &FIRST_STACKS_BLOCK_HASH,
+                tx,
+                None,
+                &ExecutionCost::max_value(),
+                &StacksEpochId::Epoch21,
+            )
+            .unwrap();
+    }
+    mempool
+        .conn()
+        .execute(
+            "UPDATE mempool SET accept_time = 123, fee_rate = 12.5 WHERE txid = ?1",
+            &[&txs[0].txid()],
+        )
+        .unwrap();
+
+    let mut export = vec![];
+    assert_eq!(mempool.export_txs(&mut export).unwrap(), 2);
+
+    let mut exported_txs = vec![];
+    let mut export_ptr = &export[..];
+    while !export_ptr.is_empty() {
+        exported_txs.push(MemPoolExportedTx::consensus_deserialize(&mut export_ptr).unwrap());
+    }
+    assert_eq!(exported_txs.len(), 2);
+    assert_eq!(exported_txs[0].tx, txs[0]);
+    assert_eq!(exported_txs[0].accept_time, 123);
+    assert_eq!(exported_txs[0].fee_rate, Some(12.5));
+    assert_eq!(exported_txs[1].tx, txs[1]);
+
+    // the importing node already has a better-paying tx with the second tx's nonce
+    let conflicting_tx = make_user_stacks_transfer(&privk, 1, 400, &recipient, 2);
+    imported_mempool
+        .submit(
+            &mut chainstate,
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &FIRST_STACKS_BLOCK_HASH,
+            &conflicting_tx,
+            None,
+            &ExecutionCost::max_value(),
+            &StacksEpochId::Epoch21,
+        )
+        .unwrap();
+
+    let results: Vec<_> = exported_txs
+        .iter()
+        .map(|exported_tx| {
+            imported_mempool.import_tx(
+                &mut chainstate,
+                &FIRST_BURNCHAIN_CONSENSUS_HASH,
+                &FIRST_STACKS_BLOCK_HASH,
+                exported_tx,
+                &ExecutionCost::max_value(),
+                &StacksEpochId::Epoch21,
+            )
+        })
+        .collect();
+    assert!(results[0].is_ok());
+    match results[1] {
+        Err(MemPoolRejection::ConflictingNonceInMempool) => {}
+        ref x => panic!("Expected ConflictingNonceInMempool, got {:?}", x),
+    }
+
+    // the imported tx keeps its metadata
+    let imported_tx = MemPoolDB::get_tx(imported_mempool.conn(), &txs[0].txid())
+        .unwrap()
+        .unwrap();
+    assert_eq!(imported_tx.metadata.accept_time, 123);
+    let fee_rate: Option<f64> = imported_mempool
+        .conn()
+        .query_row(
+            "SELECT fee_rate FROM mempool WHERE txid = ?1",
+            &[&txs[0].txid()],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(fee_rate, Some(12.5));
+    assert!(!imported_mempool.has_tx(&txs[1].txid()));
+    assert!(imported_mempool.has_tx(&conflicting_tx.txid()));
+}
+
+#[test]
+fn test_iterate_candidates_with_strategy() {
+    let privks: Vec<_> = (0..4).map(|_| StacksPrivateKey::new()).collect();
+    let addrs: Vec<_> = privks
+        .iter()
+        .map(|privk| {
+            StacksAddress::from_public_keys(
+                C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+                &AddressHashMode::SerializeP2PKH,
+                1,
+                &vec![StacksPublicKey::from_private(privk)],
+            )
+            .unwrap()
+        })
+        .collect();
+    let recipient: PrincipalData = StacksAddress {
+        version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+        bytes: Hash160([0x22; 20]),
+    }
+    .into();
+    let contract_addr = StacksAddress {
+        version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+        bytes: Hash160([0x33; 20]),
+    };
+    let contract_id = QualifiedContractIdentifier::new(
+        contract_addr.clone().into(),
+        ContractName::try_from("hello-world".to_string()).unwrap(),
+    );
+
+    let mut chainstate = instantiate_chainstate(false, 0x80000000, function_name!());
+    let chainstate_path = chainstate_path(function_name!());
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+    // (tx, fee rate); the contract-call has no fee rate estimate
+    let txs = vec![
+        (
+            make_user_stacks_transfer(&privks[0], 0, 100, &recipient, 1),
+            Some(1.0),
+        ),
+        (
+            make_user_stacks_transfer(&privks[1], 0, 300, &recipient, 1),
+            Some(3.0),
+        ),
+        (
+            make_user_stacks_transfer(&privks[2], 0, 200, &recipient, 1),
+            Some(2.0),
+        ),
+        (
+            make_user_contract_call(
+                &privks[3],
+                0,
+                400,
+                &contract_addr,
+                "hello-world",
+                "get-value",
+                vec![],
+            ),
+            None,
+        ),
+    ];
+    for (tx, fee_rate) in txs.iter() {
+        mempool
+            .miner_submit(
+                &mut chainstate,
+                &FIRST_BURNCHAIN_CONSENSUS_HASH,
+                &FIRST_STACKS_BLOCK_HASH,
+                tx,
+                None,
+                fee_rate.unwrap_or(0.0),
+            )
+            .unwrap();
+        mempool
+            .conn()
+            .execute(
+                "UPDATE mempool SET fee_rate = ?1 WHERE txid = ?2",
+                rusqlite::params![fee_rate, &tx.txid()],
+            )
+            .unwrap();
+    }
+
+    let mut walk = |tx_selection: TxSelectionSettings| {
+        let mut settings = MemPoolWalkSettings::zero();
+        settings.consider_no_estimate_tx_prob = 0;
+        settings.tx_selection = tx_selection;
+
+        let mut considered = vec![];
+        let _ = mempool.reset_nonce_cache();
+        chainstate.with_read_only_clarity_tx(
+            &TEST_BURN_STATE_DB,
+            &StacksBlockHeader::make_index_block_hash(
+                &FIRST_BURNCHAIN_CONSENSUS_HASH,
+                &FIRST_STACKS_BLOCK_HASH,
+            ),
+            |clarity_conn| {
+                mempool
+                    .iterate_candidates::<_, ChainstateError, _>(
+                        clarity_conn,
+                        &mut vec![],
+                        0,
+                        settings,
+                        |_, available_tx, _| {
+                            considered.push(available_tx.tx.tx.txid());
+                            Ok(Some(
+                                TransactionResult::skipped(&available_tx.tx.tx, "".to_string())
+                                    .convert_to_event(),
+                            ))
+                        },
+                    )
+                    .unwrap();
+            },
+        );
+        considered
+    };
+    let txids =
+        |indexes: &[usize]| -> Vec<Txid> { indexes.iter().map(|i| txs[*i].0.txid()).collect() };
+
+    // by fee rate, then the transaction without a fee rate
+    assert_eq!(walk(TxSelectionSettings::default()), txids(&[1, 2, 0, 3]));
+
+    // by fee, since each transaction is estimated to cost the same
+    assert_eq!(
+        walk(TxSelectionSettings {
+            strategy: TxSelectionStrategyName::CostPacking,
+            ..TxSelectionSettings::default()
+        }),
+        txids(&[3, 1, 2, 0])
+    );
+
+    // listed transactions come first, and excluded ones not at all
+    assert_eq!(
+        walk(TxSelectionSettings {
+            strategy: TxSelectionStrategyName::PureFeeRate,
+            priority_list: TxSelectionList {
+                principals: vec![addrs[0].clone()],
+                contracts: vec![contract_id],
+            },
+            exclusion_list: TxSelectionList {
+                principals: vec![addrs[2].clone()],
+                contracts: vec![],
+            },
+        }),
+        txids(&[0, 3, 1])
+    );
+}
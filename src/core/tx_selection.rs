@@ -0,0 +1,562 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2022 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Strategies for choosing which mempool transactions a miner considers for a block, and in what
+//! order.  The mempool walk in `MemPoolDB::iterate_candidates_with_strategy` streams candidates
+//! out of the mempool by fee rate, asks a strategy to rank them, and considers them best-first.
+//! Strategies that don't rank by fee rate alone rank each candidate against a bounded window of
+//! the candidates that have been streamed but not yet considered.
+
+use std::fmt;
+use std::str::FromStr;
+
+use clarity::vm::costs::ExecutionCost;
+use clarity::vm::types::{PrincipalData, QualifiedContractIdentifier};
+
+use crate::core::mempool::MemPoolTxInfoPartial;
+use crate::types::chainstate::StacksAddress;
+
+/// Decides which mempool transactions a miner considers, and in what order.
+pub trait TxSelectionStrategy {
+    /// Name of the strategy, as logged with each block it produces
+    fn name(&self) -> String;
+
+    /// Does `score()` use the candidates' estimated costs?  Estimating a candidate's cost means
+    /// loading and decoding it, so strategies that don't use them shouldn't ask for them.
+    fn uses_cost_estimates(&self) -> bool {
+        false
+    }
+
+    /// Does this strategy rank candidates by their fee rate alone, so that the mempool's fee-rate
+    /// order is already the order it would consider them in?
+    fn ranks_by_fee_rate(&self) -> bool {
+        false
+    }
+
+    /// Should this candidate be left out of the block altogether?
+    fn is_excluded(&self, _candidate: &MemPoolTxInfoPartial) -> bool {
+        false
+    }
+
+    /// Candidates with a higher priority are considered before any candidate with a lower
+    /// priority, regardless of their scores.
+    fn priority(&self, _candidate: &MemPoolTxInfoPartial) -> u32 {
+        0
+    }
+
+    /// Score a candidate.  Among candidates of the same priority, those with higher scores are
+    /// considered first.  Returns None if the strategy has no basis for ranking the candidate;
+    /// unranked candidates are considered after all ranked ones, save for the chance given by
+    /// `consider_unranked_prob()`.
+    ///
+    /// A candidate is scored again right before it is considered, since its score may have
+    /// dropped as the block filled up.  Scores must never go up as the block fills.
+    fn score(
+        &self,
+        candidate: &MemPoolTxInfoPartial,
+        estimated_cost: Option<&ExecutionCost>,
+    ) -> Option<f64>;
+
+    /// Probability percentage to consider an unranked candidate ahead of the ranked ones
+    fn consider_unranked_prob(&self) -> u8 {
+        0
+    }
+
+    /// Called with the actual cost of each transaction that gets mined into the block
+    fn tx_mined(&mut self, _cost: &ExecutionCost) {}
+}
+
+/// Order candidates by fee rate, and now and then consider one that has no fee rate estimate
+/// yet, so that it can get one.  This is how the miner has always chosen transactions.
+pub struct FeeRateStrategy {
+    consider_no_estimate_tx_prob: u8,
+}
+
+impl FeeRateStrategy {
+    pub fn new(consider_no_estimate_tx_prob: u8) -> FeeRateStrategy {
+        FeeRateStrategy {
+            consider_no_estimate_tx_prob,
+        }
+    }
+}
+
+impl TxSelectionStrategy for FeeRateStrategy {
+    fn name(&self) -> String {
+        TxSelectionStrategyName::FeeRate.to_string()
+    }
+
+    fn ranks_by_fee_rate(&self) -> bool {
+        true
+    }
+
+    fn score(
+        &self,
+        candidate: &MemPoolTxInfoPartial,
+        _estimated_cost: Option<&ExecutionCost>,
+    ) -> Option<f64> {
+        candidate.fee_rate
+    }
+
+    fn consider_unranked_prob(&self) -> u8 {
+        self.consider_no_estimate_tx_prob
+    }
+}
+
+/// Order candidates strictly by fee rate.  Candidates without a fee rate estimate are only
+/// considered once every candidate with one has been.
+pub struct PureFeeRateStrategy;
+
+impl TxSelectionStrategy for PureFeeRateStrategy {
+    fn name(&self) -> String {
+        TxSelectionStrategyName::PureFeeRate.to_string()
+    }
+
+    fn ranks_by_fee_rate(&self) -> bool {
+        true
+    }
+
+    fn score(
+        &self,
+        candidate: &MemPoolTxInfoPartial,
+        _estimated_cost: Option<&ExecutionCost>,
+    ) -> Option<f64> {
+        candidate.fee_rate
+    }
+}
+
+/// Pack the block by fee paid per unit of block budget, across all five `ExecutionCost`
+/// dimensions.  A candidate's estimated cost is weighed against what is left of the block's
+/// budget, so a dimension that is nearly used up weighs more heavily as the block fills.
+/// Candidates that no longer fit, or that have no cost estimate, are unranked.
+pub struct CostPackingStrategy {
+    block_limit: ExecutionCost,
+    consumed: ExecutionCost,
+}
+
+impl CostPackingStrategy {
+    pub fn new(block_limit: ExecutionCost, cost_so_far: ExecutionCost) -> CostPackingStrategy {
+        CostPackingStrategy {
+            block_limit,
+            consumed: cost_so_far,
+        }
+    }
+
+    /// Fraction of the remaining block budget that a transaction with this cost would use,
+    /// summed over each cost dimension.
+    /// Returns None if it doesn't fit in what is left of the block.
+    fn remaining_budget_used(&self, cost: &ExecutionCost) -> Option<f64> {
+        let dimensions = [
+            (
+                cost.write_length,
+                self.block_limit.write_length,
+                self.consumed.write_length,
+            ),
+            (
+                cost.write_count,
+                self.block_limit.write_count,
+                self.consumed.write_count,
+            ),
+            (
+                cost.read_length,
+                self.block_limit.read_length,
+                self.consumed.read_length,
+            ),
+            (
+                cost.read_count,
+                self.block_limit.read_count,
+                self.consumed.read_count,
+            ),
+            (
+                cost.runtime,
+                self.block_limit.runtime,
+                self.consumed.runtime,
+            ),
+        ];
+
+        let mut used = 0.0;
+        for (cost, limit, consumed) in dimensions.iter() {
+            let remaining = limit.saturating_sub(*consumed);
+            if cost > &remaining {
+                return None;
+            }
+            if *cost > 0 {
+                used += (*cost as f64) / (remaining as f64);
+            }
+        }
+        Some(used)
+    }
+}
+
+impl TxSelectionStrategy for CostPackingStrategy {
+    fn name(&self) -> String {
+        TxSelectionStrategyName::CostPacking.to_string()
+    }
+
+    fn uses_cost_estimates(&self) -> bool {
+        true
+    }
+
+    fn score(
+        &self,
+        candidate: &MemPoolTxInfoPartial,
+        estimated_cost: Option<&ExecutionCost>,
+    ) -> Option<f64> {
+        let used = self.remaining_budget_used(estimated_cost?)?;
+        // a transaction that is (nearly) free to include is ranked by its fee alone
+        Some((candidate.tx_fee as f64) / used.max(1e-9))
+    }
+
+    fn tx_mined(&mut self, cost: &ExecutionCost) {
+        if self.consumed.add(cost).is_err() {
+            self.consumed = ExecutionCost::max_value();
+        }
+    }
+}
+
+/// A set of principals and contracts that a mempool transaction can be matched against
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TxSelectionList {
+    /// Transactions with one of these origins or sponsors match
+    pub principals: Vec<StacksAddress>,
+    /// Calls to one of these contracts match
+    pub contracts: Vec<QualifiedContractIdentifier>,
+}
+
+impl TxSelectionList {
+    /// Build a list out of principal strings.  Standard principals match transactions they send
+    /// or sponsor; contract principals match transactions that call them.
+    pub fn from_principals(principals: &[String]) -> Result<TxSelectionList, String> {
+        let mut list = TxSelectionList::default();
+        for principal in principals.iter() {
+            match PrincipalData::parse(principal)
+                .map_err(|e| format!("Invalid principal '{}': {:?}", principal, &e))?
+            {
+                PrincipalData::Standard(addr) => list.principals.push(addr.into()),
+                PrincipalData::Contract(contract_id) => list.contracts.push(contract_id),
+            }
+        }
+        Ok(list)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.principals.is_empty() && self.contracts.is_empty()
+    }
+
+    pub fn matches(&self, candidate: &MemPoolTxInfoPartial) -> bool {
+        if self.principals.contains(&candidate.origin_address)
+            || self.principals.contains(&candidate.sponsor_address)
+        {
+            return true;
+        }
+        match candidate.contract_call {
+            Some(ref contract_call) => self
+                .contracts
+                .iter()
+                .any(|contract_id| contract_id.to_string() == *contract_call),
+            None => false,
+        }
+    }
+}
+
+/// Wraps another strategy: transactions on the exclusion list are never considered, and
+/// transactions on the priority list are considered before all others.  Prioritized
+/// transactions are ranked among themselves by the wrapped strategy.
+pub struct ListedTxStrategy {
+    inner: Box<dyn TxSelectionStrategy>,
+    priority_list: TxSelectionList,
+    exclusion_list: TxSelectionList,
+}
+
+impl TxSelectionStrategy for ListedTxStrategy {
+    fn name(&self) -> String {
+        let mut name = self.inner.name();
+        if !self.priority_list.is_empty() {
+            name.push_str("+priority_list");
+        }
+        if !self.exclusion_list.is_empty() {
+            name.push_str("+exclusion_list");
+        }
+        name
+    }
+
+    fn uses_cost_estimates(&self) -> bool {
+        self.inner.uses_cost_estimates()
+    }
+
+    fn ranks_by_fee_rate(&self) -> bool {
+        // prioritized transactions go first, whatever their fee rate
+        self.priority_list.is_empty() && self.inner.ranks_by_fee_rate()
+    }
+
+    fn is_excluded(&self, candidate: &MemPoolTxInfoPartial) -> bool {
+        self.exclusion_list.matches(candidate) || self.inner.is_excluded(candidate)
+    }
+
+    fn priority(&self, candidate: &MemPoolTxInfoPartial) -> u32 {
+        if self.priority_list.matches(candidate) {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn score(
+        &self,
+        candidate: &MemPoolTxInfoPartial,
+        estimated_cost: Option<&ExecutionCost>,
+    ) -> Option<f64> {
+        let score = self.inner.score(candidate, estimated_cost);
+        if score.is_none() && self.priority_list.matches(candidate) {
+            // don't let a prioritized transaction wait behind every other transaction just
+            // because the wrapped strategy can't rank it
+            return Some(0.0);
+        }
+        score
+    }
+
+    fn consider_unranked_prob(&self) -> u8 {
+        self.inner.consider_unranked_prob()
+    }
+
+    fn tx_mined(&mut self, cost: &ExecutionCost) {
+        self.inner.tx_mined(cost)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TxSelectionStrategyName {
+    FeeRate,
+    PureFeeRate,
+    CostPacking,
+}
+
+impl Default for TxSelectionStrategyName {
+    fn default() -> TxSelectionStrategyName {
+        TxSelectionStrategyName::FeeRate
+    }
+}
+
+impl fmt::Display for TxSelectionStrategyName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            TxSelectionStrategyName::FeeRate => "fee_rate",
+            TxSelectionStrategyName::PureFeeRate => "pure_fee_rate",
+            TxSelectionStrategyName::CostPacking => "cost_packing",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for TxSelectionStrategyName {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<TxSelectionStrategyName, String> {
+        match s {
+            "fee_rate" => Ok(TxSelectionStrategyName::FeeRate),
+            "pure_fee_rate" => Ok(TxSelectionStrategyName::PureFeeRate),
+            "cost_packing" => Ok(TxSelectionStrategyName::CostPacking),
+            _ => Err(format!("Unknown transaction selection strategy: {}", s)),
+        }
+    }
+}
+
+/// How a miner chooses transactions from the mempool
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TxSelectionSettings {
+    pub strategy: TxSelectionStrategyName,
+    /// Transactions that are considered before all others
+    pub priority_list: TxSelectionList,
+    /// Transactions that are never considered
+    pub exclusion_list: TxSelectionList,
+}
+
+impl TxSelectionSettings {
+    /// Instantiate the strategy for building a block with the given budget, of which
+    /// `cost_so_far` has already been spent.
+    pub fn make_strategy(
+        &self,
+        consider_no_estimate_tx_prob: u8,
+        block_limit: &ExecutionCost,
+        cost_so_far: &ExecutionCost,
+    ) -> Box<dyn TxSelectionStrategy> {
+        let strategy: Box<dyn TxSelectionStrategy> = match self.strategy {
+            TxSelectionStrategyName::FeeRate => {
+                Box::new(FeeRateStrategy::new(consider_no_estimate_tx_prob))
+            }
+            TxSelectionStrategyName::PureFeeRate => Box::new(PureFeeRateStrategy),
+            TxSelectionStrategyName::CostPacking => Box::new(CostPackingStrategy::new(
+                block_limit.clone(),
+                cost_so_far.clone(),
+            )),
+        };
+
+        if self.priority_list.is_empty() && self.exclusion_list.is_empty() {
+            return strategy;
+        }
+        Box::new(ListedTxStrategy {
+            inner: strategy,
+            priority_list: self.priority_list.clone(),
+            exclusion_list: self.exclusion_list.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::burnchains::Txid;
+    use crate::types::Address;
+
+    fn make_candidate(
+        origin: &str,
+        tx_fee: u64,
+        fee_rate: Option<f64>,
+        contract_call: Option<&str>,
+    ) -> MemPoolTxInfoPartial {
+        let origin_address = StacksAddress::from_string(origin).unwrap();
+        MemPoolTxInfoPartial {
+            txid: Txid([0u8; 32]),
+            fee_rate,
+            origin_address: origin_address.clone(),
+            origin_nonce: 0,
+            sponsor_address: origin_address,
+            sponsor_nonce: 0,
+            tx_fee,
+            len: 180,
+            contract_call: contract_call.map(|c| c.to_string()),
+        }
+    }
+
+    fn make_cost(runtime: u64, read_count: u64) -> ExecutionCost {
+        ExecutionCost {
+            write_length: 0,
+            write_count: 0,
+            read_length: 0,
+            read_count,
+            runtime,
+        }
+    }
+
+    #[test]
+    fn test_cost_packing_scores() {
+        let candidate = make_candidate(
+            "SP1A2K3ENNA6QQ7G8DVJXM24T6QMBDVS7D0TRTAR5",
+            1000,
+            None,
+            None,
+        );
+        let block_limit = make_cost(1000, 10);
+        let mut strategy = CostPackingStrategy::new(block_limit, ExecutionCost::zero());
+        assert_eq!(strategy.name(), "cost_packing");
+
+        // no estimate, no rank
+        assert_eq!(strategy.score(&candidate, None), None);
+
+        // uses a tenth of the runtime and a tenth of the reads
+        let cost = make_cost(100, 1);
+        assert_eq!(strategy.score(&candidate, Some(&cost)), Some(5000.0));
+
+        // reads get scarce, so the same transaction ranks lower
+        strategy.tx_mined(&make_cost(0, 8));
+        assert_eq!(strategy.score(&candidate, Some(&cost)), Some(1000.0 / 0.6));
+
+        // ...and once they run out, it no longer fits
+        strategy.tx_mined(&make_cost(0, 2));
+        assert_eq!(strategy.score(&candidate, Some(&cost)), None);
+        assert!(strategy
+            .score(&candidate, Some(&make_cost(100, 0)))
+            .is_some());
+    }
+
+    #[test]
+    fn test_listed_tx_strategy() {
+        let settings = TxSelectionSettings {
+            strategy: TxSelectionStrategyName::PureFeeRate,
+            priority_list: TxSelectionList::from_principals(&[
+                "SP1A2K3ENNA6QQ7G8DVJXM24T6QMBDVS7D0TRTAR5".to_string(),
+                "SP000000000000000000002Q6VF78.pox".to_string(),
+            ])
+            .unwrap(),
+            exclusion_list: TxSelectionList::from_principals(&[
+                "SP3D6PV2ACBPEKYJTCMH7HEN02KP87QSP8KTEH335".to_string(),
+            ])
+            .unwrap(),
+        };
+        let strategy =
+            settings.make_strategy(25, &ExecutionCost::max_value(), &ExecutionCost::zero());
+        assert_eq!(
+            strategy.name(),
+            "pure_fee_rate+priority_list+exclusion_list"
+        );
+        assert_eq!(strategy.consider_unranked_prob(), 0);
+        // prioritized transactions aren't in fee-rate order
+        assert!(!strategy.ranks_by_fee_rate());
+
+        let listed = make_candidate("SP1A2K3ENNA6QQ7G8DVJXM24T6QMBDVS7D0TRTAR5", 1, None, None);
+        assert!(!strategy.is_excluded(&listed));
+        assert_eq!(strategy.priority(&listed), 1);
+        assert_eq!(strategy.score(&listed, None), Some(0.0));
+
+        let pox_call = make_candidate(
+            "SP000000000000000000002Q6VF78",
+            1,
+            Some(2.0),
+            Some("SP000000000000000000002Q6VF78.pox"),
+        );
+        assert_eq!(strategy.priority(&pox_call), 1);
+        assert_eq!(strategy.score(&pox_call, None), Some(2.0));
+
+        let other = make_candidate(
+            "SP000000000000000000002Q6VF78",
+            1,
+            None,
+            Some("SP000000000000000000002Q6VF78.bns"),
+        );
+        assert_eq!(strategy.priority(&other), 0);
+        assert_eq!(strategy.score(&other, None), None);
+
+        let excluded = make_candidate("SP3D6PV2ACBPEKYJTCMH7HEN02KP87QSP8KTEH335", 1, None, None);
+        assert!(strategy.is_excluded(&excluded));
+
+        assert!(TxSelectionList::from_principals(&["not-a-principal".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_strategy_names() {
+        for name in [
+            TxSelectionStrategyName::FeeRate,
+            TxSelectionStrategyName::PureFeeRate,
+            TxSelectionStrategyName::CostPacking,
+        ]
+        .iter()
+        {
+            assert_eq!(
+                TxSelectionStrategyName::from_str(&name.to_string()),
+                Ok(*name)
+            );
+        }
+        assert!(TxSelectionStrategyName::from_str("knapsack").is_err());
+
+        let strategy = TxSelectionSettings::default().make_strategy(
+            25,
+            &ExecutionCost::max_value(),
+            &ExecutionCost::zero(),
+        );
+        assert_eq!(strategy.name(), "fee_rate");
+        assert_eq!(strategy.consider_unranked_prob(), 25);
+        assert!(strategy.ranks_by_fee_rate());
+    }
+}
@@ -23,11 +23,8 @@ pub type Signal = nix::sys::signal::Signal;
 impl SignalId {
     pub fn from_c_signal(c_sig_id: nix::libc::c_int) -> SignalId {
         match c_sig_id {
-            x if x == Signal::SIGTERM as nix::libc::c_int
-                || x == Signal::SIGHUP as nix::libc::c_int =>
-            {
-                SignalId::Termination
-            }
+            x if x == Signal::SIGTERM as nix::libc::c_int => SignalId::Termination,
+            x if x == Signal::SIGHUP as nix::libc::c_int => SignalId::Hangup,
             x if x == Signal::SIGINT as nix::libc::c_int => SignalId::CtrlC,
             x if x == Signal::SIGBUS as nix::libc::c_int => SignalId::Bus,
             _ => SignalId::Other,
@@ -39,6 +36,7 @@ impl SignalId {
             x if x == SignalId::CtrlC as u8 => SignalId::CtrlC,
             x if x == SignalId::Termination as u8 => SignalId::Termination,
             x if x == SignalId::Bus as u8 => SignalId::Bus,
+            x if x == SignalId::Hangup as u8 => SignalId::Hangup,
             _ => SignalId::Other,
         }
     }
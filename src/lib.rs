@@ -115,6 +115,7 @@ pub mod monitoring;
 pub mod cost_estimates;
 
 pub mod clarity_cli;
+pub mod clarity_lsp;
 
 // set via _compile-time_ envars
 const GIT_BRANCH: Option<&'static str> = option_env!("GIT_BRANCH");
@@ -54,9 +54,15 @@ use blockstack_lib::burnchains::Address;
 use blockstack_lib::burnchains::Burnchain;
 use blockstack_lib::burnchains::Txid;
 use blockstack_lib::chainstate::burn::ConsensusHash;
+use blockstack_lib::chainstate::coordinator::{
+    replay_burn_block, BlockEventDispatcher, OnChainRewardSetProvider,
+};
+use blockstack_lib::chainstate::stacks::address::PoxAddress;
+use blockstack_lib::chainstate::stacks::db::accounts::MinerReward;
 use blockstack_lib::chainstate::stacks::db::blocks::DummyEventDispatcher;
 use blockstack_lib::chainstate::stacks::db::blocks::StagingBlock;
 use blockstack_lib::chainstate::stacks::db::ChainStateBootData;
+use blockstack_lib::chainstate::stacks::db::MinerRewardInfo;
 use blockstack_lib::chainstate::stacks::index::marf::MARFOpenOpts;
 use blockstack_lib::chainstate::stacks::index::marf::MarfConnection;
 use blockstack_lib::chainstate::stacks::index::marf::MARF;
@@ -69,6 +75,7 @@ use blockstack_lib::clarity::vm::types::StacksAddressExtensions;
 use blockstack_lib::clarity::vm::ClarityVersion;
 use blockstack_lib::clarity_cli::vm_execute;
 use blockstack_lib::codec::StacksMessageCodec;
+use blockstack_lib::core::mempool::MemPoolExportedTx;
 use blockstack_lib::core::*;
 use blockstack_lib::cost_estimates::metrics::UnitMetric;
 use blockstack_lib::net::relay::Relayer;
@@ -823,6 +830,113 @@ simulating a miner.
         process::exit(0);
     }
 
+    if argv[1] == "export-mempool" {
+        if argv.len() < 4 {
+            eprintln!(
+                "Usage: {} export-mempool <working-dir> <output-file>
+
+Given a <working-dir>, write out every transaction in its mempool, along with its accept time
+and fee rate estimate, to <output-file>.  The file can be loaded into another node's mempool
+with import-mempool.
+",
+                argv[0]
+            );
+            process::exit(1);
+        }
+
+        let chain_state_path = format!("{}/mainnet/chainstate/", &argv[2]);
+        let estimator = Box::new(UnitEstimator);
+        let metric = Box::new(UnitMetric);
+        let mempool_db =
+            MemPoolDB::open(true, CHAIN_ID_MAINNET, &chain_state_path, estimator, metric)
+                .expect("Failed to open mempool db");
+
+        let mut output_file =
+            File::create(&argv[3]).unwrap_or_else(|_| panic!("Failed to create {}", &argv[3]));
+        let num_txs = mempool_db
+            .export_txs(&mut output_file)
+            .expect("Failed to export mempool transactions");
+
+        println!("Exported {} transactions to {}", num_txs, &argv[3]);
+        process::exit(0);
+    }
+
+    if argv[1] == "import-mempool" {
+        if argv.len() < 4 {
+            eprintln!(
+                "Usage: {} import-mempool <working-dir> <input-file>
+
+Given a <working-dir>, load the transactions in <input-file> (written by export-mempool) into
+its mempool.  Each transaction goes through the usual mempool admission checks against the
+canonical chain tip; the ones that are rejected are printed, along with the reason why.
+",
+                argv[0]
+            );
+            process::exit(1);
+        }
+
+        let sort_db_path = format!("{}/mainnet/burnchain/sortition", &argv[2]);
+        let chain_state_path = format!("{}/mainnet/chainstate/", &argv[2]);
+
+        let sort_db = SortitionDB::open(&sort_db_path, false, PoxConstants::mainnet_default())
+            .unwrap_or_else(|_| panic!("Failed to open {}", &sort_db_path));
+        let chain_id = CHAIN_ID_MAINNET;
+        let (mut chain_state, _) = StacksChainState::open(true, chain_id, &chain_state_path, None)
+            .expect("Failed to open stacks chain state");
+        let burn_tip = SortitionDB::get_canonical_burn_chain_tip(sort_db.conn())
+            .expect("Failed to get sortition chain tip");
+        let stacks_epoch = SortitionDB::get_stacks_epoch(sort_db.conn(), burn_tip.block_height)
+            .expect("Failed to load Stacks epoch")
+            .expect("No Stacks epoch for the canonical burn chain tip");
+        let stacks_tip = chain_state
+            .get_stacks_chain_tip(&sort_db)
+            .expect("Failed to load Stacks chain tip")
+            .expect("No Stacks chain tip");
+
+        let estimator = Box::new(UnitEstimator);
+        let metric = Box::new(UnitMetric);
+        let mut mempool_db = MemPoolDB::open(true, chain_id, &chain_state_path, estimator, metric)
+            .expect("Failed to open mempool db");
+
+        let export = fs::read(&argv[3]).unwrap_or_else(|_| panic!("Failed to read {}", &argv[3]));
+        let mut export_ptr = &export[..];
+        let mut num_imported = 0;
+        let mut num_skipped = 0;
+        let mut num_rejected = 0;
+        while !export_ptr.is_empty() {
+            let exported_tx = MemPoolExportedTx::consensus_deserialize(&mut export_ptr)
+                .unwrap_or_else(|_| panic!("Failed to decode transaction in {}", &argv[3]));
+            let txid = exported_tx.tx.txid();
+            if mempool_db.has_tx(&txid) {
+                num_skipped += 1;
+                continue;
+            }
+
+            match mempool_db.import_tx(
+                &mut chain_state,
+                &stacks_tip.consensus_hash,
+                &stacks_tip.anchored_block_hash,
+                &exported_tx,
+                &stacks_epoch.block_limit,
+                &stacks_epoch.epoch_id,
+            ) {
+                Ok(()) => {
+                    num_imported += 1;
+                }
+                Err(e) => {
+                    num_rejected += 1;
+                    println!("{}", e.into_json(&txid));
+                }
+            }
+        }
+
+        println!(
+            "Imported {} transactions; {} rejected, {} already in the mempool",
+            num_imported, num_rejected, num_skipped
+        );
+        process::exit(0);
+    }
+
     if argv[1] == "tip-mine" {
         tip_mine();
     }
@@ -1005,6 +1119,10 @@ simulating a miner.
         return;
     }
 
+    if argv[1] == "replay-events" {
+        replay_events();
+    }
+
     if argv[1] == "replay-chainstate" {
         if argv.len() < 7 {
             eprintln!("Usage: {} OLD_CHAINSTATE_PATH OLD_SORTITION_DB_PATH OLD_BURNCHAIN_DB_PATH NEW_CHAINSTATE_PATH NEW_BURNCHAIN_DB_PATH", &argv[0]);
@@ -1324,6 +1442,257 @@ simulating a miner.
     }
 }
 
+/// Re-sends the block and burn block events produced by a replay to a single event observer, in
+/// the same shape as the node sends them to an observer that subscribes to all events.
+struct ReplayEventDispatcher {
+    endpoint: String,
+}
+
+impl ReplayEventDispatcher {
+    fn send_payload(&self, payload: &serde_json::Value, path: &str) {
+        if let Err(e) = post_json_payload(&self.endpoint, path, payload) {
+            eprintln!(
+                "Failed to send /{} payload to {}: {}",
+                path, &self.endpoint, &e
+            );
+            process::exit(1);
+        }
+    }
+}
+
+impl BlockEventDispatcher for ReplayEventDispatcher {
+    fn announce_block(
+        &self,
+        block: &StacksBlock,
+        metadata: &StacksHeaderInfo,
+        receipts: &[events::StacksTransactionReceipt],
+        parent: &StacksBlockId,
+        winner_txid: Txid,
+        matured_rewards: &[MinerReward],
+        matured_rewards_info: Option<&MinerRewardInfo>,
+        parent_burn_block_hash: BurnchainHeaderHash,
+        parent_burn_block_height: u32,
+        parent_burn_block_timestamp: u64,
+        anchored_consumed: &ExecutionCost,
+        mblock_confirmed_consumed: &ExecutionCost,
+        pox_constants: &PoxConstants,
+    ) {
+        let mut all_events = vec![];
+        for receipt in receipts.iter() {
+            let txid = receipt.transaction.txid();
+            for event in receipt.events.iter() {
+                all_events.push((!receipt.post_condition_aborted, txid.clone(), event));
+            }
+        }
+
+        let payload = events::make_new_block_processed_payload(
+            all_events.iter().enumerate().collect(),
+            block,
+            metadata,
+            receipts.iter().enumerate().collect(),
+            parent,
+            &winner_txid,
+            &events::make_mature_rewards_payload(matured_rewards, matured_rewards_info),
+            parent_burn_block_hash,
+            parent_burn_block_height,
+            parent_burn_block_timestamp,
+            anchored_consumed,
+            mblock_confirmed_consumed,
+            pox_constants,
+        );
+        self.send_payload(&payload, events::PATH_BLOCK_PROCESSED);
+    }
+
+    fn announce_burn_block(
+        &self,
+        burn_block: &BurnchainHeaderHash,
+        burn_block_height: u64,
+        rewards: Vec<(PoxAddress, u64)>,
+        burns: u64,
+        slot_holders: Vec<PoxAddress>,
+    ) {
+        let payload = events::make_new_burn_block_payload(
+            burn_block,
+            burn_block_height,
+            rewards,
+            burns,
+            slot_holders,
+        );
+        self.send_payload(&payload, events::PATH_BURN_BLOCK_SUBMIT);
+    }
+}
+
+/// POST a JSON payload to `http://{endpoint}/{path}`, and fail unless the observer answers with a
+/// 2xx status.
+fn post_json_payload(
+    endpoint: &str,
+    path: &str,
+    payload: &serde_json::Value,
+) -> Result<(), String> {
+    let body = payload.to_string();
+    let mut sock = std::net::TcpStream::connect(endpoint)
+        .map_err(|e| format!("failed to connect: {:?}", &e))?;
+
+    let request = format!(
+        "POST /{} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        path,
+        endpoint,
+        body.len(),
+        body
+    );
+    sock.write_all(request.as_bytes())
+        .map_err(|e| format!("failed to send request: {:?}", &e))?;
+
+    let mut status_line = String::new();
+    BufReader::new(sock)
+        .read_line(&mut status_line)
+        .map_err(|e| format!("failed to read response: {:?}", &e))?;
+
+    match status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|code| code.parse::<u16>().ok())
+    {
+        Some(code) if code >= 200 && code < 300 => Ok(()),
+        _ => Err(format!("unexpected response '{}'", status_line.trim())),
+    }
+}
+
+fn replay_events() {
+    let argv: Vec<String> = env::args().collect();
+    if argv.len() < 6 {
+        eprintln!(
+            "Usage: {} replay-events <working-dir> <observer> <start-height> <end-height> [mainnet|testnet]
+
+Re-send the new_burn_block and new_block events for the canonical Stacks blocks from
+<start-height> to <end-height> (inclusive) to the event observer at <observer> (host:port).
+Each block's new_block event is preceded by the new_burn_block events of the burnchain blocks
+leading up to it.  Blocks are re-executed to regenerate their receipts, but nothing is written
+to the node's databases.  The node must not be running.  Defaults to mainnet.
+",
+            argv[0]
+        );
+        process::exit(1);
+    }
+
+    let observer = argv[3].clone();
+    let start_height: u64 = argv[4].parse().expect("Could not parse start-height");
+    let end_height: u64 = argv[5].parse().expect("Could not parse end-height");
+    let (mainnet, network_name, mode, chain_id) = match argv.get(6).map(|s| s.as_str()) {
+        None | Some("mainnet") => (true, "mainnet", "mainnet", CHAIN_ID_MAINNET),
+        Some("testnet") => (false, "testnet", "xenon", CHAIN_ID_TESTNET),
+        Some(other) => {
+            eprintln!("Unrecognized network '{}'", other);
+            process::exit(1);
+        }
+    };
+
+    let burnchain_path = format!("{}/{}/burnchain", &argv[2], mode);
+    let chain_state_path = format!("{}/{}/chainstate/", &argv[2], mode);
+
+    let burnchain = Burnchain::new(&burnchain_path, "bitcoin", network_name)
+        .expect("Failed to instantiate burnchain");
+    let burnchain_db = BurnchainDB::open(&burnchain.get_burnchaindb_path(), false)
+        .expect("Failed to open burnchain DB");
+    let mut sort_db = SortitionDB::open(
+        &burnchain.get_db_path(),
+        true,
+        burnchain.pox_constants.clone(),
+    )
+    .expect("Failed to open sortition DB");
+    let mut chain_state = StacksChainState::open(mainnet, chain_id, &chain_state_path, None)
+        .expect("Failed to open stacks chain state")
+        .0;
+
+    let sortition_tip = SortitionDB::get_canonical_sortition_tip(sort_db.conn())
+        .expect("Failed to get sortition chain tip");
+    let stacks_tip = chain_state
+        .get_stacks_chain_tip(&sort_db)
+        .expect("Failed to get Stacks chain tip")
+        .expect("No Stacks chain tip");
+    let stacks_tip_id = StacksBlockHeader::make_index_block_hash(
+        &stacks_tip.consensus_hash,
+        &stacks_tip.anchored_block_hash,
+    );
+
+    // find the canonical blocks to replay, and the burnchain height at which the burnchain
+    // replay must start
+    let mut stacks_blocks = vec![];
+    let mut next_burn_height = None;
+    {
+        let mut index_tx = chain_state
+            .index_tx_begin()
+            .expect("Failed to begin chainstate transaction");
+        for height in start_height.max(1)..=end_height.min(stacks_tip.height) {
+            let header =
+                StacksChainState::get_index_tip_ancestor(&mut index_tx, &stacks_tip_id, height)
+                    .expect("Failed to query Stacks chain tip ancestor")
+                    .expect("No canonical Stacks block at height");
+
+            if next_burn_height.is_none() {
+                let parent_header = StacksChainState::get_index_tip_ancestor(
+                    &mut index_tx,
+                    &stacks_tip_id,
+                    height - 1,
+                )
+                .expect("Failed to query Stacks chain tip ancestor")
+                .expect("No canonical Stacks block at height");
+                next_burn_height = Some(u64::from(parent_header.burn_header_height) + 1);
+            }
+
+            stacks_blocks.push((header.index_block_hash(), height, header.burn_header_height));
+        }
+    }
+
+    let dispatcher = ReplayEventDispatcher { endpoint: observer };
+    let mut next_burn_height = next_burn_height.unwrap_or(0);
+
+    for (index_block_hash, stacks_height, burn_height) in stacks_blocks.into_iter() {
+        while next_burn_height <= u64::from(burn_height) {
+            let (snapshot, parent_snapshot) = {
+                let ic = sort_db.index_handle(&sortition_tip);
+                let snapshot =
+                    SortitionDB::get_ancestor_snapshot(&ic, next_burn_height, &sortition_tip)
+                        .expect("Failed to query sortition")
+                        .expect("No canonical sortition at burnchain height");
+                let parent_snapshot =
+                    SortitionDB::get_ancestor_snapshot(&ic, next_burn_height - 1, &sortition_tip)
+                        .expect("Failed to query sortition")
+                        .expect("No canonical sortition at burnchain height");
+                (snapshot, parent_snapshot)
+            };
+            let burnchain_block =
+                BurnchainDB::get_burnchain_block(burnchain_db.conn(), &snapshot.burn_header_hash)
+                    .expect("Failed to load burnchain block");
+
+            println!("Replay burnchain block {}", next_burn_height);
+            replay_burn_block(
+                &dispatcher,
+                &burnchain_block,
+                &parent_snapshot,
+                &mut chain_state,
+                &mut sort_db,
+                &burnchain,
+                &OnChainRewardSetProvider(),
+                false,
+            )
+            .expect("Failed to replay burnchain block");
+            next_burn_height += 1;
+        }
+
+        println!(
+            "Replay Stacks block {} ({})",
+            stacks_height, &index_block_hash
+        );
+        let mut sort_tx = sort_db.tx_begin_at_tip();
+        chain_state
+            .replay_block(&mut sort_tx, &index_block_hash, &dispatcher)
+            .expect("Failed to replay Stacks block");
+    }
+
+    process::exit(0);
+}
+
 fn tip_mine() {
     let argv: Vec<String> = env::args().collect();
     if argv.len() < 6 {
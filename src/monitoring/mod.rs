@@ -313,6 +313,26 @@ pub fn increment_msg_counter(name: String) {
         .inc();
 }
 
+#[allow(unused_variables)]
+pub fn increment_rpc_rate_limited_counter(scope: &str) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::RPC_RATE_LIMITED_COUNTER_VEC
+        .with_label_values(&[scope])
+        .inc();
+}
+
+#[allow(unused_variables)]
+pub fn update_rpc_rate_limiter_clients(value: i64) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::RPC_RATE_LIMITER_CLIENTS_GAUGE.set(value);
+}
+
+#[allow(unused_variables)]
+pub fn increment_rpc_read_only_cost_charged(value: u64) {
+    #[cfg(feature = "monitoring_prom")]
+    prometheus::RPC_READ_ONLY_COST_CHARGED_COUNTER.inc_by(value as i64);
+}
+
 pub fn increment_stx_mempool_gc() {
     #[cfg(feature = "monitoring_prom")]
     prometheus::STX_MEMPOOL_GC.inc();
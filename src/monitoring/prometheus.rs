@@ -172,6 +172,22 @@ lazy_static! {
         &["name"]
     ).unwrap();
 
+    pub static ref RPC_RATE_LIMITED_COUNTER_VEC: IntCounterVec = register_int_counter_vec!(
+        "stacks_node_rpc_rate_limited_total",
+        "Total number of RPC requests refused by the rate limiter, by endpoint scope",
+        &["scope"]
+    ).unwrap();
+
+    pub static ref RPC_RATE_LIMITER_CLIENTS_GAUGE: IntGauge = register_int_gauge!(opts!(
+        "stacks_node_rpc_rate_limiter_clients",
+        "Number of RPC clients the rate limiter is tracking"
+    )).unwrap();
+
+    pub static ref RPC_READ_ONLY_COST_CHARGED_COUNTER: IntCounter = register_int_counter!(opts!(
+        "stacks_node_rpc_read_only_cost_charged_total",
+        "Total Clarity cost charged to RPC clients for read-only calls, in percent of the per-call limit"
+    )).unwrap();
+
 
     pub static ref STX_MEMPOOL_GC: IntCounter = register_int_counter!(opts!(
         "stacks_node_mempool_gc_count",
@@ -676,6 +676,20 @@ impl BatchedDNSLookupsState {
                         }
                     };
                     match url.host() {
+                        Some(url::Host::Domain(domain))
+                            if connection_options.socks5_proxy.is_some() =>
+                        {
+                            // the proxy resolves the name when we connect
+                            match DNSResolver::resolve_for_proxy(domain, port) {
+                                Ok(addrs) => {
+                                    state.dns_lookups.insert(url_str, Some(addrs));
+                                }
+                                Err(msg) => {
+                                    warn!("Atlas: Unsupported URL {:?}: {}", &url_str, msg);
+                                    state.errors.insert(url_str, net_error::LookupError(msg));
+                                }
+                            }
+                        }
                         Some(url::Host::Domain(domain)) => {
                             let res = dns_client.queue_lookup(
                                 domain.clone(),
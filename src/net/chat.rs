@@ -1291,21 +1291,58 @@ impl ConversationP2P {
             neighbors.clear();
         }
 
-        let neighbor_addrs: Vec<NeighborAddress> = neighbors
-            .iter()
-            .map(|n| NeighborAddress::from_neighbor(n))
-            .collect();
+        // neighbors known only by DNS name are of no use to a peer that can't dial them by name
+        let supports_hosts = (self.peer_services & (ServiceFlags::HOSTS as u16)) != 0;
+        let mut neighbor_addrs = vec![];
+        let mut hosts = vec![];
+        for neighbor in neighbors.iter() {
+            if neighbor.addr.addrbytes.is_host_address() {
+                if !supports_hosts {
+                    continue;
+                }
+                match PeerDB::get_peer_host(peer_dbconn, &neighbor.addr.addrbytes)
+                    .map_err(net_error::DBError)?
+                {
+                    Some(host) => {
+                        if !hosts.contains(&host) {
+                            hosts.push(host);
+                        }
+                    }
+                    None => {
+                        continue;
+                    }
+                }
+            }
+            neighbor_addrs.push(NeighborAddress::from_neighbor(neighbor));
+        }
 
         debug!(
-            "{:?}: handle GetNeighbors from {:?}. Reply with {} neighbors",
+            "{:?}: handle GetNeighbors from {:?}. Reply with {} neighbors ({} named)",
             &local_peer,
             &self,
-            neighbor_addrs.len()
+            neighbor_addrs.len(),
+            hosts.len()
         );
 
-        let payload = StacksMessageType::Neighbors(NeighborsData {
-            neighbors: neighbor_addrs,
-        });
+        if supports_hosts {
+            // tell it our own name too, since it can't learn it from our address
+            if let Some(ref host) = self.connection.options.public_host {
+                if !hosts.contains(host) {
+                    hosts.push(host.clone());
+                }
+            }
+        }
+
+        let payload = if !hosts.is_empty() {
+            StacksMessageType::NeighborHosts(NeighborHostsData {
+                neighbors: neighbor_addrs,
+                hosts,
+            })
+        } else {
+            StacksMessageType::Neighbors(NeighborsData {
+                neighbors: neighbor_addrs,
+            })
+        };
         let reply = self.sign_reply(chain_view, &local_peer.private_key, payload, preamble.seq)?;
         let reply_handle = self.relay_signed_message(reply).map_err(|e| {
             debug!(
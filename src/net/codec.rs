@@ -545,6 +545,59 @@ impl StacksMessageCodec for NeighborsData {
     }
 }
 
+impl StacksMessageCodec for PeerHostName {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        // MAX_PEER_HOST_NAME_LEN fits in a u8
+        write_next(fd, &(self.0.len() as u8))?;
+        fd.write_all(self.0.as_bytes())
+            .map_err(codec_error::WriteError)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<PeerHostName, codec_error> {
+        let len_byte: u8 = read_next(fd)?;
+        if (len_byte as usize) > MAX_PEER_HOST_NAME_LEN {
+            return Err(codec_error::DeserializeError(
+                "Failed to deserialize peer host name: too long".to_string(),
+            ));
+        }
+        let mut bytes = vec![0u8; len_byte as usize];
+        fd.read_exact(&mut bytes).map_err(codec_error::ReadError)?;
+
+        let name = String::from_utf8(bytes).map_err(|_e| {
+            codec_error::DeserializeError(
+                "Failed to deserialize peer host name: not utf8".to_string(),
+            )
+        })?;
+
+        // must already be in canonical (lowercase) form, since its hash is its address
+        let host = PeerHostName::try_from(name.as_str())?;
+        if host.0 != name {
+            return Err(codec_error::DeserializeError(
+                "Failed to deserialize peer host name: not lowercase".to_string(),
+            ));
+        }
+        Ok(host)
+    }
+}
+
+impl StacksMessageCodec for NeighborHostsData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.neighbors)?;
+        write_next(fd, &self.hosts)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<NeighborHostsData, codec_error> {
+        let neighbors: Vec<NeighborAddress> =
+            read_next_at_most::<_, NeighborAddress>(fd, MAX_NEIGHBORS_DATA_LEN)?;
+        // one name per neighbor, plus the sender's own
+        let hosts: Vec<PeerHostName> =
+            read_next_at_most::<_, PeerHostName>(fd, MAX_NEIGHBORS_DATA_LEN + 1)?;
+        Ok(NeighborHostsData { neighbors, hosts })
+    }
+}
+
 impl HandshakeData {
     pub fn from_local_peer(local_peer: &LocalPeer) -> HandshakeData {
         let (addrbytes, port) = match local_peer.public_ip_address {
@@ -787,6 +840,7 @@ impl StacksMessageType {
             StacksMessageType::Pong(ref _m) => StacksMessageID::Pong,
             StacksMessageType::NatPunchRequest(ref _m) => StacksMessageID::NatPunchRequest,
             StacksMessageType::NatPunchReply(ref _m) => StacksMessageID::NatPunchReply,
+            StacksMessageType::NeighborHosts(ref _m) => StacksMessageID::NeighborHosts,
         }
     }
 
@@ -811,6 +865,7 @@ impl StacksMessageType {
             StacksMessageType::Pong(ref _m) => "Pong",
             StacksMessageType::NatPunchRequest(ref _m) => "NatPunchRequest",
             StacksMessageType::NatPunchReply(ref _m) => "NatPunchReply",
+            StacksMessageType::NeighborHosts(ref _m) => "NeighborHosts",
         }
     }
 
@@ -869,6 +924,9 @@ impl StacksMessageType {
             StacksMessageType::NatPunchReply(ref m) => {
                 format!("NatPunchReply({},{}:{})", m.nonce, &m.addrbytes, m.port)
             }
+            StacksMessageType::NeighborHosts(ref m) => {
+                format!("NeighborHosts({:?},{:?})", m.neighbors, m.hosts)
+            }
         }
     }
 }
@@ -902,6 +960,7 @@ impl StacksMessageCodec for StacksMessageID {
             x if x == StacksMessageID::Pong as u8 => StacksMessageID::Pong,
             x if x == StacksMessageID::NatPunchRequest as u8 => StacksMessageID::NatPunchRequest,
             x if x == StacksMessageID::NatPunchReply as u8 => StacksMessageID::NatPunchReply,
+            x if x == StacksMessageID::NeighborHosts as u8 => StacksMessageID::NeighborHosts,
             _ => {
                 return Err(codec_error::DeserializeError(
                     "Unknown message ID".to_string(),
@@ -935,6 +994,7 @@ impl StacksMessageCodec for StacksMessageType {
             StacksMessageType::Pong(ref m) => write_next(fd, m)?,
             StacksMessageType::NatPunchRequest(ref nonce) => write_next(fd, nonce)?,
             StacksMessageType::NatPunchReply(ref m) => write_next(fd, m)?,
+            StacksMessageType::NeighborHosts(ref m) => write_next(fd, m)?,
         }
         Ok(())
     }
@@ -1012,6 +1072,10 @@ impl StacksMessageCodec for StacksMessageType {
                 let m: NatPunchData = read_next(fd)?;
                 StacksMessageType::NatPunchReply(m)
             }
+            StacksMessageID::NeighborHosts => {
+                let m: NeighborHostsData = read_next(fd)?;
+                StacksMessageType::NeighborHosts(m)
+            }
             StacksMessageID::Reserved => {
                 return Err(codec_error::DeserializeError(
                     "Unsupported message ID 'reserved'".to_string(),
@@ -1769,6 +1833,106 @@ pub mod test {
         check_codec_and_corruption::<NeighborsData>(&data, &bytes);
     }
 
+    #[test]
+    fn codec_NeighborHostsData() {
+        let data = NeighborHostsData {
+            neighbors: vec![NeighborAddress {
+                addrbytes: PeerAddress([
+                    0xfd, 0x87, 0xd8, 0x7e, 0xeb, 0x43, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c,
+                    0x0d, 0x0e, 0x0f,
+                ]),
+                port: 12345,
+                public_key_hash: Hash160::from_bytes(
+                    &hex_bytes("1111111111111111111111111111111111111111").unwrap(),
+                )
+                .unwrap(),
+            }],
+            hosts: vec![PeerHostName::try_from("ab.onion").unwrap()],
+        };
+        let bytes = vec![
+            // length
+            0x00, 0x00, 0x00, 0x01, // addrbytes
+            0xfd, 0x87, 0xd8, 0x7e, 0xeb, 0x43, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f, // port
+            0x30, 0x39, // public key hash
+            0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11,
+            0x11, 0x11, 0x11, 0x11, 0x11, 0x11, // hosts length
+            0x00, 0x00, 0x00, 0x01, // host name length
+            0x08, // host name
+            0x61, 0x62, 0x2e, 0x6f, 0x6e, 0x69, 0x6f, 0x6e,
+        ];
+
+        check_codec_and_corruption::<NeighborHostsData>(&data, &bytes);
+
+        // host names must be canonical
+        let mut uppercase_bytes = bytes.clone();
+        let uppercase_len = uppercase_bytes.len();
+        uppercase_bytes[uppercase_len - 8] = 0x41;
+        assert!(NeighborHostsData::consensus_deserialize(&mut &uppercase_bytes[..]).is_err());
+    }
+
+    #[test]
+    fn test_peer_host_name() {
+        let host = PeerHostName::try_from("ExAmPle-1.Onion").unwrap();
+        assert_eq!(host.as_str(), "example-1.onion");
+        assert!(host.is_onion());
+        assert!(!PeerHostName::try_from("example.com").unwrap().is_onion());
+
+        let addr = host.to_peer_address();
+        assert!(addr.is_host_address());
+        assert!(!addr.is_ipv4());
+        assert_eq!(
+            addr,
+            PeerHostName::try_from("example-1.onion")
+                .unwrap()
+                .to_peer_address()
+        );
+        assert!(
+            addr != PeerHostName::try_from("example-2.onion")
+                .unwrap()
+                .to_peer_address()
+        );
+        assert!(!PeerAddress::from_ipv4(127, 0, 0, 1).is_host_address());
+
+        for bad in [
+            "",
+            ".onion",
+            "foo..onion",
+            "-foo.onion",
+            "foo-.onion",
+            "foo_bar.onion",
+            "foo bar.onion",
+            "foo.onion:20444",
+        ]
+        .iter()
+        {
+            assert!(PeerHostName::try_from(*bad).is_err(), "accepted '{}'", bad);
+        }
+        assert!(PeerHostName::try_from("a".repeat(64).as_str()).is_err());
+        assert!(PeerHostName::try_from(
+            format!(
+                "{}.{}.{}.{}",
+                "a".repeat(63),
+                "b".repeat(63),
+                "c".repeat(63),
+                "d".repeat(62)
+            )
+            .as_str()
+        )
+        .is_err());
+        assert!(PeerHostName::try_from(
+            format!(
+                "{}.{}.{}.{}",
+                "a".repeat(63),
+                "b".repeat(63),
+                "c".repeat(63),
+                "d".repeat(61)
+            )
+            .as_str()
+        )
+        .is_ok());
+    }
+
     #[test]
     fn codec_HandshakeData() {
         let data = HandshakeData {
@@ -1935,7 +2099,8 @@ pub mod test {
 
     #[test]
     fn codec_StacksMessage() {
-        let payloads: Vec<StacksMessageType> = vec![
+        let payloads: Vec<StacksMessageType> =
+            vec![
             StacksMessageType::Handshake(HandshakeData {
                 addrbytes: PeerAddress([
                     0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c,
@@ -2049,6 +2214,17 @@ pub mod test {
                 port: 12345,
                 nonce: 0x12345678,
             }),
+            StacksMessageType::NeighborHosts(NeighborHostsData {
+                neighbors: vec![NeighborAddress {
+                    addrbytes: PeerHostName::try_from("ab.onion").unwrap().to_peer_address(),
+                    port: 12345,
+                    public_key_hash: Hash160::from_bytes(
+                        &hex_bytes("1111111111111111111111111111111111111111").unwrap(),
+                    )
+                    .unwrap(),
+                }],
+                hosts: vec![PeerHostName::try_from("ab.onion").unwrap()],
+            }),
         ];
 
         let mut maximal_relayers: Vec<RelayData> = vec![];
@@ -14,6 +14,8 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::collections::VecDeque;
 use std::convert::TryFrom;
 use std::io;
@@ -43,6 +45,7 @@ use crate::net::HttpRequestPreamble;
 use crate::net::HttpResponsePreamble;
 use crate::net::MessageSequence;
 use crate::net::PeerAddress;
+use crate::net::PeerHostName;
 use crate::net::Preamble;
 use crate::net::ProtocolFamily;
 use crate::net::RelayData;
@@ -50,11 +53,13 @@ use crate::net::StacksHttp;
 use crate::net::StacksP2P;
 
 use crate::net::download::BLOCK_DOWNLOAD_INTERVAL;
+use crate::net::http::{RPCAuthToken, RPCScope};
 use crate::net::inv::{INV_REWARD_CYCLES, INV_SYNC_INTERVAL};
 use crate::net::neighbors::{
     NEIGHBOR_REQUEST_TIMEOUT, NEIGHBOR_WALK_INTERVAL, NUM_INITIAL_WALKS, WALK_MAX_DURATION,
     WALK_MIN_DURATION, WALK_RESET_INTERVAL, WALK_RESET_PROB, WALK_RETRY_COUNT, WALK_STATE_TIMEOUT,
 };
+use crate::net::server::RPCRateLimit;
 
 use clarity::vm::{costs::ExecutionCost, types::BOUND_VALUE_SERIALIZATION_HEX};
 
@@ -380,6 +385,29 @@ pub struct ConnectionOptions {
     pub mempool_max_tx_query: u64,
     /// how long a mempool sync is allowed to take, in total, before timing out
     pub mempool_sync_timeout: u64,
+    /// scopes of RPC endpoints that clients can use without an auth token
+    pub rpc_public_scopes: HashSet<RPCScope>,
+    /// auth tokens that grant further RPC scopes.  Endpoints in a scope that is neither public
+    /// nor granted by a token are disabled.
+    pub rpc_auth_tokens: Vec<RPCAuthToken>,
+    /// per-client limits on RPC requests, by scope of endpoint.  Scopes without one are unlimited.
+    pub rpc_rate_limits: HashMap<RPCScope, RPCRateLimit>,
+    /// per-client limit on the Clarity cost of read-only calls, in percent of
+    /// `read_only_call_limit`.  Unlimited if unset.
+    pub rpc_read_only_cost_limit: Option<RPCRateLimit>,
+    /// per-IP limit on RPC requests that fail authorization.  An IP address that has spent it is
+    /// refused before its credentials are checked.
+    pub rpc_auth_failure_limit: RPCRateLimit,
+    /// PEM files with the certificate chain and private key to serve RPC clients over TLS.
+    /// The RPC server speaks plaintext HTTP unless both are set.
+    pub rpc_tls_cert_path: Option<String>,
+    pub rpc_tls_key_path: Option<String>,
+    /// SOCKS5 proxy to route outbound p2p and HTTP connections through.  Host names are resolved
+    /// by the proxy, not locally.  Needed to dial peers known only by DNS name (e.g. `.onion`).
+    pub socks5_proxy: Option<net::SocketAddr>,
+    /// DNS name this node is reachable at, if it has no public IP address of its own (e.g. a Tor
+    /// onion service).  Advertised to peers in place of `public_ip_address`.
+    pub public_host: Option<PeerHostName>,
 
     // fault injection
     pub disable_neighbor_walk: bool,
@@ -466,6 +494,18 @@ impl std::default::Default for ConnectionOptions {
             mempool_sync_interval: 30, // number of seconds in-between mempool sync
             mempool_max_tx_query: 128, // maximum number of transactions to visit per mempool query
             mempool_sync_timeout: 180, // how long a mempool sync can go for (3 minutes)
+            rpc_public_scopes: RPCScope::public_default(), // all but simulation and the admin endpoints
+            rpc_auth_tokens: vec![],
+            rpc_rate_limits: HashMap::new(), // no rate limits by default
+            rpc_read_only_cost_limit: None,
+            rpc_auth_failure_limit: RPCRateLimit {
+                burst: 20,
+                per_minute: 10,
+            },
+            rpc_tls_cert_path: None,
+            rpc_tls_key_path: None,
+            socks5_proxy: None,
+            public_host: None,
 
             // no faults on by default
             disable_neighbor_walk: false,
@@ -55,6 +55,7 @@ use crate::net::Neighbor;
 use crate::net::NeighborAddress;
 use crate::net::NeighborKey;
 use crate::net::PeerAddress;
+use crate::net::PeerHostName;
 use crate::net::ServiceFlags;
 
 use crate::burnchains::PrivateKey;
@@ -68,6 +69,11 @@ pub const PEERDB_VERSION: &'static str = "1";
 
 const NUM_SLOTS: usize = 8;
 
+/// How many DNS names of peers that are not (yet) in the frontier we remember.  Names are
+/// learned before their peers are contacted, so this bounds what a neighbor can make us store by
+/// handing out names of peers that never make it into the frontier.
+const MAX_UNSLOTTED_PEER_HOSTS: u32 = 1024;
+
 impl PeerAddress {
     pub fn to_bin(&self) -> String {
         to_bin(&self.0)
@@ -363,6 +369,17 @@ const PEERDB_INITIAL_SCHEMA: &'static [&'static str] = &[
 const PEERDB_INDEXES: &'static [&'static str] =
     &["CREATE INDEX IF NOT EXISTS peer_address_index ON frontier(network_id,addrbytes,port);"];
 
+// Tables added after the initial schema.  These get created when the DB is opened, if missing.
+const PEERDB_SCHEMA_ADDITIONS: &[&str] = &[r#"
+    -- DNS names of peers that have no IP address of their own (e.g. Tor onion services),
+    -- keyed by the synthetic address that stands in for them in the frontier.
+    CREATE TABLE IF NOT EXISTS peer_hosts(
+        addrbytes TEXT NOT NULL,
+        host TEXT NOT NULL,
+
+        PRIMARY KEY(addrbytes)
+    );"#];
+
 #[derive(Debug)]
 pub struct PeerDB {
     pub conn: Connection,
@@ -443,10 +460,20 @@ impl PeerDB {
 
         tx.commit().map_err(db_error::SqliteError)?;
 
+        self.add_schema_additions()?;
         self.add_indexes()?;
         Ok(())
     }
 
+    fn add_schema_additions(&mut self) -> Result<(), db_error> {
+        let tx = self.tx_begin()?;
+        for row_text in PEERDB_SCHEMA_ADDITIONS {
+            tx.execute_batch(row_text).map_err(db_error::SqliteError)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
     fn add_indexes(&mut self) -> Result<(), db_error> {
         let tx = self.tx_begin()?;
         for row_text in PEERDB_INDEXES {
@@ -606,6 +633,7 @@ impl PeerDB {
             }
         }
         if readwrite {
+            db.add_schema_additions()?;
             db.add_indexes()?;
         }
         Ok(db)
@@ -786,6 +814,65 @@ impl PeerDB {
         query_row::<Neighbor, _>(conn, &qry, &args)
     }
 
+    /// Remember the DNS name behind a synthetic peer address.
+    /// Names of peers that are not in the frontier are only kept up to
+    /// MAX_UNSLOTTED_PEER_HOSTS; the least recently set ones are forgotten first.
+    pub fn set_peer_host<'a>(
+        tx: &mut Transaction<'a>,
+        host: &PeerHostName,
+    ) -> Result<(), db_error> {
+        let args: &[&dyn ToSql] = &[&host.to_peer_address().to_bin(), &host.as_str()];
+        tx.execute(
+            "INSERT OR REPLACE INTO peer_hosts (addrbytes, host) VALUES (?1, ?2)",
+            args,
+        )
+        .map_err(db_error::SqliteError)?;
+
+        tx.execute(
+            "DELETE FROM peer_hosts WHERE rowid IN \
+                (SELECT rowid FROM peer_hosts WHERE addrbytes NOT IN (SELECT addrbytes FROM frontier) \
+                 ORDER BY rowid DESC LIMIT -1 OFFSET ?1)",
+            &[&MAX_UNSLOTTED_PEER_HOSTS as &dyn ToSql],
+        )
+        .map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+
+    /// Forget the DNS name behind a synthetic peer address once no frontier entry uses it
+    fn forget_peer_host<'a>(
+        tx: &mut Transaction<'a>,
+        addrbytes: &PeerAddress,
+    ) -> Result<(), db_error> {
+        if !addrbytes.is_host_address() {
+            return Ok(());
+        }
+        tx.execute(
+            "DELETE FROM peer_hosts WHERE addrbytes = ?1 AND \
+                NOT EXISTS (SELECT 1 FROM frontier WHERE addrbytes = ?1)",
+            &[&addrbytes.to_bin() as &dyn ToSql],
+        )
+        .map_err(db_error::SqliteError)?;
+        Ok(())
+    }
+
+    /// Look up the DNS name behind a synthetic peer address, if we know it
+    pub fn get_peer_host(
+        conn: &DBConn,
+        addrbytes: &PeerAddress,
+    ) -> Result<Option<PeerHostName>, db_error> {
+        if !addrbytes.is_host_address() {
+            return Ok(None);
+        }
+        let qry = "SELECT host FROM peer_hosts WHERE addrbytes = ?1";
+        let host_opt = query_row::<String, _>(conn, qry, &[&addrbytes.to_bin() as &dyn ToSql])?;
+        match host_opt {
+            Some(host) => Ok(Some(
+                PeerHostName::try_from(host.as_str()).map_err(|_| db_error::ParseError)?,
+            )),
+            None => Ok(None),
+        }
+    }
+
     /// Get peer by port (used in tests where the IP address doesn't really matter)
     #[cfg(test)]
     pub fn get_peer_by_port(
@@ -878,6 +965,7 @@ impl PeerDB {
         neighbor: &Neighbor,
         slot: u32,
     ) -> Result<(), db_error> {
+        let evicted_opt = PeerDB::get_peer_at(tx, neighbor.addr.network_id, slot)?;
         let neighbor_args: &[&dyn ToSql] = &[
             &neighbor.addr.peer_version,
             &neighbor.addr.network_id,
@@ -900,6 +988,9 @@ impl PeerDB {
                    VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)", neighbor_args)
             .map_err(db_error::SqliteError)?;
 
+        if let Some(evicted) = evicted_opt {
+            PeerDB::forget_peer_host(tx, &evicted.addr.addrbytes)?;
+        }
         Ok(())
     }
 
@@ -920,6 +1011,7 @@ impl PeerDB {
         )
         .map_err(db_error::SqliteError)?;
 
+        PeerDB::forget_peer_host(tx, peer_addr)?;
         Ok(())
     }
 
@@ -1259,6 +1351,30 @@ impl PeerDB {
         Ok(())
     }
 
+    /// Allow every peer we know of under a CIDR prefix until the given deadline (-1 for
+    /// "always"), without recording the prefix itself
+    pub fn set_allow_cidr_peers<'a>(
+        tx: &mut Transaction<'a>,
+        prefix: &PeerAddress,
+        mask: u32,
+        allow_deadline: i64,
+    ) -> Result<(), db_error> {
+        debug!("Apply allow {}/{} until {}", &prefix, mask, allow_deadline);
+        PeerDB::apply_cidr_filter(tx, prefix, mask, "allowed", allow_deadline)
+    }
+
+    /// Deny every peer we know of under a CIDR prefix until the given deadline, without
+    /// recording the prefix itself
+    pub fn set_deny_cidr_peers<'a>(
+        tx: &mut Transaction<'a>,
+        prefix: &PeerAddress,
+        mask: u32,
+        deny_deadline: u64,
+    ) -> Result<(), db_error> {
+        debug!("Apply deny {}/{} until {}", &prefix, mask, deny_deadline);
+        PeerDB::apply_cidr_filter(tx, prefix, mask, "denied", u64_to_sql(deny_deadline)?)
+    }
+
     /// Get random neighbors, optionally always including allowed neighbors
     pub fn get_random_neighbors(
         conn: &DBConn,
@@ -2062,6 +2178,157 @@ mod test {
         .unwrap());
     }
 
+    #[test]
+    fn test_peer_hosts() {
+        let mut db = PeerDB::connect_memory(
+            0x9abcdef0,
+            12345,
+            0,
+            "http://foo.com".into(),
+            &vec![],
+            &vec![],
+        )
+        .unwrap();
+
+        let host = PeerHostName::try_from("abcdefghijklmnop.onion").unwrap();
+        let addrbytes = host.to_peer_address();
+        assert_eq!(PeerDB::get_peer_host(db.conn(), &addrbytes).unwrap(), None);
+
+        {
+            let mut tx = db.tx_begin().unwrap();
+            PeerDB::set_peer_host(&mut tx, &host).unwrap();
+            // idempotent
+            PeerDB::set_peer_host(&mut tx, &host).unwrap();
+            tx.commit().unwrap();
+        }
+
+        assert_eq!(
+            PeerDB::get_peer_host(db.conn(), &addrbytes).unwrap(),
+            Some(host)
+        );
+
+        // plain IP addresses never have a name
+        assert_eq!(
+            PeerDB::get_peer_host(db.conn(), &PeerAddress::from_ipv4(127, 0, 0, 1)).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_peer_hosts_bounded() {
+        let mut db = PeerDB::connect_memory(
+            0x9abcdef0,
+            12345,
+            0,
+            "http://foo.com".into(),
+            &vec![],
+            &vec![],
+        )
+        .unwrap();
+
+        let named_neighbor = |host: &PeerHostName, key: &str| Neighbor {
+            addr: NeighborKey {
+                peer_version: 0x12345678,
+                network_id: 0x9abcdef0,
+                addrbytes: host.to_peer_address(),
+                port: 20444,
+            },
+            public_key: Secp256k1PublicKey::from_hex(key).unwrap(),
+            expire_block: 23456,
+            last_contact_time: 1552509642,
+            allowed: 0,
+            denied: 0,
+            asn: 0,
+            org: 0,
+            in_degree: 1,
+            out_degree: 1,
+        };
+
+        let host_1 = PeerHostName::try_from("abcdefghijklmnop.onion").unwrap();
+        let host_2 = PeerHostName::try_from("qrstuvwxyz234567.onion").unwrap();
+        let neighbor_1 = named_neighbor(
+            &host_1,
+            "02fa66b66f8971a8cd4d20ffded09674e030f0f33883f337f34b95ad4935bac0e3",
+        );
+        let neighbor_2 = named_neighbor(
+            &host_2,
+            "02287c1f1b280b5dde764b146976f6bad3fb485a3df9b1ad2d8ddc5719e7e91ff2",
+        );
+
+        // a name is forgotten when its peer is dropped from the frontier
+        {
+            let mut tx = db.tx_begin().unwrap();
+            PeerDB::set_peer_host(&mut tx, &host_1).unwrap();
+            PeerDB::insert_or_replace_peer(&mut tx, &neighbor_1, 0).unwrap();
+            tx.commit().unwrap();
+        }
+        assert_eq!(
+            PeerDB::get_peer_host(db.conn(), &host_1.to_peer_address()).unwrap(),
+            Some(host_1.clone())
+        );
+        {
+            let mut tx = db.tx_begin().unwrap();
+            PeerDB::drop_peer(
+                &mut tx,
+                0x9abcdef0,
+                &neighbor_1.addr.addrbytes,
+                neighbor_1.addr.port,
+            )
+            .unwrap();
+            tx.commit().unwrap();
+        }
+        assert_eq!(
+            PeerDB::get_peer_host(db.conn(), &host_1.to_peer_address()).unwrap(),
+            None
+        );
+
+        // ...or when another peer takes over its slot
+        {
+            let mut tx = db.tx_begin().unwrap();
+            PeerDB::set_peer_host(&mut tx, &host_1).unwrap();
+            PeerDB::insert_or_replace_peer(&mut tx, &neighbor_1, 0).unwrap();
+            PeerDB::set_peer_host(&mut tx, &host_2).unwrap();
+            PeerDB::insert_or_replace_peer(&mut tx, &neighbor_2, 0).unwrap();
+            tx.commit().unwrap();
+        }
+        assert_eq!(
+            PeerDB::get_peer_host(db.conn(), &host_1.to_peer_address()).unwrap(),
+            None
+        );
+        assert_eq!(
+            PeerDB::get_peer_host(db.conn(), &host_2.to_peer_address()).unwrap(),
+            Some(host_2.clone())
+        );
+
+        // names of peers that never make it into the frontier are capped, oldest first, but
+        // names of peers in the frontier are kept
+        let fake_hosts: Vec<_> = (0..(MAX_UNSLOTTED_PEER_HOSTS + 10))
+            .map(|i| PeerHostName::try_from(format!("fake{}.onion", i).as_str()).unwrap())
+            .collect();
+        {
+            let mut tx = db.tx_begin().unwrap();
+            for host in fake_hosts.iter() {
+                PeerDB::set_peer_host(&mut tx, host).unwrap();
+            }
+            tx.commit().unwrap();
+        }
+        assert_eq!(
+            query_count(db.conn(), "SELECT COUNT(*) FROM peer_hosts", NO_PARAMS).unwrap(),
+            (MAX_UNSLOTTED_PEER_HOSTS + 1) as i64
+        );
+        assert_eq!(
+            PeerDB::get_peer_host(db.conn(), &host_2.to_peer_address()).unwrap(),
+            Some(host_2)
+        );
+        for (i, host) in fake_hosts.iter().enumerate() {
+            let expected = if i < 10 { None } else { Some(host.clone()) };
+            assert_eq!(
+                PeerDB::get_peer_host(db.conn(), &host.to_peer_address()).unwrap(),
+                expected
+            );
+        }
+    }
+
     #[test]
     fn test_peer_deny_allow_cidr() {
         let neighbor_1 = Neighbor {
@@ -25,7 +25,9 @@ use std::sync::mpsc::SyncSender;
 use std::sync::mpsc::TryRecvError;
 use std::sync::mpsc::TrySendError;
 
+use std::convert::TryFrom;
 use std::hash::{Hash, Hasher};
+use std::net::IpAddr;
 use std::net::ToSocketAddrs;
 
 use crate::net::asn::ASEntry4;
@@ -33,6 +35,7 @@ use crate::net::Error as net_error;
 use crate::net::Neighbor;
 use crate::net::NeighborKey;
 use crate::net::PeerAddress;
+use crate::net::PeerHostName;
 
 use crate::net::codec::*;
 use crate::net::*;
@@ -120,6 +123,9 @@ pub struct DNSResolver {
     outbound: SyncSender<DNSResponse>,
     max_inflight: u64,
 
+    // if set, names are left for the SOCKS5 proxy to resolve
+    proxy_resolution: bool,
+
     // used mainly for testing
     hardcoded: HashMap<(String, u16), Vec<SocketAddr>>,
 }
@@ -144,11 +150,30 @@ impl DNSResolver {
             inbound: socket_chan_rx,
             outbound: dns_chan_tx,
             max_inflight: max_inflight,
+            proxy_resolution: false,
             hardcoded: HashMap::new(),
         };
         (resolver, client)
     }
 
+    /// When all connections go through a SOCKS5 proxy, don't look names up locally (that would
+    /// leak them to our DNS server, and `.onion` names don't resolve anyway).  Instead, resolve
+    /// each name to its synthetic address, and let the proxy resolve the name when we connect.
+    pub fn set_proxy_resolution(&mut self, enabled: bool) {
+        self.proxy_resolution = enabled;
+    }
+
+    /// "Resolve" a name for a connection through the SOCKS5 proxy, which does the real lookup.
+    /// IP addresses resolve to themselves, and names to their synthetic addresses.
+    pub fn resolve_for_proxy(host: &str, port: u16) -> Result<Vec<SocketAddr>, String> {
+        if let Ok(ip) = host.parse::<IpAddr>() {
+            return Ok(vec![SocketAddr::new(ip, port)]);
+        }
+        let host =
+            PeerHostName::try_from(host).map_err(|e| format!("DNS resolve error: {:?}", &e))?;
+        Ok(vec![host.to_peer_address().to_socketaddr(port)])
+    }
+
     pub fn add_hardcoded(&mut self, host: &str, port: u16, addrs: Vec<SocketAddr>) -> () {
         self.hardcoded.insert((host.to_string(), port), addrs);
     }
@@ -158,6 +183,11 @@ impl DNSResolver {
             return DNSResponse::new(req, Ok(addrs.to_vec()));
         }
 
+        if self.proxy_resolution {
+            let result = DNSResolver::resolve_for_proxy(&req.host, req.port);
+            return DNSResponse::new(req, result);
+        }
+
         // TODO: this is a blocking operation, but there's not really a good solution here other
         // than to just do this in a separate thread :shrug:
         test_debug!("Resolve {}:{}", &req.host, req.port);
@@ -378,11 +408,42 @@ impl DNSClient {
 
 #[cfg(test)]
 mod test {
+    use super::*;
     use crate::net::test::*;
     use stacks_common::util::*;
     use std::collections::HashMap;
     use std::error::Error;
 
+    #[test]
+    fn dns_resolve_via_proxy() {
+        let (mut resolver, _client) = DNSResolver::new(10);
+        resolver.set_proxy_resolution(true);
+        let deadline = get_epoch_time_ms() + 120_000;
+
+        // names resolve to their synthetic addresses, without a lookup
+        let resp = resolver.resolve(DNSRequest::new("Abc.Onion".to_string(), 20443, deadline));
+        let addrs = resp.result.unwrap();
+        assert_eq!(addrs.len(), 1);
+        assert_eq!(addrs[0].port(), 20443);
+        assert_eq!(
+            PeerAddress::from_socketaddr(&addrs[0]),
+            PeerHostName::try_from("abc.onion")
+                .unwrap()
+                .to_peer_address()
+        );
+
+        // IP addresses resolve to themselves
+        let resp = resolver.resolve(DNSRequest::new("1.2.3.4".to_string(), 80, deadline));
+        assert_eq!(
+            resp.result.unwrap(),
+            vec!["1.2.3.4:80".parse::<SocketAddr>().unwrap()]
+        );
+
+        // bad names still fail
+        let resp = resolver.resolve(DNSRequest::new("no_such.name".to_string(), 80, deadline));
+        assert!(resp.result.is_err());
+    }
+
     #[test]
     fn dns_start_stop() {
         let (client, thread_handle) = dns_thread_start(100);
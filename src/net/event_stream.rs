@@ -0,0 +1,273 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2022 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io;
+use std::io::Write;
+use std::str::FromStr;
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TryRecvError, TrySendError};
+use std::sync::{Arc, Mutex};
+
+use stacks_common::util::get_epoch_time_secs;
+
+/// How often to write a comment line to an idle event stream, so that neither the client nor
+/// the RPC server closes the connection as idle.
+pub const EVENT_STREAM_HEARTBEAT_SECS: u64 = 5;
+
+/// The kinds of chain events a client can stream.  Each kind is named after the event observer
+/// path whose payload it carries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChainEventKind {
+    Block,
+    Microblocks,
+    BurnBlock,
+    MempoolTxs,
+    DroppedMempoolTxs,
+}
+
+impl ChainEventKind {
+    pub const ALL: [ChainEventKind; 5] = [
+        ChainEventKind::Block,
+        ChainEventKind::Microblocks,
+        ChainEventKind::BurnBlock,
+        ChainEventKind::MempoolTxs,
+        ChainEventKind::DroppedMempoolTxs,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            ChainEventKind::Block => "new_block",
+            ChainEventKind::Microblocks => "new_microblocks",
+            ChainEventKind::BurnBlock => "new_burn_block",
+            ChainEventKind::MempoolTxs => "new_mempool_tx",
+            ChainEventKind::DroppedMempoolTxs => "drop_mempool_tx",
+        }
+    }
+}
+
+impl FromStr for ChainEventKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<ChainEventKind, String> {
+        ChainEventKind::ALL
+            .iter()
+            .find(|kind| kind.as_str() == s)
+            .cloned()
+            .ok_or(format!("Unknown chain event kind: {}", s))
+    }
+}
+
+struct EventStreamSubscriber {
+    kinds: Vec<ChainEventKind>,
+    frames: SyncSender<Arc<String>>,
+}
+
+/// Fans chain events out to the RPC clients that are streaming them.  Clones share the same set
+/// of subscribers, so the node's event dispatcher can publish to the hub that its RPC server
+/// subscribes clients to.
+#[derive(Clone)]
+pub struct EventStreamHub {
+    subscribers: Arc<Mutex<Vec<EventStreamSubscriber>>>,
+    /// Maximum number of clients that can stream events at once
+    max_subscribers: usize,
+    /// Maximum number of events queued for a client before it is disconnected
+    max_pending: usize,
+}
+
+/// A client's end of an event stream.  Events are written out as server-sent events
+/// (`text/event-stream`): one `event:` line naming the kind of event, and one `data:` line with
+/// its JSON payload.
+pub struct EventStreamSubscription {
+    frames: Receiver<Arc<String>>,
+    last_write: u64,
+}
+
+impl EventStreamHub {
+    pub fn new(max_subscribers: usize, max_pending: usize) -> EventStreamHub {
+        EventStreamHub {
+            subscribers: Arc::new(Mutex::new(vec![])),
+            max_subscribers,
+            max_pending,
+        }
+    }
+
+    /// Subscribe to the given kinds of events.
+    /// Returns None if there are already too many subscribers.
+    pub fn subscribe(&self, kinds: Vec<ChainEventKind>) -> Option<EventStreamSubscription> {
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .expect("FATAL: event stream lock poisoned");
+        if subscribers.len() >= self.max_subscribers {
+            return None;
+        }
+
+        let (frames_tx, frames_rx) = sync_channel(self.max_pending);
+        subscribers.push(EventStreamSubscriber {
+            kinds,
+            frames: frames_tx,
+        });
+        Some(EventStreamSubscription {
+            frames: frames_rx,
+            last_write: get_epoch_time_secs(),
+        })
+    }
+
+    /// Is anyone streaming this kind of event?  Lets callers skip building payloads nobody will
+    /// receive.
+    pub fn has_subscribers(&self, kind: ChainEventKind) -> bool {
+        self.subscribers
+            .lock()
+            .expect("FATAL: event stream lock poisoned")
+            .iter()
+            .any(|subscriber| subscriber.kinds.contains(&kind))
+    }
+
+    pub fn num_subscribers(&self) -> usize {
+        self.subscribers
+            .lock()
+            .expect("FATAL: event stream lock poisoned")
+            .len()
+    }
+
+    /// Send an event to each of its subscribers.  A subscriber that has fallen `max_pending`
+    /// events behind is dropped, which ends its stream; subscribers that went away are forgotten.
+    pub fn publish(&self, kind: ChainEventKind, payload: &serde_json::Value) {
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .expect("FATAL: event stream lock poisoned");
+        if !subscribers.iter().any(|s| s.kinds.contains(&kind)) {
+            return;
+        }
+
+        let frame = Arc::new(format!("event: {}\ndata: {}\n\n", kind.as_str(), payload));
+        subscribers.retain(|subscriber| {
+            if !subscriber.kinds.contains(&kind) {
+                return true;
+            }
+            match subscriber.frames.try_send(frame.clone()) {
+                Ok(_) => true,
+                Err(TrySendError::Full(_)) => {
+                    warn!("Event stream client is too far behind; closing its stream";
+                          "event" => kind.as_str());
+                    false
+                }
+                Err(TrySendError::Disconnected(_)) => false,
+            }
+        });
+    }
+}
+
+impl EventStreamSubscription {
+    /// Write out all events that are ready (or a heartbeat, if there has been nothing to write
+    /// for a while).
+    /// Returns Ok(false) once the stream has ended, after writing its last line.
+    pub fn stream_to<W: Write>(&mut self, fd: &mut W) -> Result<bool, io::Error> {
+        let now = get_epoch_time_secs();
+        loop {
+            match self.frames.try_recv() {
+                Ok(frame) => {
+                    fd.write_all(frame.as_bytes())?;
+                    self.last_write = now;
+                }
+                Err(TryRecvError::Empty) => {
+                    if self.last_write + EVENT_STREAM_HEARTBEAT_SECS <= now {
+                        fd.write_all(b": heartbeat\n\n")?;
+                        self.last_write = now;
+                    }
+                    return Ok(true);
+                }
+                Err(TryRecvError::Disconnected) => {
+                    fd.write_all(b": end of stream\n\n")?;
+                    return Ok(false);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn read_all(subscription: &mut EventStreamSubscription) -> (String, bool) {
+        let mut buf = vec![];
+        let open = subscription.stream_to(&mut buf).unwrap();
+        (String::from_utf8(buf).unwrap(), open)
+    }
+
+    #[test]
+    fn test_chain_event_kind_names() {
+        for kind in ChainEventKind::ALL.iter() {
+            assert_eq!(ChainEventKind::from_str(kind.as_str()), Ok(*kind));
+        }
+        assert!(ChainEventKind::from_str("new_blocks").is_err());
+    }
+
+    #[test]
+    fn test_event_stream_fan_out() {
+        let hub = EventStreamHub::new(2, 10);
+        let mut blocks = hub.subscribe(vec![ChainEventKind::Block]).unwrap();
+        let mut everything = hub.subscribe(ChainEventKind::ALL.to_vec()).unwrap();
+        assert!(hub.subscribe(vec![ChainEventKind::Block]).is_none());
+
+        assert!(hub.has_subscribers(ChainEventKind::Block));
+        hub.publish(ChainEventKind::Block, &json!({"block_height": 1}));
+        hub.publish(ChainEventKind::MempoolTxs, &json!(["0x00"]));
+
+        assert_eq!(
+            read_all(&mut blocks),
+            (
+                "event: new_block\ndata: {\"block_height\":1}\n\n".to_string(),
+                true
+            )
+        );
+        assert_eq!(
+            read_all(&mut everything),
+            (
+                "event: new_block\ndata: {\"block_height\":1}\n\nevent: new_mempool_tx\ndata: [\"0x00\"]\n\n"
+                    .to_string(),
+                true
+            )
+        );
+
+        // subscribers that went away make room for new ones
+        drop(blocks);
+        hub.publish(ChainEventKind::Block, &json!({"block_height": 2}));
+        assert_eq!(hub.num_subscribers(), 1);
+        assert!(hub.subscribe(vec![ChainEventKind::Block]).is_some());
+    }
+
+    #[test]
+    fn test_event_stream_drops_slow_subscribers() {
+        let hub = EventStreamHub::new(1, 2);
+        let mut subscription = hub.subscribe(vec![ChainEventKind::BurnBlock]).unwrap();
+        for height in 0..3 {
+            hub.publish(
+                ChainEventKind::BurnBlock,
+                &json!({ "burn_block_height": height }),
+            );
+        }
+        assert_eq!(hub.num_subscribers(), 0);
+        assert!(!hub.has_subscribers(ChainEventKind::BurnBlock));
+
+        // the queued events are still delivered before the stream ends
+        let (written, open) = read_all(&mut subscription);
+        assert!(!open);
+        assert!(written.starts_with("event: new_burn_block\ndata: {\"burn_block_height\":0}\n\n"));
+        assert!(written.ends_with(": end of stream\n\n"));
+    }
+}
@@ -17,6 +17,7 @@
  along with Blockstack. If not, see <http://www.gnu.org/licenses/>.
 */
 
+use std::cmp;
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::fmt;
@@ -30,10 +31,12 @@ use std::str::FromStr;
 use std::time::SystemTime;
 
 use clarity::vm::representations::MAX_STRING_LEN;
+use hmac::{Hmac, Mac};
 use percent_encoding::percent_decode_str;
 use regex::{Captures, Regex};
 use serde::{Deserialize, Serialize};
 use serde_json;
+use sha2::Sha256;
 use time;
 use url::{form_urlencoded, Url};
 
@@ -42,8 +45,10 @@ use crate::chainstate::burn::ConsensusHash;
 use crate::chainstate::stacks::{
     StacksBlock, StacksMicroblock, StacksPublicKey, StacksTransaction,
 };
+use crate::core::mempool::PendingTxQuery;
 use crate::deps::httparse;
 use crate::net::atlas::Attachment;
+use crate::net::event_stream::ChainEventKind;
 use crate::net::ClientError;
 use crate::net::Error as net_error;
 use crate::net::Error::ClarityError;
@@ -61,9 +66,11 @@ use crate::net::MessageSequence;
 use crate::net::NeighborAddress;
 use crate::net::PeerAddress;
 use crate::net::PeerHost;
+use crate::net::PendingTransactionsResponse;
 use crate::net::ProtocolFamily;
 use crate::net::StacksHttpMessage;
 use crate::net::StacksHttpPreamble;
+use crate::net::TransactionReplacementsResponse;
 use crate::net::UnconfirmedTransactionResponse;
 use crate::net::UnconfirmedTransactionStatus;
 use crate::net::HTTP_PREAMBLE_MAX_ENCODED_SIZE;
@@ -73,8 +80,9 @@ use crate::net::MAX_HEADERS;
 use crate::net::MAX_MICROBLOCKS_UNCONFIRMED;
 use crate::net::{CallReadOnlyRequestBody, TipRequest};
 use crate::net::{GetAttachmentResponse, GetAttachmentsInvResponse, PostTransactionRequestBody};
+use crate::net::{DEFAULT_PENDING_TXS_PAGE_SIZE, MAX_PENDING_TXS_PAGE_SIZE};
 use clarity::vm::ast::parser::v1::CLARITY_NAME_REGEX;
-use clarity::vm::types::{StandardPrincipalData, TraitIdentifier};
+use clarity::vm::types::{QualifiedContractIdentifier, StandardPrincipalData, TraitIdentifier};
 use clarity::vm::{
     representations::{
         CONTRACT_NAME_REGEX_STRING, PRINCIPAL_DATA_REGEX_STRING, STANDARD_PRINCIPAL_REGEX_STRING,
@@ -85,6 +93,7 @@ use clarity::vm::{
 use stacks_common::util::hash::hex_bytes;
 use stacks_common::util::hash::to_hex;
 use stacks_common::util::hash::Hash160;
+use stacks_common::util::hash::Sha256Sum;
 use stacks_common::util::log;
 use stacks_common::util::retry::BoundReader;
 use stacks_common::util::retry::RetryReader;
@@ -98,6 +107,8 @@ use crate::codec::{
 use crate::types::chainstate::{BlockHeaderHash, StacksAddress, StacksBlockId};
 
 use super::FeeRateEstimateRequestBody;
+use super::PeerAdminAction;
+use super::PeerAdminRequestBody;
 
 lazy_static! {
     static ref PATH_GETINFO: Regex = Regex::new(r#"^/v2/info$"#).unwrap();
@@ -113,8 +124,15 @@ lazy_static! {
         Regex::new(r#"^/v2/microblocks/unconfirmed/([0-9a-f]{64})/([0-9]{1,5})$"#).unwrap();
     static ref PATH_GETTRANSACTION_UNCONFIRMED: Regex =
         Regex::new(r#"^/v2/transactions/unconfirmed/([0-9a-f]{64})$"#).unwrap();
+    static ref PATH_GETTRANSACTION_REPLACEMENTS: Regex =
+        Regex::new(r#"^/v2/transactions/([0-9a-f]{64})/replacements$"#).unwrap();
     static ref PATH_POSTTRANSACTION: Regex = Regex::new(r#"^/v2/transactions$"#).unwrap();
+    static ref PATH_POST_SIMULATE_TRANSACTION: Regex =
+        Regex::new(r#"^/v2/transactions/simulate$"#).unwrap();
     static ref PATH_POST_FEE_RATE_ESIMATE: Regex = Regex::new(r#"^/v2/fees/transaction$"#).unwrap();
+    static ref PATH_GET_ADMIN_PEERS: Regex = Regex::new(r#"^/v2/admin/peers$"#).unwrap();
+    static ref PATH_POST_ADMIN_PEER_ACTION: Regex =
+        Regex::new(r#"^/v2/admin/peers/(ban|allow|connect|disconnect)$"#).unwrap();
     static ref PATH_POSTBLOCK: Regex = Regex::new(r#"^/v2/blocks/upload/([0-9a-f]{40})$"#).unwrap();
     static ref PATH_POSTMICROBLOCK: Regex = Regex::new(r#"^/v2/microblocks$"#).unwrap();
     static ref PATH_GET_ACCOUNT: Regex = Regex::new(&format!(
@@ -158,6 +176,17 @@ lazy_static! {
         Regex::new(r#"^/v2/attachments/([0-9a-f]{40})$"#).unwrap();
     static ref PATH_POST_MEMPOOL_QUERY: Regex =
         Regex::new(r#"^/v2/mempool/query$"#).unwrap();
+    static ref PATH_GET_MEMPOOL_ADDRESS_TXS: Regex = Regex::new(&format!(
+        "^/v2/mempool/address/(?P<address>{})$",
+        *STANDARD_PRINCIPAL_REGEX_STRING
+    ))
+    .unwrap();
+    static ref PATH_GET_MEMPOOL_CONTRACT_TXS: Regex = Regex::new(&format!(
+        "^/v2/mempool/contract/(?P<address>{})/(?P<contract>{})$",
+        *STANDARD_PRINCIPAL_REGEX_STRING, *CONTRACT_NAME_REGEX_STRING
+    ))
+    .unwrap();
+    static ref PATH_GET_EVENT_STREAM: Regex = Regex::new(r#"^/v2/events$"#).unwrap();
     static ref PATH_OPTIONS_WILDCARD: Regex = Regex::new("^/v2/.{0,4096}$").unwrap();
 }
 
@@ -641,6 +670,15 @@ impl<'a, 'state, W: Write> HttpChunkedTransferWriter<'a, 'state, W> {
         to_copy
     }
 
+    /// Send out any buffered chunk data, without ending the response.  Used for responses whose
+    /// data is sent as it becomes available.
+    pub fn flush_buffered(&mut self) -> io::Result<()> {
+        if !self.state.corked && !self.state.chunk_buf.is_empty() {
+            self.flush_chunk()?;
+        }
+        Ok(())
+    }
+
     pub fn cork(&mut self) -> () {
         // block future flushes from sending trailing empty chunks -- we're done sending
         self.state.corked = true;
@@ -877,7 +915,7 @@ fn empty_headers<W: Write>(_fd: &mut W) -> Result<(), codec_error> {
     Ok(())
 }
 
-fn stacks_height_headers<W: Write>(
+fn request_metadata_headers<W: Write>(
     fd: &mut W,
     md: &HttpRequestMetadata,
 ) -> Result<(), codec_error> {
@@ -888,6 +926,10 @@ fn stacks_height_headers<W: Write>(
         }
         _ => {}
     }
+    if let Some(ref authorization) = md.authorization {
+        fd.write_all(format!("authorization: {}\r\n", authorization).as_bytes())
+            .map_err(codec_error::WriteError)?;
+    }
     Ok(())
 }
 
@@ -1450,6 +1492,272 @@ impl StacksMessageCodec for HttpResponsePreamble {
     }
 }
 
+/// How far the timestamp of a HMAC-signed request may be from our clock, in seconds.  Signed
+/// requests carry no nonce, so a captured one can be replayed for this long.
+pub const RPC_AUTH_MAX_CLOCK_SKEW: u64 = 300;
+
+/// `Authorization` scheme for HMAC-signed requests
+const HMAC_AUTH_SCHEME: &str = "HMAC-SHA256";
+
+/// The classes of RPC endpoints that a client can be authorized to use
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RPCScope {
+    /// Reading chain state, the mempool, and node info
+    PublicRead,
+    /// Read-only contract calls, which run Clarity code
+    ReadOnlyCall,
+    /// Transaction simulation, which runs Clarity code against a chain tip's MARF
+    Simulate,
+    /// Posting transactions
+    PostTransaction,
+    /// Uploading blocks and microblocks
+    PostBlock,
+    /// The `/v2/admin` endpoints
+    Admin,
+}
+
+impl RPCScope {
+    pub const ALL: [RPCScope; 6] = [
+        RPCScope::PublicRead,
+        RPCScope::ReadOnlyCall,
+        RPCScope::Simulate,
+        RPCScope::PostTransaction,
+        RPCScope::PostBlock,
+        RPCScope::Admin,
+    ];
+
+    /// Every scope but `Simulate` and `Admin`, which is what clients without a token get by
+    /// default
+    pub fn public_default() -> HashSet<RPCScope> {
+        RPCScope::ALL
+            .iter()
+            .filter(|scope| !matches!(scope, RPCScope::Simulate | RPCScope::Admin))
+            .cloned()
+            .collect()
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RPCScope::PublicRead => "read",
+            RPCScope::ReadOnlyCall => "call_read",
+            RPCScope::Simulate => "simulate",
+            RPCScope::PostTransaction => "post_tx",
+            RPCScope::PostBlock => "post_block",
+            RPCScope::Admin => "admin",
+        }
+    }
+}
+
+impl FromStr for RPCScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<RPCScope, String> {
+        RPCScope::ALL
+            .iter()
+            .find(|scope| scope.as_str() == s)
+            .cloned()
+            .ok_or_else(|| format!("unknown RPC scope '{}'", s))
+    }
+}
+
+impl fmt::Display for RPCScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A secret that grants RPC scopes beyond the public ones.  Clients present it either as
+/// `Authorization: Bearer <secret>`, or by signing the request with it as
+/// `Authorization: HMAC-SHA256 <name>:<unix time>:<hex signature>` (see `RPCAuthToken::sign`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RPCAuthToken {
+    /// Identifies the token in signed requests and in logs
+    pub name: String,
+    pub secret: String,
+    pub scopes: HashSet<RPCScope>,
+}
+
+impl RPCAuthToken {
+    pub fn new(name: &str, secret: &str, scopes: &[RPCScope]) -> RPCAuthToken {
+        RPCAuthToken {
+            name: name.to_string(),
+            secret: secret.to_string(),
+            scopes: scopes.iter().cloned().collect(),
+        }
+    }
+
+    /// `Authorization` header value that presents this token's secret
+    pub fn bearer(&self) -> String {
+        format!("Bearer {}", self.secret)
+    }
+
+    /// `Authorization` header value that signs a request with this token at `timestamp`.  The
+    /// signature covers the timestamp, the method, the path and query, and the body.
+    pub fn sign(&self, verb: &str, path: &str, body: &[u8], timestamp: u64) -> String {
+        let mac = self.hmac(timestamp, &rpc_auth_signed_content(verb, path, body));
+        format!(
+            "{} {}:{}:{}",
+            HMAC_AUTH_SCHEME,
+            &self.name,
+            timestamp,
+            to_hex(&mac.finalize().into_bytes())
+        )
+    }
+
+    fn hmac(&self, timestamp: u64, signed_content: &str) -> Hmac<Sha256> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes())
+            .expect("FATAL: HMAC accepts keys of any length");
+        mac.update(format!("{}\n{}", timestamp, signed_content).as_bytes());
+        mac
+    }
+}
+
+/// What a HMAC signature covers besides its timestamp
+pub fn rpc_auth_signed_content(verb: &str, path: &str, body: &[u8]) -> String {
+    format!(
+        "{}\n{}\n{}",
+        verb,
+        path,
+        Sha256Sum::from_data(body).to_hex()
+    )
+}
+
+fn is_hmac_authorization(authorization: &str) -> bool {
+    authorization
+        .get(..HMAC_AUTH_SCHEME.len() + 1)
+        .map(|prefix| prefix.eq_ignore_ascii_case(&format!("{} ", HMAC_AUTH_SCHEME)))
+        .unwrap_or(false)
+}
+
+/// Compare two byte strings in time that depends only on their lengths, so a secret can't be
+/// guessed byte-by-byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b.iter())
+            .fold(0u8, |acc, (x, y)| acc | (x ^ y))
+            == 0
+}
+
+/// Why `authorize_request` refused a request
+#[derive(Debug, Clone, PartialEq)]
+pub enum RPCAuthError {
+    /// No configured token grants the scope, so its endpoints are disabled
+    Disabled(RPCScope),
+    /// The scope needs a token, and the request did not present one
+    Unauthenticated(RPCScope),
+    /// The `Authorization` header is malformed or does not verify
+    BadCredentials(String),
+    /// The token (named here) is valid but does not grant the scope
+    Forbidden(String, RPCScope),
+}
+
+impl fmt::Display for RPCAuthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RPCAuthError::Disabled(scope) => write!(f, "The '{}' endpoints are disabled", scope),
+            RPCAuthError::Unauthenticated(scope) => {
+                write!(f, "The '{}' endpoints need an authorization token", scope)
+            }
+            RPCAuthError::BadCredentials(msg) => write!(f, "{}", msg),
+            RPCAuthError::Forbidden(name, scope) => {
+                write!(f, "Token '{}' does not grant the '{}' scope", name, scope)
+            }
+        }
+    }
+}
+
+/// Check that whoever sent `req` may use the endpoint it is for: either the endpoint's scope is
+/// public, or the request carries a token that grants it.  A request that presents a token must
+/// present a valid one, whatever its endpoint.  Returns the name of the token, if one was given.
+pub fn authorize_request(
+    req: &HttpRequestType,
+    public_scopes: &HashSet<RPCScope>,
+    tokens: &[RPCAuthToken],
+    now: u64,
+) -> Result<Option<String>, RPCAuthError> {
+    let scope = req.required_scope();
+    let md = req.metadata();
+    let token = match md.authorization {
+        Some(ref authorization) => Some(authenticate(
+            authorization,
+            md.signed_content.as_deref(),
+            tokens,
+            now,
+        )?),
+        None => None,
+    };
+
+    if public_scopes.contains(&scope) {
+        return Ok(token.map(|t| t.name.clone()));
+    }
+    match token {
+        Some(token) if token.scopes.contains(&scope) => Ok(Some(token.name.clone())),
+        Some(token) => Err(RPCAuthError::Forbidden(token.name.clone(), scope)),
+        None if tokens.iter().any(|t| t.scopes.contains(&scope)) => {
+            Err(RPCAuthError::Unauthenticated(scope))
+        }
+        None => Err(RPCAuthError::Disabled(scope)),
+    }
+}
+
+/// Find the token that an `Authorization` header presents
+fn authenticate<'a>(
+    authorization: &str,
+    signed_content: Option<&str>,
+    tokens: &'a [RPCAuthToken],
+    now: u64,
+) -> Result<&'a RPCAuthToken, RPCAuthError> {
+    let bad_credentials = |msg: &str| RPCAuthError::BadCredentials(msg.to_string());
+    let (scheme, credentials) = authorization
+        .split_once(' ')
+        .ok_or_else(|| bad_credentials("Malformed Authorization header"))?;
+
+    if scheme.eq_ignore_ascii_case("Bearer") {
+        // check every token, so the time taken doesn't depend on which one matched
+        let mut found = None;
+        for token in tokens.iter() {
+            if constant_time_eq(token.secret.as_bytes(), credentials.as_bytes()) {
+                found = Some(token);
+            }
+        }
+        found.ok_or_else(|| bad_credentials("Invalid bearer token"))
+    } else if scheme.eq_ignore_ascii_case(HMAC_AUTH_SCHEME) {
+        let mut parts = credentials.splitn(3, ':');
+        let (name, timestamp, signature) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(name), Some(timestamp), Some(signature)) => (name, timestamp, signature),
+            _ => {
+                return Err(bad_credentials(
+                    "Malformed HMAC credentials; expected <name>:<timestamp>:<signature>",
+                ));
+            }
+        };
+        let timestamp = timestamp
+            .parse::<u64>()
+            .map_err(|_| bad_credentials("Malformed HMAC timestamp"))?;
+        if timestamp.max(now) - timestamp.min(now) > RPC_AUTH_MAX_CLOCK_SKEW {
+            return Err(bad_credentials(
+                "HMAC timestamp is too far from the server's clock",
+            ));
+        }
+        let signature =
+            hex_bytes(signature).map_err(|_| bad_credentials("Malformed HMAC signature"))?;
+        let signed_content =
+            signed_content.ok_or_else(|| bad_credentials("Invalid HMAC credentials"))?;
+        let token = tokens
+            .iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| bad_credentials("Invalid HMAC credentials"))?;
+        token
+            .hmac(timestamp, signed_content)
+            .verify_slice(&signature)
+            .map_err(|_| bad_credentials("Invalid HMAC credentials"))?;
+        Ok(token)
+    } else {
+        Err(bad_credentials("Unsupported authorization scheme"))
+    }
+}
+
 impl HttpRequestType {
     fn try_parse<R: Read, F>(
         protocol: &mut StacksHttp,
@@ -1526,6 +1834,11 @@ impl HttpRequestType {
                 &PATH_GETTRANSACTION_UNCONFIRMED,
                 &HttpRequestType::parse_gettransaction_unconfirmed,
             ),
+            (
+                "GET",
+                &PATH_GETTRANSACTION_REPLACEMENTS,
+                &HttpRequestType::parse_gettransaction_replacements,
+            ),
             (
                 "POST",
                 &PATH_POST_FEE_RATE_ESIMATE,
@@ -1536,6 +1849,21 @@ impl HttpRequestType {
                 &PATH_POSTTRANSACTION,
                 &HttpRequestType::parse_posttransaction,
             ),
+            (
+                "POST",
+                &PATH_POST_SIMULATE_TRANSACTION,
+                &HttpRequestType::parse_simulate_transaction,
+            ),
+            (
+                "GET",
+                &PATH_GET_ADMIN_PEERS,
+                &HttpRequestType::parse_get_admin_peers,
+            ),
+            (
+                "POST",
+                &PATH_POST_ADMIN_PEER_ACTION,
+                &HttpRequestType::parse_post_admin_peer_action,
+            ),
             ("POST", &PATH_POSTBLOCK, &HttpRequestType::parse_postblock),
             (
                 "POST",
@@ -1602,6 +1930,21 @@ impl HttpRequestType {
                 &PATH_POST_MEMPOOL_QUERY,
                 &HttpRequestType::parse_post_mempool_query,
             ),
+            (
+                "GET",
+                &PATH_GET_MEMPOOL_ADDRESS_TXS,
+                &HttpRequestType::parse_get_mempool_address_txs,
+            ),
+            (
+                "GET",
+                &PATH_GET_MEMPOOL_CONTRACT_TXS,
+                &HttpRequestType::parse_get_mempool_contract_txs,
+            ),
+            (
+                "GET",
+                &PATH_GET_EVENT_STREAM,
+                &HttpRequestType::parse_get_event_stream,
+            ),
         ];
 
         // use url::Url to parse path and query string
@@ -2257,6 +2600,36 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_gettransaction_replacements<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        regex: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetTransactionReplacements"
+                    .to_string(),
+            ));
+        }
+
+        let txid_hex = regex
+            .get(1)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to txid group".to_string(),
+            ))?
+            .as_str();
+
+        let txid = Txid::from_hex(txid_hex)
+            .map_err(|_e| net_error::DeserializeError("Failed to decode txid hex".to_string()))?;
+
+        Ok(HttpRequestType::GetTransactionReplacements(
+            HttpRequestMetadata::from_preamble(preamble),
+            txid,
+        ))
+    }
+
     fn parse_post_fee_rate_estimate<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -2521,6 +2894,124 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_simulate_transaction<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() == 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected non-zero-length body for SimulateTransaction"
+                    .to_string(),
+            ));
+        }
+
+        if preamble.get_content_length() > MAX_PAYLOAD_LEN {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: SimulateTransaction body is too big".to_string(),
+            ));
+        }
+
+        // content-type must be given, and must be application/octet-stream
+        match preamble.content_type {
+            None => {
+                return Err(net_error::DeserializeError(
+                    "Missing Content-Type for transaction".to_string(),
+                ));
+            }
+            Some(ref c) => {
+                if *c != HttpContentType::Bytes {
+                    return Err(net_error::DeserializeError(
+                        "Wrong Content-Type for transaction; expected application/octet-stream"
+                            .to_string(),
+                    ));
+                }
+            }
+        };
+
+        let mut bound_fd = BoundReader::from_reader(fd, preamble.get_content_length() as u64);
+
+        let tx = StacksTransaction::consensus_deserialize(&mut bound_fd).map_err(|e| {
+            if let codec_error::DeserializeError(msg) = e {
+                net_error::ClientError(ClientError::Message(format!(
+                    "Failed to deserialize simulated transaction: {}",
+                    msg
+                )))
+            } else {
+                e.into()
+            }
+        })?;
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::SimulateTransaction(
+            HttpRequestMetadata::from_preamble(preamble),
+            tx,
+            tip,
+        ))
+    }
+
+    fn parse_get_admin_peers<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _regex: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetAdminPeers".to_string(),
+            ));
+        }
+
+        Ok(HttpRequestType::GetAdminPeers(
+            HttpRequestMetadata::from_preamble(preamble),
+        ))
+    }
+
+    fn parse_post_admin_peer_action<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        regex: &Captures,
+        _query: Option<&str>,
+        fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let content_len = preamble.get_content_length();
+        if !(content_len > 0 && content_len < MAX_PAYLOAD_LEN) {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid Http request: invalid body length for PostAdminPeerAction ({})",
+                content_len
+            )));
+        }
+
+        if preamble.content_type != Some(HttpContentType::JSON) {
+            return Err(net_error::DeserializeError(
+                "Invalid content-type: expected application/json".to_string(),
+            ));
+        }
+
+        let action_name = regex
+            .get(1)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to peer admin action".to_string(),
+            ))?
+            .as_str();
+
+        let bound_fd = BoundReader::from_reader(fd, content_len as u64);
+        let body: PeerAdminRequestBody = serde_json::from_reader(bound_fd).map_err(|e| {
+            net_error::DeserializeError(format!("Failed to parse JSON body: {}", e))
+        })?;
+
+        let action = PeerAdminAction::from_request_body(action_name, &body)
+            .map_err(|msg| net_error::ClientError(ClientError::Message(msg)))?;
+
+        Ok(HttpRequestType::PostAdminPeerAction(
+            HttpRequestMetadata::from_preamble(preamble),
+            action,
+        ))
+    }
+
     fn parse_get_attachment<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -2664,6 +3155,152 @@ impl HttpRequestType {
         ))
     }
 
+    /// Get the `offset` and `limit` of a page of pending transactions from a query string.  The
+    /// limit is capped at `MAX_PENDING_TXS_PAGE_SIZE`.
+    fn get_pending_txs_page_query(query: Option<&str>) -> Result<(u64, u64), net_error> {
+        let mut offset = 0;
+        let mut limit = DEFAULT_PENDING_TXS_PAGE_SIZE;
+        if let Some(query) = query {
+            for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+                if key == "offset" {
+                    offset = value.parse::<u64>().map_err(|_e| {
+                        net_error::DeserializeError("Failed to parse offset".to_string())
+                    })?;
+                } else if key == "limit" {
+                    limit = value.parse::<u64>().map_err(|_e| {
+                        net_error::DeserializeError("Failed to parse limit".to_string())
+                    })?;
+                }
+            }
+        }
+        Ok((offset, cmp::min(limit, MAX_PENDING_TXS_PAGE_SIZE)))
+    }
+
+    fn parse_get_mempool_address_txs<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+
+        let address = StacksAddress::from_string(&captures["address"])
+            .ok_or_else(|| net_error::DeserializeError("Failed to parse address".into()))?;
+
+        let mut is_origin = None;
+        let mut min_nonce = 0;
+        let mut max_nonce = u64::MAX;
+        if let Some(query) = query {
+            for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+                if key == "role" {
+                    is_origin = match value.as_ref() {
+                        "origin" => Some(true),
+                        "sponsor" => Some(false),
+                        _ => {
+                            return Err(net_error::DeserializeError(
+                                "Invalid role: expected 'origin' or 'sponsor'".to_string(),
+                            ));
+                        }
+                    };
+                } else if key == "min_nonce" {
+                    min_nonce = value.parse::<u64>().map_err(|_e| {
+                        net_error::DeserializeError("Failed to parse min_nonce".to_string())
+                    })?;
+                } else if key == "max_nonce" {
+                    max_nonce = value.parse::<u64>().map_err(|_e| {
+                        net_error::DeserializeError("Failed to parse max_nonce".to_string())
+                    })?;
+                }
+            }
+        }
+        let (offset, limit) = HttpRequestType::get_pending_txs_page_query(query)?;
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetPendingTransactions(
+            HttpRequestMetadata::from_preamble(preamble),
+            PendingTxQuery::Address {
+                address,
+                is_origin,
+                min_nonce,
+                max_nonce,
+            },
+            offset,
+            limit,
+            tip,
+        ))
+    }
+
+    fn parse_get_mempool_contract_txs<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        let (offset, limit) = HttpRequestType::get_pending_txs_page_query(query)?;
+        HttpRequestType::parse_get_contract_arguments(preamble, captures).map(
+            |(preamble, addr, name)| {
+                HttpRequestType::GetPendingTransactions(
+                    preamble,
+                    PendingTxQuery::ContractCall(QualifiedContractIdentifier::new(
+                        addr.into(),
+                        name,
+                    )),
+                    offset,
+                    limit,
+                    // pending contract-calls aren't checked against a chain tip
+                    TipRequest::UseLatestAnchoredTip,
+                )
+            },
+        )
+    }
+
+    fn parse_get_event_stream<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body".to_string(),
+            ));
+        }
+
+        // stream every kind of event, unless the client asks for specific ones
+        let mut kinds = HashSet::new();
+        if let Some(query) = query {
+            for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+                if key != "events" {
+                    continue;
+                }
+                for name in value.split(",").filter(|name| !name.is_empty()) {
+                    let kind = ChainEventKind::from_str(name).map_err(|e| {
+                        net_error::DeserializeError(format!("Invalid Http request: {}", e))
+                    })?;
+                    kinds.insert(kind);
+                }
+            }
+        }
+
+        let kinds = ChainEventKind::ALL
+            .iter()
+            .filter(|kind| kinds.is_empty() || kinds.contains(kind))
+            .cloned()
+            .collect();
+
+        Ok(HttpRequestType::GetEventStream(
+            HttpRequestMetadata::from_preamble(preamble),
+            kinds,
+        ))
+    }
+
     fn parse_options_preflight<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -2688,9 +3325,14 @@ impl HttpRequestType {
             HttpRequestType::GetMicroblocksConfirmed(ref md, _) => md,
             HttpRequestType::GetMicroblocksUnconfirmed(ref md, _, _) => md,
             HttpRequestType::GetTransactionUnconfirmed(ref md, _) => md,
+            HttpRequestType::GetTransactionReplacements(ref md, _) => md,
+            HttpRequestType::GetPendingTransactions(ref md, ..) => md,
             HttpRequestType::PostTransaction(ref md, _, _) => md,
             HttpRequestType::PostBlock(ref md, ..) => md,
             HttpRequestType::PostMicroblock(ref md, ..) => md,
+            HttpRequestType::SimulateTransaction(ref md, ..) => md,
+            HttpRequestType::GetAdminPeers(ref md) => md,
+            HttpRequestType::PostAdminPeerAction(ref md, ..) => md,
             HttpRequestType::GetAccount(ref md, ..) => md,
             HttpRequestType::GetDataVar(ref md, ..) => md,
             HttpRequestType::GetMapEntry(ref md, ..) => md,
@@ -2703,6 +3345,7 @@ impl HttpRequestType {
             HttpRequestType::GetAttachmentsInv(ref md, ..) => md,
             HttpRequestType::GetAttachment(ref md, ..) => md,
             HttpRequestType::MemPoolQuery(ref md, ..) => md,
+            HttpRequestType::GetEventStream(ref md, ..) => md,
             HttpRequestType::FeeRateEstimate(ref md, _, _) => md,
             HttpRequestType::ClientError(ref md, ..) => md,
         }
@@ -2719,9 +3362,14 @@ impl HttpRequestType {
             HttpRequestType::GetMicroblocksConfirmed(ref mut md, _) => md,
             HttpRequestType::GetMicroblocksUnconfirmed(ref mut md, _, _) => md,
             HttpRequestType::GetTransactionUnconfirmed(ref mut md, _) => md,
+            HttpRequestType::GetTransactionReplacements(ref mut md, _) => md,
+            HttpRequestType::GetPendingTransactions(ref mut md, ..) => md,
             HttpRequestType::PostTransaction(ref mut md, _, _) => md,
             HttpRequestType::PostBlock(ref mut md, ..) => md,
             HttpRequestType::PostMicroblock(ref mut md, ..) => md,
+            HttpRequestType::SimulateTransaction(ref mut md, ..) => md,
+            HttpRequestType::GetAdminPeers(ref mut md) => md,
+            HttpRequestType::PostAdminPeerAction(ref mut md, ..) => md,
             HttpRequestType::GetAccount(ref mut md, ..) => md,
             HttpRequestType::GetDataVar(ref mut md, ..) => md,
             HttpRequestType::GetMapEntry(ref mut md, ..) => md,
@@ -2734,6 +3382,7 @@ impl HttpRequestType {
             HttpRequestType::GetAttachmentsInv(ref mut md, ..) => md,
             HttpRequestType::GetAttachment(ref mut md, ..) => md,
             HttpRequestType::MemPoolQuery(ref mut md, ..) => md,
+            HttpRequestType::GetEventStream(ref mut md, ..) => md,
             HttpRequestType::FeeRateEstimate(ref mut md, _, _) => md,
             HttpRequestType::ClientError(ref mut md, ..) => md,
         }
@@ -2787,12 +3436,60 @@ impl HttpRequestType {
             HttpRequestType::GetTransactionUnconfirmed(_md, txid) => {
                 format!("/v2/transactions/unconfirmed/{}", txid)
             }
+            HttpRequestType::GetTransactionReplacements(_md, txid) => {
+                format!("/v2/transactions/{}/replacements", txid)
+            }
+            HttpRequestType::GetPendingTransactions(_md, query, offset, limit, tip_req) => {
+                let page_query = format!("offset={}&limit={}", offset, limit);
+                match query {
+                    PendingTxQuery::Address {
+                        address,
+                        is_origin,
+                        min_nonce,
+                        max_nonce,
+                    } => {
+                        let role_query = match is_origin {
+                            Some(true) => "&role=origin",
+                            Some(false) => "&role=sponsor",
+                            None => "",
+                        };
+                        let tip_query = match tip_req {
+                            TipRequest::UseLatestUnconfirmedTip => "&tip=latest".to_string(),
+                            TipRequest::SpecificTip(tip) => format!("&tip={}", tip),
+                            TipRequest::UseLatestAnchoredTip => "".to_string(),
+                        };
+                        let nonce_query = if *max_nonce == u64::MAX {
+                            format!("&min_nonce={}", min_nonce)
+                        } else {
+                            format!("&min_nonce={}&max_nonce={}", min_nonce, max_nonce)
+                        };
+                        format!(
+                            "/v2/mempool/address/{}?{}{}{}{}",
+                            address, page_query, role_query, nonce_query, tip_query
+                        )
+                    }
+                    PendingTxQuery::ContractCall(contract_id) => format!(
+                        "/v2/mempool/contract/{}/{}?{}",
+                        contract_id.issuer,
+                        contract_id.name.as_str(),
+                        page_query
+                    ),
+                }
+            }
             HttpRequestType::PostTransaction(_md, ..) => "/v2/transactions".to_string(),
             HttpRequestType::PostBlock(_md, ch, ..) => format!("/v2/blocks/upload/{}", &ch),
             HttpRequestType::PostMicroblock(_md, _, tip_req) => format!(
                 "/v2/microblocks{}",
                 HttpRequestType::make_tip_query_string(tip_req, true)
             ),
+            HttpRequestType::SimulateTransaction(_md, _, tip_req) => format!(
+                "/v2/transactions/simulate{}",
+                HttpRequestType::make_tip_query_string(tip_req, true)
+            ),
+            HttpRequestType::GetAdminPeers(_md) => "/v2/admin/peers".to_string(),
+            HttpRequestType::PostAdminPeerAction(_md, action) => {
+                format!("/v2/admin/peers/{}", action.name())
+            }
             HttpRequestType::GetAccount(_md, principal, tip_req, with_proof) => {
                 format!(
                     "/v2/accounts/{}{}",
@@ -2904,6 +3601,14 @@ impl HttpRequestType {
                 }
                 None => "/v2/mempool/query".to_string(),
             },
+            HttpRequestType::GetEventStream(_, kinds) => {
+                if kinds.len() == ChainEventKind::ALL.len() {
+                    "/v2/events".to_string()
+                } else {
+                    let names: Vec<_> = kinds.iter().map(|kind| kind.as_str()).collect();
+                    format!("/v2/events?events={}", names.join(","))
+                }
+            }
             HttpRequestType::FeeRateEstimate(_, _, _) => self.get_path().to_string(),
             HttpRequestType::ClientError(_md, e) => match e {
                 ClientError::NotFound(path) => path.to_string(),
@@ -2925,9 +3630,21 @@ impl HttpRequestType {
                 "/v2/microblocks/unconfirmed/:hash/:seq"
             }
             HttpRequestType::GetTransactionUnconfirmed(..) => "/v2/transactions/unconfirmed/:txid",
+            HttpRequestType::GetTransactionReplacements(..) => {
+                "/v2/transactions/:txid/replacements"
+            }
+            HttpRequestType::GetPendingTransactions(_, PendingTxQuery::Address { .. }, ..) => {
+                "/v2/mempool/address/:principal"
+            }
+            HttpRequestType::GetPendingTransactions(_, PendingTxQuery::ContractCall(_), ..) => {
+                "/v2/mempool/contract/:principal/:contract_name"
+            }
             HttpRequestType::PostTransaction(..) => "/v2/transactions",
             HttpRequestType::PostBlock(..) => "/v2/blocks/upload/:block",
             HttpRequestType::PostMicroblock(..) => "/v2/microblocks",
+            HttpRequestType::SimulateTransaction(..) => "/v2/transactions/simulate",
+            HttpRequestType::GetAdminPeers(..) => "/v2/admin/peers",
+            HttpRequestType::PostAdminPeerAction(..) => "/v2/admin/peers/:action",
             HttpRequestType::GetAccount(..) => "/v2/accounts/:principal",
             HttpRequestType::GetDataVar(..) => "/v2/data_var/:principal/:contract_name/:var_name",
             HttpRequestType::GetMapEntry(..) => "/v2/map_entry/:principal/:contract_name/:map_name",
@@ -2943,11 +3660,28 @@ impl HttpRequestType {
             HttpRequestType::GetAttachment(..) => "/v2/attachments/:hash",
             HttpRequestType::GetIsTraitImplemented(..) => "/v2/traits/:principal/:contract_name",
             HttpRequestType::MemPoolQuery(..) => "/v2/mempool/query",
+            HttpRequestType::GetEventStream(..) => "/v2/events",
             HttpRequestType::FeeRateEstimate(_, _, _) => "/v2/fees/transaction",
             HttpRequestType::OptionsPreflight(..) | HttpRequestType::ClientError(..) => "/",
         }
     }
 
+    /// The scope a client needs to make this request
+    pub fn required_scope(&self) -> RPCScope {
+        match self {
+            HttpRequestType::CallReadOnlyFunction(..) => RPCScope::ReadOnlyCall,
+            HttpRequestType::SimulateTransaction(..) => RPCScope::Simulate,
+            HttpRequestType::PostTransaction(..) => RPCScope::PostTransaction,
+            HttpRequestType::PostBlock(..) | HttpRequestType::PostMicroblock(..) => {
+                RPCScope::PostBlock
+            }
+            HttpRequestType::GetAdminPeers(..) | HttpRequestType::PostAdminPeerAction(..) => {
+                RPCScope::Admin
+            }
+            _ => RPCScope::PublicRead,
+        }
+    }
+
     pub fn send<W: Write>(&self, _protocol: &mut StacksHttp, fd: &mut W) -> Result<(), net_error> {
         match self {
             HttpRequestType::PostTransaction(md, tx, attachment) => {
@@ -2989,7 +3723,7 @@ impl HttpRequestType {
                     md.keep_alive,
                     Some(request_body_bytes.len() as u32),
                     content_type,
-                    |fd| stacks_height_headers(fd, md),
+                    |fd| request_metadata_headers(fd, md),
                 )?;
                 fd.write_all(&request_body_bytes)
                     .map_err(net_error::WriteError)?;
@@ -3007,7 +3741,7 @@ impl HttpRequestType {
                     md.keep_alive,
                     Some(block_bytes.len() as u32),
                     Some(&HttpContentType::Bytes),
-                    |fd| stacks_height_headers(fd, md),
+                    |fd| request_metadata_headers(fd, md),
                 )?;
                 fd.write_all(&block_bytes).map_err(net_error::WriteError)?;
             }
@@ -3024,10 +3758,52 @@ impl HttpRequestType {
                     md.keep_alive,
                     Some(mb_bytes.len() as u32),
                     Some(&HttpContentType::Bytes),
-                    |fd| stacks_height_headers(fd, md),
+                    |fd| request_metadata_headers(fd, md),
                 )?;
                 fd.write_all(&mb_bytes).map_err(net_error::WriteError)?;
             }
+            HttpRequestType::SimulateTransaction(md, tx, ..) => {
+                let mut tx_bytes = vec![];
+                write_next(&mut tx_bytes, tx)?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(tx_bytes.len() as u32),
+                    Some(&HttpContentType::Bytes),
+                    |fd| request_metadata_headers(fd, md),
+                )?;
+                fd.write_all(&tx_bytes).map_err(net_error::WriteError)?;
+            }
+            HttpRequestType::PostAdminPeerAction(md, action) => {
+                let mut request_body_bytes = vec![];
+                serde_json::to_writer(&mut request_body_bytes, &action.to_request_body()).map_err(
+                    |e| {
+                        net_error::SerializeError(format!(
+                            "Failed to serialize peer admin action to JSON: {:?}",
+                            &e
+                        ))
+                    },
+                )?;
+
+                HttpRequestPreamble::new_serialized(
+                    fd,
+                    &md.version,
+                    "POST",
+                    &self.request_path(),
+                    &md.peer,
+                    md.keep_alive,
+                    Some(request_body_bytes.len() as u32),
+                    Some(&HttpContentType::JSON),
+                    |fd| request_metadata_headers(fd, md),
+                )?;
+                fd.write_all(&request_body_bytes)
+                    .map_err(net_error::WriteError)?;
+            }
             HttpRequestType::GetMapEntry(
                 md,
                 _contract_addr,
@@ -3050,7 +3826,7 @@ impl HttpRequestType {
                     md.keep_alive,
                     Some(request_json.as_bytes().len() as u32),
                     Some(&HttpContentType::JSON),
-                    |fd| stacks_height_headers(fd, md),
+                    |fd| request_metadata_headers(fd, md),
                 )?;
                 fd.write_all(&request_json.as_bytes())
                     .map_err(net_error::WriteError)?;
@@ -3096,7 +3872,7 @@ impl HttpRequestType {
                     md.keep_alive,
                     Some(request_body_bytes.len() as u32),
                     Some(&HttpContentType::JSON),
-                    |fd| stacks_height_headers(fd, md),
+                    |fd| request_metadata_headers(fd, md),
                 )?;
                 fd.write_all(&request_body_bytes)
                     .map_err(net_error::WriteError)?;
@@ -3129,7 +3905,7 @@ impl HttpRequestType {
                     md.keep_alive,
                     None,
                     None,
-                    |fd| stacks_height_headers(fd, md),
+                    |fd| request_metadata_headers(fd, md),
                 )?;
             }
         }
@@ -3197,6 +3973,14 @@ impl HttpResponseType {
             402 => HttpResponseType::PaymentRequired(md, error_text),
             403 => HttpResponseType::Forbidden(md, error_text),
             404 => HttpResponseType::NotFound(md, error_text),
+            429 => {
+                let retry_after = preamble
+                    .headers
+                    .get("retry-after")
+                    .and_then(|value| value.parse::<u64>().ok())
+                    .unwrap_or(0);
+                HttpResponseType::TooManyRequests(md, retry_after, error_text)
+            }
             500 => HttpResponseType::ServerError(md, error_text),
             503 => HttpResponseType::ServiceUnavailable(md, error_text),
             _ => HttpResponseType::Error(md, preamble.status_code, error_text),
@@ -3385,7 +4169,20 @@ impl HttpResponseType {
                 &PATH_GETTRANSACTION_UNCONFIRMED,
                 &HttpResponseType::parse_transaction_unconfirmed,
             ),
+            (
+                &PATH_GETTRANSACTION_REPLACEMENTS,
+                &HttpResponseType::parse_transaction_replacements,
+            ),
             (&PATH_POSTTRANSACTION, &HttpResponseType::parse_txid),
+            (
+                &PATH_POST_SIMULATE_TRANSACTION,
+                &HttpResponseType::parse_transaction_simulation,
+            ),
+            (&PATH_GET_ADMIN_PEERS, &HttpResponseType::parse_admin_peers),
+            (
+                &PATH_POST_ADMIN_PEER_ACTION,
+                &HttpResponseType::parse_admin_peer_action,
+            ),
             (
                 &PATH_POSTBLOCK,
                 &HttpResponseType::parse_stacks_block_accepted,
@@ -3423,6 +4220,14 @@ impl HttpResponseType {
                 &PATH_POST_MEMPOOL_QUERY,
                 &HttpResponseType::parse_post_mempool_query,
             ),
+            (
+                &PATH_GET_MEMPOOL_ADDRESS_TXS,
+                &HttpResponseType::parse_pending_transactions,
+            ),
+            (
+                &PATH_GET_MEMPOOL_CONTRACT_TXS,
+                &HttpResponseType::parse_pending_transactions,
+            ),
         ];
 
         // use url::Url to parse path and query string
@@ -3602,66 +4407,109 @@ impl HttpResponseType {
             HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
         Ok(HttpResponseType::GetMapEntry(
             HttpResponseMetadata::from_preamble(request_version, preamble),
-            map_entry,
+            map_entry,
+        ))
+    }
+
+    fn parse_get_contract_src<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let src_data =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetContractSrc(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            src_data,
+        ))
+    }
+
+    fn parse_get_is_trait_implemented<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let src_data =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetIsTraitImplemented(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            src_data,
+        ))
+    }
+
+    fn parse_get_contract_abi<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let abi = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetContractABI(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            abi,
         ))
     }
 
-    fn parse_get_contract_src<R: Read>(
+    fn parse_call_read_only<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
         preamble: &HttpResponsePreamble,
         fd: &mut R,
         len_hint: Option<usize>,
     ) -> Result<HttpResponseType, net_error> {
-        let src_data =
+        let call_data =
             HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
-        Ok(HttpResponseType::GetContractSrc(
+        Ok(HttpResponseType::CallReadOnlyFunction(
             HttpResponseMetadata::from_preamble(request_version, preamble),
-            src_data,
+            call_data,
         ))
     }
 
-    fn parse_get_is_trait_implemented<R: Read>(
+    fn parse_transaction_simulation<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
         preamble: &HttpResponsePreamble,
         fd: &mut R,
         len_hint: Option<usize>,
     ) -> Result<HttpResponseType, net_error> {
-        let src_data =
+        let simulation =
             HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
-        Ok(HttpResponseType::GetIsTraitImplemented(
+        Ok(HttpResponseType::TransactionSimulation(
             HttpResponseMetadata::from_preamble(request_version, preamble),
-            src_data,
+            simulation,
         ))
     }
 
-    fn parse_get_contract_abi<R: Read>(
+    fn parse_admin_peers<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
         preamble: &HttpResponsePreamble,
         fd: &mut R,
         len_hint: Option<usize>,
     ) -> Result<HttpResponseType, net_error> {
-        let abi = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
-        Ok(HttpResponseType::GetContractABI(
+        let peers = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::AdminPeers(
             HttpResponseMetadata::from_preamble(request_version, preamble),
-            abi,
+            peers,
         ))
     }
 
-    fn parse_call_read_only<R: Read>(
+    fn parse_admin_peer_action<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
         preamble: &HttpResponsePreamble,
         fd: &mut R,
         len_hint: Option<usize>,
     ) -> Result<HttpResponseType, net_error> {
-        let call_data =
-            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
-        Ok(HttpResponseType::CallReadOnlyFunction(
+        let result = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::AdminPeerAction(
             HttpResponseMetadata::from_preamble(request_version, preamble),
-            call_data,
+            result,
         ))
     }
 
@@ -3729,6 +4577,36 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_transaction_replacements<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let replacements: TransactionReplacementsResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::TransactionReplacements(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            replacements,
+        ))
+    }
+
+    fn parse_pending_transactions<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let pending_txs: PendingTransactionsResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::PendingTransactions(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            pending_txs,
+        ))
+    }
+
     fn parse_txid<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -3965,6 +4843,7 @@ impl HttpResponseType {
             402 => "Payment Required",
             403 => "Forbidden",
             404 => "Not Found",
+            429 => "Too Many Requests",
             500 => "Internal Server Error",
             503 => "Service Temporarily Unavailable",
             _ => "Error",
@@ -4015,12 +4894,18 @@ impl HttpResponseType {
             HttpResponseType::GetIsTraitImplemented(ref md, _) => md,
             HttpResponseType::CallReadOnlyFunction(ref md, _) => md,
             HttpResponseType::UnconfirmedTransaction(ref md, _) => md,
+            HttpResponseType::TransactionReplacements(ref md, _) => md,
+            HttpResponseType::PendingTransactions(ref md, _) => md,
             HttpResponseType::GetAttachment(ref md, _) => md,
             HttpResponseType::GetAttachmentsInv(ref md, _) => md,
             HttpResponseType::MemPoolTxStream(ref md) => md,
+            HttpResponseType::EventStream(ref md) => md,
             HttpResponseType::MemPoolTxs(ref md, ..) => md,
             HttpResponseType::OptionsPreflight(ref md) => md,
             HttpResponseType::TransactionFeeEstimation(ref md, _) => md,
+            HttpResponseType::TransactionSimulation(ref md, _) => md,
+            HttpResponseType::AdminPeers(ref md, _) => md,
+            HttpResponseType::AdminPeerAction(ref md, _) => md,
             // errors
             HttpResponseType::BadRequestJSON(ref md, _) => md,
             HttpResponseType::BadRequest(ref md, _) => md,
@@ -4029,6 +4914,7 @@ impl HttpResponseType {
             HttpResponseType::Forbidden(ref md, _) => md,
             HttpResponseType::NotFound(ref md, _) => md,
             HttpResponseType::ServerError(ref md, _) => md,
+            HttpResponseType::TooManyRequests(ref md, ..) => md,
             HttpResponseType::ServiceUnavailable(ref md, _) => md,
             HttpResponseType::Error(ref md, _, _) => md,
         }
@@ -4104,6 +4990,18 @@ impl HttpResponseType {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, data)?;
             }
+            HttpResponseType::TransactionSimulation(ref md, ref data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, data)?;
+            }
+            HttpResponseType::AdminPeers(ref md, ref data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, data)?;
+            }
+            HttpResponseType::AdminPeerAction(ref md, ref data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, data)?;
+            }
             HttpResponseType::GetContractABI(ref md, ref data) => {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, data)?;
@@ -4273,6 +5171,14 @@ impl HttpResponseType {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, unconfirmed_status)?;
             }
+            HttpResponseType::TransactionReplacements(ref md, ref replacements) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, replacements)?;
+            }
+            HttpResponseType::PendingTransactions(ref md, ref pending_txs) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, pending_txs)?;
+            }
             HttpResponseType::MemPoolTxStream(ref md) => {
                 // only send the preamble.  The caller will need to figure out how to send along
                 // the tx data itself.
@@ -4286,6 +5192,19 @@ impl HttpResponseType {
                     |ref mut fd| keep_alive_headers(fd, md),
                 )?;
             }
+            HttpResponseType::EventStream(ref md) => {
+                // only send the preamble.  Events are sent as they happen, for as long as the
+                // client stays connected.
+                HttpResponsePreamble::new_serialized(
+                    fd,
+                    200,
+                    "OK",
+                    None,
+                    &HttpContentType::EventStream,
+                    md.request_id,
+                    |ref mut fd| keep_alive_headers(fd, md),
+                )?;
+            }
             HttpResponseType::MemPoolTxs(ref md, ref page_id, ref txs) => {
                 HttpResponsePreamble::new_serialized(
                     fd,
@@ -4347,6 +5266,23 @@ impl HttpResponseType {
             HttpResponseType::PaymentRequired(_, ref msg) => self.error_response(fd, 402, msg)?,
             HttpResponseType::Forbidden(_, ref msg) => self.error_response(fd, 403, msg)?,
             HttpResponseType::NotFound(_, ref msg) => self.error_response(fd, 404, msg)?,
+            HttpResponseType::TooManyRequests(ref md, ref retry_after, ref msg) => {
+                HttpResponsePreamble::new_serialized(
+                    fd,
+                    429,
+                    HttpResponseType::error_reason(429),
+                    Some(msg.len() as u32),
+                    &HttpContentType::Text,
+                    md.request_id,
+                    |ref mut fd| {
+                        keep_alive_headers(fd, md)?;
+                        fd.write_all(format!("Retry-After: {}\r\n", retry_after).as_bytes())
+                            .map_err(codec_error::WriteError)
+                    },
+                )?;
+                fd.write_all(msg.as_bytes())
+                    .map_err(net_error::WriteError)?;
+            }
             HttpResponseType::ServerError(_, ref msg) => self.error_response(fd, 500, msg)?,
             HttpResponseType::ServiceUnavailable(_, ref msg) => {
                 self.error_response(fd, 503, msg)?
@@ -4421,9 +5357,16 @@ impl MessageSequence for StacksHttpMessage {
                 HttpRequestType::GetTransactionUnconfirmed(_, _) => {
                     "HTTP(GetTransactionUnconfirmed)"
                 }
+                HttpRequestType::GetTransactionReplacements(_, _) => {
+                    "HTTP(GetTransactionReplacements)"
+                }
+                HttpRequestType::GetPendingTransactions(..) => "HTTP(GetPendingTransactions)",
                 HttpRequestType::PostTransaction(_, _, _) => "HTTP(PostTransaction)",
                 HttpRequestType::PostBlock(..) => "HTTP(PostBlock)",
                 HttpRequestType::PostMicroblock(..) => "HTTP(PostMicroblock)",
+                HttpRequestType::SimulateTransaction(..) => "HTTP(SimulateTransaction)",
+                HttpRequestType::GetAdminPeers(..) => "HTTP(GetAdminPeers)",
+                HttpRequestType::PostAdminPeerAction(..) => "HTTP(PostAdminPeerAction)",
                 HttpRequestType::GetAccount(..) => "HTTP(GetAccount)",
                 HttpRequestType::GetDataVar(..) => "HTTP(GetDataVar)",
                 HttpRequestType::GetMapEntry(..) => "HTTP(GetMapEntry)",
@@ -4435,6 +5378,7 @@ impl MessageSequence for StacksHttpMessage {
                 HttpRequestType::GetAttachment(..) => "HTTP(GetAttachment)",
                 HttpRequestType::GetAttachmentsInv(..) => "HTTP(GetAttachmentsInv)",
                 HttpRequestType::MemPoolQuery(..) => "HTTP(MemPoolQuery)",
+                HttpRequestType::GetEventStream(..) => "HTTP(GetEventStream)",
                 HttpRequestType::OptionsPreflight(..) => "HTTP(OptionsPreflight)",
                 HttpRequestType::ClientError(..) => "HTTP(ClientError)",
                 HttpRequestType::FeeRateEstimate(_, _, _) => "HTTP(FeeRateEstimate)",
@@ -4463,7 +5407,10 @@ impl MessageSequence for StacksHttpMessage {
                 HttpResponseType::StacksBlockAccepted(..) => "HTTP(StacksBlockAccepted)",
                 HttpResponseType::MicroblockHash(_, _) => "HTTP(MicroblockHash)",
                 HttpResponseType::UnconfirmedTransaction(_, _) => "HTTP(UnconfirmedTransaction)",
+                HttpResponseType::TransactionReplacements(_, _) => "HTTP(TransactionReplacements)",
+                HttpResponseType::PendingTransactions(_, _) => "HTTP(PendingTransactions)",
                 HttpResponseType::MemPoolTxStream(..) => "HTTP(MemPoolTxStream)",
+                HttpResponseType::EventStream(..) => "HTTP(EventStream)",
                 HttpResponseType::MemPoolTxs(..) => "HTTP(MemPoolTxs)",
                 HttpResponseType::OptionsPreflight(_) => "HTTP(OptionsPreflight)",
                 HttpResponseType::BadRequestJSON(..) | HttpResponseType::BadRequest(..) => {
@@ -4474,11 +5421,15 @@ impl MessageSequence for StacksHttpMessage {
                 HttpResponseType::Forbidden(_, _) => "HTTP(403)",
                 HttpResponseType::NotFound(_, _) => "HTTP(404)",
                 HttpResponseType::ServerError(_, _) => "HTTP(500)",
+                HttpResponseType::TooManyRequests(..) => "HTTP(429)",
                 HttpResponseType::ServiceUnavailable(_, _) => "HTTP(503)",
                 HttpResponseType::Error(_, _, _) => "HTTP(other)",
                 HttpResponseType::TransactionFeeEstimation(_, _) => {
                     "HTTP(TransactionFeeEstimation)"
                 }
+                HttpResponseType::TransactionSimulation(_, _) => "HTTP(TransactionSimulation)",
+                HttpResponseType::AdminPeers(_, _) => "HTTP(AdminPeers)",
+                HttpResponseType::AdminPeerAction(_, _) => "HTTP(AdminPeerAction)",
             },
         }
     }
@@ -4601,6 +5552,27 @@ impl StacksHttp {
         self.chunk_size = size;
     }
 
+    /// If the request is HMAC-signed, record what its signature should cover, now that we have
+    /// its body
+    fn set_signed_content(
+        md: &mut HttpRequestMetadata,
+        preamble: &HttpRequestPreamble,
+        body: &[u8],
+    ) {
+        let is_signed = md
+            .authorization
+            .as_ref()
+            .map(|authorization| is_hmac_authorization(authorization))
+            .unwrap_or(false);
+        if is_signed {
+            md.signed_content = Some(rpc_auth_signed_content(
+                &preamble.verb,
+                &preamble.path,
+                body,
+            ));
+        }
+    }
+
     pub fn num_pending(&self) -> usize {
         if self.reply.is_some() {
             1
@@ -4903,17 +5875,27 @@ impl ProtocolFamily for StacksHttp {
 
                 let mut cursor = io::Cursor::new(buf);
                 match HttpRequestType::parse(self, http_request_preamble, &mut cursor) {
-                    Ok(data_request) => Ok((
-                        StacksHttpMessage::Request(data_request),
-                        cursor.position() as usize,
-                    )),
+                    Ok(mut data_request) => {
+                        StacksHttp::set_signed_content(
+                            data_request.metadata_mut(),
+                            http_request_preamble,
+                            &buf[..len],
+                        );
+                        Ok((
+                            StacksHttpMessage::Request(data_request),
+                            cursor.position() as usize,
+                        ))
+                    }
                     Err(e) => {
                         info!("Failed to parse HTTP request: {:?}", &e);
                         if let net_error::ClientError(client_err) = e {
-                            let req = HttpRequestType::ClientError(
-                                HttpRequestMetadata::from_preamble(http_request_preamble),
-                                client_err,
+                            let mut md = HttpRequestMetadata::from_preamble(http_request_preamble);
+                            StacksHttp::set_signed_content(
+                                &mut md,
+                                http_request_preamble,
+                                &buf[..len],
                             );
+                            let req = HttpRequestType::ClientError(md, client_err);
                             // consume any remaining HTTP request content by returning bytes read = len
                             Ok((StacksHttpMessage::Request(req), len))
                         } else {
@@ -5012,6 +5994,11 @@ mod test {
     use crate::net::test::*;
     use crate::net::RPCNeighbor;
     use crate::net::RPCNeighborsInfo;
+    use crate::net::RPCPeerAdminResponse;
+    use crate::net::TransactionSimulationResponse;
+    use crate::net::{PeerAdminAction, PeerAdminRequestBody, PeerAdminTarget};
+    use clarity::vm::costs::ExecutionCost;
+    use stacks_common::util::get_epoch_time_secs;
     use stacks_common::util::hash::to_hex;
     use stacks_common::util::hash::Hash160;
     use stacks_common::util::hash::MerkleTree;
@@ -5956,13 +6943,23 @@ mod test {
             ),
             keep_alive: true,
             canonical_stacks_tip_height: None,
+            authorization: None,
+            signed_content: None,
         };
         let http_request_metadata_dns = HttpRequestMetadata {
             version: HttpVersion::Http11,
             peer: PeerHost::DNS("www.foo.com".to_string(), 80),
             keep_alive: true,
             canonical_stacks_tip_height: None,
+            authorization: None,
+            signed_content: None,
         };
+        let mut http_request_metadata_admin = http_request_metadata_ip.clone();
+        http_request_metadata_admin.authorization = Some("Bearer secret".to_string());
+        let admin_action = PeerAdminAction::Ban(
+            PeerAdminTarget::Cidr(PeerAddress::from_ipv4(10, 0, 0, 0), 104),
+            Some(3600),
+        );
 
         let tests = vec![
             HttpRequestType::GetNeighbors(http_request_metadata_ip.clone()),
@@ -5976,6 +6973,15 @@ mod test {
                 make_test_transaction(),
                 None,
             ),
+            HttpRequestType::SimulateTransaction(
+                http_request_metadata_ip.clone(),
+                make_test_transaction(),
+                TipRequest::SpecificTip(StacksBlockId([4u8; 32])),
+            ),
+            HttpRequestType::PostAdminPeerAction(
+                http_request_metadata_admin.clone(),
+                admin_action.clone(),
+            ),
             HttpRequestType::OptionsPreflight(http_request_metadata_ip.clone(), "/".to_string()),
         ];
 
@@ -5995,6 +7001,37 @@ mod test {
         post_transaction_preamble.set_content_type(HttpContentType::Bytes);
         post_transaction_preamble.set_content_length(tx_body.len() as u32);
 
+        let mut simulate_transaction_preamble = HttpRequestPreamble::new(
+            HttpVersion::Http11,
+            "POST".to_string(),
+            format!(
+                "/v2/transactions/simulate?tip={}",
+                StacksBlockId([4u8; 32]).to_hex()
+            ),
+            http_request_metadata_ip.peer.hostname(),
+            http_request_metadata_ip.peer.port(),
+            http_request_metadata_ip.keep_alive,
+        );
+        simulate_transaction_preamble.set_content_type(HttpContentType::Bytes);
+        simulate_transaction_preamble.set_content_length(tx_body.len() as u32);
+
+        let admin_action_body = serde_json::to_vec(&admin_action.to_request_body()).unwrap();
+        assert_eq!(
+            str::from_utf8(&admin_action_body).unwrap(),
+            r#"{"cidr":"10.0.0.0/8","duration":3600}"#
+        );
+        let mut admin_action_preamble = HttpRequestPreamble::new(
+            HttpVersion::Http11,
+            "POST".to_string(),
+            "/v2/admin/peers/ban".to_string(),
+            http_request_metadata_admin.peer.hostname(),
+            http_request_metadata_admin.peer.port(),
+            http_request_metadata_admin.keep_alive,
+        );
+        admin_action_preamble.set_content_type(HttpContentType::JSON);
+        admin_action_preamble.set_content_length(admin_action_body.len() as u32);
+        admin_action_preamble.add_header("Authorization".to_string(), "Bearer secret".to_string());
+
         // all of these should parse
         let expected_http_preambles = vec![
             HttpRequestPreamble::new(
@@ -6022,6 +7059,8 @@ mod test {
                 http_request_metadata_ip.keep_alive,
             ),
             post_transaction_preamble,
+            simulate_transaction_preamble,
+            admin_action_preamble,
             HttpRequestPreamble::new(
                 HttpVersion::Http11,
                 "OPTIONS".to_string(),
@@ -6032,7 +7071,14 @@ mod test {
             ),
         ];
 
-        let expected_http_bodies = vec![vec![], vec![], vec![], tx_body];
+        let expected_http_bodies = vec![
+            vec![],
+            vec![],
+            vec![],
+            tx_body.clone(),
+            tx_body,
+            admin_action_body,
+        ];
 
         for (test, (expected_http_preamble, expected_http_body)) in tests.iter().zip(
             expected_http_preambles
@@ -6078,6 +7124,7 @@ mod test {
             "GET /v2/blocks/1111111111111111111111111111111111111111111111111111111111111111 HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nContent-Length: 1\r\n\r\nb",
             "GET /v2/microblocks/1111111111111111111111111111111111111111111111111111111111111111 HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nContent-Length: 1\r\n\r\nb",
             "POST /v2/transactions HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nContent-Length: 0\r\n\r\n",
+            "POST /v2/transactions/simulate HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: bad:123\r\nContent-Length: 0\r\n\r\n",
         ];
         for bad_content_length in bad_content_lengths {
             let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
@@ -6108,6 +7155,232 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_peer_admin_action_request_body() {
+        let parse = |action: &str, json: &str| {
+            let body: PeerAdminRequestBody = serde_json::from_str(json).unwrap();
+            PeerAdminAction::from_request_body(action, &body)
+        };
+
+        assert_eq!(
+            parse("ban", r#"{"peer":"1.2.3.4:20444","duration":60}"#),
+            Ok(PeerAdminAction::Ban(
+                PeerAdminTarget::Address("1.2.3.4:20444".parse().unwrap()),
+                Some(60)
+            ))
+        );
+        assert_eq!(
+            parse("allow", r#"{"cidr":"2001:db8::/32"}"#),
+            Ok(PeerAdminAction::Allow(
+                PeerAdminTarget::Cidr(PeerAddress::from_ip(&"2001:db8::".parse().unwrap()), 32),
+                None
+            ))
+        );
+        assert_eq!(
+            parse("disconnect", r#"{"event_id":7}"#),
+            Ok(PeerAdminAction::Disconnect(PeerAdminTarget::Event(7)))
+        );
+        assert_eq!(
+            parse("connect", r#"{"peer":"[::1]:20444"}"#),
+            Ok(PeerAdminAction::Connect("[::1]:20444".parse().unwrap()))
+        );
+
+        // exactly one target
+        assert!(parse("ban", r#"{}"#).is_err());
+        assert!(parse("ban", r#"{"peer":"1.2.3.4:20444","event_id":7}"#).is_err());
+        // only addresses can be connected to
+        assert!(parse("connect", r#"{"cidr":"10.0.0.0/8"}"#).is_err());
+        // durations are positive, and only apply to bans and allows
+        assert!(parse("ban", r#"{"event_id":7,"duration":0}"#).is_err());
+        assert!(parse("disconnect", r#"{"event_id":7,"duration":60}"#).is_err());
+        // malformed CIDRs
+        assert!(parse("ban", r#"{"cidr":"10.0.0.0"}"#).is_err());
+        assert!(parse("ban", r#"{"cidr":"10.0.0.0/33"}"#).is_err());
+        assert!(parse("ban", r#"{"cidr":"10.0.0.0/0"}"#).is_err());
+
+        // CIDRs round-trip, and match what they should
+        let (prefix, mask) = PeerAdminTarget::parse_cidr("192.168.0.0/16").unwrap();
+        assert_eq!(mask, 112);
+        assert_eq!(
+            PeerAdminTarget::cidr_to_string(&prefix, mask),
+            "192.168.0.0/16"
+        );
+        let target = PeerAdminTarget::Cidr(prefix, mask);
+        assert!(target.matches(0, &PeerAddress::from_ipv4(192, 168, 4, 5), 20444));
+        assert!(!target.matches(0, &PeerAddress::from_ipv4(192, 169, 4, 5), 20444));
+    }
+
+    #[test]
+    fn test_authorize_request() {
+        let now = 1_650_000_000;
+        let ops = RPCAuthToken::new("ops", "ops-secret", &[RPCScope::Admin]);
+        let miner = RPCAuthToken::new("miner", "miner-secret", &[RPCScope::PostBlock]);
+        let tokens = vec![ops.clone(), miner.clone()];
+        let public_scopes = RPCScope::public_default();
+
+        let make_md = |authorization: Option<String>| {
+            let mut md = HttpRequestMetadata::new("127.0.0.1".to_string(), 20443, None);
+            md.authorization = authorization;
+            md
+        };
+        let getinfo =
+            |authorization: Option<String>| HttpRequestType::GetInfo(make_md(authorization));
+        let admin_peers =
+            |authorization: Option<String>| HttpRequestType::GetAdminPeers(make_md(authorization));
+
+        // public endpoints need no token, but a given token must be valid
+        assert_eq!(
+            authorize_request(&getinfo(None), &public_scopes, &tokens, now),
+            Ok(None)
+        );
+        assert_eq!(
+            authorize_request(&getinfo(Some(miner.bearer())), &public_scopes, &tokens, now),
+            Ok(Some("miner".to_string()))
+        );
+        assert!(matches!(
+            authorize_request(
+                &getinfo(Some("Bearer nope".to_string())),
+                &public_scopes,
+                &tokens,
+                now
+            ),
+            Err(RPCAuthError::BadCredentials(..))
+        ));
+        assert!(matches!(
+            authorize_request(
+                &getinfo(Some("Basic b3BzOm9wcw==".to_string())),
+                &public_scopes,
+                &tokens,
+                now
+            ),
+            Err(RPCAuthError::BadCredentials(..))
+        ));
+
+        // non-public endpoints need a token that grants their scope
+        assert_eq!(
+            authorize_request(
+                &admin_peers(Some(ops.bearer())),
+                &public_scopes,
+                &tokens,
+                now
+            ),
+            Ok(Some("ops".to_string()))
+        );
+        assert_eq!(
+            authorize_request(&admin_peers(None), &public_scopes, &tokens, now),
+            Err(RPCAuthError::Unauthenticated(RPCScope::Admin))
+        );
+        assert_eq!(
+            authorize_request(
+                &admin_peers(Some(miner.bearer())),
+                &public_scopes,
+                &tokens,
+                now
+            ),
+            Err(RPCAuthError::Forbidden(
+                "miner".to_string(),
+                RPCScope::Admin
+            ))
+        );
+        assert_eq!(
+            authorize_request(&admin_peers(None), &public_scopes, &[miner.clone()], now),
+            Err(RPCAuthError::Disabled(RPCScope::Admin))
+        );
+
+        // a private node can require a token for everything
+        assert_eq!(
+            authorize_request(&getinfo(None), &HashSet::new(), &tokens, now),
+            Err(RPCAuthError::Disabled(RPCScope::PublicRead))
+        );
+
+        for scope in RPCScope::ALL.iter() {
+            assert_eq!(RPCScope::from_str(scope.as_str()), Ok(*scope));
+        }
+        assert!(RPCScope::from_str("everything").is_err());
+
+        // simulation and the admin endpoints must be enabled explicitly
+        assert!(public_scopes.contains(&RPCScope::ReadOnlyCall));
+        assert!(!public_scopes.contains(&RPCScope::Simulate));
+        assert!(!public_scopes.contains(&RPCScope::Admin));
+    }
+
+    #[test]
+    fn test_authorize_signed_request() {
+        let ops = RPCAuthToken::new("ops", "ops-secret", &[RPCScope::Admin]);
+        let tokens = vec![ops.clone()];
+        let public_scopes = RPCScope::public_default();
+        let now = get_epoch_time_secs();
+
+        let action = PeerAdminAction::Disconnect(PeerAdminTarget::Event(1));
+        let body = serde_json::to_vec(&action.to_request_body()).unwrap();
+
+        // round-trip a signed request through the codec, and check its signature
+        let send_and_authorize = |authorization: String| {
+            let mut md = HttpRequestMetadata::new("127.0.0.1".to_string(), 20443, None);
+            md.authorization = Some(authorization);
+            let req = HttpRequestType::PostAdminPeerAction(md, action.clone());
+
+            let mut bytes = vec![];
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            http.write_message(&mut bytes, &StacksHttpMessage::Request(req))
+                .unwrap();
+
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+            let (msg, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+            match msg {
+                StacksHttpMessage::Request(req) => {
+                    authorize_request(&req, &public_scopes, &tokens, now)
+                }
+                _ => panic!("Expected request"),
+            }
+        };
+
+        assert_eq!(
+            send_and_authorize(ops.sign("POST", "/v2/admin/peers/disconnect", &body, now)),
+            Ok(Some("ops".to_string()))
+        );
+
+        let bad_signatures = vec![
+            // signed for another path
+            ops.sign("POST", "/v2/admin/peers/ban", &body, now),
+            // signed for another body
+            ops.sign("POST", "/v2/admin/peers/disconnect", b"{}", now),
+            // signed too long ago
+            ops.sign(
+                "POST",
+                "/v2/admin/peers/disconnect",
+                &body,
+                now - RPC_AUTH_MAX_CLOCK_SKEW - 60,
+            ),
+            // signed with the wrong secret
+            RPCAuthToken::new("ops", "guess", &[]).sign(
+                "POST",
+                "/v2/admin/peers/disconnect",
+                &body,
+                now,
+            ),
+            // signed by a token we don't know
+            RPCAuthToken::new("nobody", "ops-secret", &[]).sign(
+                "POST",
+                "/v2/admin/peers/disconnect",
+                &body,
+                now,
+            ),
+            format!("HMAC-SHA256 ops:{}", now),
+        ];
+        for authorization in bad_signatures.into_iter() {
+            assert!(
+                matches!(
+                    send_and_authorize(authorization.clone()),
+                    Err(RPCAuthError::BadCredentials(..))
+                ),
+                "{}",
+                &authorization
+            );
+        }
+    }
+
     #[test]
     fn test_http_response_type_codec() {
         let test_neighbors_info = RPCNeighborsInfo {
@@ -6164,6 +7437,28 @@ mod test {
             .consensus_serialize(&mut test_microblock_info_bytes)
             .unwrap();
 
+        let test_transaction_simulation = TransactionSimulationResponse {
+            txid: Txid([0x1; 32]),
+            tip: StacksBlockId([0x2; 32]),
+            okay: true,
+            cause: None,
+            result: Some("0x0703".to_string()),
+            vm_error: None,
+            post_condition_mode: "deny".to_string(),
+            post_condition_aborted: false,
+            events: vec![json!({ "type": "stx_transfer_event" })],
+            asset_transfers: vec![json!({ "type": "stx_transfer_event" })],
+            execution_cost: ExecutionCost::zero(),
+            fee: 180,
+            fee_estimate: None,
+        };
+
+        let test_admin_peer_action = RPCPeerAdminResponse {
+            action: "ban".to_string(),
+            disconnected: vec![1, 2],
+            until: Some(1234567890),
+        };
+
         let tests = vec![
             // length is known
             (
@@ -6253,6 +7548,20 @@ mod test {
                 ),
                 "/v2/transactions".to_string(),
             ),
+            (
+                HttpResponseType::TransactionSimulation(
+                    HttpResponseMetadata::new(HttpVersion::Http11, 123, None, true, None),
+                    test_transaction_simulation.clone(),
+                ),
+                "/v2/transactions/simulate".to_string(),
+            ),
+            (
+                HttpResponseType::AdminPeerAction(
+                    HttpResponseMetadata::new(HttpVersion::Http11, 123, None, true, None),
+                    test_admin_peer_action.clone(),
+                ),
+                "/v2/admin/peers/ban".to_string(),
+            ),
             // errors without error messages
             (
                 HttpResponseType::BadRequest(
@@ -6438,6 +7747,22 @@ mod test {
                 true,
                 123,
             ),
+            HttpResponsePreamble::new(
+                200,
+                "OK".to_string(),
+                None,
+                HttpContentType::JSON,
+                true,
+                123,
+            ),
+            HttpResponsePreamble::new(
+                200,
+                "OK".to_string(),
+                None,
+                HttpContentType::JSON,
+                true,
+                123,
+            ),
             // errors
             HttpResponsePreamble::new_error(400, 123, None),
             HttpResponsePreamble::new_error(401, 123, None),
@@ -6476,6 +7801,14 @@ mod test {
             test_block_info_bytes,
             test_microblock_info_bytes,
             Txid([0x1; 32]).to_hex().as_bytes().to_vec(),
+            serde_json::to_string(&test_transaction_simulation)
+                .unwrap()
+                .as_bytes()
+                .to_vec(),
+            serde_json::to_string(&test_admin_peer_action)
+                .unwrap()
+                .as_bytes()
+                .to_vec(),
             // errors
             vec![],
             vec![],
@@ -6610,6 +7943,37 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_http_response_too_many_requests() {
+        let response = HttpResponseType::TooManyRequests(
+            HttpResponseMetadata::new(HttpVersion::Http11, 123, Some(3), true, None),
+            30,
+            "foo".to_string(),
+        );
+
+        let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+        let mut bytes = vec![];
+        http.begin_request(HttpVersion::Http11, "/v2/info".to_string());
+        http.write_message(&mut bytes, &StacksHttpMessage::Response(response.clone()))
+            .unwrap();
+
+        let (preamble, offset) = http.read_preamble(&bytes).unwrap();
+        match preamble {
+            StacksHttpPreamble::Response(ref resp) => {
+                assert_eq!(resp.status_code, 429);
+                assert_eq!(resp.reason, "Too Many Requests");
+                assert_eq!(resp.headers.get("retry-after"), Some(&"30".to_string()));
+            }
+            StacksHttpPreamble::Request(_) => {
+                panic!("parsed a request");
+            }
+        }
+
+        let (message, _) = http.read_payload(&preamble, &bytes[offset..]).unwrap();
+        assert_eq!(message, StacksHttpMessage::Response(response));
+        assert_eq!(http.num_pending(), 0);
+    }
+
     #[test]
     fn test_http_headers_too_big() {
         let bad_header_value = std::iter::repeat("A")
@@ -6979,6 +8343,59 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_http_parse_event_stream_query() {
+        let parse_kinds = |path: &str| {
+            let request = format!(
+                "GET {} HTTP/1.1\r\nUser-Agent: stacks/2.0\r\nHost: localhost:20443\r\n\r\n",
+                path
+            );
+            let mut http = StacksHttp::new("127.0.0.1:20443".parse().unwrap());
+            let (preamble, offset) = http.read_preamble(request.as_bytes()).unwrap();
+            http.read_payload(&preamble, &request.as_bytes()[offset..])
+                .map(|(msg, _)| match msg {
+                    StacksHttpMessage::Request(HttpRequestType::GetEventStream(_, kinds)) => kinds,
+                    _ => panic!("Not an event stream request: {:?}", &msg),
+                })
+        };
+
+        // no filter streams everything
+        assert_eq!(
+            parse_kinds("/v2/events").unwrap(),
+            ChainEventKind::ALL.to_vec()
+        );
+        assert_eq!(
+            parse_kinds("/v2/events?events=").unwrap(),
+            ChainEventKind::ALL.to_vec()
+        );
+
+        // kinds come back in canonical order, without duplicates
+        assert_eq!(
+            parse_kinds("/v2/events?events=new_burn_block,new_block,new_block").unwrap(),
+            vec![ChainEventKind::Block, ChainEventKind::BurnBlock]
+        );
+        assert_eq!(
+            parse_kinds("/v2/events?events=drop_mempool_tx&events=new_mempool_tx").unwrap(),
+            vec![
+                ChainEventKind::MempoolTxs,
+                ChainEventKind::DroppedMempoolTxs
+            ]
+        );
+
+        // request paths round-trip
+        let md =
+            HttpRequestMetadata::from_host(PeerHost::DNS("localhost".to_string(), 20443), None);
+        for kinds in [
+            ChainEventKind::ALL.to_vec(),
+            vec![ChainEventKind::Microblocks, ChainEventKind::BurnBlock],
+        ] {
+            let req = HttpRequestType::GetEventStream(md.clone(), kinds.clone());
+            assert_eq!(parse_kinds(&req.request_path()).unwrap(), kinds);
+        }
+
+        assert!(parse_kinds("/v2/events?events=new_block,new_blocks").is_err());
+    }
+
     #[test]
     fn test_http_live_headers() {
         // headers pulled from prod
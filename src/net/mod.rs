@@ -59,6 +59,7 @@ use crate::clarity_vm::clarity::Error as clarity_error;
 use crate::core::mempool::*;
 use crate::core::POX_REWARD_CYCLE_LENGTH;
 use crate::net::atlas::{Attachment, AttachmentInstance};
+use crate::net::event_stream::ChainEventKind;
 use crate::net::http::HttpReservedHeader;
 pub use crate::net::http::StacksBlockAcceptedData;
 use crate::util_lib::bloom::{BloomFilter, BloomNodeHasher};
@@ -122,6 +123,8 @@ pub mod db;
 /// which serves as an API for `DNSResolver`.  
 pub mod dns;
 pub mod download;
+/// Implements `EventStreamHub`, which fans chain events out to the RPC clients streaming them.
+pub mod event_stream;
 pub mod http;
 pub mod inv;
 pub mod neighbors;
@@ -135,6 +138,10 @@ pub mod prune;
 pub mod relay;
 pub mod rpc;
 pub mod server;
+/// Implements the client side of SOCKS5, for routing outbound connections through a proxy.
+pub mod socks;
+/// Implements TLS sessions for the RPC server, on top of the non-blocking sockets in `poll`.
+pub mod tls;
 
 #[derive(Debug)]
 pub enum Error {
@@ -246,6 +253,10 @@ pub enum Error {
     ExpectedEndOfStream,
     /// burnchain error
     BurnchainError(burnchain_error),
+    /// Failed to set up TLS
+    TlsError(String),
+    /// SOCKS5 proxy failed to set up a connection
+    ProxyError(String),
 }
 
 impl From<codec_error> for Error {
@@ -347,6 +358,8 @@ impl fmt::Display for Error {
             Error::Transient(ref s) => write!(f, "Transient network error: {}", s),
             Error::ExpectedEndOfStream => write!(f, "Expected end-of-stream"),
             Error::BurnchainError(ref e) => fmt::Display::fmt(e, f),
+            Error::TlsError(ref s) => fmt::Display::fmt(s, f),
+            Error::ProxyError(ref s) => fmt::Display::fmt(s, f),
         }
     }
 }
@@ -408,6 +421,8 @@ impl error::Error for Error {
             Error::Transient(ref _s) => None,
             Error::ExpectedEndOfStream => None,
             Error::BurnchainError(ref e) => Some(e),
+            Error::TlsError(ref _s) => None,
+            Error::ProxyError(ref _s) => None,
         }
     }
 }
@@ -623,11 +638,24 @@ impl PeerAddress {
         ])
     }
 
+    /// Do the first `mask` bits of this address match those of `prefix`?
+    pub fn is_in_cidr(&self, prefix: &PeerAddress, mask: u32) -> bool {
+        assert!(mask > 0 && mask <= 128);
+        let addr_mask = !((1u128 << (128 - mask)) - 1);
+        u128::from_be_bytes(self.0) & addr_mask == u128::from_be_bytes(prefix.0) & addr_mask
+    }
+
     /// Is this the any-network address?  i.e. 0.0.0.0 (v4) or :: (v6)?
     pub fn is_anynet(&self) -> bool {
         self.0 == [0x00; 16] || self == &PeerAddress::from_ipv4(0, 0, 0, 0)
     }
 
+    /// Is this a synthetic address standing in for a peer's DNS name?  See
+    /// `PeerHostName::to_peer_address()`.
+    pub fn is_host_address(&self) -> bool {
+        self.0[0..6] == PEER_HOST_ADDRESS_PREFIX
+    }
+
     /// Is this a private IP address?
     pub fn is_in_private_range(&self) -> bool {
         if self.is_ipv4() {
@@ -643,12 +671,89 @@ impl PeerAddress {
 
 pub const STACKS_PUBLIC_KEY_ENCODED_SIZE: u32 = 33;
 
+/// Synthetic peer addresses for DNS-named peers live in this /48 (the OnionCat prefix).
+pub const PEER_HOST_ADDRESS_PREFIX: [u8; 6] = [0xfd, 0x87, 0xd8, 0x7e, 0xeb, 0x43];
+
+/// Longest DNS name we'll accept for a peer
+pub const MAX_PEER_HOST_NAME_LEN: usize = 253;
+
+/// The DNS name of a peer that has no routable IP address of its own, such as a Tor onion
+/// service (e.g. `abcd...xyz.onion`).  Such peers can only be dialed through a SOCKS5 proxy, which
+/// resolves the name on our behalf.
+///
+/// Everywhere the p2p network expects a `PeerAddress`, a named peer is represented by a synthetic
+/// address derived from the hash of its name (see `to_peer_address()`), so the peer DB, neighbor
+/// keys and the neighbor walk work unmodified.  The name itself is kept in the peer DB and shared
+/// with peers that set `ServiceFlags::HOSTS` in `NeighborHosts` messages.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PeerHostName(String);
+
+impl PeerHostName {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Is this a well-formed, lowercase DNS name?
+    fn is_valid(name: &str) -> bool {
+        if name.is_empty() || name.len() > MAX_PEER_HOST_NAME_LEN {
+            return false;
+        }
+        name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .bytes()
+                    .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'-')
+        })
+    }
+
+    /// Is this a Tor onion service name?
+    pub fn is_onion(&self) -> bool {
+        self.0.ends_with(".onion")
+    }
+
+    /// The synthetic address that stands in for this name:  the 6-byte
+    /// `PEER_HOST_ADDRESS_PREFIX`, followed by the first 10 bytes of the Hash160 of the name.
+    pub fn to_peer_address(&self) -> PeerAddress {
+        let h = Hash160::from_data(self.0.as_bytes());
+        let mut bytes = [0u8; 16];
+        bytes[0..6].copy_from_slice(&PEER_HOST_ADDRESS_PREFIX);
+        bytes[6..16].copy_from_slice(&h.as_bytes()[0..10]);
+        PeerAddress(bytes)
+    }
+}
+
+impl TryFrom<&str> for PeerHostName {
+    type Error = codec_error;
+
+    /// DNS names are case-insensitive, so this lowercases the name first.
+    fn try_from(name: &str) -> Result<PeerHostName, codec_error> {
+        let name = name.to_lowercase();
+        if !PeerHostName::is_valid(&name) {
+            return Err(codec_error::DeserializeError(format!(
+                "Invalid peer host name '{}'",
+                &name
+            )));
+        }
+        Ok(PeerHostName(name))
+    }
+}
+
+impl fmt::Display for PeerHostName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", &self.0)
+    }
+}
+
 /// supported HTTP content types
 #[derive(Debug, Clone, PartialEq)]
 pub enum HttpContentType {
     Bytes,
     Text,
     JSON,
+    EventStream,
 }
 
 impl fmt::Display for HttpContentType {
@@ -663,6 +768,7 @@ impl HttpContentType {
             HttpContentType::Bytes => "application/octet-stream",
             HttpContentType::Text => "text/plain",
             HttpContentType::JSON => "application/json",
+            HttpContentType::EventStream => "text/event-stream",
         }
     }
 }
@@ -678,6 +784,8 @@ impl FromStr for HttpContentType {
             Ok(HttpContentType::Text)
         } else if s == "application/json" {
             Ok(HttpContentType::JSON)
+        } else if s == "text/event-stream" {
+            Ok(HttpContentType::EventStream)
         } else {
             Err(codec_error::DeserializeError(
                 "Unsupported HTTP content type".to_string(),
@@ -835,6 +943,15 @@ pub struct NeighborsData {
     pub neighbors: Vec<NeighborAddress>,
 }
 
+/// A descriptor of a list of known peers, some of which are only reachable by DNS name.  Sent
+/// instead of `NeighborsData` to peers that set `ServiceFlags::HOSTS`.  Each of the `hosts` is the
+/// name behind one of the synthetic addresses in `neighbors`, or behind the sender's own address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeighborHostsData {
+    pub neighbors: Vec<NeighborAddress>,
+    pub hosts: Vec<PeerHostName>,
+}
+
 /// Handshake request -- this is the first message sent to a peer.
 /// The remote peer will reply a HandshakeAccept with just a preamble
 /// if the peer accepts.  Otherwise it will get a HandshakeReject with just
@@ -856,6 +973,8 @@ pub struct HandshakeData {
 pub enum ServiceFlags {
     RELAY = 0x01,
     RPC = 0x02,
+    /// Can dial peers by DNS name (through a proxy), so send it `NeighborHosts`
+    HOSTS = 0x04,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -932,6 +1051,7 @@ pub enum StacksMessageType {
     Pong(PongData),
     NatPunchRequest(u32),
     NatPunchReply(NatPunchData),
+    NeighborHosts(NeighborHostsData),
 }
 
 /// Peer address variants
@@ -1086,6 +1206,10 @@ pub struct RPCPeerInfoData {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_pox_anchor: Option<RPCLastPoxAnchorData>,
+    /// Fee rate a transaction must exceed to get into the mempool, if the mempool is full
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mempool_eviction_floor: Option<f64>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1228,6 +1352,36 @@ pub struct RPCFeeEstimateResponse {
     pub cost_scalar_change_by_byte: f64,
 }
 
+/// Outcome of running a transaction against a chain tip without committing or relaying it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionSimulationResponse {
+    pub txid: Txid,
+    pub tip: StacksBlockId,
+    /// Whether or not the transaction could be mined on top of `tip`
+    pub okay: bool,
+    /// Why the transaction could not be mined, if `okay` is false
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cause: Option<String>,
+    /// 0x-prefixed hex serialization of the Clarity value returned by the transaction
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vm_error: Option<String>,
+    pub post_condition_mode: String,
+    pub post_condition_aborted: bool,
+    pub events: Vec<serde_json::Value>,
+    pub asset_transfers: Vec<serde_json::Value>,
+    pub execution_cost: ExecutionCost,
+    pub fee: u64,
+    /// Fee estimate for the observed execution cost, if this node runs a fee estimator
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_estimate: Option<RPCFeeEstimateResponse>,
+}
+
 #[derive(Debug, Clone, PartialEq, Copy, Hash)]
 #[repr(u8)]
 pub enum HttpVersion {
@@ -1241,6 +1395,10 @@ pub struct HttpRequestMetadata {
     pub peer: PeerHost,
     pub keep_alive: bool,
     pub canonical_stacks_tip_height: Option<u64>,
+    /// Value of the `Authorization` header, if given
+    pub authorization: Option<String>,
+    /// For HMAC-signed requests, the method, path and body digest that the signature covers
+    pub signed_content: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1316,6 +1474,52 @@ pub struct UnconfirmedTransactionResponse {
     pub status: UnconfirmedTransactionStatus,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionReplacement {
+    pub txid: Txid,
+    pub reason: String,
+}
+
+/// What became of a transaction that was replaced in the mempool, in order of replacement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionReplacementsResponse {
+    pub txid: Txid,
+    pub replacements: Vec<TransactionReplacement>,
+}
+
+/// Default number of pending transactions listed per page by the `/v2/mempool/...` endpoints
+pub const DEFAULT_PENDING_TXS_PAGE_SIZE: u64 = 50;
+/// Maximum number of pending transactions listed per page by the `/v2/mempool/...` endpoints
+pub const MAX_PENDING_TXS_PAGE_SIZE: u64 = 200;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingTransaction {
+    pub txid: Txid,
+    /// Hex-encoded transaction
+    pub tx: String,
+    pub origin_address: String,
+    pub origin_nonce: u64,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sponsor_address: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sponsor_nonce: Option<u64>,
+    pub fee: u64,
+    pub fee_rate: Option<f64>,
+    pub length: u64,
+    pub accept_time: u64,
+}
+
+/// A page of pending transactions, out of `total` that matched the query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingTransactionsResponse {
+    pub total: u64,
+    pub offset: u64,
+    pub limit: u64,
+    pub results: Vec<PendingTransaction>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct PostTransactionRequestBody {
     pub tx: String,
@@ -1373,6 +1577,8 @@ impl HttpRequestMetadata {
             peer: PeerHost::from_host_port(host, port),
             keep_alive: true,
             canonical_stacks_tip_height,
+            authorization: None,
+            signed_content: None,
         }
     }
 
@@ -1385,6 +1591,8 @@ impl HttpRequestMetadata {
             peer: peer_host,
             keep_alive: true,
             canonical_stacks_tip_height,
+            authorization: None,
+            signed_content: None,
         }
     }
 
@@ -1403,6 +1611,8 @@ impl HttpRequestMetadata {
             peer: preamble.host.clone(),
             keep_alive: preamble.keep_alive,
             canonical_stacks_tip_height,
+            authorization: preamble.headers.get("authorization").cloned(),
+            signed_content: None,
         }
     }
 }
@@ -1456,6 +1666,214 @@ pub struct RPCNeighborsInfo {
     pub outbound: Vec<RPCNeighbor>,
 }
 
+/// Items given back from a call to `/v2/admin/peers` -- one per ongoing p2p conversation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCPeerConversation {
+    pub event_id: usize,
+    pub outbound: bool,
+    pub network_id: u32,
+    pub peer_version: u32,
+    #[serde(rename = "ip")]
+    pub addrbytes: PeerAddress,
+    pub port: u16,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_key_hash: Option<Hash160>,
+    pub authenticated: bool,
+    pub data_url: String,
+    pub burnchain_tip_height: u64,
+    pub first_contact_time: u64,
+    pub last_contact_time: u64,
+    pub last_send_time: u64,
+    pub last_recv_time: u64,
+    pub last_handshake_time: u64,
+    pub bytes_tx: u64,
+    pub bytes_rx: u64,
+    pub msgs_tx: u64,
+    pub msgs_rx: u64,
+    pub msgs_rx_unsolicited: u64,
+    pub msgs_err: u64,
+}
+
+/// What an admin peer-management request applies to
+#[derive(Debug, Clone, PartialEq)]
+pub enum PeerAdminTarget {
+    /// A single peer, by its p2p address
+    Address(SocketAddr),
+    /// Every address under a prefix.  The mask is in bits of the 16-byte `PeerAddress`, so an
+    /// IPv4 /8 has a mask of 104.
+    Cidr(PeerAddress, u32),
+    /// A single ongoing conversation, by its event ID
+    Event(usize),
+}
+
+/// Peer-management actions served by `POST /v2/admin/peers/:action`
+#[derive(Debug, Clone, PartialEq)]
+pub enum PeerAdminAction {
+    /// Deny the target for the given number of seconds, or indefinitely
+    Ban(PeerAdminTarget, Option<u64>),
+    /// Allow the target for the given number of seconds, or indefinitely
+    Allow(PeerAdminTarget, Option<u64>),
+    /// Open an outbound connection to the given address
+    Connect(SocketAddr),
+    /// Drop every conversation the target matches
+    Disconnect(PeerAdminTarget),
+}
+
+/// JSON body of a `POST /v2/admin/peers/:action` request.  Exactly one of `peer`, `cidr`, and
+/// `event_id` must be given.  `duration` is in seconds.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct PeerAdminRequestBody {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peer: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cidr: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<usize>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u64>,
+}
+
+/// Struct given back from a call to `/v2/admin/peers/:action`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCPeerAdminResponse {
+    pub action: String,
+    /// Event IDs of the conversations that will be dropped
+    pub disconnected: Vec<usize>,
+    /// When the ban or allow lapses, if it is not indefinite
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<u64>,
+}
+
+impl PeerAdminTarget {
+    /// Parse `a.b.c.d/n` or `x:y::z/n` into a prefix and a mask over the 16-byte `PeerAddress`
+    pub fn parse_cidr(cidr: &str) -> Result<(PeerAddress, u32), String> {
+        let mut parts = cidr.splitn(2, '/');
+        let ip_str = parts.next().unwrap_or("");
+        let mask_str = parts
+            .next()
+            .ok_or_else(|| format!("CIDR '{}' has no mask", cidr))?;
+        let ip = ip_str
+            .parse::<IpAddr>()
+            .map_err(|e| format!("Invalid CIDR address '{}': {}", ip_str, e))?;
+        let mask = mask_str
+            .parse::<u32>()
+            .map_err(|e| format!("Invalid CIDR mask '{}': {}", mask_str, e))?;
+
+        // IPv4 addresses live in the last 32 bits of a PeerAddress
+        let (offset, max) = if ip.is_ipv4() { (96, 32) } else { (0, 128) };
+        if mask == 0 || mask > max {
+            return Err(format!("CIDR mask must be between 1 and {}", max));
+        }
+        Ok((PeerAddress::from_ip(&ip), mask + offset))
+    }
+
+    /// Inverse of `parse_cidr`
+    pub fn cidr_to_string(prefix: &PeerAddress, mask: u32) -> String {
+        let ip = prefix.to_socketaddr(0).ip();
+        if prefix.is_ipv4() && mask > 96 {
+            format!("{}/{}", ip, mask - 96)
+        } else {
+            format!("{}/{}", ip, mask)
+        }
+    }
+
+    fn from_request_body(body: &PeerAdminRequestBody) -> Result<PeerAdminTarget, String> {
+        match (&body.peer, &body.cidr, &body.event_id) {
+            (Some(peer), None, None) => peer
+                .parse::<SocketAddr>()
+                .map(PeerAdminTarget::Address)
+                .map_err(|e| format!("Invalid peer address '{}': {}", peer, e)),
+            (None, Some(cidr), None) => PeerAdminTarget::parse_cidr(cidr)
+                .map(|(prefix, mask)| PeerAdminTarget::Cidr(prefix, mask)),
+            (None, None, Some(event_id)) => Ok(PeerAdminTarget::Event(*event_id)),
+            _ => Err("Exactly one of 'peer', 'cidr', or 'event_id' must be given".to_string()),
+        }
+    }
+
+    fn to_request_body(&self) -> PeerAdminRequestBody {
+        match self {
+            PeerAdminTarget::Address(addr) => PeerAdminRequestBody {
+                peer: Some(addr.to_string()),
+                ..PeerAdminRequestBody::default()
+            },
+            PeerAdminTarget::Cidr(prefix, mask) => PeerAdminRequestBody {
+                cidr: Some(PeerAdminTarget::cidr_to_string(prefix, *mask)),
+                ..PeerAdminRequestBody::default()
+            },
+            PeerAdminTarget::Event(event_id) => PeerAdminRequestBody {
+                event_id: Some(*event_id),
+                ..PeerAdminRequestBody::default()
+            },
+        }
+    }
+
+    /// Does this target match the conversation with the given event ID and address?
+    pub fn matches(&self, event_id: usize, addrbytes: &PeerAddress, port: u16) -> bool {
+        match self {
+            PeerAdminTarget::Address(addr) => {
+                PeerAddress::from_socketaddr(addr) == *addrbytes && addr.port() == port
+            }
+            PeerAdminTarget::Cidr(prefix, mask) => addrbytes.is_in_cidr(prefix, *mask),
+            PeerAdminTarget::Event(eid) => *eid == event_id,
+        }
+    }
+}
+
+impl PeerAdminAction {
+    /// The `:action` path segment for this action
+    pub fn name(&self) -> &'static str {
+        match self {
+            PeerAdminAction::Ban(..) => "ban",
+            PeerAdminAction::Allow(..) => "allow",
+            PeerAdminAction::Connect(..) => "connect",
+            PeerAdminAction::Disconnect(..) => "disconnect",
+        }
+    }
+
+    pub fn from_request_body(
+        action: &str,
+        body: &PeerAdminRequestBody,
+    ) -> Result<PeerAdminAction, String> {
+        if body.duration == Some(0) {
+            return Err("Duration must be positive".to_string());
+        }
+        if body.duration.is_some() && (action == "connect" || action == "disconnect") {
+            return Err(format!("Duration does not apply to '{}'", action));
+        }
+
+        let target = PeerAdminTarget::from_request_body(body)?;
+        match action {
+            "ban" => Ok(PeerAdminAction::Ban(target, body.duration)),
+            "allow" => Ok(PeerAdminAction::Allow(target, body.duration)),
+            "connect" => match target {
+                PeerAdminTarget::Address(addr) => Ok(PeerAdminAction::Connect(addr)),
+                _ => Err("Only a 'peer' address can be connected to".to_string()),
+            },
+            "disconnect" => Ok(PeerAdminAction::Disconnect(target)),
+            _ => Err(format!("Unknown peer admin action '{}'", action)),
+        }
+    }
+
+    pub fn to_request_body(&self) -> PeerAdminRequestBody {
+        match self {
+            PeerAdminAction::Ban(target, duration) | PeerAdminAction::Allow(target, duration) => {
+                PeerAdminRequestBody {
+                    duration: *duration,
+                    ..target.to_request_body()
+                }
+            }
+            PeerAdminAction::Connect(addr) => PeerAdminTarget::Address(*addr).to_request_body(),
+            PeerAdminAction::Disconnect(target) => target.to_request_body(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TipRequest {
     UseLatestAnchoredTip,
@@ -1475,9 +1893,14 @@ pub enum HttpRequestType {
     GetMicroblocksConfirmed(HttpRequestMetadata, StacksBlockId),
     GetMicroblocksUnconfirmed(HttpRequestMetadata, StacksBlockId, u16),
     GetTransactionUnconfirmed(HttpRequestMetadata, Txid),
+    GetTransactionReplacements(HttpRequestMetadata, Txid),
+    GetPendingTransactions(HttpRequestMetadata, PendingTxQuery, u64, u64, TipRequest),
     PostTransaction(HttpRequestMetadata, StacksTransaction, Option<Attachment>),
     PostBlock(HttpRequestMetadata, ConsensusHash, StacksBlock),
     PostMicroblock(HttpRequestMetadata, StacksMicroblock, TipRequest),
+    SimulateTransaction(HttpRequestMetadata, StacksTransaction, TipRequest),
+    GetAdminPeers(HttpRequestMetadata),
+    PostAdminPeerAction(HttpRequestMetadata, PeerAdminAction),
     GetAccount(HttpRequestMetadata, PrincipalData, TipRequest, bool),
     GetDataVar(
         HttpRequestMetadata,
@@ -1527,6 +1950,7 @@ pub enum HttpRequestType {
         TipRequest,
     ),
     MemPoolQuery(HttpRequestMetadata, MemPoolSyncData, Option<Txid>),
+    GetEventStream(HttpRequestMetadata, Vec<ChainEventKind>),
     /// catch-all for any errors we should surface from parsing
     ClientError(HttpRequestMetadata, ClientError),
 }
@@ -1638,12 +2062,18 @@ pub enum HttpResponseType {
     GetContractSrc(HttpResponseMetadata, ContractSrcResponse),
     GetIsTraitImplemented(HttpResponseMetadata, GetIsTraitImplementedResponse),
     UnconfirmedTransaction(HttpResponseMetadata, UnconfirmedTransactionResponse),
+    TransactionReplacements(HttpResponseMetadata, TransactionReplacementsResponse),
+    PendingTransactions(HttpResponseMetadata, PendingTransactionsResponse),
     GetAttachment(HttpResponseMetadata, GetAttachmentResponse),
     GetAttachmentsInv(HttpResponseMetadata, GetAttachmentsInvResponse),
     MemPoolTxStream(HttpResponseMetadata),
+    EventStream(HttpResponseMetadata),
     MemPoolTxs(HttpResponseMetadata, Option<Txid>, Vec<StacksTransaction>),
     OptionsPreflight(HttpResponseMetadata),
     TransactionFeeEstimation(HttpResponseMetadata, RPCFeeEstimateResponse),
+    TransactionSimulation(HttpResponseMetadata, TransactionSimulationResponse),
+    AdminPeers(HttpResponseMetadata, Vec<RPCPeerConversation>),
+    AdminPeerAction(HttpResponseMetadata, RPCPeerAdminResponse),
     // peer-given error responses
     BadRequest(HttpResponseMetadata, String),
     BadRequestJSON(HttpResponseMetadata, serde_json::Value),
@@ -1651,6 +2081,8 @@ pub enum HttpResponseType {
     PaymentRequired(HttpResponseMetadata, String),
     Forbidden(HttpResponseMetadata, String),
     NotFound(HttpResponseMetadata, String),
+    /// Rate-limited; the client may retry after the given number of seconds
+    TooManyRequests(HttpResponseMetadata, u64, String),
     ServerError(HttpResponseMetadata, String),
     ServiceUnavailable(HttpResponseMetadata, String),
     Error(HttpResponseMetadata, u16, String),
@@ -1684,6 +2116,7 @@ pub enum StacksMessageID {
     Pong = 16,
     NatPunchRequest = 17,
     NatPunchReply = 18,
+    NeighborHosts = 19,
     // reserved
     Reserved = 255,
 }
@@ -2142,6 +2575,7 @@ pub mod test {
     use crate::net::codec::*;
     use crate::net::connection::*;
     use crate::net::db::*;
+    use crate::net::event_stream::EventStreamHub;
     use crate::net::neighbors::*;
     use crate::net::p2p::*;
     use crate::net::poll::*;
@@ -2613,6 +3047,8 @@ pub mod test {
             (),
             BitcoinIndexer,
         >,
+        /// Handed to the RPC server, so clients can stream chain events
+        pub event_stream: Option<EventStreamHub>,
     }
 
     impl<'a> TestPeer<'a> {
@@ -2883,6 +3319,7 @@ pub mod test {
                 mempool: Some(mempool),
                 chainstate_path: chainstate_path,
                 coord: coord,
+                event_stream: None,
             }
         }
 
@@ -2967,7 +3404,10 @@ pub mod test {
                 false,
                 ibd,
                 100,
-                &RPCHandlerArgs::default(),
+                &RPCHandlerArgs {
+                    event_stream: self.event_stream.as_ref(),
+                    ..RPCHandlerArgs::default()
+                },
                 &mut HashSet::new(),
             );
 
@@ -753,6 +753,64 @@ impl NeighborWalk {
         ret
     }
 
+    /// Remember the DNS names a neighbor sent us alongside its neighbors, so we can dial them
+    /// through our proxy later.  Only names that stand for one of the given neighbors (or for
+    /// the sender itself) are kept.  Returns the neighbors, less any named neighbors whose
+    /// names were not given.
+    fn store_neighbor_hosts(
+        network: &mut PeerNetwork,
+        sender: &NeighborKey,
+        data: &NeighborHostsData,
+    ) -> Result<Vec<NeighborAddress>, net_error> {
+        let mut named = HashSet::new();
+        let mut tx = network.peerdb.tx_begin()?;
+        for host in data.hosts.iter() {
+            let addrbytes = host.to_peer_address();
+            if addrbytes != sender.addrbytes
+                && !data.neighbors.iter().any(|n| n.addrbytes == addrbytes)
+            {
+                debug!("Ignoring unrequested peer host {} from {:?}", host, sender);
+                continue;
+            }
+            PeerDB::set_peer_host(&mut tx, host)?;
+            named.insert(addrbytes);
+        }
+        tx.commit()?;
+
+        Ok(data
+            .neighbors
+            .iter()
+            .filter(|n| !n.addrbytes.is_host_address() || named.contains(&n.addrbytes))
+            .cloned()
+            .collect())
+    }
+
+    /// Add the neighbors we already know about (and that are fresh) to the frontier.
+    /// Returns the neighbors we still need to resolve.
+    fn add_frontier_neighbors(
+        &mut self,
+        network: &PeerNetwork,
+        network_id: u32,
+        block_height: u64,
+        neighbors: Vec<NeighborAddress>,
+    ) -> Result<Vec<NeighborAddress>, net_error> {
+        let (mut found, to_resolve) = NeighborWalk::lookup_stale_neighbors(
+            network.peerdb.conn(),
+            network_id,
+            block_height,
+            &neighbors,
+        )?;
+
+        for (_naddr, neighbor) in found.drain() {
+            self.new_frontier
+                .insert(neighbor.addr.clone(), neighbor.clone());
+            self.frontier
+                .insert(neighbor.addr.clone(), neighbor.clone());
+        }
+
+        Ok(to_resolve)
+    }
+
     /// Try to finish the getneighbors request to cur_neighbor
     /// Returns the list of neighbors we need to resolve
     /// Return None if we're not done yet, or haven't started yet.
@@ -795,21 +853,33 @@ impl NeighborWalk {
                         );
                         let neighbors =
                             NeighborWalk::filter_sensible_neighbors(data.neighbors.clone());
-                        let (mut found, to_resolve) = NeighborWalk::lookup_stale_neighbors(
-                            network.peerdb.conn(),
+                        self.add_frontier_neighbors(
+                            network,
                             message.preamble.network_id,
                             block_height,
-                            &neighbors,
-                        )?;
-
-                        for (_naddr, neighbor) in found.drain() {
-                            self.new_frontier
-                                .insert(neighbor.addr.clone(), neighbor.clone());
-                            self.frontier
-                                .insert(neighbor.addr.clone(), neighbor.clone());
-                        }
-
-                        Ok(Some(to_resolve))
+                            neighbors,
+                        )
+                        .map(Some)
+                    }
+                    StacksMessageType::NeighborHosts(ref data) => {
+                        debug!(
+                            "{:?}: Got NeighborHosts from {:?}: {:?} {:?}",
+                            &self.local_peer, &self.cur_neighbor.addr, data.neighbors, data.hosts
+                        );
+                        let neighbors = NeighborWalk::filter_sensible_neighbors(
+                            NeighborWalk::store_neighbor_hosts(
+                                network,
+                                &self.cur_neighbor.addr,
+                                data,
+                            )?,
+                        );
+                        self.add_frontier_neighbors(
+                            network,
+                            message.preamble.network_id,
+                            block_height,
+                            neighbors,
+                        )
+                        .map(Some)
                     }
                     StacksMessageType::Nack(ref data) => {
                         debug!(
@@ -1191,6 +1261,26 @@ impl NeighborWalk {
                                     NeighborWalk::filter_sensible_neighbors(data.neighbors.clone());
                                 self.resolved_getneighbors_neighbors.insert(nkey, neighbors);
                             }
+                            StacksMessageType::NeighborHosts(ref data) => {
+                                debug!(
+                                    "{:?}: Got NeighborHosts from {:?}: {:?} {:?}",
+                                    &self.local_peer, &nkey, &data.neighbors, &data.hosts
+                                );
+                                match NeighborWalk::store_neighbor_hosts(network, &nkey, data) {
+                                    Ok(neighbors) => {
+                                        let neighbors =
+                                            NeighborWalk::filter_sensible_neighbors(neighbors);
+                                        self.resolved_getneighbors_neighbors
+                                            .insert(nkey, neighbors);
+                                    }
+                                    Err(e) => {
+                                        warn!(
+                                            "{:?}: Failed to store peer hosts from {:?}: {:?}",
+                                            &self.local_peer, &nkey, &e
+                                        );
+                                    }
+                                }
+                            }
                             StacksMessageType::Nack(ref data) => {
                                 // not broken; likely because it hasn't gotten to processing our
                                 // handshake yet.  We'll just ignore it.
@@ -3113,9 +3203,73 @@ mod test {
     use crate::util_lib::test::*;
     use stacks_common::util::hash::*;
     use stacks_common::util::sleep_ms;
+    use std::convert::TryFrom;
 
     const TEST_IN_OUT_DEGREES: u64 = 0x1;
 
+    #[test]
+    #[ignore]
+    fn test_step_walk_1_neighbor_socks5_proxy() {
+        with_timeout(600, || {
+            let mut peer_1_config = TestPeerConfig::from_port(34100);
+            let mut peer_2_config = TestPeerConfig::from_port(34102);
+
+            // peer 2 is only known to peer 1 by name, which only the proxy can resolve
+            let peer_2_host = PeerHostName::try_from("peer2.onion").unwrap();
+            let mut proxy_hosts = HashMap::new();
+            proxy_hosts.insert(
+                peer_2_host.to_string(),
+                format!("127.0.0.1:{}", peer_2_config.server_port)
+                    .parse::<SocketAddr>()
+                    .unwrap(),
+            );
+            let proxy = socks::test::TestSocks5Proxy::spawn(34104, proxy_hosts);
+
+            peer_2_config.connection_opts.public_host = Some(peer_2_host.clone());
+            let mut neighbor_2 = peer_2_config.to_neighbor();
+            neighbor_2.addr.addrbytes = peer_2_host.to_peer_address();
+
+            peer_1_config.connection_opts.socks5_proxy = Some(proxy.addr.clone());
+            peer_1_config.add_neighbor(&neighbor_2);
+
+            let mut peer_1 = TestPeer::new(peer_1_config);
+            let mut peer_2 = TestPeer::new(peer_2_config);
+            {
+                let mut tx = peer_1.network.peerdb.tx_begin().unwrap();
+                PeerDB::set_peer_host(&mut tx, &peer_2_host).unwrap();
+                tx.commit().unwrap();
+            }
+
+            let mut i = 0;
+            while peer_1
+                .network
+                .get_neighbor_stats(&neighbor_2.addr)
+                .map(|stats| stats.last_handshake_time == 0)
+                .unwrap_or(true)
+            {
+                let _ = peer_1.step();
+                let _ = peer_2.step();
+                i += 1;
+            }
+
+            debug!("Completed walk round {} step(s)", i);
+
+            // peer 1 dialed peer 2 by name, through the proxy
+            assert!(proxy
+                .get_requests()
+                .contains(&format!("peer2.onion:{}", neighbor_2.addr.port)));
+
+            // peer 1 knows peer 2 by its name's address
+            let stats_1 = peer_1.network.get_neighbor_stats(&neighbor_2.addr).unwrap();
+            assert!(stats_1.last_recv_time > 0);
+            assert!(stats_1.bytes_rx > 0);
+
+            // peer 1 never learns a public IP it doesn't have
+            assert!(peer_1.network.local_peer.public_ip_address.is_none());
+            assert!(!peer_1.network.public_ip_learned);
+        })
+    }
+
     #[test]
     #[ignore]
     fn test_step_walk_1_neighbor_plain() {
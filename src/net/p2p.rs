@@ -75,6 +75,7 @@ use crate::net::relay::*;
 use crate::net::relay::*;
 use crate::net::rpc::RPCHandlerArgs;
 use crate::net::server::*;
+use crate::net::socks::{Socks5Handshake, Socks5Target};
 use crate::net::Error as net_error;
 use crate::net::Neighbor;
 use crate::net::NeighborKey;
@@ -263,8 +264,16 @@ pub struct PeerNetwork {
     pub sockets: HashMap<usize, mio_net::TcpStream>,
     pub events: HashMap<NeighborKey, usize>,
     pub connecting: HashMap<usize, (mio_net::TcpStream, bool, u64)>, // (socket, outbound?, connection sent timestamp)
+    pub proxy_handshakes: HashMap<usize, Socks5Handshake>, // connecting sockets still opening a tunnel through the SOCKS5 proxy
     pub bans: HashSet<usize>,
 
+    // peer management requested through the admin RPC interface.  Connects and disconnects
+    // are queued, since the HTTP peer runs while the network state is checked out.  CIDR bans
+    // with a deadline live only in RAM, since the peer DB's prefix rules have no deadline.
+    pub admin_connects: Vec<NeighborKey>,
+    pub admin_disconnects: HashSet<usize>,
+    pub admin_denied_cidrs: Vec<(PeerAddress, u32, u64)>,
+
     // ongoing messages the network is sending via the p2p interface (not bound to a specific
     // conversation).
     pub relay_handles: HashMap<usize, VecDeque<ReplyHandleP2P>>,
@@ -382,10 +391,27 @@ impl PeerNetwork {
         epochs: Vec<StacksEpoch>,
     ) -> PeerNetwork {
         let http = HttpPeer::new(connection_opts.clone(), 0);
-        let pub_ip = connection_opts.public_ip_address.clone();
-        let pub_ip_learned = pub_ip.is_none();
+        let pub_ip = match connection_opts.public_host {
+            // a node known by DNS name is known by that name's synthetic address
+            Some(ref host) => Some((
+                host.to_peer_address(),
+                connection_opts
+                    .public_ip_address
+                    .as_ref()
+                    .map(|(_, port)| *port)
+                    .unwrap_or(local_peer.port),
+            )),
+            None => connection_opts.public_ip_address,
+        };
+        // behind a proxy, peers would only ever tell us the proxy's address
+        let pub_ip_learned = pub_ip.is_none() && connection_opts.socks5_proxy.is_none();
         local_peer.public_ip_address = pub_ip.clone();
 
+        if connection_opts.socks5_proxy.is_some() {
+            // we can dial peers by DNS name, so peers may tell us about them
+            local_peer.services |= ServiceFlags::HOSTS as u16;
+        }
+
         if connection_opts.disable_inbound_handshakes {
             debug!("{:?}: disable inbound handshakes", &local_peer);
         }
@@ -424,8 +450,13 @@ impl PeerNetwork {
             sockets: HashMap::new(),
             events: HashMap::new(),
             connecting: HashMap::new(),
+            proxy_handshakes: HashMap::new(),
             bans: HashSet::new(),
 
+            admin_connects: vec![],
+            admin_disconnects: HashSet::new(),
+            admin_denied_cidrs: vec![],
+
             relay_handles: HashMap::new(),
             relayer_stats: RelayerStats::new(),
 
@@ -892,6 +923,13 @@ impl PeerNetwork {
                 );
                 return Err(net_error::Denied);
             }
+            if self.is_admin_denied(&neighbor.addrbytes) {
+                debug!(
+                    "{:?}: Neighbor {:?} is denied by an admin ban; will not connect",
+                    &self.local_peer, neighbor
+                );
+                return Err(net_error::Denied);
+            }
         }
 
         // already connected?
@@ -903,17 +941,61 @@ impl PeerNetwork {
             return Ok(event_id);
         }
 
+        // peers known by DNS name can only be reached through the proxy, which resolves the name
+        let proxy_target = match self.connection_opts.socks5_proxy {
+            Some(ref proxy) => {
+                let target = if neighbor.addrbytes.is_host_address() {
+                    let host = PeerDB::get_peer_host(self.peerdb.conn(), &neighbor.addrbytes)?
+                        .ok_or_else(|| {
+                            net_error::ProxyError(format!("No DNS name known for {:?}", neighbor))
+                        })?;
+                    Socks5Target::Host(host, neighbor.port)
+                } else {
+                    Socks5Target::Addr(neighbor.addrbytes.to_socketaddr(neighbor.port))
+                };
+                Some((*proxy, target))
+            }
+            None => {
+                if neighbor.addrbytes.is_host_address() {
+                    debug!(
+                        "{:?}: no SOCKS5 proxy, so cannot connect to named peer {:?}",
+                        &self.local_peer, neighbor
+                    );
+                    return Err(net_error::ProxyError(
+                        "No SOCKS5 proxy configured".to_string(),
+                    ));
+                }
+                None
+            }
+        };
+
         let next_event_id = match self.network {
             None => {
                 test_debug!("{:?}: network not connected", &self.local_peer);
                 return Err(net_error::NotConnected);
             }
             Some(ref mut network) => {
-                let sock = NetworkState::connect(&neighbor.addrbytes.to_socketaddr(neighbor.port))?;
+                let (sock, proxy_handshake) = match proxy_target {
+                    Some((proxy, target)) => {
+                        debug!(
+                            "{:?}: connect to {:?} via SOCKS5 proxy {}",
+                            &self.local_peer, &target, &proxy
+                        );
+                        let (sock, handshake) = Socks5Handshake::connect(&proxy, target)?;
+                        (sock, Some(handshake))
+                    }
+                    None => (
+                        NetworkState::connect(&neighbor.addrbytes.to_socketaddr(neighbor.port))?,
+                        None,
+                    ),
+                };
                 let hint_event_id = network.next_event_id()?;
                 let registered_event_id =
                     network.register(self.p2p_network_handle, hint_event_id, &sock)?;
 
+                if let Some(handshake) = proxy_handshake {
+                    self.proxy_handshakes.insert(registered_event_id, handshake);
+                }
                 self.connecting
                     .insert(registered_event_id, (sock, true, get_epoch_time_secs()));
                 registered_event_id
@@ -1348,6 +1430,13 @@ impl PeerNetwork {
             );
             return Err(net_error::Denied);
         }
+        if self.is_admin_denied(&neighbor_key.addrbytes) {
+            info!(
+                "{:?}: Peer {:?} is denied by an admin ban; dropping",
+                &self.local_peer, neighbor_key
+            );
+            return Err(net_error::Denied);
+        }
 
         // already connected?
         if let Some(event_id) = self.get_event_id(&neighbor_key) {
@@ -1428,7 +1517,18 @@ impl PeerNetwork {
                 return Err(net_error::SocketError);
             }
         };
+        self.register_peer_at(event_id, socket, outbound, client_addr)
+    }
 
+    /// Register a socket that leads to `client_addr`.  This is the socket's peer address, unless
+    /// the socket is a tunnel through our SOCKS5 proxy.
+    fn register_peer_at(
+        &mut self,
+        event_id: usize,
+        socket: mio_net::TcpStream,
+        outbound: bool,
+        client_addr: SocketAddr,
+    ) -> Result<(), net_error> {
         let neighbor_opt = match self.lookup_peer(self.chain_view.burn_block_height, &client_addr) {
             Ok(neighbor_opt) => neighbor_opt,
             Err(e) => {
@@ -1572,6 +1672,7 @@ impl PeerNetwork {
                 }
             }
         }
+        self.proxy_handshakes.remove(&event_id);
 
         self.relay_handles.remove(&event_id);
         self.peers.remove(&event_id);
@@ -1604,6 +1705,220 @@ impl PeerNetwork {
         self.deregister_neighbor(neighbor);
     }
 
+    /// Is this address under a CIDR ban that was set through the admin RPC interface and has
+    /// not yet lapsed?
+    pub fn is_admin_denied(&self, addrbytes: &PeerAddress) -> bool {
+        let now = get_epoch_time_secs();
+        self.admin_denied_cidrs
+            .iter()
+            .any(|(prefix, mask, deadline)| now < *deadline && addrbytes.is_in_cidr(prefix, *mask))
+    }
+
+    /// Describe each ongoing conversation, for the admin RPC interface
+    pub fn admin_list_peers(&self) -> Vec<RPCPeerConversation> {
+        let mut ret: Vec<_> = self
+            .peers
+            .iter()
+            .map(|(event_id, convo)| {
+                let nk = convo.best_effort_neighbor_key();
+                RPCPeerConversation {
+                    event_id: *event_id,
+                    outbound: convo.is_outbound(),
+                    network_id: nk.network_id,
+                    peer_version: nk.peer_version,
+                    addrbytes: nk.addrbytes,
+                    port: nk.port,
+                    public_key_hash: convo.get_public_key_hash(),
+                    authenticated: convo.is_authenticated(),
+                    data_url: convo.data_url.to_string(),
+                    burnchain_tip_height: convo.get_burnchain_tip_height(),
+                    first_contact_time: convo.stats.first_contact_time,
+                    last_contact_time: convo.stats.last_contact_time,
+                    last_send_time: convo.stats.last_send_time,
+                    last_recv_time: convo.stats.last_recv_time,
+                    last_handshake_time: convo.stats.last_handshake_time,
+                    bytes_tx: convo.stats.bytes_tx,
+                    bytes_rx: convo.stats.bytes_rx,
+                    msgs_tx: convo.stats.msgs_tx,
+                    msgs_rx: convo.stats.msgs_rx,
+                    msgs_rx_unsolicited: convo.stats.msgs_rx_unsolicited,
+                    msgs_err: convo.stats.msgs_err,
+                }
+            })
+            .collect();
+        ret.sort_by_key(|peer| peer.event_id);
+        ret
+    }
+
+    /// Find the event IDs of the conversations an admin request targets.  A peer address
+    /// matches both the socket a conversation runs over and the p2p address it handshook with,
+    /// since inbound peers connect from ephemeral ports.
+    fn admin_target_events(&self, target: &PeerAdminTarget) -> Vec<usize> {
+        let mut ret: Vec<usize> = self
+            .peers
+            .iter()
+            .filter(|(event_id, convo)| {
+                target.matches(**event_id, &convo.peer_addrbytes, convo.peer_port)
+                    || target.matches(**event_id, &convo.handshake_addrbytes, convo.handshake_port)
+            })
+            .map(|(event_id, _)| *event_id)
+            .collect();
+        ret.sort();
+        ret
+    }
+
+    /// Resolve an admin request's target to a single peer address, if it names one
+    fn admin_target_neighbor_key(
+        &self,
+        target: &PeerAdminTarget,
+    ) -> Result<Option<NeighborKey>, net_error> {
+        match target {
+            PeerAdminTarget::Address(addr) => Ok(Some(NeighborKey::from_socketaddr(
+                self.peer_version,
+                self.local_peer.network_id,
+                addr,
+            ))),
+            PeerAdminTarget::Event(event_id) => match self.peers.get(event_id) {
+                Some(convo) => Ok(Some(convo.best_effort_neighbor_key())),
+                None => Err(net_error::PeerNotConnected),
+            },
+            PeerAdminTarget::Cidr(..) => Ok(None),
+        }
+    }
+
+    /// Carry out a peer-management request from the admin RPC interface.
+    /// Bans and allows are written to the peer DB right away.  Connects and disconnects need
+    /// the network state, so they are queued and carried out by `process_admin_requests()` later
+    /// in the same pass.
+    /// A ban or allow without a duration is indefinite.  For a CIDR prefix, an indefinite rule is
+    /// stored in the peer DB, while one with a duration is kept in RAM and lapses on restart.
+    pub fn admin_peer_action(
+        &mut self,
+        action: &PeerAdminAction,
+    ) -> Result<RPCPeerAdminResponse, net_error> {
+        let now = get_epoch_time_secs();
+        let mut until = None;
+        let mut disconnected = vec![];
+        match action {
+            PeerAdminAction::Ban(target, duration) => {
+                until = duration.map(|d| now.saturating_add(d));
+                let deny_deadline = until.unwrap_or(i64::MAX as u64);
+                let nk_opt = self.admin_target_neighbor_key(target)?;
+
+                let mut tx = self.peerdb.tx_begin()?;
+                if let Some(nk) = nk_opt {
+                    info!("Admin ban of {:?} until {}", &nk, deny_deadline);
+                    PeerDB::set_deny_peer(
+                        &mut tx,
+                        nk.network_id,
+                        &nk.addrbytes,
+                        nk.port,
+                        deny_deadline,
+                    )?;
+                } else if let PeerAdminTarget::Cidr(prefix, mask) = target {
+                    info!("Admin ban of {}/{} until {}", prefix, mask, deny_deadline);
+                    if until.is_some() {
+                        PeerDB::set_deny_cidr_peers(&mut tx, prefix, *mask, deny_deadline)?;
+                        self.admin_denied_cidrs
+                            .push((*prefix, *mask, deny_deadline));
+                    } else {
+                        PeerDB::add_deny_cidr(&mut tx, prefix, *mask)?;
+                    }
+                }
+                tx.commit()?;
+
+                disconnected = self.admin_target_events(target);
+            }
+            PeerAdminAction::Allow(target, duration) => {
+                until = duration.map(|d| now.saturating_add(d));
+                let allow_deadline = until.map(|t| t as i64).unwrap_or(-1);
+                let nk_opt = self.admin_target_neighbor_key(target)?;
+
+                let mut tx = self.peerdb.tx_begin()?;
+                if let Some(nk) = nk_opt {
+                    info!("Admin allow of {:?} until {}", &nk, allow_deadline);
+                    PeerDB::set_allow_peer(
+                        &mut tx,
+                        nk.network_id,
+                        &nk.addrbytes,
+                        nk.port,
+                        allow_deadline,
+                    )?;
+                    // lift any ban on it
+                    PeerDB::set_deny_peer(&mut tx, nk.network_id, &nk.addrbytes, nk.port, 0)?;
+                } else if let PeerAdminTarget::Cidr(prefix, mask) = target {
+                    info!(
+                        "Admin allow of {}/{} until {}",
+                        prefix, mask, allow_deadline
+                    );
+                    if until.is_some() {
+                        PeerDB::set_allow_cidr_peers(&mut tx, prefix, *mask, allow_deadline)?;
+                    } else {
+                        PeerDB::add_allow_cidr(&mut tx, prefix, *mask)?;
+                    }
+                    // lift any timed ban on the same prefix
+                    self.admin_denied_cidrs
+                        .retain(|(p, m, _)| !(p == prefix && m == mask));
+                }
+                tx.commit()?;
+            }
+            PeerAdminAction::Connect(addr) => {
+                let nk = NeighborKey::from_socketaddr(
+                    self.peer_version,
+                    self.local_peer.network_id,
+                    addr,
+                );
+                if self.is_bound(&nk)
+                    || self.is_admin_denied(&nk.addrbytes)
+                    || PeerDB::is_peer_denied(
+                        self.peerdb.conn(),
+                        nk.network_id,
+                        &nk.addrbytes,
+                        nk.port,
+                    )?
+                {
+                    return Err(net_error::Denied);
+                }
+                info!("Admin connect to {:?}", &nk);
+                self.admin_connects.push(nk);
+            }
+            PeerAdminAction::Disconnect(target) => {
+                disconnected = self.admin_target_events(target);
+            }
+        }
+
+        for event_id in disconnected.iter() {
+            info!("Admin disconnect of event {}", event_id);
+            self.admin_disconnects.insert(*event_id);
+        }
+
+        Ok(RPCPeerAdminResponse {
+            action: action.name().to_string(),
+            disconnected,
+            until,
+        })
+    }
+
+    /// Carry out the connects and disconnects queued by the admin RPC interface, and forget
+    /// lapsed admin CIDR bans
+    fn process_admin_requests(&mut self) {
+        for event_id in mem::take(&mut self.admin_disconnects).into_iter() {
+            self.deregister_peer(event_id);
+        }
+        for nk in mem::take(&mut self.admin_connects).into_iter() {
+            if let Err(e) = self.connect_peer(&nk) {
+                warn!(
+                    "{:?}: Failed to connect to {:?} at admin request: {:?}",
+                    &self.local_peer, &nk, &e
+                );
+            }
+        }
+
+        let now = get_epoch_time_secs();
+        self.admin_denied_cidrs
+            .retain(|(_, _, deadline)| now < *deadline);
+    }
+
     /// Sign a p2p message to be sent to a particular peer we're having a conversation with.
     /// The peer must already be connected.
     pub fn sign_for_peer(
@@ -1770,9 +2085,43 @@ impl PeerNetwork {
     fn process_connecting_sockets(&mut self, poll_state: &mut NetworkPollState) -> () {
         for event_id in poll_state.ready.iter() {
             if self.connecting.contains_key(event_id) {
+                // a proxied socket needs its tunnel opened first
+                let mut proxied_addr = None;
+                if let Some(handshake) = self.proxy_handshakes.get_mut(event_id) {
+                    let (socket, ..) = self
+                        .connecting
+                        .get_mut(event_id)
+                        .expect("BUG: connecting socket disappeared");
+                    match handshake.try_advance(socket) {
+                        Ok(true) => {
+                            proxied_addr = Some(handshake.target().to_socketaddr());
+                        }
+                        Ok(false) => {
+                            // wait for the proxy
+                            continue;
+                        }
+                        Err(e) => {
+                            debug!(
+                                "{:?}: SOCKS5 proxy failed to connect event {} to {:?}: {:?}",
+                                &self.local_peer,
+                                event_id,
+                                handshake.target(),
+                                &e
+                            );
+                            self.deregister_peer(*event_id);
+                            continue;
+                        }
+                    }
+                    self.proxy_handshakes.remove(event_id);
+                }
+
                 let (socket, outbound, _) = self.connecting.remove(event_id).unwrap();
                 let sock_str = format!("{:?}", &socket);
-                if let Err(_e) = self.register_peer(*event_id, socket, outbound) {
+                let res = match proxied_addr {
+                    Some(addr) => self.register_peer_at(*event_id, socket, outbound, addr),
+                    None => self.register_peer(*event_id, socket, outbound),
+                };
+                if let Err(_e) = res {
                     debug!(
                         "{:?}: Failed to register connecting socket on event {} ({}): {:?}",
                         &self.local_peer, event_id, sock_str, &_e
@@ -5417,6 +5766,9 @@ impl PeerNetwork {
         })
         .expect("FATAL: with_network_state should be infallable (not connected)");
 
+        // carry out peer management requested over HTTP, now that we have sockets again
+        self.process_admin_requests();
+
         self.dispatch_network(
             &mut network_result,
             sortdb,
@@ -5916,7 +6268,6 @@ mod test {
                     origin_nonce,
                     &sponsor_addr,
                     sponsor_nonce,
-                    None,
                 )
                 .unwrap();
 
@@ -5994,7 +6345,6 @@ mod test {
                     origin_nonce,
                     &sponsor_addr,
                     sponsor_nonce,
-                    None,
                 )
                 .unwrap();
 
@@ -6178,7 +6528,6 @@ mod test {
                     origin_nonce,
                     &sponsor_addr,
                     sponsor_nonce,
-                    None,
                 )
                 .unwrap();
 
@@ -6369,7 +6718,6 @@ mod test {
                     origin_nonce,
                     &sponsor_addr,
                     sponsor_nonce,
-                    None,
                 )
                 .unwrap();
 
@@ -6574,7 +6922,6 @@ mod test {
                     origin_nonce,
                     &sponsor_addr,
                     sponsor_nonce,
-                    None,
                 )
                 .unwrap();
 
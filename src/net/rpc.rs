@@ -17,6 +17,7 @@
  along with Blockstack. If not, see <http://www.gnu.org/licenses/>.
 */
 
+use std::cmp;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
@@ -55,10 +56,12 @@ use crate::net::connection::ConnectionHttp;
 use crate::net::connection::ConnectionOptions;
 use crate::net::connection::ReplyHandleHttp;
 use crate::net::db::PeerDB;
+use crate::net::event_stream::{ChainEventKind, EventStreamHub, EventStreamSubscription};
 use crate::net::http::*;
 use crate::net::p2p::PeerMap;
 use crate::net::p2p::PeerNetwork;
 use crate::net::relay::Relayer;
+use crate::net::server::{RPCClientKey, RPCRateLimiter};
 use crate::net::BlocksDatum;
 use crate::net::Error as net_error;
 use crate::net::HttpRequestMetadata;
@@ -70,6 +73,7 @@ use crate::net::MicroblocksData;
 use crate::net::NeighborAddress;
 use crate::net::NeighborsData;
 use crate::net::PeerAddress;
+use crate::net::PeerAdminAction;
 use crate::net::PeerHost;
 use crate::net::ProtocolFamily;
 use crate::net::RPCFeeEstimate;
@@ -77,6 +81,9 @@ use crate::net::RPCFeeEstimateResponse;
 use crate::net::StacksHttp;
 use crate::net::StacksHttpMessage;
 use crate::net::StacksMessageType;
+use crate::net::TransactionReplacement;
+use crate::net::TransactionReplacementsResponse;
+use crate::net::TransactionSimulationResponse;
 use crate::net::UnconfirmedTransactionResponse;
 use crate::net::UnconfirmedTransactionStatus;
 use crate::net::UrlString;
@@ -89,6 +96,7 @@ use crate::net::{
 };
 use crate::net::{BlocksData, GetIsTraitImplementedResponse};
 use crate::net::{ClientError, TipRequest};
+use crate::net::{PendingTransaction, PendingTransactionsResponse};
 use crate::net::{
     RPCAffirmationData, RPCLastPoxAnchorData, RPCPeerInfoData, RPCPoxContractVersion,
     RPCPoxInfoData,
@@ -97,6 +105,7 @@ use crate::net::{RPCNeighbor, RPCNeighborsInfo};
 use crate::util_lib::db::DBConn;
 use crate::util_lib::db::Error as db_error;
 use clarity::vm::database::clarity_store::make_contract_hash_key;
+use clarity::vm::events::{STXEventType, StacksTransactionEvent};
 use clarity::vm::types::TraitIdentifier;
 use clarity::vm::ClarityVersion;
 use clarity::vm::{
@@ -113,6 +122,7 @@ use clarity::vm::{
     types::{PrincipalData, QualifiedContractIdentifier, StandardPrincipalData},
     ClarityName, ContractName, SymbolicExpression, Value,
 };
+use stacks_common::util::get_epoch_time_ms;
 use stacks_common::util::get_epoch_time_secs;
 use stacks_common::util::hash::Hash160;
 use stacks_common::util::hash::{hex_bytes, to_hex};
@@ -145,6 +155,8 @@ pub struct RPCHandlerArgs<'a> {
     pub cost_estimator: Option<&'a dyn CostEstimator>,
     pub fee_estimator: Option<&'a dyn FeeEstimator>,
     pub cost_metric: Option<&'a dyn CostMetric>,
+    /// Where clients can subscribe to a stream of chain events, if enabled
+    pub event_stream: Option<&'a EventStreamHub>,
 }
 
 pub struct ConversationHttp {
@@ -170,6 +182,13 @@ pub struct ConversationHttp {
         bool,
     )>,
 
+    // ongoing chain event stream.  Once started, it is the last reply this conversation sends.
+    event_stream: Option<(
+        ReplyHandleHttp,
+        HttpChunkedTransferWriterState,
+        EventStreamSubscription,
+    )>,
+
     // our outstanding request/response to the remote peer, if any
     pending_request: Option<ReplyHandleHttp>,
     pending_response: Option<HttpResponseType>,
@@ -273,6 +292,7 @@ impl RPCPeerInfoData {
                 anchor_block_hash: network.last_anchor_block_hash.clone(),
                 anchor_block_txid: network.last_anchor_block_txid.clone(),
             }),
+            mempool_eviction_floor: None,
         }
     }
 }
@@ -612,6 +632,7 @@ impl ConversationHttp {
             conn_id: conn_id,
             timeout: conn_opts.timeout,
             reply_streams: VecDeque::new(),
+            event_stream: None,
             peer_addr: peer_addr,
             outbound_url: outbound_url,
             peer_host: peer_host,
@@ -732,17 +753,22 @@ impl ConversationHttp {
         req: &HttpRequestType,
         network: &PeerNetwork,
         chainstate: &StacksChainState,
+        mempool: &MemPoolDB,
         handler_args: &RPCHandlerArgs,
         canonical_stacks_tip_height: u64,
     ) -> Result<(), net_error> {
         let response_metadata =
             HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
-        let pi = RPCPeerInfoData::from_network(
+        let mut pi = RPCPeerInfoData::from_network(
             network,
             chainstate,
             handler_args.exit_at_block_height.clone(),
             &handler_args.genesis_chainstate_hash,
         );
+        pi.mempool_eviction_floor = mempool.get_eviction_floor().unwrap_or_else(|e| {
+            warn!("Failed to query mempool eviction floor: {:?}", &e);
+            None
+        });
         let response = HttpResponseType::PeerInfo(response_metadata, pi);
         response.send(http, fd)
     }
@@ -904,6 +930,154 @@ impl ConversationHttp {
         response.send(http, fd)
     }
 
+    /// Check that the client may make this request, per our RPC scopes and auth tokens.
+    /// Replies with a 401 if the request needs a token and lacks a valid one, or a 403 if its
+    /// token doesn't grant the request's scope or the scope is disabled.  Failures are charged
+    /// to the client's IP address, which gets a 429 once it has failed too often.  Returns who
+    /// the client is, for rate-limiting, or None if the request may not be served.
+    fn handle_authorize<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        peer_addr: &SocketAddr,
+        rate_limiter: &mut RPCRateLimiter,
+        conn_opts: &ConnectionOptions,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<Option<RPCClientKey>, net_error> {
+        let now_ms = get_epoch_time_ms();
+        if let Err(retry_after) =
+            rate_limiter.check_auth_failures(peer_addr.ip(), conn_opts, now_ms)
+        {
+            debug!(
+                "Refusing {} request from {}: too many failed authorizations",
+                req.get_path(),
+                peer_addr.ip()
+            );
+            let response_metadata = HttpResponseMetadata::from_http_request_type(
+                req,
+                Some(canonical_stacks_tip_height),
+            );
+            let response = HttpResponseType::TooManyRequests(
+                response_metadata,
+                retry_after,
+                "Too many failed authorizations".to_string(),
+            );
+            response.send(http, fd)?;
+            return Ok(None);
+        }
+
+        let err = match authorize_request(
+            req,
+            &conn_opts.rpc_public_scopes,
+            &conn_opts.rpc_auth_tokens,
+            get_epoch_time_secs(),
+        ) {
+            Ok(Some(token_name)) => {
+                return Ok(Some(RPCClientKey::Token(token_name)));
+            }
+            Ok(None) => {
+                return Ok(Some(RPCClientKey::Addr(peer_addr.ip())));
+            }
+            Err(err) => err,
+        };
+
+        debug!("Refusing {} request: {}", req.get_path(), &err);
+        rate_limiter.charge_auth_failure(peer_addr.ip(), conn_opts, now_ms);
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        let response = match err {
+            RPCAuthError::BadCredentials(..) | RPCAuthError::Unauthenticated(..) => {
+                HttpResponseType::Unauthorized(response_metadata, err.to_string())
+            }
+            RPCAuthError::Disabled(..) | RPCAuthError::Forbidden(..) => {
+                HttpResponseType::Forbidden(response_metadata, err.to_string())
+            }
+        };
+        response.send(http, fd)?;
+        Ok(None)
+    }
+
+    /// Check that the client has budget left for this request, and charge it.  Replies with a
+    /// 429 if not.  Returns whether or not the request may be served.
+    fn handle_rate_limit<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        client: &RPCClientKey,
+        rate_limiter: &mut RPCRateLimiter,
+        conn_opts: &ConnectionOptions,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<bool, net_error> {
+        let scope = req.required_scope();
+        let retry_after =
+            match rate_limiter.check_request(client, scope, conn_opts, get_epoch_time_ms()) {
+                Ok(()) => {
+                    return Ok(true);
+                }
+                Err(retry_after) => retry_after,
+            };
+
+        debug!(
+            "Rate-limiting {} request from {:?} for {} seconds",
+            req.get_path(),
+            client,
+            retry_after
+        );
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        let response = HttpResponseType::TooManyRequests(
+            response_metadata,
+            retry_after,
+            format!("Rate limit exceeded for '{}' endpoints", scope),
+        );
+        response.send(http, fd)?;
+        Ok(false)
+    }
+
+    /// Handle a GET for our ongoing p2p conversations and their statistics
+    fn handle_get_admin_peers<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        network: &PeerNetwork,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        let response = HttpResponseType::AdminPeers(response_metadata, network.admin_list_peers());
+        response.send(http, fd)
+    }
+
+    /// Handle a POST to ban, allow, connect to, or disconnect from a peer.
+    /// Takes effect in the peer network's current pass.
+    fn handle_post_admin_peer_action<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        network: &mut PeerNetwork,
+        action: &PeerAdminAction,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        let response = match network.admin_peer_action(action) {
+            Ok(result) => HttpResponseType::AdminPeerAction(response_metadata, result),
+            Err(net_error::PeerNotConnected) => {
+                HttpResponseType::NotFound(response_metadata, "No such conversation".to_string())
+            }
+            Err(net_error::Denied) => HttpResponseType::BadRequest(
+                response_metadata,
+                "Peer is denied, or is this node".to_string(),
+            ),
+            Err(e) => {
+                let msg = format!("Failed to {} peer: {:?}", action.name(), &e);
+                warn!("{}", &msg);
+                HttpResponseType::ServerError(response_metadata, msg)
+            }
+        };
+        response.send(http, fd)
+    }
+
     /// Handle a not-found
     fn handle_notfound<W: Write>(
         http: &mut StacksHttp,
@@ -1442,7 +1616,7 @@ impl ConversationHttp {
         args: &[Value],
         options: &ConnectionOptions,
         canonical_stacks_tip_height: u64,
-    ) -> Result<(), net_error> {
+    ) -> Result<ExecutionCost, net_error> {
         let response_metadata =
             HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
         let contract_identifier =
@@ -1457,6 +1631,7 @@ impl ConversationHttp {
         let mut cost_limit = options.read_only_call_limit.clone();
         cost_limit.write_length = 0;
         cost_limit.write_count = 0;
+        let mut cost = ExecutionCost::zero();
 
         let data_opt_res =
             chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
@@ -1496,7 +1671,14 @@ impl ConversationHttp {
                         // can be called, and also circumvents limitations on `define-read-only`
                         // functions that can not use `contrac-call?`, even when calling other
                         // read-only functions
-                        env.execute_contract(&contract_identifier, function.as_str(), &args, false)
+                        let result = env.execute_contract(
+                            &contract_identifier,
+                            function.as_str(),
+                            &args,
+                            false,
+                        );
+                        cost = env.global_context.cost_track.get_total();
+                        result
                     },
                 )
             });
@@ -1536,7 +1718,8 @@ impl ConversationHttp {
                 HttpResponseType::NotFound(response_metadata, "Chain tip not found".into())
             }
         };
-        response.send(http, fd).map(|_| ())
+        response.send(http, fd)?;
+        Ok(cost)
     }
 
     /// Handle a GET to fetch a contract's source code, given the chain tip.  Optionally returns a
@@ -1836,6 +2019,142 @@ impl ConversationHttp {
         return response.send(http, fd).map(|_| ());
     }
 
+    /// Handle a GET for the transactions that replaced a mempool transaction.
+    /// The response will be synchronously written to the fd.
+    fn handle_gettransaction_replacements<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        mempool: &MemPoolDB,
+        txid: &Txid,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        let response = match MemPoolDB::get_tx_replacements(mempool.conn(), txid) {
+            Ok(replacements) => HttpResponseType::TransactionReplacements(
+                response_metadata,
+                TransactionReplacementsResponse {
+                    txid: *txid,
+                    replacements: replacements
+                        .into_iter()
+                        .map(|r| TransactionReplacement {
+                            txid: r.replaced_by,
+                            reason: r.reason,
+                        })
+                        .collect(),
+                },
+            ),
+            Err(e) => {
+                warn!("Failed to query replacements of {}: {:?}", txid, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    format!("Failed to query replacements of {}", txid),
+                )
+            }
+        };
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Handle a GET for a page of pending transactions.  Mined transactions stay in the mempool
+    /// until they're garbage-collected, so address queries only list the transactions that spend
+    /// a nonce at or above the account's nonce in the given chain tip.
+    fn handle_get_pending_transactions<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: Option<&StacksBlockId>,
+        mempool: &MemPoolDB,
+        query: &PendingTxQuery,
+        offset: u64,
+        limit: u64,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        let query = match (query, tip) {
+            (
+                PendingTxQuery::Address {
+                    address,
+                    is_origin,
+                    min_nonce,
+                    max_nonce,
+                },
+                Some(tip),
+            ) => {
+                let principal: PrincipalData = (*address).into();
+                let chain_nonce = match chainstate.maybe_read_only_clarity_tx(
+                    &sortdb.index_conn(),
+                    tip,
+                    |clarity_tx| StacksChainState::get_nonce(clarity_tx, &principal),
+                ) {
+                    Ok(Some(nonce)) => nonce,
+                    Ok(None) | Err(_) => {
+                        let response = HttpResponseType::NotFound(
+                            response_metadata,
+                            "Chain tip not found".into(),
+                        );
+                        return response.send(http, fd).map(|_| ());
+                    }
+                };
+                PendingTxQuery::Address {
+                    address: *address,
+                    is_origin: *is_origin,
+                    min_nonce: cmp::max(*min_nonce, chain_nonce),
+                    max_nonce: *max_nonce,
+                }
+            }
+            _ => query.clone(),
+        };
+        let response = match MemPoolDB::get_pending_txs(mempool.conn(), &query, offset, limit) {
+            Ok((total, pending_txs)) => HttpResponseType::PendingTransactions(
+                response_metadata,
+                PendingTransactionsResponse {
+                    total,
+                    offset,
+                    limit,
+                    results: pending_txs
+                        .into_iter()
+                        .map(|pending_tx| {
+                            let md = pending_tx.tx_info.metadata;
+                            let is_sponsored = pending_tx.tx_info.tx.sponsor_address().is_some();
+                            PendingTransaction {
+                                txid: md.txid,
+                                tx: to_hex(&pending_tx.tx_info.tx.serialize_to_vec()),
+                                origin_address: md.origin_address.to_string(),
+                                origin_nonce: md.origin_nonce,
+                                sponsor_address: if is_sponsored {
+                                    Some(md.sponsor_address.to_string())
+                                } else {
+                                    None
+                                },
+                                sponsor_nonce: if is_sponsored {
+                                    Some(md.sponsor_nonce)
+                                } else {
+                                    None
+                                },
+                                fee: md.tx_fee,
+                                fee_rate: pending_tx.fee_rate,
+                                length: md.len,
+                                accept_time: md.accept_time,
+                            }
+                        })
+                        .collect(),
+                },
+            ),
+            Err(e) => {
+                warn!("Failed to query pending transactions: {:?}", &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query pending transactions".to_string(),
+                )
+            }
+        };
+        response.send(http, fd).map(|_| ())
+    }
+
     /// Load up the canonical Stacks chain tip.  Note that this is subject to both burn chain block
     /// Stacks block availability -- different nodes with different partial replicas of the Stacks chain state
     /// will return different values here.
@@ -2030,6 +2349,196 @@ impl ConversationHttp {
         }
     }
 
+    /// Handle a transaction simulation request.  Run the transaction on top of the given chain tip
+    /// in a throwaway block that gets rolled back afterwards, so neither the chainstate nor the
+    /// mempool is modified.  Signatures are only verified if the transaction carries them, so
+    /// unsigned transactions can be simulated too.
+    fn handle_simulate_transaction<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        handler_args: &RPCHandlerArgs,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        consensus_hash: &ConsensusHash,
+        block_hash: &BlockHeaderHash,
+        tx: &StacksTransaction,
+        options: &ConnectionOptions,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<ExecutionCost, net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        let burn_tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+        let stacks_epoch = SortitionDB::get_stacks_epoch(sortdb.conn(), burn_tip.block_height)?
+                .ok_or_else(|| {
+                    warn!(
+                        "Failed to simulate transaction because could not load Stacks epoch for canonical burn height = {}",
+                        burn_tip.block_height
+                    );
+                    net_error::ChainstateError("Could not load Stacks epoch for canonical burn height".into())
+                })?;
+        let ast_rules = SortitionDB::get_ast_rules(sortdb.conn(), burn_tip.block_height)?;
+
+        let txid = tx.txid();
+        let tip = StacksBlockHeader::make_index_block_hash(consensus_hash, block_hash);
+        let mainnet = chainstate.mainnet;
+        let chain_id = chainstate.chain_id;
+
+        // The simulation holds the chainstate's write transaction while it runs, so it gets no
+        // more reads or runtime than a read-only call.  Its writes are rolled back, so they are
+        // only bounded by the block limit.
+        let mut cost_limit = options.read_only_call_limit.clone();
+        cost_limit.write_length = stacks_epoch.block_limit.write_length;
+        cost_limit.write_count = stacks_epoch.block_limit.write_count;
+
+        let burn_dbconn = sortdb.index_conn();
+        let mut clarity_tx = chainstate.block_begin(
+            &burn_dbconn,
+            consensus_hash,
+            block_hash,
+            &MINER_BLOCK_CONSENSUS_HASH,
+            &MINER_BLOCK_HEADER_HASH,
+        );
+        let epoch = clarity_tx.get_epoch();
+        let cost_track = match clarity_tx.with_clarity_db_readonly(|clarity_db| {
+            LimitedCostTracker::new_mid_block(mainnet, chain_id, cost_limit, clarity_db, epoch)
+        }) {
+            Ok(cost_track) => cost_track,
+            Err(e) => {
+                clarity_tx.rollback_block();
+                warn!(
+                    "Failed to simulate transaction {} because could not load cost contracts: {:?}",
+                    &txid, &e
+                );
+                let response = HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to load cost contracts".to_string(),
+                );
+                response.send(http, fd)?;
+                return Ok(ExecutionCost::zero());
+            }
+        };
+        let (processed, cost_track) =
+            clarity_tx.with_temporary_cost_tracker(cost_track, |clarity_tx| {
+                if tx.auth.is_unsigned() {
+                    StacksChainState::process_unsigned_transaction(clarity_tx, tx, true, ast_rules)
+                } else {
+                    StacksChainState::process_transaction(clarity_tx, tx, true, ast_rules)
+                }
+            });
+        clarity_tx.rollback_block();
+        let cost = cost_track.get_total();
+
+        let post_condition_mode = match tx.post_condition_mode {
+            TransactionPostConditionMode::Allow => "allow",
+            TransactionPostConditionMode::Deny => "deny",
+        }
+        .to_string();
+
+        let simulation = match processed {
+            Ok((fee, receipt)) => {
+                let committed = !receipt.post_condition_aborted;
+                let events: Vec<_> = receipt
+                    .events
+                    .iter()
+                    .enumerate()
+                    .map(|(event_index, event)| {
+                        (event, event.json_serialize(event_index, &txid, committed))
+                    })
+                    .collect();
+                let asset_transfers = events
+                    .iter()
+                    .filter(|(event, _)| {
+                        !matches!(
+                            event,
+                            StacksTransactionEvent::SmartContractEvent(_)
+                                | StacksTransactionEvent::STXEvent(STXEventType::STXLockEvent(_))
+                        )
+                    })
+                    .map(|(_, event_json)| event_json.clone())
+                    .collect();
+
+                let fee_estimate =
+                    if let Some((_, fee_estimator, metric)) = handler_args.get_estimators_ref() {
+                        let tx_len = tx.tx_len();
+                        let scalar_cost = metric.from_cost_and_len(
+                            &receipt.execution_cost,
+                            &stacks_epoch.block_limit,
+                            tx_len,
+                        );
+                        match fee_estimator.get_rate_estimates() {
+                            Ok(fee_rates) => {
+                                let mut estimations =
+                                    RPCFeeEstimate::estimate_fees(scalar_cost, fee_rates).to_vec();
+                                let minimum_fee = tx_len * MINIMUM_TX_FEE_RATE_PER_BYTE;
+                                for estimate in estimations.iter_mut() {
+                                    if estimate.fee < minimum_fee {
+                                        estimate.fee = minimum_fee;
+                                    }
+                                }
+                                Some(RPCFeeEstimateResponse {
+                                    estimated_cost: receipt.execution_cost.clone(),
+                                    estimations,
+                                    estimated_cost_scalar: scalar_cost,
+                                    cost_scalar_change_by_byte: metric.change_per_byte(),
+                                })
+                            }
+                            Err(e) => {
+                                debug!(
+                                    "Failed to estimate fees for simulated tx {}: {:?}",
+                                    &txid, &e
+                                );
+                                None
+                            }
+                        }
+                    } else {
+                        None
+                    };
+
+                TransactionSimulationResponse {
+                    txid,
+                    tip,
+                    okay: true,
+                    cause: None,
+                    result: Some(format!("0x{}", receipt.result.serialize())),
+                    vm_error: receipt.vm_error,
+                    post_condition_mode,
+                    post_condition_aborted: receipt.post_condition_aborted,
+                    events: events
+                        .into_iter()
+                        .map(|(_, event_json)| event_json)
+                        .collect(),
+                    asset_transfers,
+                    execution_cost: receipt.execution_cost,
+                    fee,
+                    fee_estimate,
+                }
+            }
+            Err(e) => {
+                debug!("Simulated transaction {} is invalid: {:?}", &txid, &e);
+                TransactionSimulationResponse {
+                    txid,
+                    tip,
+                    okay: false,
+                    cause: Some(e.to_string()),
+                    result: None,
+                    vm_error: None,
+                    post_condition_mode,
+                    post_condition_aborted: false,
+                    events: vec![],
+                    asset_transfers: vec![],
+                    execution_cost: ExecutionCost::zero(),
+                    fee: 0,
+                    fee_estimate: None,
+                }
+            }
+        };
+
+        let response = HttpResponseType::TransactionSimulation(response_metadata, simulation);
+        response.send(http, fd)?;
+        Ok(cost)
+    }
+
     /// Handle a transaction.  Directly submit it to the mempool so the client can see any
     /// rejection reasons up-front (different from how the peer network handles it).  Indicate
     /// whether or not the transaction was accepted (and thus needs to be forwarded) in the return
@@ -2383,6 +2892,46 @@ impl ConversationHttp {
         response.send(http, fd).and_then(|_| Ok(stream))
     }
 
+    /// Handle a GET for a stream of chain events.  If the client can be subscribed, the response's
+    /// preamble will be synchronously written to the fd, and the events follow as they happen.
+    /// Return the client's subscription, so we can continue sending it events.
+    fn handle_get_event_stream<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        event_stream: Option<&EventStreamHub>,
+        kinds: &[ChainEventKind],
+        canonical_stacks_tip_height: u64,
+    ) -> Result<Option<EventStreamSubscription>, net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        let event_stream = match event_stream {
+            Some(event_stream) => event_stream,
+            None => {
+                let response = HttpResponseType::NotFound(
+                    response_metadata,
+                    "Event streaming is not enabled on this node".to_string(),
+                );
+                return response.send(http, fd).map(|_| None);
+            }
+        };
+
+        match event_stream.subscribe(kinds.to_vec()) {
+            Some(subscription) => {
+                debug!("Begin event stream"; "events" => ?kinds);
+                let response = HttpResponseType::EventStream(response_metadata);
+                response.send(http, fd).map(|_| Some(subscription))
+            }
+            None => {
+                let response = HttpResponseType::ServiceUnavailable(
+                    response_metadata,
+                    "Too many clients are streaming events".to_string(),
+                );
+                response.send(http, fd).map(|_| None)
+            }
+        }
+    }
+
     /// Handle an external HTTP request.
     /// Some requests, such as those for blocks, will create new reply streams.  This method adds
     /// those new streams into the `reply_streams` set.
@@ -2395,12 +2944,41 @@ impl ConversationHttp {
         sortdb: &SortitionDB,
         chainstate: &mut StacksChainState,
         mempool: &mut MemPoolDB,
+        rate_limiter: &mut RPCRateLimiter,
         handler_opts: &RPCHandlerArgs,
     ) -> Result<Option<StacksMessageType>, net_error> {
         let mut reply = self.connection.make_relay_handle(self.conn_id)?;
         let keep_alive = req.metadata().keep_alive;
         let mut ret = None;
 
+        let client = match ConversationHttp::handle_authorize(
+            &mut self.connection.protocol,
+            &mut reply,
+            &req,
+            &self.peer_addr,
+            rate_limiter,
+            &network.connection_opts,
+            network.burnchain_tip.canonical_stacks_tip_height,
+        )? {
+            Some(client) => client,
+            None => {
+                self.reply_streams.push_back((reply, None, keep_alive));
+                return Ok(ret);
+            }
+        };
+        if !ConversationHttp::handle_rate_limit(
+            &mut self.connection.protocol,
+            &mut reply,
+            &req,
+            &client,
+            rate_limiter,
+            &network.connection_opts,
+            network.burnchain_tip.canonical_stacks_tip_height,
+        )? {
+            self.reply_streams.push_back((reply, None, keep_alive));
+            return Ok(ret);
+        }
+
         let stream_opt = match req {
             HttpRequestType::GetInfo(ref _md) => {
                 ConversationHttp::handle_getinfo(
@@ -2409,6 +2987,7 @@ impl ConversationHttp {
                     &req,
                     network,
                     chainstate,
+                    mempool,
                     handler_opts,
                     network.burnchain_tip.canonical_stacks_tip_height,
                 )?;
@@ -2447,6 +3026,28 @@ impl ConversationHttp {
                 )?;
                 None
             }
+            HttpRequestType::GetAdminPeers(ref _md) => {
+                ConversationHttp::handle_get_admin_peers(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    network,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )?;
+                None
+            }
+            HttpRequestType::PostAdminPeerAction(ref _md, ref action) => {
+                let canonical_stacks_tip_height = network.burnchain_tip.canonical_stacks_tip_height;
+                ConversationHttp::handle_post_admin_peer_action(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    network,
+                    action,
+                    canonical_stacks_tip_height,
+                )?;
+                None
+            }
             HttpRequestType::GetHeaders(ref _md, ref quantity, ref tip_req) => {
                 if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
                     &mut self.connection.protocol,
@@ -2525,6 +3126,57 @@ impl ConversationHttp {
                 )?;
                 None
             }
+            HttpRequestType::GetTransactionReplacements(ref _md, ref txid) => {
+                ConversationHttp::handle_gettransaction_replacements(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    mempool,
+                    txid,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )?;
+                None
+            }
+            HttpRequestType::GetPendingTransactions(
+                ref _md,
+                ref query,
+                offset,
+                limit,
+                ref tip_req,
+            ) => {
+                // only address queries are checked against a chain tip
+                let tip = match query {
+                    PendingTxQuery::Address { .. } => {
+                        ConversationHttp::handle_load_stacks_chain_tip(
+                            &mut self.connection.protocol,
+                            &mut reply,
+                            &req,
+                            tip_req,
+                            sortdb,
+                            chainstate,
+                            network.burnchain_tip.canonical_stacks_tip_height,
+                        )?
+                        .map(Some)
+                    }
+                    PendingTxQuery::ContractCall(_) => Some(None),
+                };
+                if let Some(tip) = tip {
+                    ConversationHttp::handle_get_pending_transactions(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        tip.as_ref(),
+                        mempool,
+                        query,
+                        offset,
+                        limit,
+                        network.burnchain_tip.canonical_stacks_tip_height,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::GetAccount(ref _md, ref principal, ref tip_req, ref with_proof) => {
                 if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
                     &mut self.connection.protocol,
@@ -2687,7 +3339,7 @@ impl ConversationHttp {
                     chainstate,
                     network.burnchain_tip.canonical_stacks_tip_height,
                 )? {
-                    ConversationHttp::handle_readonly_function_call(
+                    let cost = ConversationHttp::handle_readonly_function_call(
                         &mut self.connection.protocol,
                         &mut reply,
                         &req,
@@ -2703,6 +3355,12 @@ impl ConversationHttp {
                         &self.connection.options,
                         network.burnchain_tip.canonical_stacks_tip_height,
                     )?;
+                    rate_limiter.charge_read_only_cost(
+                        &client,
+                        &cost,
+                        &network.connection_opts,
+                        get_epoch_time_ms(),
+                    );
                 }
                 None
             }
@@ -2823,6 +3481,49 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::SimulateTransaction(ref _md, ref tx, ref tip_req) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_req,
+                    sortdb,
+                    chainstate,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )? {
+                    if let Some((consensus_hash, block_hash)) =
+                        ConversationHttp::handle_load_stacks_chain_tip_hashes(
+                            &mut self.connection.protocol,
+                            &mut reply,
+                            &req,
+                            tip,
+                            chainstate,
+                            network.burnchain_tip.canonical_stacks_tip_height,
+                        )?
+                    {
+                        let cost = ConversationHttp::handle_simulate_transaction(
+                            &mut self.connection.protocol,
+                            &mut reply,
+                            &req,
+                            handler_opts,
+                            sortdb,
+                            chainstate,
+                            &consensus_hash,
+                            &block_hash,
+                            tx,
+                            &self.connection.options,
+                            network.burnchain_tip.canonical_stacks_tip_height,
+                        )?;
+                        rate_limiter.charge_read_only_cost(
+                            &client,
+                            &cost,
+                            &network.connection_opts,
+                            get_epoch_time_ms(),
+                        );
+                    }
+                }
+                None
+            }
             HttpRequestType::PostMicroblock(ref _md, ref mblock, ref tip_req) => {
                 if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
                     &mut self.connection.protocol,
@@ -2924,6 +3625,38 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::GetEventStream(ref _md, ref kinds) => {
+                if self.event_stream.is_some() {
+                    let response_metadata = HttpResponseMetadata::from_http_request_type(
+                        &req,
+                        Some(network.burnchain_tip.canonical_stacks_tip_height),
+                    );
+                    let response = HttpResponseType::BadRequest(
+                        response_metadata,
+                        "Already streaming events on this connection".to_string(),
+                    );
+                    response
+                        .send(&mut self.connection.protocol, &mut reply)
+                        .map(|_| ())?;
+                    None
+                } else if let Some(subscription) = ConversationHttp::handle_get_event_stream(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    handler_opts.event_stream,
+                    kinds,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )? {
+                    self.event_stream = Some((
+                        reply,
+                        HttpChunkedTransferWriterState::new(STREAM_CHUNK_SIZE as usize),
+                        subscription,
+                    ));
+                    return Ok(ret);
+                } else {
+                    None
+                }
+            }
             HttpRequestType::ClientError(ref _md, ref err) => {
                 let response_metadata = HttpResponseMetadata::from_http_request_type(
                     &req,
@@ -3088,9 +3821,74 @@ impl ConversationHttp {
                 self.keep_alive = false;
             }
         }
+
+        if self.reply_streams.is_empty() {
+            self.send_event_stream();
+        }
         Ok(())
     }
 
+    /// Make progress on our chain event stream, if we have one.  Events are written out as soon
+    /// as they are available; the stream only ends if the client falls too far behind (or goes
+    /// away), at which point the connection is closed.
+    fn send_event_stream(&mut self) {
+        let _self_str = format!("{}", &self);
+        let (reply, chunk_state, subscription) = match self.event_stream.as_mut() {
+            Some(event_stream) => event_stream,
+            None => {
+                return;
+            }
+        };
+
+        let mut encoder = HttpChunkedTransferWriter::from_writer_state(reply, chunk_state);
+        let res = if encoder.corked() {
+            // stream already ended; still draining
+            Ok(false)
+        } else {
+            subscription.stream_to(&mut encoder).and_then(|open| {
+                if open {
+                    encoder.flush_buffered()?;
+                } else {
+                    // last chunk
+                    encoder.flush()?;
+                    encoder.cork();
+                }
+                Ok(open)
+            })
+        };
+
+        let done = match res {
+            Ok(true) => false,
+            Ok(false) => {
+                // try moving some data to the connection only once we're done streaming, since
+                // this drops the reply handle's pipe once it is drained
+                match reply.try_flush() {
+                    Ok(drained) => drained,
+                    Err(e) => {
+                        warn!("{}: Broken HTTP connection: {:?}", &_self_str, &e);
+                        true
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("{}: Failed to send event stream: {:?}", &_self_str, &e);
+                true
+            }
+        };
+
+        if done {
+            test_debug!("{}: done with event stream", &_self_str);
+            self.event_stream = None;
+            self.total_reply_count += 1;
+            self.keep_alive = false;
+        }
+    }
+
+    /// Is this conversation streaming chain events?
+    pub fn is_streaming_events(&self) -> bool {
+        self.event_stream.is_some()
+    }
+
     pub fn try_send_recv_response(
         req: ReplyHandleHttp,
     ) -> Result<HttpResponseType, Result<ReplyHandleHttp, net_error>> {
@@ -3171,6 +3969,7 @@ impl ConversationHttp {
             && self.connection.inbox_len() == 0
             && self.connection.outbox_len() == 0
             && self.reply_streams.len() == 0
+            && self.event_stream.is_none()
     }
 
     /// Is the conversation out of pending data?
@@ -3209,6 +4008,7 @@ impl ConversationHttp {
         sortdb: &SortitionDB,
         chainstate: &mut StacksChainState,
         mempool: &mut MemPoolDB,
+        rate_limiter: &mut RPCRateLimiter,
         handler_args: &RPCHandlerArgs,
     ) -> Result<Vec<StacksMessageType>, net_error> {
         // if we have an in-flight error, then don't take any more requests.
@@ -3245,7 +4045,15 @@ impl ConversationHttp {
                     let start_time = Instant::now();
                     let path = req.get_path();
                     let msg_opt = monitoring::instrument_http_request_handler(req, |req| {
-                        self.handle_request(req, network, sortdb, chainstate, mempool, handler_args)
+                        self.handle_request(
+                            req,
+                            network,
+                            sortdb,
+                            chainstate,
+                            mempool,
+                            rate_limiter,
+                            handler_args,
+                        )
                     })?;
 
                     debug!("Processed HTTPRequest"; "path" => %path, "processing_time_ms" => start_time.elapsed().as_millis(), "conn_id" => self.conn_id, "peer_addr" => &self.peer_addr);
@@ -3424,6 +4232,31 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new get-tx-replacements request
+    pub fn new_gettransaction_replacements(&self, txid: Txid) -> HttpRequestType {
+        HttpRequestType::GetTransactionReplacements(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            txid,
+        )
+    }
+
+    /// Make a new request for a page of pending transactions
+    pub fn new_get_pending_transactions(
+        &self,
+        query: PendingTxQuery,
+        offset: u64,
+        limit: u64,
+        tip_req: TipRequest,
+    ) -> HttpRequestType {
+        HttpRequestType::GetPendingTransactions(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            query,
+            offset,
+            limit,
+            tip_req,
+        )
+    }
+
     /// Make a new post-transaction request
     pub fn new_post_transaction(&self, tx: StacksTransaction) -> HttpRequestType {
         HttpRequestType::PostTransaction(
@@ -3455,6 +4288,39 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new transaction-simulation request
+    pub fn new_simulate_transaction(
+        &self,
+        tx: StacksTransaction,
+        tip_req: TipRequest,
+    ) -> HttpRequestType {
+        HttpRequestType::SimulateTransaction(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            tx,
+            tip_req,
+        )
+    }
+
+    /// Make a new request for our peer's ongoing p2p conversations, authenticated with its admin
+    /// token
+    pub fn new_get_admin_peers(&self, admin_token: &str) -> HttpRequestType {
+        let mut md = HttpRequestMetadata::from_host(self.peer_host.clone(), None);
+        md.authorization = Some(format!("Bearer {}", admin_token));
+        HttpRequestType::GetAdminPeers(md)
+    }
+
+    /// Make a new request to ban, allow, connect to, or disconnect from a peer, authenticated
+    /// with our peer's admin token
+    pub fn new_post_admin_peer_action(
+        &self,
+        action: PeerAdminAction,
+        admin_token: &str,
+    ) -> HttpRequestType {
+        let mut md = HttpRequestMetadata::from_host(self.peer_host.clone(), None);
+        md.authorization = Some(format!("Bearer {}", admin_token));
+        HttpRequestType::PostAdminPeerAction(md, action)
+    }
+
     /// Make a new request for an account
     pub fn new_getaccount(
         &self,
@@ -3608,6 +4474,7 @@ mod test {
     use crate::chainstate::stacks::db::StreamCursor;
     use crate::chainstate::stacks::miner::*;
     use crate::chainstate::stacks::test::*;
+    use crate::chainstate::stacks::tests::make_user_stacks_transfer;
     use crate::chainstate::stacks::Error as chain_error;
     use crate::chainstate::stacks::*;
     use crate::net::codec::*;
@@ -3625,6 +4492,7 @@ mod test {
     use crate::types::chainstate::BurnchainHeaderHash;
 
     use crate::core::mempool::{BLOOM_COUNTER_ERROR_RATE, MAX_BLOOM_COUNTER_TXS};
+    use crate::core::{FIRST_BURNCHAIN_CONSENSUS_HASH, FIRST_STACKS_BLOCK_HASH};
 
     use super::*;
 
@@ -4040,7 +4908,6 @@ mod test {
                 origin_nonce,
                 &sponsor_addr,
                 sponsor_nonce,
-                None,
             )
             .unwrap();
         }
@@ -4127,6 +4994,7 @@ mod test {
                 &mut peer_1_sortdb,
                 &mut peer_1_stacks_node.chainstate,
                 &mut peer_1_mempool,
+                &mut RPCRateLimiter::new(),
                 &RPCHandlerArgs::default(),
             )
             .unwrap();
@@ -4162,6 +5030,7 @@ mod test {
                 &mut peer_2_sortdb,
                 &mut peer_2_stacks_node.chainstate,
                 &mut peer_2_mempool,
+                &mut RPCRateLimiter::new(),
                 &RPCHandlerArgs::default(),
             )
             .unwrap();
@@ -4215,6 +5084,7 @@ mod test {
                 &mut peer_1_sortdb,
                 &mut peer_1_stacks_node.chainstate,
                 &mut peer_1_mempool,
+                &mut RPCRateLimiter::new(),
                 &RPCHandlerArgs::default(),
             )
             .unwrap();
@@ -5074,6 +5944,187 @@ mod test {
         );
     }
 
+    #[test]
+    #[ignore]
+    fn test_rpc_transaction_replacements() {
+        let replaced_txids = RefCell::new(vec![]);
+
+        test_rpc(
+            function_name!(),
+            40230,
+            40231,
+            50230,
+            50231,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let mut mempool = peer_server.mempool.take().unwrap();
+                let mut mempool_tx = mempool.tx_begin().unwrap();
+                let origin = StacksAddress {
+                    version: 22,
+                    bytes: Hash160([0x11; 20]),
+                };
+
+                // each tx replaces the last one by fee
+                let mut txids = vec![];
+                for i in 1..4 {
+                    let txid = Txid([i; 32]);
+                    MemPoolDB::try_add_tx(
+                        &mut mempool_tx,
+                        peer_server.chainstate(),
+                        &ConsensusHash([0x1; 20]),
+                        &BlockHeaderHash([0x2; 32]),
+                        txid.clone(),
+                        vec![i; 100],
+                        100 * (i as u64),
+                        1,
+                        &origin,
+                        0,
+                        &origin,
+                        0,
+                    )
+                    .unwrap();
+                    txids.push(txid);
+                }
+                mempool_tx.commit().unwrap();
+                peer_server.mempool = Some(mempool);
+
+                *replaced_txids.borrow_mut() = txids.clone();
+                convo_client.new_gettransaction_replacements(txids[0].clone())
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let txids = replaced_txids.borrow();
+                match http_response {
+                    HttpResponseType::TransactionReplacements(_, response) => {
+                        assert_eq!(response.txid, txids[0]);
+                        assert_eq!(
+                            response.replacements,
+                            vec![
+                                TransactionReplacement {
+                                    txid: txids[1].clone(),
+                                    reason: "ReplaceByFee".to_string(),
+                                },
+                                TransactionReplacement {
+                                    txid: txids[2].clone(),
+                                    reason: "ReplaceByFee".to_string(),
+                                },
+                            ]
+                        );
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_pending_transactions() {
+        let pending_txids = RefCell::new(vec![]);
+
+        test_rpc(
+            function_name!(),
+            40240,
+            40241,
+            50240,
+            50241,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let privk = StacksPrivateKey::from_hex(
+                    "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+                )
+                .unwrap();
+                let recipient: PrincipalData = StacksAddress {
+                    version: C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+                    bytes: Hash160([0x22; 20]),
+                }
+                .into();
+
+                let mut mempool = peer_server.mempool.take().unwrap();
+                let mut txids = vec![];
+                for nonce in 0..4 {
+                    let tx = make_user_stacks_transfer(&privk, nonce, 200, &recipient, 1);
+                    mempool
+                        .miner_submit(
+                            peer_server.chainstate(),
+                            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+                            &FIRST_STACKS_BLOCK_HASH,
+                            &tx,
+                            None,
+                            nonce as f64,
+                        )
+                        .unwrap();
+                    txids.push(tx.txid());
+                }
+                peer_server.mempool = Some(mempool);
+
+                *pending_txids.borrow_mut() = txids;
+                let origin = StacksAddress::from_public_keys(
+                    C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+                    &AddressHashMode::SerializeP2PKH,
+                    1,
+                    &vec![StacksPublicKey::from_private(&privk)],
+                )
+                .unwrap();
+
+                // second page of one tx, out of the txs with nonces 1 through 3
+                convo_client.new_get_pending_transactions(
+                    PendingTxQuery::Address {
+                        address: origin,
+                        is_origin: Some(true),
+                        min_nonce: 1,
+                        max_nonce: 3,
+                    },
+                    1,
+                    1,
+                    TipRequest::UseLatestAnchoredTip,
+                )
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let txids = pending_txids.borrow();
+                match http_response {
+                    HttpResponseType::PendingTransactions(_, response) => {
+                        assert_eq!(response.total, 3);
+                        assert_eq!(response.offset, 1);
+                        assert_eq!(response.limit, 1);
+                        assert_eq!(response.results.len(), 1);
+
+                        let pending_tx = &response.results[0];
+                        assert_eq!(pending_tx.txid, txids[2]);
+                        assert_eq!(pending_tx.origin_nonce, 2);
+                        assert_eq!(pending_tx.fee, 200);
+                        assert_eq!(pending_tx.fee_rate, Some(2.0));
+                        assert!(pending_tx.sponsor_address.is_none());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_rpc_missing_getblock() {
@@ -6231,6 +7282,266 @@ mod test {
         );
     }
 
+    #[test]
+    #[ignore]
+    fn test_rpc_simulate_transaction() {
+        // Test /v2/transactions/simulate (aka SimulateTransaction) endpoint.
+        // An unsigned STX transfer is run against the canonical Stacks tip, and we expect to get
+        // back its result, its events, and its fee, without it ever reaching the mempool.
+        test_rpc(
+            function_name!(),
+            40260,
+            40261,
+            50260,
+            50261,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                // STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW
+                let privk2 = StacksPrivateKey::from_hex(
+                    "94c319327cc5cd04da7147d32d836eb2e4c44f4db39aa5ede7314a761183d0c701",
+                )
+                .unwrap();
+                let mut tx = StacksTransaction::new(
+                    TransactionVersion::Testnet,
+                    TransactionAuth::from_p2pkh(&privk2).unwrap(),
+                    TransactionPayload::TokenTransfer(
+                        StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                            .unwrap()
+                            .to_account_principal(),
+                        123,
+                        TokenTransferMemo([0u8; 34]),
+                    ),
+                );
+                tx.chain_id = 0x80000000;
+                tx.auth.set_origin_nonce(0);
+                tx.set_tx_fee(200);
+
+                // simulation is off by default
+                peer_server
+                    .network
+                    .connection_opts
+                    .rpc_public_scopes
+                    .insert(RPCScope::Simulate);
+                convo_client.new_simulate_transaction(tx, TipRequest::UseLatestAnchoredTip)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::TransactionSimulation(response_md, data) => {
+                        assert!(data.okay, "{:?}", &data.cause);
+                        assert!(data.cause.is_none());
+                        assert_eq!(
+                            Value::try_deserialize_hex_untyped(&data.result.clone().unwrap())
+                                .unwrap(),
+                            Value::okay_true()
+                        );
+                        assert!(!data.post_condition_aborted);
+                        assert_eq!(data.post_condition_mode, "deny");
+                        assert_eq!(data.events.len(), 1);
+                        assert_eq!(data.asset_transfers, data.events);
+                        assert_eq!(data.asset_transfers[0]["type"], "stx_transfer_event");
+                        assert_eq!(data.fee, 200);
+                        assert!(data.fee_estimate.is_none());
+
+                        // nothing was added to either peer's mempool
+                        for mempool in [&peer_client.mempool, &peer_server.mempool] {
+                            let mempool = mempool.as_ref().unwrap();
+                            assert!(!MemPoolDB::db_has_tx(mempool.conn(), &data.txid).unwrap());
+                        }
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_admin_ban_cidr() {
+        // Test /v2/admin/peers/ban (aka PostAdminPeerAction) endpoint.
+        // A CIDR ban with a duration is kept in RAM by the server, and takes effect immediately.
+        test_rpc(
+            function_name!(),
+            40270,
+            40271,
+            50270,
+            50271,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                peer_server.network.connection_opts.rpc_auth_tokens =
+                    vec![RPCAuthToken::new("ops", "secret", &[RPCScope::Admin])];
+                let (prefix, mask) = PeerAdminTarget::parse_cidr("10.0.0.0/8").unwrap();
+                convo_client.new_post_admin_peer_action(
+                    PeerAdminAction::Ban(PeerAdminTarget::Cidr(prefix, mask), Some(3600)),
+                    "secret",
+                )
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                match http_response {
+                    HttpResponseType::AdminPeerAction(response_md, data) => {
+                        assert_eq!(data.action, "ban");
+                        assert!(data.disconnected.is_empty());
+                        assert!(data.until.unwrap() > get_epoch_time_secs());
+
+                        assert_eq!(peer_server.network.admin_denied_cidrs.len(), 1);
+                        assert!(peer_server
+                            .network
+                            .is_admin_denied(&PeerAddress::from_ipv4(10, 1, 2, 3)));
+                        assert!(!peer_server
+                            .network
+                            .is_admin_denied(&PeerAddress::from_ipv4(11, 1, 2, 3)));
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_admin_peers() {
+        // Test /v2/admin/peers (aka GetAdminPeers) endpoint.
+        test_rpc(
+            function_name!(),
+            40280,
+            40281,
+            50280,
+            50281,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                peer_server.network.connection_opts.rpc_auth_tokens =
+                    vec![RPCAuthToken::new("ops", "secret", &[RPCScope::Admin])];
+                convo_client.new_get_admin_peers("secret")
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                match http_response {
+                    HttpResponseType::AdminPeers(response_md, data) => {
+                        assert_eq!(data.len(), peer_server.network.peers.len());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_admin_peers_unauthorized() {
+        // The admin endpoints need the server's admin token.
+        test_rpc(
+            function_name!(),
+            40290,
+            40291,
+            50290,
+            50291,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                peer_server.network.connection_opts.rpc_auth_tokens =
+                    vec![RPCAuthToken::new("ops", "secret", &[RPCScope::Admin])];
+                convo_client.new_post_admin_peer_action(
+                    PeerAdminAction::Disconnect(PeerAdminTarget::Event(0)),
+                    "guess",
+                )
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                match http_response {
+                    HttpResponseType::Unauthorized(response_md, msg) => {
+                        assert!(peer_server.network.admin_disconnects.is_empty());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_getinfo_private() {
+        // A node can require a token even for public reads.
+        test_rpc(
+            function_name!(),
+            40300,
+            40301,
+            50300,
+            50301,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                peer_server.network.connection_opts.rpc_public_scopes = HashSet::new();
+                peer_server.network.connection_opts.rpc_auth_tokens =
+                    vec![RPCAuthToken::new("ops", "secret", &[RPCScope::PublicRead])];
+                convo_client.new_getinfo(None)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                match http_response {
+                    HttpResponseType::Unauthorized(response_md, msg) => {
+                        assert_eq!(msg, "The 'read' endpoints need an authorization token");
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_rpc_call_read_only_unconfirmed() {
@@ -21,8 +21,10 @@ use std::io::Error as io_error;
 use std::io::ErrorKind;
 use std::io::{Read, Write};
 
+use std::cmp;
 use std::collections::HashMap;
 use std::collections::VecDeque;
+use std::net::IpAddr;
 
 use std::sync::mpsc::sync_channel;
 use std::sync::mpsc::Receiver;
@@ -38,6 +40,9 @@ use crate::net::http::*;
 use crate::net::p2p::{PeerMap, PeerNetwork};
 use crate::net::poll::*;
 use crate::net::rpc::*;
+use crate::net::socks::{Socks5Handshake, Socks5Target};
+use crate::net::tls;
+use crate::net::tls::{HttpSocket, TlsAcceptor};
 use crate::net::Error as net_error;
 use crate::net::*;
 
@@ -49,15 +54,267 @@ use crate::burnchains::BurnchainView;
 
 use mio::net as mio_net;
 
+use clarity::vm::costs::ExecutionCost;
+
+use stacks_common::util::get_epoch_time_ms;
 use stacks_common::util::get_epoch_time_secs;
 
+use crate::monitoring;
+
 use crate::core::mempool::*;
 
+/// How often the rate limiter forgets idle clients, in milliseconds
+pub const RPC_RATE_LIMITER_PRUNE_INTERVAL_MS: u128 = 60_000;
+
+/// A token-bucket limit: a client can spend up to `burst` at once, and regains `per_minute` every
+/// minute
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RPCRateLimit {
+    pub burst: u64,
+    pub per_minute: u64,
+}
+
+/// How the rate limiter tells RPC clients apart
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RPCClientKey {
+    /// Clients that present an auth token share its budget, wherever they connect from
+    Token(String),
+    /// Other clients are told apart by IP address
+    Addr(IpAddr),
+}
+
+#[derive(Debug, Clone)]
+struct TokenBucket {
+    /// Goes negative if the client is charged, after the fact, for more than it had
+    level: f64,
+    last_refill_ms: u128,
+}
+
+impl TokenBucket {
+    fn new(limit: &RPCRateLimit, now_ms: u128) -> TokenBucket {
+        TokenBucket {
+            level: limit.burst as f64,
+            last_refill_ms: now_ms,
+        }
+    }
+
+    fn refill(&mut self, limit: &RPCRateLimit, now_ms: u128) {
+        let elapsed_ms = now_ms.saturating_sub(self.last_refill_ms);
+        let regained = (elapsed_ms as f64) * (limit.per_minute as f64) / 60_000.0;
+        self.level = (self.level + regained).min(limit.burst as f64);
+        self.last_refill_ms = cmp::max(self.last_refill_ms, now_ms);
+    }
+
+    fn is_full(&self, limit: &RPCRateLimit) -> bool {
+        self.level >= limit.burst as f64
+    }
+
+    /// How many seconds until the bucket holds `amount`, if it doesn't already
+    fn wait_secs(&self, limit: &RPCRateLimit, amount: f64) -> Option<u64> {
+        if self.level >= amount {
+            return None;
+        }
+        let per_minute = cmp::max(1, limit.per_minute) as f64;
+        let secs = ((amount - self.level) * 60.0 / per_minute).ceil() as u64;
+        Some(cmp::max(1, secs))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct RPCClientBudget {
+    requests: HashMap<RPCScope, TokenBucket>,
+    read_only_cost: Option<TokenBucket>,
+    auth_failures: Option<TokenBucket>,
+}
+
+/// Per-client rate limiting of RPC requests.  Each client has a token bucket for each scope of
+/// endpoints, and one for the Clarity cost of its read-only calls and simulations.  Each IP
+/// address also has one for its requests that fail authorization.  The limits themselves are
+/// read from the connection options each time.
+#[derive(Debug, Default)]
+pub struct RPCRateLimiter {
+    clients: HashMap<RPCClientKey, RPCClientBudget>,
+    last_prune_ms: u128,
+}
+
+impl RPCRateLimiter {
+    pub fn new() -> RPCRateLimiter {
+        RPCRateLimiter::default()
+    }
+
+    /// Charge a client for a request to an endpoint in `scope`.  Read-only calls are also refused
+    /// while the client has spent its cost budget, since their cost is only known once they have
+    /// run.  If the request is refused, returns how many seconds the client should wait.
+    pub fn check_request(
+        &mut self,
+        client: &RPCClientKey,
+        scope: RPCScope,
+        opts: &ConnectionOptions,
+        now_ms: u128,
+    ) -> Result<(), u64> {
+        let request_limit = opts.rpc_rate_limits.get(&scope);
+        let cost_limit = match scope {
+            RPCScope::ReadOnlyCall | RPCScope::Simulate => opts.rpc_read_only_cost_limit.as_ref(),
+            _ => None,
+        };
+        if request_limit.is_none() && cost_limit.is_none() {
+            return Ok(());
+        }
+
+        let budget = self.clients.entry(client.clone()).or_default();
+
+        // check every bucket before charging any, so a refused request costs nothing
+        let mut wait_secs = None;
+        if let Some(limit) = cost_limit {
+            let bucket = budget
+                .read_only_cost
+                .get_or_insert_with(|| TokenBucket::new(limit, now_ms));
+            bucket.refill(limit, now_ms);
+            wait_secs = cmp::max(wait_secs, bucket.wait_secs(limit, 1.0));
+        }
+        if let Some(limit) = request_limit {
+            let bucket = budget
+                .requests
+                .entry(scope)
+                .or_insert_with(|| TokenBucket::new(limit, now_ms));
+            bucket.refill(limit, now_ms);
+            wait_secs = cmp::max(wait_secs, bucket.wait_secs(limit, 1.0));
+        }
+
+        if let Some(wait_secs) = wait_secs {
+            monitoring::increment_rpc_rate_limited_counter(scope.as_str());
+            return Err(wait_secs);
+        }
+        if let Some(bucket) = budget.requests.get_mut(&scope) {
+            bucket.level -= 1.0;
+        }
+        Ok(())
+    }
+
+    /// Check that an IP address has not spent its budget of failed authorizations.  This is checked
+    /// before the request's credentials are, so that a client can't keep guessing tokens.  If the
+    /// request is refused, returns how many seconds the client should wait.
+    pub fn check_auth_failures(
+        &mut self,
+        addr: IpAddr,
+        opts: &ConnectionOptions,
+        now_ms: u128,
+    ) -> Result<(), u64> {
+        let limit = &opts.rpc_auth_failure_limit;
+        let bucket = match self
+            .clients
+            .get_mut(&RPCClientKey::Addr(addr))
+            .and_then(|budget| budget.auth_failures.as_mut())
+        {
+            Some(bucket) => bucket,
+            None => {
+                return Ok(());
+            }
+        };
+        bucket.refill(limit, now_ms);
+        match bucket.wait_secs(limit, 1.0) {
+            Some(wait_secs) => {
+                monitoring::increment_rpc_rate_limited_counter("auth_failure");
+                Err(wait_secs)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Charge an IP address for a request that failed authorization
+    pub fn charge_auth_failure(&mut self, addr: IpAddr, opts: &ConnectionOptions, now_ms: u128) {
+        let limit = &opts.rpc_auth_failure_limit;
+        let bucket = self
+            .clients
+            .entry(RPCClientKey::Addr(addr))
+            .or_default()
+            .auth_failures
+            .get_or_insert_with(|| TokenBucket::new(limit, now_ms));
+        bucket.refill(limit, now_ms);
+        bucket.level -= 1.0;
+    }
+
+    /// Charge a client for the Clarity cost of a read-only call it made.  The cost is measured
+    /// in percent of `read_only_call_limit`, in its largest dimension.
+    pub fn charge_read_only_cost(
+        &mut self,
+        client: &RPCClientKey,
+        cost: &ExecutionCost,
+        opts: &ConnectionOptions,
+        now_ms: u128,
+    ) {
+        let limit = match opts.rpc_read_only_cost_limit {
+            Some(ref limit) => limit,
+            None => {
+                return;
+            }
+        };
+        let charge = cmp::max(
+            1,
+            opts.read_only_call_limit.proportion_largest_dimension(cost),
+        );
+        monitoring::increment_rpc_read_only_cost_charged(charge);
+
+        let bucket = self
+            .clients
+            .entry(client.clone())
+            .or_default()
+            .read_only_cost
+            .get_or_insert_with(|| TokenBucket::new(limit, now_ms));
+        bucket.refill(limit, now_ms);
+        bucket.level -= charge as f64;
+    }
+
+    /// Forget clients whose buckets have all refilled, since they would start out full anyway.
+    /// Only does work every `RPC_RATE_LIMITER_PRUNE_INTERVAL_MS`.
+    pub fn prune(&mut self, opts: &ConnectionOptions, now_ms: u128) {
+        if now_ms < self.last_prune_ms + RPC_RATE_LIMITER_PRUNE_INTERVAL_MS {
+            return;
+        }
+        self.last_prune_ms = now_ms;
+        self.clients.retain(|_, budget| {
+            let requests_full = budget.requests.iter_mut().all(|(scope, bucket)| {
+                match opts.rpc_rate_limits.get(scope) {
+                    Some(limit) => {
+                        bucket.refill(limit, now_ms);
+                        bucket.is_full(limit)
+                    }
+                    None => true,
+                }
+            });
+            let cost_full = match (
+                budget.read_only_cost.as_mut(),
+                opts.rpc_read_only_cost_limit,
+            ) {
+                (Some(bucket), Some(ref limit)) => {
+                    bucket.refill(limit, now_ms);
+                    bucket.is_full(limit)
+                }
+                _ => true,
+            };
+            let auth_failures_full = match budget.auth_failures.as_mut() {
+                Some(bucket) => {
+                    bucket.refill(&opts.rpc_auth_failure_limit, now_ms);
+                    bucket.is_full(&opts.rpc_auth_failure_limit)
+                }
+                None => true,
+            };
+            !(requests_full && cost_full && auth_failures_full)
+        });
+        monitoring::update_rpc_rate_limiter_clients(self.clients.len() as i64);
+    }
+
+    /// How many clients are being tracked
+    pub fn num_clients(&self) -> usize {
+        self.clients.len()
+    }
+}
+
 #[derive(Debug)]
 pub struct HttpPeer {
     // ongoing http conversations (either they reached out to us, or we to them)
     pub peers: HashMap<usize, ConversationHttp>,
-    pub sockets: HashMap<usize, mio_net::TcpStream>,
+    pub sockets: HashMap<usize, HttpSocket>,
 
     // outbound connections that are pending connection
     pub connecting: HashMap<
@@ -69,24 +326,71 @@ pub struct HttpPeer {
             u64,
         ),
     >,
+    // outbound connections still opening a tunnel through the SOCKS5 proxy
+    pub proxy_handshakes: HashMap<usize, Socks5Handshake>,
 
     // server network handle
     pub http_server_handle: usize,
 
     // connection options
     pub connection_opts: ConnectionOptions,
+
+    // per-client request budgets
+    pub rate_limiter: RPCRateLimiter,
+
+    // wraps inbound connections in TLS, if we have a certificate
+    pub tls_acceptor: Option<TlsAcceptor>,
+    // which TLS reload request the certificate was last loaded for
+    tls_generation: u64,
 }
 
 impl HttpPeer {
     pub fn new(conn_opts: ConnectionOptions, server_handle: usize) -> HttpPeer {
+        let tls_generation = tls::reload_generation();
+        let tls_acceptor = HttpPeer::load_tls_acceptor(&conn_opts)
+            .expect("FATAL: failed to load RPC TLS certificate and key");
         HttpPeer {
             peers: HashMap::new(),
             sockets: HashMap::new(),
 
             connecting: HashMap::new(),
+            proxy_handshakes: HashMap::new(),
             http_server_handle: server_handle,
 
             connection_opts: conn_opts,
+
+            rate_limiter: RPCRateLimiter::new(),
+            tls_acceptor,
+            tls_generation,
+        }
+    }
+
+    /// Load the certificate and key to serve RPC clients over TLS with, if configured
+    fn load_tls_acceptor(conn_opts: &ConnectionOptions) -> Result<Option<TlsAcceptor>, net_error> {
+        match (&conn_opts.rpc_tls_cert_path, &conn_opts.rpc_tls_key_path) {
+            (Some(cert_path), Some(key_path)) => {
+                Ok(Some(TlsAcceptor::from_pem_files(cert_path, key_path)?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Re-read the TLS certificate and key, e.g. once they have been renewed.  New connections
+    /// use the new ones; open connections keep the ones they started with.  If they can't be
+    /// loaded, keep using the old ones.
+    pub fn reload_tls(&mut self) {
+        match HttpPeer::load_tls_acceptor(&self.connection_opts) {
+            Ok(Some(tls_acceptor)) => {
+                info!("Reloaded RPC TLS certificate and key");
+                self.tls_acceptor = Some(tls_acceptor);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                error!(
+                    "Failed to reload RPC TLS certificate and key; still using the old ones: {:?}",
+                    &e
+                );
+            }
         }
     }
 
@@ -115,10 +419,7 @@ impl HttpPeer {
     pub fn get_conversation_and_socket(
         &mut self,
         event_id: usize,
-    ) -> (
-        Option<&mut ConversationHttp>,
-        Option<&mut mio::net::TcpStream>,
-    ) {
+    ) -> (Option<&mut ConversationHttp>, Option<&mut HttpSocket>) {
         (
             self.peers.get_mut(&event_id),
             self.sockets.get_mut(&event_id),
@@ -147,11 +448,29 @@ impl HttpPeer {
             return Err(net_error::AlreadyConnected(event_id, http_nk));
         }
 
-        let sock = NetworkState::connect(&addr)?;
+        let (sock, proxy_handshake) = match self.connection_opts.socks5_proxy {
+            Some(ref proxy) => {
+                let target = HttpPeer::proxy_target(&data_url, &addr)?;
+                let (sock, handshake) = Socks5Handshake::connect(proxy, target)?;
+                (sock, Some(handshake))
+            }
+            None => {
+                if PeerAddress::from_socketaddr(&addr).is_host_address() {
+                    return Err(net_error::ProxyError(format!(
+                        "No SOCKS5 proxy configured to reach {}",
+                        &data_url
+                    )));
+                }
+                (NetworkState::connect(&addr)?, None)
+            }
+        };
         let hint_event_id = network_state.next_event_id()?;
         let next_event_id =
             network_state.register(self.http_server_handle, hint_event_id, &sock)?;
 
+        if let Some(handshake) = proxy_handshake {
+            self.proxy_handshakes.insert(next_event_id, handshake);
+        }
         self.connecting.insert(
             next_event_id,
             (sock, Some(data_url), request, get_epoch_time_secs()),
@@ -159,6 +478,29 @@ impl HttpPeer {
         Ok(next_event_id)
     }
 
+    /// Where to ask the SOCKS5 proxy to connect to, in order to reach `addr`.  If `addr` stands in
+    /// for a DNS name (see `DNSResolver::set_proxy_resolution()`), then the proxy gets the name
+    /// from `data_url`.
+    fn proxy_target(data_url: &UrlString, addr: &SocketAddr) -> Result<Socks5Target, net_error> {
+        let addrbytes = PeerAddress::from_socketaddr(addr);
+        if !addrbytes.is_host_address() {
+            return Ok(Socks5Target::Addr(*addr));
+        }
+        let host = match PeerHost::try_from_url(data_url) {
+            Some(PeerHost::DNS(name, _)) => PeerHostName::try_from(name.as_str()).ok(),
+            _ => None,
+        };
+        match host {
+            Some(host) if host.to_peer_address() == addrbytes => {
+                Ok(Socks5Target::Host(host, addr.port()))
+            }
+            _ => Err(net_error::ProxyError(format!(
+                "{} does not name the host behind {}",
+                data_url, addr
+            ))),
+        }
+    }
+
     /// How many conversations are connected from this IP address?
     fn count_inbound_ip_addrs(&self, peer_addr: &SocketAddr) -> u64 {
         let mut count = 0;
@@ -218,23 +560,28 @@ impl HttpPeer {
         mempool: &MemPoolDB,
         chainstate: &mut StacksChainState,
         event_id: usize,
-        mut socket: mio_net::TcpStream,
+        mut socket: HttpSocket,
         outbound_url: Option<UrlString>,
         initial_request: Option<HttpRequestType>,
+        proxied_addr: Option<SocketAddr>,
     ) -> Result<(), net_error> {
-        let client_addr = match socket.peer_addr() {
-            Ok(addr) => addr,
-            Err(e) => {
-                warn!("Failed to get peer address of {:?}: {:?}", &socket, &e);
-                let _ = network_state.deregister(event_id, &socket);
-                return Err(net_error::SocketError);
-            }
+        // a tunnel through our SOCKS5 proxy leads somewhere other than its peer address
+        let client_addr = match proxied_addr {
+            Some(addr) => addr,
+            None => match socket.peer_addr() {
+                Ok(addr) => addr,
+                Err(e) => {
+                    warn!("Failed to get peer address of {:?}: {:?}", &socket, &e);
+                    let _ = network_state.deregister(event_id, socket.tcp_stream());
+                    return Err(net_error::SocketError);
+                }
+            },
         };
 
         match self.can_register_http(&client_addr, outbound_url.as_ref()) {
             Ok(_) => {}
             Err(e) => {
-                let _ = network_state.deregister(event_id, &socket);
+                let _ = network_state.deregister(event_id, socket.tcp_stream());
                 return Err(e);
             }
         }
@@ -264,7 +611,7 @@ impl HttpPeer {
             match new_convo.send_request(request) {
                 Ok(_) => {}
                 Err(e) => {
-                    let _ = network_state.deregister(event_id, &socket);
+                    let _ = network_state.deregister(event_id, socket.tcp_stream());
                     return Err(e);
                 }
             }
@@ -273,7 +620,7 @@ impl HttpPeer {
             match HttpPeer::saturate_http_socket(&mut socket, &mut new_convo, mempool, chainstate) {
                 Ok(_) => {}
                 Err(e) => {
-                    let _ = network_state.deregister(event_id, &socket);
+                    let _ = network_state.deregister(event_id, socket.tcp_stream());
                     return Err(e);
                 }
             }
@@ -290,8 +637,9 @@ impl HttpPeer {
 
         match self.sockets.remove(&event_id) {
             None => {}
-            Some(sock) => {
-                let _ = network_state.deregister(event_id, &sock);
+            Some(mut sock) => {
+                sock.close();
+                let _ = network_state.deregister(event_id, sock.tcp_stream());
             }
         }
         match self.connecting.remove(&event_id) {
@@ -300,6 +648,7 @@ impl HttpPeer {
                 let _ = network_state.deregister(event_id, &sock);
             }
         }
+        self.proxy_handshakes.remove(&event_id);
     }
 
     /// Remove slow/unresponsive peers
@@ -343,7 +692,7 @@ impl HttpPeer {
     /// Saturate a conversation's socket -- either sends the whole request, or fills the socket
     /// buffer.
     pub fn saturate_http_socket(
-        client_sock: &mut mio::net::TcpStream,
+        client_sock: &mut HttpSocket,
         convo: &mut ConversationHttp,
         mempool: &MemPoolDB,
         chainstate: &mut StacksChainState,
@@ -364,6 +713,12 @@ impl HttpPeer {
             }
         }
 
+        // a TLS socket can still hold records once the socket would block.  Keep pushing them
+        // out, since the client may not send anything that would make us try again.
+        client_sock.flush_pending().map_err(|e| {
+            debug!("Failed to flush socket {:?}: {:?}", &client_sock, &e);
+            net_error::WriteError(e)
+        })?;
         Ok(())
     }
 
@@ -379,10 +734,21 @@ impl HttpPeer {
         let mut registered = vec![];
 
         for (hint_event_id, client_sock) in poll_state.new.drain() {
+            let client_sock = match self.tls_acceptor {
+                Some(ref tls_acceptor) => match tls_acceptor.accept(client_sock) {
+                    Ok(client_sock) => client_sock,
+                    Err(e) => {
+                        warn!("Failed to start TLS on new HTTP connection: {:?}", &e);
+                        continue;
+                    }
+                },
+                None => HttpSocket::Plain(client_sock),
+            };
+
             let event_id = match network_state.register(
                 self.http_server_handle,
                 hint_event_id,
-                &client_sock,
+                client_sock.tcp_stream(),
             ) {
                 Ok(event_id) => event_id,
                 Err(e) => {
@@ -401,7 +767,7 @@ impl HttpPeer {
                     event_id,
                     self.peers.get(&event_id)
                 );
-                let _ = network_state.deregister(event_id, &client_sock);
+                let _ = network_state.deregister(event_id, client_sock.tcp_stream());
                 continue;
             }
 
@@ -413,6 +779,7 @@ impl HttpPeer {
                 client_sock,
                 None,
                 None,
+                None,
             ) {
                 // NOTE: register_http will deregister the socket for us
                 continue;
@@ -432,8 +799,9 @@ impl HttpPeer {
         chainstate: &mut StacksChainState,
         mempool: &mut MemPoolDB,
         event_id: usize,
-        client_sock: &mut mio_net::TcpStream,
+        client_sock: &mut HttpSocket,
         convo: &mut ConversationHttp,
+        rate_limiter: &mut RPCRateLimiter,
         handler_args: &RPCHandlerArgs,
     ) -> Result<(bool, Vec<StacksMessageType>), net_error> {
         // get incoming bytes and update the state of this conversation.
@@ -502,7 +870,14 @@ impl HttpPeer {
         // react to inbound messages -- do we need to send something out, or fulfill requests
         // to other threads?  Try to chat even if the recv() failed, since we'll want to at
         // least drain the conversation inbox.
-        let msgs = match convo.chat(network, sortdb, chainstate, mempool, handler_args) {
+        let msgs = match convo.chat(
+            network,
+            sortdb,
+            chainstate,
+            mempool,
+            rate_limiter,
+            handler_args,
+        ) {
             Ok(msgs) => msgs,
             Err(e) => {
                 debug!(
@@ -547,6 +922,35 @@ impl HttpPeer {
     ) -> () {
         for event_id in poll_state.ready.iter() {
             if self.connecting.contains_key(event_id) {
+                // a proxied socket needs its tunnel opened first
+                let mut proxied_addr = None;
+                if let Some(handshake) = self.proxy_handshakes.get_mut(event_id) {
+                    let (socket, ..) = self
+                        .connecting
+                        .get_mut(event_id)
+                        .expect("BUG: connecting socket disappeared");
+                    match handshake.try_advance(socket) {
+                        Ok(true) => {
+                            proxied_addr = Some(handshake.target().to_socketaddr());
+                        }
+                        Ok(false) => {
+                            // wait for the proxy
+                            continue;
+                        }
+                        Err(e) => {
+                            debug!(
+                                "SOCKS5 proxy failed to connect HTTP event {} to {:?}: {:?}",
+                                event_id,
+                                handshake.target(),
+                                &e
+                            );
+                            self.deregister_http(network_state, *event_id);
+                            continue;
+                        }
+                    }
+                    self.proxy_handshakes.remove(event_id);
+                }
+
                 let (socket, data_url, initial_request_opt, _) =
                     self.connecting.remove(event_id).unwrap();
 
@@ -557,9 +961,10 @@ impl HttpPeer {
                     mempool,
                     chainstate,
                     *event_id,
-                    socket,
+                    HttpSocket::Plain(socket),
                     data_url.clone(),
                     initial_request_opt,
+                    proxied_addr,
                 ) {
                     debug!(
                         "Failed to register HTTP connection ({}, {:?})",
@@ -612,6 +1017,7 @@ impl HttpPeer {
                         *event_id,
                         client_sock,
                         convo,
+                        &mut self.rate_limiter,
                         handler_args,
                     ) {
                         Ok((alive, mut new_msgs)) => {
@@ -655,7 +1061,31 @@ impl HttpPeer {
                     close.push(*event_id);
                 }
             }
-            if convo.is_drained() && !convo.is_keep_alive() {
+            if convo.is_streaming_events() {
+                // event streams get new data without any socket activity, so push it out now
+                if let Some(client_sock) = self.sockets.get_mut(event_id) {
+                    if let Err(_e) =
+                        HttpPeer::saturate_http_socket(client_sock, convo, mempool, chainstate)
+                    {
+                        info!("Broken HTTP event stream {:?}: {:?}", convo, &_e);
+                        close.push(*event_id);
+                    }
+                }
+            }
+            // don't hang up before the client has the whole response
+            let mut unsent = false;
+            if let Some(client_sock) = self.sockets.get_mut(event_id) {
+                match client_sock.flush_pending() {
+                    Ok(all_sent) => {
+                        unsent = !all_sent;
+                    }
+                    Err(_e) => {
+                        info!("Broken HTTP connection {:?}: {:?}", convo, &_e);
+                        close.push(*event_id);
+                    }
+                }
+            }
+            if convo.is_drained() && !convo.is_keep_alive() && !unsent {
                 // did some work, but nothing more to do and we're not keep-alive
                 debug!("Close drained HTTP connection {:?}", convo);
                 close.push(*event_id);
@@ -681,6 +1111,13 @@ impl HttpPeer {
         mut poll_state: NetworkPollState,
         handler_args: &RPCHandlerArgs,
     ) -> Vec<StacksMessageType> {
+        // pick up a renewed TLS certificate
+        let tls_generation = tls::reload_generation();
+        if tls_generation != self.tls_generation {
+            self.tls_generation = tls_generation;
+            self.reload_tls();
+        }
+
         // set up new inbound conversations
         self.process_new_sockets(network_state, mempool, chainstate, &mut poll_state);
 
@@ -716,6 +1153,10 @@ impl HttpPeer {
         // clear out slow or non-responsive peers
         self.disconnect_unresponsive(network_state);
 
+        // forget clients that have used none of their request budgets
+        self.rate_limiter
+            .prune(&network.connection_opts, get_epoch_time_ms());
+
         stacks_msgs
     }
 }
@@ -724,6 +1165,7 @@ impl HttpPeer {
 mod test {
     use super::*;
     use crate::net::codec::*;
+    use crate::net::event_stream::{ChainEventKind, EventStreamHub};
     use crate::net::http::*;
     use crate::net::rpc::*;
     use crate::net::test::*;
@@ -752,8 +1194,10 @@ mod test {
 
     use std::thread;
 
+    use std::fs;
     use std::net::SocketAddr;
     use std::net::TcpStream;
+    use std::sync::Arc;
 
     use stacks_common::util::get_epoch_time_secs;
     use stacks_common::util::pipe::*;
@@ -761,11 +1205,14 @@ mod test {
 
     use crate::chainstate::burn::ConsensusHash;
     use crate::chainstate::stacks::StacksBlockHeader;
+    use crate::util_lib::strings::StacksString;
     use clarity::vm::contracts::Contract;
     use clarity::vm::representations::ClarityName;
     use clarity::vm::representations::ContractName;
     use clarity::vm::types::*;
     use stacks_common::codec::MAX_MESSAGE_LEN;
+    use stacks_common::util::hash::{MerkleTree, Sha512Trunc256Sum};
+    use std::convert::TryFrom;
 
     fn test_http_server<F, C>(
         test_name: &str,
@@ -1381,4 +1828,414 @@ mod test {
             |client_id, http_response_bytes_res| true,
         );
     }
+
+    #[test]
+    fn test_rpc_rate_limiter() {
+        let mut conn_opts = ConnectionOptions::default();
+        conn_opts.rpc_rate_limits.insert(
+            RPCScope::PostTransaction,
+            RPCRateLimit {
+                burst: 2,
+                per_minute: 6,
+            },
+        );
+        conn_opts.rpc_read_only_cost_limit = Some(RPCRateLimit {
+            burst: 100,
+            per_minute: 60,
+        });
+
+        let alice = RPCClientKey::Addr("1.2.3.4".parse().unwrap());
+        let bob = RPCClientKey::Token("bob".to_string());
+        let mut limiter = RPCRateLimiter::new();
+        let now = 1_000_000;
+
+        // unlimited scopes are not tracked
+        for _ in 0..10 {
+            assert_eq!(
+                limiter.check_request(&alice, RPCScope::PublicRead, &conn_opts, now),
+                Ok(())
+            );
+        }
+        assert_eq!(limiter.num_clients(), 0);
+
+        // a client can spend its burst at once, and then regains 1 every 10 seconds
+        for _ in 0..2 {
+            assert_eq!(
+                limiter.check_request(&alice, RPCScope::PostTransaction, &conn_opts, now),
+                Ok(())
+            );
+        }
+        assert_eq!(
+            limiter.check_request(&alice, RPCScope::PostTransaction, &conn_opts, now),
+            Err(10)
+        );
+        assert_eq!(
+            limiter.check_request(&alice, RPCScope::PostTransaction, &conn_opts, now + 4_000),
+            Err(6)
+        );
+        assert_eq!(
+            limiter.check_request(&alice, RPCScope::PostTransaction, &conn_opts, now + 10_000),
+            Ok(())
+        );
+
+        // other clients have their own budgets
+        assert_eq!(
+            limiter.check_request(&bob, RPCScope::PostTransaction, &conn_opts, now),
+            Ok(())
+        );
+
+        // read-only calls are refused once their cost has used up the budget
+        let mut cost = conn_opts.read_only_call_limit.clone();
+        cost.runtime = cost.runtime * 3 / 2;
+        assert_eq!(
+            limiter.check_request(&bob, RPCScope::ReadOnlyCall, &conn_opts, now),
+            Ok(())
+        );
+        limiter.charge_read_only_cost(&bob, &cost, &conn_opts, now);
+        assert_eq!(
+            limiter.check_request(&bob, RPCScope::ReadOnlyCall, &conn_opts, now),
+            Err(51)
+        );
+        assert_eq!(
+            limiter.check_request(&bob, RPCScope::ReadOnlyCall, &conn_opts, now + 51_000),
+            Ok(())
+        );
+        assert_eq!(limiter.num_clients(), 2);
+
+        // idle clients are forgotten once their budgets have refilled
+        limiter.prune(&conn_opts, now + 60_000);
+        assert_eq!(limiter.num_clients(), 1);
+        limiter.prune(&conn_opts, now + 200_000);
+        assert_eq!(limiter.num_clients(), 0);
+    }
+
+    #[test]
+    fn test_rpc_auth_failure_limiter() {
+        let mut conn_opts = ConnectionOptions::default();
+        conn_opts.rpc_auth_failure_limit = RPCRateLimit {
+            burst: 3,
+            per_minute: 6,
+        };
+
+        let alice: IpAddr = "1.2.3.4".parse().unwrap();
+        let bob: IpAddr = "5.6.7.8".parse().unwrap();
+        let mut limiter = RPCRateLimiter::new();
+        let now = 1_000_000;
+
+        // addresses that never failed are not tracked
+        assert_eq!(limiter.check_auth_failures(alice, &conn_opts, now), Ok(()));
+        assert_eq!(limiter.num_clients(), 0);
+
+        // an address is refused once it has spent its burst of failures, whether or not any
+        // other rate limits are set, and then regains 1 every 10 seconds
+        for _ in 0..3 {
+            assert_eq!(limiter.check_auth_failures(alice, &conn_opts, now), Ok(()));
+            limiter.charge_auth_failure(alice, &conn_opts, now);
+        }
+        assert_eq!(limiter.check_auth_failures(alice, &conn_opts, now), Err(10));
+        assert_eq!(
+            limiter.check_auth_failures(alice, &conn_opts, now + 10_000),
+            Ok(())
+        );
+        assert_eq!(limiter.check_auth_failures(bob, &conn_opts, now), Ok(()));
+
+        // and is forgotten once its budget has refilled
+        limiter.prune(&conn_opts, now + 60_000);
+        assert_eq!(limiter.num_clients(), 0);
+    }
+
+    #[test]
+    fn test_http_rate_limited() {
+        let mut conn_opts = ConnectionOptions::default();
+        conn_opts.rpc_rate_limits.insert(
+            RPCScope::PublicRead,
+            RPCRateLimit {
+                burst: 1,
+                per_minute: 1,
+            },
+        );
+
+        let num_ok = RefCell::new(0);
+        let num_limited = RefCell::new(0);
+        test_http_server(
+            function_name!(),
+            51100,
+            51101,
+            conn_opts,
+            3,
+            0,
+            |client_id, _| {
+                let mut request = HttpRequestType::GetInfo(HttpRequestMetadata::from_host(
+                    PeerHost::from_host_port("127.0.0.1".to_string(), 51101),
+                    None,
+                ));
+                request.metadata_mut().keep_alive = false;
+
+                let request_bytes = StacksHttp::serialize_request(&request).unwrap();
+                request_bytes
+            },
+            |client_id, http_response_bytes_res| {
+                // all clients are on 127.0.0.1, so only one gets through
+                let http_response_bytes = http_response_bytes_res.unwrap();
+                let response =
+                    StacksHttp::parse_response("/v2/info", &http_response_bytes).unwrap();
+                match response {
+                    StacksHttpMessage::Response(HttpResponseType::PeerInfo(..)) => {
+                        *num_ok.borrow_mut() += 1;
+                        true
+                    }
+                    StacksHttpMessage::Response(HttpResponseType::TooManyRequests(
+                        _,
+                        retry_after,
+                        _,
+                    )) => {
+                        *num_limited.borrow_mut() += 1;
+                        retry_after > 0 && retry_after <= 60
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &response);
+                        false
+                    }
+                }
+            },
+        );
+        assert_eq!(*num_ok.borrow(), 1);
+        assert_eq!(*num_limited.borrow(), 2);
+    }
+
+    /// Send a request to the RPC server over TLS, trusting only the test CA.
+    /// Returns the response and the certificate the server presented.
+    /// Send a request over TLS, and read the whole response, pausing `read_pause_ms` before each
+    /// read to keep the server's socket full
+    fn tls_http_request(
+        port: u16,
+        request: &[u8],
+        read_pause_ms: u64,
+    ) -> (Vec<u8>, rustls::Certificate) {
+        let mut roots = rustls::RootCertStore::empty();
+        for ca in rustls_pemfile::certs(&mut tls::test::CA_PEM.as_bytes()).unwrap() {
+            roots.add(&rustls::Certificate(ca)).unwrap();
+        }
+        let config = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        let conn = rustls::ClientConnection::new(
+            Arc::new(config),
+            rustls::ServerName::try_from("localhost").unwrap(),
+        )
+        .unwrap();
+        let sock =
+            TcpStream::connect(&format!("127.0.0.1:{}", port).parse::<SocketAddr>().unwrap())
+                .unwrap();
+
+        let mut stream = rustls::StreamOwned::new(conn, sock);
+        stream.write_all(request).unwrap();
+        let mut resp = vec![];
+        let mut buf = [0u8; 65536];
+        loop {
+            sleep_ms(read_pause_ms);
+            let num_read = stream.read(&mut buf).unwrap();
+            if num_read == 0 {
+                break;
+            }
+            resp.extend_from_slice(&buf[..num_read]);
+        }
+
+        let cert = stream.conn.peer_certificates().unwrap()[0].clone();
+        (resp, cert)
+    }
+
+    #[test]
+    fn test_http_getinfo_tls() {
+        let tls_dir = format!("/tmp/stacks-node-tests/{}", function_name!());
+        if fs::metadata(&tls_dir).is_ok() {
+            fs::remove_dir_all(&tls_dir).unwrap();
+        }
+        fs::create_dir_all(&tls_dir).unwrap();
+        let cert_path = format!("{}/cert.pem", &tls_dir);
+        let key_path = format!("{}/key.pem", &tls_dir);
+        fs::write(&cert_path, tls::test::CERT_PEM).unwrap();
+        fs::write(&key_path, tls::test::KEY_PEM).unwrap();
+
+        let mut peer_config = TestPeerConfig::new(function_name!(), 51110, 51111);
+        peer_config.connection_opts.rpc_tls_cert_path = Some(cert_path.clone());
+        peer_config.connection_opts.rpc_tls_key_path = Some(key_path.clone());
+        let mut peer = TestPeer::new(peer_config);
+
+        // a block whose response is far bigger than the socket's buffers
+        let mut big_block = make_codec_test_block(25);
+        let privk = StacksPrivateKey::new();
+        for i in 0..64 {
+            big_block.txs.push(StacksTransaction::new(
+                TransactionVersion::Testnet,
+                TransactionAuth::from_p2pkh(&privk).unwrap(),
+                TransactionPayload::SmartContract(
+                    TransactionSmartContract {
+                        name: ContractName::try_from(format!("big-{}", i)).unwrap(),
+                        code_body: StacksString::from_str(&";".repeat(100_000)).unwrap(),
+                    },
+                    None,
+                ),
+            ));
+        }
+        let txid_vecs = big_block
+            .txs
+            .iter()
+            .map(|tx| tx.txid().as_bytes().to_vec())
+            .collect();
+        big_block.header.tx_merkle_root = MerkleTree::<Sha512Trunc256Sum>::new(&txid_vecs).root();
+        let big_block_consensus_hash = ConsensusHash([0xfe; 20]);
+        let big_block_id = StacksBlockHeader::make_index_block_hash(
+            &big_block_consensus_hash,
+            &big_block.block_hash(),
+        );
+        store_staging_block(
+            peer.chainstate(),
+            &big_block_consensus_hash,
+            &big_block,
+            &ConsensusHash([0xfd; 20]),
+            456,
+            123,
+        );
+
+        let (http_sx, http_rx) = sync_channel(1);
+        let http_thread = thread::spawn(move || loop {
+            peer.step().unwrap();
+            if http_rx.try_recv().is_ok() {
+                break;
+            }
+        });
+
+        let mut request = HttpRequestType::GetInfo(HttpRequestMetadata::from_host(
+            PeerHost::from_host_port("localhost".to_string(), 51111),
+            None,
+        ));
+        request.metadata_mut().keep_alive = false;
+        let request_bytes = StacksHttp::serialize_request(&request).unwrap();
+
+        let (resp, cert) = tls_http_request(51111, &request_bytes, 0);
+        match StacksHttp::parse_response("/v2/info", &resp).unwrap() {
+            StacksHttpMessage::Response(HttpResponseType::PeerInfo(..)) => {}
+            response => {
+                panic!("Invalid response: {:?}", &response);
+            }
+        }
+        let expected_cert = rustls_pemfile::certs(&mut tls::test::CERT_PEM.as_bytes()).unwrap();
+        assert_eq!(cert.0, expected_cert[0]);
+
+        // the whole block arrives, even though the client sends nothing after its request and
+        // reads slowly enough that the server's socket stays full
+        let mut request = HttpRequestType::GetBlock(
+            HttpRequestMetadata::from_host(
+                PeerHost::from_host_port("localhost".to_string(), 51111),
+                None,
+            ),
+            big_block_id.clone(),
+        );
+        request.metadata_mut().keep_alive = false;
+        let (resp, _) =
+            tls_http_request(51111, &StacksHttp::serialize_request(&request).unwrap(), 20);
+        assert!(resp.len() > 6_000_000);
+        match StacksHttp::parse_response(&format!("/v2/blocks/{}", &big_block_id), &resp).unwrap() {
+            StacksHttpMessage::Response(HttpResponseType::Block(_, block)) => {
+                assert_eq!(block, big_block);
+            }
+            response => {
+                panic!("Invalid response: {:?}", &response);
+            }
+        }
+
+        // plaintext clients get no HTTP response
+        let mut sock =
+            TcpStream::connect(&"127.0.0.1:51111".parse::<SocketAddr>().unwrap()).unwrap();
+        sock.write_all(&request_bytes).unwrap();
+        let mut resp = vec![];
+        let _ = sock.read_to_end(&mut resp);
+        assert!(StacksHttp::parse_response("/v2/info", &resp).is_err());
+
+        // renew the certificate, and have the server pick it up
+        fs::write(&cert_path, tls::test::CERT_2_PEM).unwrap();
+        fs::write(&key_path, tls::test::KEY_2_PEM).unwrap();
+        tls::request_reload();
+
+        let (resp, cert) = tls_http_request(51111, &request_bytes, 0);
+        match StacksHttp::parse_response("/v2/info", &resp).unwrap() {
+            StacksHttpMessage::Response(HttpResponseType::PeerInfo(..)) => {}
+            response => {
+                panic!("Invalid response: {:?}", &response);
+            }
+        }
+        let expected_cert = rustls_pemfile::certs(&mut tls::test::CERT_2_PEM.as_bytes()).unwrap();
+        assert_eq!(cert.0, expected_cert[0]);
+
+        http_sx.send(true).unwrap();
+        http_thread.join().unwrap();
+    }
+
+    #[test]
+    fn test_http_event_stream() {
+        let peer_config = TestPeerConfig::new(function_name!(), 51090, 51091);
+        let mut peer = TestPeer::new(peer_config);
+        let event_stream = EventStreamHub::new(1, 16);
+        peer.event_stream = Some(event_stream.clone());
+
+        let (http_sx, http_rx) = sync_channel(1);
+        let http_thread = thread::spawn(move || loop {
+            peer.step().unwrap();
+            if http_rx.try_recv().is_ok() {
+                break;
+            }
+        });
+
+        let mut sock = TcpStream::connect(
+            &format!("127.0.0.1:{}", 51091)
+                .parse::<SocketAddr>()
+                .unwrap(),
+        )
+        .unwrap();
+        let request = HttpRequestType::GetEventStream(
+            HttpRequestMetadata::from_host(
+                PeerHost::from_host_port("127.0.0.1".to_string(), 51091),
+                None,
+            ),
+            vec![ChainEventKind::BurnBlock],
+        );
+        sock.write_all(&StacksHttp::serialize_request(&request).unwrap())
+            .unwrap();
+
+        while event_stream.num_subscribers() == 0 {
+            sleep_ms(100);
+        }
+        event_stream.publish(ChainEventKind::BurnBlock, &json!({"burn_block_height": 1}));
+        event_stream.publish(ChainEventKind::Block, &json!({"block_height": 1}));
+        event_stream.publish(ChainEventKind::BurnBlock, &json!({"burn_block_height": 2}));
+
+        // events show up without the client sending anything else
+        let last_event = "event: new_burn_block\ndata: {\"burn_block_height\":2}\n\n";
+        let mut received = vec![];
+        while !String::from_utf8_lossy(&received).contains(last_event) {
+            let mut buf = [0u8; 4096];
+            let nr = sock.read(&mut buf).unwrap();
+            assert!(nr > 0);
+            received.extend_from_slice(&buf[0..nr]);
+        }
+
+        let received = String::from_utf8(received).unwrap();
+        assert!(received.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(received.contains("Content-Type: text/event-stream\r\n"));
+        assert!(received.contains("Transfer-Encoding: chunked"));
+        assert!(received.contains("event: new_burn_block\ndata: {\"burn_block_height\":1}\n\n"));
+        assert!(!received.contains("event: new_block\n"));
+
+        // the client's subscription goes away with its connection
+        drop(sock);
+        while event_stream.num_subscribers() > 0 {
+            sleep_ms(100);
+            event_stream.publish(ChainEventKind::BurnBlock, &json!({"burn_block_height": 3}));
+        }
+
+        http_sx.send(true).unwrap();
+        http_thread.join().unwrap();
+    }
 }
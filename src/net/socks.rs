@@ -0,0 +1,586 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2022 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::io;
+use std::io::{Read, Write};
+use std::net::SocketAddr;
+
+use mio::net as mio_net;
+
+use crate::net::poll::NetworkState;
+use crate::net::Error as net_error;
+use crate::net::PeerHostName;
+
+const SOCKS5_VERSION: u8 = 0x05;
+const SOCKS5_AUTH_NONE: u8 = 0x00;
+const SOCKS5_CMD_CONNECT: u8 = 0x01;
+const SOCKS5_ATYP_IPV4: u8 = 0x01;
+const SOCKS5_ATYP_DOMAIN: u8 = 0x03;
+const SOCKS5_ATYP_IPV6: u8 = 0x04;
+
+/// Where a proxied connection should go
+#[derive(Debug, Clone, PartialEq)]
+pub enum Socks5Target {
+    Addr(SocketAddr),
+    /// The name is handed to the proxy as-is, so it gets resolved on the far side of the proxy.
+    Host(PeerHostName, u16),
+}
+
+impl Socks5Target {
+    /// The address the rest of the network stack knows this target by.  Named targets are known
+    /// by their synthetic address.
+    pub fn to_socketaddr(&self) -> SocketAddr {
+        match *self {
+            Socks5Target::Addr(ref addr) => *addr,
+            Socks5Target::Host(ref host, ref port) => host.to_peer_address().to_socketaddr(*port),
+        }
+    }
+
+    /// Encode the CONNECT request for this target
+    fn connect_request(&self) -> Vec<u8> {
+        let mut req = vec![SOCKS5_VERSION, SOCKS5_CMD_CONNECT, 0x00];
+        let port = match *self {
+            Socks5Target::Addr(SocketAddr::V4(ref addr)) => {
+                req.push(SOCKS5_ATYP_IPV4);
+                req.extend_from_slice(&addr.ip().octets());
+                addr.port()
+            }
+            Socks5Target::Addr(SocketAddr::V6(ref addr)) => {
+                req.push(SOCKS5_ATYP_IPV6);
+                req.extend_from_slice(&addr.ip().octets());
+                addr.port()
+            }
+            Socks5Target::Host(ref host, ref port) => {
+                // PeerHostName is never longer than 253 bytes
+                req.push(SOCKS5_ATYP_DOMAIN);
+                req.push(host.as_str().len() as u8);
+                req.extend_from_slice(host.as_str().as_bytes());
+                *port
+            }
+        };
+        req.extend_from_slice(&port.to_be_bytes());
+        req
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Socks5State {
+    SendGreeting,
+    RecvMethod,
+    SendConnect,
+    /// Receiving the reply's version, status, reserved byte, address type, and the first byte of
+    /// the bound address (which is the length of the address if it's a domain name)
+    RecvReply,
+    /// Receiving the rest of the bound address and port, which we otherwise ignore
+    RecvBoundAddr(usize),
+    Done,
+}
+
+/// The client side of a SOCKS5 CONNECT (RFC 1928), without authentication, over a non-blocking
+/// socket.  Once the socket to the proxy is connected, call `try_advance()` each time it becomes
+/// ready until the tunnel to the target is open; from then on, the socket behaves as if it were
+/// connected to the target itself.
+#[derive(Debug)]
+pub struct Socks5Handshake {
+    target: Socks5Target,
+    state: Socks5State,
+    sendbuf: Vec<u8>,
+    sent: usize,
+    recvbuf: Vec<u8>,
+}
+
+impl Socks5Handshake {
+    pub fn new(target: Socks5Target) -> Socks5Handshake {
+        Socks5Handshake {
+            target,
+            state: Socks5State::SendGreeting,
+            sendbuf: vec![SOCKS5_VERSION, 1, SOCKS5_AUTH_NONE],
+            sent: 0,
+            recvbuf: vec![],
+        }
+    }
+
+    /// Begin a connection to `target` through the SOCKS5 proxy at `proxy`.
+    /// Like `NetworkState::connect()`, the connect is asynchronous:  the caller registers the
+    /// socket, and drives the returned handshake once the socket is ready.
+    pub fn connect(
+        proxy: &SocketAddr,
+        target: Socks5Target,
+    ) -> Result<(mio_net::TcpStream, Socks5Handshake), net_error> {
+        let sock = NetworkState::connect(proxy)?;
+        Ok((sock, Socks5Handshake::new(target)))
+    }
+
+    pub fn target(&self) -> &Socks5Target {
+        &self.target
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.state == Socks5State::Done
+    }
+
+    /// Send as much of the pending request as the socket will take.
+    /// Returns true once all of it has been sent.
+    fn try_send<S: Write>(&mut self, sock: &mut S) -> Result<bool, net_error> {
+        while self.sent < self.sendbuf.len() {
+            match sock.write(&self.sendbuf[self.sent..]) {
+                Ok(0) => {
+                    return Err(net_error::ConnectionBroken);
+                }
+                Ok(n) => {
+                    self.sent += n;
+                }
+                Err(e) => match e.kind() {
+                    io::ErrorKind::WouldBlock => {
+                        return Ok(false);
+                    }
+                    io::ErrorKind::Interrupted => {}
+                    _ => {
+                        return Err(net_error::WriteError(e));
+                    }
+                },
+            }
+        }
+        Ok(true)
+    }
+
+    /// Receive until we have `want` bytes of the reply, without reading past it (the proxy
+    /// relays the target's bytes right after the reply).
+    /// Returns true once we have them all.
+    fn try_recv<S: Read>(&mut self, sock: &mut S, want: usize) -> Result<bool, net_error> {
+        let mut buf = [0u8; 512];
+        while self.recvbuf.len() < want {
+            let remaining = want - self.recvbuf.len();
+            match sock.read(&mut buf[0..remaining]) {
+                Ok(0) => {
+                    return Err(net_error::ConnectionBroken);
+                }
+                Ok(n) => {
+                    self.recvbuf.extend_from_slice(&buf[0..n]);
+                }
+                Err(e) => match e.kind() {
+                    io::ErrorKind::WouldBlock => {
+                        return Ok(false);
+                    }
+                    io::ErrorKind::Interrupted => {}
+                    _ => {
+                        return Err(net_error::ReadError(e));
+                    }
+                },
+            }
+        }
+        Ok(true)
+    }
+
+    fn reply_error(&self, status: u8) -> net_error {
+        let reason = match status {
+            0x01 => "general failure",
+            0x02 => "connection not allowed by ruleset",
+            0x03 => "network unreachable",
+            0x04 => "host unreachable",
+            0x05 => "connection refused",
+            0x06 => "TTL expired",
+            0x07 => "command not supported",
+            0x08 => "address type not supported",
+            _ => "unknown error",
+        };
+        net_error::ProxyError(format!(
+            "SOCKS5 proxy could not connect to {:?}: {} ({})",
+            &self.target, reason, status
+        ))
+    }
+
+    /// Make as much progress on the handshake as the socket allows.
+    /// Returns true once the tunnel to the target is open, and false if we need to wait for the
+    /// socket to become ready again.
+    pub fn try_advance<S: Read + Write>(&mut self, sock: &mut S) -> Result<bool, net_error> {
+        loop {
+            match self.state {
+                Socks5State::SendGreeting => {
+                    if !self.try_send(sock)? {
+                        return Ok(false);
+                    }
+                    self.state = Socks5State::RecvMethod;
+                }
+                Socks5State::RecvMethod => {
+                    if !self.try_recv(sock, 2)? {
+                        return Ok(false);
+                    }
+                    if self.recvbuf[0] != SOCKS5_VERSION {
+                        return Err(net_error::ProxyError(format!(
+                            "Not a SOCKS5 proxy (version {})",
+                            self.recvbuf[0]
+                        )));
+                    }
+                    if self.recvbuf[1] != SOCKS5_AUTH_NONE {
+                        return Err(net_error::ProxyError(
+                            "SOCKS5 proxy requires authentication".to_string(),
+                        ));
+                    }
+                    self.recvbuf.clear();
+                    self.sendbuf = self.target.connect_request();
+                    self.sent = 0;
+                    self.state = Socks5State::SendConnect;
+                }
+                Socks5State::SendConnect => {
+                    if !self.try_send(sock)? {
+                        return Ok(false);
+                    }
+                    self.state = Socks5State::RecvReply;
+                }
+                Socks5State::RecvReply => {
+                    if !self.try_recv(sock, 5)? {
+                        return Ok(false);
+                    }
+                    if self.recvbuf[0] != SOCKS5_VERSION {
+                        return Err(net_error::ProxyError(format!(
+                            "Not a SOCKS5 proxy (version {})",
+                            self.recvbuf[0]
+                        )));
+                    }
+                    if self.recvbuf[1] != 0x00 {
+                        return Err(self.reply_error(self.recvbuf[1]));
+                    }
+                    // the rest of the bound address, plus the port
+                    let rest = match self.recvbuf[3] {
+                        SOCKS5_ATYP_IPV4 => 3 + 2,
+                        SOCKS5_ATYP_IPV6 => 15 + 2,
+                        SOCKS5_ATYP_DOMAIN => (self.recvbuf[4] as usize) + 2,
+                        atyp => {
+                            return Err(net_error::ProxyError(format!(
+                                "SOCKS5 proxy replied with unknown address type {}",
+                                atyp
+                            )));
+                        }
+                    };
+                    self.state = Socks5State::RecvBoundAddr(5 + rest);
+                }
+                Socks5State::RecvBoundAddr(want) => {
+                    if !self.try_recv(sock, want)? {
+                        return Ok(false);
+                    }
+                    self.recvbuf.clear();
+                    self.state = Socks5State::Done;
+                }
+                Socks5State::Done => {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+pub mod test {
+    use std::collections::HashMap;
+    use std::io::{Read, Write};
+    use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+    use std::thread;
+
+    /// A bare-bones SOCKS5 proxy for tests.  It accepts unauthenticated CONNECTs to IP addresses,
+    /// and to the DNS names in its `hosts` map, and relays bytes in both directions.  It records
+    /// each target it was asked to connect to.
+    pub struct TestSocks5Proxy {
+        pub addr: SocketAddr,
+        pub requests: std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+    }
+
+    fn relay(mut from: TcpStream, mut to: TcpStream) {
+        let mut buf = [0u8; 4096];
+        loop {
+            match from.read(&mut buf) {
+                Ok(0) | Err(_) => {
+                    break;
+                }
+                Ok(n) => {
+                    if to.write_all(&buf[0..n]).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+        let _ = to.shutdown(Shutdown::Write);
+    }
+
+    fn handle_client(
+        mut client: TcpStream,
+        hosts: &HashMap<String, SocketAddr>,
+        requests: &std::sync::Mutex<Vec<String>>,
+    ) -> Option<()> {
+        let mut greeting = [0u8; 2];
+        client.read_exact(&mut greeting).ok()?;
+        let mut methods = vec![0u8; greeting[1] as usize];
+        client.read_exact(&mut methods).ok()?;
+        client.write_all(&[0x05, 0x00]).ok()?;
+
+        let mut header = [0u8; 4];
+        client.read_exact(&mut header).ok()?;
+        let target = match header[3] {
+            0x01 => {
+                let mut octets = [0u8; 4];
+                client.read_exact(&mut octets).ok()?;
+                format!("{}.{}.{}.{}", octets[0], octets[1], octets[2], octets[3])
+            }
+            0x03 => {
+                let mut len = [0u8; 1];
+                client.read_exact(&mut len).ok()?;
+                let mut name = vec![0u8; len[0] as usize];
+                client.read_exact(&mut name).ok()?;
+                String::from_utf8(name).ok()?
+            }
+            _ => {
+                let mut octets = [0u8; 16];
+                client.read_exact(&mut octets).ok()?;
+                format!("{}", std::net::Ipv6Addr::from(octets))
+            }
+        };
+        let mut port = [0u8; 2];
+        client.read_exact(&mut port).ok()?;
+        let port = u16::from_be_bytes(port);
+        requests
+            .lock()
+            .unwrap()
+            .push(format!("{}:{}", &target, port));
+
+        let dest = match hosts.get(&target) {
+            Some(addr) => Some(*addr),
+            None => format!("{}:{}", &target, port).parse::<SocketAddr>().ok(),
+        };
+        let upstream = match dest.and_then(|addr| TcpStream::connect(addr).ok()) {
+            Some(upstream) => upstream,
+            None => {
+                // host unreachable
+                let _ = client.write_all(&[0x05, 0x04, 0x00, 0x01, 0, 0, 0, 0, 0, 0]);
+                return None;
+            }
+        };
+        client
+            .write_all(&[0x05, 0x00, 0x00, 0x01, 127, 0, 0, 1, 0, 0])
+            .ok()?;
+
+        let client_2 = client.try_clone().ok()?;
+        let upstream_2 = upstream.try_clone().ok()?;
+        thread::spawn(move || relay(client_2, upstream_2));
+        relay(upstream, client);
+        Some(())
+    }
+
+    impl TestSocks5Proxy {
+        /// Run the proxy on the given port.  `hosts` maps DNS names to where they really are.
+        pub fn spawn(port: u16, hosts: HashMap<String, SocketAddr>) -> TestSocks5Proxy {
+            let addr: SocketAddr = format!("127.0.0.1:{}", port).parse().unwrap();
+            let listener = TcpListener::bind(addr).unwrap();
+            let requests = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+            let thread_requests = requests.clone();
+            thread::spawn(move || {
+                for client in listener.incoming() {
+                    let client = match client {
+                        Ok(c) => c,
+                        Err(_) => {
+                            continue;
+                        }
+                    };
+                    let hosts = hosts.clone();
+                    let requests = thread_requests.clone();
+                    thread::spawn(move || handle_client(client, &hosts, &requests));
+                }
+            });
+            TestSocks5Proxy { addr, requests }
+        }
+
+        pub fn get_requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+    use std::convert::TryFrom;
+
+    /// An in-memory socket that moves at most `chunk` bytes per call, and says WouldBlock
+    /// whenever it has nothing to read
+    struct TrickleSocket {
+        inbound: VecDeque<u8>,
+        outbound: Vec<u8>,
+        chunk: usize,
+    }
+
+    impl TrickleSocket {
+        fn new(inbound: &[u8], chunk: usize) -> TrickleSocket {
+            TrickleSocket {
+                inbound: inbound.iter().cloned().collect(),
+                outbound: vec![],
+                chunk,
+            }
+        }
+    }
+
+    impl Read for TrickleSocket {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.inbound.len() == 0 {
+                return Err(io::Error::from(io::ErrorKind::WouldBlock));
+            }
+            let mut n = 0;
+            while n < buf.len() && n < self.chunk {
+                match self.inbound.pop_front() {
+                    Some(b) => {
+                        buf[n] = b;
+                        n += 1;
+                    }
+                    None => {
+                        break;
+                    }
+                }
+            }
+            Ok(n)
+        }
+    }
+
+    impl Write for TrickleSocket {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.chunk);
+            self.outbound.extend_from_slice(&buf[0..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_socks5_handshake_ip() {
+        let target: SocketAddr = "1.2.3.4:20444".parse().unwrap();
+        let reply = vec![
+            // method selection
+            0x05, 0x00, // connect reply, bound to [::1]:1234
+            0x05, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x01, 0x04,
+            0xd2, // target data, which must be left alone
+            0xaa, 0xbb,
+        ];
+        for chunk in [1, 3, 512].iter() {
+            let mut sock = TrickleSocket::new(&reply, *chunk);
+            let mut handshake = Socks5Handshake::new(Socks5Target::Addr(target.clone()));
+
+            let mut passes = 0;
+            while !handshake.try_advance(&mut sock).unwrap() {
+                passes += 1;
+                assert!(passes < 100);
+            }
+            assert!(handshake.is_done());
+            assert_eq!(handshake.target().to_socketaddr(), target);
+            assert_eq!(
+                sock.outbound,
+                vec![
+                    // greeting
+                    0x05, 0x01, 0x00, // connect to 1.2.3.4:20444
+                    0x05, 0x01, 0x00, 0x01, 0x01, 0x02, 0x03, 0x04, 0x4f, 0xdc,
+                ]
+            );
+            assert_eq!(sock.inbound, vec![0xaa, 0xbb]);
+        }
+    }
+
+    #[test]
+    fn test_socks5_handshake_host() {
+        let host = PeerHostName::try_from("abc.onion").unwrap();
+        let reply = vec![
+            // method selection
+            0x05, 0x00, // connect reply, bound to a domain name
+            0x05, 0x00, 0x00, 0x03, 0x03, 0x61, 0x62, 0x63, 0x00, 0x50,
+        ];
+        let mut sock = TrickleSocket::new(&reply, 2);
+        let mut handshake = Socks5Handshake::new(Socks5Target::Host(host.clone(), 20444));
+        while !handshake.try_advance(&mut sock).unwrap() {}
+
+        assert_eq!(
+            sock.outbound,
+            vec![
+                // greeting
+                0x05, 0x01, 0x00, // connect to abc.onion:20444
+                0x05, 0x01, 0x00, 0x03, 0x09, 0x61, 0x62, 0x63, 0x2e, 0x6f, 0x6e, 0x69, 0x6f, 0x6e,
+                0x4f, 0xdc,
+            ]
+        );
+        assert_eq!(sock.inbound.len(), 0);
+
+        // known to the rest of the network stack by its synthetic address
+        let addr = handshake.target().to_socketaddr();
+        assert_eq!(addr.port(), 20444);
+        assert_eq!(
+            crate::net::PeerAddress::from_socketaddr(&addr),
+            host.to_peer_address()
+        );
+    }
+
+    #[test]
+    fn test_socks5_handshake_failures() {
+        let target = Socks5Target::Addr("1.2.3.4:20444".parse().unwrap());
+
+        // not a SOCKS5 proxy
+        let mut sock = TrickleSocket::new(&[0x04, 0x00], 512);
+        let mut handshake = Socks5Handshake::new(target.clone());
+        match handshake.try_advance(&mut sock) {
+            Err(net_error::ProxyError(_)) => {}
+            x => panic!("Unexpected result {:?}", &x),
+        }
+
+        // wants a password
+        let mut sock = TrickleSocket::new(&[0x05, 0x02], 512);
+        let mut handshake = Socks5Handshake::new(target.clone());
+        match handshake.try_advance(&mut sock) {
+            Err(net_error::ProxyError(_)) => {}
+            x => panic!("Unexpected result {:?}", &x),
+        }
+
+        // connection refused
+        let mut sock =
+            TrickleSocket::new(&[0x05, 0x00, 0x05, 0x05, 0x00, 0x01, 0, 0, 0, 0, 0, 0], 512);
+        let mut handshake = Socks5Handshake::new(target.clone());
+        match handshake.try_advance(&mut sock) {
+            Err(net_error::ProxyError(msg)) => {
+                assert!(msg.contains("connection refused"));
+            }
+            x => panic!("Unexpected result {:?}", &x),
+        }
+
+        // proxy hung up mid-handshake
+        struct HungUp;
+        impl Read for HungUp {
+            fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+                Ok(0)
+            }
+        }
+        impl Write for HungUp {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                Ok(buf.len())
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+        let mut handshake = Socks5Handshake::new(target.clone());
+        match handshake.try_advance(&mut HungUp) {
+            Err(net_error::ConnectionBroken) => {}
+            x => panic!("Unexpected result {:?}", &x),
+        }
+        assert!(!handshake.is_done());
+    }
+}
@@ -1,7 +1,11 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::convert::TryFrom;
 use std::convert::TryInto;
 use std::fs;
 use std::net::{SocketAddr, ToSocketAddrs};
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::Mutex;
 
@@ -15,7 +19,10 @@ use stacks::chainstate::stacks::index::storage::TrieHashCalculationMode;
 use stacks::chainstate::stacks::miner::BlockBuilderSettings;
 use stacks::chainstate::stacks::miner::MinerStatus;
 use stacks::chainstate::stacks::MAX_BLOCK_LEN;
+use stacks::core::mempool::MemPoolLimits;
 use stacks::core::mempool::MemPoolWalkSettings;
+use stacks::core::mempool::ReplaceByFeePolicy;
+use stacks::core::tx_selection::{TxSelectionList, TxSelectionSettings, TxSelectionStrategyName};
 use stacks::core::StacksEpoch;
 use stacks::core::StacksEpochExtension;
 use stacks::core::StacksEpochId;
@@ -31,7 +38,10 @@ use stacks::cost_estimates::CostEstimator;
 use stacks::cost_estimates::FeeEstimator;
 use stacks::cost_estimates::PessimisticEstimator;
 use stacks::net::connection::ConnectionOptions;
-use stacks::net::{Neighbor, NeighborKey, PeerAddress};
+use stacks::net::http::{RPCAuthToken, RPCScope};
+use stacks::net::server::RPCRateLimit;
+use stacks::net::tls::TlsAcceptor;
+use stacks::net::{Neighbor, NeighborKey, PeerAddress, PeerHostName};
 use stacks::util::get_epoch_time_ms;
 use stacks::util::hash::hex_bytes;
 use stacks::util::secp256k1::Secp256k1PrivateKey;
@@ -39,6 +49,8 @@ use stacks::util::secp256k1::Secp256k1PublicKey;
 use stacks::vm::costs::ExecutionCost;
 use stacks::vm::types::{AssetIdentifier, PrincipalData, QualifiedContractIdentifier};
 
+use crate::event_filter::EventFilter;
+
 const DEFAULT_SATS_PER_VB: u64 = 50;
 const DEFAULT_MAX_RBF_RATE: u64 = 150; // 1.5x
 const DEFAULT_RBF_FEE_RATE_INCREMENT: u64 = 5;
@@ -55,6 +67,7 @@ pub struct ConfigFile {
     pub connection_options: Option<ConnectionOptionsFile>,
     pub fee_estimation: Option<FeeEstimationConfigFile>,
     pub miner: Option<MinerConfigFile>,
+    pub mempool: Option<MemPoolConfigFile>,
 }
 
 #[derive(Clone, Deserialize, Default)]
@@ -129,6 +142,307 @@ mod tests {
         assert!(Config::from_config_file(ConfigFile::from_str("").unwrap()).is_ok());
     }
 
+    #[test]
+    fn test_event_observer_filter_config() {
+        assert_eq!(
+            format!("Invalid transaction type in event filter: Transfer"),
+            Config::from_config_file(
+                ConfigFile::from_str(
+                    r#"
+                    [[events_observer]]
+                    endpoint = "localhost:3700"
+                    events_keys = ["*"]
+                    filter = { tx_types = ["Transfer"] }
+                    "#,
+                )
+                .unwrap()
+            )
+            .unwrap_err()
+        );
+
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [[events_observer]]
+                endpoint = "localhost:3700"
+                events_keys = ["*"]
+
+                [events_observer.filter]
+                topics = ["print"]
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(config.events_observers[0].filter.is_some());
+    }
+
+    #[test]
+    fn test_event_observer_duplicate_endpoint() {
+        assert_eq!(
+            format!("More than one event observer is configured with endpoint 'localhost:3700'"),
+            Config::from_config_file(
+                ConfigFile::from_str(
+                    r#"
+                    [[events_observer]]
+                    endpoint = "localhost:3700"
+                    events_keys = ["*"]
+
+                    [[events_observer]]
+                    endpoint = "localhost:3700"
+                    events_keys = ["stx"]
+                    filter = { topics = ["print"] }
+                    "#,
+                )
+                .unwrap()
+            )
+            .unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_socks5_proxy_config() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [node]
+                bootstrap_node = "02da7a464ac770ae8337a343670778b93410f2f3fef6bea98dd1c3e9224459d36b@abcdefghijklmnop.onion:20444,02da7a464ac770ae8337a343670778b93410f2f3fef6bea98dd1c3e9224459d36b@127.0.0.1:20444"
+
+                [connection_options]
+                socks5_proxy = "127.0.0.1:9050"
+                public_host = "qrstuvwxyz234567.onion:20444"
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let onion = PeerHostName::try_from("abcdefghijklmnop.onion").unwrap();
+        assert_eq!(
+            config.connection_options.socks5_proxy,
+            Some("127.0.0.1:9050".parse::<SocketAddr>().unwrap())
+        );
+        assert_eq!(
+            config.connection_options.public_host,
+            Some(PeerHostName::try_from("qrstuvwxyz234567.onion").unwrap())
+        );
+        assert_eq!(
+            config.connection_options.public_ip_address,
+            Some((
+                PeerHostName::try_from("qrstuvwxyz234567.onion")
+                    .unwrap()
+                    .to_peer_address(),
+                20444
+            ))
+        );
+        assert_eq!(config.node.bootstrap_hosts, vec![onion.clone()]);
+        assert_eq!(config.node.bootstrap_node.len(), 2);
+        assert_eq!(
+            config.node.bootstrap_node[0].addr.addrbytes,
+            onion.to_peer_address()
+        );
+        assert_eq!(config.node.bootstrap_node[0].addr.port, 20444);
+        assert_eq!(
+            config.node.bootstrap_node[1].addr.addrbytes,
+            PeerAddress::from_ipv4(127, 0, 0, 1)
+        );
+
+        assert!(Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [connection_options]
+                socks5_proxy = "localhost"
+                "#,
+            )
+            .unwrap(),
+        )
+        .is_err());
+
+        assert!(Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [connection_options]
+                public_ip_address = "1.2.3.4:20444"
+                public_host = "qrstuvwxyz234567.onion:20444"
+                "#,
+            )
+            .unwrap(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_rpc_auth_tokens_config() {
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [connection_options]
+                admin_rpc_token = "admin-secret"
+
+                [[connection_options.rpc_auth_tokens]]
+                name = "miners"
+                secret = "miners-secret"
+                scopes = ["post_block"]
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(config.connection_options.rpc_auth_tokens.len(), 2);
+
+        for bad_tokens in [
+            // empty name
+            r#"
+            [[connection_options.rpc_auth_tokens]]
+            name = ""
+            secret = "miners-secret"
+            scopes = ["post_block"]
+            "#,
+            // empty secret
+            r#"
+            [[connection_options.rpc_auth_tokens]]
+            name = "miners"
+            secret = ""
+            scopes = ["post_block"]
+            "#,
+            // a name that HMAC credentials can't carry
+            r#"
+            [[connection_options.rpc_auth_tokens]]
+            name = "min:ers"
+            secret = "miners-secret"
+            scopes = ["post_block"]
+            "#,
+            // duplicate name
+            r#"
+            [[connection_options.rpc_auth_tokens]]
+            name = "miners"
+            secret = "miners-secret"
+            scopes = ["post_block"]
+
+            [[connection_options.rpc_auth_tokens]]
+            name = "miners"
+            secret = "other-secret"
+            scopes = ["post_tx"]
+            "#,
+            // duplicate secret, including the admin token's
+            r#"
+            [connection_options]
+            admin_rpc_token = "miners-secret"
+
+            [[connection_options.rpc_auth_tokens]]
+            name = "miners"
+            secret = "miners-secret"
+            scopes = ["post_block"]
+            "#,
+        ] {
+            assert!(
+                Config::from_config_file(ConfigFile::from_str(bad_tokens).unwrap()).is_err(),
+                "{}",
+                bad_tokens
+            );
+        }
+    }
+
+    #[test]
+    fn test_rpc_tls_config() {
+        let tls_dir = "/tmp/stacks-node-tests/test_rpc_tls_config";
+        if fs::metadata(tls_dir).is_ok() {
+            fs::remove_dir_all(tls_dir).unwrap();
+        }
+        fs::create_dir_all(tls_dir).unwrap();
+        let cert_path = format!("{}/cert.pem", tls_dir);
+        let key_path = format!("{}/key.pem", tls_dir);
+        fs::write(&cert_path, "not a certificate").unwrap();
+        fs::write(&key_path, "not a key").unwrap();
+
+        for (cert_path, key_path) in [
+            // missing files
+            (format!("{}/nope.pem", tls_dir), key_path.clone()),
+            // files without a certificate or key
+            (cert_path.clone(), key_path.clone()),
+        ] {
+            let config_file = ConfigFile::from_str(&format!(
+                r#"
+                [connection_options]
+                rpc_tls_cert_path = "{}"
+                rpc_tls_key_path = "{}"
+                "#,
+                cert_path, key_path
+            ))
+            .unwrap();
+            assert!(Config::from_config_file(config_file).is_err());
+        }
+    }
+
+    #[test]
+    fn test_mempool_config() {
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap()).unwrap();
+        assert_eq!(config.mempool.rbf_policy(), ReplaceByFeePolicy::default());
+
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [mempool]
+                rbf_min_fee_increase = 1000
+                rbf_min_fee_increase_percent = 25
+                max_tx_count = 10000
+                max_txs_per_account = 25
+                max_nonce_gap = 5
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            config.mempool.rbf_policy(),
+            ReplaceByFeePolicy {
+                min_fee_increase: 1000,
+                min_fee_increase_percent: 25,
+            }
+        );
+        assert_eq!(
+            config.mempool.limits(),
+            MemPoolLimits {
+                max_tx_count: Some(10000),
+                max_bytes: None,
+                max_txs_per_account: Some(25),
+                max_bytes_per_account: None,
+                max_nonce_gap: Some(5),
+            }
+        );
+    }
+
+    #[test]
+    fn test_miner_tx_selection_config() {
+        let config = Config::from_config_file(ConfigFile::from_str("").unwrap()).unwrap();
+        assert_eq!(config.miner.tx_selection, TxSelectionSettings::default());
+
+        let config = Config::from_config_file(
+            ConfigFile::from_str(
+                r#"
+                [miner]
+                tx_selection_strategy = "cost_packing"
+                tx_priority_list = ["SP1A2K3ENNA6QQ7G8DVJXM24T6QMBDVS7D0TRTAR5", "SP000000000000000000002Q6VF78.pox"]
+                tx_exclusion_list = ["SP3D6PV2ACBPEKYJTCMH7HEN02KP87QSP8KTEH335"]
+                "#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let tx_selection = config
+            .make_block_builder_settings(1, false, Arc::new(Mutex::new(MinerStatus::make_ready(0))))
+            .mempool_settings
+            .tx_selection;
+        assert_eq!(tx_selection.strategy, TxSelectionStrategyName::CostPacking);
+        assert_eq!(tx_selection.priority_list.principals.len(), 1);
+        assert_eq!(
+            tx_selection.priority_list.contracts,
+            vec![QualifiedContractIdentifier::parse("SP000000000000000000002Q6VF78.pox").unwrap()]
+        );
+        assert_eq!(tx_selection.exclusion_list.principals.len(), 1);
+        assert!(tx_selection.exclusion_list.contracts.is_empty());
+    }
+
     #[test]
     fn should_load_legacy_mstx_balances_toml() {
         let config = ConfigFile::from_str(
@@ -364,6 +678,7 @@ pub struct Config {
     pub events_observers: Vec<EventObserverConfig>,
     pub connection_options: ConnectionOptions,
     pub miner: MinerConfig,
+    pub mempool: MemPoolConfig,
     pub estimation: FeeEstimationConfig,
 }
 
@@ -615,6 +930,7 @@ impl Config {
                     p2p_bind: node.p2p_bind.unwrap_or(default_node_config.p2p_bind),
                     p2p_address: node.p2p_address.unwrap_or(rpc_bind.clone()),
                     bootstrap_node: vec![],
+                    bootstrap_hosts: vec![],
                     deny_nodes: vec![],
                     data_url: match node.data_url {
                         Some(data_url) => data_url,
@@ -667,6 +983,12 @@ impl Config {
                             !node.miner.unwrap_or(!default_node_config.miner)
                         }
                     },
+                    event_stream_max_clients: node
+                        .event_stream_max_clients
+                        .unwrap_or(default_node_config.event_stream_max_clients),
+                    event_stream_buffer: node
+                        .event_stream_buffer
+                        .unwrap_or(default_node_config.event_stream_buffer),
                     // chainstate fault_injection activation for hide_blocks.
                     // you can't set this in the config file.
                     fault_injection_hide_blocks: false,
@@ -871,10 +1193,62 @@ impl Config {
                 candidate_retry_cache_size: miner
                     .candidate_retry_cache_size
                     .unwrap_or(miner_default_config.candidate_retry_cache_size),
+                tx_selection: TxSelectionSettings {
+                    strategy: miner
+                        .tx_selection_strategy
+                        .as_ref()
+                        .map(|name| {
+                            TxSelectionStrategyName::from_str(name)
+                                .unwrap_or_else(|e| panic!("FATAL: {}", e))
+                        })
+                        .unwrap_or(miner_default_config.tx_selection.strategy),
+                    priority_list: miner
+                        .tx_priority_list
+                        .as_ref()
+                        .map(|list| {
+                            TxSelectionList::from_principals(list).unwrap_or_else(|e| {
+                                panic!("FATAL: bad miner.tx_priority_list: {}", e)
+                            })
+                        })
+                        .unwrap_or(miner_default_config.tx_selection.priority_list),
+                    exclusion_list: miner
+                        .tx_exclusion_list
+                        .as_ref()
+                        .map(|list| {
+                            TxSelectionList::from_principals(list).unwrap_or_else(|e| {
+                                panic!("FATAL: bad miner.tx_exclusion_list: {}", e)
+                            })
+                        })
+                        .unwrap_or(miner_default_config.tx_selection.exclusion_list),
+                },
             },
             None => miner_default_config,
         };
 
+        let mempool_default_config = MemPoolConfig::default();
+        let mempool = match config_file.mempool {
+            Some(ref mempool) => MemPoolConfig {
+                rbf_min_fee_increase: mempool
+                    .rbf_min_fee_increase
+                    .unwrap_or(mempool_default_config.rbf_min_fee_increase),
+                rbf_min_fee_increase_percent: mempool
+                    .rbf_min_fee_increase_percent
+                    .unwrap_or(mempool_default_config.rbf_min_fee_increase_percent),
+                max_tx_count: mempool.max_tx_count.or(mempool_default_config.max_tx_count),
+                max_bytes: mempool.max_bytes.or(mempool_default_config.max_bytes),
+                max_txs_per_account: mempool
+                    .max_txs_per_account
+                    .or(mempool_default_config.max_txs_per_account),
+                max_bytes_per_account: mempool
+                    .max_bytes_per_account
+                    .or(mempool_default_config.max_bytes_per_account),
+                max_nonce_gap: mempool
+                    .max_nonce_gap
+                    .or(mempool_default_config.max_nonce_gap),
+            },
+            None => mempool_default_config,
+        };
+
         let supported_modes = vec![
             "mocknet", "helium", "neon", "argon", "krypton", "xenon", "mainnet",
         ];
@@ -934,10 +1308,27 @@ impl Config {
                         .collect();
 
                     let endpoint = format!("{}", observer.endpoint);
+                    let default_observer = EventObserverConfig::default();
+                    let filter = match observer.filter {
+                        Some(ref filter) => Some(EventFilter::from_config_file(filter)?),
+                        None => None,
+                    };
 
                     observers.push(EventObserverConfig {
                         endpoint,
                         events_keys,
+                        timeout_ms: observer.timeout_ms.unwrap_or(default_observer.timeout_ms),
+                        retry_backoff_ms: observer
+                            .retry_backoff_ms
+                            .unwrap_or(default_observer.retry_backoff_ms),
+                        max_retry_backoff_ms: observer
+                            .max_retry_backoff_ms
+                            .unwrap_or(default_observer.max_retry_backoff_ms),
+                        max_attempts: observer.max_attempts,
+                        max_lag: observer.max_lag,
+                        filter,
+                        tls: observer.tls.unwrap_or(default_observer.tls),
+                        tls_ca_path: observer.tls_ca_path.clone(),
                     });
                 }
                 observers
@@ -950,10 +1341,22 @@ impl Config {
             Ok(val) => events_observers.push(EventObserverConfig {
                 endpoint: val,
                 events_keys: vec![EventKeyType::AnyEvent],
+                ..EventObserverConfig::default()
             }),
             _ => (),
         };
 
+        // each observer's outbox queue is keyed by its endpoint, so two observers cannot share one
+        let mut observer_endpoints = HashSet::new();
+        for observer in events_observers.iter() {
+            if !observer_endpoints.insert(observer.endpoint.as_str()) {
+                return Err(format!(
+                    "More than one event observer is configured with endpoint '{}'",
+                    &observer.endpoint
+                ));
+            }
+        }
+
         let connection_options = match config_file.connection_options {
             Some(opts) => {
                 let ip_addr = match opts.public_ip_address {
@@ -964,6 +1367,28 @@ impl Config {
                     }
                     None => None,
                 };
+                let socks5_proxy = match opts.socks5_proxy {
+                    Some(ref socks5_proxy) => {
+                        Some(socks5_proxy.parse::<SocketAddr>().map_err(|e| {
+                            format!("Invalid connection_options.socks5_proxy: {}", e)
+                        })?)
+                    }
+                    None => None,
+                };
+                let (public_host, ip_addr) = match opts.public_host {
+                    Some(ref public_host) => {
+                        if ip_addr.is_some() {
+                            return Err("Only one of connection_options.public_ip_address and connection_options.public_host may be set".to_string());
+                        }
+                        let (host, port) =
+                            NodeConfig::parse_peer_host(public_host).map_err(|e| {
+                                format!("Invalid connection_options.public_host: {}", e)
+                            })?;
+                        let addrbytes = host.to_peer_address();
+                        (Some(host), Some((addrbytes, port)))
+                    }
+                    None => (None, ip_addr),
+                };
                 let mut read_only_call_limit = HELIUM_DEFAULT_CONNECTION_OPTIONS
                     .read_only_call_limit
                     .clone();
@@ -982,6 +1407,110 @@ impl Config {
                 opts.read_only_call_limit_runtime.map(|x| {
                     read_only_call_limit.runtime = x;
                 });
+                let parse_rpc_scopes = |scopes: &[String]| -> HashSet<RPCScope> {
+                    scopes
+                        .iter()
+                        .map(|scope| {
+                            RPCScope::from_str(scope).unwrap_or_else(|e| panic!("FATAL: {}", e))
+                        })
+                        .collect()
+                };
+                let mut rpc_auth_tokens: Vec<RPCAuthToken> = opts
+                    .rpc_auth_tokens
+                    .as_ref()
+                    .map(|tokens| {
+                        tokens
+                            .iter()
+                            .map(|token| RPCAuthToken {
+                                name: token.name.clone(),
+                                secret: token.secret.clone(),
+                                scopes: parse_rpc_scopes(&token.scopes),
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let make_rpc_rate_limit = |burst: Option<u64>, per_minute: u64| {
+                    if per_minute == 0 {
+                        panic!("FATAL: RPC rate limits must refill by at least 1 per minute");
+                    }
+                    RPCRateLimit {
+                        burst: burst.unwrap_or(per_minute),
+                        per_minute,
+                    }
+                };
+                let rpc_rate_limits: HashMap<RPCScope, RPCRateLimit> = opts
+                    .rpc_rate_limits
+                    .as_ref()
+                    .map(|limits| {
+                        limits
+                            .iter()
+                            .map(|limit| {
+                                let scope = RPCScope::from_str(&limit.scope)
+                                    .unwrap_or_else(|e| panic!("FATAL: {}", e));
+                                (scope, make_rpc_rate_limit(limit.burst, limit.per_minute))
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let rpc_read_only_cost_limit =
+                    opts.rpc_read_only_cost_per_minute.map(|per_minute| {
+                        make_rpc_rate_limit(opts.rpc_read_only_cost_burst, per_minute)
+                    });
+                let rpc_auth_failure_limit = match opts.rpc_auth_failure_per_minute {
+                    Some(per_minute) => {
+                        make_rpc_rate_limit(opts.rpc_auth_failure_burst, per_minute)
+                    }
+                    None => ConnectionOptions::default().rpc_auth_failure_limit,
+                };
+                if opts.rpc_tls_cert_path.is_some() != opts.rpc_tls_key_path.is_some() {
+                    return Err("Config must set both or neither of `connection_options.rpc_tls_cert_path` and `connection_options.rpc_tls_key_path`".into());
+                }
+                if let (Some(cert_path), Some(key_path)) =
+                    (&opts.rpc_tls_cert_path, &opts.rpc_tls_key_path)
+                {
+                    // the RPC server loads them again at startup, but can't report errors as well
+                    TlsAcceptor::from_pem_files(cert_path, key_path).map_err(|e| {
+                        format!("Config has an unusable RPC TLS certificate or key: {}", e)
+                    })?;
+                }
+                if let Some(ref admin_rpc_token) = opts.admin_rpc_token {
+                    // shorthand for a token that only unlocks the admin endpoints
+                    rpc_auth_tokens.push(RPCAuthToken::new(
+                        "admin",
+                        admin_rpc_token,
+                        &[RPCScope::Admin],
+                    ));
+                }
+                let mut rpc_auth_token_names = HashSet::new();
+                let mut rpc_auth_token_secrets = HashSet::new();
+                for token in rpc_auth_tokens.iter() {
+                    if token.name.is_empty() || token.secret.is_empty() {
+                        return Err(
+                            "Config RPC auth tokens must have a non-empty `name` and `secret`"
+                                .into(),
+                        );
+                    }
+                    if token.name.contains(':') {
+                        // it would be ambiguous in HMAC credentials
+                        return Err(format!(
+                            "Config RPC auth token name '{}' must not contain ':'",
+                            &token.name
+                        ));
+                    }
+                    if !rpc_auth_token_names.insert(token.name.as_str()) {
+                        return Err(format!(
+                            "Config has more than one RPC auth token named '{}'",
+                            &token.name
+                        ));
+                    }
+                    if !rpc_auth_token_secrets.insert(token.secret.as_str()) {
+                        // don't log the secret itself
+                        return Err(format!(
+                            "Config RPC auth token '{}' has the same secret as another token",
+                            &token.name
+                        ));
+                    }
+                }
                 ConnectionOptions {
                     read_only_call_limit,
                     inbox_maxlen: opts
@@ -1087,6 +1616,8 @@ impl Config {
                         }
                     }),
                     public_ip_address: ip_addr,
+                    socks5_proxy,
+                    public_host,
                     disable_inbound_walks: opts.disable_inbound_walks.unwrap_or(false),
                     disable_inbound_handshakes: opts.disable_inbound_handshakes.unwrap_or(false),
                     disable_block_download: opts.disable_block_download.unwrap_or(false),
@@ -1098,6 +1629,17 @@ impl Config {
                     handshake_timeout: opts.connect_timeout.unwrap_or(5),
                     max_sockets: opts.max_sockets.unwrap_or(800) as usize,
                     antientropy_public: opts.antientropy_public.unwrap_or(true),
+                    rpc_public_scopes: opts
+                        .rpc_public_scopes
+                        .as_ref()
+                        .map(|scopes| parse_rpc_scopes(scopes))
+                        .unwrap_or_else(RPCScope::public_default),
+                    rpc_auth_tokens,
+                    rpc_rate_limits,
+                    rpc_read_only_cost_limit,
+                    rpc_auth_failure_limit,
+                    rpc_tls_cert_path: opts.rpc_tls_cert_path.clone(),
+                    rpc_tls_key_path: opts.rpc_tls_key_path.clone(),
                     ..ConnectionOptions::default()
                 }
             }
@@ -1117,6 +1659,7 @@ impl Config {
             connection_options,
             estimation,
             miner,
+            mempool,
         })
     }
 
@@ -1184,6 +1727,22 @@ impl Config {
         path.to_str().expect("Unable to produce path").to_string()
     }
 
+    /// Returns the path to the event observers' outbox, `{get_chainstate_path()}/../event_observers.sqlite`,
+    /// and ensures that its directory exists.
+    pub fn get_event_observers_db_path(&self) -> PathBuf {
+        let mut path = self.get_chainstate_path();
+        path.set_file_name("event_observers.sqlite");
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).unwrap_or_else(|_| {
+                panic!(
+                    "Failed to create event observer outbox directory at {}",
+                    dir.to_string_lossy()
+                )
+            });
+        }
+        path
+    }
+
     pub fn get_atlas_db_file_path(&self) -> String {
         let mut path = self.get_chainstate_path();
         path.set_file_name("atlas.sqlite");
@@ -1249,6 +1808,7 @@ impl Config {
                 consider_no_estimate_tx_prob: self.miner.probability_pick_no_estimate_tx,
                 nonce_cache_size: self.miner.nonce_cache_size,
                 candidate_retry_cache_size: self.miner.candidate_retry_cache_size,
+                tx_selection: self.miner.tx_selection.clone(),
             },
             miner_status,
         }
@@ -1277,6 +1837,7 @@ impl std::default::Default for Config {
             connection_options,
             estimation,
             miner: MinerConfig::default(),
+            mempool: MemPoolConfig::default(),
         }
     }
 }
@@ -1437,6 +1998,8 @@ pub struct NodeConfig {
     pub p2p_address: String,
     pub local_peer_seed: Vec<u8>,
     pub bootstrap_node: Vec<Neighbor>,
+    /// DNS names of bootstrap nodes that can only be reached through the SOCKS5 proxy
+    pub bootstrap_hosts: Vec<PeerHostName>,
     pub deny_nodes: Vec<Neighbor>,
     pub miner: bool,
     pub mock_mining: bool,
@@ -1452,6 +2015,11 @@ pub struct NodeConfig {
     pub use_test_genesis_chainstate: Option<bool>,
     pub always_use_affirmation_maps: bool,
     pub require_affirmed_anchor_blocks: bool,
+    /// Maximum number of RPC clients that can stream chain events from `/v2/events` at once.
+    /// 0 disables event streaming.
+    pub event_stream_max_clients: u64,
+    /// Maximum number of events queued for a streaming client before it is disconnected
+    pub event_stream_buffer: u64,
     // fault injection for hiding blocks.
     // not part of the config file.
     pub fault_injection_hide_blocks: bool,
@@ -1715,6 +2283,7 @@ impl NodeConfig {
             data_url: format!("http://127.0.0.1:{}", rpc_port),
             p2p_address: format!("127.0.0.1:{}", rpc_port),
             bootstrap_node: vec![],
+            bootstrap_hosts: vec![],
             deny_nodes: vec![],
             local_peer_seed: local_peer_seed.to_vec(),
             miner: false,
@@ -1731,6 +2300,8 @@ impl NodeConfig {
             use_test_genesis_chainstate: None,
             always_use_affirmation_maps: true,
             require_affirmed_anchor_blocks: true,
+            event_stream_max_clients: 0,
+            event_stream_buffer: 256,
             fault_injection_hide_blocks: false,
         }
     }
@@ -1771,12 +2342,46 @@ impl NodeConfig {
         let (pubkey_str, hostport) = (parts[0], parts[1]);
         let pubkey = Secp256k1PublicKey::from_hex(pubkey_str)
             .expect(&format!("Invalid public key '{}'", pubkey_str));
+        if NodeConfig::is_onion_hostport(hostport) {
+            // can't be resolved here; the proxy will dial it by name
+            let (host, port) = NodeConfig::parse_peer_host(hostport)
+                .unwrap_or_else(|e| panic!("Invalid bootstrap node '{}': {}", bootstrap_node, e));
+            let neighbor = NodeConfig::default_neighbor(
+                host.to_peer_address().to_socketaddr(port),
+                pubkey,
+                chain_id,
+                peer_version,
+            );
+            self.bootstrap_node.push(neighbor);
+            self.bootstrap_hosts.push(host);
+            return;
+        }
         debug!("Resolve '{}'", &hostport);
         let sockaddr = hostport.to_socket_addrs().unwrap().next().unwrap();
         let neighbor = NodeConfig::default_neighbor(sockaddr, pubkey, chain_id, peer_version);
         self.bootstrap_node.push(neighbor);
     }
 
+    fn is_onion_hostport(hostport: &str) -> bool {
+        match hostport.rsplit_once(':') {
+            Some((host, _port)) => host.to_lowercase().ends_with(".onion"),
+            None => false,
+        }
+    }
+
+    /// Parse a `NAME:PORT` string into a peer host name and port
+    pub fn parse_peer_host(hostport: &str) -> Result<(PeerHostName, u16), String> {
+        let (host_str, port_str) = hostport
+            .rsplit_once(':')
+            .ok_or_else(|| format!("expected NAME:PORT, got '{}'", hostport))?;
+        let port = port_str
+            .parse::<u16>()
+            .map_err(|e| format!("invalid port '{}': {}", port_str, e))?;
+        let host = PeerHostName::try_from(host_str)
+            .map_err(|e| format!("invalid host name '{}': {:?}", host_str, e))?;
+        Ok((host, port))
+    }
+
     pub fn set_bootstrap_nodes(
         &mut self,
         bootstrap_nodes: String,
@@ -1844,6 +2449,8 @@ pub struct MinerConfig {
     pub wait_for_block_download: bool,
     pub nonce_cache_size: u64,
     pub candidate_retry_cache_size: u64,
+    /// How to choose transactions from the mempool
+    pub tx_selection: TxSelectionSettings,
 }
 
 impl MinerConfig {
@@ -1859,6 +2466,61 @@ impl MinerConfig {
             wait_for_block_download: true,
             nonce_cache_size: 10_000,
             candidate_retry_cache_size: 10_000,
+            tx_selection: TxSelectionSettings::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MemPoolConfig {
+    /// Minimum amount by which a transaction's fee must exceed the fees of the transactions it
+    /// replaces
+    pub rbf_min_fee_increase: u64,
+    /// Minimum percentage by which a transaction's fee must exceed the fees of the transactions
+    /// it replaces
+    pub rbf_min_fee_increase_percent: u64,
+    /// Maximum number of transactions to keep in the mempool
+    pub max_tx_count: Option<u64>,
+    /// Maximum total size of the transactions to keep in the mempool, in bytes
+    pub max_bytes: Option<u64>,
+    /// Maximum number of pending transactions per origin or sponsor account
+    pub max_txs_per_account: Option<u64>,
+    /// Maximum total size of the pending transactions per origin or sponsor account, in bytes
+    pub max_bytes_per_account: Option<u64>,
+    /// Maximum amount by which a transaction's nonce may exceed its account's confirmed nonce
+    pub max_nonce_gap: Option<u64>,
+}
+
+impl Default for MemPoolConfig {
+    fn default() -> MemPoolConfig {
+        let rbf_policy = ReplaceByFeePolicy::default();
+        MemPoolConfig {
+            rbf_min_fee_increase: rbf_policy.min_fee_increase,
+            rbf_min_fee_increase_percent: rbf_policy.min_fee_increase_percent,
+            max_tx_count: None,
+            max_bytes: None,
+            max_txs_per_account: None,
+            max_bytes_per_account: None,
+            max_nonce_gap: None,
+        }
+    }
+}
+
+impl MemPoolConfig {
+    pub fn rbf_policy(&self) -> ReplaceByFeePolicy {
+        ReplaceByFeePolicy {
+            min_fee_increase: self.rbf_min_fee_increase,
+            min_fee_increase_percent: self.rbf_min_fee_increase_percent,
+        }
+    }
+
+    pub fn limits(&self) -> MemPoolLimits {
+        MemPoolLimits {
+            max_tx_count: self.max_tx_count,
+            max_bytes: self.max_bytes,
+            max_txs_per_account: self.max_txs_per_account,
+            max_bytes_per_account: self.max_bytes_per_account,
+            max_nonce_gap: self.max_nonce_gap,
         }
     }
 }
@@ -1900,11 +2562,37 @@ pub struct ConnectionOptionsFile {
     pub full_inv_sync_interval: Option<u64>,
     pub inv_reward_cycles: Option<u64>,
     pub public_ip_address: Option<String>,
+    pub socks5_proxy: Option<String>,
+    pub public_host: Option<String>,
     pub disable_inbound_walks: Option<bool>,
     pub disable_inbound_handshakes: Option<bool>,
     pub disable_block_download: Option<bool>,
     pub force_disconnect_interval: Option<u64>,
     pub antientropy_public: Option<bool>,
+    pub admin_rpc_token: Option<String>,
+    pub rpc_public_scopes: Option<Vec<String>>,
+    pub rpc_auth_tokens: Option<Vec<RPCAuthTokenConfigFile>>,
+    pub rpc_rate_limits: Option<Vec<RPCRateLimitConfigFile>>,
+    pub rpc_read_only_cost_burst: Option<u64>,
+    pub rpc_read_only_cost_per_minute: Option<u64>,
+    pub rpc_auth_failure_burst: Option<u64>,
+    pub rpc_auth_failure_per_minute: Option<u64>,
+    pub rpc_tls_cert_path: Option<String>,
+    pub rpc_tls_key_path: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Default, Debug)]
+pub struct RPCAuthTokenConfigFile {
+    pub name: String,
+    pub secret: String,
+    pub scopes: Vec<String>,
+}
+
+#[derive(Clone, Deserialize, Default, Debug)]
+pub struct RPCRateLimitConfigFile {
+    pub scope: String,
+    pub burst: Option<u64>,
+    pub per_minute: u64,
 }
 
 #[derive(Clone, Deserialize, Default, Debug)]
@@ -1933,6 +2621,8 @@ pub struct NodeConfigFile {
     pub use_test_genesis_chainstate: Option<bool>,
     pub always_use_affirmation_maps: Option<bool>,
     pub require_affirmed_anchor_blocks: Option<bool>,
+    pub event_stream_max_clients: Option<u64>,
+    pub event_stream_buffer: Option<u64>,
 }
 
 #[derive(Clone, Deserialize, Debug)]
@@ -1971,18 +2661,85 @@ pub struct MinerConfigFile {
     pub segwit: Option<bool>,
     pub nonce_cache_size: Option<u64>,
     pub candidate_retry_cache_size: Option<u64>,
+    /// One of "fee_rate", "pure_fee_rate" or "cost_packing"
+    pub tx_selection_strategy: Option<String>,
+    /// Principals whose transactions, and contracts whose calls, are mined first
+    pub tx_priority_list: Option<Vec<String>>,
+    /// Principals whose transactions, and contracts whose calls, are never mined
+    pub tx_exclusion_list: Option<Vec<String>>,
+}
+
+#[derive(Clone, Deserialize, Default, Debug)]
+pub struct MemPoolConfigFile {
+    pub rbf_min_fee_increase: Option<u64>,
+    pub rbf_min_fee_increase_percent: Option<u64>,
+    pub max_tx_count: Option<u64>,
+    pub max_bytes: Option<u64>,
+    pub max_txs_per_account: Option<u64>,
+    pub max_bytes_per_account: Option<u64>,
+    pub max_nonce_gap: Option<u64>,
 }
 
 #[derive(Clone, Deserialize, Default, Debug)]
 pub struct EventObserverConfigFile {
     pub endpoint: String,
     pub events_keys: Vec<String>,
+    pub timeout_ms: Option<u64>,
+    pub retry_backoff_ms: Option<u64>,
+    pub max_retry_backoff_ms: Option<u64>,
+    pub max_attempts: Option<u64>,
+    pub max_lag: Option<u64>,
+    pub filter: Option<EventObserverFilterFile>,
+    pub tls: Option<bool>,
+    pub tls_ca_path: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Default, Debug)]
+pub struct EventObserverFilterFile {
+    pub contract_ids: Option<Vec<String>>,
+    pub asset_ids: Option<Vec<String>>,
+    pub principals: Option<Vec<String>>,
+    pub topics: Option<Vec<String>>,
+    pub tx_types: Option<Vec<String>>,
 }
 
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Debug)]
 pub struct EventObserverConfig {
     pub endpoint: String,
     pub events_keys: Vec<EventKeyType>,
+    /// How long to wait for the observer to acknowledge a single POST
+    pub timeout_ms: u64,
+    /// How long to wait before retrying a failed POST.  Doubles after each consecutive failure.
+    pub retry_backoff_ms: u64,
+    /// Upper bound on the retry delay
+    pub max_retry_backoff_ms: u64,
+    /// Drop a payload after this many failed attempts.  If not set, retry forever.
+    pub max_attempts: Option<u64>,
+    /// Raise an alarm once more than this many payloads are waiting to be delivered
+    pub max_lag: Option<u64>,
+    /// Only deliver the transactions and events that match this filter
+    pub filter: Option<EventFilter>,
+    /// POST to the observer over TLS.  Its endpoint must then be a DNS name, not an IP address.
+    pub tls: bool,
+    /// PEM file with CA certificates to trust for the observer, besides the usual web roots
+    pub tls_ca_path: Option<String>,
+}
+
+impl Default for EventObserverConfig {
+    fn default() -> Self {
+        EventObserverConfig {
+            endpoint: String::new(),
+            events_keys: vec![],
+            timeout_ms: 30_000,
+            retry_backoff_ms: 1_000,
+            max_retry_backoff_ms: 30_000,
+            max_attempts: None,
+            max_lag: None,
+            filter: None,
+            tls: false,
+            tls_ca_path: None,
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -1,11 +1,16 @@
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::thread::sleep;
 use std::time::Duration;
 
 use async_h1::client;
 use async_std::net::TcpStream;
-use http_types::{Method, Request, Url};
+use futures_rustls::TlsConnector;
+use http_types::{Method, Request, Response, Url};
+use rustls::{ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
 use serde_json::json;
 
 use stacks::burnchains::{PoxConstants, Txid};
@@ -13,7 +18,8 @@ use stacks::chainstate::coordinator::BlockEventDispatcher;
 use stacks::chainstate::stacks::address::PoxAddress;
 use stacks::chainstate::stacks::db::StacksHeaderInfo;
 use stacks::chainstate::stacks::events::{
-    StacksTransactionEvent, StacksTransactionReceipt, TransactionOrigin,
+    make_mature_rewards_payload, make_new_block_processed_payload, make_new_burn_block_payload,
+    StacksTransactionEvent, StacksTransactionReceipt,
 };
 use stacks::chainstate::stacks::{
     db::accounts::MinerReward, db::MinerRewardInfo, StacksTransaction,
@@ -22,47 +28,97 @@ use stacks::chainstate::stacks::{StacksBlock, StacksMicroblock};
 use stacks::codec::StacksMessageCodec;
 use stacks::core::mempool::{MemPoolDropReason, MemPoolEventDispatcher};
 use stacks::net::atlas::{Attachment, AttachmentInstance};
+use stacks::net::event_stream::{ChainEventKind, EventStreamHub};
+use stacks::net::tls::load_certificates;
 use stacks::types::chainstate::{BlockHeaderHash, BurnchainHeaderHash, StacksBlockId};
 use stacks::util::hash::bytes_to_hex;
-use stacks::vm::analysis::contract_interface_builder::build_contract_interface;
 use stacks::vm::costs::ExecutionCost;
 use stacks::vm::events::{FTEventType, NFTEventType, STXEventType};
-use stacks::vm::types::{AssetIdentifier, QualifiedContractIdentifier, Value};
+use stacks::vm::types::{AssetIdentifier, QualifiedContractIdentifier};
 
 use super::config::{EventKeyType, EventObserverConfig};
-use stacks::chainstate::burn::operations::BlockstackOperationType;
+use super::event_filter::EventFilter;
+use super::event_outbox::ObserverOutbox;
 use stacks::chainstate::burn::ConsensusHash;
 use stacks::chainstate::stacks::db::unconfirmed::ProcessedUnconfirmedState;
 use stacks::chainstate::stacks::miner::TransactionEvent;
-use stacks::chainstate::stacks::TransactionPayload;
 
 #[derive(Debug, Clone)]
 struct EventObserver {
     endpoint: String,
+    /// Only the transactions and events that match this filter are sent to the observer
+    filter: Option<EventFilter>,
+    timeout: Duration,
+    retry_backoff: Duration,
+    max_retry_backoff: Duration,
+    max_attempts: Option<u64>,
+    /// If set, payloads are queued here and delivered by a dedicated worker thread, instead of
+    /// being sent from the calling thread.
+    outbox: Option<ObserverOutbox>,
+    /// If set, payloads are POSTed over TLS
+    tls: Option<ObserverTls>,
 }
 
-struct ReceiptPayloadInfo<'a> {
-    txid: String,
-    success: &'a str,
-    raw_result: String,
-    raw_tx: String,
-    contract_interface_json: serde_json::Value,
-    burnchain_op_json: serde_json::Value,
+/// How to open a TLS session to an event observer
+#[derive(Clone)]
+struct ObserverTls {
+    connector: TlsConnector,
+    server_name: ServerName,
+}
+
+impl fmt::Debug for ObserverTls {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ObserverTls({:?})", &self.server_name)
+    }
 }
 
-const STATUS_RESP_TRUE: &str = "success";
-const STATUS_RESP_NOT_COMMITTED: &str = "abort_by_response";
-const STATUS_RESP_POST_CONDITION: &str = "abort_by_post_condition";
+impl ObserverTls {
+    /// Trust the usual web roots, plus any CA certificates in the observer's `tls_ca_path`.
+    /// The observer's certificate must name the host in its endpoint.
+    fn new(conf: &EventObserverConfig) -> Result<ObserverTls, String> {
+        let host = match conf.endpoint.rsplit_once(':') {
+            Some((host, _port)) => host,
+            None => conf.endpoint.as_str(),
+        };
+        let server_name = ServerName::try_from(host).map_err(|_| {
+            format!(
+                "TLS event observer endpoint {} must be a DNS name",
+                &conf.endpoint
+            )
+        })?;
+
+        let mut roots = RootCertStore::empty();
+        roots.add_server_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|ta| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(
+                ta.subject,
+                ta.spki,
+                ta.name_constraints,
+            )
+        }));
+        if let Some(ref ca_path) = conf.tls_ca_path {
+            for cert in load_certificates(ca_path).map_err(|e| e.to_string())? {
+                roots
+                    .add(&cert)
+                    .map_err(|e| format!("Invalid CA certificate in {}: {:?}", ca_path, &e))?;
+            }
+        }
+
+        let config = ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+        Ok(ObserverTls {
+            connector: TlsConnector::from(Arc::new(config)),
+            server_name,
+        })
+    }
+}
 
 /// Update `serve()` in `neon_integrations.rs` with any new paths that need to be tested
-pub const PATH_MICROBLOCK_SUBMIT: &str = "new_microblocks";
-pub const PATH_MEMPOOL_TX_SUBMIT: &str = "new_mempool_tx";
-pub const PATH_MEMPOOL_TX_DROP: &str = "drop_mempool_tx";
-pub const PATH_MINED_BLOCK: &str = "mined_block";
-pub const PATH_MINED_MICROBLOCK: &str = "mined_microblock";
-pub const PATH_BURN_BLOCK_SUBMIT: &str = "new_burn_block";
-pub const PATH_BLOCK_PROCESSED: &str = "new_block";
-pub const PATH_ATTACHMENT_PROCESSED: &str = "attachments/new";
+pub use stacks::chainstate::stacks::events::{
+    PATH_ATTACHMENT_PROCESSED, PATH_BLOCK_PROCESSED, PATH_BURN_BLOCK_SUBMIT, PATH_MEMPOOL_TX_DROP,
+    PATH_MEMPOOL_TX_SUBMIT, PATH_MICROBLOCK_SUBMIT, PATH_MINED_BLOCK, PATH_MINED_MICROBLOCK,
+};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MinedBlockEvent {
@@ -85,190 +141,203 @@ pub struct MinedMicroblockEvent {
 }
 
 impl EventObserver {
-    pub fn send_payload(&self, payload: &serde_json::Value, path: &str) {
-        let body = match serde_json::to_vec(&payload) {
-            Ok(body) => body,
-            Err(err) => {
-                error!("Event dispatcher: serialization failed  - {:?}", err);
-                return;
-            }
-        };
+    fn new(conf: &EventObserverConfig) -> EventObserver {
+        EventObserver {
+            endpoint: conf.endpoint.clone(),
+            filter: conf.filter.clone(),
+            timeout: Duration::from_millis(conf.timeout_ms),
+            retry_backoff: Duration::from_millis(conf.retry_backoff_ms),
+            max_retry_backoff: Duration::from_millis(conf.max_retry_backoff_ms),
+            max_attempts: conf.max_attempts,
+            outbox: None,
+            tls: match conf.tls {
+                true => Some(
+                    ObserverTls::new(conf)
+                        .unwrap_or_else(|e| panic!("FATAL: invalid event observer TLS: {}", e)),
+                ),
+                false => None,
+            },
+        }
+    }
 
-        let url = {
-            let joined_components = match path.starts_with("/") {
-                true => format!("{}{}", &self.endpoint, path),
-                false => format!("{}/{}", &self.endpoint, path),
-            };
-            let url = format!("http://{}", joined_components);
-            Url::parse(&url).expect(&format!(
-                "Event dispatcher: unable to parse {} as a URL",
-                url
-            ))
-        };
+    /// Instantiate an observer whose payloads go through a durable outbox at `outbox_path`.
+    /// This starts the observer's delivery thread, which first replays anything left in the
+    /// outbox by a previous run.
+    fn new_with_outbox(conf: &EventObserverConfig, outbox_path: &Path) -> EventObserver {
+        let mut observer = EventObserver::new(conf);
+        let endpoint = conf.endpoint.clone();
+        let tls = observer.tls.clone();
+        let timeout = observer.timeout;
+        let outbox = ObserverOutbox::spawn(outbox_path, conf, move |path, body| {
+            EventObserver::post_payload(&endpoint, tls.as_ref(), path, body, timeout)
+        })
+        .expect("FATAL: failed to open event observer outbox");
+        observer.outbox = Some(outbox);
+        observer
+    }
 
-        let backoff = Duration::from_millis((1.0 * 1_000.0) as u64);
+    fn make_url(endpoint: &str, tls: bool, path: &str) -> Url {
+        let joined_components = match path.starts_with("/") {
+            true => format!("{}{}", endpoint, path),
+            false => format!("{}/{}", endpoint, path),
+        };
+        let scheme = if tls { "https" } else { "http" };
+        let url = format!("{}://{}", scheme, joined_components);
+        Url::parse(&url).expect(&format!(
+            "Event dispatcher: unable to parse {} as a URL",
+            url
+        ))
+    }
 
-        loop {
-            let body = body.clone();
-            let mut req = Request::new(Method::Post, url.clone());
-            req.append_header("Content-Type", "application/json");
-            req.set_body(body);
+    /// Send a request to the observer, over TLS if given
+    async fn send_request(
+        endpoint: &str,
+        tls: Option<&ObserverTls>,
+        req: Request,
+    ) -> Option<Response> {
+        let stream = match TcpStream::connect(endpoint).await {
+            Ok(stream) => stream,
+            Err(err) => {
+                warn!("Event dispatcher: connection failed  - {:?}", err);
+                return None;
+            }
+        };
 
-            let response = async_std::task::block_on(async {
-                let stream = match TcpStream::connect(self.endpoint.clone()).await {
+        let response = match tls {
+            Some(tls) => {
+                let stream = match tls.connector.connect(tls.server_name.clone(), stream).await {
                     Ok(stream) => stream,
                     Err(err) => {
-                        warn!("Event dispatcher: connection failed  - {:?}", err);
+                        warn!("Event dispatcher: TLS handshake failed  - {:?}", err);
                         return None;
                     }
                 };
-
-                match client::connect(stream, req).await {
-                    Ok(response) => Some(response),
-                    Err(err) => {
-                        warn!("Event dispatcher: rpc invocation failed  - {:?}", err);
-                        return None;
-                    }
-                }
-            });
-
-            if let Some(response) = response {
-                if response.status().is_success() {
-                    debug!(
-                        "Event dispatcher: Successful POST"; "url" => %url
-                    );
-                    break;
-                } else {
-                    error!(
-                        "Event dispatcher: Failed POST"; "url" => %url, "err" => ?response
-                    );
-                }
+                client::connect(stream, req).await
+            }
+            None => client::connect(stream, req).await,
+        };
+        match response {
+            Ok(response) => Some(response),
+            Err(err) => {
+                warn!("Event dispatcher: rpc invocation failed  - {:?}", err);
+                None
             }
-            sleep(backoff);
         }
     }
 
-    fn make_new_mempool_txs_payload(transactions: Vec<StacksTransaction>) -> serde_json::Value {
-        let raw_txs = transactions
-            .into_iter()
-            .map(|tx| {
-                serde_json::Value::String(format!("0x{}", &bytes_to_hex(&tx.serialize_to_vec())))
-            })
-            .collect();
+    /// Make a single attempt at POSTing `body` to the observer's `path`.
+    /// Returns true if the observer acknowledged it.
+    fn post_payload(
+        endpoint: &str,
+        tls: Option<&ObserverTls>,
+        path: &str,
+        body: &[u8],
+        timeout: Duration,
+    ) -> bool {
+        let url = EventObserver::make_url(endpoint, tls.is_some(), path);
+        let mut req = Request::new(Method::Post, url.clone());
+        req.append_header("Content-Type", "application/json");
+        req.set_body(body);
+
+        let response = async_std::task::block_on(async {
+            let attempt = EventObserver::send_request(endpoint, tls, req);
+            match async_std::future::timeout(timeout, attempt).await {
+                Ok(response) => response,
+                Err(_) => {
+                    warn!("Event dispatcher: POST timed out"; "url" => %url);
+                    None
+                }
+            }
+        });
 
-        serde_json::Value::Array(raw_txs)
+        match response {
+            Some(response) if response.status().is_success() => {
+                debug!(
+                    "Event dispatcher: Successful POST"; "url" => %url
+                );
+                true
+            }
+            Some(response) => {
+                error!(
+                    "Event dispatcher: Failed POST"; "url" => %url, "err" => ?response
+                );
+                false
+            }
+            None => false,
+        }
     }
 
-    fn make_new_burn_block_payload(
-        burn_block: &BurnchainHeaderHash,
-        burn_block_height: u64,
-        rewards: Vec<(PoxAddress, u64)>,
-        burns: u64,
-        slot_holders: Vec<PoxAddress>,
-    ) -> serde_json::Value {
-        let reward_recipients = rewards
-            .into_iter()
-            .map(|(pox_addr, amt)| {
-                json!({
-                    "recipient": pox_addr.to_b58(),
-                    "amt": amt,
-                })
-            })
-            .collect();
-
-        let reward_slot_holders = slot_holders
-            .into_iter()
-            .map(|pox_addr| json!(pox_addr.to_b58()))
-            .collect();
+    pub fn send_payload(&self, payload: &serde_json::Value, path: &str) {
+        let body = match serde_json::to_string(&payload) {
+            Ok(body) => body,
+            Err(err) => {
+                error!("Event dispatcher: serialization failed  - {:?}", err);
+                return;
+            }
+        };
 
-        json!({
-            "burn_block_hash": format!("0x{}", burn_block),
-            "burn_block_height": burn_block_height,
-            "reward_recipients": serde_json::Value::Array(reward_recipients),
-            "reward_slot_holders": serde_json::Value::Array(reward_slot_holders),
-            "burn_amount": burns
-        })
+        match self.outbox {
+            Some(ref outbox) => {
+                if let Err(e) = outbox.push(path, &body) {
+                    // don't lose the payload just because the outbox is unavailable (e.g. the
+                    // disk is full); it may arrive ahead of older queued payloads, though.
+                    error!("Event dispatcher: failed to store payload in outbox; sending directly";
+                           "endpoint" => &self.endpoint,
+                           "path" => path,
+                           "err" => ?e);
+                    self.send_payload_directly(body.as_bytes(), path);
+                }
+            }
+            None => self.send_payload_directly(body.as_bytes(), path),
+        }
     }
 
-    /// Returns tuple of (txid, success, raw_result, raw_tx, contract_interface_json)
-    fn generate_payload_info_for_receipt(receipt: &StacksTransactionReceipt) -> ReceiptPayloadInfo {
-        let tx = &receipt.transaction;
+    /// Send a payload from the calling thread, retrying according to this observer's policy.
+    fn send_payload_directly(&self, body: &[u8], path: &str) {
+        let mut backoff = self.retry_backoff;
+        let mut attempts = 0;
 
-        let success = match (receipt.post_condition_aborted, &receipt.result) {
-            (false, Value::Response(response_data)) => {
-                if response_data.committed {
-                    STATUS_RESP_TRUE
-                } else {
-                    STATUS_RESP_NOT_COMMITTED
-                }
+        loop {
+            if EventObserver::post_payload(
+                &self.endpoint,
+                self.tls.as_ref(),
+                path,
+                body,
+                self.timeout,
+            ) {
+                break;
             }
-            (true, Value::Response(_)) => STATUS_RESP_POST_CONDITION,
-            _ => {
-                if let TransactionOrigin::Stacks(inner_tx) = &tx {
-                    if let TransactionPayload::PoisonMicroblock(..) = &inner_tx.payload {
-                        STATUS_RESP_TRUE
-                    } else {
-                        unreachable!() // Transaction results should otherwise always be a Value::Response type
-                    }
-                } else {
-                    unreachable!() // Transaction results should always be a Value::Response type
+            attempts += 1;
+            if let Some(max_attempts) = self.max_attempts {
+                if attempts >= max_attempts {
+                    error!("Event dispatcher: giving up on payload";
+                           "endpoint" => &self.endpoint,
+                           "path" => path,
+                           "attempts" => attempts);
+                    break;
                 }
             }
-        };
-
-        let (txid, raw_tx, burnchain_op_json) = match tx {
-            TransactionOrigin::Burn(op) => (
-                op.txid().to_string(),
-                "00".to_string(),
-                BlockstackOperationType::blockstack_op_to_json(&op),
-            ),
-            TransactionOrigin::Stacks(ref tx) => {
-                let txid = tx.txid().to_string();
-                let bytes = tx.serialize_to_vec();
-                (txid, bytes_to_hex(&bytes), json!(null))
-            }
-        };
-
-        let raw_result = {
-            let bytes = receipt.result.serialize_to_vec();
-            bytes_to_hex(&bytes)
-        };
-        let contract_interface_json = {
-            match &receipt.contract_analysis {
-                Some(analysis) => json!(build_contract_interface(analysis)),
-                None => json!(null),
-            }
-        };
-        ReceiptPayloadInfo {
-            txid,
-            success,
-            raw_result,
-            raw_tx,
-            contract_interface_json,
-            burnchain_op_json,
+            sleep(backoff);
+            backoff = std::cmp::min(backoff * 2, self.max_retry_backoff);
         }
     }
 
-    /// Returns json payload to send for new block or microblock event
-    fn make_new_block_txs_payload(
-        receipt: &StacksTransactionReceipt,
-        tx_index: u32,
-    ) -> serde_json::Value {
-        let receipt_payload_info = EventObserver::generate_payload_info_for_receipt(receipt);
+    /// Serializes each transaction for the mempool payload
+    fn make_new_mempool_txs_payload(transactions: &[StacksTransaction]) -> Vec<serde_json::Value> {
+        transactions
+            .iter()
+            .map(|tx| {
+                serde_json::Value::String(format!("0x{}", &bytes_to_hex(&tx.serialize_to_vec())))
+            })
+            .collect()
+    }
 
-        json!({
-            "txid": format!("0x{}", &receipt_payload_info.txid),
-            "tx_index": tx_index,
-            "status": receipt_payload_info.success,
-            "raw_result": format!("0x{}", &receipt_payload_info.raw_result),
-            "raw_tx": format!("0x{}", &receipt_payload_info.raw_tx),
-            "contract_abi": receipt_payload_info.contract_interface_json,
-            "burnchain_op": receipt_payload_info.burnchain_op_json,
-            "execution_cost": receipt.execution_cost,
-            "microblock_sequence": receipt.microblock_header.as_ref().map(|x| x.sequence),
-            "microblock_hash": receipt.microblock_header.as_ref().map(|x| format!("0x{}", x.block_hash())),
-            "microblock_parent_hash": receipt.microblock_header.as_ref().map(|x| format!("0x{}", x.prev_block)),
-        })
+    /// Does this observer want to be sent this processed transaction?
+    fn matches_receipt(&self, receipt: &StacksTransactionReceipt) -> bool {
+        self.filter
+            .as_ref()
+            .map(|filter| filter.matches_receipt(receipt))
+            .unwrap_or(true)
     }
 
     fn make_new_attachment_payload(
@@ -294,16 +363,15 @@ impl EventObserver {
         self.send_payload(payload, PATH_MEMPOOL_TX_SUBMIT);
     }
 
-    /// Serializes new microblocks data into a JSON payload and sends it off to the correct path
-    fn send_new_microblocks(
-        &self,
+    /// Serializes new microblocks data into a JSON payload
+    fn make_new_microblocks_payload(
         parent_index_block_hash: StacksBlockId,
         filtered_events: Vec<(usize, &(bool, Txid, &StacksTransactionEvent))>,
         serialized_txs: &Vec<serde_json::Value>,
         burn_block_hash: BurnchainHeaderHash,
         burn_block_height: u32,
         burn_block_timestamp: u64,
-    ) {
+    ) -> serde_json::Value {
         // Serialize events to JSON
         let serialized_events: Vec<serde_json::Value> = filtered_events
             .iter()
@@ -312,16 +380,18 @@ impl EventObserver {
             })
             .collect();
 
-        let payload = json!({
+        json!({
             "parent_index_block_hash": format!("0x{}", parent_index_block_hash),
             "events": serialized_events,
             "transactions": serialized_txs,
             "burn_block_hash": format!("0x{}", burn_block_hash),
             "burn_block_height": burn_block_height,
             "burn_block_timestamp": burn_block_timestamp,
-        });
+        })
+    }
 
-        self.send_payload(&payload, PATH_MICROBLOCK_SUBMIT);
+    fn send_new_microblocks(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_MICROBLOCK_SUBMIT);
     }
 
     fn send_dropped_mempool_txs(&self, payload: &serde_json::Value) {
@@ -339,68 +409,13 @@ impl EventObserver {
     fn send_new_burn_block(&self, payload: &serde_json::Value) {
         self.send_payload(payload, PATH_BURN_BLOCK_SUBMIT);
     }
-
-    fn make_new_block_processed_payload(
-        &self,
-        filtered_events: Vec<(usize, &(bool, Txid, &StacksTransactionEvent))>,
-        block: &StacksBlock,
-        metadata: &StacksHeaderInfo,
-        receipts: &[StacksTransactionReceipt],
-        parent_index_hash: &StacksBlockId,
-        winner_txid: &Txid,
-        mature_rewards: &serde_json::Value,
-        parent_burn_block_hash: BurnchainHeaderHash,
-        parent_burn_block_height: u32,
-        parent_burn_block_timestamp: u64,
-        anchored_consumed: &ExecutionCost,
-        mblock_confirmed_consumed: &ExecutionCost,
-        pox_constants: &PoxConstants,
-    ) -> serde_json::Value {
-        // Serialize events to JSON
-        let serialized_events: Vec<serde_json::Value> = filtered_events
-            .iter()
-            .map(|(event_index, (committed, txid, event))| {
-                event.json_serialize(*event_index, txid, *committed)
-            })
-            .collect();
-
-        let mut tx_index: u32 = 0;
-        let mut serialized_txs = vec![];
-        for receipt in receipts.iter() {
-            let payload = EventObserver::make_new_block_txs_payload(receipt, tx_index);
-            serialized_txs.push(payload);
-            tx_index += 1;
-        }
-
-        // Wrap events
-        json!({
-            "block_hash": format!("0x{}", block.block_hash()),
-            "block_height": metadata.stacks_block_height,
-            "burn_block_hash": format!("0x{}", metadata.burn_header_hash),
-            "burn_block_height": metadata.burn_header_height,
-            "miner_txid": format!("0x{}", winner_txid),
-            "burn_block_time": metadata.burn_header_timestamp,
-            "index_block_hash": format!("0x{}", metadata.index_block_hash()),
-            "parent_block_hash": format!("0x{}", block.header.parent_block),
-            "parent_index_block_hash": format!("0x{}", parent_index_hash),
-            "parent_microblock": format!("0x{}", block.header.parent_microblock),
-            "parent_microblock_sequence": block.header.parent_microblock_sequence,
-            "matured_miner_rewards": mature_rewards.clone(),
-            "events": serialized_events,
-            "transactions": serialized_txs,
-            "parent_burn_block_hash":  format!("0x{}", parent_burn_block_hash),
-            "parent_burn_block_height": parent_burn_block_height,
-            "parent_burn_block_timestamp": parent_burn_block_timestamp,
-            "anchored_cost": anchored_consumed,
-            "confirmed_microblocks_cost": mblock_confirmed_consumed,
-            "pox_v1_unlock_height": pox_constants.v1_unlock_height,
-        })
-    }
 }
 
 #[derive(Clone)]
 pub struct EventDispatcher {
     registered_observers: Vec<EventObserver>,
+    /// Where observers queue their payloads, if delivery is durable
+    outbox_path: Option<PathBuf>,
     contract_events_observers_lookup: HashMap<(QualifiedContractIdentifier, String), HashSet<u16>>,
     assets_observers_lookup: HashMap<AssetIdentifier, HashSet<u16>>,
     burn_block_observers_lookup: HashSet<u16>,
@@ -410,6 +425,9 @@ pub struct EventDispatcher {
     any_event_observers_lookup: HashSet<u16>,
     miner_observers_lookup: HashSet<u16>,
     mined_microblocks_observers_lookup: HashSet<u16>,
+    /// RPC clients streaming chain events, if the node allows it.  They receive every event,
+    /// unfiltered.
+    event_stream: Option<EventStreamHub>,
 }
 
 impl MemPoolEventDispatcher for EventDispatcher {
@@ -510,6 +528,7 @@ impl EventDispatcher {
     pub fn new() -> EventDispatcher {
         EventDispatcher {
             registered_observers: vec![],
+            outbox_path: None,
             contract_events_observers_lookup: HashMap::new(),
             assets_observers_lookup: HashMap::new(),
             stx_observers_lookup: HashSet::new(),
@@ -519,9 +538,35 @@ impl EventDispatcher {
             microblock_observers_lookup: HashSet::new(),
             miner_observers_lookup: HashSet::new(),
             mined_microblocks_observers_lookup: HashSet::new(),
+            event_stream: None,
         }
     }
 
+    /// Instantiate a dispatcher whose observers each get a durable outbox in the SQLite
+    /// database at `outbox_path`, drained by their own delivery thread.
+    pub fn with_outbox(outbox_path: PathBuf) -> EventDispatcher {
+        EventDispatcher {
+            outbox_path: Some(outbox_path),
+            ..EventDispatcher::new()
+        }
+    }
+
+    /// Also publish chain events to the RPC clients streaming from `event_stream`.
+    pub fn set_event_stream(&mut self, event_stream: EventStreamHub) {
+        self.event_stream = Some(event_stream);
+    }
+
+    pub fn event_stream(&self) -> Option<&EventStreamHub> {
+        self.event_stream.as_ref()
+    }
+
+    /// The event stream to publish this kind of event to, if any RPC client is listening for it
+    fn streaming(&self, kind: ChainEventKind) -> Option<&EventStreamHub> {
+        self.event_stream
+            .as_ref()
+            .filter(|event_stream| event_stream.has_subscribers(kind))
+    }
+
     pub fn process_burn_block(
         &self,
         burn_block: &BurnchainHeaderHash,
@@ -540,11 +585,12 @@ impl EventDispatcher {
                     || self.any_event_observers_lookup.contains(&(*obs_id as u16))
             })
             .collect();
-        if interested_observers.len() < 1 {
+        let event_stream = self.streaming(ChainEventKind::BurnBlock);
+        if interested_observers.is_empty() && event_stream.is_none() {
             return;
         }
 
-        let payload = EventObserver::make_new_burn_block_payload(
+        let payload = make_new_burn_block_payload(
             burn_block,
             burn_block_height,
             rewards,
@@ -555,6 +601,9 @@ impl EventDispatcher {
         for (_, observer) in interested_observers.iter() {
             observer.send_new_burn_block(&payload);
         }
+        if let Some(event_stream) = event_stream {
+            event_stream.publish(ChainEventKind::BurnBlock, &payload);
+        }
     }
 
     /// Iterates through tx receipts, and then the events corresponding to each receipt to
@@ -649,6 +698,13 @@ impl EventDispatcher {
                 for o_i in &self.any_event_observers_lookup {
                     dispatch_matrix[*o_i as usize].insert(i);
                 }
+                for (o_i, observer) in self.registered_observers.iter().enumerate() {
+                    if let Some(filter) = observer.filter.as_ref() {
+                        if !filter.matches_event(&receipt.transaction, event) {
+                            dispatch_matrix[o_i].remove(&i);
+                        }
+                    }
+                }
                 i += 1;
             }
         }
@@ -674,56 +730,60 @@ impl EventDispatcher {
     ) {
         let all_receipts = receipts.to_owned();
         let (dispatch_matrix, events) = self.create_dispatch_matrix_and_event_vector(&all_receipts);
+        let event_stream = self.streaming(ChainEventKind::Block);
 
-        if dispatch_matrix.len() > 0 {
-            let mature_rewards_vec = if let Some(rewards_info) = mature_rewards_info {
-                mature_rewards
-                    .iter()
-                    .map(|reward| {
-                        json!({
-                            "recipient": reward.recipient.to_string(),
-                            "miner_address": reward.address.to_string(),
-                            "coinbase_amount": reward.coinbase.to_string(),
-                            "tx_fees_anchored": reward.tx_fees_anchored.to_string(),
-                            "tx_fees_streamed_confirmed": reward.tx_fees_streamed_confirmed.to_string(),
-                            "tx_fees_streamed_produced": reward.tx_fees_streamed_produced.to_string(),
-                            "from_stacks_block_hash": format!("0x{}", rewards_info.from_stacks_block_hash),
-                            "from_index_consensus_hash": format!("0x{}", StacksBlockId::new(&rewards_info.from_block_consensus_hash,
-                                                                                            &rewards_info.from_stacks_block_hash)),
-                        })
-                    })
-                    .collect()
-            } else {
-                vec![]
-            };
-
-            let mature_rewards = serde_json::Value::Array(mature_rewards_vec);
+        if !dispatch_matrix.is_empty() || event_stream.is_some() {
+            let mature_rewards = make_mature_rewards_payload(mature_rewards, mature_rewards_info);
 
             for (observer_id, filtered_events_ids) in dispatch_matrix.iter().enumerate() {
                 let filtered_events: Vec<_> = filtered_events_ids
                     .iter()
                     .map(|event_id| (*event_id, &events[*event_id]))
                     .collect();
+                let observer = &self.registered_observers[observer_id];
+                let filtered_receipts: Vec<_> = receipts
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, receipt)| observer.matches_receipt(receipt))
+                    .collect();
 
-                let payload = self.registered_observers[observer_id]
-                    .make_new_block_processed_payload(
-                        filtered_events,
-                        block,
-                        metadata,
-                        receipts,
-                        parent_index_hash,
-                        &winner_txid,
-                        &mature_rewards,
-                        parent_burn_block_hash,
-                        parent_burn_block_height,
-                        parent_burn_block_timestamp,
-                        anchored_consumed,
-                        mblock_confirmed_consumed,
-                        pox_constants,
-                    );
+                let payload = make_new_block_processed_payload(
+                    filtered_events,
+                    block,
+                    metadata,
+                    filtered_receipts,
+                    parent_index_hash,
+                    &winner_txid,
+                    &mature_rewards,
+                    parent_burn_block_hash,
+                    parent_burn_block_height,
+                    parent_burn_block_timestamp,
+                    anchored_consumed,
+                    mblock_confirmed_consumed,
+                    pox_constants,
+                );
 
                 // Send payload
-                self.registered_observers[observer_id].send_payload(&payload, PATH_BLOCK_PROCESSED);
+                observer.send_payload(&payload, PATH_BLOCK_PROCESSED);
+            }
+
+            if let Some(event_stream) = event_stream {
+                let payload = make_new_block_processed_payload(
+                    events.iter().enumerate().collect(),
+                    block,
+                    metadata,
+                    receipts.iter().enumerate().collect(),
+                    parent_index_hash,
+                    &winner_txid,
+                    &mature_rewards,
+                    parent_burn_block_hash,
+                    parent_burn_block_height,
+                    parent_burn_block_timestamp,
+                    anchored_consumed,
+                    mblock_confirmed_consumed,
+                    pox_constants,
+                );
+                event_stream.publish(ChainEventKind::Block, &payload);
             }
         }
     }
@@ -746,7 +806,8 @@ impl EventDispatcher {
                     || self.any_event_observers_lookup.contains(&(*obs_id as u16))
             })
             .collect();
-        if interested_observers.len() < 1 {
+        let event_stream = self.streaming(ChainEventKind::Microblocks);
+        if interested_observers.is_empty() && event_stream.is_none() {
             return;
         }
         let flattened_receipts = processed_unconfirmed_state
@@ -764,8 +825,8 @@ impl EventDispatcher {
         for (_, _, receipts) in processed_unconfirmed_state.receipts.iter() {
             tx_index = 0;
             for receipt in receipts.iter() {
-                let payload = EventObserver::make_new_block_txs_payload(receipt, tx_index);
-                serialized_txs.push(payload);
+                let payload = receipt.json_serialize(tx_index);
+                serialized_txs.push((receipt, payload));
                 tx_index += 1;
             }
         }
@@ -776,15 +837,37 @@ impl EventDispatcher {
                 .iter()
                 .map(|event_id| (*event_id, &events[*event_id]))
                 .collect();
+            let filtered_txs: Vec<_> = serialized_txs
+                .iter()
+                .filter(|(receipt, _)| observer.matches_receipt(receipt))
+                .map(|(_, payload)| payload.clone())
+                .collect();
 
-            observer.send_new_microblocks(
+            let payload = EventObserver::make_new_microblocks_payload(
                 parent_index_block_hash,
                 filtered_events,
-                &serialized_txs,
+                &filtered_txs,
                 processed_unconfirmed_state.burn_block_hash,
                 processed_unconfirmed_state.burn_block_height,
                 processed_unconfirmed_state.burn_block_timestamp,
             );
+            observer.send_new_microblocks(&payload);
+        }
+
+        if let Some(event_stream) = event_stream {
+            let all_txs: Vec<_> = serialized_txs
+                .into_iter()
+                .map(|(_, payload)| payload)
+                .collect();
+            let payload = EventObserver::make_new_microblocks_payload(
+                parent_index_block_hash,
+                events.iter().enumerate().collect(),
+                &all_txs,
+                processed_unconfirmed_state.burn_block_hash,
+                processed_unconfirmed_state.burn_block_height,
+                processed_unconfirmed_state.burn_block_timestamp,
+            );
+            event_stream.publish(ChainEventKind::Microblocks, &payload);
         }
     }
 
@@ -799,14 +882,34 @@ impl EventDispatcher {
                     || self.any_event_observers_lookup.contains(&(*obs_id as u16))
             })
             .collect();
-        if interested_observers.len() < 1 {
+        let event_stream = self.streaming(ChainEventKind::MempoolTxs);
+        if interested_observers.is_empty() && event_stream.is_none() {
             return;
         }
 
-        let payload = EventObserver::make_new_mempool_txs_payload(txs);
+        let raw_txs = EventObserver::make_new_mempool_txs_payload(&txs);
+        let payload = serde_json::Value::Array(raw_txs.clone());
 
         for (_, observer) in interested_observers.iter() {
-            observer.send_new_mempool_txs(&payload);
+            match observer.filter.as_ref() {
+                Some(filter) => {
+                    let filtered_txs: Vec<_> = txs
+                        .iter()
+                        .zip(raw_txs.iter())
+                        .filter(|(tx, _)| filter.matches_transaction(tx))
+                        .map(|(_, raw_tx)| raw_tx.clone())
+                        .collect();
+                    if filtered_txs.is_empty() {
+                        // nothing this observer cares about
+                        continue;
+                    }
+                    observer.send_new_mempool_txs(&serde_json::Value::Array(filtered_txs));
+                }
+                None => observer.send_new_mempool_txs(&payload),
+            }
+        }
+        if let Some(event_stream) = event_stream {
+            event_stream.publish(ChainEventKind::MempoolTxs, &payload);
         }
     }
 
@@ -890,7 +993,8 @@ impl EventDispatcher {
                     || self.any_event_observers_lookup.contains(&(*obs_id as u16))
             })
             .collect();
-        if interested_observers.len() < 1 {
+        let event_stream = self.streaming(ChainEventKind::DroppedMempoolTxs);
+        if interested_observers.is_empty() && event_stream.is_none() {
             return;
         }
 
@@ -907,6 +1011,9 @@ impl EventDispatcher {
         for (_, observer) in interested_observers.iter() {
             observer.send_dropped_mempool_txs(&payload);
         }
+        if let Some(event_stream) = event_stream {
+            event_stream.publish(ChainEventKind::DroppedMempoolTxs, &payload);
+        }
     }
 
     pub fn process_new_attachments(&self, attachments: &Vec<(AttachmentInstance, Attachment)>) {
@@ -941,8 +1048,9 @@ impl EventDispatcher {
 
     pub fn register_observer(&mut self, conf: &EventObserverConfig) {
         info!("Registering event observer at: {}", conf.endpoint);
-        let event_observer = EventObserver {
-            endpoint: conf.endpoint.clone(),
+        let event_observer = match self.outbox_path {
+            Some(ref outbox_path) => EventObserver::new_with_outbox(conf, outbox_path),
+            None => EventObserver::new(conf),
         };
 
         let observer_index = self.registered_observers.len() as u16;
@@ -1007,19 +1115,135 @@ impl EventDispatcher {
 
 #[cfg(test)]
 mod test {
-    use crate::event_dispatcher::EventObserver;
     use clarity::vm::costs::ExecutionCost;
     use stacks::burnchains::{PoxConstants, Txid};
     use stacks::chainstate::stacks::db::StacksHeaderInfo;
+    use stacks::chainstate::stacks::events::make_new_block_processed_payload;
     use stacks::chainstate::stacks::StacksBlock;
+    use stacks::core::mempool::MemPoolDropReason;
+    use stacks::net::event_stream::{ChainEventKind, EventStreamHub};
     use stacks_common::types::chainstate::{BurnchainHeaderHash, StacksBlockId};
 
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    use stacks::net::tls;
+
+    use stacks::chainstate::stacks::{
+        StacksTransaction, TransactionAuth, TransactionContractCall, TransactionPayload,
+        TransactionVersion,
+    };
+    use stacks::types::chainstate::StacksAddress;
+    use stacks::util::secp256k1::Secp256k1PrivateKey;
+
+    use super::{EventDispatcher, EventObserver};
+    use crate::config::{EventKeyType, EventObserverConfig, EventObserverFilterFile};
+    use crate::event_filter::EventFilter;
+
+    /// Accept one connection over TLS, with the test certificate for localhost, and reply 200 to
+    /// the request on it.  Returns the request's body, if the handshake succeeded.
+    fn serve_one_tls_request(listener: TcpListener) -> Option<Vec<u8>> {
+        let certs = rustls_pemfile::certs(&mut tls::test::CERT_PEM.as_bytes()).unwrap();
+        let key = rustls_pemfile::pkcs8_private_keys(&mut tls::test::KEY_PEM.as_bytes()).unwrap();
+        let config = rustls::ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(
+                certs.into_iter().map(rustls::Certificate).collect(),
+                rustls::PrivateKey(key[0].clone()),
+            )
+            .unwrap();
+
+        let (sock, _) = listener.accept().unwrap();
+        let conn = rustls::ServerConnection::new(Arc::new(config)).unwrap();
+        let mut stream = rustls::StreamOwned::new(conn, sock);
+
+        let mut request = vec![];
+        let mut buf = [0u8; 4096];
+        let body_start = loop {
+            let num_read = stream.read(&mut buf).ok()?;
+            if num_read == 0 {
+                return None;
+            }
+            request.extend_from_slice(&buf[..num_read]);
+            if let Some(i) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                break i + 4;
+            }
+        };
+        let headers = String::from_utf8(request[..body_start].to_vec()).unwrap();
+        let content_length: usize = headers
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                match name.eq_ignore_ascii_case("content-length") {
+                    true => value.trim().parse().ok(),
+                    false => None,
+                }
+            })
+            .unwrap();
+        while request.len() < body_start + content_length {
+            let num_read = stream.read(&mut buf).unwrap();
+            request.extend_from_slice(&buf[..num_read]);
+        }
+
+        stream
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+        stream.conn.send_close_notify();
+        stream.flush().unwrap();
+        Some(request[body_start..].to_vec())
+    }
+
     #[test]
-    fn build_block_processed_event() {
-        let observer = EventObserver {
-            endpoint: "nowhere".to_string(),
+    fn post_payload_over_tls() {
+        let ca_path = "/tmp/stacks-node-tests/post_payload_over_tls/ca.pem";
+        fs::create_dir_all("/tmp/stacks-node-tests/post_payload_over_tls").unwrap();
+        fs::write(ca_path, tls::test::CA_PEM).unwrap();
+
+        // an observer whose CA we trust
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let conf = EventObserverConfig {
+            endpoint: format!("localhost:{}", listener.local_addr().unwrap().port()),
+            tls: true,
+            tls_ca_path: Some(ca_path.to_string()),
+            ..EventObserverConfig::default()
+        };
+        let server = thread::spawn(move || serve_one_tls_request(listener));
+        let observer = EventObserver::new(&conf);
+        assert!(EventObserver::post_payload(
+            &observer.endpoint,
+            observer.tls.as_ref(),
+            "/new_block",
+            b"{}",
+            Duration::from_secs(10),
+        ));
+        assert_eq!(server.join().unwrap(), Some(b"{}".to_vec()));
+
+        // an observer whose certificate we can't verify
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let conf = EventObserverConfig {
+            endpoint: format!("localhost:{}", listener.local_addr().unwrap().port()),
+            tls: true,
+            ..EventObserverConfig::default()
         };
+        let server = thread::spawn(move || serve_one_tls_request(listener));
+        let observer = EventObserver::new(&conf);
+        assert!(!EventObserver::post_payload(
+            &observer.endpoint,
+            observer.tls.as_ref(),
+            "/new_block",
+            b"{}",
+            Duration::from_secs(10),
+        ));
+        assert_eq!(server.join().unwrap(), None);
+    }
 
+    #[test]
+    fn build_block_processed_event() {
         let filtered_events = vec![];
         let block = StacksBlock::genesis_block();
         let metadata = StacksHeaderInfo::regtest_genesis();
@@ -1034,11 +1258,11 @@ mod test {
         let mblock_confirmed_consumed = ExecutionCost::zero();
         let pox_constants = PoxConstants::testnet_default();
 
-        let payload = observer.make_new_block_processed_payload(
+        let payload = make_new_block_processed_payload(
             filtered_events,
             &block,
             &metadata,
-            &receipts,
+            receipts,
             &parent_index_hash,
             &winner_txid,
             &mature_rewards,
@@ -1058,4 +1282,83 @@ mod test {
             pox_constants.v1_unlock_height as u64
         );
     }
+
+    #[test]
+    fn filtered_mempool_txs_skip_empty_sends() {
+        let dir = "/tmp/stacks-node-tests/filtered_mempool_txs_skip_empty_sends";
+        if fs::metadata(dir).is_ok() {
+            fs::remove_dir_all(dir).unwrap();
+        }
+        fs::create_dir_all(dir).unwrap();
+
+        let observer_conf = |tx_type: &str| EventObserverConfig {
+            // nothing listens here, so queued payloads stay queued
+            endpoint: "127.0.0.1:1".to_string(),
+            events_keys: vec![EventKeyType::MemPoolTransactions],
+            filter: Some(
+                EventFilter::from_config_file(&EventObserverFilterFile {
+                    tx_types: Some(vec![tx_type.to_string()]),
+                    ..EventObserverFilterFile::default()
+                })
+                .unwrap(),
+            ),
+            ..EventObserverConfig::default()
+        };
+
+        let mut dispatcher = EventDispatcher::with_outbox(format!("{}/outbox.sqlite", dir).into());
+        dispatcher.register_observer(&observer_conf("TokenTransfer"));
+        dispatcher.register_observer(&observer_conf("ContractCall"));
+
+        let tx = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            TransactionAuth::from_p2pkh(&Secp256k1PrivateKey::new()).unwrap(),
+            TransactionPayload::ContractCall(TransactionContractCall {
+                address: StacksAddress::burn_address(false),
+                contract_name: "pox".into(),
+                function_name: "stack-stx".into(),
+                function_args: vec![],
+            }),
+        );
+        dispatcher.process_new_mempool_txs(vec![tx]);
+
+        let pending = |i: usize| {
+            dispatcher.registered_observers[i]
+                .outbox
+                .as_ref()
+                .unwrap()
+                .pending()
+        };
+        assert_eq!(pending(0), 0);
+        assert_eq!(pending(1), 1);
+    }
+
+    #[test]
+    fn publish_to_event_stream() {
+        let mut dispatcher = EventDispatcher::new();
+        let event_stream = EventStreamHub::new(1, 10);
+        dispatcher.set_event_stream(event_stream.clone());
+
+        // no one is listening yet
+        dispatcher
+            .process_dropped_mempool_txs(vec![Txid([1; 32])], MemPoolDropReason::STALE_COLLECT);
+
+        let mut subscription = event_stream
+            .subscribe(vec![ChainEventKind::DroppedMempoolTxs])
+            .unwrap();
+        dispatcher.process_new_mempool_txs(vec![]);
+        dispatcher
+            .process_dropped_mempool_txs(vec![Txid([2; 32])], MemPoolDropReason::STALE_COLLECT);
+
+        let mut written = vec![];
+        assert!(subscription.stream_to(&mut written).unwrap());
+        let written = String::from_utf8(written).unwrap();
+        assert_eq!(
+            written,
+            format!(
+                "event: drop_mempool_tx\ndata: {{\"dropped_txids\":[\"0x{}\"],\"reason\":\"{}\"}}\n\n",
+                Txid([2; 32]),
+                MemPoolDropReason::STALE_COLLECT
+            )
+        );
+    }
 }
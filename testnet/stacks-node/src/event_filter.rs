@@ -0,0 +1,478 @@
+//! Per-observer filters on the transactions and events that an observer receives.
+//!
+//! A filter is made of up to five criteria -- contract identifiers, asset identifiers,
+//! principals, `print` topics and transaction payload types.  Each criterion that is set must
+//! be satisfied (and is satisfied by any one of its values), so an observer that sets both
+//! `contract_ids` and `topics` only receives the events with one of those topics that are
+//! emitted by one of those contracts.
+//!
+//! An event is matched against the contract, asset, principals and topic it carries, and
+//! against the payload type of the transaction that emitted it.  A transaction is delivered if
+//! any of its events match, or if the transaction itself matches -- its payload type, the
+//! contract it calls or deploys, and the principals that send, sponsor or receive it.  Asset
+//! identifiers and topics only ever match events.
+
+use std::collections::HashSet;
+use std::convert::TryFrom;
+
+use stacks::chainstate::stacks::events::{
+    StacksTransactionEvent, StacksTransactionReceipt, TransactionOrigin,
+};
+use stacks::chainstate::stacks::{StacksTransaction, TransactionPayload};
+use stacks::vm::events::{FTEventType, NFTEventType, STXEventType};
+use stacks::vm::types::{
+    AssetIdentifier, PrincipalData, QualifiedContractIdentifier, StandardPrincipalData,
+};
+use stacks::vm::ClarityName;
+
+use crate::config::EventObserverFilterFile;
+
+/// Asset identifier that matches STX events
+const STX_ASSET: &str = "stx";
+
+/// Payload type of transactions that come from burnchain operations
+const BURNCHAIN_OP_TX_TYPE: &str = "BurnchainOp";
+
+const TX_TYPES: &[&str] = &[
+    "TokenTransfer",
+    "ContractCall",
+    "SmartContract",
+    "PoisonMicroblock",
+    "Coinbase",
+    BURNCHAIN_OP_TX_TYPE,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventFilter {
+    contract_ids: Option<HashSet<QualifiedContractIdentifier>>,
+    asset_ids: Option<HashSet<AssetIdentifier>>,
+    /// Whether or not `asset_ids` contains "stx"
+    stx_asset: bool,
+    principals: Option<HashSet<PrincipalData>>,
+    topics: Option<HashSet<String>>,
+    tx_types: Option<HashSet<String>>,
+}
+
+/// What an event or a transaction can be matched on
+#[derive(Default)]
+struct FilterSubject<'a> {
+    contract_ids: Vec<QualifiedContractIdentifier>,
+    asset_id: Option<&'a AssetIdentifier>,
+    is_stx: bool,
+    principals: Vec<PrincipalData>,
+    topic: Option<&'a str>,
+}
+
+fn parse_asset_identifier(raw: &str) -> Option<AssetIdentifier> {
+    let mut comps = raw.splitn(2, "::");
+    let contract_identifier = QualifiedContractIdentifier::parse(comps.next()?).ok()?;
+    let asset_name = ClarityName::try_from(comps.next()?.to_string()).ok()?;
+    Some(AssetIdentifier {
+        contract_identifier,
+        asset_name,
+    })
+}
+
+/// Parse each of `raw`, failing on the first one that `parse` rejects
+fn parse_all<T: std::hash::Hash + Eq, F: Fn(&str) -> Option<T>>(
+    raw: &Option<Vec<String>>,
+    what: &str,
+    parse: F,
+) -> Result<Option<HashSet<T>>, String> {
+    match raw {
+        Some(values) => values
+            .iter()
+            .map(|value| parse(value).ok_or(format!("Invalid {} in event filter: {}", what, value)))
+            .collect::<Result<HashSet<_>, _>>()
+            .map(Some),
+        None => Ok(None),
+    }
+}
+
+fn payload_type(tx: &TransactionOrigin) -> &'static str {
+    match tx {
+        TransactionOrigin::Stacks(tx) => tx.payload.name(),
+        TransactionOrigin::Burn(_) => BURNCHAIN_OP_TX_TYPE,
+    }
+}
+
+impl EventFilter {
+    pub fn from_config_file(file: &EventObserverFilterFile) -> Result<EventFilter, String> {
+        let contract_ids = parse_all(&file.contract_ids, "contract identifier", |s| {
+            QualifiedContractIdentifier::parse(s).ok()
+        })?;
+        let asset_ids = parse_all(
+            &file.asset_ids.as_ref().map(|ids| {
+                ids.iter()
+                    .filter(|id| id.as_str() != STX_ASSET)
+                    .cloned()
+                    .collect()
+            }),
+            "asset identifier",
+            parse_asset_identifier,
+        )?;
+        let stx_asset = file
+            .asset_ids
+            .as_ref()
+            .map(|ids| ids.iter().any(|id| id == STX_ASSET))
+            .unwrap_or(false);
+        let principals = parse_all(&file.principals, "principal", |s| {
+            PrincipalData::parse(s).ok()
+        })?;
+        let topics = file
+            .topics
+            .as_ref()
+            .map(|topics| topics.iter().cloned().collect());
+        let tx_types = parse_all(&file.tx_types, "transaction type", |s| {
+            if TX_TYPES.contains(&s) {
+                Some(s.to_string())
+            } else {
+                None
+            }
+        })?;
+
+        Ok(EventFilter {
+            contract_ids,
+            asset_ids,
+            stx_asset,
+            principals,
+            topics,
+            tx_types,
+        })
+    }
+
+    /// Does this filter let through an event emitted by `tx`?
+    pub fn matches_event(&self, tx: &TransactionOrigin, event: &StacksTransactionEvent) -> bool {
+        self.matches_tx_type(payload_type(tx)) && self.matches_subject(&Self::event_subject(event))
+    }
+
+    /// Does this filter let through a processed transaction?
+    pub fn matches_receipt(&self, receipt: &StacksTransactionReceipt) -> bool {
+        if receipt
+            .events
+            .iter()
+            .any(|event| self.matches_event(&receipt.transaction, event))
+        {
+            return true;
+        }
+        match &receipt.transaction {
+            TransactionOrigin::Stacks(tx) => self.matches_transaction(tx),
+            TransactionOrigin::Burn(_) => {
+                self.matches_tx_type(BURNCHAIN_OP_TX_TYPE)
+                    && self.matches_subject(&FilterSubject::default())
+            }
+        }
+    }
+
+    /// Does this filter let through a transaction, judging only by the transaction itself (i.e.
+    /// a transaction that has not been processed yet)?
+    pub fn matches_transaction(&self, tx: &StacksTransaction) -> bool {
+        self.matches_tx_type(tx.payload.name()) && self.matches_subject(&Self::tx_subject(tx))
+    }
+
+    fn matches_tx_type(&self, tx_type: &str) -> bool {
+        self.tx_types
+            .as_ref()
+            .map(|tx_types| tx_types.contains(tx_type))
+            .unwrap_or(true)
+    }
+
+    fn matches_subject(&self, subject: &FilterSubject) -> bool {
+        if let Some(contract_ids) = self.contract_ids.as_ref() {
+            if !subject
+                .contract_ids
+                .iter()
+                .any(|c| contract_ids.contains(c))
+            {
+                return false;
+            }
+        }
+        if let Some(asset_ids) = self.asset_ids.as_ref() {
+            let asset_match = subject
+                .asset_id
+                .map(|asset_id| asset_ids.contains(asset_id))
+                .unwrap_or(false);
+            let stx_match = self.stx_asset && subject.is_stx;
+            if !(asset_match || stx_match) {
+                return false;
+            }
+        }
+        if let Some(principals) = self.principals.as_ref() {
+            if !subject.principals.iter().any(|p| principals.contains(p)) {
+                return false;
+            }
+        }
+        if let Some(topics) = self.topics.as_ref() {
+            if !subject
+                .topic
+                .map(|topic| topics.contains(topic))
+                .unwrap_or(false)
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn asset_subject<'a>(
+        asset_id: &'a AssetIdentifier,
+        principals: Vec<PrincipalData>,
+    ) -> FilterSubject<'a> {
+        FilterSubject {
+            contract_ids: vec![asset_id.contract_identifier.clone()],
+            asset_id: Some(asset_id),
+            principals,
+            ..FilterSubject::default()
+        }
+    }
+
+    fn stx_subject<'a>(principals: Vec<PrincipalData>) -> FilterSubject<'a> {
+        FilterSubject {
+            is_stx: true,
+            principals,
+            ..FilterSubject::default()
+        }
+    }
+
+    fn event_subject(event: &StacksTransactionEvent) -> FilterSubject<'_> {
+        match event {
+            StacksTransactionEvent::SmartContractEvent(data) => FilterSubject {
+                contract_ids: vec![data.key.0.clone()],
+                principals: vec![PrincipalData::Contract(data.key.0.clone())],
+                topic: Some(data.key.1.as_str()),
+                ..FilterSubject::default()
+            },
+            StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(data)) => {
+                Self::stx_subject(vec![data.sender.clone(), data.recipient.clone()])
+            }
+            StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(data)) => {
+                Self::stx_subject(vec![data.recipient.clone()])
+            }
+            StacksTransactionEvent::STXEvent(STXEventType::STXBurnEvent(data)) => {
+                Self::stx_subject(vec![data.sender.clone()])
+            }
+            StacksTransactionEvent::STXEvent(STXEventType::STXLockEvent(data)) => FilterSubject {
+                contract_ids: vec![data.contract_identifier.clone()],
+                ..Self::stx_subject(vec![data.locked_address.clone()])
+            },
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(data)) => {
+                Self::asset_subject(
+                    &data.asset_identifier,
+                    vec![data.sender.clone(), data.recipient.clone()],
+                )
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(data)) => {
+                Self::asset_subject(&data.asset_identifier, vec![data.recipient.clone()])
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(data)) => {
+                Self::asset_subject(&data.asset_identifier, vec![data.sender.clone()])
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(data)) => {
+                Self::asset_subject(
+                    &data.asset_identifier,
+                    vec![data.sender.clone(), data.recipient.clone()],
+                )
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(data)) => {
+                Self::asset_subject(&data.asset_identifier, vec![data.recipient.clone()])
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(data)) => {
+                Self::asset_subject(&data.asset_identifier, vec![data.sender.clone()])
+            }
+        }
+    }
+
+    fn tx_subject<'a>(tx: &StacksTransaction) -> FilterSubject<'a> {
+        let origin = tx.origin_address();
+        let mut principals = vec![PrincipalData::from(origin)];
+        if let Some(sponsor) = tx.sponsor_address() {
+            principals.push(PrincipalData::from(sponsor));
+        }
+
+        let mut contract_ids = vec![];
+        match &tx.payload {
+            TransactionPayload::TokenTransfer(recipient, ..) => {
+                principals.push(recipient.clone());
+            }
+            TransactionPayload::ContractCall(call) => {
+                let contract_id = call.contract_identifier();
+                principals.push(PrincipalData::Contract(contract_id.clone()));
+                contract_ids.push(contract_id);
+            }
+            TransactionPayload::SmartContract(contract, _) => {
+                let contract_id = QualifiedContractIdentifier::new(
+                    StandardPrincipalData::from(origin),
+                    contract.name.clone(),
+                );
+                principals.push(PrincipalData::Contract(contract_id.clone()));
+                contract_ids.push(contract_id);
+            }
+            TransactionPayload::Coinbase(_, Some(recipient)) => {
+                principals.push(recipient.clone());
+            }
+            TransactionPayload::Coinbase(_, None) | TransactionPayload::PoisonMicroblock(..) => {}
+        }
+
+        FilterSubject {
+            contract_ids,
+            principals,
+            ..FilterSubject::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use stacks::chainstate::stacks::{
+        TransactionAuth, TransactionContractCall, TransactionVersion,
+    };
+    use stacks::types::chainstate::StacksAddress;
+    use stacks::util::secp256k1::Secp256k1PrivateKey;
+    use stacks::vm::costs::ExecutionCost;
+    use stacks::vm::events::{STXTransferEventData, SmartContractEventData};
+    use stacks::vm::types::BuffData;
+    use stacks::vm::Value;
+
+    const CONTRACT: &str = "ST000000000000000000002AMW42H.pox";
+    const ALICE: &str = "ST2QKZ4FKHAH1NQKYKYAYZPY440FEPK7GZ1R5HBP2";
+    const BOB: &str = "ST319CF5WV77KYR1H3GT0GZ7B8Q4AQPY42ETP1VPF";
+
+    fn filter(file: EventObserverFilterFile) -> EventFilter {
+        EventFilter::from_config_file(&file).unwrap()
+    }
+
+    fn strings(values: &[&str]) -> Option<Vec<String>> {
+        Some(values.iter().map(|s| s.to_string()).collect())
+    }
+
+    fn contract_call_tx() -> StacksTransaction {
+        let privk = Secp256k1PrivateKey::new();
+        let contract_id = QualifiedContractIdentifier::parse(CONTRACT).unwrap();
+        StacksTransaction::new(
+            TransactionVersion::Testnet,
+            TransactionAuth::from_p2pkh(&privk).unwrap(),
+            TransactionPayload::ContractCall(TransactionContractCall {
+                address: StacksAddress::from(contract_id.issuer.clone()),
+                contract_name: contract_id.name.clone(),
+                function_name: "stack-stx".into(),
+                function_args: vec![],
+            }),
+        )
+    }
+
+    fn print_event(topic: &str) -> StacksTransactionEvent {
+        StacksTransactionEvent::SmartContractEvent(SmartContractEventData {
+            key: (
+                QualifiedContractIdentifier::parse(CONTRACT).unwrap(),
+                topic.to_string(),
+            ),
+            value: Value::UInt(1),
+        })
+    }
+
+    fn stx_transfer_event(sender: &str, recipient: &str) -> StacksTransactionEvent {
+        StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(STXTransferEventData {
+            sender: PrincipalData::parse(sender).unwrap(),
+            recipient: PrincipalData::parse(recipient).unwrap(),
+            amount: 1,
+            memo: BuffData::empty(),
+        }))
+    }
+
+    #[test]
+    fn empty_filter_matches_everything() {
+        let filter = filter(EventObserverFilterFile::default());
+        let tx = TransactionOrigin::Stacks(contract_call_tx());
+        assert!(filter.matches_event(&tx, &print_event("print")));
+        assert!(filter.matches_event(&tx, &stx_transfer_event(ALICE, BOB)));
+    }
+
+    #[test]
+    fn criteria_must_all_match() {
+        let filter = filter(EventObserverFilterFile {
+            contract_ids: strings(&[CONTRACT]),
+            topics: strings(&["print"]),
+            ..EventObserverFilterFile::default()
+        });
+        let tx = TransactionOrigin::Stacks(contract_call_tx());
+        assert!(filter.matches_event(&tx, &print_event("print")));
+        assert!(!filter.matches_event(&tx, &print_event("other")));
+        assert!(!filter.matches_event(&tx, &stx_transfer_event(ALICE, BOB)));
+    }
+
+    #[test]
+    fn stx_events_match_by_principal_and_asset() {
+        let filter = filter(EventObserverFilterFile {
+            asset_ids: strings(&["stx"]),
+            principals: strings(&[BOB]),
+            ..EventObserverFilterFile::default()
+        });
+        let tx = TransactionOrigin::Stacks(contract_call_tx());
+        assert!(filter.matches_event(&tx, &stx_transfer_event(ALICE, BOB)));
+        assert!(!filter.matches_event(&tx, &stx_transfer_event(ALICE, ALICE)));
+        assert!(!filter.matches_event(&tx, &print_event("print")));
+    }
+
+    #[test]
+    fn transactions_match_on_their_own_fields() {
+        let tx = contract_call_tx();
+
+        let by_contract = filter(EventObserverFilterFile {
+            contract_ids: strings(&[CONTRACT]),
+            tx_types: strings(&["ContractCall"]),
+            ..EventObserverFilterFile::default()
+        });
+        assert!(by_contract.matches_transaction(&tx));
+
+        let by_type = filter(EventObserverFilterFile {
+            tx_types: strings(&["TokenTransfer"]),
+            ..EventObserverFilterFile::default()
+        });
+        assert!(!by_type.matches_transaction(&tx));
+
+        // topics only ever match events
+        let by_topic = filter(EventObserverFilterFile {
+            topics: strings(&["print"]),
+            ..EventObserverFilterFile::default()
+        });
+        assert!(!by_topic.matches_transaction(&tx));
+
+        let mut receipt = StacksTransactionReceipt::from_contract_call(
+            tx,
+            vec![print_event("print")],
+            Value::okay_true(),
+            0,
+            ExecutionCost::zero(),
+        );
+        assert!(by_topic.matches_receipt(&receipt));
+        receipt.events.clear();
+        assert!(!by_topic.matches_receipt(&receipt));
+    }
+
+    #[test]
+    fn invalid_filters_are_rejected() {
+        for file in [
+            EventObserverFilterFile {
+                contract_ids: strings(&["not-a-contract"]),
+                ..EventObserverFilterFile::default()
+            },
+            EventObserverFilterFile {
+                asset_ids: strings(&[CONTRACT]),
+                ..EventObserverFilterFile::default()
+            },
+            EventObserverFilterFile {
+                principals: strings(&["alice"]),
+                ..EventObserverFilterFile::default()
+            },
+            EventObserverFilterFile {
+                tx_types: strings(&["Transfer"]),
+                ..EventObserverFilterFile::default()
+            },
+        ]
+        .iter()
+        {
+            assert!(EventFilter::from_config_file(file).is_err());
+        }
+    }
+}
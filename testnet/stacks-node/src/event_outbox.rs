@@ -0,0 +1,488 @@
+//! Durable delivery queue for event observers.
+//!
+//! Every payload destined for an observer is first written to an on-disk outbox (a SQLite
+//! database next to the chainstate), and is then delivered by a worker thread dedicated to that
+//! observer.  The node's own threads only pay for the insert, so a slow or unreachable observer
+//! can no longer stall block processing.  A payload is only removed from the outbox once the
+//! observer has acknowledged it, so anything still undelivered when the node stops is replayed,
+//! in order, the next time it starts.
+
+use std::cmp;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use rusqlite::{Connection, Error as SqliteError, OpenFlags, OptionalExtension, NO_PARAMS};
+
+use stacks::util::get_epoch_time_secs;
+use stacks::util::sleep_ms;
+use stacks::util_lib::db::{sqlite_open, table_exists, tx_begin_immediate_sqlite};
+
+use crate::config::EventObserverConfig;
+
+const OUTBOX_SCHEMA: &[&str] = &[
+    r#"
+    CREATE TABLE pending_payloads(
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        endpoint TEXT NOT NULL,
+        path TEXT NOT NULL,
+        payload TEXT NOT NULL,
+        queued_at INTEGER NOT NULL,
+        attempts INTEGER NOT NULL DEFAULT 0
+    );"#,
+    "CREATE INDEX pending_payloads_by_endpoint ON pending_payloads(endpoint, id);",
+];
+
+/// A payload that has been queued for an observer, but not yet acknowledged by it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingPayload {
+    pub id: i64,
+    pub path: String,
+    pub payload: String,
+    pub queued_at: u64,
+    pub attempts: u64,
+}
+
+/// The on-disk outbox.  A single database holds the queues of every observer, keyed by the
+/// observer's endpoint, so an observer keeps its backlog across restarts as long as its
+/// endpoint does not change.  The config rejects two observers with the same endpoint, since
+/// their delivery threads would otherwise share (and both send) one queue.
+#[derive(Debug)]
+pub struct EventOutboxDB {
+    conn: Connection,
+}
+
+impl EventOutboxDB {
+    /// Open the outbox at the given path, creating it if it does not exist.
+    pub fn open(path: &Path) -> Result<EventOutboxDB, SqliteError> {
+        let mut conn = sqlite_open(
+            path,
+            OpenFlags::SQLITE_OPEN_CREATE | OpenFlags::SQLITE_OPEN_READ_WRITE,
+            false,
+        )?;
+
+        // every observer opens its own connections, so only the first one in creates the schema
+        let tx = tx_begin_immediate_sqlite(&mut conn)?;
+        if !table_exists(&tx, "pending_payloads")? {
+            for cmd in OUTBOX_SCHEMA.iter() {
+                tx.execute(cmd, NO_PARAMS)?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(EventOutboxDB { conn })
+    }
+
+    /// Append a payload to the end of an observer's queue.  Returns its ID.
+    pub fn push(&mut self, endpoint: &str, path: &str, payload: &str) -> Result<i64, SqliteError> {
+        let queued_at = get_epoch_time_secs() as i64;
+        self.conn.execute(
+            "INSERT INTO pending_payloads (endpoint, path, payload, queued_at) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![endpoint, path, payload, queued_at],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Get the oldest payload still waiting to be delivered to an observer.
+    pub fn peek(&self, endpoint: &str) -> Result<Option<PendingPayload>, SqliteError> {
+        self.conn
+            .query_row(
+                "SELECT id, path, payload, queued_at, attempts FROM pending_payloads
+                 WHERE endpoint = ?1 ORDER BY id ASC LIMIT 1",
+                &[endpoint],
+                |row| {
+                    let queued_at: i64 = row.get(3)?;
+                    let attempts: i64 = row.get(4)?;
+                    Ok(PendingPayload {
+                        id: row.get(0)?,
+                        path: row.get(1)?,
+                        payload: row.get(2)?,
+                        queued_at: queued_at as u64,
+                        attempts: attempts as u64,
+                    })
+                },
+            )
+            .optional()
+    }
+
+    /// Count how many payloads are waiting to be delivered to an observer.
+    pub fn count_pending(&self, endpoint: &str) -> Result<u64, SqliteError> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM pending_payloads WHERE endpoint = ?1",
+            &[endpoint],
+            |row| row.get(0),
+        )?;
+        Ok(count as u64)
+    }
+
+    /// Record that an attempt to deliver a payload failed.
+    pub fn record_failed_attempt(&mut self, id: i64) -> Result<(), SqliteError> {
+        self.conn.execute(
+            "UPDATE pending_payloads SET attempts = attempts + 1 WHERE id = ?1",
+            [id],
+        )?;
+        Ok(())
+    }
+
+    /// Remove a payload from the outbox, either because it was delivered or because we gave up.
+    pub fn remove(&mut self, id: i64) -> Result<(), SqliteError> {
+        self.conn
+            .execute("DELETE FROM pending_payloads WHERE id = ?1", [id])?;
+        Ok(())
+    }
+}
+
+/// Lag bookkeeping shared between an observer's producers and its worker.
+#[derive(Debug)]
+struct OutboxLag {
+    endpoint: String,
+    max_lag: Option<u64>,
+    pending: AtomicU64,
+    alarmed: AtomicBool,
+}
+
+impl OutboxLag {
+    fn queued(&self) {
+        let pending = self.pending.fetch_add(1, Ordering::SeqCst) + 1;
+        if let Some(max_lag) = self.max_lag {
+            if pending > max_lag && !self.alarmed.swap(true, Ordering::SeqCst) {
+                error!("Event dispatcher: observer is lagging behind";
+                       "endpoint" => &self.endpoint,
+                       "pending" => pending,
+                       "max_lag" => max_lag);
+            }
+        }
+    }
+
+    fn dequeued(&self) {
+        let pending = self
+            .pending
+            .fetch_sub(1, Ordering::SeqCst)
+            .saturating_sub(1);
+        if let Some(max_lag) = self.max_lag {
+            if pending <= max_lag && self.alarmed.swap(false, Ordering::SeqCst) {
+                info!("Event dispatcher: observer has caught up";
+                      "endpoint" => &self.endpoint,
+                      "pending" => pending);
+            }
+        }
+    }
+}
+
+/// Producer-side handle on an observer's outbox.  Clones are cheap, and all of them feed the
+/// same worker.  The worker exits once every handle has been dropped and its queue is empty.
+#[derive(Debug, Clone)]
+pub struct ObserverOutbox {
+    endpoint: String,
+    db: Arc<Mutex<EventOutboxDB>>,
+    wakeup: SyncSender<()>,
+    lag: Arc<OutboxLag>,
+}
+
+impl ObserverOutbox {
+    /// Open the outbox at `db_path` for the observer described by `conf`, and start the worker
+    /// thread that drains it through `deliver`.  `deliver` makes a single attempt at sending a
+    /// payload body to the given path, and returns whether or not the observer accepted it.
+    /// Payloads left over from a previous run are delivered before any new ones.
+    pub fn spawn<F>(
+        db_path: &Path,
+        conf: &EventObserverConfig,
+        deliver: F,
+    ) -> Result<ObserverOutbox, SqliteError>
+    where
+        F: FnMut(&str, &[u8]) -> bool + Send + 'static,
+    {
+        let db = EventOutboxDB::open(db_path)?;
+        let worker_db = EventOutboxDB::open(db_path)?;
+
+        let pending = db.count_pending(&conf.endpoint)?;
+        if pending > 0 {
+            info!("Event dispatcher: replaying undelivered payloads";
+                  "endpoint" => &conf.endpoint,
+                  "pending" => pending);
+        }
+
+        let lag = Arc::new(OutboxLag {
+            endpoint: conf.endpoint.clone(),
+            max_lag: conf.max_lag,
+            pending: AtomicU64::new(pending),
+            alarmed: AtomicBool::new(false),
+        });
+
+        // a single slot is enough: the worker drains everything it finds once woken up
+        let (wakeup, wakeup_rx) = sync_channel(1);
+
+        let worker = OutboxWorker {
+            endpoint: conf.endpoint.clone(),
+            db: worker_db,
+            wakeup: wakeup_rx,
+            lag: lag.clone(),
+            retry_backoff_ms: cmp::max(conf.retry_backoff_ms, 1),
+            max_retry_backoff_ms: cmp::max(conf.max_retry_backoff_ms, conf.retry_backoff_ms),
+            max_attempts: conf.max_attempts,
+        };
+
+        thread::Builder::new()
+            .name(format!("event-observer:{}", &conf.endpoint))
+            .spawn(move || worker.run(deliver))
+            .expect("FATAL: failed to start event observer delivery thread");
+
+        Ok(ObserverOutbox {
+            endpoint: conf.endpoint.clone(),
+            db: Arc::new(Mutex::new(db)),
+            wakeup,
+            lag,
+        })
+    }
+
+    /// Queue a payload for delivery to the given path.  Returns as soon as the payload is on
+    /// disk, or an error if it could not be stored (in which case nothing was queued).
+    pub fn push(&self, path: &str, payload: &str) -> Result<(), SqliteError> {
+        {
+            let mut db = self
+                .db
+                .lock()
+                .expect("FATAL: event observer outbox lock poisoned");
+            db.push(&self.endpoint, path, payload)?;
+        }
+        self.lag.queued();
+
+        // if the worker already has a wakeup pending, it will pick this payload up as well
+        let _ = self.wakeup.try_send(());
+        Ok(())
+    }
+
+    /// How many payloads are waiting to be delivered.
+    pub fn pending(&self) -> u64 {
+        self.lag.pending.load(Ordering::SeqCst)
+    }
+}
+
+struct OutboxWorker {
+    endpoint: String,
+    db: EventOutboxDB,
+    wakeup: Receiver<()>,
+    lag: Arc<OutboxLag>,
+    retry_backoff_ms: u64,
+    max_retry_backoff_ms: u64,
+    max_attempts: Option<u64>,
+}
+
+impl OutboxWorker {
+    fn run<F>(mut self, mut deliver: F)
+    where
+        F: FnMut(&str, &[u8]) -> bool,
+    {
+        let mut backoff_ms = self.retry_backoff_ms;
+        loop {
+            let pending = match self.db.peek(&self.endpoint) {
+                Ok(Some(pending)) => pending,
+                Ok(None) => {
+                    // nothing to do until a producer queues something.  If all producers are
+                    // gone, then nothing ever will be.
+                    if self.wakeup.recv().is_err() {
+                        debug!("Event dispatcher: outbox worker exiting"; "endpoint" => &self.endpoint);
+                        return;
+                    }
+                    continue;
+                }
+                Err(e) => {
+                    error!("Event dispatcher: failed to read outbox";
+                           "endpoint" => &self.endpoint,
+                           "err" => ?e);
+                    sleep_ms(backoff_ms);
+                    continue;
+                }
+            };
+
+            if deliver(&pending.path, pending.payload.as_bytes()) {
+                self.remove(&pending);
+                backoff_ms = self.retry_backoff_ms;
+                continue;
+            }
+
+            let attempts = pending.attempts + 1;
+            if let Some(max_attempts) = self.max_attempts {
+                if attempts >= max_attempts {
+                    error!("Event dispatcher: giving up on payload";
+                           "endpoint" => &self.endpoint,
+                           "path" => &pending.path,
+                           "queued_at" => pending.queued_at,
+                           "attempts" => attempts);
+                    self.remove(&pending);
+                    backoff_ms = self.retry_backoff_ms;
+                    continue;
+                }
+            }
+
+            if let Err(e) = self.db.record_failed_attempt(pending.id) {
+                warn!("Event dispatcher: failed to record delivery attempt";
+                      "endpoint" => &self.endpoint,
+                      "err" => ?e);
+            }
+            sleep_ms(backoff_ms);
+            backoff_ms = cmp::min(backoff_ms.saturating_mul(2), self.max_retry_backoff_ms);
+        }
+    }
+
+    fn remove(&mut self, pending: &PendingPayload) {
+        match self.db.remove(pending.id) {
+            Ok(()) => self.lag.dequeued(),
+            Err(e) => {
+                // we'll see it again on the next pass, so the observer may get a duplicate
+                error!("Event dispatcher: failed to remove payload from outbox";
+                       "endpoint" => &self.endpoint,
+                       "err" => ?e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::mpsc::channel;
+    use std::time::Duration;
+
+    fn outbox_path(name: &str) -> PathBuf {
+        let path = PathBuf::from(format!(
+            "/tmp/stacks-node-tests/event-outbox/{}.sqlite",
+            name
+        ));
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        if fs::metadata(&path).is_ok() {
+            fs::remove_file(&path).unwrap();
+        }
+        path
+    }
+
+    fn observer_config(endpoint: &str) -> EventObserverConfig {
+        EventObserverConfig {
+            endpoint: endpoint.to_string(),
+            retry_backoff_ms: 1,
+            max_retry_backoff_ms: 4,
+            ..EventObserverConfig::default()
+        }
+    }
+
+    #[test]
+    fn outbox_db_is_fifo_per_endpoint() {
+        let path = outbox_path("outbox_db_is_fifo_per_endpoint");
+        let mut db = EventOutboxDB::open(&path).unwrap();
+
+        let a1 = db.push("a", "new_block", "1").unwrap();
+        let b1 = db.push("b", "new_block", "2").unwrap();
+        let a2 = db.push("a", "new_burn_block", "3").unwrap();
+
+        assert_eq!(db.count_pending("a").unwrap(), 2);
+        assert_eq!(db.count_pending("b").unwrap(), 1);
+
+        db.record_failed_attempt(a1).unwrap();
+        let head = db.peek("a").unwrap().unwrap();
+        assert_eq!(head.id, a1);
+        assert_eq!(head.path, "new_block");
+        assert_eq!(head.attempts, 1);
+
+        db.remove(a1).unwrap();
+        assert_eq!(db.peek("a").unwrap().unwrap().id, a2);
+        assert_eq!(db.peek("b").unwrap().unwrap().id, b1);
+
+        db.remove(a2).unwrap();
+        assert_eq!(db.peek("a").unwrap(), None);
+    }
+
+    #[test]
+    fn outbox_retries_in_order() {
+        let path = outbox_path("outbox_retries_in_order");
+        let (delivered_tx, delivered_rx) = channel();
+        let mut failures = 3;
+
+        let outbox = ObserverOutbox::spawn(&path, &observer_config("a"), move |path, body| {
+            if failures > 0 {
+                failures -= 1;
+                return false;
+            }
+            delivered_tx
+                .send((path.to_string(), String::from_utf8(body.to_vec()).unwrap()))
+                .unwrap();
+            true
+        })
+        .unwrap();
+
+        outbox.push("new_block", "1").unwrap();
+        outbox.push("new_mempool_tx", "2").unwrap();
+
+        let timeout = Duration::from_secs(10);
+        assert_eq!(
+            delivered_rx.recv_timeout(timeout).unwrap(),
+            ("new_block".to_string(), "1".to_string())
+        );
+        assert_eq!(
+            delivered_rx.recv_timeout(timeout).unwrap(),
+            ("new_mempool_tx".to_string(), "2".to_string())
+        );
+    }
+
+    #[test]
+    fn outbox_replays_after_restart() {
+        let path = outbox_path("outbox_replays_after_restart");
+        {
+            let mut db = EventOutboxDB::open(&path).unwrap();
+            db.push("a", "new_block", "1").unwrap();
+            db.push("a", "new_burn_block", "2").unwrap();
+        }
+
+        let (delivered_tx, delivered_rx) = channel();
+        let outbox = ObserverOutbox::spawn(&path, &observer_config("a"), move |_path, body| {
+            delivered_tx
+                .send(String::from_utf8(body.to_vec()).unwrap())
+                .unwrap();
+            true
+        })
+        .unwrap();
+
+        let timeout = Duration::from_secs(10);
+        assert_eq!(delivered_rx.recv_timeout(timeout).unwrap(), "1");
+        assert_eq!(delivered_rx.recv_timeout(timeout).unwrap(), "2");
+
+        drop(outbox);
+        let db = EventOutboxDB::open(&path).unwrap();
+        while db.count_pending("a").unwrap() > 0 {
+            sleep_ms(10);
+        }
+    }
+
+    #[test]
+    fn outbox_gives_up_after_max_attempts() {
+        let path = outbox_path("outbox_gives_up_after_max_attempts");
+        let (delivered_tx, delivered_rx) = channel();
+        let conf = EventObserverConfig {
+            max_attempts: Some(2),
+            ..observer_config("a")
+        };
+
+        let outbox = ObserverOutbox::spawn(&path, &conf, move |_path, body| {
+            if body == b"poison" {
+                return false;
+            }
+            delivered_tx
+                .send(String::from_utf8(body.to_vec()).unwrap())
+                .unwrap();
+            true
+        })
+        .unwrap();
+
+        outbox.push("new_block", "poison").unwrap();
+        outbox.push("new_block", "ok").unwrap();
+
+        let timeout = Duration::from_secs(10);
+        assert_eq!(delivered_rx.recv_timeout(timeout).unwrap(), "ok");
+        while outbox.pending() > 0 {
+            sleep_ms(10);
+        }
+    }
+}
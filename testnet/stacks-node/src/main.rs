@@ -25,6 +25,8 @@ pub mod monitoring;
 pub mod burnchains;
 pub mod config;
 pub mod event_dispatcher;
+pub mod event_filter;
+pub mod event_outbox;
 pub mod genesis_data;
 pub mod keychain;
 pub mod neon_node;
@@ -2110,7 +2110,7 @@ impl RelayerThread {
             .make_cost_metric()
             .unwrap_or_else(|| Box::new(UnitMetric));
 
-        let mempool = MemPoolDB::open(
+        let mut mempool = MemPoolDB::open(
             is_mainnet,
             chain_id,
             &stacks_chainstate_path,
@@ -2118,6 +2118,8 @@ impl RelayerThread {
             metric,
         )
         .expect("Database failure opening mempool");
+        mempool.rbf_policy = config.mempool.rbf_policy();
+        mempool.limits = config.mempool.limits();
 
         let keychain = Keychain::default(config.node.seed.clone());
         let bitcoin_controller = BitcoinRegtestController::new_dummy(config.clone());
@@ -3561,7 +3563,7 @@ impl PeerThread {
             .make_cost_metric()
             .unwrap_or_else(|| Box::new(UnitMetric));
 
-        let mempool = MemPoolDB::open(
+        let mut mempool = MemPoolDB::open(
             config.is_mainnet(),
             config.burnchain.chain_id,
             &config.get_chainstate_path_str(),
@@ -3569,6 +3571,8 @@ impl PeerThread {
             metric,
         )
         .expect("Database failure opening mempool");
+        mempool.rbf_policy = config.mempool.rbf_policy();
+        mempool.limits = config.mempool.limits();
 
         mempool
     }
@@ -3723,6 +3727,7 @@ impl PeerThread {
                 genesis_chainstate_hash: Sha256Sum::from_hex(stx_genesis::GENESIS_CHAINSTATE_HASH)
                     .unwrap(),
                 event_observer: Some(event_dispatcher),
+                event_stream: event_dispatcher.event_stream(),
                 cost_estimator: Some(cost_estimator.as_ref()),
                 cost_metric: Some(cost_metric.as_ref()),
                 fee_estimator: fee_estimator.map(|boxed_estimator| boxed_estimator.as_ref()),
@@ -3955,6 +3960,10 @@ impl StacksNode {
                 )
                 .unwrap();
             }
+            // remember the names behind bootstrap nodes we can only reach through the proxy
+            for bootstrap_host in config.node.bootstrap_hosts.iter() {
+                PeerDB::set_peer_host(&mut tx, bootstrap_host).unwrap();
+            }
             tx.commit().unwrap();
         }
 
@@ -4066,6 +4075,8 @@ impl StacksNode {
     /// Continuously receives, until told otherwise.
     pub fn p2p_main(mut p2p_thread: PeerThread, event_dispatcher: EventDispatcher) {
         let (mut dns_resolver, mut dns_client) = DNSResolver::new(10);
+        dns_resolver
+            .set_proxy_resolution(p2p_thread.config.connection_options.socks5_proxy.is_some());
 
         // spawn a daemon thread that runs the DNS resolver.
         // It will die when the rest of the system dies.
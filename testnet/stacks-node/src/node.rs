@@ -365,7 +365,8 @@ impl Node {
         )
         .expect("FATAL: failed to initiate mempool");
 
-        let mut event_dispatcher = EventDispatcher::new();
+        let mut event_dispatcher =
+            EventDispatcher::with_outbox(config.get_event_observers_db_path());
 
         for observer in &config.events_observers {
             event_dispatcher.register_observer(observer);
@@ -404,7 +405,8 @@ impl Node {
 
         let keychain = Keychain::default(config.node.seed.clone());
 
-        let mut event_dispatcher = EventDispatcher::new();
+        let mut event_dispatcher =
+            EventDispatcher::with_outbox(config.get_event_observers_db_path());
 
         for observer in &config.events_observers {
             event_dispatcher.register_observer(observer);
@@ -29,6 +29,8 @@ use stacks::chainstate::coordinator::{
 use stacks::chainstate::stacks::db::{ChainStateBootData, StacksChainState};
 use stacks::core::StacksEpochId;
 use stacks::net::atlas::{AtlasConfig, Attachment, AttachmentInstance, ATTACHMENTS_CHANNEL_SIZE};
+use stacks::net::event_stream::EventStreamHub;
+use stacks::net::tls;
 use stacks::util_lib::db::Error as db_error;
 use stx_genesis::GenesisData;
 
@@ -185,10 +187,17 @@ impl RunLoop {
             config.burnchain.burn_fee_cap,
         )));
 
-        let mut event_dispatcher = EventDispatcher::new();
+        let mut event_dispatcher =
+            EventDispatcher::with_outbox(config.get_event_observers_db_path());
         for observer in config.events_observers.iter() {
             event_dispatcher.register_observer(observer);
         }
+        if config.node.event_stream_max_clients > 0 {
+            event_dispatcher.set_event_stream(EventStreamHub::new(
+                config.node.event_stream_max_clients as usize,
+                config.node.event_stream_buffer as usize,
+            ));
+        }
 
         Self {
             config,
@@ -281,9 +290,11 @@ impl RunLoop {
     }
 
     /// Set up termination handler.  Have a signal set the `should_keep_running` atomic bool to
-    /// false.  Panics of called more than once.
+    /// false, except for SIGHUP, which reloads the RPC server's TLS certificate and key if it
+    /// has any.  Panics of called more than once.
     fn setup_termination_handler(&self) {
         let keep_running_writer = self.should_keep_running.clone();
+        let reload_tls_on_hangup = self.config.connection_options.rpc_tls_cert_path.is_some();
         let install = termination::set_handler(move |sig_id| match sig_id {
             SignalId::Bus => {
                 let msg = "Caught SIGBUS; crashing immediately and dumping core\n";
@@ -292,6 +303,12 @@ impl RunLoop {
                     libc::abort();
                 }
             }
+            SignalId::Hangup if reload_tls_on_hangup => {
+                // e.g. once the RPC server's certificate has been renewed
+                let msg = "Caught SIGHUP; reloading the RPC TLS certificate and key\n";
+                async_safe_write_stderr(msg);
+                tls::request_reload();
+            }
             _ => {
                 let msg = format!("Graceful termination request received (signal `{}`), will complete the ongoing runloop cycles and terminate\n", sig_id);
                 async_safe_write_stderr(&msg);
@@ -1253,3 +1270,32 @@ impl RunLoop {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_sighup_reloads_tls() {
+        let mut conf = Config::default();
+        conf.connection_options.rpc_tls_cert_path = Some("/tmp/unused-cert.pem".to_string());
+        conf.connection_options.rpc_tls_key_path = Some("/tmp/unused-key.pem".to_string());
+        let run_loop = RunLoop::new(conf);
+        run_loop.setup_termination_handler();
+
+        // the handler runs on its own thread, so wait for it to pick the signal up
+        let generation = tls::reload_generation();
+        unsafe {
+            assert_eq!(libc::raise(libc::SIGHUP), 0);
+        }
+        for _ in 0..100 {
+            if tls::reload_generation() > generation {
+                break;
+            }
+            sleep_ms(100);
+        }
+        assert!(tls::reload_generation() > generation);
+        assert!(run_loop.should_keep_running.load(Ordering::SeqCst));
+    }
+}
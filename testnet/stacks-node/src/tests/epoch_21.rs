@@ -100,6 +100,7 @@ fn advance_to_2_1(
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut epochs = core::STACKS_EPOCHS_REGTEST.to_vec();
@@ -596,6 +597,7 @@ fn transition_fixes_bitcoin_rigidity() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut epochs = core::STACKS_EPOCHS_REGTEST.to_vec();
@@ -1475,6 +1477,7 @@ fn transition_removes_pox_sunset() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     conf.initial_balances.push(InitialBalance {
@@ -1787,6 +1790,7 @@ fn transition_empty_blocks() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let keychain = Keychain::default(conf.node.seed.clone());
@@ -4720,6 +4724,7 @@ fn trait_invocation_cross_epoch() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
     let mut epochs = core::STACKS_EPOCHS_REGTEST.to_vec();
     epochs[1].end_height = epoch_2_05;
@@ -4966,6 +4971,7 @@ fn test_v1_unlock_height_with_current_stackers() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
     conf.initial_balances.append(&mut initial_balances);
 
@@ -5226,6 +5232,7 @@ fn test_v1_unlock_height_with_delay_and_current_stackers() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
     conf.initial_balances.append(&mut initial_balances);
 
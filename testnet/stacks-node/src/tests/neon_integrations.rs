@@ -1076,6 +1076,7 @@ fn deep_contract() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let spender_bal = 10_000_000_000 * (core::MICROSTACKS_PER_STACKS as u64);
@@ -1180,6 +1181,7 @@ fn bad_microblock_pubkey() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -1265,6 +1267,7 @@ fn liquid_ustx_integration() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let spender_bal = 10_000_000_000 * (core::MICROSTACKS_PER_STACKS as u64);
@@ -1393,6 +1396,7 @@ fn lockup_integration() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -1509,6 +1513,7 @@ fn stx_transfer_btc_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     conf.initial_balances.push(InitialBalance {
@@ -1778,6 +1783,7 @@ fn stx_delegate_btc_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -2347,6 +2353,7 @@ fn microblock_fork_poison_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -2578,6 +2585,7 @@ fn microblock_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -3563,6 +3571,7 @@ fn size_overflow_unconfirmed_microblocks_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -3760,6 +3769,7 @@ fn size_overflow_unconfirmed_stream_microblocks_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -3955,6 +3965,7 @@ fn size_overflow_unconfirmed_invalid_stream_microblocks_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -4222,6 +4233,7 @@ fn runtime_overflow_unconfirmed_microblocks_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -4395,6 +4407,7 @@ fn block_replay_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -4527,6 +4540,7 @@ fn cost_voting_integration() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let spender_bal = 10_000_000_000 * (core::MICROSTACKS_PER_STACKS as u64);
@@ -4850,6 +4864,7 @@ fn mining_events_integration_test() {
             EventKeyType::MinedBlocks,
             EventKeyType::MinedMicroblocks,
         ],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -5094,6 +5109,7 @@ fn block_limit_hit_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -5350,6 +5366,7 @@ fn microblock_limit_hit_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -5500,6 +5517,7 @@ fn block_large_tx_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     conf.initial_balances.push(InitialBalance {
@@ -5639,6 +5657,7 @@ fn microblock_large_tx_integration_test_FLAKY() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     conf.initial_balances.push(InitialBalance {
@@ -5778,6 +5797,7 @@ fn pox_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let first_bal = 6_000_000_000 * (core::MICROSTACKS_PER_STACKS as u64);
@@ -6314,6 +6334,7 @@ fn atlas_integration_test() {
         .push(EventObserverConfig {
             endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
             events_keys: vec![EventKeyType::AnyEvent],
+            ..EventObserverConfig::default()
         });
 
     conf_follower_node.node.always_use_affirmation_maps = false;
@@ -6854,6 +6875,7 @@ fn antientropy_integration_test() {
         .push(EventObserverConfig {
             endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
             events_keys: vec![EventKeyType::AnyEvent],
+            ..EventObserverConfig::default()
         });
 
     conf_follower_node.node.mine_microblocks = true;
@@ -7852,6 +7874,7 @@ fn fuzzed_median_fee_rate_estimation_test(window_size: u64, expected_final_value
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -8032,6 +8055,7 @@ fn use_latest_tip_integration_test() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -8426,6 +8450,7 @@ fn test_problematic_txs_are_not_stored() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -8578,6 +8603,7 @@ fn spawn_follower_node(
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     conf.initial_balances = initial_conf.initial_balances.clone();
@@ -8677,6 +8703,7 @@ fn test_problematic_blocks_are_not_mined() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -9035,6 +9062,7 @@ fn test_problematic_blocks_are_not_relayed_or_stored() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -9429,6 +9457,7 @@ fn test_problematic_microblocks_are_not_mined() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -9810,6 +9839,7 @@ fn test_problematic_microblocks_are_not_relayed_or_stored() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let mut btcd_controller = BitcoinCoreController::new(conf.clone());
@@ -10159,6 +10189,7 @@ fn push_boot_receipts() {
     conf.events_observers.push(EventObserverConfig {
         endpoint: format!("localhost:{}", test_observer::EVENT_OBSERVER_PORT),
         events_keys: vec![EventKeyType::AnyEvent],
+        ..EventObserverConfig::default()
     });
 
     let burnchain_config = Burnchain::regtest(&conf.get_burn_db_path());
@@ -0,0 +1,199 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Builds the Rust bindings that `clarity-cli bindgen` generates for `bindgen/all-types.clar`, a
+//! contract that uses every Clarity type, against this crate, and checks that they still match
+//! what the generator produces and that they encode and decode the values Clarity does.
+
+extern crate blockstack_lib;
+
+use std::convert::TryFrom;
+
+use blockstack_lib::types::chainstate::StacksAddress;
+use blockstack_lib::types::StacksEpochId;
+use blockstack_lib::util::hash::Hash160;
+use blockstack_lib::vm::analysis::contract_interface_builder::bindgen::{
+    generate_bindings, infer_print_event_types, BindingsLanguage,
+};
+use blockstack_lib::vm::analysis::contract_interface_builder::build_contract_interface;
+use blockstack_lib::vm::analysis::mem_type_check;
+use blockstack_lib::vm::types::{PrincipalData, QualifiedContractIdentifier};
+use blockstack_lib::vm::{execute_v2, ClarityVersion, ContractName, Value};
+
+#[rustfmt::skip]
+#[path = "bindgen/all_types.rs"]
+mod all_types;
+
+use all_types::*;
+
+const OWNER: &str = "ST1PQHQKV0RJXZFY1DGX8MNSNYVE3VGZJSRTPGZGM";
+
+fn eval(program: &str) -> Value {
+    execute_v2(program).unwrap().unwrap()
+}
+
+#[test]
+fn test_bindings_up_to_date() {
+    let (_, analysis) = mem_type_check(
+        include_str!("bindgen/all-types.clar"),
+        ClarityVersion::Clarity2,
+        StacksEpochId::Epoch21,
+    )
+    .unwrap();
+    let bindings = generate_bindings(
+        BindingsLanguage::Rust,
+        "all-types",
+        &build_contract_interface(&analysis),
+        &infer_print_event_types(&analysis),
+    );
+    assert!(
+        bindings == include_str!("bindgen/all_types.rs"),
+        "tests/bindgen/all_types.rs is stale; regenerate it with \
+         `cargo run --bin clarity-cli -- bindgen tests/bindgen/all-types.clar > tests/bindgen/all_types.rs`"
+    );
+}
+
+#[test]
+fn test_bindings_encode() {
+    let contract = AllTypesContract::new(
+        StacksAddress::new(26, Hash160([0x01; 20])),
+        ContractName::try_from("all-types".to_string()).unwrap(),
+    );
+    let owner = PrincipalData::parse(OWNER).unwrap();
+
+    let call = contract
+        .store(
+            7,
+            owner.clone(),
+            "label".to_string(),
+            Some("n\u{f6}te".to_string()),
+            vec![vec![0x01, 0x02], vec![]],
+        )
+        .unwrap();
+    assert_eq!(call.contract_name.as_str(), "all-types");
+    assert_eq!(call.function_name.as_str(), "store");
+    assert_eq!(
+        call.function_args,
+        vec![
+            eval("u7"),
+            eval(&format!("'{}", OWNER)),
+            eval("\"label\""),
+            eval("(some u\"n\\u{f6}te\")"),
+            eval("(list 0x0102 0x)"),
+        ]
+    );
+
+    let call = contract.bump(-3, true).unwrap();
+    assert_eq!(call.function_args, vec![eval("-3"), eval("true")]);
+
+    let callee = QualifiedContractIdentifier::parse(&format!("{}.callee", OWNER)).unwrap();
+    let call = contract.call_out(callee).unwrap();
+    assert_eq!(
+        call.function_args,
+        vec![eval(&format!("'{}.callee", OWNER))]
+    );
+
+    assert!(contract.noop().unwrap().function_args.is_empty());
+
+    assert_eq!(
+        AllTypesContract::encode_entries_key(EntriesKey {
+            id: 7,
+            owner: owner.clone()
+        })
+        .unwrap(),
+        eval(&format!("{{ id: u7, owner: '{} }}", OWNER))
+    );
+    assert_eq!(
+        EntriesValue {
+            label: "label".to_string(),
+            note: None,
+            tags: vec![vec![0xff]],
+        }
+        .to_clarity()
+        .unwrap(),
+        eval("{ label: \"label\", note: none, tags: (list 0xff) }")
+    );
+    assert_eq!(
+        LookupResponse::Err(404).to_clarity().unwrap(),
+        eval("(err u404)")
+    );
+
+    // values that don't fit the Clarity type are refused
+    assert!(contract
+        .store(7, owner, "\u{f6}".to_string(), None, vec![])
+        .is_err());
+}
+
+#[test]
+fn test_bindings_decode() {
+    let owner = PrincipalData::parse(OWNER).unwrap();
+
+    assert_eq!(
+        AllTypesContract::decode_lookup_result(eval(
+            "(ok { label: \"label\", note: (some u\"n\\u{f6}te\"), tags: (list 0x01 0x) })"
+        ))
+        .unwrap(),
+        LookupResponse::Ok(EntriesValue {
+            label: "label".to_string(),
+            note: Some("n\u{f6}te".to_string()),
+            tags: vec![vec![0x01], vec![]],
+        })
+    );
+    assert_eq!(
+        AllTypesContract::decode_lookup_result(eval("(err u404)")).unwrap(),
+        LookupResponse::Err(404)
+    );
+    assert_eq!(
+        AllTypesContract::decode_bump_result(eval("(ok -3)")).unwrap(),
+        BumpResponse::Ok(-3)
+    );
+    assert_eq!(
+        AllTypesContract::decode_call_out_result(eval("(ok false)")).unwrap(),
+        CallOutResponse::Ok(false)
+    );
+    assert_eq!(
+        AllTypesContract::decode_noop_result(eval("(ok none)")).unwrap(),
+        NoopResponse::Ok(None)
+    );
+    assert_eq!(
+        AllTypesContract::decode_err_not_found_constant(eval("(err u404)")).unwrap(),
+        ErrNotFoundResponse::Err(404)
+    );
+    assert_eq!(
+        AllTypesContract::decode_counter_var(eval("-5")).unwrap(),
+        -5
+    );
+    assert_eq!(
+        AllTypesContract::decode_entries_entry(eval("none")).unwrap(),
+        None
+    );
+    assert_eq!(
+        AllTypesPrintEvent::from_clarity(eval(&format!(
+            "{{ event: \"store\", id: u7, owner: '{} }}",
+            OWNER
+        )))
+        .unwrap(),
+        AllTypesPrintEvent::Store(StorePrint {
+            event: "store".to_string(),
+            id: 7,
+            owner,
+        })
+    );
+
+    // values of the wrong type are refused
+    assert!(AllTypesContract::decode_bump_result(eval("(ok u3)")).is_err());
+    assert!(AllTypesContract::decode_lookup_result(eval("(ok { label: \"label\" })")).is_err());
+}
@@ -0,0 +1,467 @@
+// Rust bindings for the `all-types` contract, generated by `clarity-cli bindgen`.  Do not edit.
+#![allow(dead_code, unused_imports, clippy::all)]
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use blockstack_lib::chainstate::stacks::TransactionContractCall;
+use blockstack_lib::types::chainstate::StacksAddress;
+use blockstack_lib::vm::errors::Error as ClarityError;
+use blockstack_lib::vm::types::{
+    CharType, PrincipalData, QualifiedContractIdentifier, SequenceData, TupleData,
+};
+use blockstack_lib::vm::{ClarityName, ContractName, Value};
+
+/// Error converting between binding types and Clarity values
+#[derive(Debug)]
+pub enum BindingError {
+    /// A Clarity value could not be constructed
+    Clarity(ClarityError),
+    /// A Clarity value did not have the expected type
+    UnexpectedValue(&'static str, Value),
+}
+
+impl fmt::Display for BindingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BindingError::Clarity(e) => write!(f, "{}", e),
+            BindingError::UnexpectedValue(expected, value) => {
+                write!(f, "expected {}, got {}", expected, value)
+            }
+        }
+    }
+}
+
+impl From<ClarityError> for BindingError {
+    fn from(e: ClarityError) -> BindingError {
+        BindingError::Clarity(e)
+    }
+}
+
+pub type BindingResult<T> = Result<T, BindingError>;
+
+fn clarity_name(name: &str) -> ClarityName {
+    ClarityName::try_from(name.to_string()).expect("BUG: invalid Clarity name in bindings")
+}
+
+fn expect_int(value: Value) -> BindingResult<i128> {
+    match value {
+        Value::Int(x) => Ok(x),
+        value => Err(BindingError::UnexpectedValue("int", value)),
+    }
+}
+
+fn expect_uint(value: Value) -> BindingResult<u128> {
+    match value {
+        Value::UInt(x) => Ok(x),
+        value => Err(BindingError::UnexpectedValue("uint", value)),
+    }
+}
+
+fn expect_bool(value: Value) -> BindingResult<bool> {
+    match value {
+        Value::Bool(x) => Ok(x),
+        value => Err(BindingError::UnexpectedValue("bool", value)),
+    }
+}
+
+fn expect_principal(value: Value) -> BindingResult<PrincipalData> {
+    match value {
+        Value::Principal(principal) => Ok(principal),
+        Value::CallableContract(callable) => {
+            Ok(PrincipalData::Contract(callable.contract_identifier))
+        }
+        value => Err(BindingError::UnexpectedValue("principal", value)),
+    }
+}
+
+fn expect_contract(value: Value) -> BindingResult<QualifiedContractIdentifier> {
+    match value {
+        Value::Principal(PrincipalData::Contract(contract)) => Ok(contract),
+        Value::CallableContract(callable) => Ok(callable.contract_identifier),
+        value => Err(BindingError::UnexpectedValue("contract principal", value)),
+    }
+}
+
+fn expect_buff(value: Value) -> BindingResult<Vec<u8>> {
+    match value {
+        Value::Sequence(SequenceData::Buffer(buff)) => Ok(buff.data),
+        value => Err(BindingError::UnexpectedValue("buff", value)),
+    }
+}
+
+fn expect_string_ascii(value: Value) -> BindingResult<String> {
+    match value {
+        Value::Sequence(SequenceData::String(CharType::ASCII(string))) => {
+            Ok(string.data.into_iter().map(char::from).collect())
+        }
+        value => Err(BindingError::UnexpectedValue("string-ascii", value)),
+    }
+}
+
+fn expect_string_utf8(value: Value) -> BindingResult<String> {
+    match value {
+        Value::Sequence(SequenceData::String(CharType::UTF8(string))) => {
+            Ok(String::from_utf8_lossy(&string.data.concat()).into_owned())
+        }
+        value => Err(BindingError::UnexpectedValue("string-utf8", value)),
+    }
+}
+
+fn expect_optional(value: Value) -> BindingResult<Option<Value>> {
+    match value {
+        Value::Optional(optional) => Ok(optional.data.map(|value| *value)),
+        value => Err(BindingError::UnexpectedValue("optional", value)),
+    }
+}
+
+fn expect_response(value: Value) -> BindingResult<Result<Value, Value>> {
+    match value {
+        Value::Response(response) if response.committed => Ok(Ok(*response.data)),
+        Value::Response(response) => Ok(Err(*response.data)),
+        value => Err(BindingError::UnexpectedValue("response", value)),
+    }
+}
+
+fn expect_list(value: Value) -> BindingResult<Vec<Value>> {
+    match value {
+        Value::Sequence(SequenceData::List(list)) => Ok(list.data),
+        value => Err(BindingError::UnexpectedValue("list", value)),
+    }
+}
+
+fn expect_tuple(value: Value, num_entries: usize) -> BindingResult<TupleData> {
+    match value {
+        Value::Tuple(tuple) if tuple.data_map.len() == num_entries => Ok(tuple),
+        value => Err(BindingError::UnexpectedValue("tuple", value)),
+    }
+}
+
+fn tuple_entry(tuple: &TupleData, name: &str) -> BindingResult<Value> {
+    Ok(tuple.get(name)?.clone())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrNotFoundResponse {
+    Ok(()),
+    Err(u128),
+}
+
+impl ErrNotFoundResponse {
+    pub fn from_clarity(value: Value) -> BindingResult<ErrNotFoundResponse> {
+        match expect_response(value)? {
+            Ok(value) => Ok(ErrNotFoundResponse::Ok({ let _ = value; })),
+            Err(value) => Ok(ErrNotFoundResponse::Err(expect_uint(value)?)),
+        }
+    }
+
+    pub fn to_clarity(self) -> BindingResult<Value> {
+        match self {
+            ErrNotFoundResponse::Ok(value) => Ok(Value::okay({ let _ = value; Value::none() })?),
+            ErrNotFoundResponse::Err(value) => Ok(Value::error(Value::UInt(value))?),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntriesKey {
+    pub id: u128,
+    pub owner: PrincipalData,
+}
+
+impl EntriesKey {
+    pub fn from_clarity(value: Value) -> BindingResult<EntriesKey> {
+        let tuple = expect_tuple(value, 2)?;
+        Ok(EntriesKey {
+            id: expect_uint(tuple_entry(&tuple, "id")?)?,
+            owner: expect_principal(tuple_entry(&tuple, "owner")?)?,
+        })
+    }
+
+    pub fn to_clarity(self) -> BindingResult<Value> {
+        Ok(Value::Tuple(TupleData::from_data(vec![
+            (clarity_name("id"), Value::UInt(self.id)),
+            (clarity_name("owner"), Value::Principal(self.owner)),
+        ])?))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntriesValue {
+    pub label: String,
+    pub note: Option<String>,
+    pub tags: Vec<Vec<u8>>,
+}
+
+impl EntriesValue {
+    pub fn from_clarity(value: Value) -> BindingResult<EntriesValue> {
+        let tuple = expect_tuple(value, 3)?;
+        Ok(EntriesValue {
+            label: expect_string_ascii(tuple_entry(&tuple, "label")?)?,
+            note: match expect_optional(tuple_entry(&tuple, "note")?)? { Some(value) => Some(expect_string_utf8(value)?), None => None },
+            tags: expect_list(tuple_entry(&tuple, "tags")?)?.into_iter().map(|value| -> BindingResult<_> { Ok(expect_buff(value)?) }).collect::<BindingResult<Vec<_>>>()?,
+        })
+    }
+
+    pub fn to_clarity(self) -> BindingResult<Value> {
+        Ok(Value::Tuple(TupleData::from_data(vec![
+            (clarity_name("label"), Value::string_ascii_from_bytes(self.label.into_bytes())?),
+            (clarity_name("note"), match self.note { Some(value) => Value::some(Value::string_utf8_from_bytes(value.into_bytes())?)?, None => Value::none() }),
+            (clarity_name("tags"), Value::list_from(self.tags.into_iter().map(|value| -> BindingResult<_> { Ok(Value::buff_from(value)?) }).collect::<BindingResult<Vec<_>>>()?)?),
+        ])?))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BumpResponse {
+    Ok(i128),
+    Err(u128),
+}
+
+impl BumpResponse {
+    pub fn from_clarity(value: Value) -> BindingResult<BumpResponse> {
+        match expect_response(value)? {
+            Ok(value) => Ok(BumpResponse::Ok(expect_int(value)?)),
+            Err(value) => Ok(BumpResponse::Err(expect_uint(value)?)),
+        }
+    }
+
+    pub fn to_clarity(self) -> BindingResult<Value> {
+        match self {
+            BumpResponse::Ok(value) => Ok(Value::okay(Value::Int(value))?),
+            BumpResponse::Err(value) => Ok(Value::error(Value::UInt(value))?),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CallOutResponse {
+    Ok(bool),
+    Err(u128),
+}
+
+impl CallOutResponse {
+    pub fn from_clarity(value: Value) -> BindingResult<CallOutResponse> {
+        match expect_response(value)? {
+            Ok(value) => Ok(CallOutResponse::Ok(expect_bool(value)?)),
+            Err(value) => Ok(CallOutResponse::Err(expect_uint(value)?)),
+        }
+    }
+
+    pub fn to_clarity(self) -> BindingResult<Value> {
+        match self {
+            CallOutResponse::Ok(value) => Ok(Value::okay(Value::Bool(value))?),
+            CallOutResponse::Err(value) => Ok(Value::error(Value::UInt(value))?),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoreResponse {
+    Ok(bool),
+    Err(()),
+}
+
+impl StoreResponse {
+    pub fn from_clarity(value: Value) -> BindingResult<StoreResponse> {
+        match expect_response(value)? {
+            Ok(value) => Ok(StoreResponse::Ok(expect_bool(value)?)),
+            Err(value) => Ok(StoreResponse::Err({ let _ = value; })),
+        }
+    }
+
+    pub fn to_clarity(self) -> BindingResult<Value> {
+        match self {
+            StoreResponse::Ok(value) => Ok(Value::okay(Value::Bool(value))?),
+            StoreResponse::Err(value) => Ok(Value::error({ let _ = value; Value::none() })?),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LookupResponse {
+    Ok(EntriesValue),
+    Err(u128),
+}
+
+impl LookupResponse {
+    pub fn from_clarity(value: Value) -> BindingResult<LookupResponse> {
+        match expect_response(value)? {
+            Ok(value) => Ok(LookupResponse::Ok(EntriesValue::from_clarity(value)?)),
+            Err(value) => Ok(LookupResponse::Err(expect_uint(value)?)),
+        }
+    }
+
+    pub fn to_clarity(self) -> BindingResult<Value> {
+        match self {
+            LookupResponse::Ok(value) => Ok(Value::okay(value.to_clarity()?)?),
+            LookupResponse::Err(value) => Ok(Value::error(Value::UInt(value))?),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum NoopResponse {
+    Ok(Option<()>),
+    Err(()),
+}
+
+impl NoopResponse {
+    pub fn from_clarity(value: Value) -> BindingResult<NoopResponse> {
+        match expect_response(value)? {
+            Ok(value) => Ok(NoopResponse::Ok(match expect_optional(value)? { Some(value) => Some({ let _ = value; }), None => None })),
+            Err(value) => Ok(NoopResponse::Err({ let _ = value; })),
+        }
+    }
+
+    pub fn to_clarity(self) -> BindingResult<Value> {
+        match self {
+            NoopResponse::Ok(value) => Ok(Value::okay(match value { Some(value) => Value::some({ let _ = value; Value::none() })?, None => Value::none() })?),
+            NoopResponse::Err(value) => Ok(Value::error({ let _ = value; Value::none() })?),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StorePrint {
+    pub event: String,
+    pub id: u128,
+    pub owner: PrincipalData,
+}
+
+impl StorePrint {
+    pub fn from_clarity(value: Value) -> BindingResult<StorePrint> {
+        let tuple = expect_tuple(value, 3)?;
+        Ok(StorePrint {
+            event: expect_string_ascii(tuple_entry(&tuple, "event")?)?,
+            id: expect_uint(tuple_entry(&tuple, "id")?)?,
+            owner: expect_principal(tuple_entry(&tuple, "owner")?)?,
+        })
+    }
+
+    pub fn to_clarity(self) -> BindingResult<Value> {
+        Ok(Value::Tuple(TupleData::from_data(vec![
+            (clarity_name("event"), Value::string_ascii_from_bytes(self.event.into_bytes())?),
+            (clarity_name("id"), Value::UInt(self.id)),
+            (clarity_name("owner"), Value::Principal(self.owner)),
+        ])?))
+    }
+}
+
+/// Typed calls into the `all-types` contract
+#[derive(Debug, Clone, PartialEq)]
+pub struct AllTypesContract {
+    pub address: StacksAddress,
+    pub name: ContractName,
+}
+
+impl AllTypesContract {
+    pub fn new(address: StacksAddress, name: ContractName) -> AllTypesContract {
+        AllTypesContract { address, name }
+    }
+
+    fn contract_call(&self, function_name: &str, function_args: Vec<Value>) -> TransactionContractCall {
+        TransactionContractCall {
+            address: self.address.clone(),
+            contract_name: self.name.clone(),
+            function_name: clarity_name(function_name),
+            function_args,
+        }
+    }
+
+    /// Call the public function `bump`
+    pub fn bump(&self, delta: i128, flag: bool) -> BindingResult<TransactionContractCall> {
+        Ok(self.contract_call("bump", vec![Value::Int(delta), Value::Bool(flag)]))
+    }
+
+    /// Decode the result of `bump`
+    pub fn decode_bump_result(value: Value) -> BindingResult<BumpResponse> {
+        Ok(BumpResponse::from_clarity(value)?)
+    }
+
+    /// Call the public function `call-out`
+    pub fn call_out(&self, callee: QualifiedContractIdentifier) -> BindingResult<TransactionContractCall> {
+        Ok(self.contract_call("call-out", vec![Value::Principal(PrincipalData::Contract(callee))]))
+    }
+
+    /// Decode the result of `call-out`
+    pub fn decode_call_out_result(value: Value) -> BindingResult<CallOutResponse> {
+        Ok(CallOutResponse::from_clarity(value)?)
+    }
+
+    /// Call the public function `store`
+    pub fn store(&self, id: u128, owner: PrincipalData, label: String, note: Option<String>, tags: Vec<Vec<u8>>) -> BindingResult<TransactionContractCall> {
+        Ok(self.contract_call("store", vec![Value::UInt(id), Value::Principal(owner), Value::string_ascii_from_bytes(label.into_bytes())?, match note { Some(value) => Value::some(Value::string_utf8_from_bytes(value.into_bytes())?)?, None => Value::none() }, Value::list_from(tags.into_iter().map(|value| -> BindingResult<_> { Ok(Value::buff_from(value)?) }).collect::<BindingResult<Vec<_>>>()?)?]))
+    }
+
+    /// Decode the result of `store`
+    pub fn decode_store_result(value: Value) -> BindingResult<StoreResponse> {
+        Ok(StoreResponse::from_clarity(value)?)
+    }
+
+    /// Call the read-only function `lookup`
+    pub fn lookup(&self, id: u128, owner: PrincipalData) -> BindingResult<TransactionContractCall> {
+        Ok(self.contract_call("lookup", vec![Value::UInt(id), Value::Principal(owner)]))
+    }
+
+    /// Decode the result of `lookup`
+    pub fn decode_lookup_result(value: Value) -> BindingResult<LookupResponse> {
+        Ok(LookupResponse::from_clarity(value)?)
+    }
+
+    /// Call the read-only function `noop`
+    pub fn noop(&self) -> BindingResult<TransactionContractCall> {
+        Ok(self.contract_call("noop", vec![]))
+    }
+
+    /// Decode the result of `noop`
+    pub fn decode_noop_result(value: Value) -> BindingResult<NoopResponse> {
+        Ok(NoopResponse::from_clarity(value)?)
+    }
+
+    /// Name of the constant `ERR-NOT-FOUND`
+    pub const ERR_NOT_FOUND_CONSTANT: &'static str = "ERR-NOT-FOUND";
+
+    /// Decode the value of the constant `ERR-NOT-FOUND`
+    pub fn decode_err_not_found_constant(value: Value) -> BindingResult<ErrNotFoundResponse> {
+        Ok(ErrNotFoundResponse::from_clarity(value)?)
+    }
+
+    /// Name of the data var `counter`
+    pub const COUNTER_VAR: &'static str = "counter";
+
+    /// Decode the value of the data var `counter`
+    pub fn decode_counter_var(value: Value) -> BindingResult<i128> {
+        Ok(expect_int(value)?)
+    }
+
+    /// Name of the map `entries`
+    pub const ENTRIES_MAP: &'static str = "entries";
+
+    /// Encode a key of the map `entries`
+    pub fn encode_entries_key(key: EntriesKey) -> BindingResult<Value> {
+        Ok(key.to_clarity()?)
+    }
+
+    /// Decode an entry of the map `entries`, as returned by `map-get?`
+    pub fn decode_entries_entry(value: Value) -> BindingResult<Option<EntriesValue>> {
+        Ok(match expect_optional(value)? { Some(value) => Some(EntriesValue::from_clarity(value)?), None => None })
+    }
+}
+
+/// Values printed by the `all-types` contract, as far as their types could be inferred
+#[derive(Debug, Clone, PartialEq)]
+pub enum AllTypesPrintEvent {
+    /// Printed by `store`
+    Store(StorePrint),
+}
+
+impl AllTypesPrintEvent {
+    /// Decode a printed value by trying each known type in turn
+    pub fn from_clarity(value: Value) -> BindingResult<AllTypesPrintEvent> {
+        if let Ok(event) = (|value: Value| -> BindingResult<_> { Ok(StorePrint::from_clarity(value)?) })(value.clone()) {
+            return Ok(AllTypesPrintEvent::Store(event));
+        }
+        Err(BindingError::UnexpectedValue("print event", value))
+    }
+}